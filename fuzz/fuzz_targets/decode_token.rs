@@ -0,0 +1,11 @@
+#![no_main]
+
+use axum_rest::helpers::auth::AuthHelper;
+use axum_rest::helpers::clock::SystemClock;
+use libfuzzer_sys::fuzz_target;
+
+// Tokens arrive from cookies and headers verbatim, so decoding must reject garbage cleanly
+fuzz_target!(|token: &str| {
+    let _ = AuthHelper::validate_token(token, &SystemClock);
+    let _ = AuthHelper::extract_user_id_from_token(token, &SystemClock);
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use axum_rest::helpers::validation::validate_user_registration;
+use axum_rest::model::model::{
+    CreatePostRequest, CreateUserRequest, Limits, LoginRequest, UpdatePasswordRequest,
+    UpdatePostRequest, UpdateUserRequest,
+};
+use libfuzzer_sys::fuzz_target;
+
+// Every JSON body a handler accepts must either deserialize or be rejected, never panic
+fuzz_target!(|data: &[u8]| {
+    if let Ok(user) = serde_json::from_slice::<CreateUserRequest>(data) {
+        let _ = validate_user_registration(&user, &Limits::default());
+    }
+    let _ = serde_json::from_slice::<LoginRequest>(data);
+    let _ = serde_json::from_slice::<UpdateUserRequest>(data);
+    let _ = serde_json::from_slice::<UpdatePasswordRequest>(data);
+    let _ = serde_json::from_slice::<CreatePostRequest>(data);
+    let _ = serde_json::from_slice::<UpdatePostRequest>(data);
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use axum_rest::helpers::markdown::{extract_images, extract_mentions, render_markdown, summarize};
+use libfuzzer_sys::fuzz_target;
+
+// Post bodies are rendered for every reader, so whatever the author writes
+// must come out sanitized
+fuzz_target!(|source: &str| {
+    // Text that looks like a tag comes out escaped, so any `<script` is a real one
+    let html = render_markdown(source).to_ascii_lowercase();
+    assert!(
+        !html.contains("<script"),
+        "script survived sanitizing: {}",
+        html
+    );
+
+    let _ = extract_images(source);
+    let _ = extract_mentions(source);
+    let _ = summarize(source);
+});
@@ -0,0 +1,90 @@
+#![no_main]
+
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::Body,
+    extract::ConnectInfo,
+    http::{Method, Request, header},
+};
+use axum_rest::helpers::mailer::MemoryMailer;
+use axum_rest::{AppConfig, AppState, build_router};
+use libfuzzer_sys::fuzz_target;
+use sqlx::postgres::PgPoolOptions;
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+
+const METHODS: [Method; 6] = [
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+    Method::OPTIONS,
+];
+
+const CONTENT_TYPES: [&str; 4] = [
+    "application/json",
+    "application/merge-patch+json",
+    "application/x-www-form-urlencoded",
+    "text/plain",
+];
+
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| Runtime::new().unwrap());
+
+// Nothing listens on port 1, so handlers that reach the database fail fast
+// instead of writing anything
+static ROUTER: LazyLock<Router> = LazyLock::new(|| {
+    let _guard = RUNTIME.enter();
+    let pool = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(10))
+        .connect_lazy("postgres://fuzz@127.0.0.1:1/fuzz")
+        .unwrap();
+    let state = AppState::new(Arc::new(pool), AppConfig::from_env())
+        .with_mailer(Arc::new(MemoryMailer::new()));
+    build_router(state)
+});
+
+// A fresh client address per request, so the per-IP rate limit never
+// answers in place of the route
+static CLIENTS: AtomicU64 = AtomicU64::new(1);
+
+// Arbitrary methods, paths, headers and bodies go through the full middleware
+// stack and every handler; none of them may panic
+fuzz_target!(|data: &[u8]| {
+    let [method, content_type, rest @ ..] = data else {
+        return;
+    };
+    let (path, body) = match rest.iter().position(|&byte| byte == b'\n') {
+        Some(end) => (&rest[..end], &rest[end + 1..]),
+        None => (rest, &[][..]),
+    };
+    let Ok(path) = std::str::from_utf8(path) else {
+        return;
+    };
+
+    let Ok(mut request) = Request::builder()
+        .method(METHODS[*method as usize % METHODS.len()].clone())
+        .uri(format!("/{}", path.trim_start_matches('/')))
+        .header(
+            header::CONTENT_TYPE,
+            CONTENT_TYPES[*content_type as usize % CONTENT_TYPES.len()],
+        )
+        .body(Body::from(body.to_vec()))
+    else {
+        return;
+    };
+    let client = CLIENTS.fetch_add(1, Ordering::Relaxed);
+    request.extensions_mut().insert(ConnectInfo(SocketAddr::new(
+        IpAddr::V6(Ipv6Addr::from(client as u128)),
+        443,
+    )));
+
+    RUNTIME.block_on(async {
+        let _ = ROUTER.clone().oneshot(request).await;
+    });
+});
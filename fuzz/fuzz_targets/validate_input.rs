@@ -0,0 +1,9 @@
+#![no_main]
+
+use axum_rest::helpers::validation::strong_password;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = strong_password(input);
+    let _ = mailchecker::is_valid(input);
+});
@@ -0,0 +1,554 @@
+//! The API as an Axum router, independent of how it is served.
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Router,
+    extract::{DefaultBodyLimit, FromRef, State},
+    http::{HeaderMap, HeaderValue, StatusCode, Uri, header},
+    middleware,
+    response::{Html, IntoResponse, Response},
+    routing::{delete, get, patch, post, put},
+};
+use tower::util::option_layer;
+use tower_governor::{GovernorLayer, governor::GovernorConfigBuilder};
+use tower_http::set_header::SetResponseHeaderLayer;
+use tracing::info;
+use utoipa_scalar::{Scalar, Servable};
+#[cfg(feature = "swagger-ui")]
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::config::AppConfig;
+use crate::docs::ApiDoc;
+use crate::handlers::{
+    admin_handlers::{
+        add_email_domain_rule, ban_user_admin, get_audit_log, get_deprecation_stats,
+        get_diagnostics, get_feature_flags, get_jobs, get_maintenance, get_metrics,
+        impersonate_user, import_wordpress, list_email_domain_rules, list_hook_deliveries,
+        list_reports, remove_email_domain_rule, resend_verifications, resolve_report,
+        set_maintenance, suspend_user_admin, unban_user_admin, unverify_email_admin,
+        update_feature_flags, verify_email_admin,
+    },
+    auth_handlers::{
+        change_password, confirm_revoke_session, delete_user_account, delete_user_admin,
+        export_user_data, get_all_users_admin, get_profile, get_profile_stats, get_public_profile,
+        get_usage, jwks, list_sessions, login_user, logout_user, password_strength, patch_profile,
+        register_user, registration_form, revoke_session, revoke_session_link, stop_impersonation,
+        update_profile, username_available, verify_email,
+    },
+    feed_handlers::{site_feed, user_feed},
+    home_handlers::{get_limits, home, liveness, readiness},
+    notification_handlers::{
+        get_notification_summary, list_notifications, mark_all_notifications_read,
+        mark_notification_read,
+    },
+    org_handlers::{
+        add_member, create_org, delete_org, get_org, list_members, list_orgs, remove_member,
+        update_member, update_org,
+    },
+    post_handlers::{
+        add_collaborator, archive_post, create_post, create_posts_bulk, create_preview_token,
+        create_share_link, delete_post, export_my_posts, export_post, get_all_posts,
+        get_media_missing_alt, get_post, get_preview, get_trending_posts, get_user_posts,
+        import_posts, list_share_links, patch_post, remove_collaborator, report_post,
+        revoke_share_link, unarchive_post, update_post,
+    },
+    sitemap_handlers::{sitemap, sitemap_page},
+    webhook_handlers::resend_webhook,
+};
+#[cfg(feature = "redis")]
+use crate::helpers::access::shared_rate_limit_middleware;
+use crate::helpers::access::{PublicReadTier, api_key_middleware, public_read_middleware};
+use crate::helpers::deprecation::gone_middleware;
+use crate::helpers::i18n::locale_middleware;
+use crate::helpers::maintenance::{MaintenanceGate, maintenance_middleware};
+use crate::helpers::middleware::{
+    SessionGuard, auth_middleware, forbid_impersonation, org_middleware,
+};
+use crate::helpers::negotiation::{is_api_path, negotiation_middleware, prefers_html};
+use crate::helpers::post_archive::MAX_IMPORT_BYTES;
+use crate::helpers::redaction::redaction_middleware;
+use crate::helpers::request_id::request_id_middleware;
+use crate::helpers::request_log::request_log_middleware;
+use crate::helpers::response::method_not_allowed;
+use crate::helpers::timestamps::timestamps_middleware;
+use crate::helpers::usage::usage_middleware;
+use crate::helpers::versioning::{ApiVersion, unversioned_middleware, versioned_middleware};
+use crate::helpers::wordpress::MAX_WXR_BYTES;
+use crate::model::model::ErrorResponse;
+use crate::state::AppState;
+
+/// Every route with its middleware, mounted under the configured base path
+///
+/// Also starts a task that prunes the per-IP rate limiters and usage counts,
+/// so it must be called from within a Tokio runtime. Serve the router with
+/// `into_make_service_with_connect_info::<SocketAddr>()`: rate limiting keys
+/// on the peer address.
+pub fn build_router(state: AppState) -> Router {
+    let config = state.config.clone();
+    let usage = state.usage.clone();
+    let clock = state.clock.clone();
+    let messages = state.messages.clone();
+
+    // governor keeps time with its own monotonic clock, so the injected
+    // `Clock` doesn't reach this quota and tests can't fast-forward it
+    let rate_conf = GovernorConfigBuilder::default()
+        .burst_size(5)
+        .per_second(1)
+        .use_headers()
+        .finish()
+        .unwrap();
+
+    let rate_limiter = rate_conf.limiter().clone();
+
+    // With Redis every replica shares one per-IP quota instead of keeping its own
+    let local_rate_limit = (!state.shares_rate_limits()).then(|| GovernorLayer::new(rate_conf));
+    #[cfg(feature = "redis")]
+    let shared_rate_limit = state
+        .shares_rate_limits()
+        .then(|| middleware::from_fn_with_state(state.clone(), shared_rate_limit_middleware));
+    #[cfg(not(feature = "redis"))]
+    let shared_rate_limit: Option<tower::layer::util::Identity> = None;
+
+    // Anonymous readers of public posts get their own, stricter per-IP quota
+    let public_reads = PublicReadTier::new(&config.access);
+    #[cfg(feature = "redis")]
+    let public_reads = match &state.redis {
+        Some(client) => public_reads.with_redis(client.clone()),
+        None => public_reads,
+    };
+    let public_reads = Arc::new(public_reads);
+    let public_read_layer =
+        middleware::from_fn_with_state(public_reads.clone(), public_read_middleware);
+
+    let cors = config.cors.layer(config.environment);
+
+    // Everything that isn't part of the versioned API
+    let site = Router::new()
+        .route("/", get(home))
+        .route("/health/live", get(liveness))
+        .route("/health/ready", get(readiness))
+        .route("/.well-known/jwks.json", get(jwks))
+        // Feeds
+        .route(
+            "/feeds/{file}",
+            get(site_feed).route_layer(public_read_layer.clone()),
+        )
+        .route(
+            "/feeds/users/{file}",
+            get(user_feed).route_layer(public_read_layer),
+        )
+        .route("/sitemap.xml", get(sitemap))
+        .route("/sitemaps/{file}", get(sitemap_page))
+        // Email delivery events, signed by the provider
+        .route("/webhooks/resend", post(resend_webhook));
+
+    #[cfg(feature = "swagger-ui")]
+    let site = site.merge(SwaggerUi::new("/swagger").url(
+        "/api-docs/openapi.json",
+        ApiDoc::for_version(
+            &config.base_path,
+            &config.api.prefix,
+            ApiVersion::LATEST,
+            &config.limits,
+        ),
+    ));
+
+    #[cfg(feature = "activitypub")]
+    let site = {
+        use crate::handlers::federation_handlers::{
+            get_actor, get_followers, get_outbox, post_inbox, webfinger,
+        };
+        info!(
+            "ActivityPub federation enabled as {}",
+            config.federation.public_url
+        );
+        site.route("/.well-known/webfinger", get(webfinger))
+            .route("/users/{name}", get(get_actor))
+            .route("/users/{name}/outbox", get(get_outbox))
+            .route("/users/{name}/followers", get(get_followers))
+            .route("/users/{name}/inbox", post(post_inbox))
+    };
+
+    // Must follow every route: only routes added so far get this fallback
+    let mut app = site.method_not_allowed_fallback(method_not_allowed);
+
+    if config.api.unversioned_routes {
+        app = app.merge(
+            api_routes(ApiVersion::UNVERSIONED, &state, &public_reads).layer(
+                middleware::from_fn_with_state(config.clone(), unversioned_middleware),
+            ),
+        );
+    }
+    for &version in ApiVersion::ALL {
+        let prefix = format!("{}{}", config.api.prefix, version.segment());
+        app = app.nest(
+            &prefix,
+            api_routes(version, &state, &public_reads).layer(middleware::from_fn_with_state(
+                version,
+                versioned_middleware,
+            )),
+        );
+    }
+
+    let app = app
+        .fallback(handler_404)
+        .layer(config.compression.layer())
+        .layer(config.compression.decompression_layer())
+        // Picks the success body format from `Accept`
+        .layer(middleware::from_fn(negotiation_middleware))
+        // Picks how timestamps in those bodies are written
+        .layer(middleware::from_fn(timestamps_middleware))
+        .layer(option_layer(local_rate_limit))
+        .layer(option_layer(shared_rate_limit))
+        .layer(cors)
+        // Decides which private fields each response may include
+        .layer(middleware::from_fn_with_state(
+            SessionGuard::from_ref(&state),
+            redaction_middleware,
+        ))
+        // Removed endpoints answer 410 with a migration hint, ahead of auth and the 404 fallback
+        .layer(middleware::from_fn_with_state(
+            state.gone.clone(),
+            gone_middleware,
+        ))
+        // Daily quotas of signed-in users and API keys, once the key is checked
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            usage_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            api_key_middleware,
+        ))
+        // Outermost here so rate-limited and rejected requests are logged too
+        .layer(middleware::from_fn_with_state(
+            config.slow_request_threshold,
+            request_log_middleware,
+        ))
+        .with_state(state);
+
+    let app = mount_at_base_path(app, &config.base_path);
+
+    let app = if config.tls_enabled() {
+        app.layer(SetResponseHeaderLayer::overriding(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_static("max-age=31536000; includeSubDomains"),
+        ))
+    } else {
+        app
+    };
+
+    #[cfg(feature = "chaos")]
+    let app = {
+        tracing::warn!("Chaos mode enabled: fault injection is active");
+        app.layer(middleware::from_fn(crate::helpers::chaos::chaos_middleware))
+    };
+
+    // Translates the messages of every response, including rejected ones
+    let app = app.layer(middleware::from_fn_with_state(messages, locale_middleware));
+
+    // Outermost so every response, including rejected ones, carries the id
+    let app = app.layer(middleware::from_fn(request_id_middleware));
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            info!("Running periodic cleanup tasks...");
+            info!("Rate Limiting Storage Size: {}", rate_limiter.len());
+            rate_limiter.retain_recent();
+            public_reads.retain_recent();
+            usage.prune(clock.now());
+            info!(
+                "Rate Limiting Storage Size after cleanup: {}",
+                rate_limiter.len()
+            );
+            info!("Cleanup tasks completed.");
+        }
+    });
+
+    app
+}
+
+/// The routes of one API version, behind the auth and organization middleware
+///
+/// Versions share their handlers; a route that changes or first appears in a
+/// later version is added here by matching on `version`. Nested routers see
+/// paths without the version prefix, so the auth checks below hold for every
+/// version.
+fn api_routes(
+    version: ApiVersion,
+    state: &AppState,
+    public_reads: &Arc<PublicReadTier>,
+) -> Router<AppState> {
+    let config = &state.config;
+    let public_read_layer =
+        middleware::from_fn_with_state(public_reads.clone(), public_read_middleware);
+    // Account takeover and data export stay out of reach of impersonation tokens
+    let no_impersonation = middleware::from_fn(forbid_impersonation);
+
+    Router::new()
+        .merge(Scalar::with_url(
+            "/docs",
+            ApiDoc::for_version(
+                &config.base_path,
+                &config.api.prefix,
+                version,
+                &config.limits,
+            ),
+        ))
+        .route("/limits", get(get_limits))
+        // Authentication routes
+        .route("/auth/register", post(register_user))
+        .route("/auth/register/form", get(registration_form))
+        .route("/auth/login", post(login_user))
+        .route("/auth/username-available", get(username_available))
+        .route("/auth/password-strength", post(password_strength))
+        .route("/auth/verify-email", get(verify_email))
+        .route(
+            "/auth/revoke-session",
+            get(revoke_session_link).post(confirm_revoke_session),
+        )
+        .route("/auth/logout", post(logout_user))
+        .route("/auth/stop-impersonation", post(stop_impersonation))
+        .route("/auth/profile", get(get_profile))
+        .route("/auth/profile", put(update_profile))
+        .route("/auth/profile", patch(patch_profile))
+        .route(
+            "/auth/profile",
+            delete(delete_user_account).route_layer(no_impersonation.clone()),
+        )
+        .route(
+            "/auth/profile/export",
+            get(export_user_data).route_layer(no_impersonation.clone()),
+        )
+        .route("/auth/profile/stats", get(get_profile_stats))
+        .route("/auth/usage", get(get_usage))
+        .route(
+            "/auth/change-password",
+            put(change_password).route_layer(no_impersonation.clone()),
+        )
+        .route("/auth/sessions", get(list_sessions))
+        .route(
+            "/auth/sessions/{id}",
+            delete(revoke_session).route_layer(no_impersonation.clone()),
+        )
+        // Admin routes
+        .route("/admin/users", get(get_all_users_admin))
+        .route(
+            "/admin/users/resend-verifications",
+            post(resend_verifications),
+        )
+        .route("/admin/users/{user_id}", delete(delete_user_admin))
+        .route("/admin/users/{user_id}/suspend", post(suspend_user_admin))
+        .route("/admin/users/{user_id}/ban", post(ban_user_admin))
+        .route("/admin/users/{user_id}/unban", post(unban_user_admin))
+        .route(
+            "/admin/users/{user_id}/verify-email",
+            post(verify_email_admin),
+        )
+        .route(
+            "/admin/users/{user_id}/unverify-email",
+            post(unverify_email_admin),
+        )
+        .route("/admin/deprecations", get(get_deprecation_stats))
+        .route("/admin/metrics", get(get_metrics))
+        .route("/admin/diagnostics", get(get_diagnostics))
+        .route("/admin/jobs", get(get_jobs))
+        .route(
+            "/admin/email-domains",
+            get(list_email_domain_rules).post(add_email_domain_rule),
+        )
+        .route(
+            "/admin/email-domains/{domain}",
+            delete(remove_email_domain_rule),
+        )
+        .route(
+            "/admin/feature-flags",
+            get(get_feature_flags).put(update_feature_flags),
+        )
+        .route(
+            "/admin/maintenance",
+            get(get_maintenance).post(set_maintenance),
+        )
+        .route("/admin/hook-deliveries", get(list_hook_deliveries))
+        .route("/admin/reports", get(list_reports))
+        .route("/admin/reports/{id}/resolve", post(resolve_report))
+        .route("/admin/audit-log", get(get_audit_log))
+        .route("/admin/impersonate/{user_id}", post(impersonate_user))
+        .route(
+            "/admin/import/wordpress",
+            post(import_wordpress).layer(DefaultBodyLimit::max(MAX_WXR_BYTES)),
+        )
+        // Public post routes
+        .route(
+            "/posts",
+            get(get_all_posts).route_layer(public_read_layer.clone()),
+        )
+        .route(
+            "/posts/trending",
+            get(get_trending_posts).route_layer(public_read_layer.clone()),
+        )
+        .route(
+            "/posts/{id}",
+            get(get_post).route_layer(public_read_layer.clone()),
+        )
+        .route(
+            "/posts/{id}/export",
+            get(export_post).route_layer(public_read_layer),
+        )
+        .route("/previews/{token}", get(get_preview))
+        .route("/profiles/{id}", get(get_public_profile))
+        // Protected post routes
+        .route("/posts", post(create_post))
+        .route("/posts/bulk", post(create_posts_bulk))
+        .route("/posts/my", get(get_user_posts))
+        .route(
+            "/posts/my/export",
+            get(export_my_posts).route_layer(no_impersonation),
+        )
+        .route(
+            "/posts/import",
+            post(import_posts).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+        )
+        .route("/posts/my/media/missing-alt", get(get_media_missing_alt))
+        .route("/posts/{id}", put(update_post))
+        .route("/posts/{id}", patch(patch_post))
+        .route("/posts/{id}", delete(delete_post))
+        .route("/posts/{id}/archive", post(archive_post))
+        .route("/posts/{id}/unarchive", post(unarchive_post))
+        .route("/posts/{id}/collaborators", post(add_collaborator))
+        .route(
+            "/posts/{id}/collaborators/{user_id}",
+            delete(remove_collaborator),
+        )
+        .route("/posts/{id}/report", post(report_post))
+        .route("/posts/{id}/preview-token", post(create_preview_token))
+        // Notification routes
+        .route("/notifications", get(list_notifications))
+        .route("/notifications/summary", get(get_notification_summary))
+        .route("/notifications/read-all", post(mark_all_notifications_read))
+        .route("/notifications/{id}/read", post(mark_notification_read))
+        // Organization routes
+        .route("/orgs", post(create_org).get(list_orgs))
+        .route(
+            "/orgs/{id}",
+            get(get_org).put(update_org).delete(delete_org),
+        )
+        .route("/orgs/{id}/members", get(list_members).post(add_member))
+        .route(
+            "/orgs/{id}/members/{user_id}",
+            put(update_member).delete(remove_member),
+        )
+        // Must follow every route: only routes added so far get this fallback
+        .method_not_allowed_fallback(method_not_allowed)
+        // Runs inside auth so membership roles can be resolved for the caller
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            org_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            |State(state): State<AppState>,
+             req: axum::extract::Request,
+             next: axum::middleware::Next| async move {
+                // Auth middleware
+                let path = req.uri().path();
+                if path.starts_with("/auth/profile")
+                    || path.starts_with("/auth/logout")
+                    || path.starts_with("/auth/stop-impersonation")
+                    || path.starts_with("/auth/change-password")
+                    || path.starts_with("/auth/sessions")
+                    || path.starts_with("/auth/usage")
+                    || path.starts_with("/admin")
+                    || path.starts_with("/orgs")
+                    || path.starts_with("/notifications")
+                    || path.starts_with("/posts") && req.method() == "POST"
+                    || path.starts_with("/posts/my")
+                    || (path.starts_with("/posts/")
+                        && (req.method() == "PUT"
+                            || req.method() == "PATCH"
+                            || req.method() == "DELETE"))
+                {
+                    auth_middleware(State(state), req, next).await
+                } else {
+                    Ok(next.run(req).await)
+                }
+            },
+        ))
+        // Added after the gate above, so its path rules don't apply to them
+        .merge(authenticated_routes(state))
+        // Ahead of auth so paused writes are turned away without a session lookup
+        .layer(middleware::from_fn_with_state(
+            MaintenanceGate::from_ref(state),
+            maintenance_middleware,
+        ))
+}
+
+/// Routes that need a signed-in user for every method
+fn authenticated_routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/posts/{id}/share-links",
+            post(create_share_link).get(list_share_links),
+        )
+        .route(
+            "/posts/{id}/share-links/{link_id}",
+            delete(revoke_share_link),
+        )
+        .method_not_allowed_fallback(method_not_allowed)
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            org_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ))
+}
+
+/// Nests the whole API under `base_path` for path-based ingress routing
+fn mount_at_base_path(app: Router, base_path: &str) -> Router {
+    if base_path.is_empty() {
+        app
+    } else {
+        tracing::info!("Serving API under {}", base_path);
+        Router::new().nest(base_path, app)
+    }
+}
+
+/// JSON for API clients and unknown API paths, an HTML page for browsers
+async fn handler_404(
+    State(config): State<Arc<AppConfig>>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Response {
+    if is_api_path(uri.path()) || !prefers_html(&headers) {
+        return ErrorResponse::new(
+            StatusCode::NOT_FOUND,
+            "Not Found",
+            "The requested resource does not exist",
+        )
+        .into_response();
+    }
+
+    let html = format!(
+        r#"
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <title>404 - Not Found</title>
+        </head>
+        <body>
+            <h1>404 - Page Not Found</h1>
+            <h2>Sorry, the page you are looking for does not exist.</h2>
+            <p><a href="{}/">Go back to Home</a></p>
+        </body>
+        </html>
+    "#,
+        config.base_path
+    );
+
+    (StatusCode::NOT_FOUND, Html(html)).into_response()
+}
@@ -0,0 +1,354 @@
+//! Read-through cache for hot public reads.
+//!
+//! Values are stored as JSON so the in-memory and Redis backends behave the
+//! same. Repositories fill the cache on reads and drop the affected keys on
+//! writes; the TTL bounds staleness for changes no key is invalidated for,
+//! such as view counts and author details embedded in posts.
+//!
+//! Hot lists are read through [`Cache::get_or_refresh`]: for `CACHE_STALE_SECS`
+//! past the TTL an entry is still served while one background task reloads it,
+//! and on a miss only one caller per key reaches the database.
+
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use crate::cache::memory_store::MemoryStore;
+#[cfg(feature = "redis")]
+use crate::cache::redis_store::RedisStore;
+use crate::config::{CacheBackend, CacheConfig};
+#[cfg(feature = "redis")]
+use crate::helpers::redis_client::RedisClient;
+
+/// Latest posts outside any organization, as listed by `GET /posts`
+pub const LATEST_POSTS_KEY: &str = "posts:latest";
+
+/// Trending posts outside any organization, ranked up to the largest page size
+pub const TRENDING_POSTS_KEY: &str = "posts:trending";
+
+pub fn post_key(public_id: &str) -> String {
+    format!("post:{}", public_id)
+}
+
+pub fn profile_key(public_id: &str) -> String {
+    format!("profile:{}", public_id)
+}
+
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// A backend holding serialized values; entries expire once they are too stale to serve
+pub trait CacheStore: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<String>>;
+    fn set<'a>(&'a self, key: &'a str, value: String) -> StoreFuture<'a, ()>;
+    fn remove<'a>(&'a self, key: &'a str) -> StoreFuture<'a, ()>;
+    /// Number of entries, when the backend can tell
+    fn entry_count(&self) -> Option<u64>;
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CacheStats {
+    /// `memory`, `redis` or `disabled`
+    pub backend: String,
+    pub hits: u64,
+    /// Expired entries served while a background refresh reloaded them
+    pub stale_hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    /// Share of lookups answered from the cache, stale or not; 0 when nothing was looked up
+    pub hit_ratio: f64,
+    pub entries: Option<u64>,
+}
+
+/// What the store holds for every key; the age decides between fresh and stale
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    /// Milliseconds since the Unix epoch
+    stored_at: u64,
+    value: T,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+pub struct Cache {
+    store: Option<Arc<dyn CacheStore>>,
+    ttl: Duration,
+    stale_for: Duration,
+    /// Keys with a background refresh in flight
+    refreshing: Mutex<HashSet<String>>,
+    /// Held while a missing key is loaded so concurrent readers wait for it
+    loading: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl Cache {
+    /// Entries are fresh for `config.ttl` and kept by `store` for `config.stale_for` longer
+    pub fn new(store: Arc<dyn CacheStore>, config: &CacheConfig) -> Self {
+        Self {
+            store: Some(store),
+            ttl: config.ttl,
+            stale_for: config.stale_for,
+            refreshing: Mutex::default(),
+            loading: Mutex::default(),
+            hits: AtomicU64::new(0),
+            stale_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    /// A cache that never holds anything
+    pub fn disabled() -> Self {
+        Self {
+            store: None,
+            ttl: Duration::ZERO,
+            stale_for: Duration::ZERO,
+            refreshing: Mutex::default(),
+            loading: Mutex::default(),
+            hits: AtomicU64::new(0),
+            stale_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    pub fn memory(config: &CacheConfig) -> Self {
+        Self::new(
+            Arc::new(MemoryStore::new(
+                config.max_entries,
+                config.ttl + config.stale_for,
+            )),
+            config,
+        )
+    }
+
+    /// Builds the configured in-process backend; the Redis backend is built
+    /// with [`Cache::redis`] from the shared client
+    pub fn from_config(config: &CacheConfig) -> Self {
+        let cache = match config.backend {
+            CacheBackend::Disabled => Self::disabled(),
+            CacheBackend::Redis => {
+                warn!(
+                    "CACHE_BACKEND=redis needs the redis feature and REDIS_URL; using the in-memory cache"
+                );
+                Self::memory(config)
+            }
+            CacheBackend::Memory => Self::memory(config),
+        };
+
+        info!("Caching hot reads in {} backend", cache.backend());
+        cache
+    }
+
+    #[cfg(feature = "redis")]
+    pub fn redis(client: RedisClient, config: &CacheConfig) -> Self {
+        info!("Caching hot reads in redis backend");
+        Self::new(
+            Arc::new(RedisStore::new(client, config.ttl + config.stale_for)),
+            config,
+        )
+    }
+
+    pub fn backend(&self) -> &'static str {
+        self.store.as_ref().map_or("disabled", |store| store.name())
+    }
+
+    /// The value under `key` and whether it is still fresh; cache errors are
+    /// logged and treated as a miss
+    async fn read<T: DeserializeOwned>(&self, key: &str) -> Option<(T, bool)> {
+        let store = self.store.as_ref()?;
+
+        let value = match store.get(key).await {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Cache read of {} failed: {}", key, e);
+                None
+            }
+        }?;
+
+        let entry: Entry<T> = serde_json::from_str(&value).ok()?;
+        let age = Duration::from_millis(now_millis().saturating_sub(entry.stored_at));
+        if age > self.ttl + self.stale_for {
+            return None;
+        }
+        Some((entry.value, age <= self.ttl))
+    }
+
+    /// Only fresh entries are returned; stale ones count as a miss
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.store.as_ref()?;
+
+        match self.read(key).await {
+            Some((value, true)) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value)
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Serves `key` while it is fresh, or stale within `CACHE_STALE_SECS` while a
+    /// single background task reloads it with `load`. On a miss the first caller
+    /// runs `load` and concurrent callers for the same key wait for its result.
+    pub async fn get_or_refresh<T, F, Fut>(self: &Arc<Self>, key: &str, load: F) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+    {
+        if self.store.is_none() {
+            return load().await;
+        }
+
+        match self.read::<T>(key).await {
+            Some((value, true)) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(value);
+            }
+            Some((value, false)) => {
+                self.stale_hits.fetch_add(1, Ordering::Relaxed);
+                self.refresh_in_background(key, load);
+                return Ok(value);
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let lock = self.loading_lock(key);
+        let guard = lock.lock().await;
+
+        // Whoever held the lock before us has most likely just loaded it
+        let value = match self.read::<T>(key).await {
+            Some((value, true)) => Ok(value),
+            _ => match load().await {
+                Ok(value) => {
+                    self.set(key, &value).await;
+                    Ok(value)
+                }
+                Err(e) => Err(e),
+            },
+        };
+
+        drop(guard);
+        self.release_loading_lock(key, lock);
+        value
+    }
+
+    fn loading_lock(&self, key: &str) -> Arc<AsyncMutex<()>> {
+        let mut loading = self.loading.lock().unwrap_or_else(|e| e.into_inner());
+        loading.entry(key.to_string()).or_default().clone()
+    }
+
+    /// Forgets the lock once no other caller is waiting on it
+    fn release_loading_lock(&self, key: &str, lock: Arc<AsyncMutex<()>>) {
+        let mut loading = self.loading.lock().unwrap_or_else(|e| e.into_inner());
+        // One reference in the map and ours
+        if Arc::strong_count(&lock) <= 2 {
+            loading.remove(key);
+        }
+    }
+
+    /// Reloads `key` unless a refresh of it is already running; failures keep
+    /// the stale entry until it expires
+    fn refresh_in_background<T, F, Fut>(self: &Arc<Self>, key: &str, load: F)
+    where
+        T: Serialize + Send + Sync + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+    {
+        {
+            let mut refreshing = self.refreshing.lock().unwrap_or_else(|e| e.into_inner());
+            if !refreshing.insert(key.to_string()) {
+                return;
+            }
+        }
+
+        debug!("Refreshing stale cache entry {}", key);
+        let cache = self.clone();
+        let key = key.to_string();
+        tokio::spawn(async move {
+            match load().await {
+                Ok(value) => cache.set(&key, &value).await,
+                Err(e) => warn!("Background refresh of {} failed: {}", key, e),
+            }
+            cache
+                .refreshing
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&key);
+        });
+    }
+
+    pub async fn set<T: Serialize + Sync>(&self, key: &str, value: &T) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        let entry = Entry {
+            stored_at: now_millis(),
+            value,
+        };
+        let result = match serde_json::to_string(&entry) {
+            Ok(value) => store.set(key, value).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            warn!("Cache write of {} failed: {}", key, e);
+        }
+    }
+
+    pub async fn invalidate(&self, key: &str) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = store.remove(key).await {
+            warn!("Cache invalidation of {} failed: {}", key, e);
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let stale_hits = self.stale_hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + stale_hits + misses;
+
+        CacheStats {
+            backend: self.backend().to_string(),
+            hits,
+            stale_hits,
+            misses,
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            hit_ratio: if lookups == 0 {
+                0.0
+            } else {
+                (hits + stale_hits) as f64 / lookups as f64
+            },
+            entries: self.store.as_ref().and_then(|store| store.entry_count()),
+        }
+    }
+}
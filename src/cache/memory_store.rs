@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use moka::future::Cache as MokaCache;
+use tracing::debug;
+
+use crate::cache::{CacheStore, StoreFuture};
+
+/// Per-process cache; each replica keeps its own entries
+pub struct MemoryStore {
+    entries: MokaCache<String, String>,
+}
+
+impl MemoryStore {
+    pub fn new(max_entries: u64, ttl: Duration) -> Self {
+        debug!(
+            "Creating in-memory cache with {} entries, ttl {:?}",
+            max_entries, ttl
+        );
+        Self {
+            entries: MokaCache::builder()
+                .max_capacity(max_entries)
+                .time_to_live(ttl)
+                .build(),
+        }
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<String>> {
+        Box::pin(async move { Ok(self.entries.get(key).await) })
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: String) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.entries.insert(key.to_string(), value).await;
+            Ok(())
+        })
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.entries.invalidate(key).await;
+            Ok(())
+        })
+    }
+
+    fn entry_count(&self) -> Option<u64> {
+        Some(self.entries.entry_count())
+    }
+}
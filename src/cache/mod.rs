@@ -0,0 +1,6 @@
+pub mod cache;
+pub mod memory_store;
+#[cfg(feature = "redis")]
+pub mod redis_store;
+
+pub use cache::*;
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use redis::AsyncCommands;
+use tracing::debug;
+
+use crate::cache::{CacheStore, StoreFuture};
+use crate::helpers::redis_client::RedisClient;
+
+/// Cache shared by every replica pointed at the same Redis
+pub struct RedisStore {
+    client: RedisClient,
+    ttl: Duration,
+}
+
+impl RedisStore {
+    pub fn new(client: RedisClient, ttl: Duration) -> Self {
+        debug!("Creating Redis cache, ttl {:?}", ttl);
+        Self { client, ttl }
+    }
+}
+
+impl CacheStore for RedisStore {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let mut connection = self.client.connection();
+            Ok(connection.get(self.client.key(key)).await?)
+        })
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: String) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let mut connection = self.client.connection();
+            let _: () = connection
+                .set_ex(self.client.key(key), value, self.ttl.as_secs().max(1))
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let mut connection = self.client.connection();
+            let _: () = connection.del(self.client.key(key)).await?;
+            Ok(())
+        })
+    }
+
+    /// Other applications may share the database, so entries aren't counted
+    fn entry_count(&self) -> Option<u64> {
+        None
+    }
+}
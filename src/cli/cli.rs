@@ -0,0 +1,249 @@
+//! Operator commands run from the server binary, so the first admin can be
+//! created and accounts maintained without hand-written SQL.
+
+use anyhow::{Context, Result, bail};
+use chrono::{Duration, Utc};
+use clap::{Parser, Subcommand};
+
+use crate::cli::seed::{DEFAULT_SEED_PASSWORD, generate};
+use crate::config::AppConfig;
+use crate::db::db::get_pg_client;
+use crate::db::repositories::{post_repo::PostRepository, user_repo::UserRepository};
+use crate::helpers::auth::AuthHelper;
+use crate::helpers::slugs::validate_username;
+use crate::helpers::validation::{strong_password, validate_user_registration};
+use crate::model::model::{CreatePostRequest, CreateUserRequest};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "axum-rest",
+    version,
+    about = "Axum REST API server and admin tools"
+)]
+pub struct Cli {
+    /// Write the OpenAPI spec to PATH (`-` for stdout) and exit; needs no
+    /// config or database
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "-")]
+    pub export_openapi: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Run the API server (the default)
+    Serve,
+    /// Create a verified admin account, or promote an existing one
+    CreateAdmin {
+        #[arg(long)]
+        email: String,
+        /// Ignored when the account already exists
+        #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+        password: String,
+        #[arg(long, default_value = "Admin")]
+        name: String,
+        /// Derived from the name when omitted
+        #[arg(long)]
+        username: Option<String>,
+    },
+    /// Print every account
+    ListUsers,
+    /// Delete accounts that never verified their email
+    PruneUnverified {
+        /// Minimum account age, e.g. `30d`, `12h` or `2w`
+        #[arg(long, value_parser = parse_age, default_value = "30d")]
+        older_than: Duration,
+    },
+    /// Create any missing tables and indexes, then exit
+    Migrate,
+    /// Fill a development database with fake, verified users and their posts
+    Seed {
+        #[arg(long, default_value_t = 10)]
+        users: usize,
+        #[arg(long, default_value_t = 5)]
+        posts_per_user: usize,
+        /// Same seed, same users and posts
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+        /// Password of every seeded account
+        #[arg(long, env = "SEED_PASSWORD", default_value = DEFAULT_SEED_PASSWORD)]
+        password: String,
+    },
+}
+
+/// Parses an age such as `90s`, `15m`, `12h`, `30d` or `2w`
+pub fn parse_age(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in `{}`; use s, m, h, d or w", value))?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("invalid age `{}`", value))?;
+
+    let age = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => return Err(format!("unknown unit `{}`; use s, m, h, d or w", unit)),
+    };
+    age.ok_or_else(|| format!("age `{}` is too large", value))
+}
+
+/// Runs a maintenance command against the configured database. Connecting
+/// creates the schema, which is all `migrate` needs.
+pub async fn run(command: Command, config: &AppConfig) -> Result<()> {
+    if matches!(command, Command::Seed { .. }) && config.is_production() {
+        bail!("`seed` creates accounts with a known password and never runs in production");
+    }
+
+    let db = get_pg_client(&config.database)
+        .await
+        .context("connecting to DATABASE_URL")?;
+    let repo = UserRepository::new(db.get_pool().clone());
+
+    match command {
+        Command::Serve => bail!("`serve` starts the server and is not a maintenance command"),
+        Command::CreateAdmin {
+            email,
+            password,
+            name,
+            username,
+        } => {
+            if let Some(user) = repo.find_by_email(&email).await? {
+                repo.make_admin(user.id, Utc::now()).await?;
+                println!(
+                    "Promoted existing account {} ({}) to ADMIN; its password is unchanged",
+                    user.public_id, email
+                );
+                return Ok(());
+            }
+
+            let username = match username {
+                Some(username) => {
+                    let username = username.to_lowercase();
+                    if let Err(message) = validate_username(&username, &config.slugs) {
+                        bail!(message);
+                    }
+                    if repo.username_taken(&username).await? {
+                        bail!("Username {} is already taken", username);
+                    }
+                    username
+                }
+                None => repo.free_username(&name, &config.slugs).await?,
+            };
+            let request = CreateUserRequest {
+                name,
+                username: Some(username),
+                email,
+                password,
+                locale: None,
+                redirect_uri: None,
+                website: None,
+                form_token: None,
+                captcha_token: None,
+            };
+            if let Err(message) = validate_user_registration(&request, &config.limits) {
+                bail!(message);
+            }
+            let hashed = AuthHelper::hash_password(&request.password)?;
+            let user = repo.create_user(request, hashed, Utc::now()).await?;
+            repo.make_admin(user.id, Utc::now()).await?;
+            println!("Created admin account {} ({})", user.public_id, user.email);
+        }
+        Command::ListUsers => {
+            let users = repo.get_all_users().await?;
+            println!("ID\tUSERNAME\tEMAIL\tNAME\tROLE\tVERIFIED\tCREATED");
+            for user in &users {
+                println!(
+                    "{}\t{}\t{}\t{}\t{:?}\t{}\t{}",
+                    user.id,
+                    user.username.as_deref().unwrap_or("-"),
+                    user.email,
+                    user.name,
+                    user.role,
+                    user.email_verified,
+                    user.created_at.to_rfc3339()
+                );
+            }
+            println!("{} account(s)", users.len());
+        }
+        Command::PruneUnverified { older_than } => {
+            let cutoff = Utc::now() - older_than;
+            let deleted = repo.delete_unverified_before(cutoff).await?;
+            println!(
+                "Deleted {} unverified account(s) created before {}",
+                deleted,
+                cutoff.to_rfc3339()
+            );
+        }
+        Command::Migrate => println!("Database schema is up to date"),
+        Command::Seed {
+            users,
+            posts_per_user,
+            seed,
+            password,
+        } => {
+            if !strong_password(&password) {
+                bail!("Password is not strong enough");
+            }
+            // One hash serves every account, since they share the password
+            let hashed = AuthHelper::hash_password(&password)?;
+            let posts = PostRepository::new(db.get_pool().clone());
+            let (mut created_users, mut created_posts) = (0, 0);
+
+            for seed_user in generate(seed, users, posts_per_user, Utc::now()) {
+                // Rerunning with the same seed leaves existing accounts alone
+                if repo.find_by_email(&seed_user.email).await?.is_some() {
+                    continue;
+                }
+                let username = repo.free_username(&seed_user.name, &config.slugs).await?;
+                let user = repo
+                    .create_user(
+                        CreateUserRequest {
+                            name: seed_user.name,
+                            username: Some(username),
+                            email: seed_user.email,
+                            password: password.clone(),
+                            locale: None,
+                            redirect_uri: None,
+                            website: None,
+                            form_token: None,
+                            captcha_token: None,
+                        },
+                        hashed.clone(),
+                        Utc::now(),
+                    )
+                    .await?;
+                repo.verify_email(user.id, Utc::now()).await?;
+                created_users += 1;
+
+                for post in seed_user.posts {
+                    let slug = posts.free_slug(&post.title, &config.slugs).await?;
+                    let request = CreatePostRequest {
+                        title: post.title,
+                        content: post.content,
+                        slug: None,
+                        license: config.default_license.clone(),
+                        visibility: None,
+                    };
+                    posts
+                        .import_post(request, user.id, post.created_at, slug)
+                        .await?;
+                    created_posts += 1;
+                }
+            }
+
+            println!(
+                "Seeded {} user(s) and {} post(s); sign in as seed-user-N@example.com with password {}",
+                created_users, created_posts, password
+            );
+        }
+    }
+
+    Ok(())
+}
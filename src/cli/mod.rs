@@ -0,0 +1,4 @@
+pub mod cli;
+pub mod seed;
+
+pub use cli::*;
@@ -0,0 +1,130 @@
+//! Deterministic fake users and posts for local development.
+//!
+//! The same seed always produces the same accounts and posts, so a front end
+//! can rely on `seed-user-1@example.com` existing with a known password.
+
+use chrono::{DateTime, Duration, Utc};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+/// Password of every seeded account unless `--password` says otherwise
+pub const DEFAULT_SEED_PASSWORD: &str = "seed-lantern-orbit-tundra";
+
+const FIRST_NAMES: [&str; 12] = [
+    "Ada", "Grace", "Alan", "Edsger", "Barbara", "Ken", "Margaret", "Dennis", "Frances", "Linus",
+    "Radia", "Donald",
+];
+
+const LAST_NAMES: [&str; 12] = [
+    "Lovelace", "Hopper", "Turing", "Dijkstra", "Liskov", "Thompson", "Hamilton", "Ritchie",
+    "Allen", "Torvalds", "Perlman", "Knuth",
+];
+
+const TITLE_OPENERS: [&str; 8] = [
+    "Notes on",
+    "A Gentle Introduction to",
+    "Lessons Learned from",
+    "Rethinking",
+    "Five Myths About",
+    "Getting Started with",
+    "Why I Switched to",
+    "Debugging",
+];
+
+const TOPICS: [&str; 12] = [
+    "Async Rust",
+    "Postgres Indexes",
+    "API Versioning",
+    "Rate Limiting",
+    "Error Handling",
+    "Connection Pools",
+    "Markdown Rendering",
+    "JWT Rotation",
+    "Edge Caching",
+    "Email Deliverability",
+    "Feature Flags",
+    "Structured Logging",
+];
+
+const SENTENCES: [&str; 12] = [
+    "It started as a small experiment on a quiet afternoon.",
+    "The first version worked, but only on my machine.",
+    "Measuring before optimizing saved us a week of guesswork.",
+    "Most of the complexity came from handling the edge cases.",
+    "The documentation was right, we just had not read it carefully.",
+    "A single missing index turned a fast query into a slow one.",
+    "Retries are easy to add and surprisingly hard to get right.",
+    "We rolled the change out behind a flag and watched the metrics.",
+    "Nobody noticed the migration, which is exactly what we wanted.",
+    "The tests caught the regression before it reached production.",
+    "In hindsight the simplest option was also the most robust.",
+    "There is still plenty left to improve, but it is a good start.",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedPost {
+    pub title: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedUser {
+    pub name: String,
+    pub email: String,
+    pub posts: Vec<SeedPost>,
+}
+
+/// `users` accounts with `posts_per_user` posts each, dated within the year
+/// before `now`, newest last
+pub fn generate(
+    seed: u64,
+    users: usize,
+    posts_per_user: usize,
+    now: DateTime<Utc>,
+) -> Vec<SeedUser> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (1..=users)
+        .map(|index| {
+            let name = format!(
+                "{} {}",
+                FIRST_NAMES.choose(&mut rng).unwrap(),
+                LAST_NAMES.choose(&mut rng).unwrap()
+            );
+
+            let mut posts: Vec<SeedPost> = (0..posts_per_user)
+                .map(|_| SeedPost {
+                    title: format!(
+                        "{} {}",
+                        TITLE_OPENERS.choose(&mut rng).unwrap(),
+                        TOPICS.choose(&mut rng).unwrap()
+                    ),
+                    content: paragraphs(&mut rng),
+                    created_at: now - Duration::minutes(rng.random_range(0..365 * 24 * 60)),
+                })
+                .collect();
+            posts.sort_by_key(|post| post.created_at);
+
+            SeedUser {
+                name,
+                email: format!("seed-user-{}@example.com", index),
+                posts,
+            }
+        })
+        .collect()
+}
+
+/// Two to four Markdown paragraphs of a few sentences each
+fn paragraphs(rng: &mut StdRng) -> String {
+    let count = rng.random_range(2..=4);
+    (0..count)
+        .map(|_| {
+            let sentences = rng.random_range(2..=4);
+            (0..sentences)
+                .map(|_| *SENTENCES.choose(rng).unwrap())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
@@ -0,0 +1,1211 @@
+use std::{
+    env,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    time::Duration,
+};
+
+use axum::http::{HeaderName, HeaderValue, Method, header};
+use axum_extra::extract::cookie::SameSite;
+use tower_http::{
+    compression::{
+        CompressionLayer,
+        predicate::{NotForContentType, Predicate, SizeAbove},
+    },
+    cors::{AllowHeaders, AllowOrigin, CorsLayer},
+    decompression::RequestDecompressionLayer,
+};
+use tracing::warn;
+
+use crate::helpers::access::API_KEY_HEADER;
+use crate::helpers::licenses::normalize_license;
+use crate::helpers::middleware::{CSRF_HEADER, ORG_HEADER};
+use crate::helpers::timestamps::{TIMESTAMP_FORMAT_HEADER, TIMEZONE_HEADER};
+use crate::helpers::versioning::{API_VERSION_HEADER, ApiVersion, DEFAULT_API_PREFIX};
+use crate::model::model::Limits;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+impl From<&str> for Environment {
+    fn from(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "production" | "prod" => Environment::Production,
+            _ => Environment::Development,
+        }
+    }
+}
+
+/// Shape of error bodies: RFC 7807 problem details, or the original `{error, message}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    #[default]
+    Problem,
+    Legacy,
+}
+
+impl From<&str> for ErrorFormat {
+    fn from(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "legacy" => ErrorFormat::Legacy,
+            _ => ErrorFormat::Problem,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allow_credentials: bool,
+    pub exposed_headers: Vec<String>,
+    pub max_age: Duration,
+}
+
+/// Response compression and request decompression, per algorithm
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    pub gzip: bool,
+    pub br: bool,
+    pub zstd: bool,
+    /// Responses smaller than this many bytes are sent uncompressed
+    pub min_size: u16,
+    /// Accept request bodies sent with a matching `Content-Encoding`
+    pub decompress_requests: bool,
+}
+
+/// Public identity used by the `activitypub` feature
+#[derive(Debug, Clone)]
+pub struct FederationConfig {
+    /// Absolute origin remote servers reach us at, e.g. `https://blog.example.com`
+    pub public_url: String,
+}
+
+impl FederationConfig {
+    /// Host part of `public_url`, used in `acct:` handles
+    pub fn domain(&self) -> &str {
+        let without_scheme = self
+            .public_url
+            .split_once("://")
+            .map_or(self.public_url.as_str(), |(_, rest)| rest);
+        without_scheme.split('/').next().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub host: IpAddr,
+    pub port: u16,
+    /// Pending connections the kernel queues before refusing new ones
+    pub tcp_backlog: u32,
+    pub tcp_nodelay: bool,
+    /// Reuse HTTP/1.1 connections between requests
+    pub http1_keep_alive: bool,
+    /// Time a client gets to send request headers before the connection is closed
+    pub http1_header_read_timeout: Duration,
+    /// Off serves HTTP/1.1 only, over TLS too
+    pub http2: bool,
+    pub http2_max_concurrent_streams: u32,
+    /// How often idle HTTP/2 connections are pinged; `None` never pings
+    pub http2_keep_alive_interval: Option<Duration>,
+    /// How long a ping may go unanswered before the connection is closed
+    pub http2_keep_alive_timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// Plain HTTP port that redirects every request to HTTPS
+    pub redirect_http_port: Option<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountConfig {
+    /// How long a deleted account lingers (and can be restored by logging in)
+    pub deletion_grace_period: Duration,
+    /// How often expired accounts are purged
+    pub purge_interval: Duration,
+    /// When non-empty, only these email domains may register
+    pub allowed_email_domains: Vec<String>,
+    pub denied_email_domains: Vec<String>,
+    /// What happens to accounts left unverified; `None` keeps them
+    pub unverified_cleanup: Option<UnverifiedCleanup>,
+    /// How long an account may stay unverified before it is cleaned up
+    pub unverified_max_age: Duration,
+    /// How often unverified accounts are looked for
+    pub unverified_cleanup_interval: Duration,
+    /// Email users when they sign in from a device they haven't used before
+    pub new_device_alerts: bool,
+    /// Request header carrying the client's country, such as Cloudflare's
+    /// `CF-IPCountry`; only set it when a proxy in front of the API sets it
+    pub country_header: Option<String>,
+}
+
+/// How the cleanup job gets rid of accounts that never verified their email
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnverifiedCleanup {
+    /// Remove the account; ones with posts are kept
+    Delete,
+    /// Scrub the name, email and password but keep the account and its posts
+    Anonymize,
+}
+
+impl UnverifiedCleanup {
+    /// `delete` or `anonymize`; anything else turns the cleanup off
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "delete" => Some(UnverifiedCleanup::Delete),
+            "anonymize" => Some(UnverifiedCleanup::Anonymize),
+            _ => None,
+        }
+    }
+}
+
+/// Lifetimes of the tokens handed out at login and in emails
+#[derive(Debug, Clone, Copy)]
+pub struct TokenConfig {
+    pub access_ttl: Duration,
+    /// Also how long a login session lasts
+    pub refresh_ttl: Duration,
+    pub email_verification_ttl: Duration,
+}
+
+impl Default for TokenConfig {
+    fn default() -> Self {
+        Self {
+            access_ttl: Duration::from_secs(24 * 60 * 60),
+            refresh_ttl: Duration::from_secs(7 * 24 * 60 * 60),
+            email_verification_ttl: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+/// Attributes shared by the auth, refresh and CSRF cookies
+#[derive(Debug, Clone)]
+pub struct CookieConfig {
+    pub secure: bool,
+    pub same_site: SameSite,
+    /// `None` keeps cookies to the host that set them
+    pub domain: Option<String>,
+    pub path: String,
+}
+
+/// Maintenance mode, which pauses writes for everyone but admins
+#[derive(Debug, Clone)]
+pub struct MaintenanceConfig {
+    /// Keeps maintenance mode on whatever the admin API says
+    pub forced: bool,
+    /// Sent as `Retry-After` on rejected writes
+    pub retry_after: Duration,
+}
+
+/// Dial between fully open and locked-down deployments
+#[derive(Debug, Clone)]
+pub struct AccessConfig {
+    /// Reject every request that lacks a valid `X-API-Key`
+    pub require_api_key: bool,
+    pub api_keys: Vec<String>,
+    /// Anonymous `GET /posts` and `GET /posts/{id}` quota per client IP
+    pub public_read_burst: u32,
+    pub public_read_period: Duration,
+    /// `Cache-Control: max-age` sent on anonymous reads
+    pub public_cache_max_age: Duration,
+    /// Requests a signed-in user or API key may make in any 24 hours; `0`
+    /// turns quotas off
+    pub daily_quota: u64,
+    /// The same for admins
+    pub admin_daily_quota: u64,
+}
+
+/// What a publish hook does with the post URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishHookKind {
+    /// POST an IndexNow submission (needs `INDEXNOW_KEY`)
+    IndexNow,
+    /// GET the URL with `{url}` replaced by the post URL
+    Ping,
+    /// POST a JSON event, e.g. to a CDN cache purge webhook
+    Purge,
+}
+
+/// An endpoint notified when a public post is published or updated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishHookConfig {
+    pub kind: PublishHookKind,
+    pub url: String,
+}
+
+impl PublishHookConfig {
+    /// Parses `KIND=URL`, e.g. `indexnow=https://api.indexnow.org/indexnow`
+    pub fn parse(entry: &str) -> Option<Self> {
+        let (kind, url) = entry.split_once('=')?;
+        let kind = match kind.trim().to_ascii_lowercase().as_str() {
+            "indexnow" => PublishHookKind::IndexNow,
+            "ping" => PublishHookKind::Ping,
+            "purge" => PublishHookKind::Purge,
+            _ => return None,
+        };
+        let url = url.trim();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return None;
+        }
+        Some(Self {
+            kind,
+            url: url.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PublishHooksConfig {
+    pub hooks: Vec<PublishHookConfig>,
+    pub indexnow_key: Option<String>,
+    /// Public URL of a post; `{id}` is replaced by its public id
+    pub post_url_template: String,
+    /// Deliveries are marked failed after this many attempts
+    pub max_attempts: i32,
+    /// Delay before the first retry; doubles with every further attempt
+    pub retry_base: Duration,
+    /// How often the delivery job looks for due deliveries
+    pub poll_interval: Duration,
+}
+
+/// CDN whose edge cache is purged when a post changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CdnProvider {
+    Cloudflare { zone_id: String, api_token: String },
+    Fastly { api_token: String },
+}
+
+/// Which cached URLs are purged at the CDN when posts are published, updated or deleted
+#[derive(Debug, Clone)]
+pub struct CdnConfig {
+    /// `None` leaves cached copies to expire on their own
+    pub provider: Option<CdnProvider>,
+    /// Origin the CDN serves the API under, e.g. `https://api.example.com`
+    pub public_url: String,
+    /// Listings purged with every post change, relative to the base path
+    pub listing_paths: Vec<String>,
+}
+
+/// CAPTCHA service registrations are checked with
+#[derive(Debug, Clone)]
+pub enum CaptchaProvider {
+    HCaptcha { site_key: String, secret: String },
+    Turnstile { site_key: String, secret: String },
+}
+
+/// Bot checks on `POST /auth/register`; all off by default
+#[derive(Debug, Clone, Default)]
+pub struct SignupConfig {
+    /// Reject registrations that fill in the hidden `website` field
+    pub honeypot: bool,
+    /// Reject forms sent sooner than this after their form token was issued;
+    /// `None` asks for no form token
+    pub min_fill_time: Option<Duration>,
+    /// Disposable email domains, one per line, refused on top of mailchecker's list
+    pub disposable_domains_file: Option<PathBuf>,
+    /// `None` asks for no CAPTCHA
+    pub captcha: Option<CaptchaProvider>,
+}
+
+/// Names that would shadow a route or look official; always reserved
+pub const DEFAULT_RESERVED_SLUGS: [&str; 4] = ["admin", "api", "auth", "docs"];
+
+/// How post slugs are derived from titles and which names nobody can take
+#[derive(Debug, Clone)]
+pub struct SlugConfig {
+    /// Replace accented and non-Latin characters with ASCII approximations
+    pub transliterate: bool,
+    pub max_length: usize,
+    /// Words dropped from generated slugs, e.g. `a,an,the`
+    pub stop_words: Vec<String>,
+    /// Lowercase names post slugs and usernames may not use
+    pub reserved: Vec<String>,
+}
+
+/// Where cached reads are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheBackend {
+    /// Caching turned off; every read goes to the database
+    Disabled,
+    /// Per-process in-memory cache
+    Memory,
+    /// Shared Redis cache (needs the `redis` feature and `REDIS_URL`)
+    Redis,
+}
+
+impl From<&str> for CacheBackend {
+    fn from(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "off" | "none" | "disabled" => CacheBackend::Disabled,
+            "redis" => CacheBackend::Redis,
+            _ => CacheBackend::Memory,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub backend: CacheBackend,
+    /// How long a cached read is served as fresh
+    pub ttl: Duration,
+    /// How long past `ttl` hot lists are still served while being refreshed
+    pub stale_for: Duration,
+    /// Entries kept by the in-memory backend
+    pub max_entries: u64,
+}
+
+/// Postgres pool sizing, startup retries and health checks
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+    pub max_connections: u32,
+    /// How long a query waits for a free connection before failing
+    pub acquire_timeout: Duration,
+    /// Connection attempts at startup before giving up; 0 keeps trying
+    pub connect_attempts: u32,
+    /// Delay before the first retry; doubles with every further attempt
+    pub connect_backoff: Duration,
+    pub max_connect_backoff: Duration,
+    /// How often the database is probed while running
+    pub health_interval: Duration,
+    /// Read replica that public reads go to while it is up
+    pub read_url: Option<String>,
+    /// Repository calls taking at least this long are logged as warnings
+    pub slow_query_threshold: Duration,
+}
+
+impl DatabaseConfig {
+    /// Wait before the attempt following `failed` failed ones
+    pub fn connect_delay(&self, failed: u32) -> Duration {
+        let exponent = failed.saturating_sub(1).min(16);
+        self.connect_backoff
+            .saturating_mul(2u32.pow(exponent))
+            .min(self.max_connect_backoff)
+    }
+}
+
+/// Redis shared by every replica for rate limits, revoked sessions and the cache
+#[derive(Debug, Clone)]
+pub struct RedisConfig {
+    /// `None` keeps all of that state in each process (needs the `redis` feature)
+    pub url: Option<String>,
+    /// Prepended to every key so several deployments can share one database
+    pub key_prefix: String,
+}
+
+/// A removed endpoint answered with 410 Gone and a migration hint
+#[derive(Debug, Clone)]
+pub struct GoneEndpointConfig {
+    /// `None` matches every method
+    pub method: Option<Method>,
+    /// Exact path, or a prefix when it ends in `*`
+    pub path: String,
+    pub hint: String,
+}
+
+impl GoneEndpointConfig {
+    /// Parses `[METHOD ]PATH=HINT`, e.g. `DELETE /auth/delete-account=Use DELETE /auth/profile`
+    pub fn parse(entry: &str) -> Option<Self> {
+        let (target, hint) = entry.split_once('=')?;
+        let target = target.trim();
+        let (method, path) = match target.split_once(' ') {
+            Some((method, path)) => (Some(method.trim().parse().ok()?), path.trim()),
+            None => (None, target),
+        };
+
+        if !path.starts_with('/') {
+            return None;
+        }
+
+        Some(Self {
+            method,
+            path: path.to_string(),
+            hint: hint.trim().to_string(),
+        })
+    }
+}
+
+/// Transactional emails
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    /// Templates and the `locales/` catalogs they are rendered with
+    pub templates_dir: PathBuf,
+    /// Locale of users who haven't chosen one; must have a catalog
+    pub default_locale: String,
+    /// Origins, e.g. `https://app.example.com`, that verification links may
+    /// send users back to
+    pub redirect_origins: Vec<String>,
+    /// Unverified accounts at least this old are sent a new verification
+    /// email when an admin resends them
+    pub resend_min_age: Duration,
+    /// Verification emails sent at a time when resending
+    pub resend_batch_size: usize,
+    /// Pause between resent batches, keeping within the provider's rate limits
+    pub resend_batch_interval: Duration,
+    /// Signing secret (`whsec_...`) of the Resend webhook; `None` leaves
+    /// `POST /webhooks/resend` switched off
+    pub webhook_secret: Option<String>,
+}
+
+/// Branding for the `/` endpoint
+#[derive(Debug, Clone)]
+pub struct HomepageConfig {
+    pub api_name: String,
+    pub tagline: String,
+    pub docs_url: String,
+    /// Number of latest posts featured on the homepage
+    pub featured_posts: i64,
+}
+
+/// RSS and Atom feeds of the latest posts
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+    pub title: String,
+    pub description: String,
+    /// Absolute origin feed and site links point to, e.g. `https://blog.example.com`
+    pub public_url: String,
+    /// Posts per feed, newest first
+    pub max_items: i64,
+    /// How long clients and CDNs may cache a feed
+    pub max_age: Duration,
+}
+
+/// `/sitemap.xml` of the public posts and profiles
+#[derive(Debug, Clone)]
+pub struct SitemapConfig {
+    /// Absolute origin the sitemap index points to its pages under
+    pub public_url: String,
+    /// Public URL of an author's profile; `{id}` is replaced by their public id
+    pub profile_url_template: String,
+    /// How long clients and CDNs may cache a sitemap
+    pub max_age: Duration,
+}
+
+/// Where the versioned API is mounted
+#[derive(Debug, Clone)]
+pub struct ApiConfig {
+    /// Versions are served at `{prefix}/v1`, `{prefix}/v2`, ... under the base path
+    pub prefix: String,
+    /// Also answer the unversioned paths as the oldest version, marked deprecated
+    pub unversioned_routes: bool,
+}
+
+impl ApiConfig {
+    /// Every prefix the API routes are served under, unversioned first
+    pub fn roots(&self) -> Vec<String> {
+        let unversioned = self.unversioned_routes.then(String::new);
+        unversioned
+            .into_iter()
+            .chain(
+                ApiVersion::ALL
+                    .iter()
+                    .map(|version| format!("{}{}", self.prefix, version.segment())),
+            )
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub environment: Environment,
+    pub server: ServerConfig,
+    /// Prefix the whole API is mounted under, e.g. `/blog-api`; empty for the root
+    pub base_path: String,
+    pub api: ApiConfig,
+    pub tls: Option<TlsConfig>,
+    pub cors: CorsConfig,
+    pub compression: CompressionConfig,
+    pub error_format: ErrorFormat,
+    /// Catalogs translating response messages, one `<locale>.json` each
+    pub messages_dir: PathBuf,
+    pub accounts: AccountConfig,
+    pub tokens: TokenConfig,
+    pub cookies: CookieConfig,
+    pub maintenance: MaintenanceConfig,
+    pub database: DatabaseConfig,
+    pub access: AccessConfig,
+    pub gone_endpoints: Vec<GoneEndpointConfig>,
+    pub homepage: HomepageConfig,
+    pub federation: FederationConfig,
+    pub publish_hooks: PublishHooksConfig,
+    pub cdn: CdnConfig,
+    pub signup: SignupConfig,
+    pub feeds: FeedConfig,
+    pub sitemap: SitemapConfig,
+    pub email: EmailConfig,
+    pub cache: CacheConfig,
+    pub redis: RedisConfig,
+    pub slugs: SlugConfig,
+    /// License given to new posts that don't name one; `None` leaves them unlicensed
+    pub default_license: Option<String>,
+    /// Reject posts with images that have no alt text
+    pub require_alt_text: bool,
+    /// How long post preview links work, at most; they also end with the issuing session
+    pub preview_link_ttl: Duration,
+    /// How long post share links work when their author doesn't say
+    pub share_link_ttl: Duration,
+    /// How long an admin's impersonation token works
+    pub impersonation_ttl: Duration,
+    /// Requests taking at least this long are logged as warnings
+    pub slow_request_threshold: Duration,
+    /// How often feature flags are reloaded to pick up changes made on other replicas
+    pub feature_flag_refresh: Duration,
+    /// Caps on submitted fields, published at `GET /limits` and in the OpenAPI schemas
+    pub limits: Limits,
+}
+
+impl AppConfig {
+    pub fn from_env() -> Self {
+        let environment = Environment::from(
+            env::var("APP_ENV")
+                .unwrap_or_else(|_| "development".to_string())
+                .as_str(),
+        );
+
+        let cors = CorsConfig {
+            allowed_origins: env_list("CORS_ALLOWED_ORIGINS"),
+            allow_credentials: env_bool("CORS_ALLOW_CREDENTIALS", true),
+            exposed_headers: env_list("CORS_EXPOSED_HEADERS"),
+            max_age: Duration::from_secs(env_parse("CORS_MAX_AGE_SECS", 3600)),
+        };
+
+        let compression = CompressionConfig {
+            gzip: env_bool("COMPRESSION_GZIP", true),
+            br: env_bool("COMPRESSION_BR", true),
+            zstd: env_bool("COMPRESSION_ZSTD", true),
+            min_size: env_parse("COMPRESSION_MIN_SIZE", 1024),
+            decompress_requests: env_bool("REQUEST_DECOMPRESSION", true),
+        };
+
+        let server = ServerConfig {
+            host: env_parse("HOST", IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            port: env_parse("PORT", 8080),
+            tcp_backlog: env_parse("TCP_BACKLOG", 1024),
+            tcp_nodelay: env_bool("TCP_NODELAY", true),
+            http1_keep_alive: env_bool("HTTP1_KEEPALIVE", true),
+            http1_header_read_timeout: Duration::from_secs(env_parse(
+                "HTTP1_HEADER_READ_TIMEOUT_SECS",
+                30,
+            )),
+            http2: env_bool("HTTP2_ENABLED", true),
+            http2_max_concurrent_streams: env_parse("HTTP2_MAX_CONCURRENT_STREAMS", 200),
+            http2_keep_alive_interval: Some(env_parse("HTTP2_KEEPALIVE_INTERVAL_SECS", 20u64))
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs),
+            http2_keep_alive_timeout: Duration::from_secs(env_parse(
+                "HTTP2_KEEPALIVE_TIMEOUT_SECS",
+                20,
+            )),
+        };
+
+        let tls = match (env::var("TLS_CERT_PATH"), env::var("TLS_KEY_PATH")) {
+            (Ok(cert_path), Ok(key_path)) => Some(TlsConfig {
+                cert_path: PathBuf::from(cert_path),
+                key_path: PathBuf::from(key_path),
+                redirect_http_port: env::var("TLS_REDIRECT_HTTP_PORT")
+                    .ok()
+                    .and_then(|port| port.parse().ok()),
+            }),
+            (Ok(_), Err(_)) | (Err(_), Ok(_)) => {
+                warn!("Both TLS_CERT_PATH and TLS_KEY_PATH are required; serving plain HTTP");
+                None
+            }
+            _ => None,
+        };
+
+        let error_format = ErrorFormat::from(
+            env::var("ERROR_FORMAT")
+                .unwrap_or_else(|_| "problem".to_string())
+                .as_str(),
+        );
+
+        let database = DatabaseConfig {
+            max_connections: env_parse("DB_MAX_CONNECTIONS", 10u32).max(1),
+            acquire_timeout: Duration::from_secs(env_parse("DB_ACQUIRE_TIMEOUT_SECS", 5)),
+            connect_attempts: env_parse("DB_CONNECT_ATTEMPTS", 5),
+            connect_backoff: Duration::from_millis(env_parse("DB_CONNECT_BACKOFF_MS", 500)),
+            max_connect_backoff: Duration::from_secs(env_parse("DB_MAX_CONNECT_BACKOFF_SECS", 30)),
+            health_interval: Duration::from_secs(env_parse("DB_HEALTH_INTERVAL_SECS", 10).max(1)),
+            read_url: env::var("DATABASE_READ_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            slow_query_threshold: Duration::from_millis(env_parse("SLOW_QUERY_MS", 250)),
+        };
+
+        let accounts = AccountConfig {
+            deletion_grace_period: Duration::from_secs(
+                env_parse("ACCOUNT_DELETION_GRACE_DAYS", 14u64) * 24 * 60 * 60,
+            ),
+            purge_interval: Duration::from_secs(env_parse("ACCOUNT_PURGE_INTERVAL_SECS", 3600)),
+            allowed_email_domains: env_list("ALLOWED_EMAIL_DOMAINS"),
+            denied_email_domains: env_list("DENIED_EMAIL_DOMAINS"),
+            unverified_cleanup: env::var("UNVERIFIED_CLEANUP")
+                .ok()
+                .and_then(|mode| UnverifiedCleanup::parse(&mode)),
+            unverified_max_age: Duration::from_secs(
+                env_parse("UNVERIFIED_MAX_AGE_DAYS", 30u64) * 24 * 60 * 60,
+            ),
+            unverified_cleanup_interval: Duration::from_secs(
+                env_parse("UNVERIFIED_CLEANUP_INTERVAL_SECS", 86400).max(1),
+            ),
+            new_device_alerts: env_bool("NEW_DEVICE_ALERTS", true),
+            country_header: env::var("GEO_COUNTRY_HEADER")
+                .ok()
+                .map(|header| header.trim().to_string())
+                .filter(|header| !header.is_empty()),
+        };
+
+        let access = AccessConfig {
+            require_api_key: env_bool("REQUIRE_API_KEY", false),
+            api_keys: env_list("API_KEYS"),
+            public_read_burst: env_parse("PUBLIC_READ_BURST", 3),
+            public_read_period: Duration::from_millis(env_parse("PUBLIC_READ_PERIOD_MS", 2000)),
+            public_cache_max_age: Duration::from_secs(env_parse("PUBLIC_CACHE_MAX_AGE_SECS", 60)),
+            daily_quota: env_parse("DAILY_QUOTA", 10_000),
+            admin_daily_quota: env_parse("ADMIN_DAILY_QUOTA", 100_000),
+        };
+
+        if access.require_api_key && access.api_keys.is_empty() {
+            warn!("REQUIRE_API_KEY is set but API_KEYS is empty; every request will be rejected");
+        }
+
+        // Semicolon-separated because migration hints often contain commas
+        let gone_endpoints = env::var("GONE_ENDPOINTS")
+            .unwrap_or_default()
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let parsed = GoneEndpointConfig::parse(entry);
+                if parsed.is_none() {
+                    warn!("Ignoring invalid GONE_ENDPOINTS entry: {}", entry);
+                }
+                parsed
+            })
+            .collect();
+
+        let base_path = normalize_base_path(&env::var("BASE_PATH").unwrap_or_default());
+
+        let defaults = TokenConfig::default();
+        let tokens = TokenConfig {
+            access_ttl: env_secs("ACCESS_TOKEN_TTL_SECS", defaults.access_ttl),
+            refresh_ttl: env_secs("REFRESH_TOKEN_TTL_SECS", defaults.refresh_ttl),
+            email_verification_ttl: env_secs(
+                "EMAIL_VERIFICATION_TTL_SECS",
+                defaults.email_verification_ttl,
+            ),
+        };
+
+        let same_site = match env::var("COOKIE_SAME_SITE")
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "strict" => SameSite::Strict,
+            "none" => SameSite::None,
+            _ => SameSite::Lax,
+        };
+        let cookies = CookieConfig {
+            // Browsers drop SameSite=None cookies that aren't Secure
+            secure: env_bool("COOKIE_SECURE", tls.is_some()) || same_site == SameSite::None,
+            same_site,
+            domain: env::var("COOKIE_DOMAIN")
+                .ok()
+                .filter(|domain| !domain.trim().is_empty()),
+            path: env::var("COOKIE_PATH")
+                .ok()
+                .filter(|path| path.starts_with('/'))
+                .unwrap_or_else(|| {
+                    if base_path.is_empty() {
+                        "/".to_string()
+                    } else {
+                        base_path.clone()
+                    }
+                }),
+        };
+
+        let api = ApiConfig {
+            prefix: normalize_base_path(
+                &env::var("API_PREFIX").unwrap_or_else(|_| DEFAULT_API_PREFIX.to_string()),
+            ),
+            unversioned_routes: env_bool("API_UNVERSIONED_ROUTES", true),
+        };
+
+        let homepage = HomepageConfig {
+            api_name: env::var("HOMEPAGE_NAME").unwrap_or_else(|_| "Axum REST API".to_string()),
+            tagline: env::var("HOMEPAGE_TAGLINE")
+                .unwrap_or_else(|_| "User authentication and blog post management".to_string()),
+            docs_url: env::var("HOMEPAGE_DOCS_URL").unwrap_or_else(|_| {
+                format!(
+                    "{}{}{}/docs",
+                    base_path,
+                    api.prefix,
+                    ApiVersion::LATEST.segment()
+                )
+            }),
+            featured_posts: env_parse("HOMEPAGE_FEATURED_POSTS", 3),
+        };
+
+        let federation = FederationConfig {
+            public_url: env::var("FEDERATION_PUBLIC_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+        };
+
+        // Semicolon-separated because ping URLs often contain commas
+        let publish_hooks = PublishHooksConfig {
+            hooks: env::var("PUBLISH_HOOKS")
+                .unwrap_or_default()
+                .split(';')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .filter_map(|entry| {
+                    let parsed = PublishHookConfig::parse(entry);
+                    if parsed.is_none() {
+                        warn!("Ignoring invalid PUBLISH_HOOKS entry: {}", entry);
+                    }
+                    parsed
+                })
+                .collect(),
+            indexnow_key: env::var("INDEXNOW_KEY").ok().filter(|key| !key.is_empty()),
+            post_url_template: env::var("PUBLIC_POST_URL")
+                .unwrap_or_else(|_| format!("{}/posts/{{id}}", federation.public_url)),
+            max_attempts: env_parse("PUBLISH_HOOK_MAX_ATTEMPTS", 5),
+            retry_base: Duration::from_secs(env_parse("PUBLISH_HOOK_RETRY_BASE_SECS", 30)),
+            poll_interval: Duration::from_secs(env_parse("PUBLISH_HOOK_POLL_SECS", 10)),
+        };
+
+        if publish_hooks
+            .hooks
+            .iter()
+            .any(|hook| hook.kind == PublishHookKind::IndexNow)
+            && publish_hooks.indexnow_key.is_none()
+        {
+            warn!(
+                "An indexnow publish hook is configured without INDEXNOW_KEY; it will be skipped"
+            );
+        }
+
+        let cdn = CdnConfig {
+            provider: match env::var("CDN_PURGE_PROVIDER")
+                .unwrap_or_default()
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "" | "none" => None,
+                "cloudflare" => match (
+                    env::var("CLOUDFLARE_ZONE_ID")
+                        .ok()
+                        .filter(|id| !id.is_empty()),
+                    env::var("CLOUDFLARE_API_TOKEN")
+                        .ok()
+                        .filter(|token| !token.is_empty()),
+                ) {
+                    (Some(zone_id), Some(api_token)) => {
+                        Some(CdnProvider::Cloudflare { zone_id, api_token })
+                    }
+                    _ => {
+                        warn!(
+                            "CDN_PURGE_PROVIDER=cloudflare needs CLOUDFLARE_ZONE_ID and CLOUDFLARE_API_TOKEN; purging disabled"
+                        );
+                        None
+                    }
+                },
+                "fastly" => match env::var("FASTLY_API_TOKEN")
+                    .ok()
+                    .filter(|token| !token.is_empty())
+                {
+                    Some(api_token) => Some(CdnProvider::Fastly { api_token }),
+                    None => {
+                        warn!("CDN_PURGE_PROVIDER=fastly needs FASTLY_API_TOKEN; purging disabled");
+                        None
+                    }
+                },
+                other => {
+                    warn!("Ignoring unknown CDN_PURGE_PROVIDER: {}", other);
+                    None
+                }
+            },
+            public_url: env::var("CDN_PUBLIC_URL")
+                .unwrap_or_else(|_| federation.public_url.clone())
+                .trim_end_matches('/')
+                .to_string(),
+            listing_paths: match env_list("CDN_PURGE_PATHS") {
+                paths if paths.is_empty() => {
+                    let mut paths = vec!["/".to_string()];
+                    for root in api.roots() {
+                        paths.push(format!("{}/posts", root));
+                        paths.push(format!("{}/posts/trending", root));
+                    }
+                    paths.push("/feeds/posts.xml".to_string());
+                    paths.push("/feeds/posts.atom".to_string());
+                    paths
+                }
+                paths => paths,
+            },
+        };
+
+        let signup = SignupConfig {
+            honeypot: env_bool("SIGNUP_HONEYPOT", false),
+            min_fill_time: Some(env_parse("SIGNUP_MIN_FILL_SECS", 0u64))
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs),
+            disposable_domains_file: env::var("DISPOSABLE_DOMAINS_FILE")
+                .ok()
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            captcha: match env::var("CAPTCHA_PROVIDER")
+                .unwrap_or_default()
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "" | "none" => None,
+                provider => match (
+                    env::var("CAPTCHA_SITE_KEY")
+                        .ok()
+                        .filter(|key| !key.is_empty()),
+                    env::var("CAPTCHA_SECRET")
+                        .ok()
+                        .filter(|secret| !secret.is_empty()),
+                ) {
+                    (Some(site_key), Some(secret)) => match provider {
+                        "hcaptcha" => Some(CaptchaProvider::HCaptcha { site_key, secret }),
+                        "turnstile" => Some(CaptchaProvider::Turnstile { site_key, secret }),
+                        other => {
+                            warn!("Ignoring unknown CAPTCHA_PROVIDER: {}", other);
+                            None
+                        }
+                    },
+                    _ => {
+                        warn!(
+                            "CAPTCHA_PROVIDER={} needs CAPTCHA_SITE_KEY and CAPTCHA_SECRET; CAPTCHA disabled",
+                            provider
+                        );
+                        None
+                    }
+                },
+            },
+        };
+
+        let feeds = FeedConfig {
+            title: env::var("FEED_TITLE").unwrap_or_else(|_| homepage.api_name.clone()),
+            description: env::var("FEED_DESCRIPTION").unwrap_or_else(|_| homepage.tagline.clone()),
+            public_url: env::var("FEED_PUBLIC_URL")
+                .unwrap_or_else(|_| federation.public_url.clone())
+                .trim_end_matches('/')
+                .to_string(),
+            max_items: env_parse("FEED_MAX_ITEMS", 20i64).clamp(1, 100),
+            max_age: Duration::from_secs(env_parse("FEED_MAX_AGE_SECS", 300)),
+        };
+
+        let sitemap = SitemapConfig {
+            public_url: env::var("SITEMAP_PUBLIC_URL")
+                .unwrap_or_else(|_| federation.public_url.clone())
+                .trim_end_matches('/')
+                .to_string(),
+            profile_url_template: env::var("PUBLIC_PROFILE_URL")
+                .unwrap_or_else(|_| format!("{}/profiles/{{id}}", federation.public_url)),
+            max_age: Duration::from_secs(env_parse("SITEMAP_MAX_AGE_SECS", 3600)),
+        };
+
+        let email = EmailConfig {
+            templates_dir: PathBuf::from(
+                env::var("EMAIL_TEMPLATES_DIR").unwrap_or_else(|_| "templates/email".to_string()),
+            ),
+            default_locale: env::var("EMAIL_DEFAULT_LOCALE").unwrap_or_else(|_| "en".to_string()),
+            redirect_origins: env_list("VERIFY_EMAIL_REDIRECT_ORIGINS"),
+            resend_min_age: Duration::from_secs(env_parse(
+                "VERIFICATION_RESEND_MIN_AGE_SECS",
+                24 * 60 * 60,
+            )),
+            resend_batch_size: env_parse("VERIFICATION_RESEND_BATCH_SIZE", 50usize).max(1),
+            resend_batch_interval: Duration::from_secs(env_parse(
+                "VERIFICATION_RESEND_BATCH_INTERVAL_SECS",
+                60,
+            )),
+            webhook_secret: env::var("RESEND_WEBHOOK_SECRET")
+                .ok()
+                .filter(|secret| !secret.is_empty()),
+        };
+
+        let cache = CacheConfig {
+            backend: CacheBackend::from(
+                env::var("CACHE_BACKEND")
+                    .unwrap_or_else(|_| "memory".to_string())
+                    .as_str(),
+            ),
+            ttl: Duration::from_secs(env_parse("CACHE_TTL_SECS", 60)),
+            stale_for: Duration::from_secs(env_parse("CACHE_STALE_SECS", 30)),
+            max_entries: env_parse("CACHE_MAX_ENTRIES", 10_000),
+        };
+
+        let redis = RedisConfig {
+            url: env::var("REDIS_URL").ok().filter(|url| !url.is_empty()),
+            key_prefix: env::var("REDIS_KEY_PREFIX").unwrap_or_else(|_| "axum-rest:".to_string()),
+        };
+
+        let defaults = Limits::default();
+        let limits = Limits {
+            max_title_length: env_parse("MAX_TITLE_LENGTH", defaults.max_title_length).max(1),
+            max_content_length: env_parse("MAX_CONTENT_LENGTH", defaults.max_content_length).max(1),
+            max_name_length: env_parse("MAX_NAME_LENGTH", defaults.max_name_length).max(1),
+            max_report_details_length: env_parse(
+                "MAX_REPORT_DETAILS_LENGTH",
+                defaults.max_report_details_length,
+            ),
+        };
+
+        let slugs = SlugConfig {
+            transliterate: env_bool("SLUG_TRANSLITERATE", true),
+            max_length: env_parse("SLUG_MAX_LENGTH", 80usize).max(8),
+            stop_words: env_list("SLUG_STOP_WORDS")
+                .into_iter()
+                .map(|word| word.to_lowercase())
+                .collect(),
+            reserved: DEFAULT_RESERVED_SLUGS
+                .iter()
+                .map(|name| name.to_string())
+                .chain(
+                    env_list("SLUG_RESERVED")
+                        .into_iter()
+                        .map(|name| name.to_lowercase()),
+                )
+                .collect(),
+        };
+
+        let default_license = env::var("DEFAULT_LICENSE")
+            .ok()
+            .filter(|license| !license.trim().is_empty())
+            .and_then(|license| match normalize_license(&license) {
+                Ok(license) => Some(license),
+                Err(e) => {
+                    warn!("Ignoring DEFAULT_LICENSE: {}", e);
+                    None
+                }
+            });
+
+        Self {
+            environment,
+            server,
+            base_path,
+            api,
+            tls,
+            cors,
+            compression,
+            error_format,
+            messages_dir: PathBuf::from(
+                env::var("API_MESSAGES_DIR").unwrap_or_else(|_| "locales".to_string()),
+            ),
+            accounts,
+            tokens,
+            cookies,
+            maintenance: MaintenanceConfig {
+                forced: env_bool("MAINTENANCE_MODE", false),
+                retry_after: Duration::from_secs(env_parse("MAINTENANCE_RETRY_AFTER_SECS", 300)),
+            },
+            database,
+            access,
+            gone_endpoints,
+            homepage,
+            federation,
+            publish_hooks,
+            cdn,
+            signup,
+            feeds,
+            sitemap,
+            email,
+            cache,
+            redis,
+            slugs,
+            default_license,
+            require_alt_text: env_bool("REQUIRE_ALT_TEXT", false),
+            preview_link_ttl: Duration::from_secs(env_parse("PREVIEW_LINK_TTL_SECS", 3600)),
+            share_link_ttl: Duration::from_secs(env_parse("SHARE_LINK_TTL_SECS", 604_800)),
+            impersonation_ttl: Duration::from_secs(env_parse("IMPERSONATION_TTL_SECS", 900)),
+            slow_request_threshold: Duration::from_millis(env_parse("SLOW_REQUEST_MS", 1000)),
+            feature_flag_refresh: Duration::from_secs(
+                env_parse("FEATURE_FLAG_REFRESH_SECS", 30).max(1),
+            ),
+            limits,
+        }
+    }
+
+    pub fn is_production(&self) -> bool {
+        self.environment == Environment::Production
+    }
+
+    pub fn tls_enabled(&self) -> bool {
+        self.tls.is_some()
+    }
+
+    pub fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.server.host, self.server.port)
+    }
+
+    /// `path` as seen by clients, i.e. with the base path prepended
+    pub fn public_path(&self, path: &str) -> String {
+        format!("{}{}", self.base_path, path)
+    }
+
+    /// `path` under `version` of the API as seen by clients
+    pub fn versioned_path(&self, version: ApiVersion, path: &str) -> String {
+        format!(
+            "{}{}{}{}",
+            self.base_path,
+            self.api.prefix,
+            version.segment(),
+            path
+        )
+    }
+
+    /// `path` under the latest API version as seen by clients
+    pub fn api_path(&self, path: &str) -> String {
+        self.versioned_path(ApiVersion::LATEST, path)
+    }
+}
+
+impl CorsConfig {
+    /// Development mirrors localhost origins; production only allows the
+    /// configured list.
+    pub fn layer(&self, environment: Environment) -> CorsLayer {
+        let exposed: Vec<HeaderName> = self
+            .exposed_headers
+            .iter()
+            .filter_map(|name| name.parse().ok())
+            .collect();
+
+        let layer = CorsLayer::new()
+            .allow_methods([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ])
+            .allow_credentials(self.allow_credentials)
+            .expose_headers(exposed)
+            .max_age(self.max_age);
+
+        match environment {
+            Environment::Development if self.allowed_origins.is_empty() => layer
+                .allow_origin(AllowOrigin::predicate(|origin, _| is_local_origin(origin)))
+                .allow_headers(AllowHeaders::mirror_request()),
+            _ => {
+                let origins: Vec<HeaderValue> = self
+                    .allowed_origins
+                    .iter()
+                    .filter_map(|origin| match origin.parse() {
+                        Ok(value) => Some(value),
+                        Err(_) => {
+                            warn!("Ignoring invalid CORS origin: {}", origin);
+                            None
+                        }
+                    })
+                    .collect();
+
+                if origins.is_empty() {
+                    warn!("No CORS origins configured; cross-origin requests will be rejected");
+                }
+
+                // Every request header the API reads, so preflights don't strip any
+                layer.allow_origin(origins).allow_headers([
+                    header::AUTHORIZATION,
+                    header::CONTENT_TYPE,
+                    HeaderName::from_static(CSRF_HEADER),
+                    HeaderName::from_static(API_KEY_HEADER),
+                    HeaderName::from_static(ORG_HEADER),
+                    HeaderName::from_static(TIMESTAMP_FORMAT_HEADER),
+                    HeaderName::from_static(TIMEZONE_HEADER),
+                    API_VERSION_HEADER,
+                ])
+            }
+        }
+    }
+}
+
+/// Whether `origin` is a page served from this machine, so credentialed
+/// requests from other sites never pass when `APP_ENV` was left unset
+fn is_local_origin(origin: &HeaderValue) -> bool {
+    let Some(authority) = origin.to_str().ok().and_then(|origin| {
+        origin
+            .strip_prefix("http://")
+            .or_else(|| origin.strip_prefix("https://"))
+    }) else {
+        return false;
+    };
+
+    let host = match authority.strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map(|(host, _)| host),
+        None => authority.split(':').next(),
+    };
+    matches!(host, Some("localhost" | "127.0.0.1" | "::1"))
+}
+
+impl CompressionConfig {
+    pub fn layer(&self) -> CompressionLayer<impl Predicate + use<>> {
+        // Same exclusions as tower-http's default predicate, with our size threshold
+        let predicate = SizeAbove::new(self.min_size)
+            .and(NotForContentType::GRPC)
+            .and(NotForContentType::IMAGES)
+            .and(NotForContentType::SSE);
+
+        CompressionLayer::new()
+            .gzip(self.gzip)
+            .br(self.br)
+            .zstd(self.zstd)
+            .compress_when(predicate)
+    }
+
+    pub fn decompression_layer(&self) -> RequestDecompressionLayer {
+        let enabled = self.decompress_requests;
+        RequestDecompressionLayer::new()
+            .gzip(enabled && self.gzip)
+            .br(enabled && self.br)
+            .zstd(enabled && self.zstd)
+    }
+}
+
+/// `blog-api/` and `/blog-api` both become `/blog-api`; `/` becomes empty
+pub fn normalize_base_path(raw: &str) -> String {
+    let trimmed = raw.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", trimmed)
+    }
+}
+
+pub fn env_list(key: &str) -> Vec<String> {
+    env::var(key)
+        .map(|value| {
+            value
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn env_bool(key: &str, default: bool) -> bool {
+    env::var(key)
+        .map(|value| matches!(value.to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(default)
+}
+
+/// `key` read as a number of seconds
+pub fn env_secs(key: &str, default: Duration) -> Duration {
+    Duration::from_secs(env_parse(key, default.as_secs()))
+}
+
+pub fn env_parse<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
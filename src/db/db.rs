@@ -1,15 +1,45 @@
 use anyhow::Result;
-use sqlx::PgPool;
+use sqlx::{PgPool, postgres::PgPoolOptions};
 
-use tracing::info;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::DatabaseConfig;
+use crate::helpers::ids::new_public_id;
+
+/// Every table `init_db` creates, checked by the admin diagnostics
+pub const TABLES: [&str; 18] = [
+    "users",
+    "posts",
+    "organizations",
+    "memberships",
+    "post_views",
+    "post_media",
+    "mentions",
+    "post_collaborators",
+    "share_links",
+    "sessions",
+    "link_tokens",
+    "ap_followers",
+    "hook_deliveries",
+    "email_domain_rules",
+    "reports",
+    "audit_log",
+    "notifications",
+    "feature_flags",
+];
 
 pub struct Db {
     pool: PgPool,
 }
 
 impl Db {
-    pub async fn new(database_url: &str) -> Result<Self> {
-        let pool = PgPool::connect(database_url).await?;
+    pub async fn new(database_url: &str, config: &DatabaseConfig) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(config.acquire_timeout)
+            .connect(database_url)
+            .await?;
         info!("Connected to the database at {}", database_url);
 
         Self::init_db(&pool).await?;
@@ -21,11 +51,14 @@ impl Db {
             r#"
                 CREATE TABLE IF NOT EXISTS users (
                 id UUID PRIMARY KEY,
+                public_id TEXT UNIQUE NOT NULL,
                 name TEXT NOT NULL,
                 email TEXT UNIQUE NOT NULL,
                 password TEXT NOT NULL,
                 role VARCHAR(10) NOT NULL DEFAULT 'USER',
                 email_verified BOOLEAN NOT NULL DEFAULT FALSE,
+                avatar_url TEXT,
+                deletion_scheduled_at TIMESTAMP WITH TIME ZONE,
                 created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
                 updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
             )
@@ -38,6 +71,7 @@ impl Db {
             r#"
                 CREATE TABLE IF NOT EXISTS posts (
                 id UUID PRIMARY KEY,
+                public_id TEXT UNIQUE NOT NULL,
                 title TEXT NOT NULL,
                 content TEXT NOT NULL,
                 author_id UUID NOT NULL REFERENCES users(id),
@@ -49,6 +83,588 @@ impl Db {
         .execute(pool)
         .await?;
 
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS organizations (
+                id UUID PRIMARY KEY,
+                public_id TEXT UNIQUE NOT NULL,
+                name TEXT NOT NULL,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+                updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS memberships (
+                org_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                role VARCHAR(10) NOT NULL DEFAULT 'VIEWER',
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (org_id, user_id)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Posts outside any organization keep a NULL org_id
+        sqlx::query(
+            r#"
+                ALTER TABLE posts ADD COLUMN IF NOT EXISTS org_id UUID REFERENCES organizations(id) ON DELETE CASCADE
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Rendered Markdown, cached on write; NULL rows are rendered on read
+        sqlx::query(
+            r#"
+                ALTER TABLE posts ADD COLUMN IF NOT EXISTS content_html TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Counted views per post
+        sqlx::query(
+            r#"
+                ALTER TABLE posts ADD COLUMN IF NOT EXISTS view_count BIGINT NOT NULL DEFAULT 0
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // URL-friendly names; posts from before slugs existed keep NULL
+        sqlx::query(
+            r#"
+                ALTER TABLE posts ADD COLUMN IF NOT EXISTS slug TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                CREATE UNIQUE INDEX IF NOT EXISTS posts_slug_key ON posts (slug)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // An author's posts by date, for their dashboard statistics
+        sqlx::query(
+            r#"
+                CREATE INDEX IF NOT EXISTS posts_author_created_idx ON posts (author_id, created_at) INCLUDE (view_count)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // SPDX identifier or free text; existing posts stay unlicensed
+        sqlx::query(
+            r#"
+                ALTER TABLE posts ADD COLUMN IF NOT EXISTS license TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Filled in as posts are written; older posts are summarized on read
+        sqlx::query(
+            r#"
+                ALTER TABLE posts
+                    ADD COLUMN IF NOT EXISTS word_count INTEGER,
+                    ADD COLUMN IF NOT EXISTS reading_time_minutes INTEGER,
+                    ADD COLUMN IF NOT EXISTS excerpt TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Set while the author keeps the post out of listings and feeds
+        sqlx::query(
+            r#"
+                ALTER TABLE posts ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP WITH TIME ZONE
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // PUBLIC, UNLISTED or PRIVATE; existing posts stay public
+        sqlx::query(
+            r#"
+                ALTER TABLE posts ADD COLUMN IF NOT EXISTS visibility VARCHAR(10) NOT NULL DEFAULT 'PUBLIC'
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // One row per viewer, refreshed when a view counts again
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS post_views (
+                post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+                viewer_key VARCHAR(128) NOT NULL,
+                viewed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                PRIMARY KEY (post_id, viewer_key)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                CREATE INDEX IF NOT EXISTS post_views_viewed_at_idx ON post_views (viewed_at)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Images referenced by post Markdown, rewritten whenever the content changes
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS post_media (
+                post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+                position INTEGER NOT NULL,
+                url TEXT NOT NULL,
+                alt_text TEXT,
+                PRIMARY KEY (post_id, position)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Users mentioned in post Markdown, rewritten whenever the content changes
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS mentions (
+                post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                position INTEGER NOT NULL,
+                PRIMARY KEY (post_id, user_id)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Co-authors an author invited to a post, as EDITOR or VIEWER
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS post_collaborators (
+                post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                role VARCHAR(10) NOT NULL DEFAULT 'VIEWER',
+                invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (post_id, user_id)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Links that let anyone holding them read a post; only token hashes are kept
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS share_links (
+                id UUID PRIMARY KEY,
+                post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+                token_hash TEXT NOT NULL UNIQUE,
+                created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                max_uses INTEGER,
+                uses INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                CREATE INDEX IF NOT EXISTS share_links_post_id_idx ON share_links (post_id)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Login sessions, keyed by the refresh token's jti
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS sessions (
+                id TEXT PRIMARY KEY,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                device TEXT NOT NULL,
+                ip TEXT,
+                user_agent TEXT,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                last_seen_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                revoked_at TIMESTAMP WITH TIME ZONE
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Where each session signed in from, to tell new devices from known ones
+        sqlx::query(
+            r#"
+                ALTER TABLE sessions
+                    ADD COLUMN IF NOT EXISTS network TEXT,
+                    ADD COLUMN IF NOT EXISTS country TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                CREATE INDEX IF NOT EXISTS sessions_user_id_idx ON sessions (user_id)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Single-use tokens of emailed links, stored as SHA-256 hashes
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS link_tokens (
+                token_hash TEXT PRIMARY KEY,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                purpose TEXT NOT NULL,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // What a link acts on besides its user, e.g. the session a revoke link signs out
+        sqlx::query(
+            r#"
+                ALTER TABLE link_tokens ADD COLUMN IF NOT EXISTS subject TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Remote ActivityPub followers of local authors
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS ap_followers (
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                actor TEXT NOT NULL,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                PRIMARY KEY (user_id, actor)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Where activities are delivered; NULL for followers recorded before deliveries
+        sqlx::query(
+            r#"
+                ALTER TABLE ap_followers ADD COLUMN IF NOT EXISTS inbox TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Publish hook notifications; doubles as the delivery log
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS hook_deliveries (
+                id UUID PRIMARY KEY,
+                event TEXT NOT NULL,
+                post_id TEXT NOT NULL,
+                method VARCHAR(10) NOT NULL,
+                url TEXT NOT NULL,
+                body TEXT,
+                status VARCHAR(10) NOT NULL DEFAULT 'PENDING',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_status INTEGER,
+                last_error TEXT,
+                next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                updated_at TIMESTAMP WITH TIME ZONE NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                CREATE INDEX IF NOT EXISTS hook_deliveries_due_idx ON hook_deliveries (next_attempt_at) WHERE status = 'PENDING'
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // ActivityPub deliveries go through the same queue, signed under
+        // `signed_by`; follow acceptances have no post
+        sqlx::query(
+            r#"
+                ALTER TABLE hook_deliveries
+                    ALTER COLUMN post_id DROP NOT NULL,
+                    ADD COLUMN IF NOT EXISTS signed_by TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Registration domain rules managed at runtime by admins
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS email_domain_rules (
+                domain VARCHAR(253) PRIMARY KEY,
+                kind VARCHAR(5) NOT NULL,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Backfill public ids for tables created before they existed
+        for table in ["users", "posts"] {
+            sqlx::query(&format!(
+                r#"
+                ALTER TABLE {table} ADD COLUMN IF NOT EXISTS public_id TEXT UNIQUE
+                "#
+            ))
+            .execute(pool)
+            .await?;
+
+            let ids: Vec<Uuid> = sqlx::query_scalar(&format!(
+                r#"
+                SELECT id FROM {table} WHERE public_id IS NULL
+                "#
+            ))
+            .fetch_all(pool)
+            .await?;
+            if !ids.is_empty() {
+                info!("Backfilling {} public ids in {}", ids.len(), table);
+                let public_ids: Vec<String> = ids.iter().map(|_| new_public_id()).collect();
+                sqlx::query(&format!(
+                    r#"
+                    UPDATE {table}
+                    SET public_id = backfill.public_id
+                    FROM UNNEST($1::UUID[], $2::TEXT[]) AS backfill (id, public_id)
+                    WHERE {table}.id = backfill.id
+                    "#
+                ))
+                .bind(&ids)
+                .bind(&public_ids)
+                .execute(pool)
+                .await?;
+            }
+
+            sqlx::query(&format!(
+                r#"
+                ALTER TABLE {table} ALTER COLUMN public_id SET NOT NULL
+                "#
+            ))
+            .execute(pool)
+            .await?;
+        }
+
+        // Accounts awaiting deletion after the grace period
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS deletion_scheduled_at TIMESTAMP WITH TIME ZONE
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Custom avatars; NULL falls back to Gravatar
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS avatar_url TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // ACTIVE, SUSPENDED or BANNED; restricted accounts cannot sign in or use their tokens
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS status VARCHAR(16) NOT NULL DEFAULT 'ACTIVE'
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // NULL suspends until lifted by an admin
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS suspended_until TIMESTAMP WITH TIME ZONE
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS status_reason TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Moderation queue; outlives the reported content so decisions stay on record
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS reports (
+                id UUID PRIMARY KEY,
+                target_kind VARCHAR(16) NOT NULL,
+                target_id UUID NOT NULL,
+                target_public_id TEXT NOT NULL,
+                author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+                reporter_id UUID REFERENCES users(id) ON DELETE SET NULL,
+                reason VARCHAR(32) NOT NULL,
+                details TEXT,
+                status VARCHAR(16) NOT NULL DEFAULT 'OPEN',
+                action VARCHAR(16),
+                note TEXT,
+                resolved_by UUID REFERENCES users(id) ON DELETE SET NULL,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                resolved_at TIMESTAMP WITH TIME ZONE
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // One open report per reporter and piece of content
+        sqlx::query(
+            r#"
+                CREATE UNIQUE INDEX IF NOT EXISTS reports_open_key ON reports (reporter_id, target_kind, target_id) WHERE status = 'OPEN'
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Administrative actions, kept after the acting admin is deleted
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS audit_log (
+                id UUID PRIMARY KEY,
+                actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+                action VARCHAR(64) NOT NULL,
+                target TEXT NOT NULL,
+                detail TEXT,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // NULL sends emails in the default locale
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS locale VARCHAR(16)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // NULL for accounts created before usernames; unique regardless of case
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS username TEXT
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                CREATE UNIQUE INDEX IF NOT EXISTS users_username_key ON users (LOWER(username))
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Set when the unverified account cleanup scrubbed the account
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS anonymized_at TIMESTAMP WITH TIME ZONE
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Each user's inbox; goes with their account
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS notifications (
+                id UUID PRIMARY KEY,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                kind VARCHAR(32) NOT NULL,
+                message TEXT NOT NULL,
+                target TEXT,
+                read_at TIMESTAMP WITH TIME ZONE,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                CREATE INDEX IF NOT EXISTS notifications_user_created ON notifications (user_id, created_at DESC)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Flags switched by admins; a flag without a row is on
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS feature_flags (
+                name VARCHAR(64) PRIMARY KEY,
+                enabled BOOLEAN NOT NULL,
+                updated_by UUID,
+                updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Last delivery event Resend reported for the address, so hard bounces
+        // and spam complaints stop further emails
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS email_delivery VARCHAR(16)
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+                ALTER TABLE users ADD COLUMN IF NOT EXISTS email_delivery_at TIMESTAMP WITH TIME ZONE
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
         info!("Database initialized");
         Ok(())
     }
@@ -58,9 +674,55 @@ impl Db {
     }
 }
 
-pub async fn get_pg_client() -> Result<Db> {
+/// Tables from [`TABLES`] missing in the current schema
+pub async fn missing_tables(pool: &PgPool) -> Result<Vec<String>> {
+    let present: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT table_name::TEXT
+        FROM information_schema.tables
+        WHERE table_schema = current_schema()
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(TABLES
+        .iter()
+        .filter(|table| !present.iter().any(|name| name == *table))
+        .map(|table| table.to_string())
+        .collect())
+}
+
+/// Connects to `DATABASE_URL`, retrying with backoff while the database is
+/// unreachable, e.g. when it starts alongside the API
+pub async fn get_pg_client(config: &DatabaseConfig) -> Result<Db> {
     let database_url =
         std::env::var("DATABASE_URL").expect("DATABASE_URL must be set in environment variables");
-    let db = Db::new(&database_url).await?;
-    Ok(db)
+
+    let mut failed = 0;
+    loop {
+        match Db::new(&database_url, config).await {
+            Ok(db) => {
+                if failed > 0 {
+                    info!(
+                        "Connected to the database after {} failed attempt(s)",
+                        failed
+                    );
+                }
+                return Ok(db);
+            }
+            Err(e) => {
+                failed += 1;
+                if config.connect_attempts != 0 && failed >= config.connect_attempts {
+                    return Err(e.context(format!("giving up after {} attempt(s)", failed)));
+                }
+                let delay = config.connect_delay(failed);
+                warn!(
+                    "Database connection attempt {} failed ({}), retrying in {:?}",
+                    failed, e, delay
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
 }
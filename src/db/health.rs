@@ -0,0 +1,78 @@
+//! Database health as seen by the background monitor, which backs the
+//! readiness endpoint so load balancers stop routing to an instance whose
+//! database is gone or whose pool is exhausted.
+
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::model::model::{DbStatus, Readiness};
+
+#[derive(Debug)]
+pub struct DbHealth {
+    state: RwLock<(DbStatus, DateTime<Utc>)>,
+}
+
+impl DbHealth {
+    /// Starts out `UP`, since the server only starts once it has connected
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            state: RwLock::new((DbStatus::UP, now)),
+        }
+    }
+
+    pub fn status(&self) -> DbStatus {
+        self.state.read().unwrap().0
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.status() == DbStatus::UP
+    }
+
+    pub fn readiness(&self) -> Readiness {
+        let (database, since) = *self.state.read().unwrap();
+        Readiness {
+            ready: database == DbStatus::UP,
+            database,
+            since,
+        }
+    }
+
+    /// Stores the latest probe result; returns the previous status and when it
+    /// began if this is a change
+    pub fn record(
+        &self,
+        status: DbStatus,
+        now: DateTime<Utc>,
+    ) -> Option<(DbStatus, DateTime<Utc>)> {
+        let mut state = self.state.write().unwrap();
+        if state.0 == status {
+            return None;
+        }
+        let previous = *state;
+        *state = (status, now);
+        Some(previous)
+    }
+}
+
+impl Default for DbHealth {
+    fn default() -> Self {
+        Self::new(Utc::now())
+    }
+}
+
+/// Runs a trivial query through the pool. A timed-out acquire only counts as
+/// saturation when every connection is checked out; otherwise the pool could
+/// not open a new one and the database is down.
+pub async fn probe(pool: &PgPool) -> DbStatus {
+    match sqlx::query("SELECT 1").execute(pool).await {
+        Ok(_) => DbStatus::UP,
+        Err(sqlx::Error::PoolTimedOut)
+            if pool.num_idle() == 0 && pool.size() >= pool.options().get_max_connections() =>
+        {
+            DbStatus::SATURATED
+        }
+        Err(_) => DbStatus::DOWN,
+    }
+}
@@ -0,0 +1,190 @@
+//! Latency of every repository method, for `GET /admin/metrics`.
+//!
+//! Each method starts a [`QueryTimer`] named after itself, such as
+//! `post_repo.find_listed_page`, right before it touches the database. When
+//! the timer drops, the time taken goes into that name's histogram, and a
+//! warning is logged if it reached the slow query threshold. Figures cover
+//! this process since it started.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::warn;
+use utoipa::ToSchema;
+
+/// Upper bounds of the histogram buckets in milliseconds; slower calls land
+/// in a final, open-ended bucket
+pub const BUCKETS_MS: [u64; 10] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500];
+
+const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(250);
+
+static SLOW_QUERY_THRESHOLD: OnceLock<Duration> = OnceLock::new();
+
+lazy_static::lazy_static! {
+    static ref HISTOGRAMS: Mutex<HashMap<&'static str, Histogram>> = Mutex::new(HashMap::new());
+}
+
+/// Sets the latency at which queries are logged as slow for the whole
+/// process; later calls are ignored
+pub fn set_slow_query_threshold(threshold: Duration) {
+    let _ = SLOW_QUERY_THRESHOLD.set(threshold);
+}
+
+pub fn slow_query_threshold() -> Duration {
+    SLOW_QUERY_THRESHOLD
+        .get()
+        .copied()
+        .unwrap_or(DEFAULT_SLOW_QUERY_THRESHOLD)
+}
+
+/// Times a repository method from creation until dropped
+pub struct QueryTimer {
+    name: &'static str,
+    started: Instant,
+}
+
+impl QueryTimer {
+    pub fn start(name: &'static str) -> Self {
+        Self {
+            name,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Drop for QueryTimer {
+    fn drop(&mut self) {
+        record(self.name, self.started.elapsed());
+    }
+}
+
+/// Adds one call of `name` taking `elapsed`
+pub fn record(name: &'static str, elapsed: Duration) {
+    let slow = elapsed >= slow_query_threshold();
+    if slow {
+        warn!(
+            query = name,
+            latency_ms = elapsed.as_millis() as u64,
+            "slow query"
+        );
+    }
+
+    let mut histograms = HISTOGRAMS.lock().unwrap();
+    let histogram = histograms.entry(name).or_default();
+    let millis = elapsed.as_secs_f64() * 1000.0;
+    let bucket = BUCKETS_MS
+        .iter()
+        .position(|&upper| millis <= upper as f64)
+        .unwrap_or(BUCKETS_MS.len());
+    histogram.counts[bucket] += 1;
+    histogram.total += elapsed;
+    histogram.max = histogram.max.max(elapsed);
+    if slow {
+        histogram.slow += 1;
+    }
+}
+
+/// Every query called so far, slowest p99 first
+pub fn query_stats() -> Vec<QueryStats> {
+    let histograms = HISTOGRAMS.lock().unwrap();
+    let mut stats: Vec<QueryStats> = histograms
+        .iter()
+        .map(|(name, histogram)| histogram.stats(name))
+        .collect();
+    stats.sort_by(|a, b| {
+        b.p99_ms
+            .total_cmp(&a.p99_ms)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    stats
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    counts: [u64; BUCKETS_MS.len() + 1],
+    total: Duration,
+    max: Duration,
+    slow: u64,
+}
+
+impl Histogram {
+    fn calls(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Upper bound of the bucket holding the `quantile`; the slowest call for
+    /// the open-ended bucket
+    fn percentile(&self, quantile: f64) -> f64 {
+        let rank = ((self.calls() as f64) * quantile).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return match BUCKETS_MS.get(bucket) {
+                    Some(&upper) => (upper as f64).min(millis(self.max)),
+                    None => millis(self.max),
+                };
+            }
+        }
+        millis(self.max)
+    }
+
+    fn stats(&self, name: &str) -> QueryStats {
+        let calls = self.calls();
+        QueryStats {
+            name: name.to_string(),
+            calls,
+            slow_calls: self.slow,
+            mean_ms: if calls == 0 {
+                0.0
+            } else {
+                millis(self.total) / calls as f64
+            },
+            max_ms: millis(self.max),
+            p50_ms: self.percentile(0.5),
+            p95_ms: self.percentile(0.95),
+            p99_ms: self.percentile(0.99),
+            buckets: self
+                .counts
+                .iter()
+                .enumerate()
+                .map(|(bucket, &count)| LatencyBucket {
+                    le_ms: BUCKETS_MS.get(bucket).copied(),
+                    count,
+                })
+                .collect(),
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Latency of one repository method since the process started
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct QueryStats {
+    /// Repository and method, e.g. `post_repo.find_listed_page`
+    pub name: String,
+    pub calls: u64,
+    /// Calls at or above `SLOW_QUERY_MS`
+    pub slow_calls: u64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    /// Percentiles are the upper bound of the bucket they fall in, capped at
+    /// the slowest call
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub buckets: Vec<LatencyBucket>,
+}
+
+/// Calls that took longer than the previous bucket's bound, up to `le_ms`
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct LatencyBucket {
+    /// `None` for the open-ended last bucket
+    pub le_ms: Option<u64>,
+    pub count: u64,
+}
@@ -0,0 +1,164 @@
+//! Batched loading of data related to a page of rows.
+//!
+//! List queries select only their own table; the loader then fetches what the
+//! rows refer to with one grouped query per kind of data, keyed by id, however
+//! many rows share it. New kinds of related data get a method here rather than
+//! another join in every list query.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::db::repositories::collaborator_repo::collaborator;
+use crate::helpers::chaos;
+use crate::model::model::{PostCollaborator, PostMention};
+
+/// The author fields post responses are built from
+#[derive(Debug, Clone)]
+pub struct AuthorRow {
+    pub id: Uuid,
+    pub public_id: String,
+    pub name: String,
+    pub username: Option<String>,
+    /// Only used to derive the Gravatar; never part of a response
+    pub email: String,
+    pub avatar_url: Option<String>,
+}
+
+pub struct Loader {
+    pool: PgPool,
+}
+
+impl Loader {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating Loader");
+        Self { pool }
+    }
+
+    /// Users by id, in one query; ids that match no user are left out
+    pub async fn authors(
+        &self,
+        ids: impl IntoIterator<Item = Uuid>,
+    ) -> Result<HashMap<Uuid, AuthorRow>> {
+        let ids = distinct(ids);
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("loader.authors");
+
+        debug!("Loading {} author(s)", ids.len());
+
+        let rows = sqlx::query_as!(
+            AuthorRow,
+            r#"
+                SELECT id, public_id, name, username, email, avatar_url
+                FROM users
+                WHERE id = ANY($1)
+            "#,
+            &ids,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.id, row)).collect())
+    }
+
+    /// Users mentioned by each post, keyed by the post's public id, in the
+    /// order they are first mentioned; posts without mentions are left out
+    pub async fn mentions(
+        &self,
+        post_ids: impl IntoIterator<Item = String>,
+    ) -> Result<HashMap<String, Vec<PostMention>>> {
+        let post_ids = distinct(post_ids);
+        if post_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("loader.mentions");
+
+        debug!("Loading mentions of {} post(s)", post_ids.len());
+
+        let rows = sqlx::query!(
+            r#"
+                SELECT p.public_id as post_id, u.public_id, u.username, u.name
+                FROM mentions m
+                JOIN posts p ON p.id = m.post_id
+                JOIN users u ON u.id = m.user_id
+                WHERE p.public_id = ANY($1)
+                ORDER BY m.position
+            "#,
+            &post_ids,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut mentions: HashMap<String, Vec<PostMention>> = HashMap::new();
+        for row in rows {
+            // Users who dropped their username since can no longer be linked
+            let Some(username) = row.username else {
+                continue;
+            };
+            mentions.entry(row.post_id).or_default().push(PostMention {
+                id: row.public_id,
+                username,
+                name: row.name,
+            });
+        }
+        Ok(mentions)
+    }
+
+    /// Co-authors of each post, keyed by the post's public id, in the order
+    /// they were invited; posts without any are left out
+    pub async fn collaborators(
+        &self,
+        post_ids: impl IntoIterator<Item = String>,
+    ) -> Result<HashMap<String, Vec<PostCollaborator>>> {
+        let post_ids = distinct(post_ids);
+        if post_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("loader.collaborators");
+
+        debug!("Loading collaborators of {} post(s)", post_ids.len());
+
+        let rows = sqlx::query(
+            r#"
+                SELECT p.public_id as post_id, u.public_id, u.username, u.name, c.role
+                FROM post_collaborators c
+                JOIN posts p ON p.id = c.post_id
+                JOIN users u ON u.id = c.user_id
+                WHERE p.public_id = ANY($1)
+                ORDER BY c.created_at
+            "#,
+        )
+        .bind(&post_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut collaborators: HashMap<String, Vec<PostCollaborator>> = HashMap::new();
+        for row in rows {
+            collaborators
+                .entry(row.get("post_id"))
+                .or_default()
+                .push(collaborator(&row));
+        }
+        Ok(collaborators)
+    }
+}
+
+fn distinct<T: Eq + Hash>(ids: impl IntoIterator<Item = T>) -> Vec<T> {
+    ids.into_iter()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect()
+}
@@ -1,2 +1,6 @@
 pub mod db;
+pub mod health;
+pub mod instrument;
+pub mod loader;
+pub mod pools;
 pub mod repositories;
@@ -0,0 +1,63 @@
+//! The primary pool and an optional read replica.
+//!
+//! Writes, and reads that must see them, always use the primary. Public reads
+//! go to the replica while the background monitor finds it up and fall back to
+//! the primary otherwise, so a replica outage only costs the primary some load.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+
+use crate::config::DatabaseConfig;
+
+#[derive(Debug, Clone)]
+pub struct DbPools {
+    pub primary: Arc<PgPool>,
+    pub replica: Option<Arc<PgPool>>,
+    /// Starts `false`, so reads stay on the primary until the replica answers
+    replica_up: Arc<AtomicBool>,
+}
+
+impl DbPools {
+    pub fn new(primary: Arc<PgPool>) -> Self {
+        Self {
+            primary,
+            replica: None,
+            replica_up: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn with_replica(mut self, replica: Arc<PgPool>) -> Self {
+        self.replica = Some(replica);
+        self
+    }
+
+    /// The replica while it is up, otherwise the primary
+    pub fn reader(&self) -> &PgPool {
+        match &self.replica {
+            Some(replica) if self.replica_up() => replica,
+            _ => &self.primary,
+        }
+    }
+
+    pub fn replica_up(&self) -> bool {
+        self.replica_up.load(Ordering::Relaxed)
+    }
+
+    /// Stores the latest replica probe; returns whether this is a change
+    pub fn set_replica_up(&self, up: bool) -> bool {
+        self.replica_up.swap(up, Ordering::Relaxed) != up
+    }
+}
+
+/// A pool for the replica at `url` that connects on first use, so a replica
+/// that is down at startup doesn't keep the server from starting
+pub fn connect_replica(url: &str, config: &DatabaseConfig) -> Result<PgPool> {
+    Ok(PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout)
+        .connect_lazy(url)?)
+}
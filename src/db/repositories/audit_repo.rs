@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::helpers::ids::{IdGenerator, TimeOrderedIds};
+use crate::model::model::AuditEntry;
+
+/// Append-only record of administrative actions
+pub struct AuditRepository {
+    pool: PgPool,
+    ids: Arc<dyn IdGenerator>,
+}
+
+impl AuditRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating AuditRepository");
+        Self {
+            pool,
+            ids: Arc::new(TimeOrderedIds),
+        }
+    }
+
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    pub async fn record(
+        &self,
+        actor_id: Uuid,
+        action: &str,
+        target: &str,
+        detail: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        info!("Audit: {} did {} on {}", actor_id, action, target);
+        self.insert(Some(actor_id), action, target, detail, now)
+            .await
+    }
+
+    /// Records something a background job did, with no actor
+    pub async fn record_system(
+        &self,
+        action: &str,
+        target: &str,
+        detail: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        info!("Audit: system did {} on {}", action, target);
+        self.insert(None, action, target, detail, now).await
+    }
+
+    async fn insert(
+        &self,
+        actor_id: Option<Uuid>,
+        action: &str,
+        target: &str,
+        detail: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("audit_repo.insert");
+
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (id, actor_id, action, target, detail, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(self.ids.generate())
+        .bind(actor_id)
+        .bind(action)
+        .bind(target)
+        .bind(detail)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_recent(&self, limit: i64) -> Result<Vec<AuditEntry>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("audit_repo.list_recent");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT l.id, u.public_id as actor_public_id, l.action, l.target, l.detail, l.created_at
+            FROM audit_log l
+            LEFT JOIN users u ON u.id = l.actor_id
+            ORDER BY l.created_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let entries = rows
+            .into_iter()
+            .map(|row| AuditEntry {
+                id: row.get("id"),
+                actor_id: row.get("actor_public_id"),
+                action: row.get("action"),
+                target: row.get("target"),
+                detail: row.get("detail"),
+                created_at: row.get("created_at"),
+            })
+            .collect();
+
+        Ok(entries)
+    }
+}
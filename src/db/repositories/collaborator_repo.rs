@@ -0,0 +1,127 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::model::model::{CollaboratorRole, PostCollaborator};
+
+/// Co-authors invited to posts by their authors
+pub struct CollaboratorRepository {
+    pool: PgPool,
+}
+
+impl CollaboratorRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating CollaboratorRepository");
+        Self { pool }
+    }
+
+    /// The user's role on the post; `None` when they weren't invited
+    pub async fn role(&self, post_id: Uuid, user_id: Uuid) -> Result<Option<CollaboratorRole>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("collaborator_repo.role");
+
+        let role: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT role FROM post_collaborators
+            WHERE post_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(post_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(role.as_deref().map(CollaboratorRole::from))
+    }
+
+    pub async fn list(&self, post_id: Uuid) -> Result<Vec<PostCollaborator>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("collaborator_repo.list");
+
+        debug!("Listing collaborators of post {}", post_id);
+        let rows = sqlx::query(
+            r#"
+            SELECT u.public_id, u.username, u.name, c.role
+            FROM post_collaborators c
+            JOIN users u ON c.user_id = u.id
+            WHERE c.post_id = $1
+            ORDER BY c.created_at
+            "#,
+        )
+        .bind(post_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| collaborator(&row)).collect())
+    }
+
+    /// Invites the user, or changes their role if they were already invited;
+    /// returns whether they are new to the post
+    pub async fn upsert(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        role: CollaboratorRole,
+        invited_by: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("collaborator_repo.upsert");
+
+        info!(
+            "Setting role {:?} for user {} on post {}",
+            role, user_id, post_id
+        );
+        // `xmax` is only zero on a row this statement inserted
+        let inserted: bool = sqlx::query_scalar(
+            r#"
+            INSERT INTO post_collaborators (post_id, user_id, role, invited_by, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (post_id, user_id) DO UPDATE SET role = EXCLUDED.role
+            RETURNING (xmax = 0)
+            "#,
+        )
+        .bind(post_id)
+        .bind(user_id)
+        .bind(String::from(role))
+        .bind(invited_by)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(inserted)
+    }
+
+    pub async fn remove(&self, post_id: Uuid, user_id: Uuid) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("collaborator_repo.remove");
+
+        info!("Removing user {} from post {}", user_id, post_id);
+        let result = sqlx::query(
+            r#"
+            DELETE FROM post_collaborators
+            WHERE post_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(post_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Builds a collaborator from a row with `public_id`, `username`, `name` and `role`
+pub(crate) fn collaborator(row: &sqlx::postgres::PgRow) -> PostCollaborator {
+    PostCollaborator {
+        id: row.get("public_id"),
+        username: row.get("username"),
+        name: row.get("name"),
+        role: CollaboratorRole::from(row.get::<&str, _>("role")),
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::model::model::{DomainRuleKind, EmailDomainRule};
+
+pub struct EmailDomainRepository {
+    pool: PgPool,
+}
+
+impl EmailDomainRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating EmailDomainRepository");
+        Self { pool }
+    }
+
+    /// Rules added at runtime through the admin API
+    pub async fn list_rules(&self) -> Result<Vec<EmailDomainRule>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("email_domain_repo.list_rules");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT domain, kind
+            FROM email_domain_rules
+            ORDER BY domain
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let rules = rows
+            .into_iter()
+            .map(|row| EmailDomainRule {
+                domain: row.get("domain"),
+                kind: DomainRuleKind::from(row.get::<&str, _>("kind")),
+                from_config: false,
+            })
+            .collect();
+
+        Ok(rules)
+    }
+
+    /// Adds the rule, or switches an existing rule for the domain to `kind`
+    pub async fn upsert_rule(&self, domain: &str, kind: DomainRuleKind) -> Result<EmailDomainRule> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("email_domain_repo.upsert_rule");
+
+        info!("Setting email domain rule {} -> {:?}", domain, kind);
+
+        sqlx::query(
+            r#"
+            INSERT INTO email_domain_rules (domain, kind)
+            VALUES ($1, $2)
+            ON CONFLICT (domain) DO UPDATE SET kind = EXCLUDED.kind
+            "#,
+        )
+        .bind(domain)
+        .bind(String::from(kind))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(EmailDomainRule {
+            domain: domain.to_string(),
+            kind,
+            from_config: false,
+        })
+    }
+
+    pub async fn remove_rule(&self, domain: &str) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("email_domain_repo.remove_rule");
+
+        let result = sqlx::query(
+            r#"
+            DELETE FROM email_domain_rules
+            WHERE domain = $1
+            "#,
+        )
+        .bind(domain)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,348 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::model::model::{
+    AuditEntry, CollaboratorRole, ExportedCollaboration, ExportedFollower, ExportedLinkToken,
+    ExportedMembership, ExportedReport, ExportedSession, ExportedShareLink, Notification,
+    NotificationKind, OrgRole, ReportAction, ReportReason, ReportStatus, ShareLink,
+};
+
+/// Columns pointing at a user that `GET /auth/profile/export` reads, as `table.column`.
+/// Posts come from `PostRepository::find_by_author`, the rest from this repository.
+pub const EXPORTED_USER_COLUMNS: [&str; 11] = [
+    "posts.author_id",
+    "sessions.user_id",
+    "notifications.user_id",
+    "memberships.user_id",
+    "post_collaborators.user_id",
+    "mentions.user_id",
+    "share_links.created_by",
+    "link_tokens.user_id",
+    "ap_followers.user_id",
+    "reports.reporter_id",
+    "audit_log.actor_id",
+];
+
+/// Columns pointing at a user that are left out of the export, and why
+pub const WITHHELD_USER_COLUMNS: [(&str, &str); 4] = [
+    (
+        "post_collaborators.invited_by",
+        "the invitation is exported to the invited collaborator",
+    ),
+    (
+        "reports.author_id",
+        "reports of the user's posts would identify their reporters",
+    ),
+    (
+        "reports.resolved_by",
+        "resolving a report is also recorded in the exported audit log",
+    ),
+    (
+        "feature_flags.updated_by",
+        "deployment settings, not data about the user",
+    ),
+];
+
+/// Reads everything stored about one user for an account export
+pub struct ExportRepository {
+    pool: PgPool,
+}
+
+impl ExportRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating ExportRepository");
+        Self { pool }
+    }
+
+    /// Every session, newest first, including revoked and expired ones
+    pub async fn sessions(&self, user_id: Uuid) -> Result<Vec<ExportedSession>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.sessions");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, device, ip, user_agent, network, country, created_at, last_seen_at, expires_at, revoked_at
+            FROM sessions
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExportedSession {
+                id: row.get("id"),
+                device: row.get("device"),
+                ip: row.get("ip"),
+                user_agent: row.get("user_agent"),
+                network: row.get("network"),
+                country: row.get("country"),
+                created_at: row.get("created_at"),
+                last_seen_at: row.get("last_seen_at"),
+                expires_at: row.get("expires_at"),
+                revoked_at: row.get("revoked_at"),
+            })
+            .collect())
+    }
+
+    pub async fn notifications(&self, user_id: Uuid) -> Result<Vec<Notification>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.notifications");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, kind, message, target, read_at, created_at
+            FROM notifications
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Notification {
+                id: row.get("id"),
+                kind: NotificationKind::from(row.get::<&str, _>("kind")),
+                message: row.get("message"),
+                target: row.get("target"),
+                read_at: row.get("read_at"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    pub async fn memberships(&self, user_id: Uuid) -> Result<Vec<ExportedMembership>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.memberships");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT o.public_id, o.name, m.role, m.created_at
+            FROM memberships m
+            JOIN organizations o ON o.id = m.org_id
+            WHERE m.user_id = $1
+            ORDER BY m.created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExportedMembership {
+                org_id: row.get("public_id"),
+                org_name: row.get("name"),
+                role: OrgRole::from(row.get::<&str, _>("role")),
+                joined_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    pub async fn collaborations(&self, user_id: Uuid) -> Result<Vec<ExportedCollaboration>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.collaborations");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT p.public_id, c.role, c.created_at
+            FROM post_collaborators c
+            JOIN posts p ON p.id = c.post_id
+            WHERE c.user_id = $1
+            ORDER BY c.created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExportedCollaboration {
+                post_id: row.get("public_id"),
+                role: CollaboratorRole::from(row.get::<&str, _>("role")),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    /// Public ids of the posts mentioning the user
+    pub async fn mentioned_in(&self, user_id: Uuid) -> Result<Vec<String>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.mentioned_in");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT p.public_id
+            FROM mentions m
+            JOIN posts p ON p.id = m.post_id
+            WHERE m.user_id = $1
+            ORDER BY p.created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.get("public_id")).collect())
+    }
+
+    pub async fn share_links(&self, user_id: Uuid) -> Result<Vec<ExportedShareLink>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.share_links");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT p.public_id, s.id, s.created_at, s.expires_at, s.max_uses, s.uses
+            FROM share_links s
+            JOIN posts p ON p.id = s.post_id
+            WHERE s.created_by = $1
+            ORDER BY s.created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExportedShareLink {
+                post_id: row.get("public_id"),
+                link: ShareLink {
+                    id: row.get("id"),
+                    created_at: row.get("created_at"),
+                    expires_at: row.get("expires_at"),
+                    max_uses: row.get("max_uses"),
+                    uses: row.get("uses"),
+                },
+            })
+            .collect())
+    }
+
+    /// Outstanding emailed links; only their hashes are stored, and those are left out
+    pub async fn link_tokens(&self, user_id: Uuid) -> Result<Vec<ExportedLinkToken>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.link_tokens");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT purpose, subject, created_at, expires_at
+            FROM link_tokens
+            WHERE user_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExportedLinkToken {
+                purpose: row.get("purpose"),
+                subject: row.get("subject"),
+                created_at: row.get("created_at"),
+                expires_at: row.get("expires_at"),
+            })
+            .collect())
+    }
+
+    pub async fn followers(&self, user_id: Uuid) -> Result<Vec<ExportedFollower>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.followers");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT actor, created_at
+            FROM ap_followers
+            WHERE user_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExportedFollower {
+                actor: row.get("actor"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    /// Reports the user filed
+    pub async fn reports(&self, user_id: Uuid) -> Result<Vec<ExportedReport>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.reports");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, target_kind, target_public_id, reason, details, status, action, created_at, resolved_at
+            FROM reports
+            WHERE reporter_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExportedReport {
+                id: row.get("id"),
+                target_kind: row.get("target_kind"),
+                target_id: row.get("target_public_id"),
+                reason: ReportReason::from(row.get::<&str, _>("reason")),
+                details: row.get("details"),
+                status: ReportStatus::from(row.get::<&str, _>("status")),
+                action: row.get::<Option<&str>, _>("action").map(ReportAction::from),
+                created_at: row.get("created_at"),
+                resolved_at: row.get("resolved_at"),
+            })
+            .collect())
+    }
+
+    /// Administrative actions the user took
+    pub async fn audit_log(&self, user_id: Uuid) -> Result<Vec<AuditEntry>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("export_repo.audit_log");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT l.id, u.public_id as actor_public_id, l.action, l.target, l.detail, l.created_at
+            FROM audit_log l
+            JOIN users u ON u.id = l.actor_id
+            WHERE l.actor_id = $1
+            ORDER BY l.created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| AuditEntry {
+                id: row.get("id"),
+                actor_id: row.get("actor_public_id"),
+                action: row.get("action"),
+                target: row.get("target"),
+                detail: row.get("detail"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+}
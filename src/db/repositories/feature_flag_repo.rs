@@ -0,0 +1,69 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::model::model::{FeatureFlag, FeatureFlagSet};
+
+pub struct FeatureFlagRepository {
+    pool: PgPool,
+}
+
+impl FeatureFlagRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating FeatureFlagRepository");
+        Self { pool }
+    }
+
+    /// Stored flags over the defaults; rows for flags this build doesn't
+    /// know are ignored
+    pub async fn load(&self) -> Result<FeatureFlagSet> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("feature_flag_repo.load");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT name, enabled
+            FROM feature_flags
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut flags = FeatureFlagSet::default();
+        for row in rows {
+            if let Some(flag) = FeatureFlag::from_name(row.get("name")) {
+                flags.set(flag, row.get("enabled"));
+            }
+        }
+
+        Ok(flags)
+    }
+
+    pub async fn set(&self, flag: FeatureFlag, enabled: bool, updated_by: Uuid) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("feature_flag_repo.set");
+
+        info!("Setting feature flag {} -> {}", flag.name(), enabled);
+
+        sqlx::query(
+            r#"
+            INSERT INTO feature_flags (name, enabled, updated_by, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (name) DO UPDATE
+            SET enabled = EXCLUDED.enabled,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(flag.name())
+        .bind(enabled)
+        .bind(updated_by)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+
+/// Remote ActivityPub actors following local authors
+pub struct FollowerRepository {
+    pool: PgPool,
+}
+
+impl FollowerRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating FollowerRepository");
+        Self { pool }
+    }
+
+    /// Records `actor` as a follower, or updates the inbox of one already recorded
+    pub async fn add_follower(
+        &self,
+        user_id: Uuid,
+        actor: &str,
+        inbox: &str,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("follower_repo.add_follower");
+
+        info!("Recording follower {} for user {}", actor, user_id);
+
+        sqlx::query(
+            r#"
+            INSERT INTO ap_followers (user_id, actor, inbox, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, actor) DO UPDATE SET inbox = EXCLUDED.inbox
+            "#,
+        )
+        .bind(user_id)
+        .bind(actor)
+        .bind(inbox)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_follower(&self, user_id: Uuid, actor: &str) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("follower_repo.remove_follower");
+
+        info!("Removing follower {} from user {}", actor, user_id);
+
+        let result = sqlx::query(
+            r#"
+            DELETE FROM ap_followers
+            WHERE user_id = $1 AND actor = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(actor)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn count_followers(&self, user_id: Uuid) -> Result<i64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("follower_repo.count_followers");
+
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS count
+            FROM ap_followers
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("count"))
+    }
+
+    /// Distinct inboxes of the user's followers; followers sharing an inbox get one delivery
+    pub async fn inboxes(&self, user_id: Uuid) -> Result<Vec<String>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("follower_repo.inboxes");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT inbox
+            FROM ap_followers
+            WHERE user_id = $1 AND inbox IS NOT NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.get("inbox")).collect())
+    }
+}
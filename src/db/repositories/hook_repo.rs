@@ -0,0 +1,151 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row, postgres::PgRow};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::model::model::{DeliveryStatus, HookDelivery};
+
+/// Queue and delivery log of publish hook notifications
+pub struct HookRepository {
+    pool: PgPool,
+}
+
+fn delivery_from_row(row: PgRow) -> HookDelivery {
+    HookDelivery {
+        id: row.get("id"),
+        event: row.get("event"),
+        post_id: row.get("post_id"),
+        method: row.get("method"),
+        url: row.get("url"),
+        signed_by: row.get("signed_by"),
+        body: row.get("body"),
+        status: DeliveryStatus::from(row.get::<&str, _>("status")),
+        attempts: row.get("attempts"),
+        last_status: row.get("last_status"),
+        last_error: row.get("last_error"),
+        next_attempt_at: row.get("next_attempt_at"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+impl HookRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating HookRepository");
+        Self { pool }
+    }
+
+    pub async fn enqueue(&self, delivery: &HookDelivery) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("hook_repo.enqueue");
+
+        info!("Queueing {} hook to {}", delivery.event, delivery.url);
+
+        sqlx::query(
+            r#"
+            INSERT INTO hook_deliveries (id, event, post_id, method, url, signed_by, body, status, attempts, next_attempt_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+        )
+        .bind(delivery.id)
+        .bind(&delivery.event)
+        .bind(&delivery.post_id)
+        .bind(&delivery.method)
+        .bind(&delivery.url)
+        .bind(&delivery.signed_by)
+        .bind(&delivery.body)
+        .bind(String::from(delivery.status))
+        .bind(delivery.attempts)
+        .bind(delivery.next_attempt_at)
+        .bind(delivery.created_at)
+        .bind(delivery.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Pending deliveries that are due, claimed so concurrent workers skip them
+    pub async fn claim_due(&self, now: DateTime<Utc>, limit: i64) -> Result<Vec<HookDelivery>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("hook_repo.claim_due");
+
+        // Pushing next_attempt_at out keeps a crashed worker's claim from
+        // blocking the delivery forever
+        let rows = sqlx::query(
+            r#"
+            UPDATE hook_deliveries
+            SET next_attempt_at = $1 + INTERVAL '5 minutes'
+            WHERE id IN (
+                SELECT id FROM hook_deliveries
+                WHERE status = 'PENDING' AND next_attempt_at <= $1
+                ORDER BY next_attempt_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, event, post_id, method, url, signed_by, body, status, attempts, last_status, last_error, next_attempt_at, created_at, updated_at
+            "#,
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(delivery_from_row).collect())
+    }
+
+    /// Records the outcome of an attempt
+    pub async fn record_attempt(
+        &self,
+        id: Uuid,
+        status: DeliveryStatus,
+        last_status: Option<i32>,
+        last_error: Option<&str>,
+        next_attempt_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("hook_repo.record_attempt");
+
+        sqlx::query(
+            r#"
+            UPDATE hook_deliveries
+            SET status = $2, attempts = attempts + 1, last_status = $3, last_error = $4,
+                next_attempt_at = $5, updated_at = $6
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(String::from(status))
+        .bind(last_status)
+        .bind(last_error)
+        .bind(next_attempt_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_recent(&self, limit: i64) -> Result<Vec<HookDelivery>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("hook_repo.list_recent");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, event, post_id, method, url, signed_by, body, status, attempts, last_status, last_error, next_attempt_at, created_at, updated_at
+            FROM hook_deliveries
+            ORDER BY created_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(delivery_from_row).collect())
+    }
+}
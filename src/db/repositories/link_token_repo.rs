@@ -0,0 +1,94 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::helpers::link_tokens::LinkGrant;
+
+/// Hashes of the single-use tokens in emailed links
+pub struct LinkTokenRepository {
+    pool: PgPool,
+}
+
+impl LinkTokenRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating LinkTokenRepository");
+        Self { pool }
+    }
+
+    pub async fn insert(
+        &self,
+        token_hash: &str,
+        user_id: Uuid,
+        purpose: &str,
+        subject: Option<&str>,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("link_token_repo.insert");
+
+        debug!("Storing {} link token for user {}", purpose, user_id);
+
+        sqlx::query(
+            r#"
+            INSERT INTO link_tokens (token_hash, user_id, purpose, subject, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(token_hash)
+        .bind(user_id)
+        .bind(purpose)
+        .bind(subject)
+        .bind(created_at)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the token and returns its user and subject, if it exists for
+    /// `purpose` and hasn't expired; a second call for the same token finds
+    /// nothing
+    pub async fn take(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<LinkGrant>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("link_token_repo.take");
+
+        let grant = sqlx::query_as::<_, (Uuid, Option<String>)>(
+            r#"
+            DELETE FROM link_tokens
+            WHERE token_hash = $1 AND purpose = $2 AND expires_at > $3
+            RETURNING user_id, subject
+            "#,
+        )
+        .bind(token_hash)
+        .bind(purpose)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(grant.map(|(user_id, subject)| LinkGrant { user_id, subject }))
+    }
+
+    /// Removes tokens that expired unused
+    pub async fn delete_expired(&self, now: DateTime<Utc>) -> Result<u64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("link_token_repo.delete_expired");
+
+        let result = sqlx::query("DELETE FROM link_tokens WHERE expires_at <= $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::helpers::markdown::MarkdownImage;
+use crate::model::model::{MediaMissingAlt, PostMedia};
+
+/// Images referenced by posts, kept in step with their Markdown
+pub struct MediaRepository {
+    pool: PgPool,
+}
+
+impl MediaRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating MediaRepository");
+        Self { pool }
+    }
+
+    /// Replaces the media records of a post with `images`
+    pub async fn replace_for_post(&self, post_id: Uuid, images: &[MarkdownImage]) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("media_repo.replace_for_post");
+
+        debug!("Recording {} image(s) for post {}", images.len(), post_id);
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM post_media
+            WHERE post_id = $1
+            "#,
+        )
+        .bind(post_id)
+        .execute(&mut *tx)
+        .await?;
+
+        for (position, image) in images.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO post_media (post_id, position, url, alt_text)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(post_id)
+            .bind(position as i32)
+            .bind(&image.url)
+            .bind(&image.alt)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Images without alt text in the author's posts, newest post first
+    pub async fn find_missing_alt(&self, author_id: Uuid) -> Result<Vec<MediaMissingAlt>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("media_repo.find_missing_alt");
+
+        info!("Listing images without alt text for author {}", author_id);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT p.public_id, p.title, m.position, m.url
+            FROM post_media m
+            JOIN posts p ON p.id = m.post_id
+            WHERE p.author_id = $1 AND m.alt_text IS NULL
+            ORDER BY p.created_at DESC, m.position
+            "#,
+        )
+        .bind(author_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let missing = rows
+            .into_iter()
+            .map(|row| MediaMissingAlt {
+                post_id: row.get("public_id"),
+                post_title: row.get("title"),
+                position: row.get("position"),
+                url: row.get("url"),
+            })
+            .collect();
+
+        Ok(missing)
+    }
+
+    /// Images of a post in document order
+    pub async fn find_for_post(&self, post_id: Uuid) -> Result<Vec<PostMedia>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("media_repo.find_for_post");
+
+        debug!("Listing images of post {}", post_id);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT url, alt_text
+            FROM post_media
+            WHERE post_id = $1
+            ORDER BY position
+            "#,
+        )
+        .bind(post_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PostMedia {
+                url: row.get("url"),
+                alt_text: row.get("alt_text"),
+            })
+            .collect())
+    }
+}
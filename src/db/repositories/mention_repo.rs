@@ -0,0 +1,66 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+
+/// Users mentioned by posts, kept in step with their Markdown
+pub struct MentionRepository {
+    pool: PgPool,
+}
+
+impl MentionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating MentionRepository");
+        Self { pool }
+    }
+
+    /// Replaces the mentions of a post with the users named by `usernames`,
+    /// skipping names nobody has; returns the users the post didn't mention
+    /// before
+    pub async fn replace_for_post(&self, post_id: Uuid, usernames: &[String]) -> Result<Vec<Uuid>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("mention_repo.replace_for_post");
+
+        debug!(
+            "Recording {} mention(s) for post {}",
+            usernames.len(),
+            post_id
+        );
+
+        let mut tx = self.pool.begin().await?;
+
+        let previous: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            DELETE FROM mentions
+            WHERE post_id = $1
+            RETURNING user_id
+            "#,
+        )
+        .bind(post_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mentioned: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO mentions (post_id, user_id, position)
+            SELECT $1, u.id, (names.position - 1)::INTEGER
+            FROM UNNEST($2::TEXT[]) WITH ORDINALITY AS names (username, position)
+            JOIN users u ON u.username = names.username
+            RETURNING user_id
+            "#,
+        )
+        .bind(post_id)
+        .bind(usernames)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(mentioned
+            .into_iter()
+            .filter(|user_id| !previous.contains(user_id))
+            .collect())
+    }
+}
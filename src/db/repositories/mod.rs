@@ -1,2 +1,19 @@
+pub mod audit_repo;
+pub mod collaborator_repo;
+pub mod email_domain_repo;
+pub mod export_repo;
+pub mod feature_flag_repo;
+#[cfg(feature = "activitypub")]
+pub mod follower_repo;
+pub mod hook_repo;
+pub mod link_token_repo;
+pub mod media_repo;
+pub mod mention_repo;
+pub mod notification_repo;
+pub mod org_repo;
 pub mod post_repo;
+pub mod report_repo;
+pub mod session_repo;
+pub mod share_link_repo;
+pub mod stats_repo;
 pub mod user_repo;
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row, postgres::PgRow};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::helpers::ids::{IdGenerator, TimeOrderedIds};
+use crate::model::model::{Notification, NotificationKind, NotificationSummary};
+
+/// Users' inboxes of events about their account and content
+pub struct NotificationRepository {
+    pool: PgPool,
+    ids: Arc<dyn IdGenerator>,
+}
+
+fn notification_from_row(row: PgRow) -> Notification {
+    Notification {
+        id: row.get("id"),
+        kind: NotificationKind::from(row.get::<&str, _>("kind")),
+        message: row.get("message"),
+        target: row.get("target"),
+        read_at: row.get("read_at"),
+        created_at: row.get("created_at"),
+    }
+}
+
+impl NotificationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating NotificationRepository");
+        Self {
+            pool,
+            ids: Arc::new(TimeOrderedIds),
+        }
+    }
+
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        kind: NotificationKind,
+        message: &str,
+        target: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<Notification> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("notification_repo.create");
+
+        info!("Notifying user {} of {:?}", user_id, kind);
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO notifications (id, user_id, kind, message, target, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, kind, message, target, read_at, created_at
+            "#,
+        )
+        .bind(self.ids.generate())
+        .bind(user_id)
+        .bind(String::from(kind))
+        .bind(message)
+        .bind(target)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(notification_from_row(row))
+    }
+
+    /// Newest first; `before` pages back from the oldest notification seen
+    pub async fn list(
+        &self,
+        user_id: Uuid,
+        unread_only: bool,
+        before: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<Notification>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("notification_repo.list");
+
+        debug!("Fetching notifications of user {}", user_id);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, kind, message, target, read_at, created_at
+            FROM notifications
+            WHERE user_id = $1
+                AND (NOT $2 OR read_at IS NULL)
+                AND ($3::timestamptz IS NULL OR created_at < $3)
+            ORDER BY created_at DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(user_id)
+        .bind(unread_only)
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(notification_from_row).collect())
+    }
+
+    /// Marks one of the user's notifications read; `false` if they have no such
+    /// notification. Reading it again keeps the first read time.
+    pub async fn mark_read(&self, user_id: Uuid, id: Uuid, now: DateTime<Utc>) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("notification_repo.mark_read");
+
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications
+            SET read_at = COALESCE(read_at, $1)
+            WHERE id = $2 AND user_id = $3
+            "#,
+        )
+        .bind(now)
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns how many notifications were unread
+    pub async fn mark_all_read(&self, user_id: Uuid, now: DateTime<Utc>) -> Result<u64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("notification_repo.mark_all_read");
+
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications
+            SET read_at = $1
+            WHERE user_id = $2 AND read_at IS NULL
+            "#,
+        )
+        .bind(now)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        debug!(
+            "Marked {} notification(s) of user {} read",
+            result.rows_affected(),
+            user_id
+        );
+        Ok(result.rows_affected())
+    }
+
+    pub async fn summary(&self, user_id: Uuid) -> Result<NotificationSummary> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("notification_repo.summary");
+
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE read_at IS NULL) as unread, MAX(created_at) as latest_at
+            FROM notifications
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(NotificationSummary {
+            unread: row.get("unread"),
+            latest_at: row.get("latest_at"),
+        })
+    }
+}
@@ -0,0 +1,301 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::helpers::ids::{IdGenerator, TimeOrderedIds, new_public_id};
+use crate::model::model::{MemberResponse, OrgRole, Organization};
+
+pub struct OrgRepository {
+    pool: PgPool,
+    ids: Arc<dyn IdGenerator>,
+}
+
+impl OrgRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating OrgRepository");
+        Self {
+            pool,
+            ids: Arc::new(TimeOrderedIds),
+        }
+    }
+
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Creates the organization with `owner_id` as its first owner
+    pub async fn create_org(
+        &self,
+        name: String,
+        owner_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Organization> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("org_repo.create_org");
+
+        let org = Organization {
+            id: self.ids.generate(),
+            public_id: new_public_id(),
+            name,
+            created_at: now,
+            updated_at: now,
+        };
+
+        info!("Creating organization: {}", org.name);
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO organizations (id, public_id, name, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(org.id)
+        .bind(&org.public_id)
+        .bind(&org.name)
+        .bind(org.created_at)
+        .bind(org.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO memberships (org_id, user_id, role, created_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(org.id)
+        .bind(owner_id)
+        .bind(String::from(OrgRole::OWNER))
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        debug!("Organization created with ID: {}", org.id);
+        Ok(org)
+    }
+
+    pub async fn find_by_public_id(&self, public_id: &str) -> Result<Option<Organization>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("org_repo.find_by_public_id");
+
+        debug!("Finding organization by public ID: {}", public_id);
+        let row = sqlx::query(
+            r#"
+            SELECT id, public_id, name, created_at, updated_at
+            FROM organizations
+            WHERE public_id = $1
+            "#,
+        )
+        .bind(public_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| Organization {
+            id: row.get("id"),
+            public_id: row.get("public_id"),
+            name: row.get("name"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }))
+    }
+
+    pub async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<(Organization, OrgRole)>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("org_repo.list_for_user");
+
+        debug!("Listing organizations for user ID: {}", user_id);
+        let rows = sqlx::query(
+            r#"
+            SELECT o.id, o.public_id, o.name, o.created_at, o.updated_at, m.role
+            FROM organizations o
+            JOIN memberships m ON m.org_id = o.id
+            WHERE m.user_id = $1
+            ORDER BY o.created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let orgs = rows
+            .into_iter()
+            .map(|row| {
+                let org = Organization {
+                    id: row.get("id"),
+                    public_id: row.get("public_id"),
+                    name: row.get("name"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                };
+                (org, OrgRole::from(row.get::<&str, _>("role")))
+            })
+            .collect();
+
+        Ok(orgs)
+    }
+
+    pub async fn update_org(
+        &self,
+        id: Uuid,
+        name: String,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Organization>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("org_repo.update_org");
+
+        info!("Updating organization ID: {}", id);
+        let row = sqlx::query(
+            r#"
+            UPDATE organizations
+            SET name = $1, updated_at = $2
+            WHERE id = $3
+            RETURNING id, public_id, name, created_at, updated_at
+            "#,
+        )
+        .bind(&name)
+        .bind(now)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| Organization {
+            id: row.get("id"),
+            public_id: row.get("public_id"),
+            name: row.get("name"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }))
+    }
+
+    /// Deletes the organization; memberships and org posts cascade
+    pub async fn delete_org(&self, id: Uuid) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("org_repo.delete_org");
+
+        info!("Deleting organization ID: {}", id);
+        let result = sqlx::query(
+            r#"
+            DELETE FROM organizations
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn membership_role(&self, org_id: Uuid, user_id: Uuid) -> Result<Option<OrgRole>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("org_repo.membership_role");
+
+        debug!("Looking up role of user {} in org {}", user_id, org_id);
+        let row = sqlx::query(
+            r#"
+            SELECT role
+            FROM memberships
+            WHERE org_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(org_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| OrgRole::from(row.get::<&str, _>("role"))))
+    }
+
+    pub async fn list_members(&self, org_id: Uuid) -> Result<Vec<MemberResponse>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("org_repo.list_members");
+
+        debug!("Listing members of org {}", org_id);
+        let rows = sqlx::query(
+            r#"
+            SELECT u.public_id, u.name, m.role, m.created_at
+            FROM memberships m
+            JOIN users u ON m.user_id = u.id
+            WHERE m.org_id = $1
+            ORDER BY m.created_at
+            "#,
+        )
+        .bind(org_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let members = rows
+            .into_iter()
+            .map(|row| MemberResponse {
+                user_id: row.get("public_id"),
+                name: row.get("name"),
+                role: OrgRole::from(row.get::<&str, _>("role")),
+                joined_at: row.get("created_at"),
+            })
+            .collect();
+
+        Ok(members)
+    }
+
+    /// Adds the user, or changes their role if they already belong to the organization
+    pub async fn upsert_member(
+        &self,
+        org_id: Uuid,
+        user_id: Uuid,
+        role: OrgRole,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("org_repo.upsert_member");
+
+        info!(
+            "Setting role {:?} for user {} in org {}",
+            role, user_id, org_id
+        );
+        sqlx::query(
+            r#"
+            INSERT INTO memberships (org_id, user_id, role, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (org_id, user_id) DO UPDATE SET role = EXCLUDED.role
+            "#,
+        )
+        .bind(org_id)
+        .bind(user_id)
+        .bind(String::from(role))
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_member(&self, org_id: Uuid, user_id: Uuid) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("org_repo.remove_member");
+
+        info!("Removing user {} from org {}", user_id, org_id);
+        let result = sqlx::query(
+            r#"
+            DELETE FROM memberships
+            WHERE org_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(org_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -1,180 +1,611 @@
-use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+use sqlx::{PgPool, Postgres, QueryBuilder};
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 use uuid::Uuid;
 
+use crate::cache::{Cache, LATEST_POSTS_KEY, TRENDING_POSTS_KEY, post_key};
+use crate::config::SlugConfig;
+use crate::db::instrument::QueryTimer;
+use crate::db::loader::{AuthorRow, Loader};
+use crate::db::pools::DbPools;
+use crate::helpers::avatar::avatar_url;
+use crate::helpers::chaos;
+use crate::helpers::ids::{IdGenerator, TimeOrderedIds, new_public_id};
+use crate::helpers::markdown::{cached_or_render, render_markdown, stored_or_summarize, summarize};
+use crate::helpers::slugs::{is_reserved, slugify, with_suffix};
+use crate::helpers::views::MAX_TRENDING_LIMIT;
 use crate::model::model::{
-    CreatePostRequest, Post, PostResponse, Role, UpdatePostRequest, UserResponse,
+    CreatePostRequest, PatchPostRequest, Post, PostCollaborator, PostMention, PostResponse,
+    PublicAuthor, Visibility,
 };
 
+/// A post as selected by the feed queries, before its author is loaded
+struct PostRow {
+    public_id: String,
+    slug: Option<String>,
+    title: String,
+    content: String,
+    content_html: Option<String>,
+    license: Option<String>,
+    word_count: Option<i32>,
+    reading_time_minutes: Option<i32>,
+    excerpt: Option<String>,
+    author_id: Uuid,
+    view_count: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    archived_at: Option<DateTime<Utc>>,
+    visibility: Visibility,
+}
+
+/// A post together with its author, as the cache keeps it
+#[derive(Clone, Serialize, Deserialize)]
+struct PostWithAuthorRow {
+    public_id: String,
+    slug: Option<String>,
+    title: String,
+    content: String,
+    content_html: Option<String>,
+    license: Option<String>,
+    /// Missing from rows cached before posts were summarized
+    #[serde(default)]
+    word_count: Option<i32>,
+    #[serde(default)]
+    reading_time_minutes: Option<i32>,
+    #[serde(default)]
+    excerpt: Option<String>,
+    author_id: Uuid,
+    view_count: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    /// Missing from rows cached before posts could be archived
+    #[serde(default)]
+    archived_at: Option<DateTime<Utc>>,
+    /// Missing from rows cached before posts had a visibility
+    #[serde(default)]
+    visibility: Visibility,
+    author_public_id: String,
+    author_name: String,
+    /// Missing from rows cached before usernames existed
+    #[serde(default)]
+    author_username: Option<String>,
+    /// Only used to derive the Gravatar; never part of the response
+    author_email: String,
+    author_avatar_url: Option<String>,
+    /// Missing from rows cached before mentions were recorded
+    #[serde(default)]
+    mentions: Vec<PostMention>,
+    /// Missing from rows cached before posts had collaborators
+    #[serde(default)]
+    collaborators: Vec<PostCollaborator>,
+}
+
+impl PostWithAuthorRow {
+    fn new(
+        post: PostRow,
+        author: &AuthorRow,
+        mentions: Vec<PostMention>,
+        collaborators: Vec<PostCollaborator>,
+    ) -> Self {
+        PostWithAuthorRow {
+            public_id: post.public_id,
+            slug: post.slug,
+            title: post.title,
+            content: post.content,
+            content_html: post.content_html,
+            license: post.license,
+            word_count: post.word_count,
+            reading_time_minutes: post.reading_time_minutes,
+            excerpt: post.excerpt,
+            author_id: post.author_id,
+            view_count: post.view_count,
+            created_at: post.created_at,
+            updated_at: post.updated_at,
+            archived_at: post.archived_at,
+            visibility: post.visibility,
+            author_public_id: author.public_id.clone(),
+            author_name: author.name.clone(),
+            author_username: author.username.clone(),
+            author_email: author.email.clone(),
+            author_avatar_url: author.avatar_url.clone(),
+            mentions,
+            collaborators,
+        }
+    }
+}
+
+/// What the cache keeps for `GET /posts/{id}`; the internal id is needed to count views
+#[derive(Serialize, Deserialize)]
+struct CachedPost {
+    id: Uuid,
+    row: PostWithAuthorRow,
+}
+
+impl From<PostWithAuthorRow> for PostResponse {
+    fn from(row: PostWithAuthorRow) -> Self {
+        let summary = stored_or_summarize(
+            row.word_count,
+            row.reading_time_minutes,
+            row.excerpt,
+            &row.content,
+        );
+
+        PostResponse {
+            id: row.public_id,
+            slug: row.slug,
+            title: row.title,
+            content_html: Some(cached_or_render(row.content_html, &row.content)),
+            content: row.content,
+            license: row.license,
+            word_count: summary.word_count,
+            reading_time_minutes: summary.reading_time_minutes,
+            excerpt: summary.excerpt,
+            author: PublicAuthor {
+                id: row.author_public_id,
+                name: row.author_name,
+                username: row.author_username,
+                avatar_url: avatar_url(row.author_avatar_url.as_deref(), &row.author_email),
+            },
+            view_count: row.view_count,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            archived_at: row.archived_at,
+            visibility: row.visibility,
+            mentions: row.mentions,
+            collaborators: row.collaborators,
+        }
+    }
+}
+
 pub struct PostRepository {
     pool: PgPool,
+    /// Where read-only queries go; the primary unless built from [`DbPools`]
+    reads: PgPool,
+    ids: Arc<dyn IdGenerator>,
+    cache: Option<Arc<Cache>>,
 }
 
 impl PostRepository {
     pub fn new(pool: PgPool) -> Self {
         debug!("Creating new PostRepository");
-        Self { pool }
+        Self {
+            reads: pool.clone(),
+            pool,
+            ids: Arc::new(TimeOrderedIds),
+            cache: None,
+        }
+    }
+
+    /// Reads from the replica while it is up and writes to the primary; only
+    /// for requests that don't read back what they write
+    pub fn from_pools(pools: &DbPools) -> Self {
+        let mut repo = Self::new((*pools.primary).clone());
+        repo.reads = pools.reader().clone();
+        repo
+    }
+
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Serves public reads from `cache` and invalidates it on writes
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Drops the cached copies of a post whose related data changed, such as
+    /// its collaborators
+    pub async fn invalidate_post(&self, post: &Post) {
+        self.invalidate(Some(&post.public_id), post.org_id).await;
+    }
+
+    /// Drops the cached copies of a post and, for public posts, the latest and trending lists
+    async fn invalidate(&self, public_id: Option<&str>, org_id: Option<Uuid>) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+
+        if let Some(public_id) = public_id {
+            cache.invalidate(&post_key(public_id)).await;
+        }
+        if org_id.is_none() {
+            cache.invalidate(LATEST_POSTS_KEY).await;
+            cache.invalidate(TRENDING_POSTS_KEY).await;
+        }
     }
 
-    pub async fn create_post(&self, post_data: CreatePostRequest, authod_id: Uuid) -> Result<Post> {
-        let id = Uuid::new_v4();
-        let now: DateTime<Utc> = Utc::now();
+    pub async fn create_post(
+        &self,
+        post_data: CreatePostRequest,
+        authod_id: Uuid,
+        org_id: Option<Uuid>,
+        slug: String,
+        now: DateTime<Utc>,
+    ) -> Result<Post> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.create_post");
 
         info!("Creating new post with title: {}", post_data.title);
 
         let post = Post {
-            id,
+            id: self.ids.generate(),
+            public_id: new_public_id(),
+            slug: Some(slug),
             title: post_data.title,
             content: post_data.content,
+            license: post_data.license,
+            visibility: post_data.visibility.unwrap_or_default(),
             author_id: authod_id,
+            org_id,
             created_at: now,
             updated_at: now,
+            archived_at: None,
+        };
+
+        self.insert_post(&post).await?;
+        self.invalidate(None, post.org_id).await;
+
+        debug!("Post created with ID: {}", post.id);
+        Ok(post)
+    }
+
+    /// Inserts `posts` with their slugs in one statement inside a transaction;
+    /// returns an entry per post in order, `None` where another post took the
+    /// slug in the meantime
+    pub async fn create_posts(
+        &self,
+        posts: Vec<(CreatePostRequest, String)>,
+        author_id: Uuid,
+        org_id: Option<Uuid>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Option<Post>>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.create_posts");
+
+        if posts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        info!("Creating {} posts in bulk", posts.len());
+
+        let posts: Vec<Post> = posts
+            .into_iter()
+            .map(|(post_data, slug)| Post {
+                id: self.ids.generate(),
+                public_id: new_public_id(),
+                slug: Some(slug),
+                title: post_data.title,
+                content: post_data.content,
+                license: post_data.license,
+                visibility: post_data.visibility.unwrap_or_default(),
+                author_id,
+                org_id,
+                created_at: now,
+                updated_at: now,
+                archived_at: None,
+            })
+            .collect();
+
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO posts (id, public_id, slug, title, content, content_html, license, word_count, reading_time_minutes, excerpt, author_id, org_id, created_at, updated_at, visibility) ",
+        );
+        query.push_values(&posts, |mut row, post| {
+            let summary = summarize(&post.content);
+            row.push_bind(post.id)
+                .push_bind(post.public_id.clone())
+                .push_bind(post.slug.clone())
+                .push_bind(post.title.clone())
+                .push_bind(post.content.clone())
+                .push_bind(render_markdown(&post.content))
+                .push_bind(post.license.clone())
+                .push_bind(summary.word_count)
+                .push_bind(summary.reading_time_minutes)
+                .push_bind(summary.excerpt)
+                .push_bind(post.author_id)
+                .push_bind(post.org_id)
+                .push_bind(post.created_at)
+                .push_bind(post.updated_at)
+                .push_bind(String::from(post.visibility));
+        });
+        query.push(" ON CONFLICT DO NOTHING RETURNING public_id");
+
+        let mut tx = self.pool.begin().await?;
+        let inserted: Vec<String> = query
+            .build_query_scalar::<String>()
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        self.invalidate(None, org_id).await;
+
+        debug!("Bulk created {} of {} posts", inserted.len(), posts.len());
+        Ok(posts
+            .into_iter()
+            .map(|post| inserted.contains(&post.public_id).then_some(post))
+            .collect())
+    }
+
+    /// Inserts a post carried over from another system, keeping its original date
+    pub async fn import_post(
+        &self,
+        post_data: CreatePostRequest,
+        authod_id: Uuid,
+        created_at: DateTime<Utc>,
+        slug: String,
+    ) -> Result<Post> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.import_post");
+
+        info!("Importing post with title: {}", post_data.title);
+
+        let post = Post {
+            id: self.ids.generate(),
+            public_id: new_public_id(),
+            slug: Some(slug),
+            title: post_data.title,
+            content: post_data.content,
+            license: post_data.license,
+            visibility: post_data.visibility.unwrap_or_default(),
+            author_id: authod_id,
+            org_id: None,
+            created_at,
+            updated_at: created_at,
+            archived_at: None,
         };
 
-        sqlx::query(
+        self.insert_post(&post).await?;
+        self.invalidate(None, None).await;
+
+        debug!("Post imported with ID: {}", post.id);
+        Ok(post)
+    }
+
+    async fn insert_post(&self, post: &Post) -> Result<()> {
+        let summary = summarize(&post.content);
+
+        sqlx::query!(
             r#"
-                INSERT INTO posts (id, title, content, author_id, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6
+                INSERT INTO posts (id, public_id, slug, title, content, content_html, license, word_count, reading_time_minutes, excerpt, author_id, org_id, created_at, updated_at, visibility)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
             "#,
+            post.id,
+            post.public_id,
+            post.slug,
+            post.title,
+            post.content,
+            render_markdown(&post.content),
+            post.license,
+            summary.word_count,
+            summary.reading_time_minutes,
+            summary.excerpt,
+            post.author_id,
+            post.org_id,
+            post.created_at,
+            post.updated_at,
+            String::from(post.visibility),
         )
-        .bind(post.id)
-        .bind(&post.title)
-        .bind(&post.content)
-        .bind(post.author_id)
-        .bind(post.created_at)
-        .bind(post.updated_at)
         .execute(&self.pool)
         .await?;
 
-        debug!("Post created with ID: {}", post.id);
-        Ok(post)
+        Ok(())
+    }
+
+    pub async fn slug_taken(&self, slug: &str) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.slug_taken");
+
+        let taken = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS(SELECT 1 FROM posts WHERE slug = $1) as "taken!"
+            "#,
+            slug,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(taken)
+    }
+
+    /// A slug derived from `title`, made unique with a random suffix when
+    /// another post or a reserved name already has it
+    pub async fn free_slug(&self, title: &str, config: &SlugConfig) -> Result<String> {
+        let slug = slugify(title, config);
+        if is_reserved(&slug, config) || self.slug_taken(&slug).await? {
+            return Ok(with_suffix(&slug, &new_public_id(), config));
+        }
+        Ok(slug)
     }
 
     pub async fn find_by_id(&self, id: Uuid) -> Result<Option<Post>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.find_by_id");
+
         debug!("Finding post by ID: {}", id);
 
-        let row = sqlx::query(
+        let post = sqlx::query_as!(
+            Post,
             r#"
-                SELECT id, title, content, author_id, created_at, updated_at
+                SELECT id, public_id, slug, title, content, license, author_id, org_id, created_at, updated_at, archived_at, visibility as "visibility: Visibility"
                 FROM posts
                 WHERE id = $1
             "#,
+            id,
         )
-        .bind(id)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&self.reads)
         .await?;
 
-        match row {
-            Some(row) => {
-                let post = Post {
-                    id: row.get("id"),
-                    title: row.get("title"),
-                    content: row.get("content"),
-                    author_id: row.get("author_id"),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                };
-                debug!("Post found with id {}", id);
-                Ok(Some(post))
-            }
-            None => {
-                debug!("No post found with id {}", id);
-                Ok(None)
-            }
-        }
+        debug!("Post with id {} found: {}", id, post.is_some());
+        Ok(post)
+    }
+
+    pub async fn find_by_public_id(&self, public_id: &str) -> Result<Option<Post>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.find_by_public_id");
+
+        debug!("Finding post by public ID: {}", public_id);
+
+        let post = sqlx::query_as!(
+            Post,
+            r#"
+                SELECT id, public_id, slug, title, content, license, author_id, org_id, created_at, updated_at, archived_at, visibility as "visibility: Visibility"
+                FROM posts
+                WHERE public_id = $1
+            "#,
+            public_id,
+        )
+        .fetch_optional(&self.reads)
+        .await?;
+
+        debug!(
+            "Post with public id {} found: {}",
+            public_id,
+            post.is_some()
+        );
+        Ok(post)
     }
 
     pub async fn find_by_id_with_author(&self, id: Uuid) -> Result<Option<PostResponse>> {
+        Ok(self.find_row_by_id(id).await?.map(PostResponse::from))
+    }
+
+    /// A post outside any organization with its author, by public id, along
+    /// with the post's internal id. Served from the cache when one is attached.
+    /// Private posts are returned too; callers check who may read them, see
+    /// [`crate::helpers::policy::can_view_post`]
+    pub async fn find_public_with_author(
+        &self,
+        public_id: &str,
+    ) -> Result<Option<(Uuid, PostResponse)>> {
+        let key = post_key(public_id);
+        if let Some(cache) = &self.cache
+            && let Some(cached) = cache.get::<CachedPost>(&key).await
+        {
+            return Ok(Some((cached.id, cached.row.into())));
+        }
+
+        let id = match self.find_by_public_id(public_id).await? {
+            Some(post) if post.org_id.is_none() => post.id,
+            _ => return Ok(None),
+        };
+        let Some(row) = self.find_row_by_id(id).await? else {
+            return Ok(None);
+        };
+
+        if let Some(cache) = &self.cache {
+            let cached = CachedPost {
+                id,
+                row: row.clone(),
+            };
+            cache.set(&key, &cached).await;
+        }
+
+        Ok(Some((id, row.into())))
+    }
+
+    async fn find_row_by_id(&self, id: Uuid) -> Result<Option<PostWithAuthorRow>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.find_row_by_id");
+
         debug!("Finding post with author by ID: {}", id);
 
-        let row = sqlx::query(
+        let row = sqlx::query_as!(
+            PostRow,
             r#"
-                SELECT 
-                    p.id as post_id, p.title, p.content, p.author_id, p.created_at as post_created_at, p.updated_at as post_updated_at,
-                    u.id as user_id, u.name as user_name, u.email as user_email, u.role as user_role, u.email_verified as user_email_verified, u.created_at as user_created_at, u.updated_at as user_updated_at
+                SELECT
+                    p.public_id, p.slug, p.title, p.content, p.content_html, p.license, p.word_count, p.reading_time_minutes, p.excerpt, p.author_id, p.view_count, p.created_at, p.updated_at, p.archived_at, p.visibility as "visibility: Visibility"
                 FROM posts p
-                JOIN users u ON p.author_id = u.id
                 WHERE p.id = $1
-            "#
+            "#,
+            id,
         )
-        .bind(id)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&self.reads)
         .await?;
 
-        match row {
-            Some(row) => {
-                let author = UserResponse {
-                    id: row.get("user_id"),
-                    name: row.get("user_name"),
-                    email: row.get("user_email"),
-                    role: Role::from(row.get::<&str, _>("user_role")),
-                    email_verified: row.get("user_email_verified"),
-                    created_at: row.get("user_created_at"),
-                    updated_at: row.get("user_updated_at"),
-                };
-
-                let post_response = PostResponse {
-                    id: row.get("id"),
-                    title: row.get("title"),
-                    content: row.get("content"),
-                    author,
-                    created_at: row.get("post_created_at"),
-                    updated_at: row.get("post_updated_at"),
-                };
-
-                debug!("Post with author found with id {}", id);
-                Ok(Some(post_response))
-            }
-            None => {
-                debug!("No post found with id {}", id);
-                Ok(None)
-            }
-        }
+        debug!("Post with author and id {} found: {}", id, row.is_some());
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(with_related(&self.reads, vec![row]).await?.pop())
     }
 
     pub async fn find_by_author(&self, authod_id: Uuid) -> Result<Vec<Post>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.find_by_author");
+
         debug!("Finding posts by author ID: {}", authod_id);
 
-        let rows = sqlx::query(
+        let posts = sqlx::query_as!(
+            Post,
             r#"
-                SELECT id, title, content, author_id, created_at, updated_at
+                SELECT id, public_id, slug, title, content, license, author_id, org_id, created_at, updated_at, archived_at, visibility as "visibility: Visibility"
                 FROM posts
                 WHERE author_id = $1
-                ORDER BY created_at DESC   
+                ORDER BY created_at DESC
             "#,
+            authod_id,
         )
-        .bind(authod_id)
-        .fetch_all(&self.pool)
+        .fetch_all(&self.reads)
         .await?;
 
-        let posts: Result<Vec<Post>> = rows
-            .into_iter()
-            .map(|row| {
-                Ok(Post {
-                    id: Uuid::parse_str(&row.get::<String, _>("id"))?,
-                    title: row.get("title"),
-                    content: row.get("content"),
-                    author_id: Uuid::parse_str(&row.get::<String, _>("author_id"))?,
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?
-                        .with_timezone(&Utc),
-                    updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?
-                        .with_timezone(&Utc),
-                })
-            })
-            .collect();
+        Ok(posts)
+    }
 
-        posts
+    /// Newest listed posts of an author together with their total count
+    pub async fn find_recent_by_author(
+        &self,
+        author_id: Uuid,
+        limit: i64,
+    ) -> Result<(Vec<Post>, i64)> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.find_recent_by_author");
+
+        debug!("Finding recent posts by author ID: {}", author_id);
+
+        let posts = sqlx::query_as!(
+            Post,
+            r#"
+                SELECT id, public_id, slug, title, content, license, author_id, org_id, created_at, updated_at, archived_at, visibility as "visibility: Visibility"
+                FROM posts
+                WHERE author_id = $1 AND org_id IS NULL AND archived_at IS NULL AND visibility = 'PUBLIC'
+                ORDER BY created_at DESC
+                LIMIT $2
+            "#,
+            author_id,
+            limit,
+        )
+        .fetch_all(&self.reads)
+        .await?;
+
+        let total = sqlx::query_scalar!(
+            r#"
+                SELECT COUNT(*) as "count!"
+                FROM posts
+                WHERE author_id = $1 AND org_id IS NULL AND archived_at IS NULL AND visibility = 'PUBLIC'
+            "#,
+            author_id,
+        )
+        .fetch_one(&self.reads)
+        .await?;
+
+        Ok((posts, total))
     }
 
+    /// Callers check who may edit the post; see [`crate::helpers::policy`]
     pub async fn update_post(
         &self,
         id: Uuid,
-        authod_id: Uuid,
-        update_data: UpdatePostRequest,
+        update_data: PatchPostRequest,
+        now: DateTime<Utc>,
     ) -> Result<Option<Post>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.update_post");
+
         debug!("Updating post ID: {}", id);
 
         let existing_post = self.find_by_id(id).await?;
@@ -186,132 +617,406 @@ impl PostRepository {
 
         let existing_post = existing_post.unwrap();
 
-        if existing_post.author_id != authod_id {
-            anyhow::bail!("Unauthorized: You can only update your own posts");
-        }
-
         let updated_title = update_data
             .title
+            .into_value()
             .unwrap_or(existing_post.title)
             .trim()
             .to_string();
         let updated_content = update_data
             .content
+            .into_value()
             .unwrap_or(existing_post.content)
             .trim()
             .to_string();
-        let now: DateTime<Utc> = Utc::now();
-
-        sqlx::query(
+        let updated_license = update_data.license.apply(existing_post.license);
+        let updated_visibility = update_data
+            .visibility
+            .into_value()
+            .unwrap_or(existing_post.visibility);
+        let summary = summarize(&updated_content);
+
+        sqlx::query!(
             r#"
                 UPDATE posts
-                SET title = $1, content = $2, updated_at = $3
-                WHERE id = $4
+                SET title = $1, content = $2, content_html = $3, license = $4,
+                    word_count = $5, reading_time_minutes = $6, excerpt = $7, updated_at = $8,
+                    visibility = $9
+                WHERE id = $10
             "#,
+            updated_title,
+            updated_content,
+            render_markdown(&updated_content),
+            updated_license,
+            summary.word_count,
+            summary.reading_time_minutes,
+            summary.excerpt,
+            now,
+            String::from(updated_visibility),
+            id,
         )
-        .bind(&updated_title)
-        .bind(&updated_content)
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await?;
+        self.invalidate(Some(&existing_post.public_id), existing_post.org_id)
+            .await;
 
         let updated_post = Post {
             id,
+            public_id: existing_post.public_id,
+            slug: existing_post.slug,
             title: updated_title,
             content: updated_content,
-            author_id: authod_id,
+            license: updated_license,
+            visibility: updated_visibility,
+            author_id: existing_post.author_id,
+            org_id: existing_post.org_id,
             created_at: existing_post.created_at,
             updated_at: now,
+            archived_at: existing_post.archived_at,
         };
 
         debug!("Post updated with ID: {}", id);
         Ok(Some(updated_post))
     }
 
-    pub async fn delete_post(&self, id: Uuid, authod_id: Uuid) -> Result<bool> {
-        debug!("Deleting post ID: {}", id);
+    /// Archives the post as of `archived_at`, or puts it back in listings with
+    /// `None`; callers check who may, see [`crate::helpers::policy`]
+    pub async fn set_archived(
+        &self,
+        id: Uuid,
+        archived_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<Post>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.set_archived");
 
-        let existing_post = self.find_by_id(id).await?;
+        info!("Setting archived_at of post ID {} to {:?}", id, archived_at);
 
-        if existing_post.is_none() {
+        let Some(mut post) = self.find_by_id(id).await? else {
             debug!("No post found with id {}", id);
-            return Ok(false);
-        }
+            return Ok(None);
+        };
 
-        let existing_post = existing_post.unwrap();
+        sqlx::query!(
+            r#"
+                UPDATE posts
+                SET archived_at = $1
+                WHERE id = $2
+            "#,
+            archived_at,
+            id,
+        )
+        .execute(&self.pool)
+        .await?;
+        self.invalidate(Some(&post.public_id), post.org_id).await;
 
-        if existing_post.author_id != authod_id {
-            anyhow::bail!("Unauthorized: You can only delete your own posts");
-        }
+        post.archived_at = archived_at;
+        Ok(Some(post))
+    }
+
+    /// Deletes a post regardless of its author; callers check who may, see
+    /// [`crate::helpers::policy`]
+    pub async fn remove_post(&self, id: Uuid) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.remove_post");
+
+        info!("Removing post ID: {}", id);
+
+        let Some(existing_post) = self.find_by_id(id).await? else {
+            debug!("No post found with id {}", id);
+            return Ok(false);
+        };
 
-        let result = sqlx::query(
+        let result = sqlx::query!(
             r#"
                 DELETE FROM posts
                 WHERE id = $1
             "#,
+            id,
         )
-        .bind(id)
         .execute(&self.pool)
         .await?;
 
-        if result.rows_affected() == 0 {
-            debug!("No post deleted with id {}", id);
-            Ok(false)
-        } else {
-            debug!("Post deleted with ID: {}", id);
-            Ok(true)
+        if result.rows_affected() > 0 {
+            self.invalidate(Some(&existing_post.public_id), existing_post.org_id)
+                .await;
         }
+        Ok(result.rows_affected() > 0)
     }
 
-    pub async fn get_all_posts(&self) -> Result<Vec<PostResponse>> {
-        debug!("Retrieving all posts");
+    /// Posts outside any organization are served from the cache when one is
+    /// attached, stale while a refresh runs
+    pub async fn get_all_posts(&self, org_id: Option<Uuid>) -> Result<Vec<PostResponse>> {
+        let rows = match self.cache.as_ref().filter(|_| org_id.is_none()) {
+            Some(cache) => {
+                let pool = self.reads.clone();
+                cache
+                    .get_or_refresh(LATEST_POSTS_KEY, move || fetch_all_rows(pool, None))
+                    .await?
+            }
+            None => fetch_all_rows(self.reads.clone(), org_id).await?,
+        };
+
+        Ok(rows.into_iter().map(PostResponse::from).collect())
+    }
+
+    pub async fn find_latest_with_author(&self, limit: i64) -> Result<Vec<PostResponse>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.find_latest_with_author");
+
+        debug!("Retrieving latest {} posts", limit);
 
-        let rows = sqlx::query(
+        let rows = sqlx::query_as!(
+            PostRow,
             r#"
-                SELECT 
-                    p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at,
-                    u.name as author_name, u.email as author_email, u.role as author_role, u.email_verified as author_email_verified, u.created_at as author_created_at, u.updated_at as author_updated_at
+                SELECT
+                    p.public_id, p.slug, p.title, p.content, p.content_html, p.license, p.word_count, p.reading_time_minutes, p.excerpt, p.author_id, p.view_count, p.created_at, p.updated_at, p.archived_at, p.visibility as "visibility: Visibility"
                 FROM posts p
-                JOIN users u ON p.author_id = u.id
+                WHERE p.org_id IS NULL AND p.archived_at IS NULL AND p.visibility = 'PUBLIC'
                 ORDER BY p.created_at DESC
-            "#
+                LIMIT $1
+            "#,
+            limit,
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.reads)
         .await?;
 
-        let posts = rows
+        let rows = with_related(&self.reads, rows).await?;
+        Ok(rows.into_iter().map(PostResponse::from).collect())
+    }
+
+    /// Newest listed posts outside any organization by the author with this
+    /// public id
+    pub async fn find_latest_by_author(
+        &self,
+        author_public_id: &str,
+        limit: i64,
+    ) -> Result<Vec<PostResponse>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.find_latest_by_author");
+
+        debug!(
+            "Retrieving latest {} posts by author {}",
+            limit, author_public_id
+        );
+
+        let rows = sqlx::query_as!(
+            PostRow,
+            r#"
+                SELECT
+                    p.public_id, p.slug, p.title, p.content, p.content_html, p.license, p.word_count, p.reading_time_minutes, p.excerpt, p.author_id, p.view_count, p.created_at, p.updated_at, p.archived_at, p.visibility as "visibility: Visibility"
+                FROM posts p
+                WHERE p.author_id = (SELECT id FROM users WHERE public_id = $1) AND p.org_id IS NULL AND p.archived_at IS NULL AND p.visibility = 'PUBLIC'
+                ORDER BY p.created_at DESC
+                LIMIT $2
+            "#,
+            author_public_id,
+            limit,
+        )
+        .fetch_all(&self.reads)
+        .await?;
+
+        let rows = with_related(&self.reads, rows).await?;
+        Ok(rows.into_iter().map(PostResponse::from).collect())
+    }
+
+    /// Number of listed posts outside any organization, as listed in the sitemap
+    pub async fn count_listed(&self) -> Result<i64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.count_listed");
+
+        let count = sqlx::query_scalar!(
+            r#"
+                SELECT COUNT(*) as "count!"
+                FROM posts
+                WHERE org_id IS NULL AND archived_at IS NULL AND visibility = 'PUBLIC'
+            "#,
+        )
+        .fetch_one(&self.reads)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Public ids and last changes of listed posts outside any organization,
+    /// oldest first
+    pub async fn find_listed_page(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<(String, DateTime<Utc>)>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.find_listed_page");
+
+        debug!("Listing {} posts from offset {}", limit, offset);
+
+        let rows = sqlx::query!(
+            r#"
+                SELECT public_id, updated_at
+                FROM posts
+                WHERE org_id IS NULL AND archived_at IS NULL AND visibility = 'PUBLIC'
+                ORDER BY created_at, id
+                OFFSET $1
+                LIMIT $2
+            "#,
+            offset,
+            limit,
+        )
+        .fetch_all(&self.reads)
+        .await?;
+
+        Ok(rows
             .into_iter()
-            .map(|row| {
-                let author = UserResponse {
-                    id: Uuid::parse_str(&row.get::<String, _>("author_id"))?,
-                    name: row.get("author_name"),
-                    email: row.get("author_email"),
-                    role: Role::from(row.get::<&str, _>("author_role")),
-                    email_verified: row.get("author_email_verified"),
-                    created_at: DateTime::parse_from_rfc3339(
-                        &row.get::<String, _>("author_created_at"),
-                    )?
-                    .with_timezone(&Utc),
-                    updated_at: DateTime::parse_from_rfc3339(
-                        &row.get::<String, _>("author_updated_at"),
-                    )?
-                    .with_timezone(&Utc),
-                };
-
-                Ok(PostResponse {
-                    id: Uuid::parse_str(&row.get::<String, _>("id"))?,
-                    title: row.get("title"),
-                    content: row.get("content"),
-                    author,
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))?
-                        .with_timezone(&Utc),
-                    updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))?
-                        .with_timezone(&Utc),
-                })
-            })
-            .collect();
+            .map(|row| (row.public_id, row.updated_at))
+            .collect())
+    }
+
+    /// Counts a view unless the same viewer was already counted in the last 24 hours
+    pub async fn record_view(
+        &self,
+        post_id: Uuid,
+        viewer_key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("post_repo.record_view");
+
+        let result = sqlx::query!(
+            r#"
+                WITH counted AS (
+                    INSERT INTO post_views (post_id, viewer_key, viewed_at)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (post_id, viewer_key) DO UPDATE SET viewed_at = EXCLUDED.viewed_at
+                    WHERE post_views.viewed_at <= EXCLUDED.viewed_at - INTERVAL '24 hours'
+                    RETURNING post_id
+                )
+                UPDATE posts SET view_count = view_count + 1
+                WHERE id IN (SELECT post_id FROM counted)
+            "#,
+            post_id,
+            viewer_key,
+            now,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        debug!(
+            "View on post {} counted: {}",
+            post_id,
+            result.rows_affected() > 0
+        );
+        Ok(result.rows_affected() > 0)
+    }
 
-        posts
+    /// Posts ranked by views in the trending window, newer views weighing more.
+    /// The ranking outside any organization is cached at the largest page
+    /// size and cut to `limit`, stale while a refresh runs.
+    pub async fn find_trending(
+        &self,
+        org_id: Option<Uuid>,
+        limit: i64,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PostResponse>> {
+        let rows = match self.cache.as_ref().filter(|_| org_id.is_none()) {
+            Some(cache) => {
+                let pool = self.reads.clone();
+                let mut rows = cache
+                    .get_or_refresh(TRENDING_POSTS_KEY, move || {
+                        fetch_trending_rows(pool, None, MAX_TRENDING_LIMIT, now)
+                    })
+                    .await?;
+                rows.truncate(limit.max(0) as usize);
+                rows
+            }
+            None => fetch_trending_rows(self.reads.clone(), org_id, limit, now).await?,
+        };
+
+        Ok(rows.into_iter().map(PostResponse::from).collect())
     }
 }
+
+/// Owns its pool so cache refreshes can run it in the background
+async fn fetch_all_rows(pool: PgPool, org_id: Option<Uuid>) -> Result<Vec<PostWithAuthorRow>> {
+    chaos::db_fault()?;
+    let _timer = QueryTimer::start("post_repo.fetch_all_rows");
+
+    debug!("Retrieving all posts for org: {:?}", org_id);
+
+    let rows = sqlx::query_as!(
+        PostRow,
+        r#"
+            SELECT
+                p.public_id, p.slug, p.title, p.content, p.content_html, p.license, p.word_count, p.reading_time_minutes, p.excerpt, p.author_id, p.view_count, p.created_at, p.updated_at, p.archived_at, p.visibility as "visibility: Visibility"
+            FROM posts p
+            WHERE p.org_id IS NOT DISTINCT FROM $1 AND p.archived_at IS NULL AND p.visibility = 'PUBLIC'
+            ORDER BY p.created_at DESC
+        "#,
+        org_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    with_related(&pool, rows).await
+}
+
+async fn fetch_trending_rows(
+    pool: PgPool,
+    org_id: Option<Uuid>,
+    limit: i64,
+    now: DateTime<Utc>,
+) -> Result<Vec<PostWithAuthorRow>> {
+    chaos::db_fault()?;
+    let _timer = QueryTimer::start("post_repo.fetch_trending_rows");
+
+    debug!("Retrieving {} trending posts for org: {:?}", limit, org_id);
+
+    // Each viewer contributes 1 / (1 + days since their last counted view)
+    let rows = sqlx::query_as!(
+        PostRow,
+        r#"
+            WITH scores AS (
+                SELECT post_id,
+                    SUM(1.0 / (1.0 + EXTRACT(EPOCH FROM ($3::timestamptz - viewed_at)) / 86400.0)) as score
+                FROM post_views
+                WHERE viewed_at > $3::timestamptz - INTERVAL '7 days'
+                GROUP BY post_id
+            )
+            SELECT
+                p.public_id, p.slug, p.title, p.content, p.content_html, p.license, p.word_count, p.reading_time_minutes, p.excerpt, p.author_id, p.view_count, p.created_at, p.updated_at, p.archived_at, p.visibility as "visibility: Visibility"
+            FROM scores s
+            JOIN posts p ON p.id = s.post_id
+            WHERE p.org_id IS NOT DISTINCT FROM $1 AND p.archived_at IS NULL AND p.visibility = 'PUBLIC'
+            ORDER BY s.score DESC, p.created_at DESC
+            LIMIT $2
+        "#,
+        org_id,
+        limit,
+        now,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    with_related(&pool, rows).await
+}
+
+/// Pairs each post with its author, the users it mentions and its
+/// collaborators, loading each in one query
+async fn with_related(pool: &PgPool, rows: Vec<PostRow>) -> Result<Vec<PostWithAuthorRow>> {
+    let loader = Loader::new(pool.clone());
+    let authors = loader.authors(rows.iter().map(|row| row.author_id)).await?;
+    let mut mentions = loader
+        .mentions(rows.iter().map(|row| row.public_id.clone()))
+        .await?;
+    let mut collaborators = loader
+        .collaborators(rows.iter().map(|row| row.public_id.clone()))
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let author = authors.get(&row.author_id)?;
+            let mentions = mentions.remove(&row.public_id).unwrap_or_default();
+            let collaborators = collaborators.remove(&row.public_id).unwrap_or_default();
+            Some(PostWithAuthorRow::new(row, author, mentions, collaborators))
+        })
+        .collect())
+}
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row, postgres::PgRow};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::helpers::ids::{IdGenerator, TimeOrderedIds};
+use crate::model::model::{CreateReportRequest, Report, ReportAction, ReportReason, ReportStatus};
+
+/// Reported content awaiting moderation, and the decisions taken on it
+pub struct ReportRepository {
+    pool: PgPool,
+    ids: Arc<dyn IdGenerator>,
+}
+
+const REPORT_COLUMNS: &str = r#"
+    r.id, r.target_kind, r.target_id, r.target_public_id, r.author_id,
+    a.public_id as author_public_id, rp.public_id as reporter_public_id,
+    r.reason, r.details, r.status, r.action, r.note, r.created_at, r.resolved_at
+"#;
+
+fn report_from_row(row: PgRow) -> Report {
+    Report {
+        id: row.get("id"),
+        target_kind: row.get("target_kind"),
+        target_internal_id: row.get("target_id"),
+        target_id: row.get("target_public_id"),
+        author_internal_id: row.get("author_id"),
+        author_id: row.get("author_public_id"),
+        reporter_id: row.get("reporter_public_id"),
+        reason: ReportReason::from(row.get::<&str, _>("reason")),
+        details: row.get("details"),
+        status: ReportStatus::from(row.get::<&str, _>("status")),
+        action: row.get::<Option<&str>, _>("action").map(ReportAction::from),
+        note: row.get("note"),
+        created_at: row.get("created_at"),
+        resolved_at: row.get("resolved_at"),
+    }
+}
+
+impl ReportRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating ReportRepository");
+        Self {
+            pool,
+            ids: Arc::new(TimeOrderedIds),
+        }
+    }
+
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Files a report of a post; `None` when the reporter already has an open report on it
+    pub async fn report_post(
+        &self,
+        post_id: Uuid,
+        post_public_id: &str,
+        author_id: Uuid,
+        reporter_id: Uuid,
+        request: &CreateReportRequest,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Report>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("report_repo.report_post");
+
+        info!("User {} reporting post {}", reporter_id, post_public_id);
+
+        let id = self.ids.generate();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO reports (id, target_kind, target_id, target_public_id, author_id, reporter_id, reason, details, status, created_at)
+            VALUES ($1, 'post', $2, $3, $4, $5, $6, $7, 'OPEN', $8)
+            ON CONFLICT (reporter_id, target_kind, target_id) WHERE status = 'OPEN' DO NOTHING
+            "#,
+        )
+        .bind(id)
+        .bind(post_id)
+        .bind(post_public_id)
+        .bind(author_id)
+        .bind(reporter_id)
+        .bind(String::from(request.reason))
+        .bind(&request.details)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            debug!("Duplicate open report on post {}", post_public_id);
+            return Ok(None);
+        }
+
+        self.find_by_id(id).await
+    }
+
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<Report>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("report_repo.find_by_id");
+
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {REPORT_COLUMNS}
+            FROM reports r
+            LEFT JOIN users a ON a.id = r.author_id
+            LEFT JOIN users rp ON rp.id = r.reporter_id
+            WHERE r.id = $1
+            "#
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(report_from_row))
+    }
+
+    /// Reports with `status`, oldest first so the queue is worked in order
+    pub async fn list(&self, status: ReportStatus, limit: i64) -> Result<Vec<Report>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("report_repo.list");
+
+        debug!("Listing {:?} reports", status);
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {REPORT_COLUMNS}
+            FROM reports r
+            LEFT JOIN users a ON a.id = r.author_id
+            LEFT JOIN users rp ON rp.id = r.reporter_id
+            WHERE r.status = $1
+            ORDER BY r.created_at
+            LIMIT $2
+            "#
+        ))
+        .bind(String::from(status))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(report_from_row).collect())
+    }
+
+    /// Closes the report, and with `whole_target` every other open report on
+    /// the same content; returns the reporters of the closed reports who still
+    /// have an account
+    pub async fn resolve(
+        &self,
+        report: &Report,
+        action: ReportAction,
+        note: Option<&str>,
+        admin_id: Uuid,
+        whole_target: bool,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("report_repo.resolve");
+
+        let status = match action {
+            ReportAction::DISMISS => ReportStatus::DISMISSED,
+            ReportAction::DELETE | ReportAction::SUSPEND => ReportStatus::RESOLVED,
+        };
+        info!(
+            "Admin {} resolving report {} with {:?}",
+            admin_id, report.id, action
+        );
+
+        let reporters: Vec<Option<Uuid>> = sqlx::query_scalar(
+            r#"
+            UPDATE reports
+            SET status = $1, action = $2, note = $3, resolved_by = $4, resolved_at = $5
+            WHERE status = 'OPEN'
+                AND (id = $6 OR ($7 AND target_kind = $8 AND target_id = $9))
+            RETURNING reporter_id
+            "#,
+        )
+        .bind(String::from(status))
+        .bind(String::from(action))
+        .bind(note)
+        .bind(admin_id)
+        .bind(now)
+        .bind(report.id)
+        .bind(whole_target)
+        .bind(&report.target_kind)
+        .bind(report.target_internal_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reporters.into_iter().flatten().collect())
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::model::model::Session;
+
+pub struct SessionRepository {
+    pool: PgPool,
+}
+
+impl SessionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating SessionRepository");
+        Self { pool }
+    }
+
+    pub async fn create_session(&self, session: &Session) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("session_repo.create_session");
+
+        info!("Creating session for user: {}", session.user_id);
+
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (id, user_id, device, ip, user_agent, network, country, created_at, last_seen_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(&session.id)
+        .bind(session.user_id)
+        .bind(&session.device)
+        .bind(&session.ip)
+        .bind(&session.user_agent)
+        .bind(&session.network)
+        .bind(&session.country)
+        .bind(session.created_at)
+        .bind(session.last_seen_at)
+        .bind(session.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether `session`, not yet stored, comes from a device the user hasn't
+    /// signed in with before: no earlier session has the same user agent and
+    /// either the same network or the same country. The first sign-in of an
+    /// account is never new, as there is nothing to compare it with.
+    pub async fn is_new_device(&self, session: &Session) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("session_repo.is_new_device");
+
+        let row = sqlx::query(
+            r#"
+            SELECT
+                EXISTS (SELECT 1 FROM sessions WHERE user_id = $1) AS has_history,
+                EXISTS (
+                    SELECT 1 FROM sessions
+                    WHERE user_id = $1
+                      AND user_agent = $2
+                      AND (network = $3 OR country = $4)
+                ) AS known
+            "#,
+        )
+        .bind(session.user_id)
+        .bind(&session.user_agent)
+        .bind(&session.network)
+        .bind(&session.country)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let has_history: bool = row.get("has_history");
+        let known: bool = row.get("known");
+        Ok(has_history && !known)
+    }
+
+    /// Records activity on the session; `false` if it was revoked or has expired
+    pub async fn touch(&self, id: &str, user_id: Uuid, now: DateTime<Utc>) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("session_repo.touch");
+
+        let result = sqlx::query(
+            r#"
+            UPDATE sessions
+            SET last_seen_at = $3
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > $3
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether the session is neither revoked nor expired; unlike `touch` it
+    /// doesn't count as activity
+    pub async fn is_active(&self, id: &str, now: DateTime<Utc>) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("session_repo.is_active");
+
+        let active = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM sessions
+                WHERE id = $1 AND revoked_at IS NULL AND expires_at > $2
+            )
+            "#,
+        )
+        .bind(id)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(active)
+    }
+
+    pub async fn list_active(&self, user_id: Uuid, now: DateTime<Utc>) -> Result<Vec<Session>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("session_repo.list_active");
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_id, device, ip, user_agent, network, country, created_at, last_seen_at, expires_at
+            FROM sessions
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > $2
+            ORDER BY last_seen_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let sessions = rows
+            .into_iter()
+            .map(|row| Session {
+                id: row.get("id"),
+                user_id: row.get("user_id"),
+                device: row.get("device"),
+                ip: row.get("ip"),
+                user_agent: row.get("user_agent"),
+                network: row.get("network"),
+                country: row.get("country"),
+                created_at: row.get("created_at"),
+                last_seen_at: row.get("last_seen_at"),
+                expires_at: row.get("expires_at"),
+            })
+            .collect();
+
+        Ok(sessions)
+    }
+
+    pub async fn revoke(&self, id: &str, user_id: Uuid, now: DateTime<Utc>) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("session_repo.revoke");
+
+        info!("Revoking session {} of user {}", id, user_id);
+
+        let result = sqlx::query(
+            r#"
+            UPDATE sessions
+            SET revoked_at = $3
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revokes every session of the user except `keep`; returns the revoked ids
+    pub async fn revoke_all_except(
+        &self,
+        user_id: Uuid,
+        keep: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<String>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("session_repo.revoke_all_except");
+
+        info!("Revoking other sessions of user {}", user_id);
+
+        let revoked = sqlx::query_scalar(
+            r#"
+            UPDATE sessions
+            SET revoked_at = $3
+            WHERE user_id = $1 AND revoked_at IS NULL AND id IS DISTINCT FROM $2
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(keep)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(revoked)
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::helpers::chaos;
+use crate::helpers::ids::{IdGenerator, TimeOrderedIds};
+use crate::model::model::ShareLink;
+
+/// Hashes of the tokens in links authors share to their posts
+pub struct ShareLinkRepository {
+    pool: PgPool,
+    ids: Arc<dyn IdGenerator>,
+}
+
+impl ShareLinkRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating ShareLinkRepository");
+        Self {
+            pool,
+            ids: Arc::new(TimeOrderedIds),
+        }
+    }
+
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    pub async fn insert(
+        &self,
+        post_id: Uuid,
+        token_hash: &str,
+        created_by: Uuid,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+        max_uses: Option<i32>,
+    ) -> Result<ShareLink> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("share_link_repo.insert");
+
+        info!("Creating share link for post {}", post_id);
+        let row = sqlx::query(
+            r#"
+            INSERT INTO share_links (id, post_id, token_hash, created_by, created_at, expires_at, max_uses)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, created_at, expires_at, max_uses, uses
+            "#,
+        )
+        .bind(self.ids.generate())
+        .bind(post_id)
+        .bind(token_hash)
+        .bind(created_by)
+        .bind(created_at)
+        .bind(expires_at)
+        .bind(max_uses)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(share_link(&row))
+    }
+
+    /// Every link to the post, newest first, including spent and expired ones
+    pub async fn list(&self, post_id: Uuid) -> Result<Vec<ShareLink>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("share_link_repo.list");
+
+        debug!("Listing share links of post {}", post_id);
+        let rows = sqlx::query(
+            r#"
+            SELECT id, created_at, expires_at, max_uses, uses
+            FROM share_links
+            WHERE post_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(post_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(share_link).collect())
+    }
+
+    pub async fn revoke(&self, post_id: Uuid, id: Uuid) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("share_link_repo.revoke");
+
+        info!("Revoking share link {} of post {}", id, post_id);
+        let result = sqlx::query("DELETE FROM share_links WHERE id = $1 AND post_id = $2")
+            .bind(id)
+            .bind(post_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Counts a read through the link, if it is for the post, hasn't expired
+    /// and has uses left; returns whether it did
+    pub async fn redeem(
+        &self,
+        post_id: Uuid,
+        token_hash: &str,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("share_link_repo.redeem");
+
+        let result = sqlx::query(
+            r#"
+            UPDATE share_links SET uses = uses + 1
+            WHERE post_id = $1 AND token_hash = $2 AND expires_at > $3
+              AND (max_uses IS NULL OR uses < max_uses)
+            "#,
+        )
+        .bind(post_id)
+        .bind(token_hash)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn share_link(row: &sqlx::postgres::PgRow) -> ShareLink {
+    ShareLink {
+        id: row.get("id"),
+        created_at: row.get("created_at"),
+        expires_at: row.get("expires_at"),
+        max_uses: row.get("max_uses"),
+        uses: row.get("uses"),
+    }
+}
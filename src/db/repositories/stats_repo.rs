@@ -0,0 +1,68 @@
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::db::instrument::QueryTimer;
+use crate::db::pools::DbPools;
+use crate::helpers::chaos;
+use crate::model::model::{MonthlyPosts, ProfileStats};
+
+/// Aggregates over posts for dashboards; read-only, so it runs on the read
+/// replica when there is one
+pub struct StatsRepository {
+    reads: PgPool,
+}
+
+impl StatsRepository {
+    pub fn new(pool: PgPool) -> Self {
+        debug!("Creating StatsRepository");
+        Self { reads: pool }
+    }
+
+    pub fn from_pools(pools: &DbPools) -> Self {
+        Self::new(pools.reader().clone())
+    }
+
+    pub async fn author_stats(&self, author_id: Uuid) -> Result<ProfileStats> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("stats_repo.author_stats");
+
+        debug!("Computing statistics for author {}", author_id);
+        // One statement, so the totals and the months come from the same snapshot
+        let months = sqlx::query(
+            r#"
+            SELECT to_char(date_trunc('month', created_at AT TIME ZONE 'UTC'), 'YYYY-MM') AS month,
+                   COUNT(*) AS posts,
+                   SUM(COUNT(*)) OVER ()::BIGINT AS post_count,
+                   SUM(SUM(view_count)) OVER ()::BIGINT AS total_views
+            FROM posts
+            WHERE author_id = $1
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(author_id)
+        .fetch_all(&self.reads)
+        .await?;
+
+        // Without posts there are no rows, and every total is zero
+        let (post_count, total_views) = months
+            .first()
+            .map(|row| (row.get("post_count"), row.get("total_views")))
+            .unwrap_or((0, 0));
+        Ok(ProfileStats {
+            post_count,
+            total_views,
+            total_likes: None,
+            total_comments: None,
+            posts_per_month: months
+                .iter()
+                .map(|row| MonthlyPosts {
+                    month: row.get("month"),
+                    posts: row.get("posts"),
+                })
+                .collect(),
+        })
+    }
+}
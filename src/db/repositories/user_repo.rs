@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use mailchecker::is_valid;
@@ -6,29 +8,79 @@ use tracing::{debug, info};
 use uuid::Uuid;
 
 use crate::{
-    helpers::validation::strong_password,
+    cache::{Cache, LATEST_POSTS_KEY, profile_key},
+    config::SlugConfig,
+    db::{instrument::QueryTimer, pools::DbPools},
+    helpers::{
+        avatar::avatar_url,
+        chaos,
+        ids::{IdGenerator, TimeOrderedIds, new_public_id},
+        slugs::{is_reserved, suggest_username, username_with_suffix},
+        validation::strong_password,
+    },
     model::model::{
-        CreateUserRequest, Role, UpdatePasswordRequest, UpdateUserRequest, User, UserResponse,
+        CreateUserRequest, EmailDelivery, PendingVerification, PublicProfile, Role,
+        UpdatePasswordRequest, UpdateUserRequest, User, UserResponse, UserStanding, UserStatus,
     },
 };
 
 pub struct UserRepository {
     pool: PgPool,
+    /// Where public profile and user list reads go; the primary unless built
+    /// from [`DbPools`]
+    reads: PgPool,
+    ids: Arc<dyn IdGenerator>,
+    cache: Option<Arc<Cache>>,
 }
 
 impl UserRepository {
     pub fn new(pool: PgPool) -> Self {
         debug!("Creating UserRepository");
-        Self { pool }
+        Self {
+            reads: pool.clone(),
+            pool,
+            ids: Arc::new(TimeOrderedIds),
+            cache: None,
+        }
+    }
+
+    /// Serves public profiles and user lists from the replica while it is up;
+    /// everything else, including the reads behind logins, uses the primary
+    pub fn from_pools(pools: &DbPools) -> Self {
+        let mut repo = Self::new((*pools.primary).clone());
+        repo.reads = pools.reader().clone();
+        repo
+    }
+
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Serves public profiles from `cache` and invalidates it on writes
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Drops the user's cached profile and the latest posts list, which embeds authors
+    async fn invalidate(&self, public_id: &str) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(&profile_key(public_id)).await;
+            cache.invalidate(LATEST_POSTS_KEY).await;
+        }
     }
 
     pub async fn create_user(
         &self,
         user_data: CreateUserRequest,
         hashed_password: String,
+        now: DateTime<Utc>,
     ) -> Result<User> {
-        let id = Uuid::new_v4();
-        let now: DateTime<Utc> = Utc::now();
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.create_user");
+
+        let id = self.ids.generate();
 
         info!("Creating new user with email: {}", user_data.email);
 
@@ -39,27 +91,33 @@ impl UserRepository {
         } else {
             let user = User {
                 id,
+                public_id: new_public_id(),
                 name: user_data.name,
+                username: user_data.username,
                 email: user_data.email,
                 password: hashed_password,
                 role: Role::default(), // Default to USER role
                 email_verified: false, // Default to false, requires verification
+                avatar_url: None,
                 created_at: now,
                 updated_at: now,
             };
 
             sqlx::query(
                 r#"
-                INSERT INTO users (id, name, email, password, role, email_verified, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                INSERT INTO users (id, public_id, name, username, email, password, role, email_verified, locale, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
                 "#,
             )
             .bind(id)
+            .bind(&user.public_id)
             .bind(&user.name)
+            .bind(&user.username)
             .bind(&user.email)
             .bind(&user.password)
             .bind(&String::from(user.role.clone()))
             .bind(user.email_verified)
+            .bind(&user_data.locale)
             .bind(user.created_at)
             .bind(user.updated_at)
             .execute(&self.pool)
@@ -71,10 +129,13 @@ impl UserRepository {
     }
 
     pub async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.find_by_id");
+
         debug!("Finding user by ID: {}", id);
         let row = sqlx::query(
             r#"
-            SELECT id, name, email, password, role, email_verified, created_at, updated_at
+            SELECT id, public_id, name, username, email, password, role, email_verified, avatar_url, created_at, updated_at
             FROM users
             WHERE id = $1
             "#,
@@ -87,11 +148,14 @@ impl UserRepository {
             Some(row) => {
                 let user = User {
                     id: row.get("id"),
+                    public_id: row.get("public_id"),
                     name: row.get("name"),
+                    username: row.get("username"),
                     email: row.get("email"),
                     password: row.get("password"),
                     role: Role::from(row.get::<&str, _>("role")),
                     email_verified: row.get("email_verified"),
+                    avatar_url: row.get("avatar_url"),
                     created_at: row.get("created_at"),
                     updated_at: row.get("updated_at"),
                 };
@@ -106,11 +170,147 @@ impl UserRepository {
         }
     }
 
+    pub async fn find_by_public_id(&self, public_id: &str) -> Result<Option<User>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.find_by_public_id");
+
+        debug!("Finding user by public ID: {}", public_id);
+        let row = sqlx::query(
+            r#"
+            SELECT id, public_id, name, username, email, password, role, email_verified, avatar_url, created_at, updated_at
+            FROM users
+            WHERE public_id = $1
+            "#,
+        )
+        .bind(public_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let user = User {
+                    id: row.get("id"),
+                    public_id: row.get("public_id"),
+                    name: row.get("name"),
+                    username: row.get("username"),
+                    email: row.get("email"),
+                    password: row.get("password"),
+                    role: Role::from(row.get::<&str, _>("role")),
+                    email_verified: row.get("email_verified"),
+                    avatar_url: row.get("avatar_url"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                };
+
+                debug!("User found with public ID: {}", public_id);
+                Ok(Some(user))
+            }
+            None => {
+                debug!("No user found with public ID: {}", public_id);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Accounts pending deletion have no public profile
+    pub async fn find_public_profile(&self, public_id: &str) -> Result<Option<PublicProfile>> {
+        let key = profile_key(public_id);
+        if let Some(cache) = &self.cache
+            && let Some(profile) = cache.get::<PublicProfile>(&key).await
+        {
+            return Ok(Some(profile));
+        }
+
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.find_public_profile");
+
+        debug!("Finding public profile by public ID: {}", public_id);
+        let row = sqlx::query(
+            r#"
+            SELECT public_id, name, username, email, avatar_url, created_at
+            FROM users
+            WHERE public_id = $1 AND deletion_scheduled_at IS NULL
+            "#,
+        )
+        .bind(public_id)
+        .fetch_optional(&self.reads)
+        .await?;
+
+        let Some(row) = row else {
+            debug!("No public profile for public ID: {}", public_id);
+            return Ok(None);
+        };
+
+        let profile = PublicProfile {
+            id: row.get("public_id"),
+            name: row.get("name"),
+            username: row.get("username"),
+            avatar_url: avatar_url(row.get("avatar_url"), row.get("email")),
+            created_at: row.get("created_at"),
+        };
+
+        if let Some(cache) = &self.cache {
+            cache.set(&key, &profile).await;
+        }
+
+        Ok(Some(profile))
+    }
+
+    /// Number of accounts with a public profile, as listed in the sitemap
+    pub async fn count_public_profiles(&self) -> Result<i64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.count_public_profiles");
+
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM users WHERE deletion_scheduled_at IS NULL
+            "#,
+        )
+        .fetch_one(&self.reads)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Public ids and last changes of accounts with a public profile, oldest first
+    pub async fn find_public_profile_page(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<(String, DateTime<Utc>)>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.find_public_profile_page");
+
+        debug!("Listing {} public profiles from offset {}", limit, offset);
+        let rows = sqlx::query(
+            r#"
+            SELECT public_id, updated_at
+            FROM users
+            WHERE deletion_scheduled_at IS NULL
+            ORDER BY created_at, id
+            OFFSET $1
+            LIMIT $2
+            "#,
+        )
+        .bind(offset)
+        .bind(limit)
+        .fetch_all(&self.reads)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get("public_id"), row.get("updated_at")))
+            .collect())
+    }
+
     pub async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.find_by_email");
+
         debug!("Finding user by email: {}", email);
         let row = sqlx::query(
             r#"
-            SELECT id, name, email, password, role, email_verified, created_at, updated_at
+            SELECT id, public_id, name, username, email, password, role, email_verified, avatar_url, created_at, updated_at
             FROM users
             WHERE email = $1
             "#,
@@ -123,11 +323,14 @@ impl UserRepository {
             Some(row) => {
                 let user = User {
                     id: row.get("id"),
+                    public_id: row.get("public_id"),
                     name: row.get("name"),
+                    username: row.get("username"),
                     email: row.get("email"),
                     password: row.get("password"),
                     role: Role::from(row.get::<&str, _>("role")),
                     email_verified: row.get("email_verified"),
+                    avatar_url: row.get("avatar_url"),
                     created_at: row.get("created_at"),
                     updated_at: row.get("updated_at"),
                 };
@@ -142,11 +345,84 @@ impl UserRepository {
         }
     }
 
+    /// Usernames are matched case-insensitively
+    pub async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.find_by_username");
+
+        debug!("Finding user by username: {}", username);
+        let row = sqlx::query(
+            r#"
+            SELECT id, public_id, name, username, email, password, role, email_verified, avatar_url, created_at, updated_at
+            FROM users
+            WHERE LOWER(username) = LOWER($1)
+            "#,
+        )
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let user = User {
+                    id: row.get("id"),
+                    public_id: row.get("public_id"),
+                    name: row.get("name"),
+                    username: row.get("username"),
+                    email: row.get("email"),
+                    password: row.get("password"),
+                    role: Role::from(row.get::<&str, _>("role")),
+                    email_verified: row.get("email_verified"),
+                    avatar_url: row.get("avatar_url"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                };
+
+                debug!("User found with username: {}", username);
+                Ok(Some(user))
+            }
+            None => {
+                debug!("No user found with username: {}", username);
+                Ok(None)
+            }
+        }
+    }
+
+    pub async fn username_taken(&self, username: &str) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.username_taken");
+
+        let taken: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(username) = LOWER($1))
+            "#,
+        )
+        .bind(username)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(taken)
+    }
+
+    /// A username derived from `name`, made unique with a random suffix when
+    /// another account or a reserved name already has it
+    pub async fn free_username(&self, name: &str, config: &SlugConfig) -> Result<String> {
+        let username = suggest_username(name);
+        if is_reserved(&username, config) || self.username_taken(&username).await? {
+            return Ok(username_with_suffix(&username, &new_public_id()));
+        }
+        Ok(username)
+    }
+
     pub async fn update_user(
         &self,
         id: Uuid,
         update_data: UpdateUserRequest,
+        now: DateTime<Utc>,
     ) -> Result<(Option<User>, bool)> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.update_user");
+
         info!("Updating user with ID: {}", id);
 
         let mut email_updated = false;
@@ -159,7 +435,12 @@ impl UserRepository {
 
         if let Some(name) = update_data.name {
             user.name = name;
-            user.updated_at = Utc::now();
+            user.updated_at = now;
+        }
+
+        if let Some(username) = update_data.username {
+            user.username = Some(username);
+            user.updated_at = now;
         }
 
         if let Some(email) = update_data.email {
@@ -171,29 +452,112 @@ impl UserRepository {
             email_updated = true;
         }
 
+        if let Some(avatar_url) = update_data.avatar_url {
+            user.avatar_url = (!avatar_url.is_empty()).then_some(avatar_url);
+            user.updated_at = now;
+        }
+
         sqlx::query(
             r#"
             UPDATE users
-            SET name = $1, email = $2, email_verified = $3, updated_at = $4
-            WHERE id = $5
+            SET name = $1, username = $2, email = $3, email_verified = $4, avatar_url = $5, updated_at = $6,
+                email_delivery = CASE WHEN email = $3 THEN email_delivery END,
+                email_delivery_at = CASE WHEN email = $3 THEN email_delivery_at END
+            WHERE id = $7
             "#,
         )
         .bind(&user.name)
+        .bind(&user.username)
         .bind(&user.email)
         .bind(user.email_verified)
+        .bind(&user.avatar_url)
         .bind(user.updated_at)
         .bind(id)
         .execute(&self.pool)
         .await?;
+        self.invalidate(&user.public_id).await;
 
         Ok((Some(user), email_updated))
     }
 
+    /// Last delivery event reported for `email`; `None` when nothing was
+    /// reported or no account has the address
+    pub async fn email_delivery(&self, email: &str) -> Result<Option<EmailDelivery>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.email_delivery");
+
+        let delivery: Option<Option<String>> =
+            sqlx::query_scalar("SELECT email_delivery FROM users WHERE LOWER(email) = LOWER($1)")
+                .bind(email)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(delivery.flatten().as_deref().map(EmailDelivery::from))
+    }
+
+    /// Stores a delivery event for `email` unless a later one is already
+    /// stored, since webhooks may arrive out of order; returns how many
+    /// accounts it applied to
+    pub async fn record_email_delivery(
+        &self,
+        email: &str,
+        delivery: EmailDelivery,
+        at: DateTime<Utc>,
+    ) -> Result<u64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.record_email_delivery");
+
+        debug!("Recording {:?} for {}", delivery, email);
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET email_delivery = $1, email_delivery_at = $2
+            WHERE LOWER(email) = LOWER($3)
+              AND (email_delivery_at IS NULL OR email_delivery_at <= $2)
+            "#,
+        )
+        .bind(String::from(delivery))
+        .bind(at)
+        .bind(email)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Locale the user's emails are written in; `None` for the default
+    pub async fn locale(&self, id: Uuid) -> Result<Option<String>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.locale");
+
+        let locale: Option<Option<String>> =
+            sqlx::query_scalar("SELECT locale FROM users WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(locale.flatten())
+    }
+
+    pub async fn set_locale(&self, id: Uuid, locale: Option<&str>) -> Result<()> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.set_locale");
+
+        debug!("Setting locale of user {} to {:?}", id, locale);
+        sqlx::query("UPDATE users SET locale = $1 WHERE id = $2")
+            .bind(locale)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     pub async fn update_password(
         &self,
         id: Uuid,
         update_data: UpdatePasswordRequest,
+        now: DateTime<Utc>,
     ) -> Result<Option<User>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.update_password");
+
         info!("Updating password for user ID: {}", id);
 
         let existing_user = self.find_by_id(id).await?;
@@ -215,7 +579,7 @@ impl UserRepository {
         }
 
         if updated {
-            user.updated_at = Utc::now();
+            user.updated_at = now;
 
             sqlx::query(
                 r#"
@@ -237,47 +601,128 @@ impl UserRepository {
     }
 
     pub async fn delete_user(&self, id: Uuid) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.delete_user");
+
         info!("Deleting user with ID: {}", id);
-        let result = sqlx::query(
+        let row = sqlx::query(
             r#"
             DELETE FROM users
             WHERE id = $1
+            RETURNING public_id
             "#,
         )
         .bind(id)
-        .execute(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
 
-        if result.rows_affected() == 0 {
-            debug!("No user found to delete with ID: {}", id);
-            Ok(false)
-        } else {
-            debug!("User deleted with ID: {}", id);
-            Ok(true)
+        match row {
+            Some(row) => {
+                self.invalidate(row.get("public_id")).await;
+                debug!("User deleted with ID: {}", id);
+                Ok(true)
+            }
+            None => {
+                debug!("No user found to delete with ID: {}", id);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Gives the account the ADMIN role and marks its email verified
+    pub async fn make_admin(&self, id: Uuid, now: DateTime<Utc>) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.make_admin");
+
+        info!("Granting ADMIN role to user ID: {}", id);
+        let row = sqlx::query(
+            r#"
+            UPDATE users
+            SET role = $2, email_verified = TRUE, updated_at = $3
+            WHERE id = $1
+            RETURNING public_id
+            "#,
+        )
+        .bind(id)
+        .bind(String::from(Role::ADMIN))
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                self.invalidate(row.get("public_id")).await;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Marks the user's email verified or not without the emailed token;
+    /// `None` if there is no such user
+    pub async fn set_email_verified(
+        &self,
+        id: Uuid,
+        verified: bool,
+        now: DateTime<Utc>,
+    ) -> Result<Option<User>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.set_email_verified");
+
+        info!("Setting email_verified of user ID {} to {}", id, verified);
+        let row = sqlx::query(
+            r#"
+            UPDATE users
+            SET email_verified = $2, updated_at = $3
+            WHERE id = $1
+            RETURNING public_id
+            "#,
+        )
+        .bind(id)
+        .bind(verified)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                self.invalidate(row.get("public_id")).await;
+                self.find_by_id(id).await
+            }
+            None => Ok(None),
         }
     }
 
     pub async fn get_all_users(&self) -> Result<Vec<UserResponse>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.get_all_users");
+
         debug!("Fetching all users");
         let rows = sqlx::query(
             r#"
-            SELECT id, name, email, role, email_verified, created_at, updated_at
+            SELECT id, public_id, name, username, email, role, email_verified, avatar_url, created_at, updated_at, email_delivery
             FROM users
             "#,
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.reads)
         .await?;
 
         let users: Vec<UserResponse> = rows
             .into_iter()
             .map(|row| UserResponse {
-                id: row.get("id"),
+                internal_id: row.get("id"),
+                id: row.get("public_id"),
+                avatar_url: avatar_url(row.get("avatar_url"), row.get("email")),
                 name: row.get("name"),
+                username: row.get("username"),
                 email: row.get("email"),
                 role: Role::from(row.get::<&str, _>("role")),
                 email_verified: row.get("email_verified"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
+                email_delivery: row
+                    .get::<Option<&str>, _>("email_delivery")
+                    .map(EmailDelivery::from),
             })
             .collect();
 
@@ -289,7 +734,11 @@ impl UserRepository {
         &self,
         id: Uuid,
         new_hashed_password: String,
+        now: DateTime<Utc>,
     ) -> Result<Option<User>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.change_password");
+
         info!("Changing password for user ID: {}", id);
 
         let existing_user = self.find_by_id(id).await?;
@@ -299,7 +748,7 @@ impl UserRepository {
 
         let mut user = existing_user.unwrap();
         user.password = new_hashed_password;
-        user.updated_at = Utc::now();
+        user.updated_at = now;
 
         sqlx::query(
             r#"
@@ -318,7 +767,10 @@ impl UserRepository {
         Ok(Some(user))
     }
 
-    pub async fn verify_email(&self, id: Uuid) -> Result<Option<User>> {
+    pub async fn verify_email(&self, id: Uuid, now: DateTime<Utc>) -> Result<Option<User>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.verify_email");
+
         info!("Verifying Email for User: {}", id);
 
         let existing_user = self.find_by_id(id).await?;
@@ -335,7 +787,7 @@ impl UserRepository {
             WHERE id = $2
             "#,
         )
-        .bind(Utc::now())
+        .bind(now)
         .bind(user.id)
         .execute(&self.pool)
         .await?;
@@ -352,6 +804,9 @@ impl UserRepository {
     }
 
     pub async fn is_verified(&self, id: Uuid) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.is_verified");
+
         debug!("Checking if user ID: {} is verified", id);
         let row = sqlx::query(
             r#"
@@ -376,4 +831,321 @@ impl UserRepository {
             }
         }
     }
+
+    /// Suspensions that have run out read as `ACTIVE` even before the
+    /// background job lifts them
+    pub async fn standing(&self, id: Uuid, now: DateTime<Utc>) -> Result<Option<UserStanding>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.standing");
+
+        debug!("Checking standing of user ID: {}", id);
+        let row = sqlx::query(
+            r#"
+            SELECT public_id, status, suspended_until, status_reason
+            FROM users
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| {
+            UserStanding {
+                user_id: row.get("public_id"),
+                status: UserStatus::from(row.get::<&str, _>("status")),
+                suspended_until: row.get("suspended_until"),
+                reason: row.get("status_reason"),
+            }
+            .as_of(now)
+        }))
+    }
+
+    pub async fn set_standing(
+        &self,
+        id: Uuid,
+        status: UserStatus,
+        suspended_until: Option<DateTime<Utc>>,
+        reason: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.set_standing");
+
+        info!("Setting status of user ID: {} to {:?}", id, status);
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET status = $1, suspended_until = $2, status_reason = $3, updated_at = $4
+            WHERE id = $5
+            "#,
+        )
+        .bind(String::from(status))
+        .bind(suspended_until)
+        .bind(reason)
+        .bind(now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Reactivates accounts whose suspension has run out; returns how many
+    pub async fn lift_expired_suspensions(&self, now: DateTime<Utc>) -> Result<u64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.lift_expired_suspensions");
+
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET status = 'ACTIVE', suspended_until = NULL, status_reason = NULL, updated_at = $1
+            WHERE status = 'SUSPENDED' AND suspended_until <= $1
+            "#,
+        )
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            info!("Lifted {} expired suspension(s)", result.rows_affected());
+        }
+        Ok(result.rows_affected())
+    }
+
+    pub async fn schedule_deletion(
+        &self,
+        id: Uuid,
+        delete_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.schedule_deletion");
+
+        info!("Scheduling deletion of user ID: {} at {}", id, delete_at);
+        let row = sqlx::query(
+            r#"
+            UPDATE users
+            SET deletion_scheduled_at = $1, updated_at = $2
+            WHERE id = $3
+            RETURNING public_id
+            "#,
+        )
+        .bind(delete_at)
+        .bind(now)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                self.invalidate(row.get("public_id")).await;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Returns `true` when a pending deletion was actually cancelled
+    pub async fn cancel_deletion(&self, id: Uuid, now: DateTime<Utc>) -> Result<bool> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.cancel_deletion");
+
+        debug!("Cancelling scheduled deletion for user ID: {}", id);
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET deletion_scheduled_at = NULL, updated_at = $1
+            WHERE id = $2 AND deletion_scheduled_at IS NOT NULL
+            "#,
+        )
+        .bind(now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            info!("Scheduled deletion cancelled for user ID: {}", id);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub async fn deletion_scheduled_at(&self, id: Uuid) -> Result<Option<DateTime<Utc>>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.deletion_scheduled_at");
+
+        let row = sqlx::query(
+            r#"
+            SELECT deletion_scheduled_at
+            FROM users
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.and_then(|row| row.get("deletion_scheduled_at")))
+    }
+
+    /// Permanently removes accounts whose grace period ended, along with their posts
+    pub async fn purge_scheduled_deletions(&self, now: DateTime<Utc>) -> Result<u64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.purge_scheduled_deletions");
+
+        debug!("Purging accounts scheduled for deletion before {}", now);
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM posts
+            WHERE author_id IN (
+                SELECT id FROM users
+                WHERE deletion_scheduled_at IS NOT NULL AND deletion_scheduled_at <= $1
+            )
+            "#,
+        )
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query(
+            r#"
+            DELETE FROM users
+            WHERE deletion_scheduled_at IS NOT NULL AND deletion_scheduled_at <= $1
+            "#,
+        )
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let purged = result.rows_affected();
+        if purged > 0 {
+            info!(
+                "Purged {} accounts past their deletion grace period",
+                purged
+            );
+        }
+        Ok(purged)
+    }
+
+    /// Accounts created before `cutoff` that haven't verified their email,
+    /// oldest first
+    pub async fn find_pending_verifications(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<PendingVerification>> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.find_pending_verifications");
+
+        debug!("Finding unverified accounts created before {}", cutoff);
+        let rows = sqlx::query(
+            r#"
+            SELECT id, public_id, name, username, email, password, role, email_verified, avatar_url, created_at, updated_at,
+                locale, status = 'ACTIVE' AND deletion_scheduled_at IS NULL AS eligible
+            FROM users
+            WHERE email_verified = FALSE AND created_at < $1 AND anonymized_at IS NULL
+            ORDER BY created_at
+            "#,
+        )
+        .bind(cutoff)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PendingVerification {
+                user: User {
+                    id: row.get("id"),
+                    public_id: row.get("public_id"),
+                    name: row.get("name"),
+                    username: row.get("username"),
+                    email: row.get("email"),
+                    password: row.get("password"),
+                    role: Role::from(row.get::<&str, _>("role")),
+                    email_verified: row.get("email_verified"),
+                    avatar_url: row.get("avatar_url"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                },
+                locale: row.get("locale"),
+                eligible: row.get("eligible"),
+            })
+            .collect())
+    }
+
+    /// Deletes accounts created before `cutoff` that never verified their
+    /// email. Accounts with posts are kept, since those were verified once and
+    /// are only waiting on a changed address.
+    pub async fn delete_unverified_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.delete_unverified_before");
+
+        debug!("Deleting unverified accounts created before {}", cutoff);
+        let result = sqlx::query(
+            r#"
+            DELETE FROM users
+            WHERE email_verified = FALSE
+              AND created_at < $1
+              AND NOT EXISTS (SELECT 1 FROM posts WHERE posts.author_id = users.id)
+            "#,
+        )
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?;
+
+        let deleted = result.rows_affected();
+        if deleted > 0 {
+            info!("Deleted {} unverified accounts", deleted);
+        }
+        Ok(deleted)
+    }
+
+    /// Scrubs the name, email, username, password and avatar of accounts
+    /// created before `cutoff` that never verified their email. The email
+    /// becomes an unroutable placeholder, so the address can register again.
+    /// Accounts with posts are left alone, as they are by
+    /// [`Self::delete_unverified_before`]; that includes authors brought over
+    /// by a WordPress import, who stay unverified until they sign in.
+    pub async fn anonymize_unverified_before(
+        &self,
+        cutoff: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<u64> {
+        chaos::db_fault()?;
+        let _timer = QueryTimer::start("user_repo.anonymize_unverified_before");
+
+        debug!("Anonymizing unverified accounts created before {}", cutoff);
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET name = 'Deleted user',
+                email = 'deleted-' || public_id || '@invalid',
+                username = NULL,
+                password = '',
+                avatar_url = NULL,
+                locale = NULL,
+                anonymized_at = $2,
+                updated_at = $2
+            WHERE email_verified = FALSE
+              AND created_at < $1
+              AND anonymized_at IS NULL
+              AND NOT EXISTS (SELECT 1 FROM posts WHERE posts.author_id = users.id)
+            "#,
+        )
+        .bind(cutoff)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        let anonymized = result.rows_affected();
+        if anonymized > 0 {
+            info!("Anonymized {} unverified accounts", anonymized);
+        }
+        Ok(anonymized)
+    }
 }
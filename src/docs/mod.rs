@@ -0,0 +1,3 @@
+pub mod openapi;
+
+pub use openapi::{ApiDoc, export_openapi};
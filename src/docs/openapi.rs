@@ -0,0 +1,438 @@
+use utoipa::OpenApi;
+use utoipa::openapi::{RefOr, Schema};
+
+use crate::helpers::negotiation::ResponseFormat;
+use crate::helpers::versioning::{ApiVersion, DEFAULT_API_PREFIX};
+use crate::model::model::Limits;
+use crate::{cache, db, handlers, helpers, model};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::home_handlers::home,
+        handlers::home_handlers::get_limits,
+        handlers::home_handlers::liveness,
+        handlers::home_handlers::readiness,
+        handlers::feed_handlers::site_feed,
+        handlers::feed_handlers::user_feed,
+        handlers::sitemap_handlers::sitemap,
+        handlers::sitemap_handlers::sitemap_page,
+        handlers::webhook_handlers::resend_webhook,
+        handlers::auth_handlers::register_user,
+        handlers::auth_handlers::registration_form,
+        handlers::auth_handlers::login_user,
+        handlers::auth_handlers::username_available,
+        handlers::auth_handlers::password_strength,
+        handlers::auth_handlers::logout_user,
+        handlers::auth_handlers::stop_impersonation,
+        handlers::auth_handlers::get_profile,
+        handlers::auth_handlers::get_profile_stats,
+        handlers::auth_handlers::get_usage,
+        handlers::auth_handlers::get_public_profile,
+        handlers::auth_handlers::update_profile,
+        handlers::auth_handlers::patch_profile,
+        handlers::auth_handlers::change_password,
+        handlers::auth_handlers::list_sessions,
+        handlers::auth_handlers::revoke_session,
+        handlers::auth_handlers::revoke_session_link,
+        handlers::auth_handlers::confirm_revoke_session,
+        handlers::auth_handlers::delete_user_account,
+        handlers::auth_handlers::export_user_data,
+        handlers::auth_handlers::delete_user_admin,
+        handlers::auth_handlers::verify_email,
+        handlers::auth_handlers::get_all_users_admin,
+        handlers::auth_handlers::jwks,
+        handlers::admin_handlers::get_deprecation_stats,
+        handlers::admin_handlers::get_metrics,
+        handlers::admin_handlers::get_diagnostics,
+        handlers::admin_handlers::get_jobs,
+        handlers::admin_handlers::list_email_domain_rules,
+        handlers::admin_handlers::add_email_domain_rule,
+        handlers::admin_handlers::remove_email_domain_rule,
+        handlers::admin_handlers::get_feature_flags,
+        handlers::admin_handlers::update_feature_flags,
+        handlers::admin_handlers::get_maintenance,
+        handlers::admin_handlers::set_maintenance,
+        handlers::admin_handlers::import_wordpress,
+        handlers::admin_handlers::list_hook_deliveries,
+        handlers::admin_handlers::list_reports,
+        handlers::admin_handlers::resolve_report,
+        handlers::admin_handlers::get_audit_log,
+        handlers::admin_handlers::impersonate_user,
+        handlers::admin_handlers::suspend_user_admin,
+        handlers::admin_handlers::ban_user_admin,
+        handlers::admin_handlers::unban_user_admin,
+        handlers::admin_handlers::verify_email_admin,
+        handlers::admin_handlers::unverify_email_admin,
+        handlers::admin_handlers::resend_verifications,
+        handlers::post_handlers::create_post,
+        handlers::post_handlers::create_posts_bulk,
+        handlers::post_handlers::delete_post,
+        handlers::post_handlers::archive_post,
+        handlers::post_handlers::unarchive_post,
+        handlers::post_handlers::add_collaborator,
+        handlers::post_handlers::remove_collaborator,
+        handlers::post_handlers::create_share_link,
+        handlers::post_handlers::list_share_links,
+        handlers::post_handlers::revoke_share_link,
+        handlers::post_handlers::update_post,
+        handlers::post_handlers::patch_post,
+        handlers::post_handlers::get_all_posts,
+        handlers::post_handlers::get_user_posts,
+        handlers::post_handlers::export_my_posts,
+        handlers::post_handlers::import_posts,
+        handlers::post_handlers::get_media_missing_alt,
+        handlers::post_handlers::get_post,
+        handlers::post_handlers::export_post,
+        handlers::post_handlers::get_trending_posts,
+        handlers::post_handlers::report_post,
+        handlers::post_handlers::create_preview_token,
+        handlers::post_handlers::get_preview,
+        handlers::org_handlers::create_org,
+        handlers::org_handlers::list_orgs,
+        handlers::org_handlers::get_org,
+        handlers::org_handlers::update_org,
+        handlers::org_handlers::delete_org,
+        handlers::org_handlers::list_members,
+        handlers::org_handlers::add_member,
+        handlers::org_handlers::update_member,
+        handlers::org_handlers::remove_member,
+        handlers::notification_handlers::list_notifications,
+        handlers::notification_handlers::get_notification_summary,
+        handlers::notification_handlers::mark_notification_read,
+        handlers::notification_handlers::mark_all_notifications_read,
+    ),
+    components(schemas(
+        model::model::User,
+        model::model::CreateUserRequest,
+        model::model::UpdatePasswordRequest,
+        model::model::UpdateUserRequest,
+        model::model::PatchUserRequest,
+        model::model::LoginRequest,
+        model::model::UsernameAvailability,
+        model::model::PasswordStrengthRequest,
+        model::model::PasswordStrength,
+        model::model::LoginResponse,
+        model::model::UserResponse,
+        model::model::PublicProfile,
+        model::model::PublicAuthor,
+        model::model::Post,
+        model::model::CreatePostRequest,
+        model::model::UpdatePostRequest,
+        model::model::PatchPostRequest,
+        model::model::PostResponse,
+        model::model::PostStatus,
+        model::model::PostMention,
+        model::model::PostCollaborator,
+        model::model::CollaboratorRole,
+        model::model::Visibility,
+        model::model::AddCollaboratorRequest,
+        model::model::ShareLink,
+        model::model::CreatedShareLink,
+        model::model::CreateShareLinkRequest,
+        model::model::PostWithIncluded,
+        model::model::PostIncluded,
+        model::model::PostInclude,
+        model::model::PostMedia,
+        model::model::RelatedPost,
+        model::model::ContentFormat,
+        model::model::AccountExport,
+        model::model::ExportedSession,
+        model::model::ExportedMembership,
+        model::model::ExportedCollaboration,
+        model::model::ExportedShareLink,
+        model::model::ExportedLinkToken,
+        model::model::ExportedFollower,
+        model::model::ExportedReport,
+        model::model::SessionResponse,
+        model::model::HomeResponse,
+        model::model::Readiness,
+        model::model::DbStatus,
+        model::model::Limits,
+        model::model::OrgRole,
+        model::model::OrganizationResponse,
+        model::model::CreateOrganizationRequest,
+        model::model::UpdateOrganizationRequest,
+        model::model::MemberResponse,
+        model::model::AddMemberRequest,
+        model::model::UpdateMemberRequest,
+        helpers::deprecation::GoneEndpointStats,
+        helpers::jwt_keys::Jwk,
+        helpers::jwt_keys::JwkSet,
+        model::model::DomainRuleKind,
+        model::model::EmailDomainRule,
+        model::model::CreateEmailDomainRuleRequest,
+        model::model::FeatureFlagSet,
+        model::model::UpdateFeatureFlagsRequest,
+        model::model::SetMaintenanceRequest,
+        model::model::MaintenanceStatus,
+        helpers::wordpress::ImportReport,
+        model::model::HookDelivery,
+        model::model::MediaMissingAlt,
+        model::model::DeliveryStatus,
+        helpers::wordpress::ImportIssue,
+        helpers::post_archive::ArchiveFormat,
+        helpers::post_archive::PortablePost,
+        helpers::post_archive::PostArchive,
+        helpers::post_archive::ImportedItem,
+        helpers::post_archive::PostImportReport,
+        helpers::post_export::ExportFormat,
+        helpers::bulk_posts::BulkCreatePostsRequest,
+        helpers::bulk_posts::BulkPostResult,
+        helpers::bulk_posts::BulkCreateReport,
+        model::model::Metrics,
+        model::model::Diagnostics,
+        model::model::SchemaStatus,
+        model::model::PoolStats,
+        model::model::JobInfo,
+        model::model::DependencyCheck,
+        model::model::DependencyStatus,
+        model::model::Report,
+        model::model::ReportReason,
+        model::model::ReportStatus,
+        model::model::ReportAction,
+        model::model::CreateReportRequest,
+        model::model::ResolveReportRequest,
+        model::model::AuditEntry,
+        model::model::JobRun,
+        model::model::JobStatus,
+        model::model::ResendVerificationsRequest,
+        model::model::ResendVerificationsSummary,
+        model::model::ImpersonationResponse,
+        model::model::UserStatus,
+        model::model::UserStanding,
+        model::model::EmailDelivery,
+        model::model::SuspendUserRequest,
+        model::model::BanUserRequest,
+        model::model::Notification,
+        model::model::NotificationKind,
+        model::model::NotificationSummary,
+        helpers::previews::PreviewLink,
+        helpers::previews::PostMeta,
+        helpers::previews::PostPreview,
+        cache::CacheStats,
+        db::instrument::QueryStats,
+        db::instrument::LatencyBucket,
+        model::model::ProfileStats,
+        model::model::MonthlyPosts,
+        helpers::usage::Usage,
+        helpers::usage::UsageHour,
+        helpers::signup_guard::RegistrationForm,
+        helpers::signup_guard::CaptchaWidget,
+        model::model::ApiResponse<model::model::UserResponse>,
+        model::model::ApiResponse<model::model::LoginResponse>,
+        model::model::ApiResponse<model::model::PostResponse>,
+        model::model::ApiResponse<model::model::PostWithIncluded>,
+        model::model::ApiResponse<Vec<model::model::PostResponse>>,
+        model::model::ApiResponse<Vec<model::model::Post>>,
+        model::model::ErrorResponse,
+        model::model::LegacyErrorResponse,
+        helpers::response::UnifiedResponse<model::model::UserResponse>,
+        helpers::response::UnifiedResponse<model::model::LoginResponse>,
+        helpers::response::UnifiedResponse<model::model::PostResponse>,
+        helpers::response::UnifiedResponse<Vec<model::model::PostResponse>>,
+        helpers::response::UnifiedResponse<Vec<model::model::Post>>,
+        helpers::response::UnifiedResponse<Vec<model::model::UserResponse>>,
+    )),
+    tags(
+        (name = "Home", description = "API homepage"),
+        (name = "Authentication", description = "User authentication and profile management"),
+        (name = "Posts", description = "Blog post management operations"),
+        (name = "Feeds", description = "RSS and Atom feeds of the latest posts, and the sitemap"),
+        (name = "Organizations", description = "Organizations, memberships and per-org roles"),
+        (name = "Notifications", description = "In-app inbox of events about the caller's account and posts"),
+        (name = "Administration", description = "Admin-only operations for user management")
+    ),
+    info(
+        title = "Axum REST API",
+        version = "1.0.0",
+        description = "A REST API built with Axum framework for user authentication and blog post management. Supports both Bearer token and cookie-based authentication.",
+        contact(
+            name = "API Support",
+            email = "support@example.com"
+        ),
+        license(
+            name = "MIT",
+            url = "https://opensource.org/licenses/MIT"
+        )
+    ),
+    servers(
+        (url = "http://localhost:8080", description = "Local development server")
+    )
+)]
+pub struct ApiDoc;
+
+impl ApiDoc {
+    pub fn with_security() -> utoipa::openapi::OpenApi {
+        use utoipa::openapi::security::{
+            ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme,
+        };
+
+        let mut openapi = Self::openapi();
+        let components = openapi.components.as_mut().unwrap();
+
+        // Bearer token authentication
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("JWT token for API authentication. Include as: Authorization: Bearer <token>"))
+                    .build(),
+            ),
+        );
+
+        // Cookie authentication
+        components.add_security_scheme(
+            "cookie_auth",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("auth_token"))),
+        );
+
+        document_negotiated_types(&mut openapi);
+        openapi
+    }
+
+    /// Spec whose request schemas carry `limits` as `maxLength` constraints
+    pub fn with_limits(limits: &Limits) -> utoipa::openapi::OpenApi {
+        let mut openapi = Self::with_security();
+        let constraints = [
+            ("CreatePostRequest", "title", limits.max_title_length),
+            ("CreatePostRequest", "content", limits.max_content_length),
+            ("UpdatePostRequest", "title", limits.max_title_length),
+            ("UpdatePostRequest", "content", limits.max_content_length),
+            ("PatchPostRequest", "title", limits.max_title_length),
+            ("PatchPostRequest", "content", limits.max_content_length),
+            ("CreateUserRequest", "name", limits.max_name_length),
+            ("UpdateUserRequest", "name", limits.max_name_length),
+            ("PatchUserRequest", "name", limits.max_name_length),
+            (
+                "CreateReportRequest",
+                "details",
+                limits.max_report_details_length,
+            ),
+        ];
+
+        let schemas = &mut openapi.components.as_mut().unwrap().schemas;
+        for (schema, property, max_length) in constraints {
+            if let Some(RefOr::T(Schema::Object(object))) = schemas.get_mut(schema)
+                && let Some(RefOr::T(Schema::Object(property))) =
+                    object.properties.get_mut(property)
+            {
+                property.max_length = Some(max_length);
+            }
+        }
+        openapi
+    }
+
+    /// Spec for one API version, with `servers` pointing at its prefix under
+    /// `base_path`; the unversioned site routes keep a server of their own
+    pub fn for_version(
+        base_path: &str,
+        api_prefix: &str,
+        version: ApiVersion,
+        limits: &Limits,
+    ) -> utoipa::openapi::OpenApi {
+        use utoipa::openapi::server::Server;
+
+        let mut openapi = Self::with_limits(limits);
+        openapi.servers = Some(vec![Server::new(format!(
+            "{}{}{}",
+            base_path,
+            api_prefix,
+            version.segment()
+        ))]);
+
+        let site_root = if base_path.is_empty() { "/" } else { base_path };
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            if SITE_PATHS.contains(&path.as_str()) {
+                item.servers = Some(vec![Server::new(site_root)]);
+            }
+        }
+        openapi
+    }
+}
+
+/// Documented routes served at the base path rather than under an API version
+const SITE_PATHS: &[&str] = &[
+    "/",
+    "/health/live",
+    "/health/ready",
+    "/.well-known/jwks.json",
+    "/feeds/{file}",
+    "/feeds/users/{file}",
+    "/sitemap.xml",
+    "/sitemaps/{file}",
+    "/webhooks/resend",
+];
+
+/// Lists every negotiable content type next to `application/json` on
+/// success responses, and documents the 406 for an unsupported `Accept`
+fn document_negotiated_types(openapi: &mut utoipa::openapi::OpenApi) {
+    use utoipa::openapi::{Response, ResponseBuilder};
+
+    let json = ResponseFormat::Json.content_type();
+    let not_acceptable: Response = ResponseBuilder::new()
+        .description("Not Acceptable - `Accept` allows none of the supported response types")
+        .build();
+
+    for item in openapi.paths.paths.values_mut() {
+        let operations = [
+            &mut item.get,
+            &mut item.post,
+            &mut item.put,
+            &mut item.patch,
+            &mut item.delete,
+        ];
+        for operation in operations.into_iter().flatten() {
+            let mut negotiated = false;
+            for (status, response) in operation.responses.responses.iter_mut() {
+                let RefOr::T(response) = response else {
+                    continue;
+                };
+                if !status.starts_with('2') {
+                    continue;
+                }
+                let Some(content) = response.content.get(json).cloned() else {
+                    continue;
+                };
+                negotiated = true;
+                for format in &ResponseFormat::ALL[1..] {
+                    response
+                        .content
+                        .insert(format.content_type().to_string(), content.clone());
+                }
+            }
+            if negotiated {
+                operation
+                    .responses
+                    .responses
+                    .entry("406".to_string())
+                    .or_insert_with(|| RefOr::T(not_acceptable.clone()));
+            }
+        }
+    }
+}
+
+/// Writes the spec to `path` (or stdout for `-`), as YAML when the path ends in `.yaml`/`.yml`.
+/// Without config to read, the schemas carry the default limits.
+pub fn export_openapi(path: &str) -> anyhow::Result<()> {
+    let openapi = ApiDoc::for_version(
+        "",
+        DEFAULT_API_PREFIX,
+        ApiVersion::LATEST,
+        &Limits::default(),
+    );
+    let rendered = if path.ends_with(".yaml") || path.ends_with(".yml") {
+        openapi.to_yaml()?
+    } else {
+        openapi.to_pretty_json()?
+    };
+
+    if path == "-" {
+        println!("{}", rendered);
+    } else {
+        std::fs::write(path, rendered)?;
+    }
+    Ok(())
+}
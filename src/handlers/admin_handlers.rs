@@ -0,0 +1,1630 @@
+use crate::cache::Cache;
+use crate::config::AppConfig;
+use crate::db::db::{TABLES, missing_tables};
+use crate::db::instrument::query_stats;
+use crate::db::repositories::audit_repo::AuditRepository;
+use crate::db::repositories::email_domain_repo::EmailDomainRepository;
+use crate::db::repositories::feature_flag_repo::FeatureFlagRepository;
+use crate::db::repositories::hook_repo::HookRepository;
+use crate::db::repositories::post_repo::PostRepository;
+use crate::db::repositories::report_repo::ReportRepository;
+use crate::db::repositories::session_repo::SessionRepository;
+use crate::db::repositories::user_repo::UserRepository;
+use crate::helpers::auth::AuthHelper;
+use crate::helpers::clock::Clock;
+use crate::helpers::denylist::TokenDenylist;
+use crate::helpers::deprecation::{GoneEndpointStats, GoneEndpoints};
+use crate::helpers::diagnostics::{check_dependencies, config_summary, enabled_features};
+use crate::helpers::edge_cache::EdgeCache;
+use crate::helpers::email_policy::{EmailDomainPolicy, normalize_domain};
+use crate::helpers::feature_flags::FeatureFlags;
+use crate::helpers::json::Json;
+use crate::helpers::maintenance::MaintenanceGate;
+use crate::helpers::middleware::check_admin_role;
+use crate::helpers::notifications::{NotificationEvent, notify};
+use crate::helpers::path_params::ValidatedPath;
+use crate::helpers::redaction::{Audience, SeenBy};
+use crate::helpers::response::{
+    UnifiedResponse, error_response_generic, not_found_response_generic, sql_error_generic,
+    success_response,
+};
+use crate::helpers::verification::VerificationMailer;
+use crate::helpers::wordpress::{ImportReport, import_export, parse_wxr};
+use crate::jobs::runs::JobRuns;
+use crate::jobs::{scheduled_jobs, spawn_verification_resend};
+use crate::model::model::{
+    AuditEntry, BanUserRequest, CreateEmailDomainRuleRequest, Diagnostics, EmailDomainRule,
+    FeatureFlag, FeatureFlagSet, HookDelivery, ImpersonationResponse, JobStatus, MaintenanceStatus,
+    Metrics, PoolStats, Report, ReportAction, ReportStatus, ReportsQuery,
+    ResendVerificationsRequest, ResendVerificationsSummary, ResolveReportRequest, Role,
+    SchemaStatus, Session, SetMaintenanceRequest, SuspendUserRequest, UpdateFeatureFlagsRequest,
+    UserResponse, UserStanding, UserStatus,
+};
+use crate::state::AppState;
+use axum::extract::{Extension, Query, State};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+use utoipa;
+use uuid::Uuid;
+
+/// Get hit counts for removed endpoints (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/deprecations",
+    responses(
+        (status = 200, description = "Removed endpoints with their migration hints and hit counts", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<GoneEndpointStats>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn get_deprecation_stats(
+    State(gone): State<Arc<GoneEndpoints>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<Vec<GoneEndpointStats>> {
+    info!(
+        "Handler: Admin fetching deprecation stats, requested by user_id: {:?}",
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    success_response("Deprecation Stats Retrieved".to_string(), gone.stats())
+}
+
+/// Get runtime metrics such as cache hit rates and query latencies (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/metrics",
+    responses(
+        (status = 200, description = "Counters since the process started", body = inline(crate::helpers::response::ApiSuccessResponse<Metrics>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn get_metrics(
+    State(cache): State<Arc<Cache>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<Metrics> {
+    info!(
+        "Handler: Admin fetching metrics, requested by user_id: {:?}",
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    success_response(
+        "Metrics Retrieved".to_string(),
+        Metrics {
+            cache: cache.stats(),
+            queries: query_stats(),
+        },
+    )
+}
+
+/// Get the effective configuration, schema, pool, jobs and dependency health (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/diagnostics",
+    responses(
+        (status = 200, description = "Deployment snapshot with secrets masked", body = inline(crate::helpers::response::ApiSuccessResponse<Diagnostics>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn get_diagnostics(
+    State(state): State<AppState>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<Diagnostics> {
+    info!(
+        "Handler: Admin fetching diagnostics, requested by user_id: {:?}",
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let config = &state.config;
+    // A failed lookup reports every table missing rather than failing the snapshot
+    let missing_tables = missing_tables(&state.pool).await.unwrap_or_else(|e| {
+        error!("Handler: Failed to check the schema: {}", e);
+        TABLES.iter().map(|table| table.to_string()).collect()
+    });
+
+    let diagnostics = Diagnostics {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        environment: format!("{:?}", config.environment).to_lowercase(),
+        features: enabled_features(),
+        config: config_summary(config),
+        schema: SchemaStatus {
+            up_to_date: missing_tables.is_empty(),
+            missing_tables,
+        },
+        pool: PoolStats {
+            size: state.pool.size(),
+            idle: state.pool.num_idle(),
+            max_connections: config.database.max_connections,
+            acquire_timeout_secs: config.database.acquire_timeout.as_secs(),
+            health: state.health.readiness(),
+        },
+        jobs: scheduled_jobs(config),
+        dependencies: check_dependencies(&state).await,
+    };
+
+    success_response("Diagnostics Retrieved".to_string(), diagnostics)
+}
+
+/// List the recurring background jobs and how each last ran on this instance (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/jobs",
+    responses(
+        (status = 200, description = "Recurring jobs with their latest run", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<JobStatus>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn get_jobs(
+    State(config): State<Arc<AppConfig>>,
+    State(runs): State<Arc<JobRuns>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<Vec<JobStatus>> {
+    info!(
+        "Handler: Admin fetching job status, requested by user_id: {:?}",
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let jobs = scheduled_jobs(&config)
+        .into_iter()
+        .map(|job| JobStatus {
+            last_run: runs.last(&job.name),
+            job,
+        })
+        .collect();
+
+    success_response("Jobs Retrieved".to_string(), jobs)
+}
+
+/// List email domain rules applied at registration (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/email-domains",
+    responses(
+        (status = 200, description = "Allow and deny rules from configuration and the admin API", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<EmailDomainRule>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn list_email_domain_rules(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<Vec<EmailDomainRule>> {
+    info!(
+        "Handler: Admin listing email domain rules, requested by user_id: {:?}",
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    match EmailDomainPolicy::load(&pool, &config.accounts).await {
+        Ok(policy) => success_response(
+            "Email Domain Rules Retrieved".to_string(),
+            policy.rules().to_vec(),
+        ),
+        Err(e) => {
+            error!("Handler: Failed to load email domain rules: {}", e);
+            sql_error_generic(e, "Unable to retrieve email domain rules")
+        }
+    }
+}
+
+/// Allow or deny an email domain at runtime (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/email-domains",
+    request_body = CreateEmailDomainRuleRequest,
+    responses(
+        (status = 200, description = "Rule added, or switched to the given kind", body = inline(crate::helpers::response::ApiSuccessResponse<EmailDomainRule>)),
+        (status = 400, description = "Invalid domain", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn add_email_domain_rule(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    Json(payload): Json<CreateEmailDomainRuleRequest>,
+) -> UnifiedResponse<EmailDomainRule> {
+    info!(
+        "Handler: Admin setting email domain rule {:?} for {}, requested by user_id: {:?}",
+        payload.kind, payload.domain, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let Some(domain) = normalize_domain(&payload.domain) else {
+        return error_response_generic(
+            "Invalid Domain".to_string(),
+            "Provide a domain such as company.com".to_string(),
+        );
+    };
+
+    let repo = EmailDomainRepository::new((*pool).clone());
+
+    match repo.upsert_rule(&domain, payload.kind).await {
+        Ok(rule) => success_response("Email Domain Rule Saved".to_string(), rule),
+        Err(e) => {
+            error!("Handler: Failed to save email domain rule: {}", e);
+            sql_error_generic(e, "Unable to save email domain rule")
+        }
+    }
+}
+
+/// Remove a runtime email domain rule (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/email-domains/{domain}",
+    params(
+        ("domain" = String, Path, description = "Domain whose rule should be removed")
+    ),
+    responses(
+        (status = 200, description = "Rule removed", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "No runtime rule for this domain; configured rules can only be changed in the environment", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn remove_email_domain_rule(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    ValidatedPath(domain): ValidatedPath<String>,
+) -> UnifiedResponse<Value> {
+    info!(
+        "Handler: Admin removing email domain rule for {}, requested by user_id: {:?}",
+        domain, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let domain = normalize_domain(&domain).unwrap_or(domain);
+    let repo = EmailDomainRepository::new((*pool).clone());
+
+    match repo.remove_rule(&domain).await {
+        Ok(true) => success_response("Email Domain Rule Removed".to_string(), Value::Null),
+        Ok(false) => not_found_response_generic("Email domain rule not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to remove email domain rule: {}", e);
+            sql_error_generic(e, "Unable to remove email domain rule")
+        }
+    }
+}
+
+/// Current feature flags (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/feature-flags",
+    responses(
+        (status = 200, description = "Every flag and whether it is on", body = inline(crate::helpers::response::ApiSuccessResponse<FeatureFlagSet>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn get_feature_flags(
+    State(pool): State<Arc<PgPool>>,
+    State(flags): State<Arc<FeatureFlags>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<FeatureFlagSet> {
+    info!(
+        "Handler: Admin listing feature flags, requested by user_id: {:?}",
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    // Read through so a change made on another replica shows up right away
+    match FeatureFlagRepository::new((*pool).clone()).load().await {
+        Ok(loaded) => {
+            flags.replace(loaded);
+            success_response("Feature Flags Retrieved".to_string(), loaded)
+        }
+        Err(e) => {
+            error!("Handler: Failed to load feature flags: {}", e);
+            sql_error_generic(e, "Unable to retrieve feature flags")
+        }
+    }
+}
+
+/// Switch features on or off at runtime (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/feature-flags",
+    request_body = UpdateFeatureFlagsRequest,
+    responses(
+        (status = 200, description = "Flags after the change; other replicas pick it up within FEATURE_FLAG_REFRESH_SECS", body = inline(crate::helpers::response::ApiSuccessResponse<FeatureFlagSet>)),
+        (status = 400, description = "No flags given", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn update_feature_flags(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(flags): State<Arc<FeatureFlags>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    Json(payload): Json<UpdateFeatureFlagsRequest>,
+) -> UnifiedResponse<FeatureFlagSet> {
+    info!(
+        "Handler: Admin updating feature flags {:?}, requested by user_id: {:?}",
+        payload, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let changes = payload.changes();
+    if changes.is_empty() {
+        return error_response_generic(
+            "Invalid Feature Flags".to_string(),
+            "Provide at least one of registration, post_creation, email_sending or maintenance"
+                .to_string(),
+        );
+    }
+
+    let repo = FeatureFlagRepository::new((*pool).clone());
+    let audit = AuditRepository::new((*pool).clone());
+    for (flag, enabled) in changes {
+        if let Err(e) = repo.set(flag, enabled, user_id).await {
+            error!(
+                "Handler: Failed to save feature flag {}: {}",
+                flag.name(),
+                e
+            );
+            return sql_error_generic(e, "Unable to save feature flags");
+        }
+
+        let action = if enabled {
+            "feature_flag.enabled"
+        } else {
+            "feature_flag.disabled"
+        };
+        if let Err(e) = audit
+            .record(
+                user_id,
+                action,
+                &format!("feature_flag:{}", flag.name()),
+                None,
+                clock.now(),
+            )
+            .await
+        {
+            error!(
+                "Handler: Failed to record audit entry for feature flag {}: {}",
+                flag.name(),
+                e
+            );
+        }
+    }
+
+    match repo.load().await {
+        Ok(loaded) => {
+            flags.replace(loaded);
+            success_response("Feature Flags Updated".to_string(), loaded)
+        }
+        Err(e) => {
+            error!("Handler: Failed to reload feature flags: {}", e);
+            sql_error_generic(e, "Unable to retrieve feature flags")
+        }
+    }
+}
+
+/// Whether maintenance mode is on (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/maintenance",
+    responses(
+        (status = 200, description = "Maintenance mode status", body = inline(crate::helpers::response::ApiSuccessResponse<MaintenanceStatus>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn get_maintenance(
+    State(gate): State<MaintenanceGate>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<MaintenanceStatus> {
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    success_response("Maintenance Status Retrieved".to_string(), gate.status())
+}
+
+/// Turn maintenance mode on or off; while on, writes by non-admins answer 503 (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/maintenance",
+    request_body = SetMaintenanceRequest,
+    responses(
+        (status = 200, description = "Maintenance mode after the change; it stays on while MAINTENANCE_MODE is set", body = inline(crate::helpers::response::ApiSuccessResponse<MaintenanceStatus>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn set_maintenance(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(gate): State<MaintenanceGate>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    Json(payload): Json<SetMaintenanceRequest>,
+) -> UnifiedResponse<MaintenanceStatus> {
+    info!(
+        "Handler: Admin setting maintenance mode to {}, requested by user_id: {:?}",
+        payload.enabled, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let repo = FeatureFlagRepository::new((*pool).clone());
+    if let Err(e) = repo
+        .set(FeatureFlag::Maintenance, payload.enabled, user_id)
+        .await
+    {
+        error!("Handler: Failed to save maintenance mode: {}", e);
+        return sql_error_generic(e, "Unable to change maintenance mode");
+    }
+
+    let action = if payload.enabled {
+        "maintenance.started"
+    } else {
+        "maintenance.ended"
+    };
+    if let Err(e) = AuditRepository::new((*pool).clone())
+        .record(user_id, action, "maintenance", None, clock.now())
+        .await
+    {
+        error!(
+            "Handler: Failed to record audit entry for maintenance: {}",
+            e
+        );
+    }
+
+    match repo.load().await {
+        Ok(loaded) => {
+            gate.flags.replace(loaded);
+            success_response("Maintenance Mode Updated".to_string(), gate.status())
+        }
+        Err(e) => {
+            error!("Handler: Failed to reload feature flags: {}", e);
+            sql_error_generic(e, "Unable to retrieve maintenance mode")
+        }
+    }
+}
+
+/// Import authors and posts from a WordPress WXR export (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/import/wordpress",
+    request_body(content = String, description = "WordPress WXR export (Tools → Export)", content_type = "application/xml"),
+    responses(
+        (status = 200, description = "Migration report: what was imported, matched and left out", body = inline(crate::helpers::response::ApiSuccessResponse<ImportReport>)),
+        (status = 400, description = "Body is not a WXR export", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn import_wordpress(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    body: String,
+) -> UnifiedResponse<ImportReport> {
+    info!(
+        "Handler: Admin importing WordPress export ({} bytes), requested by user_id: {:?}",
+        body.len(),
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let export = match parse_wxr(&body) {
+        Ok(export) => export,
+        Err(e) => {
+            error!("Handler: Invalid WordPress export: {:#}", e);
+            return error_response_generic("Invalid Export".to_string(), format!("{:#}", e));
+        }
+    };
+
+    match import_export(&pool, cache, &config, export, clock.now()).await {
+        Ok(report) => success_response("WordPress Import Finished".to_string(), report),
+        Err(e) => {
+            error!("Handler: WordPress import failed: {}", e);
+            sql_error_generic(e, "Unable to import WordPress export")
+        }
+    }
+}
+
+/// Recent publish hook deliveries with their retry state (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/hook-deliveries",
+    responses(
+        (status = 200, description = "The 100 most recent deliveries, newest first", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<HookDelivery>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn list_hook_deliveries(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<Vec<HookDelivery>> {
+    info!(
+        "Handler: Admin listing hook deliveries, requested by user_id: {:?}",
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let repo = HookRepository::new((*pool).clone());
+
+    match repo.list_recent(100).await {
+        Ok(deliveries) => success_response("Hook Deliveries Retrieved".to_string(), deliveries),
+        Err(e) => {
+            error!("Handler: Failed to list hook deliveries: {}", e);
+            sql_error_generic(e, "Unable to retrieve hook deliveries")
+        }
+    }
+}
+
+/// The moderation queue: reports with the given status, oldest first (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/reports",
+    params(
+        ("status" = Option<ReportStatus>, Query, description = "Defaults to `OPEN`")
+    ),
+    responses(
+        (status = 200, description = "Up to 100 reports, oldest first", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<Report>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn list_reports(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    Query(query): Query<ReportsQuery>,
+) -> UnifiedResponse<Vec<Report>> {
+    info!(
+        "Handler: Admin listing {:?} reports, requested by user_id: {:?}",
+        query.status, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let repo = ReportRepository::new((*pool).clone());
+
+    match repo.list(query.status, 100).await {
+        Ok(reports) => success_response("Reports Retrieved".to_string(), reports),
+        Err(e) => {
+            error!("Handler: Failed to list reports: {}", e);
+            sql_error_generic(e, "Unable to retrieve reports")
+        }
+    }
+}
+
+/// Resolve a report by dismissing it, deleting the post or suspending its author (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/reports/{id}/resolve",
+    params(
+        ("id" = Uuid, Path, description = "Report to resolve")
+    ),
+    request_body = ResolveReportRequest,
+    responses(
+        (status = 200, description = "Report resolved; deleting the post also closes every other open report on it", body = inline(crate::helpers::response::ApiSuccessResponse<Report>)),
+        (status = 400, description = "The id is not a UUID, the report is already resolved, or its author no longer exists", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Report not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn resolve_report(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    State(denylist): State<Arc<TokenDenylist>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    ValidatedPath(id): ValidatedPath<Uuid>,
+    Json(payload): Json<ResolveReportRequest>,
+) -> UnifiedResponse<Report> {
+    info!(
+        "Handler: Admin resolving report {} with {:?}, requested by user_id: {:?}",
+        id, payload.action, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let reports = ReportRepository::new((*pool).clone());
+
+    let report = match reports.find_by_id(id).await {
+        Ok(Some(report)) if report.status == ReportStatus::OPEN => report,
+        Ok(Some(_)) => {
+            return error_response_generic(
+                "Report Already Resolved".to_string(),
+                "This report has already been resolved".to_string(),
+            );
+        }
+        Ok(None) => return not_found_response_generic("Report not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up report: {}", e);
+            return sql_error_generic(e, "Unable to resolve report");
+        }
+    };
+
+    let now = clock.now();
+    let note = payload
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+
+    let (audit_action, audit_target) = match payload.action {
+        ReportAction::DISMISS => ("report.dismissed", format!("report:{}", report.id)),
+        ReportAction::DELETE => {
+            let posts = PostRepository::new((*pool).clone()).with_cache(cache);
+            // Already gone counts as deleted; the report is closed either way
+            if let Err(e) = posts.remove_post(report.target_internal_id).await {
+                error!("Handler: Failed to delete reported post: {}", e);
+                return sql_error_generic(e, "Unable to delete reported post");
+            }
+            edge.purge_post(&report.target_id);
+            (
+                "report.content_deleted",
+                format!("post:{}", report.target_id),
+            )
+        }
+        ReportAction::SUSPEND => {
+            let (Some(author_id), Some(author_public_id)) =
+                (report.author_internal_id, report.author_id.as_deref())
+            else {
+                return error_response_generic(
+                    "Author Not Found".to_string(),
+                    "The author of the reported post no longer exists".to_string(),
+                );
+            };
+            if let Err(e) = restrict_user(
+                &pool,
+                &denylist,
+                author_id,
+                UserStatus::SUSPENDED,
+                None,
+                note,
+                now,
+            )
+            .await
+            {
+                error!("Handler: Failed to suspend user {}: {}", author_id, e);
+                return sql_error_generic(e, "Unable to suspend author");
+            }
+            (
+                "report.author_suspended",
+                format!("user:{}", author_public_id),
+            )
+        }
+    };
+
+    let whole_target = payload.action == ReportAction::DELETE;
+    let reporters = match reports
+        .resolve(&report, payload.action, note, user_id, whole_target, now)
+        .await
+    {
+        Ok(reporters) => reporters,
+        Err(e) => {
+            error!("Handler: Failed to resolve report {}: {}", id, e);
+            return sql_error_generic(e, "Unable to resolve report");
+        }
+    };
+
+    for reporter_id in reporters {
+        let event = NotificationEvent::ReportResolved {
+            post_id: &report.target_id,
+            action: payload.action,
+        };
+        notify(&pool, reporter_id, event, now).await;
+    }
+    if let Some(author_id) = report.author_internal_id {
+        let event = match payload.action {
+            ReportAction::DISMISS => None,
+            ReportAction::DELETE => Some(NotificationEvent::PostRemoved {
+                post_id: &report.target_id,
+                reason: note,
+            }),
+            ReportAction::SUSPEND => Some(NotificationEvent::AccountSuspended {
+                until: None,
+                reason: note,
+            }),
+        };
+        if let Some(event) = event {
+            notify(&pool, author_id, event, now).await;
+        }
+    }
+
+    let audit = AuditRepository::new((*pool).clone());
+    if let Err(e) = audit
+        .record(user_id, audit_action, &audit_target, note, now)
+        .await
+    {
+        error!(
+            "Handler: Failed to record audit entry for report {}: {}",
+            id, e
+        );
+    }
+
+    match reports.find_by_id(id).await {
+        Ok(Some(report)) => success_response("Report Resolved".to_string(), report),
+        Ok(None) => not_found_response_generic("Report not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to retrieve resolved report: {}", e);
+            sql_error_generic(e, "Unable to retrieve report")
+        }
+    }
+}
+
+/// Longest suspension or ban reason accepted
+const MAX_STATUS_REASON_LENGTH: usize = 500;
+
+/// Restricts the user and signs them out of every session
+async fn restrict_user(
+    pool: &PgPool,
+    denylist: &TokenDenylist,
+    user_id: Uuid,
+    status: UserStatus,
+    until: Option<DateTime<Utc>>,
+    reason: Option<&str>,
+    now: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    UserRepository::new(pool.clone())
+        .set_standing(user_id, status, until, reason, now)
+        .await?;
+    let revoked = SessionRepository::new(pool.clone())
+        .revoke_all_except(user_id, None, now)
+        .await?;
+    denylist.deny(&revoked).await;
+    Ok(())
+}
+
+/// Trims the reason and drops it when empty; returns the message to show when it is too long
+fn status_reason(reason: Option<&str>) -> Result<Option<&str>, String> {
+    let reason = reason.map(str::trim).filter(|reason| !reason.is_empty());
+    if reason.is_some_and(|reason| reason.chars().count() > MAX_STATUS_REASON_LENGTH) {
+        return Err(format!(
+            "Reason can be at most {} characters",
+            MAX_STATUS_REASON_LENGTH
+        ));
+    }
+    Ok(reason)
+}
+
+/// Resolves the user an admin acts on; admins cannot restrict themselves
+async fn moderated_user<T>(
+    pool: &PgPool,
+    admin_id: Uuid,
+    public_id: &str,
+) -> Result<Uuid, UnifiedResponse<T>> {
+    match UserRepository::new(pool.clone())
+        .find_by_public_id(public_id)
+        .await
+    {
+        Ok(Some(user)) if user.id == admin_id => Err(error_response_generic(
+            "Invalid Operation".to_string(),
+            "Admins cannot change the status of their own account".to_string(),
+        )),
+        Ok(Some(user)) => Ok(user.id),
+        Ok(None) => Err(not_found_response_generic("User not found".to_string())),
+        Err(e) => {
+            error!("Handler: Failed to look up user {}: {}", public_id, e);
+            Err(sql_error_generic(e, "Unable to find user account"))
+        }
+    }
+}
+
+/// Records the change and returns the user's new standing
+async fn standing_changed(
+    pool: &PgPool,
+    admin_id: Uuid,
+    user_id: Uuid,
+    action: &str,
+    reason: Option<&str>,
+    now: DateTime<Utc>,
+) -> UnifiedResponse<UserStanding> {
+    let standing = match UserRepository::new(pool.clone())
+        .standing(user_id, now)
+        .await
+    {
+        Ok(Some(standing)) => standing,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to retrieve standing of {}: {}", user_id, e);
+            return sql_error_generic(e, "Unable to retrieve account status");
+        }
+    };
+
+    let audit = AuditRepository::new(pool.clone());
+    if let Err(e) = audit
+        .record(
+            admin_id,
+            action,
+            &format!("user:{}", standing.user_id),
+            reason,
+            now,
+        )
+        .await
+    {
+        error!(
+            "Handler: Failed to record audit entry for {}: {}",
+            action, e
+        );
+    }
+
+    success_response("Account Status Updated".to_string(), standing)
+}
+
+/// Suspend a user, until a given time or until lifted (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/suspend",
+    params(
+        ("user_id" = String, Path, description = "Public ID of the user to suspend")
+    ),
+    request_body = SuspendUserRequest,
+    responses(
+        (status = 200, description = "User suspended and signed out everywhere", body = inline(crate::helpers::response::ApiSuccessResponse<UserStanding>)),
+        (status = 400, description = "End time in the past, reason too long, or the admin's own account", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn suspend_user_admin(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(denylist): State<Arc<TokenDenylist>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    ValidatedPath(target_public_id): ValidatedPath<String>,
+    Json(payload): Json<SuspendUserRequest>,
+) -> UnifiedResponse<UserStanding> {
+    info!(
+        "Handler: Admin suspending user {} until {:?}, requested by user_id: {:?}",
+        target_public_id, payload.until, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let now = clock.now();
+    if payload.until.is_some_and(|until| until <= now) {
+        return error_response_generic(
+            "Validation Error".to_string(),
+            "Suspension end must be in the future".to_string(),
+        );
+    }
+    let reason = match status_reason(payload.reason.as_deref()) {
+        Ok(reason) => reason,
+        Err(message) => return error_response_generic("Validation Error".to_string(), message),
+    };
+
+    let target_id = match moderated_user(&pool, user_id, &target_public_id).await {
+        Ok(target_id) => target_id,
+        Err(response) => return response,
+    };
+
+    if let Err(e) = restrict_user(
+        &pool,
+        &denylist,
+        target_id,
+        UserStatus::SUSPENDED,
+        payload.until,
+        reason,
+        now,
+    )
+    .await
+    {
+        error!("Handler: Failed to suspend user {}: {}", target_id, e);
+        return sql_error_generic(e, "Unable to suspend user");
+    }
+
+    let event = NotificationEvent::AccountSuspended {
+        until: payload.until,
+        reason,
+    };
+    notify(&pool, target_id, event, now).await;
+
+    standing_changed(&pool, user_id, target_id, "user.suspended", reason, now).await
+}
+
+/// Ban a user until an admin lifts it (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/ban",
+    params(
+        ("user_id" = String, Path, description = "Public ID of the user to ban")
+    ),
+    request_body = BanUserRequest,
+    responses(
+        (status = 200, description = "User banned and signed out everywhere", body = inline(crate::helpers::response::ApiSuccessResponse<UserStanding>)),
+        (status = 400, description = "Reason too long, or the admin's own account", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn ban_user_admin(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(denylist): State<Arc<TokenDenylist>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    ValidatedPath(target_public_id): ValidatedPath<String>,
+    Json(payload): Json<BanUserRequest>,
+) -> UnifiedResponse<UserStanding> {
+    info!(
+        "Handler: Admin banning user {}, requested by user_id: {:?}",
+        target_public_id, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let reason = match status_reason(payload.reason.as_deref()) {
+        Ok(reason) => reason,
+        Err(message) => return error_response_generic("Validation Error".to_string(), message),
+    };
+
+    let target_id = match moderated_user(&pool, user_id, &target_public_id).await {
+        Ok(target_id) => target_id,
+        Err(response) => return response,
+    };
+
+    let now = clock.now();
+    if let Err(e) = restrict_user(
+        &pool,
+        &denylist,
+        target_id,
+        UserStatus::BANNED,
+        None,
+        reason,
+        now,
+    )
+    .await
+    {
+        error!("Handler: Failed to ban user {}: {}", target_id, e);
+        return sql_error_generic(e, "Unable to ban user");
+    }
+
+    notify(
+        &pool,
+        target_id,
+        NotificationEvent::AccountBanned { reason },
+        now,
+    )
+    .await;
+
+    standing_changed(&pool, user_id, target_id, "user.banned", reason, now).await
+}
+
+/// Lift a user's suspension or ban (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/unban",
+    params(
+        ("user_id" = String, Path, description = "Public ID of the user to reinstate")
+    ),
+    responses(
+        (status = 200, description = "User reinstated; they can sign in again", body = inline(crate::helpers::response::ApiSuccessResponse<UserStanding>)),
+        (status = 400, description = "The admin's own account", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn unban_user_admin(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    ValidatedPath(target_public_id): ValidatedPath<String>,
+) -> UnifiedResponse<UserStanding> {
+    info!(
+        "Handler: Admin reinstating user {}, requested by user_id: {:?}",
+        target_public_id, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let target_id = match moderated_user(&pool, user_id, &target_public_id).await {
+        Ok(target_id) => target_id,
+        Err(response) => return response,
+    };
+
+    let repo = UserRepository::new((*pool).clone());
+    if let Err(e) = repo
+        .set_standing(target_id, UserStatus::ACTIVE, None, None, clock.now())
+        .await
+    {
+        error!("Handler: Failed to reinstate user {}: {}", target_id, e);
+        return sql_error_generic(e, "Unable to reinstate user");
+    }
+
+    let now = clock.now();
+    notify(&pool, target_id, NotificationEvent::AccountReinstated, now).await;
+
+    standing_changed(&pool, user_id, target_id, "user.reinstated", None, now).await
+}
+
+/// Mark a user's email verified without the emailed link (Admin only)
+///
+/// For support cases where the verification email never arrived; the user
+/// can sign in straight away
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/verify-email",
+    params(
+        ("user_id" = String, Path, description = "Public ID of the user")
+    ),
+    responses(
+        (status = 200, description = "Email marked verified", body = inline(crate::helpers::response::ApiSuccessResponse<UserResponse>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn verify_email_admin(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    ValidatedPath(target_public_id): ValidatedPath<String>,
+) -> UnifiedResponse<SeenBy<UserResponse>> {
+    info!(
+        "Handler: Admin verifying email of user {}, requested by user_id: {:?}",
+        target_public_id, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    set_email_verified(&pool, clock.now(), user_id, &target_public_id, true).await
+}
+
+/// Mark a user's email unverified (Admin only)
+///
+/// The user can't sign in again until they follow a new verification link;
+/// sessions they already have keep working
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/unverify-email",
+    params(
+        ("user_id" = String, Path, description = "Public ID of the user")
+    ),
+    responses(
+        (status = 200, description = "Email marked unverified", body = inline(crate::helpers::response::ApiSuccessResponse<UserResponse>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn unverify_email_admin(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    ValidatedPath(target_public_id): ValidatedPath<String>,
+) -> UnifiedResponse<SeenBy<UserResponse>> {
+    info!(
+        "Handler: Admin unverifying email of user {}, requested by user_id: {:?}",
+        target_public_id, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    set_email_verified(&pool, clock.now(), user_id, &target_public_id, false).await
+}
+
+/// Sets the verification state and records it in the audit log
+async fn set_email_verified(
+    pool: &PgPool,
+    now: DateTime<Utc>,
+    admin_id: Uuid,
+    public_id: &str,
+    verified: bool,
+) -> UnifiedResponse<SeenBy<UserResponse>> {
+    let repo = UserRepository::new(pool.clone());
+    let target = match repo.find_by_public_id(public_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up user {}: {}", public_id, e);
+            return sql_error_generic(e, "Unable to find user account");
+        }
+    };
+
+    let user = match repo.set_email_verified(target.id, verified, now).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!(
+                "Handler: Failed to update email verification of {}: {}",
+                target.id, e
+            );
+            return sql_error_generic(e, "Unable to update email verification");
+        }
+    };
+
+    let action = if verified {
+        "user.email_verified"
+    } else {
+        "user.email_unverified"
+    };
+    if let Err(e) = AuditRepository::new(pool.clone())
+        .record(
+            admin_id,
+            action,
+            &format!("user:{}", user.public_id),
+            None,
+            now,
+        )
+        .await
+    {
+        error!(
+            "Handler: Failed to record audit entry for {}: {}",
+            action, e
+        );
+    }
+
+    success_response(
+        "Email Verification Updated".to_string(),
+        SeenBy(
+            Audience::user(admin_id, Role::ADMIN),
+            UserResponse::from(user),
+        ),
+    )
+}
+
+/// Resend verification emails to unverified accounts (Admin only)
+///
+/// Emails go out in the background, in batches of
+/// `VERIFICATION_RESEND_BATCH_SIZE` spaced `VERIFICATION_RESEND_BATCH_INTERVAL_SECS`
+/// apart; the response only says how many were queued.
+#[utoipa::path(
+    post,
+    path = "/admin/users/resend-verifications",
+    request_body = ResendVerificationsRequest,
+    responses(
+        (status = 200, description = "How many accounts will get an email and how many were skipped", body = inline(crate::helpers::response::ApiSuccessResponse<ResendVerificationsSummary>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 503, description = "Email sending is switched off", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn resend_verifications(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(sender): State<VerificationMailer>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    Json(payload): Json<ResendVerificationsRequest>,
+) -> UnifiedResponse<ResendVerificationsSummary> {
+    info!(
+        "Handler: Admin resending verification emails, requested by user_id: {:?}",
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+    if let Err(err) = sender.flags.require(FeatureFlag::EmailSending) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let email = &sender.config.email;
+    let min_age = match payload.older_than_hours {
+        Some(hours) => chrono::Duration::hours(hours.into()),
+        None => chrono::Duration::seconds(email.resend_min_age.as_secs() as i64),
+    };
+    let now = clock.now();
+
+    let pending = match UserRepository::new((*pool).clone())
+        .find_pending_verifications(now - min_age)
+        .await
+    {
+        Ok(pending) => pending,
+        Err(e) => {
+            error!("Handler: Failed to find unverified accounts: {}", e);
+            return sql_error_generic(e, "Unable to find unverified accounts");
+        }
+    };
+    let (queued, skipped): (Vec<_>, Vec<_>) =
+        pending.into_iter().partition(|account| account.eligible);
+
+    let summary = ResendVerificationsSummary {
+        queued: queued.len(),
+        skipped: skipped.len(),
+        batch_size: email.resend_batch_size,
+        batch_interval_secs: email.resend_batch_interval.as_secs(),
+    };
+    let detail = format!("queued {}, skipped {}", summary.queued, summary.skipped);
+    if let Err(e) = AuditRepository::new((*pool).clone())
+        .record(
+            user_id,
+            "users.verification_resent",
+            "users",
+            Some(detail.as_str()),
+            now,
+        )
+        .await
+    {
+        error!(
+            "Handler: Failed to record audit entry for verification resend: {}",
+            e
+        );
+    }
+
+    if !queued.is_empty() {
+        spawn_verification_resend(
+            sender.clone(),
+            queued,
+            summary.batch_size,
+            email.resend_batch_interval,
+        );
+    }
+
+    success_response("Verification Emails Queued".to_string(), summary)
+}
+
+/// Recent administrative actions, newest first (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/audit-log",
+    responses(
+        (status = 200, description = "The 100 most recent entries, newest first", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<AuditEntry>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn get_audit_log(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<Vec<AuditEntry>> {
+    info!(
+        "Handler: Admin fetching audit log, requested by user_id: {:?}",
+        user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let repo = AuditRepository::new((*pool).clone());
+
+    match repo.list_recent(100).await {
+        Ok(entries) => success_response("Audit Log Retrieved".to_string(), entries),
+        Err(e) => {
+            error!("Handler: Failed to retrieve audit log: {}", e);
+            sql_error_generic(e, "Unable to retrieve audit log")
+        }
+    }
+}
+
+/// Act as a user to reproduce a support issue (Admin only)
+///
+/// The token is short-lived, has no refresh token and shows up in the user's
+/// sessions. Every request made with it is recorded in the audit log.
+#[utoipa::path(
+    post,
+    path = "/admin/impersonate/{user_id}",
+    params(
+        ("user_id" = String, Path, description = "Public ID of the user to act as")
+    ),
+    responses(
+        (status = 200, description = "Bearer token acting as the user, valid for IMPERSONATION_TTL_SECS", body = inline(crate::helpers::response::ApiSuccessResponse<ImpersonationResponse>)),
+        (status = 400, description = "The admin's own account or another admin", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Administration"
+)]
+pub async fn impersonate_user(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+    ValidatedPath(target_public_id): ValidatedPath<String>,
+) -> UnifiedResponse<ImpersonationResponse> {
+    info!(
+        "Handler: Admin impersonating user {}, requested by user_id: {:?}",
+        target_public_id, user_id
+    );
+
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let target = match UserRepository::new((*pool).clone())
+        .find_by_public_id(&target_public_id)
+        .await
+    {
+        Ok(Some(target)) => target,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!(
+                "Handler: Failed to look up user {}: {}",
+                target_public_id, e
+            );
+            return sql_error_generic(e, "Unable to find user account");
+        }
+    };
+    // Acting as another admin would hand out their privileges
+    if target.id == user_id || target.role == Role::ADMIN {
+        return error_response_generic(
+            "Invalid Operation".to_string(),
+            "Admins cannot impersonate themselves or other admins".to_string(),
+        );
+    }
+
+    let now = clock.now();
+    let ttl = chrono::Duration::from_std(config.impersonation_ttl)
+        .unwrap_or_else(|_| chrono::Duration::minutes(15));
+    let session = Session {
+        id: Uuid::new_v4().simple().to_string(),
+        user_id: target.id,
+        device: "Support access by an administrator".to_string(),
+        ip: None,
+        user_agent: None,
+        network: None,
+        country: None,
+        created_at: now,
+        last_seen_at: now,
+        expires_at: now + ttl,
+    };
+    if let Err(e) = SessionRepository::new((*pool).clone())
+        .create_session(&session)
+        .await
+    {
+        error!("Handler: Failed to create impersonation session: {}", e);
+        return sql_error_generic(e, "Unable to start impersonation");
+    }
+
+    let token = match AuthHelper::generate_impersonation_token(
+        target.id,
+        target.role.clone(),
+        user_id,
+        &session.id,
+        ttl,
+        clock.as_ref(),
+    ) {
+        Ok(token) => token,
+        Err(e) => {
+            error!("Handler: Failed to sign impersonation token: {}", e);
+            return error_response_generic(
+                "Impersonation Failed".to_string(),
+                "Unable to issue an impersonation token".to_string(),
+            );
+        }
+    };
+
+    let audit = AuditRepository::new((*pool).clone());
+    if let Err(e) = audit
+        .record(
+            user_id,
+            "impersonation.started",
+            &format!("user:{}", target.public_id),
+            None,
+            now,
+        )
+        .await
+    {
+        error!(
+            "Handler: Failed to record audit entry for impersonation: {}",
+            e
+        );
+    }
+
+    success_response(
+        "Impersonation Started".to_string(),
+        ImpersonationResponse {
+            auth_token: token,
+            user_id: target.public_id,
+            expires_at: session.expires_at,
+        },
+    )
+}
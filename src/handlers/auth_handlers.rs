@@ -1,41 +1,88 @@
 use crate::{
-    helpers::resend::{ResendClient, verify_email_template},
+    cache::Cache,
+    config::AppConfig,
+    helpers::{
+        avatar::validate_avatar_url,
+        clock::Clock,
+        email_policy::EmailDomainPolicy,
+        email_templates::{EmailTemplates, LandingPage, preferred_locale},
+        feature_flags::FeatureFlags,
+        fields::{FieldSelection, Sparse},
+        json::Json,
+        link_tokens::{LinkGrant, LinkPurpose, LinkTokenStore},
+        merge_patch::MergePatch,
+        negotiation::prefers_html,
+        password_strength::estimate_password,
+        path_params::ValidatedPath,
+        redaction::{Audience, SeenBy},
+        redirects::{is_allowed_redirect, with_query},
+        sessions::{describe_device, truncate_user_agent},
+        sign_in_alerts::{SignInAlerts, coarse_network, country_from},
+        signup_guard::{RegistrationForm, SignupGuard, SignupRejection},
+        slugs::validate_username,
+        usage::{Usage, UsageTracker, user_usage},
+        verification::VerificationMailer,
+    },
     model::{
         VerifyEmailQuery,
         model::{
-            CreateUserRequest, LoginRequest, LoginResponse, Role, UpdatePasswordRequest,
-            UpdateUserRequest, UserResponse,
+            AccountExport, CreateUserRequest, ErrorResponse, FeatureFlag, FieldsQuery,
+            Impersonator, LoginRequest, LoginResponse, PasswordStrength, PasswordStrengthRequest,
+            PatchUserRequest, ProfileStats, PublicProfile, RevokeSessionToken, Role, Session,
+            SessionId, SessionResponse, UpdatePasswordRequest, UpdateUserRequest, UserResponse,
+            UsernameAvailability, UsernameQuery,
         },
     },
 };
 use axum::{
-    Json,
-    extract::{Extension, Path, Query, State},
+    Form,
+    extract::{ConnectInfo, Extension, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
 };
-use axum_extra::extract::cookie::Cookie;
 use mailchecker::is_valid;
-use resend_rs::types::CreateEmailBaseOptions;
 use sqlx::PgPool;
-use std::{
-    env,
-    sync::{Arc, LazyLock},
-};
-use time::Duration;
+use std::{net::SocketAddr, sync::Arc};
 use utoipa;
 use uuid::Uuid;
 
-use crate::db::repositories::user_repo::UserRepository;
-use crate::helpers::auth::AuthHelper;
+use crate::db::pools::DbPools;
+use crate::db::repositories::{
+    audit_repo::AuditRepository, export_repo::ExportRepository, post_repo::PostRepository,
+    session_repo::SessionRepository, stats_repo::StatsRepository, user_repo::UserRepository,
+};
+use crate::helpers::auth::{AuthHelper, JWT_KEYS};
+use crate::helpers::cookies::CookieFactory;
+use crate::helpers::denylist::TokenDenylist;
+use crate::helpers::jwt_keys::JwkSet;
 use crate::helpers::middleware::check_admin_role;
 use crate::helpers::response::{
-    CookieResponse, UnifiedResponse, error_response_generic, error_response_with_cookies,
-    not_found_response_generic, sql_error_generic, sql_error_response_with_cookies,
-    success_response, success_response_with_cookies,
+    CookieResponse, UnifiedResponse, created_response, error_response_generic,
+    error_response_with_cookies, not_found_response_generic, sql_error_generic,
+    sql_error_response_with_cookies, success_response, success_response_with_cookies,
 };
-use crate::helpers::validation::{strong_password, validate_user_registration};
-use tracing::{error, info};
-
-static RESEND_CLIENT: LazyLock<ResendClient> = LazyLock::new(|| ResendClient::new());
+use crate::helpers::validation::{
+    check_length, check_password, generate_base64_string, validate_user_registration,
+};
+use crate::helpers::versioning::ApiVersion;
+use tracing::{error, info, warn};
+
+/// Why `username` (already lowercased) can't be used, or `None` if it is free;
+/// `owner` may keep the username they already have
+async fn username_problem(
+    repo: &UserRepository,
+    username: &str,
+    config: &AppConfig,
+    owner: Option<Uuid>,
+) -> anyhow::Result<Option<String>> {
+    if let Err(message) = validate_username(username, &config.slugs) {
+        return Ok(Some(message));
+    }
+    match repo.find_by_username(username).await? {
+        Some(user) if Some(user.id) != owner => Ok(Some("Username is already taken".to_string())),
+        _ => Ok(None),
+    }
+}
 
 /// Register a new user
 #[utoipa::path(
@@ -43,23 +90,66 @@ static RESEND_CLIENT: LazyLock<ResendClient> = LazyLock::new(|| ResendClient::ne
     path = "/auth/register",
     request_body = CreateUserRequest,
     responses(
-        (status = 200, description = "User registered successfully", body = inline(crate::helpers::response::ApiSuccessResponse<UserResponse>)),
-        (status = 400, description = "Validation error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 201, description = "User registered successfully", body = inline(crate::helpers::response::ApiSuccessResponse<UserResponse>),
+            headers(("Location" = String, description = "Path of the new user's public profile"))),
+        (status = 400, description = "Validation error, or a bot check failed", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 409, description = "User already exists", body = inline(crate::helpers::response::ApiErrorResponse)),
-        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 503, description = "Registration is switched off, or the CAPTCHA service can't be reached", body = inline(crate::helpers::response::ApiErrorResponse))
     ),
     tag = "Authentication"
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn register_user(
     State(pool): State<Arc<PgPool>>,
-    Json(payload): Json<CreateUserRequest>,
-) -> UnifiedResponse<UserResponse> {
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(emails): State<Arc<EmailTemplates>>,
+    State(flags): State<Arc<FeatureFlags>>,
+    State(signup): State<Arc<SignupGuard>>,
+    State(verification): State<VerificationMailer>,
+    version: ApiVersion,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Json(mut payload): Json<CreateUserRequest>,
+) -> UnifiedResponse<SeenBy<UserResponse>> {
     info!("Handler: Registering user: {:?}", payload.email);
 
-    if let Err(validation_errors) = validate_user_registration(&payload) {
+    if let Err(err) = flags.require(FeatureFlag::Registration) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    if let Err(validation_errors) = validate_user_registration(&payload, &config.limits) {
         return error_response_generic("Registration Failed".to_string(), validation_errors);
     }
 
+    // An explicit choice must have a catalog; the browser's language only if it does
+    payload.locale = match payload.locale.as_deref() {
+        Some(locale) => match emails.check_locale(locale) {
+            Ok(locale) => Some(locale),
+            Err(message) => {
+                return error_response_generic("Registration Failed".to_string(), message);
+            }
+        },
+        None => headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(preferred_locale)
+            .filter(|locale| emails.supports(locale))
+            .map(|locale| locale.to_lowercase()),
+    };
+
+    if payload
+        .redirect_uri
+        .as_deref()
+        .is_some_and(|uri| !is_allowed_redirect(uri, &config.email.redirect_origins))
+    {
+        return error_response_generic(
+            "Invalid Redirect".to_string(),
+            "redirect_uri must be on an allowed origin".to_string(),
+        );
+    }
+
     if !is_valid(&payload.email) {
         return error_response_generic(
             "Invalid Email".to_string(),
@@ -67,6 +157,38 @@ pub async fn register_user(
         );
     }
 
+    let remote_ip = connect_info.map(|Extension(ConnectInfo(addr))| addr.ip());
+    if let Err(rejection) = signup
+        .check(&payload.email, &payload.proof(), remote_ip, clock.as_ref())
+        .await
+    {
+        warn!(
+            "Registration of {:?} turned away: {:?}",
+            payload.email, rejection
+        );
+        let status = match rejection {
+            SignupRejection::CaptchaUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        return UnifiedResponse::Error(ErrorResponse::new(
+            status,
+            "Registration Failed",
+            rejection.message(),
+        ));
+    }
+
+    match EmailDomainPolicy::load(&pool, &config.accounts).await {
+        Ok(policy) => {
+            if let Err(message) = policy.check(&payload.email) {
+                return error_response_generic("Registration Failed".to_string(), message);
+            }
+        }
+        Err(e) => {
+            error!("Failed to load email domain policy: {:?}", e);
+            return sql_error_generic(e, "Error checking email domain");
+        }
+    }
+
     let repo = UserRepository::new((*pool).clone());
 
     match repo.find_by_email(&payload.email).await {
@@ -83,6 +205,29 @@ pub async fn register_user(
         }
     }
 
+    payload.username = match payload.username.as_deref() {
+        Some(username) => {
+            let username = username.to_lowercase();
+            match username_problem(&repo, &username, &config, None).await {
+                Ok(None) => Some(username),
+                Ok(Some(message)) => {
+                    return error_response_generic("Registration Failed".to_string(), message);
+                }
+                Err(e) => {
+                    error!("Database error: {:?}", e);
+                    return sql_error_generic(e, "Error checking username");
+                }
+            }
+        }
+        None => match repo.free_username(&payload.name, &config.slugs).await {
+            Ok(username) => Some(username),
+            Err(e) => {
+                error!("Database error: {:?}", e);
+                return sql_error_generic(e, "Error generating username");
+            }
+        },
+    };
+
     let hashed_password = match AuthHelper::hash_password(&payload.password) {
         Ok(hash) => hash,
         Err(e) => {
@@ -94,48 +239,26 @@ pub async fn register_user(
         }
     };
 
-    match repo.create_user(payload.clone(), hashed_password).await {
+    match repo
+        .create_user(payload.clone(), hashed_password, clock.now())
+        .await
+    {
         Ok(user) => {
-            let user_email = user.email.clone(); // Clone email before moving user
-            let user_name = user.name.clone();
-
-            let user_response = UserResponse {
-                id: user.id,
-                name: user.name,
-                email: user.email,
-                role: user.role,
-                email_verified: user.email_verified,
-                created_at: user.created_at,
-                updated_at: user.updated_at,
-            };
-
-            let verification_token = AuthHelper::generate_email_verification_token(user.id);
-            let base_url = env::var("BASE_URL").unwrap_or_else(|_| "localhost:3000".to_string());
-            // Send verification email
-            let verification_link = format!(
-                "http://{}/auth/verify-email?token={}",
-                base_url, verification_token
-            );
-
-            // Send verification email using Resend
-            let from = "AXUM-REST <onboarding@resend.dev>";
-            let to = [user_email];
-            let subject = "Verify your email address";
-
-            let email = CreateEmailBaseOptions::new(from, to, subject)
-                .with_html(&verify_email_template(&user_name, &verification_link));
-
-            match RESEND_CLIENT.resend.emails.send(email).await {
-                Ok(response) => {
-                    info!("Verification email sent: {:?}", response);
-                }
-                Err(e) => {
-                    error!("Failed to send verification email: {:?}", e);
-                }
-            }
-            success_response(
+            verification
+                .send(
+                    &user,
+                    payload.locale.as_deref(),
+                    payload.redirect_uri.as_deref(),
+                )
+                .await;
+
+            let user_response = UserResponse::from(user);
+            // The request is anonymous, but the new account is the caller's own
+            let audience = Audience::user(user_response.internal_id, user_response.role.clone());
+            created_response(
+                config.versioned_path(version, &format!("/profiles/{}", user_response.id)),
                 "Registration Complete, Check Email for Verification Link".to_string(),
-                user_response,
+                SeenBy(audience, user_response),
             )
         }
         Err(e) => {
@@ -145,6 +268,96 @@ pub async fn register_user(
     }
 }
 
+/// What a registration form needs to pass the bot checks that are switched on
+#[utoipa::path(
+    get,
+    path = "/auth/register/form",
+    responses(
+        (status = 200, description = "Form token, honeypot and CAPTCHA settings for the registration form", body = inline(crate::helpers::response::ApiSuccessResponse<RegistrationForm>)),
+        (status = 400, description = "Unable to issue a form token", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Authentication"
+)]
+pub async fn registration_form(
+    State(signup): State<Arc<SignupGuard>>,
+    State(clock): State<Arc<dyn Clock>>,
+) -> UnifiedResponse<RegistrationForm> {
+    match signup.form(clock.as_ref()) {
+        Ok(form) => success_response("Registration Form Ready".to_string(), form),
+        Err(e) => {
+            error!("Handler: Failed to sign registration form token: {}", e);
+            error_response_generic(
+                "Registration Failed".to_string(),
+                "Unable to prepare the registration form".to_string(),
+            )
+        }
+    }
+}
+
+/// Check whether a username can be registered
+#[utoipa::path(
+    get,
+    path = "/auth/username-available",
+    params(
+        ("u" = String, Query, description = "Username to check; case-insensitive")
+    ),
+    responses(
+        (status = 200, description = "Availability of the username, with the reason when it can't be used", body = inline(crate::helpers::response::ApiSuccessResponse<UsernameAvailability>)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Authentication"
+)]
+pub async fn username_available(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    Query(query): Query<UsernameQuery>,
+) -> UnifiedResponse<UsernameAvailability> {
+    let username = query.u.trim().to_lowercase();
+    let repo = UserRepository::new((*pool).clone());
+
+    match username_problem(&repo, &username, &config, None).await {
+        Ok(reason) => success_response(
+            "Username Checked".to_string(),
+            UsernameAvailability {
+                username,
+                available: reason.is_none(),
+                reason,
+            },
+        ),
+        Err(e) => {
+            error!("Database error: {:?}", e);
+            sql_error_generic(e, "Error checking username")
+        }
+    }
+}
+
+/// Score a password as the user types it
+///
+/// Registration and password changes accept the password when `acceptable`
+/// is true. The password is neither stored nor logged.
+#[utoipa::path(
+    post,
+    path = "/auth/password-strength",
+    request_body = PasswordStrengthRequest,
+    responses(
+        (status = 200, description = "Score from 0 to 4, whether it is acceptable, and how to improve it", body = inline(crate::helpers::response::ApiSuccessResponse<PasswordStrength>))
+    ),
+    tag = "Authentication"
+)]
+pub async fn password_strength(
+    Json(payload): Json<PasswordStrengthRequest>,
+) -> UnifiedResponse<PasswordStrength> {
+    let user_inputs: Vec<&str> = [&payload.name, &payload.email, &payload.username]
+        .into_iter()
+        .filter_map(|input| input.as_deref())
+        .collect();
+
+    success_response(
+        "Password Checked".to_string(),
+        estimate_password(&payload.password, &user_inputs),
+    )
+}
+
 /// Get user profile
 #[utoipa::path(
     get,
@@ -171,18 +384,104 @@ pub async fn get_profile(
 
     match repo.find_by_id(user_id).await {
         Ok(Some(user)) => {
-            let user_response = UserResponse {
-                id: user.id,
-                name: user.name,
-                email: user.email,
-                role: user.role,
-                email_verified: user.email_verified,
-                created_at: user.created_at,
-                updated_at: user.updated_at,
-            };
+            success_response("Profile Retrieved".to_string(), UserResponse::from(user))
+        }
+        Ok(None) => not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!("Handler: Database error: {:?}", e);
+            sql_error_generic(e, "Error fetching user profile")
+        }
+    }
+}
+
+/// Get totals across the current user's posts, for their dashboard
+///
+/// Counts every post they wrote, including archived and private ones and posts
+/// in organizations.
+#[utoipa::path(
+    get,
+    path = "/auth/profile/stats",
+    responses(
+        (status = 200, description = "Statistics retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<ProfileStats>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn get_profile_stats(
+    State(db): State<DbPools>,
+    Extension(user_id): Extension<Uuid>,
+) -> UnifiedResponse<ProfileStats> {
+    info!("Handler: Computing statistics for user_id: {:?}", user_id);
 
-            success_response("Profile Retrieved".to_string(), user_response)
+    match StatsRepository::from_pools(&db).author_stats(user_id).await {
+        Ok(stats) => success_response("Statistics Retrieved".to_string(), stats),
+        Err(e) => {
+            error!("Handler: Failed to compute statistics: {}", e);
+            sql_error_generic(e, "Unable to compute statistics")
         }
+    }
+}
+
+/// Get the current user's daily quota and how much of it is used
+#[utoipa::path(
+    get,
+    path = "/auth/usage",
+    responses(
+        (status = 200, description = "Usage retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<Usage>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Usage quotas are turned off", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn get_usage(
+    State(usage): State<Arc<UsageTracker>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<Usage> {
+    info!("Handler: Fetching usage for user_id: {:?}", user_id);
+
+    match user_usage(&usage, &config.access, user_id, user_role, clock.now()) {
+        Some(usage) => success_response("Usage Retrieved".to_string(), usage),
+        None => not_found_response_generic("Usage quotas are turned off".to_string()),
+    }
+}
+
+/// Get a user's public profile
+#[utoipa::path(
+    get,
+    path = "/profiles/{id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the user")
+    ),
+    responses(
+        (status = 200, description = "Public profile retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<PublicProfile>)),
+        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Authentication"
+)]
+pub async fn get_public_profile(
+    State(db): State<DbPools>,
+    State(cache): State<Arc<Cache>>,
+    ValidatedPath(id): ValidatedPath<String>,
+) -> UnifiedResponse<PublicProfile> {
+    info!("Handler: Fetching public profile for id: {}", id);
+
+    let repo = UserRepository::from_pools(&db).with_cache(cache);
+
+    match repo.find_public_profile(&id).await {
+        Ok(Some(profile)) => success_response("Profile Retrieved".to_string(), profile),
         Ok(None) => not_found_response_generic("User not found".to_string()),
         Err(e) => {
             error!("Handler: Database error: {:?}", e);
@@ -191,6 +490,117 @@ pub async fn get_profile(
     }
 }
 
+/// Export all data held about the current user
+#[utoipa::path(
+    get,
+    path = "/auth/profile/export",
+    responses(
+        (status = 200, description = "JSON archive of everything stored about the user", body = inline(crate::helpers::response::ApiSuccessResponse<AccountExport>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not allowed while impersonating a user", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn export_user_data(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    Extension(user_role): Extension<Role>,
+) -> UnifiedResponse<SeenBy<AccountExport>> {
+    info!("Handler: Exporting data for user_id: {:?}", user_id);
+
+    let repo = UserRepository::new((*pool).clone());
+
+    let user = match repo.find_by_id(user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!("Handler: Database error: {:?}", e);
+            return sql_error_generic(e, "Error fetching user profile");
+        }
+    };
+
+    let deletion_scheduled_at = match repo.deletion_scheduled_at(user_id).await {
+        Ok(at) => at,
+        Err(e) => {
+            error!("Handler: Database error: {:?}", e);
+            return sql_error_generic(e, "Error fetching account status");
+        }
+    };
+
+    let posts = match PostRepository::new((*pool).clone())
+        .find_by_author(user_id)
+        .await
+    {
+        Ok(posts) => posts,
+        Err(e) => {
+            error!("Handler: Database error: {:?}", e);
+            return sql_error_generic(e, "Error fetching user posts");
+        }
+    };
+
+    let export = ExportRepository::new((*pool).clone());
+    let related = tokio::try_join!(
+        export.sessions(user_id),
+        export.notifications(user_id),
+        export.memberships(user_id),
+        export.collaborations(user_id),
+        export.mentioned_in(user_id),
+        export.share_links(user_id),
+        export.link_tokens(user_id),
+        export.followers(user_id),
+        export.reports(user_id),
+        export.audit_log(user_id),
+    );
+    let (
+        sessions,
+        notifications,
+        memberships,
+        collaborations,
+        mentioned_in,
+        share_links,
+        link_tokens,
+        followers,
+        reports,
+        audit_log,
+    ) = match related {
+        Ok(related) => related,
+        Err(e) => {
+            error!("Handler: Database error: {:?}", e);
+            return sql_error_generic(e, "Error fetching account data");
+        }
+    };
+
+    success_response(
+        "Data Exported".to_string(),
+        SeenBy(
+            Audience::user(user_id, user_role),
+            AccountExport {
+                user: UserResponse::from(user),
+                posts,
+                sessions,
+                notifications,
+                memberships,
+                collaborations,
+                mentioned_in,
+                share_links,
+                link_tokens,
+                followers,
+                reports,
+                audit_log,
+                deletion_scheduled_at,
+                exported_at: clock.now(),
+            },
+        ),
+    )
+}
+
 /// Update user profile
 #[utoipa::path(
     put,
@@ -210,13 +620,66 @@ pub async fn get_profile(
     tag = "Authentication"
 )]
 pub async fn update_profile(
+    pool: State<Arc<PgPool>>,
+    config: State<Arc<AppConfig>>,
+    cache: State<Arc<Cache>>,
+    emails: State<Arc<EmailTemplates>>,
+    verification: State<VerificationMailer>,
+    user_id: Extension<Uuid>,
+    Json(payload): Json<UpdateUserRequest>,
+) -> UnifiedResponse<UserResponse> {
+    if payload.name.is_none() {
+        return error_response_generic("Update Failed".to_string(), "Name is required".to_string());
+    }
+
+    patch_profile(
+        pool,
+        config,
+        cache,
+        emails,
+        verification,
+        user_id,
+        MergePatch(payload.into()),
+    )
+    .await
+}
+
+/// Change some profile fields with a JSON Merge Patch
+#[utoipa::path(
+    patch,
+    path = "/auth/profile",
+    request_body(content = PatchUserRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = 200, description = "User profile updated successfully", body = inline(crate::helpers::response::ApiSuccessResponse<UserResponse>)),
+        (status = 400, description = "Validation error, a malformed patch, or a null name, username or email", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 415, description = "The patch isn't sent as JSON", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn patch_profile(
     State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(cache): State<Arc<Cache>>,
+    State(emails): State<Arc<EmailTemplates>>,
+    State(verification): State<VerificationMailer>,
     Extension(user_id): Extension<Uuid>,
-    Json(payload): Json<UpdateUserRequest>,
+    MergePatch(patch): MergePatch<PatchUserRequest>,
 ) -> UnifiedResponse<UserResponse> {
     info!("Handler: Updating profile for user_id: {:?}", user_id);
 
-    let repo = UserRepository::new((*pool).clone());
+    let mut payload = match UpdateUserRequest::try_from(patch) {
+        Ok(payload) => payload,
+        Err(message) => return error_response_generic("Update Failed".to_string(), message),
+    };
+
+    let repo = UserRepository::new((*pool).clone()).with_cache(cache);
 
     // Validate name
     if let Some(name) = &payload.name {
@@ -226,56 +689,93 @@ pub async fn update_profile(
                 "Name cannot be empty".to_string(),
             );
         }
-    } else {
-        return error_response_generic("Update Failed".to_string(), "Name is required".to_string());
+        if let Err(message) = check_length("Name", name.trim(), config.limits.max_name_length) {
+            return error_response_generic("Update Failed".to_string(), message);
+        }
+    }
+
+    // Usernames are stored lowercased
+    if let Some(username) = payload.username.as_mut() {
+        *username = username.to_lowercase();
+        match username_problem(&repo, username, &config, Some(user_id)).await {
+            Ok(None) => {}
+            Ok(Some(message)) => {
+                return error_response_generic("Update Failed".to_string(), message);
+            }
+            Err(e) => {
+                error!("Handler: Database error: {:?}", e);
+                return sql_error_generic(e, "Error checking username");
+            }
+        }
     }
 
     // Validate email if provided
+    if let Some(email) = &payload.email
+        && !is_valid(email)
+    {
+        return error_response_generic(
+            "Update Failed".to_string(),
+            "Please provide a valid email address".to_string(),
+        );
+    }
+
     if let Some(email) = &payload.email {
-        if !is_valid(email) {
-            return error_response_generic(
-                "Update Failed".to_string(),
-                "Please provide a valid email address".to_string(),
-            );
+        match EmailDomainPolicy::load(&pool, &config.accounts).await {
+            Ok(policy) => {
+                if let Err(message) = policy.check(email) {
+                    return error_response_generic("Update Failed".to_string(), message);
+                }
+            }
+            Err(e) => {
+                error!("Failed to load email domain policy: {:?}", e);
+                return sql_error_generic(e, "Error checking email domain");
+            }
         }
     }
 
-    match repo.update_user(user_id, payload.clone()).await {
+    // Validate avatar URL if provided; an empty string clears it
+    if let Some(avatar_url) = payload.avatar_url.as_deref().filter(|url| !url.is_empty())
+        && let Err(message) = validate_avatar_url(avatar_url)
+    {
+        return error_response_generic("Update Failed".to_string(), message);
+    }
+
+    // An empty string clears the locale
+    let locale = match payload.locale.as_deref().map(str::trim) {
+        Some("") => Some(String::new()),
+        Some(locale) => match emails.check_locale(locale) {
+            Ok(locale) => Some(locale),
+            Err(message) => return error_response_generic("Update Failed".to_string(), message),
+        },
+        None => None,
+    };
+
+    match repo
+        .update_user(user_id, payload.clone(), verification.clock.now())
+        .await
+    {
         Ok((Some(user), email_updated)) => {
-            let user_response = UserResponse {
-                id: user.id,
-                name: user.name.clone(),
-                email: user.email.clone(),
-                role: user.role,
-                email_verified: user.email_verified,
-                created_at: user.created_at,
-                updated_at: user.updated_at,
-            };
+            if let Some(locale) = &locale {
+                let locale = (!locale.is_empty()).then_some(locale.as_str());
+                if let Err(e) = repo.set_locale(user.id, locale).await {
+                    error!("Handler: Database error: {:?}", e);
+                    return sql_error_generic(e, "Error updating user locale");
+                }
+            }
 
             // Send verification email only if email changed
             if email_updated {
-                let verification_token = AuthHelper::generate_email_verification_token(user.id);
-                let base_url =
-                    std::env::var("BASE_URL").unwrap_or_else(|_| "localhost:3000".to_string());
-                let verification_link = format!(
-                    "http://{}/auth/verify-email?token={}",
-                    base_url, verification_token
-                );
-
-                let from = "AXUM-REST <onboarding@resend.dev>";
-                let to = [user_response.email.clone()];
-                let subject = "Verify your email address";
-
-                let email = CreateEmailBaseOptions::new(from, &to, subject).with_html(
-                    &verify_email_template(&user_response.name, &verification_link),
-                );
-
-                match RESEND_CLIENT.resend.emails.send(email).await {
-                    Ok(response) => info!("Verification email sent: {:?}", response),
-                    Err(e) => error!("Failed to send verification email: {:?}", e),
-                }
+                let locale = match locale {
+                    Some(locale) => Some(locale).filter(|locale| !locale.is_empty()),
+                    None => repo.locale(user.id).await.unwrap_or_else(|e| {
+                        error!("Failed to load locale of user {}: {:?}", user.id, e);
+                        None
+                    }),
+                };
+                verification.send(&user, locale.as_deref(), None).await;
             }
 
+            let user_response = UserResponse::from(user);
             success_response("Profile Updated".to_string(), user_response)
         }
         Ok((None, _)) => not_found_response_generic("User not found".to_string()),
@@ -292,7 +792,7 @@ pub async fn update_profile(
     path = "/auth/login",
     request_body = LoginRequest,
     responses(
-        (status = 200, description = "Login successful - returns JWT token and sets HTTP-only auth cookies (auth_token: 24h, refresh_token: 7d)", body = inline(crate::helpers::response::ApiSuccessResponse<LoginResponse>)),
+        (status = 200, description = "Login successful - returns JWT token, sets HTTP-only auth cookies (auth_token: 24h, refresh_token: 7d) and a readable csrf_token cookie", body = inline(crate::helpers::response::ApiSuccessResponse<LoginResponse>)),
         (status = 400, description = "Invalid credentials", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
     ),
@@ -300,18 +800,29 @@ pub async fn update_profile(
 )]
 pub async fn login_user(
     State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(alerts): State<SignInAlerts>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
-) -> CookieResponse<LoginResponse> {
-    info!("Handler: Logging in user: {:?}", payload.email);
+) -> CookieResponse<SeenBy<LoginResponse>> {
+    info!("Handler: Logging in user: {:?}", payload.identifier);
 
     let repo = UserRepository::new((*pool).clone());
 
-    let user = match repo.find_by_email(&payload.email).await {
+    // Usernames can't contain `@`, so anything with one is an email
+    let found = if payload.identifier.contains('@') {
+        repo.find_by_email(&payload.identifier).await
+    } else {
+        repo.find_by_username(&payload.identifier).await
+    };
+    let user = match found {
         Ok(Some(user)) => user,
         Ok(None) => {
             return error_response_with_cookies(
                 "Login Failed".to_string(),
-                "Invalid email or password".to_string(),
+                "Invalid email, username or password".to_string(),
             );
         }
         Err(e) => {
@@ -342,7 +853,90 @@ pub async fn login_user(
                 }
             }
 
-            let tokens = match AuthHelper::generate_token(user.id, user.role.clone()) {
+            match repo.standing(user_id, clock.now()).await {
+                Ok(Some(standing)) if standing.is_restricted() => {
+                    return error_response_with_cookies(
+                        "Login Failed".to_string(),
+                        standing.describe(),
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Account status check error: {:?}", e);
+                    return sql_error_response_with_cookies(e, "Unable to verify account status");
+                }
+            }
+
+            // Logging back in during the grace period restores the account
+            match repo.cancel_deletion(user_id, clock.now()).await {
+                Ok(true) => info!("Login cancelled scheduled deletion for user: {}", user_id),
+                Ok(false) => {}
+                Err(e) => {
+                    error!("Failed to cancel scheduled deletion: {:?}", e);
+                    return sql_error_response_with_cookies(e, "Unable to restore account");
+                }
+            }
+
+            let session_lifetime = match chrono::Duration::from_std(config.tokens.refresh_ttl) {
+                Ok(lifetime) => lifetime,
+                Err(e) => {
+                    error!("Invalid refresh token lifetime: {:?}", e);
+                    return error_response_with_cookies(
+                        "Login Failed".to_string(),
+                        "Unable to create authentication session".to_string(),
+                    );
+                }
+            };
+            let now = clock.now();
+            let user_agent = headers
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(truncate_user_agent);
+            let ip = connect_info.map(|Extension(ConnectInfo(addr))| addr.ip());
+            let session = Session {
+                id: Uuid::new_v4().simple().to_string(),
+                user_id,
+                device: describe_device(user_agent.as_deref()),
+                ip: ip.map(|ip| ip.to_string()),
+                user_agent,
+                network: ip.map(coarse_network),
+                country: country_from(&headers, config.accounts.country_header.as_deref()),
+                created_at: now,
+                last_seen_at: now,
+                expires_at: now + session_lifetime,
+            };
+
+            let sessions = SessionRepository::new((*pool).clone());
+            // A failed lookup only costs the alert, never the login
+            let new_device = config.accounts.new_device_alerts
+                && sessions.is_new_device(&session).await.unwrap_or_else(|e| {
+                    error!("Known device check error: {:?}", e);
+                    false
+                });
+            if let Err(e) = sessions.create_session(&session).await {
+                error!("Session creation error: {:?}", e);
+                return sql_error_response_with_cookies(
+                    e,
+                    "Unable to create authentication session",
+                );
+            }
+
+            if new_device {
+                info!("Login of user {} from a new device", user_id);
+                let locale = repo.locale(user_id).await.ok().flatten();
+                let (user, session) = (user.clone(), session.clone());
+                tokio::spawn(async move {
+                    alerts.send(&user, locale.as_deref(), &session).await;
+                });
+            }
+
+            let tokens = match AuthHelper::generate_session_tokens(
+                user.id,
+                user.role.clone(),
+                &session.id,
+                &config.tokens,
+                clock.as_ref(),
+            ) {
                 Ok(t) => t,
                 Err(e) => {
                     error!("Token generation error: {:?}", e);
@@ -355,15 +949,9 @@ pub async fn login_user(
 
             let (auth_token, refresh_token) = tokens;
 
-            let user_response = UserResponse {
-                id: user.id,
-                name: user.name,
-                email: user.email,
-                role: user.role,
-                email_verified: user.email_verified,
-                created_at: user.created_at,
-                updated_at: user.updated_at,
-            };
+            let user_response = UserResponse::from(user);
+            // The request is anonymous, but the account is the caller's own
+            let audience = Audience::user(user_response.internal_id, user_response.role.clone());
 
             let login_response = LoginResponse {
                 user: user_response,
@@ -371,32 +959,19 @@ pub async fn login_user(
                 refresh_token: refresh_token.clone(),
             };
 
-            // Create cookies for auth tokens
-            let auth_cookie = Cookie::build(("auth_token", auth_token))
-                .path("/")
-                .max_age(Duration::hours(24)) // 24 hours
-                .http_only(true)
-                .secure(false) // Set to true in production with HTTPS
-                .same_site(axum_extra::extract::cookie::SameSite::Lax)
-                .build();
-
-            let refresh_cookie = Cookie::build(("refresh_token", refresh_token))
-                .path("/")
-                .max_age(Duration::days(7)) // 7 days
-                .http_only(true)
-                .secure(false) // Set to true in production with HTTPS
-                .same_site(axum_extra::extract::cookie::SameSite::Lax)
-                .build();
-
             success_response_with_cookies(
                 "Login Successful".to_string(),
-                login_response,
-                vec![auth_cookie, refresh_cookie],
+                SeenBy(audience, login_response),
+                CookieFactory::new(&config).login(
+                    auth_token,
+                    refresh_token,
+                    generate_base64_string(),
+                ),
             )
         }
         Ok(false) => error_response_with_cookies(
             "Login Failed".to_string(),
-            "Invalid email or password".to_string(),
+            "Invalid email, username or password".to_string(),
         ),
         Err(e) => {
             error!("Password verification error: {:?}", e);
@@ -422,30 +997,102 @@ pub async fn login_user(
     ),
     tag = "Authentication"
 )]
-pub async fn logout_user() -> CookieResponse<String> {
+pub async fn logout_user(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(denylist): State<Arc<TokenDenylist>>,
+    Extension(user_id): Extension<Uuid>,
+    session_id: Option<Extension<SessionId>>,
+) -> CookieResponse<String> {
     info!("Handler: Logging out user");
 
-    // Create expired cookies to clear them
-    let auth_cookie = Cookie::build(("auth_token", ""))
-        .path("/")
-        .max_age(Duration::seconds(-1)) // Expired
-        .http_only(true)
-        .secure(false) // Set to true in production with HTTPS
-        .same_site(axum_extra::extract::cookie::SameSite::Lax)
-        .build();
-
-    let refresh_cookie = Cookie::build(("refresh_token", ""))
-        .path("/")
-        .max_age(Duration::seconds(-1)) // Expired
-        .http_only(true)
-        .secure(false) // Set to true in production with HTTPS
-        .same_site(axum_extra::extract::cookie::SameSite::Lax)
-        .build();
+    // Revoke the session so copies of its tokens stop working too
+    if let Some(Extension(SessionId(session_id))) = session_id {
+        let sessions = SessionRepository::new((*pool).clone());
+        if let Err(e) = sessions.revoke(&session_id, user_id, clock.now()).await {
+            error!("Failed to revoke session on logout: {:?}", e);
+            return sql_error_response_with_cookies(e, "Unable to end session");
+        }
+        denylist.deny(&[session_id]).await;
+    }
 
     success_response_with_cookies(
         "Logout Successful".to_string(),
         "Authentication session ended".to_string(),
-        vec![auth_cookie, refresh_cookie],
+        CookieFactory::new(&config).clear(),
+    )
+}
+
+/// End an impersonation; its token stops working right away
+#[utoipa::path(
+    post,
+    path = "/auth/stop-impersonation",
+    responses(
+        (status = 200, description = "Impersonation ended", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
+        (status = 400, description = "The token isn't an impersonation token", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn stop_impersonation(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(denylist): State<Arc<TokenDenylist>>,
+    Extension(user_id): Extension<Uuid>,
+    impersonator: Option<Extension<Impersonator>>,
+    session_id: Option<Extension<SessionId>>,
+) -> UnifiedResponse<String> {
+    let (Some(Extension(Impersonator(admin_id))), Some(Extension(SessionId(session_id)))) =
+        (impersonator, session_id)
+    else {
+        return error_response_generic(
+            "Invalid Operation".to_string(),
+            "This request is not made while impersonating a user".to_string(),
+        );
+    };
+    info!(
+        "Handler: Admin {} stopping impersonation of user_id: {:?}",
+        admin_id, user_id
+    );
+
+    let now = clock.now();
+    let sessions = SessionRepository::new((*pool).clone());
+    if let Err(e) = sessions.revoke(&session_id, user_id, now).await {
+        error!("Failed to revoke impersonation session: {:?}", e);
+        return sql_error_generic(e, "Unable to end impersonation");
+    }
+    denylist.deny(&[session_id]).await;
+
+    // Audit targets name users by their public id
+    let target = match UserRepository::new((*pool).clone())
+        .find_by_id(user_id)
+        .await
+    {
+        Ok(Some(user)) => user.public_id,
+        _ => user_id.to_string(),
+    };
+    let audit = AuditRepository::new((*pool).clone());
+    if let Err(e) = audit
+        .record(
+            admin_id,
+            "impersonation.stopped",
+            &format!("user:{}", target),
+            None,
+            now,
+        )
+        .await
+    {
+        error!("Failed to record audit entry for impersonation: {:?}", e);
+    }
+
+    success_response(
+        "Impersonation Stopped".to_string(),
+        "The impersonation token has been revoked".to_string(),
     )
 }
 
@@ -458,6 +1105,7 @@ pub async fn logout_user() -> CookieResponse<String> {
         (status = 200, description = "Password changed successfully", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
         (status = 400, description = "Validation error", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not allowed while impersonating a user", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
     ),
@@ -469,17 +1117,17 @@ pub async fn logout_user() -> CookieResponse<String> {
 )]
 pub async fn change_password(
     State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(denylist): State<Arc<TokenDenylist>>,
     Extension(user_id): Extension<Uuid>,
+    session_id: Option<Extension<SessionId>>,
     Json(payload): Json<UpdatePasswordRequest>,
 ) -> UnifiedResponse<String> {
     info!("Handler: Changing password for user_id: {:?}", user_id);
 
     // Validate new password strength
-    if !strong_password(&payload.new_password) {
-        return error_response_generic(
-            "Weak Password".to_string(),
-            "Password must be at least 8 characters long with mixed case, numbers, and special characters".to_string(),
-        );
+    if let Err(message) = check_password(&payload.new_password, &[]) {
+        return error_response_generic("Weak Password".to_string(), message);
     }
 
     // Check if new password is same as old password
@@ -540,18 +1188,143 @@ pub async fn change_password(
     };
 
     // Update password in database using the simpler change_password function
-    match repo.change_password(user_id, hashed_new_password).await {
-        Ok(Some(_)) => success_response(
+    match repo
+        .change_password(user_id, hashed_new_password, clock.now())
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return error_response_generic(
+                "Password Change Failed".to_string(),
+                "User account not found".to_string(),
+            );
+        }
+        Err(e) => {
+            error!("Password update error: {:?}", e);
+            return sql_error_generic(e, "Unable to update password");
+        }
+    }
+
+    if !payload.log_out_everywhere {
+        return success_response(
             "Password Changed".to_string(),
             "Password has been updated successfully".to_string(),
-        ),
-        Ok(None) => error_response_generic(
-            "Password Change Failed".to_string(),
-            "User account not found".to_string(),
+        );
+    }
+
+    // Keep the session that made the change signed in
+    let current = session_id.map(|Extension(SessionId(id))| id);
+    let sessions = SessionRepository::new((*pool).clone());
+    match sessions
+        .revoke_all_except(user_id, current.as_deref(), clock.now())
+        .await
+    {
+        Ok(revoked) => {
+            denylist.deny(&revoked).await;
+            success_response(
+                "Password Changed".to_string(),
+                format!(
+                    "Password has been updated successfully and {} other session(s) were signed out",
+                    revoked.len()
+                ),
+            )
+        }
+        Err(e) => {
+            error!("Session revocation error: {:?}", e);
+            sql_error_generic(
+                e,
+                "Password updated but other sessions could not be signed out",
+            )
+        }
+    }
+}
+
+/// List active sessions of the current user
+#[utoipa::path(
+    get,
+    path = "/auth/sessions",
+    responses(
+        (status = 200, description = "Active sessions retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<SessionResponse>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn list_sessions(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    session_id: Option<Extension<SessionId>>,
+) -> UnifiedResponse<Vec<SessionResponse>> {
+    info!("Handler: Listing sessions for user_id: {:?}", user_id);
+
+    let current = session_id.map(|Extension(SessionId(id))| id);
+    let sessions = SessionRepository::new((*pool).clone());
+
+    match sessions.list_active(user_id, clock.now()).await {
+        Ok(list) => success_response(
+            "Sessions Retrieved".to_string(),
+            list.into_iter()
+                .map(|session| SessionResponse::new(session, current.as_deref()))
+                .collect(),
         ),
         Err(e) => {
-            error!("Password update error: {:?}", e);
-            sql_error_generic(e, "Unable to update password")
+            error!("Database error: {:?}", e);
+            sql_error_generic(e, "Unable to retrieve sessions")
+        }
+    }
+}
+
+/// Revoke one of the current user's sessions
+#[utoipa::path(
+    delete,
+    path = "/auth/sessions/{id}",
+    params(
+        ("id" = String, Path, description = "Session ID")
+    ),
+    responses(
+        (status = 200, description = "Session revoked successfully", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not allowed while impersonating a user", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Session not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Authentication"
+)]
+pub async fn revoke_session(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(denylist): State<Arc<TokenDenylist>>,
+    Extension(user_id): Extension<Uuid>,
+    ValidatedPath(id): ValidatedPath<String>,
+) -> UnifiedResponse<String> {
+    info!(
+        "Handler: Revoking session {} for user_id: {:?}",
+        id, user_id
+    );
+
+    let sessions = SessionRepository::new((*pool).clone());
+
+    match sessions.revoke(&id, user_id, clock.now()).await {
+        Ok(true) => {
+            denylist.deny(&[id]).await;
+            success_response(
+                "Session Revoked".to_string(),
+                "The session has been signed out".to_string(),
+            )
+        }
+        Ok(false) => not_found_response_generic("Session not found".to_string()),
+        Err(e) => {
+            error!("Database error: {:?}", e);
+            sql_error_generic(e, "Unable to revoke session")
         }
     }
 }
@@ -562,8 +1335,12 @@ pub async fn change_password(
 #[utoipa::path(
     get,
     path = "/admin/users",
+    params(
+        ("fields" = Option<String>, Query, description = "Comma-separated fields to return for each user, e.g. `id,name,email`")
+    ),
     responses(
         (status = 200, description = "Users retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<UserResponse>>)),
+        (status = 400, description = "Malformed field name in `fields`", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 403, description = "Forbidden - Admin access required", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
@@ -578,24 +1355,35 @@ pub async fn get_all_users_admin(
     State(pool): State<Arc<PgPool>>,
     Extension(user_id): Extension<Uuid>,
     Extension(user_role): Extension<Role>,
-) -> UnifiedResponse<Vec<UserResponse>> {
+    Query(query): Query<FieldsQuery>,
+) -> UnifiedResponse<SeenBy<Sparse<Vec<UserResponse>>>> {
     info!(
         "Handler: Admin getting all users, requested by user_id: {:?}",
         user_id
     );
 
     // Check if user has admin role
-    if let Err((_, json_response)) = check_admin_role(&user_role) {
-        let error_resp = json_response.0;
-        return error_response_generic(error_resp.error, error_resp.message);
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
     }
 
+    let fields = match FieldSelection::parse(query.fields.as_deref()) {
+        Ok(fields) => fields,
+        Err(message) => return error_response_generic("Invalid Fields".to_string(), message),
+    };
+
     let repo = UserRepository::new((*pool).clone());
 
     match repo.get_all_users().await {
         Ok(users) => {
             info!("Retrieved {} users for admin", users.len());
-            success_response("Users Retrieved".to_string(), users)
+            success_response(
+                "Users Retrieved".to_string(),
+                SeenBy(
+                    Audience::user(user_id, user_role),
+                    Sparse::new(users, fields),
+                ),
+            )
         }
         Err(e) => {
             error!("Handler: Database error: {:?}", e);
@@ -604,13 +1392,14 @@ pub async fn get_all_users_admin(
     }
 }
 
-/// Delete user account (Self or Admin)
+/// Schedule deletion of the current user's account
 #[utoipa::path(
     delete,
     path = "/auth/profile",
     responses(
-        (status = 200, description = "User account deleted successfully", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
+        (status = 200, description = "Account scheduled for deletion after the grace period; logging in again cancels it", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
         (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not allowed while impersonating a user", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
     ),
@@ -622,6 +1411,9 @@ pub async fn get_all_users_admin(
 )]
 pub async fn delete_user_account(
     State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
     Extension(user_id): Extension<Uuid>,
 ) -> CookieResponse<String> {
     info!(
@@ -629,33 +1421,37 @@ pub async fn delete_user_account(
         user_id
     );
 
-    let repo = UserRepository::new((*pool).clone());
+    let repo = UserRepository::new((*pool).clone()).with_cache(cache);
+
+    let grace_period = match chrono::Duration::from_std(config.accounts.deletion_grace_period) {
+        Ok(grace_period) => grace_period,
+        Err(e) => {
+            error!("Invalid account deletion grace period: {:?}", e);
+            return error_response_with_cookies(
+                "Deletion Failed".to_string(),
+                "Unable to schedule account deletion".to_string(),
+            );
+        }
+    };
+    let delete_at = clock.now() + grace_period;
 
-    match repo.delete_user(user_id).await {
+    match repo
+        .schedule_deletion(user_id, delete_at, clock.now())
+        .await
+    {
         Ok(true) => {
-            info!("User account deleted successfully: {}", user_id);
-
-            // Create expired cookies to clear them after account deletion
-            let auth_cookie = Cookie::build(("auth_token", ""))
-                .path("/")
-                .max_age(Duration::seconds(-1)) // Expired
-                .http_only(true)
-                .secure(false) // Set to true in production with HTTPS
-                .same_site(axum_extra::extract::cookie::SameSite::Lax)
-                .build();
-
-            let refresh_cookie = Cookie::build(("refresh_token", ""))
-                .path("/")
-                .max_age(Duration::seconds(-1)) // Expired
-                .http_only(true)
-                .secure(false) // Set to true in production with HTTPS
-                .same_site(axum_extra::extract::cookie::SameSite::Lax)
-                .build();
+            info!(
+                "User account scheduled for deletion: {} at {}",
+                user_id, delete_at
+            );
 
             success_response_with_cookies(
-                "Account Deleted".to_string(),
-                "Your account has been permanently deleted".to_string(),
-                vec![auth_cookie, refresh_cookie],
+                "Account Deletion Scheduled".to_string(),
+                format!(
+                    "Your account will be permanently deleted on {}. Log in before then to cancel.",
+                    delete_at.to_rfc3339()
+                ),
+                CookieFactory::new(&config).clear(),
             )
         }
         Ok(false) => error_response_with_cookies(
@@ -663,8 +1459,8 @@ pub async fn delete_user_account(
             "User account not found".to_string(),
         ),
         Err(e) => {
-            error!("Database error during user deletion: {:?}", e);
-            sql_error_response_with_cookies(e, "Unable to delete user account")
+            error!("Database error while scheduling user deletion: {:?}", e);
+            sql_error_response_with_cookies(e, "Unable to schedule account deletion")
         }
     }
 }
@@ -674,7 +1470,7 @@ pub async fn delete_user_account(
     delete,
     path = "/admin/users/{user_id}",
     params(
-        ("user_id" = String, Path, description = "User ID to delete")
+        ("user_id" = String, Path, description = "Public ID of the user to delete")
     ),
     responses(
         (status = 200, description = "User account deleted successfully", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
@@ -691,21 +1487,32 @@ pub async fn delete_user_account(
 )]
 pub async fn delete_user_admin(
     State(pool): State<Arc<PgPool>>,
+    State(cache): State<Arc<Cache>>,
     Extension(admin_user_id): Extension<Uuid>,
     Extension(user_role): Extension<Role>,
-    Path(target_user_id): Path<Uuid>,
+    ValidatedPath(target_public_id): ValidatedPath<String>,
 ) -> UnifiedResponse<String> {
     info!(
         "Handler: Admin deleting user account, admin_id: {:?}, target_user_id: {:?}",
-        admin_user_id, target_user_id
+        admin_user_id, target_public_id
     );
 
     // Check if user has admin role
-    if let Err((_, json_response)) = check_admin_role(&user_role) {
-        let error_resp = json_response.0;
-        return error_response_generic(error_resp.error, error_resp.message);
+    if let Err(err) = check_admin_role(&user_role) {
+        return UnifiedResponse::Error(*err);
     }
 
+    let repo = UserRepository::new((*pool).clone()).with_cache(cache);
+
+    let target_user_id = match repo.find_by_public_id(&target_public_id).await {
+        Ok(Some(user)) => user.id,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!("Database error during admin user lookup: {:?}", e);
+            return sql_error_generic(e, "Unable to find user account");
+        }
+    };
+
     // Prevent admin from deleting their own account through this endpoint
     if admin_user_id == target_user_id {
         return error_response_generic(
@@ -714,8 +1521,6 @@ pub async fn delete_user_admin(
         );
     }
 
-    let repo = UserRepository::new((*pool).clone());
-
     match repo.delete_user(target_user_id).await {
         Ok(true) => {
             info!(
@@ -726,7 +1531,7 @@ pub async fn delete_user_admin(
                 "User Deleted".to_string(),
                 format!(
                     "User account {} has been permanently deleted",
-                    target_user_id
+                    target_public_id
                 ),
             )
         }
@@ -739,48 +1544,312 @@ pub async fn delete_user_admin(
 }
 
 /// Verify user email address
+///
+/// Browsers get a landing page instead of JSON, and with an allowed
+/// `redirect_uri` the result is passed on to the front-end as `verified` and
+/// `error` query parameters
 #[utoipa::path(
     get,
     path = "/auth/verify-email",
     params(
-        ("token" = String, Query, description = "Email verification token")
+        ("token" = String, Query, description = "Email verification token"),
+        ("redirect_uri" = Option<String>, Query, description = "Front-end URL to redirect to afterwards; its origin must be in VERIFY_EMAIL_REDIRECT_ORIGINS")
     ),
     responses(
-        (status = 200, description = "Email verified successfully", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
-        (status = 400, description = "Invalid or expired token", body = inline(crate::helpers::response::ApiErrorResponse)),
-        (status = 404, description = "User not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 200, description = "Email verified successfully", content(
+            (inline(crate::helpers::response::ApiSuccessResponse<String>) = "application/json"),
+            (String = "text/html")
+        )),
+        (status = 303, description = "Redirect to redirect_uri with verified=true, or verified=false and an error code",
+            headers(("Location" = String, description = "redirect_uri with the result appended"))),
+        (status = 400, description = "Invalid or expired token, or a redirect_uri on an origin that isn't allowed", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
     ),
     tag = "Authentication"
 )]
 pub async fn verify_email(
     State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(tokens): State<Arc<dyn LinkTokenStore>>,
+    State(config): State<Arc<AppConfig>>,
+    State(emails): State<Arc<EmailTemplates>>,
+    headers: HeaderMap,
     Query(query): Query<VerifyEmailQuery>,
-) -> UnifiedResponse<String> {
-    let user_id = match AuthHelper::extract_user_id_from_token(&query.token) {
-        Ok(id) => id,
-        Err(_) => {
-            return error_response_generic(
-                "Invalid Token".to_string(),
-                "The email verification token is invalid or has expired".to_string(),
-            );
+) -> Response {
+    if query
+        .redirect_uri
+        .as_deref()
+        .is_some_and(|uri| !is_allowed_redirect(uri, &config.email.redirect_origins))
+    {
+        return error_response_generic::<String>(
+            "Invalid Redirect".to_string(),
+            "redirect_uri must be on an allowed origin".to_string(),
+        )
+        .into_response();
+    }
+
+    // The user's name when verified, otherwise the reason it failed
+    let outcome = match tokens
+        .redeem(&query.token, LinkPurpose::VerifyEmail, clock.now())
+        .await
+    {
+        Ok(None) => Err("invalid_token"),
+        Ok(Some(grant)) => match UserRepository::new((*pool).clone())
+            .verify_email(grant.user_id, clock.now())
+            .await
+        {
+            Ok(Some(user)) => Ok(user.name),
+            Ok(None) => Err("already_verified"),
+            Err(e) => {
+                error!("Database error: {:?}", e);
+                Err("server_error")
+            }
+        },
+        Err(e) => {
+            error!("Database error: {:?}", e);
+            Err("server_error")
         }
     };
 
-    let repo = UserRepository::new((*pool).clone());
+    if let Some(redirect_uri) = &query.redirect_uri {
+        let params: &[(&str, &str)] = match outcome {
+            Ok(_) => &[("verified", "true")],
+            Err(reason) => &[("verified", "false"), ("error", reason)],
+        };
+        return Redirect::to(&with_query(redirect_uri, params)).into_response();
+    }
 
-    match repo.verify_email(user_id).await {
-        Ok(Some(_)) => success_response(
+    if prefers_html(&headers) {
+        let locale = headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(preferred_locale);
+        let link = format!("{}/", config.base_path);
+        let (status, page) = match &outcome {
+            Ok(name) => (
+                StatusCode::OK,
+                emails.render_page(
+                    LandingPage::EmailVerified,
+                    locale.as_deref(),
+                    &[("name", name), ("link", &link)],
+                ),
+            ),
+            Err(reason) => (
+                match *reason {
+                    "server_error" => StatusCode::INTERNAL_SERVER_ERROR,
+                    _ => StatusCode::BAD_REQUEST,
+                },
+                emails.render_page(
+                    LandingPage::VerificationFailed,
+                    locale.as_deref(),
+                    &[("reason", reason), ("link", &link)],
+                ),
+            ),
+        };
+        match page {
+            Ok(page) => return (status, Html(page)).into_response(),
+            Err(e) => error!("Failed to render verification page: {:?}", e),
+        }
+    }
+
+    match outcome {
+        Ok(_) => success_response(
             "Email Verified".to_string(),
             "Your email has been successfully verified".to_string(),
-        ),
-        Ok(None) => error_response_generic(
+        )
+        .into_response(),
+        Err("invalid_token") => error_response_generic::<String>(
+            "Invalid Token".to_string(),
+            "The email verification link is invalid, expired or already used".to_string(),
+        )
+        .into_response(),
+        Err("already_verified") => error_response_generic::<String>(
             "Verification Failed".to_string(),
             "User not found or already verified".to_string(),
-        ),
+        )
+        .into_response(),
+        Err(_) => ErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database Error",
+            "Unable to verify email",
+        )
+        .into_response(),
+    }
+}
+
+/// Ask before signing out the session a new sign-in alert was sent about
+///
+/// Follows the link in the alert email, so it needs no login. It only shows a
+/// page whose form posts the token back to sign the session out, so mail
+/// scanners and link previews fetching the link don't revoke anything
+#[utoipa::path(
+    get,
+    path = "/auth/revoke-session",
+    params(
+        ("token" = String, Query, description = "Token from the new sign-in alert")
+    ),
+    responses(
+        (status = 200, description = "Confirmation page with a form that signs the session out", body = String, content_type = "text/html"),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Authentication"
+)]
+pub async fn revoke_session_link(
+    State(config): State<Arc<AppConfig>>,
+    State(emails): State<Arc<EmailTemplates>>,
+    headers: HeaderMap,
+    Query(query): Query<RevokeSessionToken>,
+) -> Response {
+    let locale = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(preferred_locale);
+    let action = config.api_path("/auth/revoke-session");
+    match emails.render_page(
+        LandingPage::ConfirmRevoke,
+        locale.as_deref(),
+        &[("token", &query.token), ("link", &action)],
+    ) {
+        Ok(page) => Html(page).into_response(),
+        Err(e) => {
+            error!("Failed to render session revoke confirmation: {:?}", e);
+            ErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "Unable to show the confirmation page",
+            )
+            .into_response()
+        }
+    }
+}
+
+/// Sign out the session a new sign-in alert was sent about
+///
+/// Posted by the confirmation page of the alert's link, so it needs no login;
+/// the token works once. Browsers get a landing page instead of JSON
+#[utoipa::path(
+    post,
+    path = "/auth/revoke-session",
+    request_body(content = inline(RevokeSessionToken), content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Session revoked successfully", content(
+            (inline(crate::helpers::response::ApiSuccessResponse<String>) = "application/json"),
+            (String = "text/html")
+        )),
+        (status = 400, description = "Invalid, expired or already used token", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Session already revoked or expired", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Authentication"
+)]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn confirm_revoke_session(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(tokens): State<Arc<dyn LinkTokenStore>>,
+    State(config): State<Arc<AppConfig>>,
+    State(emails): State<Arc<EmailTemplates>>,
+    State(denylist): State<Arc<TokenDenylist>>,
+    headers: HeaderMap,
+    Form(form): Form<RevokeSessionToken>,
+) -> Response {
+    // The revoked session, otherwise the reason it wasn't
+    let outcome = match tokens
+        .redeem(&form.token, LinkPurpose::RevokeSession, clock.now())
+        .await
+    {
+        Ok(Some(LinkGrant {
+            user_id,
+            subject: Some(session_id),
+        })) => match SessionRepository::new((*pool).clone())
+            .revoke(&session_id, user_id, clock.now())
+            .await
+        {
+            Ok(true) => Ok(session_id),
+            Ok(false) => Err("already_ended"),
+            Err(e) => {
+                error!("Database error: {:?}", e);
+                Err("server_error")
+            }
+        },
+        Ok(_) => Err("invalid_token"),
         Err(e) => {
             error!("Database error: {:?}", e);
-            sql_error_generic(e, "Unable to verify email")
+            Err("server_error")
+        }
+    };
+    if let Ok(session_id) = &outcome {
+        info!("Session {} revoked from a new sign-in alert", session_id);
+        denylist.deny(std::slice::from_ref(session_id)).await;
+    }
+
+    if prefers_html(&headers) {
+        let locale = headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(preferred_locale);
+        let link = format!("{}/", config.base_path);
+        let (status, page) = match &outcome {
+            Ok(_) => (
+                StatusCode::OK,
+                emails.render_page(
+                    LandingPage::SessionRevoked,
+                    locale.as_deref(),
+                    &[("link", &link)],
+                ),
+            ),
+            Err(reason) => (
+                match *reason {
+                    "server_error" => StatusCode::INTERNAL_SERVER_ERROR,
+                    "already_ended" => StatusCode::NOT_FOUND,
+                    _ => StatusCode::BAD_REQUEST,
+                },
+                emails.render_page(
+                    LandingPage::RevokeFailed,
+                    locale.as_deref(),
+                    &[("reason", reason), ("link", &link)],
+                ),
+            ),
+        };
+        match page {
+            Ok(page) => return (status, Html(page)).into_response(),
+            Err(e) => error!("Failed to render session revoke page: {:?}", e),
         }
     }
+
+    match outcome {
+        Ok(_) => success_response(
+            "Session Revoked".to_string(),
+            "The session has been signed out".to_string(),
+        )
+        .into_response(),
+        Err("invalid_token") => error_response_generic::<String>(
+            "Invalid Token".to_string(),
+            "The link is invalid, expired or already used".to_string(),
+        )
+        .into_response(),
+        Err("already_ended") => {
+            not_found_response_generic::<String>("Session already ended".to_string())
+                .into_response()
+        }
+        Err(_) => ErrorResponse::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Database Error",
+            "Unable to revoke session",
+        )
+        .into_response(),
+    }
+}
+
+/// Public keys for verifying auth tokens
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    responses(
+        (status = 200, description = "JSON Web Key Set of the active signing keys; empty when tokens use a shared HS256 secret", body = JwkSet)
+    ),
+    tag = "Authentication"
+)]
+pub async fn jwks(State(clock): State<Arc<dyn Clock>>) -> Json<JwkSet> {
+    Json(JWT_KEYS.jwks(clock.as_ref()))
 }
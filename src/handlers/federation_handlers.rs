@@ -0,0 +1,218 @@
+use crate::config::AppConfig;
+use crate::db::repositories::{
+    follower_repo::FollowerRepository, hook_repo::HookRepository, post_repo::PostRepository,
+    user_repo::UserRepository,
+};
+use crate::helpers::clock::Clock;
+use crate::helpers::federation::{
+    ACTIVITY_JSON, ActorFetcher, Federation, InboxActivity, JRD_JSON, OUTBOX_LIMIT, delivery,
+    verify_delivery,
+};
+use crate::helpers::http_signatures::INSTANCE_KEY;
+use crate::helpers::ids::{IdGenerator, TimeOrderedIds};
+use crate::helpers::json::Json;
+use crate::helpers::path_params::ValidatedPath;
+use crate::model::model::{ErrorResponse, User, WebFingerQuery};
+use axum::{
+    body::Bytes,
+    extract::{OriginalUri, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+fn with_content_type<T: Serialize>(content_type: &'static str, body: T) -> Response {
+    ([(header::CONTENT_TYPE, content_type)], Json(body)).into_response()
+}
+
+fn not_found() -> ErrorResponse {
+    ErrorResponse::new(StatusCode::NOT_FOUND, "Not Found", "Actor not found")
+}
+
+fn database_error(err: anyhow::Error) -> ErrorResponse {
+    error!("Federation database error: {:?}", err);
+    ErrorResponse::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Database Error",
+        "Unable to load actor",
+    )
+}
+
+async fn find_actor(pool: &PgPool, name: &str) -> Result<User, ErrorResponse> {
+    UserRepository::new(pool.clone())
+        .find_by_public_id(name)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(not_found)
+}
+
+/// Resolve `acct:name@domain` to an actor
+pub async fn webfinger(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    Query(query): Query<WebFingerQuery>,
+) -> Result<Response, ErrorResponse> {
+    info!("Handler: WebFinger lookup for {}", query.resource);
+
+    let federation = Federation::new(&config.federation);
+    let name = federation
+        .resolve_resource(&query.resource)
+        .ok_or_else(not_found)?;
+    let user = find_actor(&pool, name).await?;
+
+    Ok(with_content_type(
+        JRD_JSON,
+        federation.webfinger(&user.public_id),
+    ))
+}
+
+/// Author profile as an ActivityPub `Person`
+pub async fn get_actor(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    ValidatedPath(name): ValidatedPath<String>,
+) -> Result<Response, ErrorResponse> {
+    info!("Handler: Fetching actor {}", name);
+
+    let user = find_actor(&pool, &name).await?;
+
+    Ok(with_content_type(
+        ACTIVITY_JSON,
+        Federation::new(&config.federation).actor(&user, INSTANCE_KEY.public_key_pem()),
+    ))
+}
+
+/// The author's posts as `Create` activities, newest first
+pub async fn get_outbox(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    ValidatedPath(name): ValidatedPath<String>,
+) -> Result<Response, ErrorResponse> {
+    info!("Handler: Fetching outbox of {}", name);
+
+    let user = find_actor(&pool, &name).await?;
+    let (posts, total) = PostRepository::new((*pool).clone())
+        .find_recent_by_author(user.id, OUTBOX_LIMIT)
+        .await
+        .map_err(database_error)?;
+
+    Ok(with_content_type(
+        ACTIVITY_JSON,
+        Federation::new(&config.federation).outbox(&user, &posts, total),
+    ))
+}
+
+pub async fn get_followers(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    ValidatedPath(name): ValidatedPath<String>,
+) -> Result<Response, ErrorResponse> {
+    info!("Handler: Fetching followers of {}", name);
+
+    let user = find_actor(&pool, &name).await?;
+    let total = FollowerRepository::new((*pool).clone())
+        .count_followers(user.id)
+        .await
+        .map_err(database_error)?;
+
+    Ok(with_content_type(
+        ACTIVITY_JSON,
+        Federation::new(&config.federation).followers(&user, total),
+    ))
+}
+
+/// Accepts `Follow` and `Undo { Follow }` signed by the sending actor's key;
+/// other activities are acknowledged and dropped
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn post_inbox(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(actors): State<Arc<dyn ActorFetcher>>,
+    ValidatedPath(name): ValidatedPath<String>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, ErrorResponse> {
+    info!("Handler: Inbox delivery for {}", name);
+
+    let activity: Value = serde_json::from_slice(&body).map_err(|_| {
+        ErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            "Bad Request",
+            "Body is not a JSON activity",
+        )
+    })?;
+    let sender = activity
+        .get("actor")
+        .and_then(|actor| actor.as_str())
+        .ok_or_else(|| {
+            ErrorResponse::new(
+                StatusCode::BAD_REQUEST,
+                "Bad Request",
+                "Activity has no actor",
+            )
+        })?;
+
+    let user = find_actor(&pool, &name).await?;
+    let request_target = uri
+        .path_and_query()
+        .map_or(uri.path(), |target| target.as_str());
+    let remote = verify_delivery(
+        actors.as_ref(),
+        sender,
+        request_target,
+        &headers,
+        &body,
+        clock.now(),
+    )
+    .await
+    .map_err(|e| {
+        warn!("Rejected inbox delivery from {}: {:#}", sender, e);
+        ErrorResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized",
+            "Missing or invalid HTTP signature",
+        )
+    })?;
+
+    let federation = Federation::new(&config.federation);
+    let followers = FollowerRepository::new((*pool).clone());
+
+    match federation.inbox_activity(&user.public_id, &activity) {
+        InboxActivity::Follow { follower } => {
+            followers
+                .add_follower(user.id, &follower, remote.delivery_inbox(), clock.now())
+                .await
+                .map_err(database_error)?;
+
+            let ids = TimeOrderedIds;
+            let accept = federation.accept(ids.generate(), &user.public_id, &activity);
+            HookRepository::new((*pool).clone())
+                .enqueue(&delivery(
+                    ids.generate(),
+                    "follow.accepted",
+                    None,
+                    &remote.inbox,
+                    &federation.key_id(&user.public_id),
+                    &accept,
+                    clock.now(),
+                ))
+                .await
+                .map_err(database_error)?;
+        }
+        InboxActivity::Unfollow { follower } => {
+            followers
+                .remove_follower(user.id, &follower)
+                .await
+                .map_err(database_error)?;
+        }
+        InboxActivity::Ignored => {}
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
@@ -0,0 +1,167 @@
+use crate::cache::Cache;
+use crate::config::AppConfig;
+use crate::db::pools::DbPools;
+use crate::db::repositories::{post_repo::PostRepository, user_repo::UserRepository};
+use crate::helpers::feeds::{Feed, FeedFormat};
+use crate::helpers::path_params::ValidatedPath;
+use crate::model::model::{ErrorResponse, PostResponse};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing::{error, info};
+use utoipa;
+
+/// Latest posts across the site as a feed
+#[utoipa::path(
+    get,
+    path = "/feeds/{file}",
+    params(
+        ("file" = String, Path, description = "`posts.xml` for RSS 2.0, `posts.atom` for Atom")
+    ),
+    responses(
+        (status = 200, description = "The feed", content(
+            (String = "application/rss+xml"),
+            (String = "application/atom+xml")
+        )),
+        (status = 304, description = "No post changed since `If-Modified-Since`"),
+        (status = 404, description = "No such feed", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Feeds"
+)]
+pub async fn site_feed(
+    State(db): State<DbPools>,
+    State(config): State<Arc<AppConfig>>,
+    ValidatedPath(file): ValidatedPath<String>,
+    headers: HeaderMap,
+) -> Response {
+    let format = match FeedFormat::from_file_name(&file) {
+        Some(("posts", format)) => format,
+        _ => return feed_not_found(),
+    };
+    info!("Handler: Rendering site feed as {:?}", format);
+
+    let repo = PostRepository::from_pools(&db);
+    match repo.find_latest_with_author(config.feeds.max_items).await {
+        Ok(posts) => {
+            let path = format!("/feeds/posts.{}", format.extension());
+            feed_response(&config, &path, None, posts, format, &headers)
+        }
+        Err(e) => feed_error(e),
+    }
+}
+
+/// Latest posts of one author as a feed
+#[utoipa::path(
+    get,
+    path = "/feeds/users/{file}",
+    params(
+        ("file" = String, Path, description = "The author's public ID followed by `.xml` for RSS 2.0 or `.atom` for Atom")
+    ),
+    responses(
+        (status = 200, description = "The feed", content(
+            (String = "application/rss+xml"),
+            (String = "application/atom+xml")
+        )),
+        (status = 304, description = "No post changed since `If-Modified-Since`"),
+        (status = 404, description = "No such author or feed", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Feeds"
+)]
+pub async fn user_feed(
+    State(db): State<DbPools>,
+    State(cache): State<Arc<Cache>>,
+    State(config): State<Arc<AppConfig>>,
+    ValidatedPath(file): ValidatedPath<String>,
+    headers: HeaderMap,
+) -> Response {
+    let Some((id, format)) = FeedFormat::from_file_name(&file) else {
+        return feed_not_found();
+    };
+    info!("Handler: Rendering feed of user {} as {:?}", id, format);
+
+    let users = UserRepository::from_pools(&db).with_cache(cache);
+    let profile = match users.find_public_profile(id).await {
+        Ok(Some(profile)) => profile,
+        Ok(None) => return feed_not_found(),
+        Err(e) => return feed_error(e),
+    };
+
+    let repo = PostRepository::from_pools(&db);
+    match repo.find_latest_by_author(id, config.feeds.max_items).await {
+        Ok(posts) => feed_response(
+            &config,
+            &format!("/feeds/users/{}", file),
+            Some(&profile.name),
+            posts,
+            format,
+            &headers,
+        ),
+        Err(e) => feed_error(e),
+    }
+}
+
+/// The rendered feed, or 304 when the client's copy is still current
+fn feed_response(
+    config: &AppConfig,
+    path: &str,
+    subtitle: Option<&str>,
+    posts: Vec<PostResponse>,
+    format: FeedFormat,
+    headers: &HeaderMap,
+) -> Response {
+    let feed = Feed::from_posts(config, path, subtitle, posts);
+    let updated = feed.updated();
+    let cache_headers = [
+        (
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", config.feeds.max_age.as_secs()),
+        ),
+        (header::LAST_MODIFIED, http_date(updated)),
+    ];
+
+    // HTTP dates have whole seconds, so compare at that precision
+    let unchanged = headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .is_some_and(|since| since.timestamp() >= updated.timestamp());
+    if unchanged {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+
+    (
+        cache_headers,
+        [(header::CONTENT_TYPE, format.content_type())],
+        feed.render(format),
+    )
+        .into_response()
+}
+
+fn http_date(date: DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn feed_not_found() -> Response {
+    ErrorResponse::new(
+        StatusCode::NOT_FOUND,
+        "Not Found".to_string(),
+        "No such feed".to_string(),
+    )
+    .into_response()
+}
+
+fn feed_error(e: anyhow::Error) -> Response {
+    error!("Handler: Failed to load feed posts: {}", e);
+    ErrorResponse::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Feed Unavailable".to_string(),
+        "Unable to load the feed".to_string(),
+    )
+    .into_response()
+}
@@ -0,0 +1,156 @@
+use crate::config::AppConfig;
+use crate::db::health::DbHealth;
+use crate::db::pools::DbPools;
+use crate::db::repositories::post_repo::PostRepository;
+use crate::helpers::markdown::escape_html;
+use crate::helpers::negotiation::prefers_html;
+use crate::helpers::response::{UnifiedResponse, create_response, success_response};
+use crate::model::model::{ApiResponse, HomeResponse, Limits, Readiness};
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::{error, info};
+use utoipa;
+
+/// API homepage
+#[utoipa::path(
+    get,
+    path = "/",
+    responses(
+        (status = 200, description = "API name, docs link, status and featured posts; HTML when the client accepts text/html", body = inline(crate::helpers::response::ApiSuccessResponse<HomeResponse>))
+    ),
+    tag = "Home"
+)]
+pub async fn home(
+    State(db): State<DbPools>,
+    State(config): State<Arc<AppConfig>>,
+    headers: HeaderMap,
+) -> Response {
+    info!("Handler: Rendering homepage");
+
+    let homepage = &config.homepage;
+    let repo = PostRepository::from_pools(&db);
+
+    let (status, featured_posts) = match repo.find_latest_with_author(homepage.featured_posts).await
+    {
+        Ok(posts) => ("ok", posts),
+        Err(e) => {
+            error!("Handler: Failed to load featured posts: {}", e);
+            ("degraded", Vec::new())
+        }
+    };
+
+    let home = HomeResponse {
+        name: homepage.api_name.clone(),
+        tagline: homepage.tagline.clone(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        docs_url: homepage.docs_url.clone(),
+        status: status.to_string(),
+        featured_posts,
+    };
+
+    if prefers_html(&headers) {
+        Html(render_home(&home, &config.base_path)).into_response()
+    } else {
+        success_response("Welcome".to_string(), home).into_response()
+    }
+}
+
+/// Largest titles, posts, names and report details the API accepts, so clients
+/// can check input before sending it
+#[utoipa::path(
+    get,
+    path = "/limits",
+    responses(
+        (status = 200, description = "Field limits, in characters", body = inline(crate::helpers::response::ApiSuccessResponse<Limits>))
+    ),
+    tag = "Home"
+)]
+pub async fn get_limits(State(config): State<Arc<AppConfig>>) -> UnifiedResponse<Limits> {
+    success_response("Limits Retrieved".to_string(), config.limits)
+}
+
+fn render_home(home: &HomeResponse, base_path: &str) -> String {
+    let posts: String = home
+        .featured_posts
+        .iter()
+        .map(|post| {
+            format!(
+                r#"<li><a href="{}/posts/{}">{}</a> by {}</li>"#,
+                escape_html(base_path),
+                escape_html(&post.id),
+                escape_html(&post.title),
+                escape_html(&post.author.name)
+            )
+        })
+        .collect();
+
+    format!(
+        r#"
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <title>{name}</title>
+        </head>
+        <body>
+            <h1>{name}</h1>
+            <p>{tagline}</p>
+            <p>Version {version} &middot; Status: {status}</p>
+            <p><a href="{docs_url}">API documentation</a></p>
+            <h2>Featured posts</h2>
+            <ul>{posts}</ul>
+        </body>
+        </html>
+    "#,
+        name = escape_html(&home.name),
+        tagline = escape_html(&home.tagline),
+        version = escape_html(&home.version),
+        status = escape_html(&home.status),
+        docs_url = escape_html(&home.docs_url),
+        posts = posts,
+    )
+}
+
+/// Liveness probe: answers as long as the process is serving requests
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    responses(
+        (status = 200, description = "Process is up", body = inline(crate::helpers::response::ApiSuccessResponse<String>))
+    ),
+    tag = "Home"
+)]
+pub async fn liveness() -> UnifiedResponse<String> {
+    success_response("Alive".to_string(), "ok".to_string())
+}
+
+/// Readiness probe: fails while the database is unreachable or the connection
+/// pool is exhausted, so traffic goes to other instances until it recovers
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    responses(
+        (status = 200, description = "Ready to serve traffic", body = inline(crate::helpers::response::ApiSuccessResponse<Readiness>)),
+        (status = 503, description = "Database down or connection pool exhausted", body = inline(crate::helpers::response::ApiSuccessResponse<Readiness>))
+    ),
+    tag = "Home"
+)]
+pub async fn readiness(
+    State(health): State<Arc<DbHealth>>,
+) -> (StatusCode, Json<ApiResponse<Readiness>>) {
+    let readiness = health.readiness();
+    if readiness.ready {
+        create_response("Ready".to_string(), Some(readiness), StatusCode::OK)
+    } else {
+        create_response(
+            "Not Ready".to_string(),
+            Some(readiness),
+            StatusCode::SERVICE_UNAVAILABLE,
+        )
+    }
+}
@@ -1,2 +1,11 @@
+pub mod admin_handlers;
 pub mod auth_handlers;
+#[cfg(feature = "activitypub")]
+pub mod federation_handlers;
+pub mod feed_handlers;
+pub mod home_handlers;
+pub mod notification_handlers;
+pub mod org_handlers;
 pub mod post_handlers;
+pub mod sitemap_handlers;
+pub mod webhook_handlers;
@@ -0,0 +1,170 @@
+use crate::db::repositories::notification_repo::NotificationRepository;
+use crate::helpers::clock::Clock;
+use crate::helpers::notifications::{DEFAULT_NOTIFICATION_LIMIT, MAX_NOTIFICATION_LIMIT};
+use crate::helpers::path_params::ValidatedPath;
+use crate::helpers::response::{
+    UnifiedResponse, not_found_response_generic, sql_error_generic, success_response,
+};
+use crate::model::model::{Notification, NotificationSummary, NotificationsQuery};
+use axum::extract::{Extension, Query, State};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+use utoipa;
+use uuid::Uuid;
+
+/// The caller's notifications, newest first
+#[utoipa::path(
+    get,
+    path = "/notifications",
+    params(
+        ("unread" = Option<bool>, Query, description = "Only unread notifications"),
+        ("before" = Option<String>, Query, description = "RFC 3339 time; only notifications created before it, for paging"),
+        ("limit" = Option<i64>, Query, description = "Defaults to 50, at most 100")
+    ),
+    responses(
+        (status = 200, description = "Notifications retrieved", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<Notification>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Notifications"
+)]
+pub async fn list_notifications(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    Query(query): Query<NotificationsQuery>,
+) -> UnifiedResponse<Vec<Notification>> {
+    info!("Handler: Listing notifications for user_id: {}", user_id);
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_NOTIFICATION_LIMIT)
+        .clamp(1, MAX_NOTIFICATION_LIMIT);
+    let repo = NotificationRepository::new((*pool).clone());
+
+    match repo.list(user_id, query.unread, query.before, limit).await {
+        Ok(notifications) => success_response("Notifications Retrieved".to_string(), notifications),
+        Err(e) => {
+            error!("Handler: Failed to list notifications: {}", e);
+            sql_error_generic(e, "Unable to retrieve notifications")
+        }
+    }
+}
+
+/// Unread count for badges; cheap enough to poll
+#[utoipa::path(
+    get,
+    path = "/notifications/summary",
+    responses(
+        (status = 200, description = "Summary retrieved", body = inline(crate::helpers::response::ApiSuccessResponse<NotificationSummary>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Notifications"
+)]
+pub async fn get_notification_summary(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+) -> UnifiedResponse<NotificationSummary> {
+    summary(&pool, user_id).await
+}
+
+async fn summary(pool: &PgPool, user_id: Uuid) -> UnifiedResponse<NotificationSummary> {
+    match NotificationRepository::new(pool.clone())
+        .summary(user_id)
+        .await
+    {
+        Ok(summary) => success_response("Notification Summary Retrieved".to_string(), summary),
+        Err(e) => {
+            error!("Handler: Failed to summarize notifications: {}", e);
+            sql_error_generic(e, "Unable to retrieve notification summary")
+        }
+    }
+}
+
+/// Mark one notification read
+#[utoipa::path(
+    post,
+    path = "/notifications/{id}/read",
+    params(
+        ("id" = Uuid, Path, description = "Notification to mark read")
+    ),
+    responses(
+        (status = 200, description = "Marked read; returns the updated summary", body = inline(crate::helpers::response::ApiSuccessResponse<NotificationSummary>)),
+        (status = 400, description = "The id is not a UUID", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Notification not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Notifications"
+)]
+pub async fn mark_notification_read(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    ValidatedPath(id): ValidatedPath<Uuid>,
+) -> UnifiedResponse<NotificationSummary> {
+    info!(
+        "Handler: Marking notification {} read for user_id: {}",
+        id, user_id
+    );
+
+    let repo = NotificationRepository::new((*pool).clone());
+
+    match repo.mark_read(user_id, id, clock.now()).await {
+        Ok(true) => summary(&pool, user_id).await,
+        Ok(false) => not_found_response_generic("Notification not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to mark notification {} read: {}", id, e);
+            sql_error_generic(e, "Unable to update notification")
+        }
+    }
+}
+
+/// Mark every notification read
+#[utoipa::path(
+    post,
+    path = "/notifications/read-all",
+    responses(
+        (status = 200, description = "All marked read; returns the updated summary", body = inline(crate::helpers::response::ApiSuccessResponse<NotificationSummary>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Notifications"
+)]
+pub async fn mark_all_notifications_read(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+) -> UnifiedResponse<NotificationSummary> {
+    info!(
+        "Handler: Marking all notifications read for user_id: {}",
+        user_id
+    );
+
+    let repo = NotificationRepository::new((*pool).clone());
+
+    match repo.mark_all_read(user_id, clock.now()).await {
+        Ok(_) => summary(&pool, user_id).await,
+        Err(e) => {
+            error!("Handler: Failed to mark notifications read: {}", e);
+            sql_error_generic(e, "Unable to update notifications")
+        }
+    }
+}
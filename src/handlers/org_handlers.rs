@@ -0,0 +1,544 @@
+use crate::db::repositories::{org_repo::OrgRepository, user_repo::UserRepository};
+use crate::helpers::clock::Clock;
+use crate::helpers::json::Json;
+use crate::helpers::path_params::ValidatedPath;
+use crate::helpers::response::{
+    UnifiedResponse, error_response_generic, forbidden_response_generic,
+    not_found_response_generic, sql_error_generic, success_response,
+};
+use crate::model::model::{
+    AddMemberRequest, CreateOrganizationRequest, MemberResponse, OrgRole, Organization,
+    OrganizationResponse, UpdateMemberRequest, UpdateOrganizationRequest,
+};
+use axum::extract::{Extension, State};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+use utoipa;
+use uuid::Uuid;
+
+/// Resolves an organization and the caller's role in it; non-members get a 404
+async fn load_membership<T>(
+    repo: &OrgRepository,
+    org_public_id: &str,
+    user_id: Uuid,
+) -> Result<(Organization, OrgRole), UnifiedResponse<T>> {
+    let org = match repo.find_by_public_id(org_public_id).await {
+        Ok(Some(org)) => org,
+        Ok(None) => {
+            return Err(not_found_response_generic(
+                "Organization not found".to_string(),
+            ));
+        }
+        Err(e) => {
+            error!("Handler: Failed to look up organization: {}", e);
+            return Err(sql_error_generic(e, "Unable to find organization"));
+        }
+    };
+
+    match repo.membership_role(org.id, user_id).await {
+        Ok(Some(role)) => Ok((org, role)),
+        Ok(None) => Err(not_found_response_generic(
+            "Organization not found".to_string(),
+        )),
+        Err(e) => {
+            error!("Handler: Failed to look up membership: {}", e);
+            Err(sql_error_generic(e, "Unable to verify membership"))
+        }
+    }
+}
+
+/// Create an organization
+#[utoipa::path(
+    post,
+    path = "/orgs",
+    request_body = CreateOrganizationRequest,
+    responses(
+        (status = 200, description = "Organization created; the caller becomes its owner", body = inline(crate::helpers::response::ApiSuccessResponse<OrganizationResponse>)),
+        (status = 400, description = "Validation error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Organizations"
+)]
+pub async fn create_org(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> UnifiedResponse<OrganizationResponse> {
+    info!("Handler: Creating organization for user_id: {}", user_id);
+
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return error_response_generic(
+            "Creation Failed".to_string(),
+            "Organization name is required".to_string(),
+        );
+    }
+
+    let repo = OrgRepository::new((*pool).clone());
+
+    match repo.create_org(name, user_id, clock.now()).await {
+        Ok(org) => success_response(
+            "Organization Created".to_string(),
+            OrganizationResponse::new(org, OrgRole::OWNER),
+        ),
+        Err(e) => {
+            error!("Handler: Failed to create organization: {}", e);
+            sql_error_generic(e, "Unable to create organization")
+        }
+    }
+}
+
+/// List the current user's organizations
+#[utoipa::path(
+    get,
+    path = "/orgs",
+    responses(
+        (status = 200, description = "Organizations the user belongs to", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<OrganizationResponse>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Organizations"
+)]
+pub async fn list_orgs(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+) -> UnifiedResponse<Vec<OrganizationResponse>> {
+    info!("Handler: Listing organizations for user_id: {}", user_id);
+
+    let repo = OrgRepository::new((*pool).clone());
+
+    match repo.list_for_user(user_id).await {
+        Ok(orgs) => success_response(
+            "Organizations Retrieved".to_string(),
+            orgs.into_iter()
+                .map(|(org, role)| OrganizationResponse::new(org, role))
+                .collect(),
+        ),
+        Err(e) => {
+            error!("Handler: Failed to list organizations: {}", e);
+            sql_error_generic(e, "Unable to retrieve organizations")
+        }
+    }
+}
+
+/// Get an organization
+#[utoipa::path(
+    get,
+    path = "/orgs/{id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the organization")
+    ),
+    responses(
+        (status = 200, description = "Organization retrieved", body = inline(crate::helpers::response::ApiSuccessResponse<OrganizationResponse>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Organization not found or not a member", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Organizations"
+)]
+pub async fn get_org(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    ValidatedPath(id): ValidatedPath<String>,
+) -> UnifiedResponse<OrganizationResponse> {
+    info!(
+        "Handler: Retrieving organization {} for user_id: {}",
+        id, user_id
+    );
+
+    let repo = OrgRepository::new((*pool).clone());
+
+    match load_membership(&repo, &id, user_id).await {
+        Ok((org, role)) => success_response(
+            "Organization Retrieved".to_string(),
+            OrganizationResponse::new(org, role),
+        ),
+        Err(response) => response,
+    }
+}
+
+/// Rename an organization (owners only)
+#[utoipa::path(
+    put,
+    path = "/orgs/{id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the organization")
+    ),
+    request_body = UpdateOrganizationRequest,
+    responses(
+        (status = 200, description = "Organization updated", body = inline(crate::helpers::response::ApiSuccessResponse<OrganizationResponse>)),
+        (status = 400, description = "Validation error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Owner role required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Organization not found or not a member", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Organizations"
+)]
+pub async fn update_org(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    ValidatedPath(id): ValidatedPath<String>,
+    Json(payload): Json<UpdateOrganizationRequest>,
+) -> UnifiedResponse<OrganizationResponse> {
+    info!(
+        "Handler: Updating organization {} for user_id: {}",
+        id, user_id
+    );
+
+    let repo = OrgRepository::new((*pool).clone());
+
+    let (org, role) = match load_membership(&repo, &id, user_id).await {
+        Ok(membership) => membership,
+        Err(response) => return response,
+    };
+
+    if !role.is_owner() {
+        return forbidden_response_generic("Only owners can update the organization".to_string());
+    }
+
+    let name = payload.name.unwrap_or(org.name).trim().to_string();
+    if name.is_empty() {
+        return error_response_generic(
+            "Update Failed".to_string(),
+            "Organization name cannot be empty".to_string(),
+        );
+    }
+
+    match repo.update_org(org.id, name, clock.now()).await {
+        Ok(Some(org)) => success_response(
+            "Organization Updated".to_string(),
+            OrganizationResponse::new(org, role),
+        ),
+        Ok(None) => not_found_response_generic("Organization not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to update organization: {}", e);
+            sql_error_generic(e, "Unable to update organization")
+        }
+    }
+}
+
+/// Delete an organization and its posts (owners only)
+#[utoipa::path(
+    delete,
+    path = "/orgs/{id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the organization")
+    ),
+    responses(
+        (status = 200, description = "Organization deleted", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Owner role required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Organization not found or not a member", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Organizations"
+)]
+pub async fn delete_org(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    ValidatedPath(id): ValidatedPath<String>,
+) -> UnifiedResponse<Value> {
+    info!(
+        "Handler: Deleting organization {} for user_id: {}",
+        id, user_id
+    );
+
+    let repo = OrgRepository::new((*pool).clone());
+
+    let (org, role) = match load_membership(&repo, &id, user_id).await {
+        Ok(membership) => membership,
+        Err(response) => return response,
+    };
+
+    if !role.is_owner() {
+        return forbidden_response_generic("Only owners can delete the organization".to_string());
+    }
+
+    match repo.delete_org(org.id).await {
+        Ok(true) => success_response("Organization Deleted".to_string(), Value::Null),
+        Ok(false) => not_found_response_generic("Organization not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to delete organization: {}", e);
+            sql_error_generic(e, "Unable to delete organization")
+        }
+    }
+}
+
+/// List organization members
+#[utoipa::path(
+    get,
+    path = "/orgs/{id}/members",
+    params(
+        ("id" = String, Path, description = "Public ID of the organization")
+    ),
+    responses(
+        (status = 200, description = "Members retrieved", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<MemberResponse>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Organization not found or not a member", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Organizations"
+)]
+pub async fn list_members(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    ValidatedPath(id): ValidatedPath<String>,
+) -> UnifiedResponse<Vec<MemberResponse>> {
+    info!("Handler: Listing members of organization {}", id);
+
+    let repo = OrgRepository::new((*pool).clone());
+
+    let (org, _) = match load_membership(&repo, &id, user_id).await {
+        Ok(membership) => membership,
+        Err(response) => return response,
+    };
+
+    match repo.list_members(org.id).await {
+        Ok(members) => success_response("Members Retrieved".to_string(), members),
+        Err(e) => {
+            error!("Handler: Failed to list members: {}", e);
+            sql_error_generic(e, "Unable to retrieve members")
+        }
+    }
+}
+
+/// Add a member to an organization (owners only)
+#[utoipa::path(
+    post,
+    path = "/orgs/{id}/members",
+    params(
+        ("id" = String, Path, description = "Public ID of the organization")
+    ),
+    request_body = AddMemberRequest,
+    responses(
+        (status = 200, description = "Member added, or their role updated if already a member", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<MemberResponse>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Owner role required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Organization or user not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Organizations"
+)]
+pub async fn add_member(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    ValidatedPath(id): ValidatedPath<String>,
+    Json(payload): Json<AddMemberRequest>,
+) -> UnifiedResponse<Vec<MemberResponse>> {
+    info!(
+        "Handler: Adding member {} to organization {}",
+        payload.user_id, id
+    );
+
+    set_member_role(
+        pool,
+        clock.as_ref(),
+        user_id,
+        id,
+        payload.user_id,
+        payload.role,
+    )
+    .await
+}
+
+/// Change a member's role (owners only)
+#[utoipa::path(
+    put,
+    path = "/orgs/{id}/members/{user_id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the organization"),
+        ("user_id" = String, Path, description = "Public ID of the member")
+    ),
+    request_body = UpdateMemberRequest,
+    responses(
+        (status = 200, description = "Member role updated", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<MemberResponse>>)),
+        (status = 400, description = "Owners cannot change their own role", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Owner role required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Organization or user not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Organizations"
+)]
+pub async fn update_member(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    ValidatedPath((id, member_id)): ValidatedPath<(String, String)>,
+    Json(payload): Json<UpdateMemberRequest>,
+) -> UnifiedResponse<Vec<MemberResponse>> {
+    info!(
+        "Handler: Updating member {} in organization {}",
+        member_id, id
+    );
+
+    set_member_role(pool, clock.as_ref(), user_id, id, member_id, payload.role).await
+}
+
+async fn set_member_role(
+    pool: Arc<PgPool>,
+    clock: &dyn Clock,
+    user_id: Uuid,
+    org_public_id: String,
+    member_public_id: String,
+    role: OrgRole,
+) -> UnifiedResponse<Vec<MemberResponse>> {
+    let repo = OrgRepository::new((*pool).clone());
+
+    let (org, caller_role) = match load_membership(&repo, &org_public_id, user_id).await {
+        Ok(membership) => membership,
+        Err(response) => return response,
+    };
+
+    if !caller_role.is_owner() {
+        return forbidden_response_generic("Only owners can manage members".to_string());
+    }
+
+    let member = match UserRepository::new((*pool).clone())
+        .find_by_public_id(&member_public_id)
+        .await
+    {
+        Ok(Some(member)) => member,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up user: {}", e);
+            return sql_error_generic(e, "Unable to find user");
+        }
+    };
+
+    // Keeps every organization with at least one owner
+    if member.id == user_id {
+        return error_response_generic(
+            "Invalid Operation".to_string(),
+            "Owners cannot change their own role".to_string(),
+        );
+    }
+
+    if let Err(e) = repo
+        .upsert_member(org.id, member.id, role, clock.now())
+        .await
+    {
+        error!("Handler: Failed to set member role: {}", e);
+        return sql_error_generic(e, "Unable to update member");
+    }
+
+    match repo.list_members(org.id).await {
+        Ok(members) => success_response("Members Updated".to_string(), members),
+        Err(e) => {
+            error!("Handler: Failed to list members: {}", e);
+            sql_error_generic(e, "Unable to retrieve members")
+        }
+    }
+}
+
+/// Remove a member (owners), or leave an organization (any member)
+#[utoipa::path(
+    delete,
+    path = "/orgs/{id}/members/{user_id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the organization"),
+        ("user_id" = String, Path, description = "Public ID of the member")
+    ),
+    responses(
+        (status = 200, description = "Member removed", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
+        (status = 400, description = "Owners cannot leave their own organization", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Owner role required", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Organization or member not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Organizations"
+)]
+pub async fn remove_member(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    ValidatedPath((id, member_id)): ValidatedPath<(String, String)>,
+) -> UnifiedResponse<Value> {
+    info!(
+        "Handler: Removing member {} from organization {}",
+        member_id, id
+    );
+
+    let repo = OrgRepository::new((*pool).clone());
+
+    let (org, caller_role) = match load_membership(&repo, &id, user_id).await {
+        Ok(membership) => membership,
+        Err(response) => return response,
+    };
+
+    let member = match UserRepository::new((*pool).clone())
+        .find_by_public_id(&member_id)
+        .await
+    {
+        Ok(Some(member)) => member,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up user: {}", e);
+            return sql_error_generic(e, "Unable to find user");
+        }
+    };
+
+    let leaving = member.id == user_id;
+    if leaving && caller_role.is_owner() {
+        return error_response_generic(
+            "Invalid Operation".to_string(),
+            "Owners cannot leave their own organization; delete it or hand over ownership first"
+                .to_string(),
+        );
+    }
+    if !leaving && !caller_role.is_owner() {
+        return forbidden_response_generic("Only owners can remove members".to_string());
+    }
+
+    match repo.remove_member(org.id, member.id).await {
+        Ok(true) => success_response("Member Removed".to_string(), Value::Null),
+        Ok(false) => not_found_response_generic("Member not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to remove member: {}", e);
+            sql_error_generic(e, "Unable to remove member")
+        }
+    }
+}
@@ -1,30 +1,194 @@
-use crate::db::repositories::post_repo::PostRepository;
+use crate::cache::Cache;
+use crate::config::AppConfig;
+use crate::db::pools::DbPools;
+use crate::db::repositories::{
+    collaborator_repo::CollaboratorRepository, media_repo::MediaRepository,
+    mention_repo::MentionRepository, org_repo::OrgRepository, post_repo::PostRepository,
+    report_repo::ReportRepository, session_repo::SessionRepository,
+    share_link_repo::ShareLinkRepository, user_repo::UserRepository,
+};
+use crate::helpers::bulk_posts::{self, BulkCreatePostsRequest, BulkCreateReport, MAX_BULK_POSTS};
+use crate::helpers::clock::Clock;
+use crate::helpers::denylist::TokenDenylist;
+use crate::helpers::edge_cache::EdgeCache;
+use crate::helpers::feature_flags::FeatureFlags;
+use crate::helpers::fields::{FieldSelection, Sparse};
+use crate::helpers::json::Json;
+use crate::helpers::licenses::normalize_license;
+use crate::helpers::link_tokens::{hash_link_token, new_link_token};
+use crate::helpers::markdown::{extract_images, extract_mentions};
+use crate::helpers::merge_patch::MergePatch;
+use crate::helpers::middleware::OptionalUser;
+use crate::helpers::notifications::{NotificationEvent, notify};
+use crate::helpers::path_params::ValidatedPath;
+use crate::helpers::policy::{self, Actor, Authorize};
+use crate::helpers::post_archive::{
+    self, ArchiveFormat, ArchiveQuery, PortablePost, PostArchive, PostImportReport, parse_json,
+    parse_zip, to_json, to_zip,
+};
+use crate::helpers::post_export::{self, ExportFormat, ExportQuery};
+use crate::helpers::previews::{
+    PostPreview, PreviewLink, issue_preview_token, post_meta, verify_preview_token,
+};
+use crate::helpers::publish_hooks::{PublishEvent, enqueue_publish_hooks, post_url};
 use crate::helpers::response::{
-    UnifiedResponse, error_response_generic, not_found_response_generic, sql_error_generic,
-    success_response,
+    UnifiedResponse, created_response, error_response_generic, not_found_response_generic,
+    sql_error_generic, success_response,
+};
+use crate::helpers::slugs::validate_slug;
+use crate::helpers::validation::{check_alt_text, check_length, validate_post_fields};
+use crate::helpers::versioning::ApiVersion;
+use crate::helpers::views::{trending_limit, viewer_key};
+use crate::model::model::{
+    self, AddCollaboratorRequest, ContentFormat, ContentFormatQuery, CreatePostRequest,
+    CreateReportRequest, CreateShareLinkRequest, CreatedShareLink, ErrorResponse, FeatureFlag,
+    FieldsQuery, IncludeQuery, MediaMissingAlt, MyPostsQuery, OrgContext, PatchPostRequest, Post,
+    PostCollaborator, PostInclude, PostIncluded, PostResponse, PostStatus, PostWithIncluded,
+    RelatedPost, Report, SessionId, ShareLink, ShareQuery, TrendingQuery, UpdatePostRequest,
+    Visibility,
 };
-use crate::model::model::{self, CreatePostRequest, PostResponse, UpdatePostRequest};
 use axum::{
-    Json,
-    extract::{Extension, Path, State},
+    body::Bytes,
+    extract::{ConnectInfo, Extension, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
 };
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 use sqlx::PgPool;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tracing::{error, info};
 use utoipa;
 use uuid::Uuid;
 
+/// Other posts by the author returned with `?include=author_posts`
+const INCLUDED_AUTHOR_POSTS: i64 = 5;
+
+/// Queues publish hook notifications, and deliveries to the author's
+/// ActivityPub followers; failures are logged and never fail the request
+async fn notify_publish_hooks(
+    pool: &PgPool,
+    config: &AppConfig,
+    clock: &dyn Clock,
+    event: PublishEvent,
+    post: &Post,
+) {
+    if let Err(e) =
+        enqueue_publish_hooks(pool, &config.publish_hooks, event, post, clock.now()).await
+    {
+        error!(
+            "Handler: Failed to queue publish hooks for post {}: {}",
+            post.public_id, e
+        );
+    }
+
+    #[cfg(feature = "activitypub")]
+    if let Err(e) = crate::helpers::federation::enqueue_post_activities(
+        pool,
+        &config.federation,
+        event,
+        post,
+        clock.now(),
+    )
+    .await
+    {
+        error!(
+            "Handler: Failed to queue deliveries to followers for post {}: {}",
+            post.public_id, e
+        );
+    }
+}
+
+/// Records the post's images for the alt text report; failures are logged and never fail the request
+async fn record_media(pool: &PgPool, post: &Post) {
+    let media = MediaRepository::new(pool.clone());
+    if let Err(e) = media
+        .replace_for_post(post.id, &extract_images(&post.content))
+        .await
+    {
+        error!(
+            "Handler: Failed to record media of post {}: {}",
+            post.public_id, e
+        );
+    }
+}
+
+/// Records the users the post mentions and notifies those it didn't mention
+/// before, apart from its author and those who can't read it: non-members
+/// inside an organization, and anyone but collaborators on a private post;
+/// failures are logged and never fail the request
+async fn record_mentions(pool: &PgPool, clock: &dyn Clock, post: &Post) {
+    let mentions = MentionRepository::new(pool.clone());
+    let newly_mentioned = match mentions
+        .replace_for_post(post.id, &extract_mentions(&post.content))
+        .await
+    {
+        Ok(newly_mentioned) => newly_mentioned,
+        Err(e) => {
+            error!(
+                "Handler: Failed to record mentions of post {}: {}",
+                post.public_id, e
+            );
+            return;
+        }
+    };
+
+    let orgs = OrgRepository::new(pool.clone());
+    let collaborators = CollaboratorRepository::new(pool.clone());
+    for user_id in newly_mentioned {
+        if user_id == post.author_id {
+            continue;
+        }
+        if let Some(org_id) = post.org_id {
+            match orgs.membership_role(org_id, user_id).await {
+                Ok(Some(_)) => {}
+                Ok(None) => continue,
+                Err(e) => {
+                    error!("Handler: Failed to check membership of {}: {}", user_id, e);
+                    continue;
+                }
+            }
+        }
+        if post.visibility == Visibility::PRIVATE {
+            match collaborators.role(post.id, user_id).await {
+                Ok(Some(_)) => {}
+                Ok(None) => continue,
+                Err(e) => {
+                    error!("Handler: Failed to check collaborator {}: {}", user_id, e);
+                    continue;
+                }
+            }
+        }
+        notify(
+            pool,
+            user_id,
+            NotificationEvent::Mentioned {
+                post_id: &post.public_id,
+                title: &post.title,
+            },
+            clock.now(),
+        )
+        .await;
+    }
+}
+
 /// Create a new post
 #[utoipa::path(
     post,
     path = "/posts",
+    params(
+        ("X-Org-Id" = Option<String>, Header, description = "Create the post inside this organization (requires EDITOR or OWNER)")
+    ),
     request_body = CreatePostRequest,
     responses(
-        (status = 200, description = "Post created successfully", body = inline(crate::helpers::response::ApiSuccessResponse<PostResponse>)),
-        (status = 400, description = "Validation error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 201, description = "Post created successfully", body = inline(crate::helpers::response::ApiSuccessResponse<PostResponse>),
+            headers(("Location" = String, description = "Path of the new post"))),
+        (status = 400, description = "Validation error, an image without required alt text, or the slug is reserved or taken", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
-        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+        (status = 403, description = "Forbidden - Not an editor of the organization", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 503, description = "Post creation is switched off", body = inline(crate::helpers::response::ApiErrorResponse))
     ),
     security(
         ("bearer_auth" = []),
@@ -32,13 +196,25 @@ use uuid::Uuid;
     ),
     tag = "Posts"
 )]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
 pub async fn create_post(
     State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    State(flags): State<Arc<FeatureFlags>>,
     Extension(user_id): Extension<Uuid>,
-    Json(payload): Json<CreatePostRequest>,
+    org: Option<Extension<OrgContext>>,
+    version: ApiVersion,
+    Json(mut payload): Json<CreatePostRequest>,
 ) -> UnifiedResponse<PostResponse> {
     info!("Handler: Creating new post for user_id: {}", user_id);
 
+    if let Err(err) = flags.require(FeatureFlag::PostCreation) {
+        return UnifiedResponse::Error(*err);
+    }
+
     if payload.title.trim().is_empty() || payload.content.trim().is_empty() {
         error!("Validation error: Title and content cannot be empty");
         return error_response_generic(
@@ -47,26 +223,99 @@ pub async fn create_post(
         );
     }
 
-    let repo = PostRepository::new((*pool).clone());
+    if let Err(message) =
+        validate_post_fields(Some(&payload.title), Some(&payload.content), &config.limits)
+    {
+        return error_response_generic("Creation Failed".to_string(), message);
+    }
 
-    match repo.create_post(payload, user_id).await {
-        Ok(post) => match repo.find_by_id_with_author(post.id).await {
-            Ok(Some(post_response)) => success_response("Post Created".to_string(), post_response),
-            Ok(None) => {
-                error!("Post created but not found: {}", post.id);
-                error_response_generic(
-                    "Creation Failed".to_string(),
-                    "Post was created but could not be retrieved".to_string(),
-                )
+    if let Err(message) = check_alt_text(&config, &payload.content) {
+        return error_response_generic("Creation Failed".to_string(), message);
+    }
+
+    payload.license = match payload.license.as_deref().map(normalize_license) {
+        Some(Ok(license)) => Some(license),
+        Some(Err(message)) => {
+            return error_response_generic("Creation Failed".to_string(), message);
+        }
+        None => config.default_license.clone(),
+    };
+
+    if let Err(err) = policy::can_write_in_org(org.as_deref()) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let repo = PostRepository::new((*pool).clone()).with_cache(cache);
+    let org_id = org.map(|Extension(org)| org.org_id);
+
+    let slug = match payload.slug.as_deref() {
+        Some(slug) => {
+            if let Err(message) = validate_slug(slug, &config.slugs) {
+                return error_response_generic("Creation Failed".to_string(), message);
             }
+            match repo.slug_taken(slug).await {
+                Ok(false) => slug.to_string(),
+                Ok(true) => {
+                    return error_response_generic(
+                        "Creation Failed".to_string(),
+                        "Slug is already in use".to_string(),
+                    );
+                }
+                Err(e) => {
+                    error!("Handler: Failed to check slug: {}", e);
+                    return sql_error_generic(e, "Unable to create post");
+                }
+            }
+        }
+        None => match repo.free_slug(&payload.title, &config.slugs).await {
+            Ok(slug) => slug,
             Err(e) => {
-                error!(
-                    "Handler: Failed to retrieve created post with author info: {}",
-                    e
-                );
-                sql_error_generic(e, "Unable to retrieve post details")
+                error!("Handler: Failed to pick a slug: {}", e);
+                return sql_error_generic(e, "Unable to create post");
             }
         },
+    };
+
+    match repo
+        .create_post(payload, user_id, org_id, slug, clock.now())
+        .await
+    {
+        Ok(post) => {
+            record_mentions(&pool, clock.as_ref(), &post).await;
+            match repo.find_by_id_with_author(post.id).await {
+                Ok(Some(post_response)) => {
+                    record_media(&pool, &post).await;
+                    notify_publish_hooks(
+                        &pool,
+                        &config,
+                        clock.as_ref(),
+                        PublishEvent::Published,
+                        &post,
+                    )
+                    .await;
+                    edge.purge_post(&post.public_id);
+                    created_response(
+                        config.versioned_path(version, &format!("/posts/{}", post.public_id)),
+                        "Post Created".to_string(),
+                        post_response,
+                    )
+                }
+                Ok(None) => {
+                    error!("Post created but not found: {}", post.id);
+                    error_response_generic(
+                        "Creation Failed".to_string(),
+                        "Post was created but could not be retrieved".to_string(),
+                    )
+                }
+                Err(e) => {
+                    error!(
+                        "Handler: Failed to retrieve created post with author info: {}",
+                        e
+                    );
+                    sql_error_generic(e, "Unable to retrieve post details")
+                }
+            }
+        }
         Err(e) => {
             error!("Handler: Failed to create post: {}", e);
             sql_error_generic(e, "Unable to create post")
@@ -74,12 +323,100 @@ pub async fn create_post(
     }
 }
 
+/// Create many posts in one request
+#[utoipa::path(
+    post,
+    path = "/posts/bulk",
+    params(
+        ("X-Org-Id" = Option<String>, Header, description = "Create the posts inside this organization (requires EDITOR or OWNER)")
+    ),
+    request_body = BulkCreatePostsRequest,
+    responses(
+        (status = 200, description = "What was created; items that failed validation or whose slug is taken are listed with the reason", body = inline(crate::helpers::response::ApiSuccessResponse<BulkCreateReport>)),
+        (status = 400, description = "No posts, or more than 100", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not an editor of the organization", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 503, description = "Post creation is switched off", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn create_posts_bulk(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    State(flags): State<Arc<FeatureFlags>>,
+    Extension(user_id): Extension<Uuid>,
+    org: Option<Extension<OrgContext>>,
+    Json(payload): Json<BulkCreatePostsRequest>,
+) -> UnifiedResponse<BulkCreateReport> {
+    info!(
+        "Handler: Creating {} posts in bulk for user_id: {}",
+        payload.posts.len(),
+        user_id
+    );
+
+    if let Err(err) = flags.require(FeatureFlag::PostCreation) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    if payload.posts.is_empty() || payload.posts.len() > MAX_BULK_POSTS {
+        return error_response_generic(
+            "Creation Failed".to_string(),
+            format!(
+                "Between 1 and {} posts can be created at once",
+                MAX_BULK_POSTS
+            ),
+        );
+    }
+
+    if let Err(err) = policy::can_write_in_org(org.as_deref()) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let repo = PostRepository::new((*pool).clone()).with_cache(cache);
+    let org_id = org.map(|Extension(org)| org.org_id);
+
+    match bulk_posts::create_posts(&repo, &config, user_id, org_id, payload.posts, clock.now())
+        .await
+    {
+        Ok((report, created)) => {
+            for post in &created {
+                record_media(&pool, post).await;
+                record_mentions(&pool, clock.as_ref(), post).await;
+                notify_publish_hooks(
+                    &pool,
+                    &config,
+                    clock.as_ref(),
+                    PublishEvent::Published,
+                    post,
+                )
+                .await;
+                edge.purge_post(&post.public_id);
+            }
+            success_response("Posts Created".to_string(), report)
+        }
+        Err(e) => {
+            error!("Handler: Bulk post creation failed: {}", e);
+            sql_error_generic(e, "Unable to create posts")
+        }
+    }
+}
+
 /// Delete a post by ID
 #[utoipa::path(
     delete,
     path = "/posts/{id}",
     params(
-        ("id" = Uuid, Path, description = "Post ID to delete")
+        ("id" = String, Path, description = "Public ID of the post to delete"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
     ),
     responses(
         (status = 200, description = "Post deleted successfully", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
@@ -96,22 +433,38 @@ pub async fn create_post(
 )]
 pub async fn delete_post(
     State(pool): State<Arc<PgPool>>,
-    Extension(user_id): Extension<Uuid>,
-    Path(id): Path<Uuid>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    Authorize(actor): Authorize,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath(id): ValidatedPath<String>,
 ) -> UnifiedResponse<Value> {
     info!(
         "Handler: Deleting post with id: {} for user_id: {}",
-        id, user_id
+        id, actor.user_id
     );
 
-    let repo = PostRepository::new((*pool).clone());
+    let repo = PostRepository::new((*pool).clone()).with_cache(cache);
+
+    let post = match repo.find_by_public_id(&id).await {
+        Ok(Some(post)) if in_org_scope(&post, org.as_deref()) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to delete post");
+        }
+    };
+
+    if let Err(err) = policy::can_delete_post(&actor, &post, org.as_deref()) {
+        return UnifiedResponse::Error(*err);
+    }
 
-    match repo.delete_post(id, user_id).await {
-        Ok(true) => success_response("Post Deleted".to_string(), Value::Null),
-        Ok(false) => {
-            error!("Post not found or unauthorized deletion attempt: {}", id);
-            not_found_response_generic("Post not found or unauthorized access".to_string())
+    match repo.remove_post(post.id).await {
+        Ok(true) => {
+            edge.purge_post(&id);
+            success_response("Post Deleted".to_string(), Value::Null)
         }
+        Ok(false) => not_found_response_generic("Post not found".to_string()),
         Err(e) => {
             error!("Handler: Failed to delete post: {}", e);
             sql_error_generic(e, "Unable to delete post")
@@ -119,17 +472,20 @@ pub async fn delete_post(
     }
 }
 
-/// Update a post by ID
+/// Archive a post
+///
+/// The post leaves public listings, feeds and the sitemap but stays readable
+/// by direct link, with `archived_at` set. Archiving an archived post keeps
+/// its original date.
 #[utoipa::path(
-    put,
-    path = "/posts/{id}",
+    post,
+    path = "/posts/{id}/archive",
     params(
-        ("id" = Uuid, Path, description = "Post ID to update")
+        ("id" = String, Path, description = "Public ID of the post to archive"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
     ),
-    request_body = UpdatePostRequest,
     responses(
-        (status = 200, description = "Post updated successfully", body = inline(crate::helpers::response::ApiSuccessResponse<PostResponse>)),
-        (status = 400, description = "Validation error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 200, description = "Post archived", body = inline(crate::helpers::response::ApiSuccessResponse<PostResponse>)),
         (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 403, description = "Forbidden - Not the post author", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 404, description = "Post not found", body = inline(crate::helpers::response::ApiErrorResponse)),
@@ -141,78 +497,138 @@ pub async fn delete_post(
     ),
     tag = "Posts"
 )]
-pub async fn update_post(
+pub async fn archive_post(
     State(pool): State<Arc<PgPool>>,
-    Extension(user_id): Extension<Uuid>,
-    Path(id): Path<Uuid>,
-    Json(payload): Json<UpdatePostRequest>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    Authorize(actor): Authorize,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath(id): ValidatedPath<String>,
 ) -> UnifiedResponse<PostResponse> {
     info!(
-        "Handler: Updating post with id: {} for user_id: {}",
-        id, user_id
+        "Handler: Archiving post with id: {} for user_id: {}",
+        id, actor.user_id
     );
 
-    let repo = PostRepository::new((*pool).clone());
-
-    match repo.update_post(id, user_id, payload).await {
-        Ok(Some(post)) => match repo.find_by_id_with_author(post.id).await {
-            Ok(Some(post_response)) => success_response("Post Updated".to_string(), post_response),
-            Ok(None) => error_response_generic(
-                "Update Failed".to_string(),
-                "Post was updated but could not be retrieved".to_string(),
-            ),
-            Err(e) => {
-                error!(
-                    "Handler: Failed to retrieve updated post with author info: {}",
-                    e
-                );
-                sql_error_generic(e, "Unable to retrieve updated post details")
-            }
-        },
-        Ok(None) => {
-            error!("Post not found or unauthorized update attempt: {}", id);
-            not_found_response_generic(
-                "Post not found or you are not authorized to update it".to_string(),
-            )
-        }
-        Err(e) => {
-            error!("Handler: Failed to update post: {}", e);
-            sql_error_generic(e, "Unable to update post")
-        }
-    }
+    set_archived(
+        &pool,
+        cache,
+        &edge,
+        &actor,
+        org.as_deref(),
+        &id,
+        Some(clock.now()),
+    )
+    .await
 }
 
-/// Get all posts
+/// Put an archived post back in listings and feeds
 #[utoipa::path(
-    get,
-    path = "/posts",
+    post,
+    path = "/posts/{id}/unarchive",
+    params(
+        ("id" = String, Path, description = "Public ID of the post to unarchive"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
     responses(
-        (status = 200, description = "All posts retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<PostResponse>>)),
+        (status = 200, description = "Post unarchived", body = inline(crate::helpers::response::ApiSuccessResponse<PostResponse>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not the post author", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post not found", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
     ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
     tag = "Posts"
 )]
-pub async fn get_all_posts(State(pool): State<Arc<PgPool>>) -> UnifiedResponse<Vec<PostResponse>> {
-    info!("Handler: Retrieving all posts");
+pub async fn unarchive_post(
+    State(pool): State<Arc<PgPool>>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    Authorize(actor): Authorize,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath(id): ValidatedPath<String>,
+) -> UnifiedResponse<PostResponse> {
+    info!(
+        "Handler: Unarchiving post with id: {} for user_id: {}",
+        id, actor.user_id
+    );
 
-    let repo = PostRepository::new((*pool).clone());
+    set_archived(&pool, cache, &edge, &actor, org.as_deref(), &id, None).await
+}
 
-    match repo.get_all_posts().await {
-        Ok(posts) => success_response("Posts Retrieved".to_string(), posts),
+/// Archives the post as of `archived_at`, keeping an earlier date, or
+/// unarchives it with `None`
+async fn set_archived(
+    pool: &PgPool,
+    cache: Arc<Cache>,
+    edge: &EdgeCache,
+    actor: &Actor,
+    org: Option<&OrgContext>,
+    id: &str,
+    archived_at: Option<DateTime<Utc>>,
+) -> UnifiedResponse<PostResponse> {
+    let repo = PostRepository::new(pool.clone()).with_cache(cache);
+
+    let post = match repo.find_by_public_id(id).await {
+        Ok(Some(post)) if in_org_scope(&post, org) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
         Err(e) => {
-            error!("Handler: Failed to retrieve posts: {}", e);
-            sql_error_generic(e, "Unable to retrieve posts")
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to update post");
+        }
+    };
+
+    if let Err(err) = policy::can_archive_post(actor, &post, org) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let archived_at = archived_at.map(|now| post.archived_at.unwrap_or(now));
+    let updated = match repo.set_archived(post.id, archived_at).await {
+        Ok(Some(updated)) => updated,
+        Ok(None) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to archive post: {}", e);
+            return sql_error_generic(e, "Unable to update post");
+        }
+    };
+    edge.purge_post(&updated.public_id);
+
+    match repo.find_by_id_with_author(updated.id).await {
+        Ok(Some(post_response)) => {
+            let title = if archived_at.is_some() {
+                "Post Archived"
+            } else {
+                "Post Unarchived"
+            };
+            success_response(title.to_string(), post_response)
+        }
+        Ok(None) => not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to retrieve archived post: {}", e);
+            sql_error_generic(e, "Unable to retrieve post details")
         }
     }
 }
 
-/// Get current user's posts
+/// Update a post by ID
 #[utoipa::path(
-    get,
-    path = "/posts/my",
+    put,
+    path = "/posts/{id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the post to update"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    request_body = UpdatePostRequest,
     responses(
-        (status = 200, description = "User posts retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<crate::model::model::Post>>)),
+        (status = 200, description = "Post updated successfully", body = inline(crate::helpers::response::ApiSuccessResponse<PostResponse>)),
+        (status = 400, description = "Validation error, or an image without required alt text", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Neither the author nor an invited editor", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post not found", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
     ),
     security(
@@ -221,54 +637,1495 @@ pub async fn get_all_posts(State(pool): State<Arc<PgPool>>) -> UnifiedResponse<V
     ),
     tag = "Posts"
 )]
-pub async fn get_user_posts(
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn update_post(
     State(pool): State<Arc<PgPool>>,
-    Extension(user_id): Extension<Uuid>,
-) -> UnifiedResponse<Vec<model::Post>> {
-    info!("Handler: Retrieving posts for user_id: {}", user_id);
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    actor: Authorize,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath(id): ValidatedPath<String>,
+    Json(payload): Json<UpdatePostRequest>,
+) -> UnifiedResponse<PostResponse> {
+    patch_post(
+        State(pool),
+        State(config),
+        State(clock),
+        State(cache),
+        State(edge),
+        actor,
+        org,
+        ValidatedPath(id),
+        MergePatch(payload.into()),
+    )
+    .await
+}
 
-    let repo = PostRepository::new((*pool).clone());
+/// Change some fields of a post with a JSON Merge Patch
+#[utoipa::path(
+    patch,
+    path = "/posts/{id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the post to update"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    request_body(content = PatchPostRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = 200, description = "Post updated successfully", body = inline(crate::helpers::response::ApiSuccessResponse<PostResponse>)),
+        (status = 400, description = "Validation error, a malformed patch, a null title or content, or an image without required alt text", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Neither the author nor an invited editor", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 415, description = "The patch isn't sent as JSON", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn patch_post(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    Authorize(actor): Authorize,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath(id): ValidatedPath<String>,
+    MergePatch(mut patch): MergePatch<PatchPostRequest>,
+) -> UnifiedResponse<PostResponse> {
+    info!(
+        "Handler: Updating post with id: {} for user_id: {}",
+        id, actor.user_id
+    );
 
-    match repo.find_by_author(user_id).await {
-        Ok(posts) => success_response("Your Posts Retrieved".to_string(), posts),
+    if patch.title.is_null() || patch.content.is_null() || patch.visibility.is_null() {
+        return error_response_generic(
+            "Update Failed".to_string(),
+            "Title, content and visibility cannot be null".to_string(),
+        );
+    }
+
+    if let Some(license) = patch.license.as_value_mut() {
+        match normalize_license(license) {
+            Ok(normalized) => *license = normalized,
+            Err(message) => {
+                return error_response_generic("Update Failed".to_string(), message);
+            }
+        }
+    }
+
+    if let Err(message) = validate_post_fields(
+        patch.title.as_value().map(String::as_str),
+        patch.content.as_value().map(String::as_str),
+        &config.limits,
+    ) {
+        return error_response_generic("Update Failed".to_string(), message);
+    }
+
+    if let Some(content) = patch.content.as_value()
+        && let Err(message) = check_alt_text(&config, content)
+    {
+        return error_response_generic("Update Failed".to_string(), message);
+    }
+
+    let repo = PostRepository::new((*pool).clone()).with_cache(cache);
+
+    let post = match repo.find_by_public_id(&id).await {
+        Ok(Some(post)) if in_org_scope(&post, org.as_deref()) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
         Err(e) => {
-            error!("Handler: Failed to retrieve user posts: {}", e);
-            sql_error_generic(e, "Unable to retrieve your posts")
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to update post");
+        }
+    };
+
+    let collaborator = if post.author_id == actor.user_id {
+        None
+    } else {
+        match CollaboratorRepository::new((*pool).clone())
+            .role(post.id, actor.user_id)
+            .await
+        {
+            Ok(role) => role,
+            Err(e) => {
+                error!("Handler: Failed to look up collaborator: {}", e);
+                return sql_error_generic(e, "Unable to update post");
+            }
+        }
+    };
+
+    if let Err(err) = policy::can_edit_post(&actor, &post, org.as_deref(), collaborator) {
+        return UnifiedResponse::Error(*err);
+    }
+    let visibility_changes = patch
+        .visibility
+        .as_value()
+        .is_some_and(|visibility| *visibility != post.visibility);
+    if visibility_changes && let Err(err) = policy::can_change_visibility(&actor, &post) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    match repo.update_post(post.id, patch, clock.now()).await {
+        Ok(Some(post)) => {
+            record_mentions(&pool, clock.as_ref(), &post).await;
+            match repo.find_by_id_with_author(post.id).await {
+                Ok(Some(post_response)) => {
+                    record_media(&pool, &post).await;
+                    notify_publish_hooks(
+                        &pool,
+                        &config,
+                        clock.as_ref(),
+                        PublishEvent::Updated,
+                        &post,
+                    )
+                    .await;
+                    edge.purge_post(&post.public_id);
+                    success_response("Post Updated".to_string(), post_response)
+                }
+                Ok(None) => error_response_generic(
+                    "Update Failed".to_string(),
+                    "Post was updated but could not be retrieved".to_string(),
+                ),
+                Err(e) => {
+                    error!(
+                        "Handler: Failed to retrieve updated post with author info: {}",
+                        e
+                    );
+                    sql_error_generic(e, "Unable to retrieve updated post details")
+                }
+            }
+        }
+        Ok(None) => not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to update post: {}", e);
+            sql_error_generic(e, "Unable to update post")
         }
     }
 }
 
-/// Get a specific post by ID
+/// Invite a co-author to a post (author only)
+///
+/// Editors can change the post but not delete or archive it; viewers are
+/// listed as collaborators only. Inviting someone again changes their role.
 #[utoipa::path(
-    get,
-    path = "/posts/{id}",
+    post,
+    path = "/posts/{id}/collaborators",
     params(
-        ("id" = Uuid, Path, description = "Post ID to retrieve")
+        ("id" = String, Path, description = "Public ID of the post"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
     ),
+    request_body = AddCollaboratorRequest,
     responses(
-        (status = 200, description = "Post retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<PostResponse>)),
-        (status = 404, description = "Post not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 200, description = "Collaborator invited, or their role updated if already invited", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<PostCollaborator>>)),
+        (status = 400, description = "The author can't be invited to their own post", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not the post author", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post or user not found", body = inline(crate::helpers::response::ApiErrorResponse)),
         (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
     ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
     tag = "Posts"
 )]
-pub async fn get_post(
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn add_collaborator(
     State(pool): State<Arc<PgPool>>,
-    Path(id): Path<Uuid>,
-) -> UnifiedResponse<PostResponse> {
-    info!("Handler: Retrieving post with id: {}", id);
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    Authorize(actor): Authorize,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath(id): ValidatedPath<String>,
+    Json(payload): Json<AddCollaboratorRequest>,
+) -> UnifiedResponse<Vec<PostCollaborator>> {
+    info!(
+        "Handler: Inviting {} to post {} as {:?}",
+        payload.user_id, id, payload.role
+    );
 
-    let repo = PostRepository::new((*pool).clone());
+    let repo = PostRepository::new((*pool).clone()).with_cache(cache);
 
-    match repo.find_by_id_with_author(id).await {
-        Ok(Some(post)) => success_response("Post Retrieved".to_string(), post),
-        Ok(None) => {
-            error!("Post not found: {}", id);
-            not_found_response_generic("Post not found".to_string())
+    let post = match repo.find_by_public_id(&id).await {
+        Ok(Some(post)) if in_org_scope(&post, org.as_deref()) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to update collaborators");
         }
+    };
+
+    if let Err(err) = policy::can_manage_collaborators(&actor, &post, org.as_deref()) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let user = match UserRepository::new((*pool).clone())
+        .find_by_public_id(&payload.user_id)
+        .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
         Err(e) => {
-            error!("Handler: Failed to retrieve post: {}", e);
-            sql_error_generic(e, "Unable to retrieve post")
+            error!("Handler: Failed to look up user: {}", e);
+            return sql_error_generic(e, "Unable to find user");
+        }
+    };
+
+    if user.id == post.author_id {
+        return error_response_generic(
+            "Invalid Operation".to_string(),
+            "The author can't be a collaborator on their own post".to_string(),
+        );
+    }
+
+    let collaborators = CollaboratorRepository::new((*pool).clone());
+    let invited = match collaborators
+        .upsert(post.id, user.id, payload.role, actor.user_id, clock.now())
+        .await
+    {
+        Ok(invited) => invited,
+        Err(e) => {
+            error!("Handler: Failed to invite collaborator: {}", e);
+            return sql_error_generic(e, "Unable to update collaborators");
         }
+    };
+    repo.invalidate_post(&post).await;
+    edge.purge_post(&post.public_id);
+
+    if invited {
+        notify(
+            &pool,
+            user.id,
+            NotificationEvent::CollaboratorInvited {
+                post_id: &post.public_id,
+                title: &post.title,
+                role: payload.role,
+            },
+            clock.now(),
+        )
+        .await;
     }
+
+    match collaborators.list(post.id).await {
+        Ok(list) => success_response("Collaborators Updated".to_string(), list),
+        Err(e) => {
+            error!("Handler: Failed to list collaborators: {}", e);
+            sql_error_generic(e, "Unable to retrieve collaborators")
+        }
+    }
+}
+
+/// Remove a co-author (author only), or step down from a post (any collaborator)
+#[utoipa::path(
+    delete,
+    path = "/posts/{id}/collaborators/{user_id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the post"),
+        ("user_id" = String, Path, description = "Public ID of the collaborator"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    responses(
+        (status = 200, description = "Collaborator removed", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not the post author", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post or collaborator not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+pub async fn remove_collaborator(
+    State(pool): State<Arc<PgPool>>,
+    State(cache): State<Arc<Cache>>,
+    State(edge): State<Arc<EdgeCache>>,
+    Authorize(actor): Authorize,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath((id, user_id)): ValidatedPath<(String, String)>,
+) -> UnifiedResponse<Value> {
+    info!(
+        "Handler: Removing collaborator {} from post {}",
+        user_id, id
+    );
+
+    let repo = PostRepository::new((*pool).clone()).with_cache(cache);
+
+    let post = match repo.find_by_public_id(&id).await {
+        Ok(Some(post)) if in_org_scope(&post, org.as_deref()) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to update collaborators");
+        }
+    };
+
+    let user = match UserRepository::new((*pool).clone())
+        .find_by_public_id(&user_id)
+        .await
+    {
+        Ok(Some(user)) => user,
+        Ok(None) => return not_found_response_generic("User not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up user: {}", e);
+            return sql_error_generic(e, "Unable to find user");
+        }
+    };
+
+    // Collaborators may always step down; only the author removes others
+    let allowed = if user.id == actor.user_id {
+        Ok(())
+    } else {
+        policy::can_manage_collaborators(&actor, &post, org.as_deref())
+    };
+    if let Err(err) = allowed {
+        return UnifiedResponse::Error(*err);
+    }
+
+    match CollaboratorRepository::new((*pool).clone())
+        .remove(post.id, user.id)
+        .await
+    {
+        Ok(true) => {
+            repo.invalidate_post(&post).await;
+            edge.purge_post(&post.public_id);
+            success_response("Collaborator Removed".to_string(), Value::Null)
+        }
+        Ok(false) => not_found_response_generic("Collaborator not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to remove collaborator: {}", e);
+            sql_error_generic(e, "Unable to update collaborators")
+        }
+    }
+}
+
+/// Longest a share link can be made to last, a year
+const MAX_SHARE_LINK_SECS: u64 = 365 * 24 * 60 * 60;
+
+/// Create a share link for one of the caller's posts
+///
+/// Anyone holding the link can read the post with `GET /posts/{id}?share=TOKEN`,
+/// even when it is private, until it expires or its reads run out.
+#[utoipa::path(
+    post,
+    path = "/posts/{id}/share-links",
+    params(
+        ("id" = String, Path, description = "Public ID of the post to share"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    request_body = CreateShareLinkRequest,
+    responses(
+        (status = 200, description = "Share link created; the token isn't shown again", body = inline(crate::helpers::response::ApiSuccessResponse<CreatedShareLink>)),
+        (status = 400, description = "`expires_in_secs` or `max_uses` out of range", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not the post author", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn create_share_link(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Authorize(actor): Authorize,
+    org: Option<Extension<OrgContext>>,
+    version: ApiVersion,
+    ValidatedPath(id): ValidatedPath<String>,
+    Json(payload): Json<CreateShareLinkRequest>,
+) -> UnifiedResponse<CreatedShareLink> {
+    info!(
+        "Handler: Creating share link for post {} for user_id: {}",
+        id, actor.user_id
+    );
+
+    let lifetime = payload
+        .expires_in_secs
+        .unwrap_or(config.share_link_ttl.as_secs());
+    if !(1..=MAX_SHARE_LINK_SECS).contains(&lifetime) {
+        return error_response_generic(
+            "Invalid Share Link".to_string(),
+            format!(
+                "expires_in_secs must be between 1 and {}",
+                MAX_SHARE_LINK_SECS
+            ),
+        );
+    }
+    if payload.max_uses.is_some_and(|max_uses| max_uses < 1) {
+        return error_response_generic(
+            "Invalid Share Link".to_string(),
+            "max_uses must be at least 1".to_string(),
+        );
+    }
+
+    let post = match PostRepository::new((*pool).clone())
+        .find_by_public_id(&id)
+        .await
+    {
+        Ok(Some(post)) if in_org_scope(&post, org.as_deref()) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to share post");
+        }
+    };
+
+    if let Err(err) = policy::can_share_post(&actor, &post, org.as_deref()) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let now = clock.now();
+    let token = new_link_token();
+    match ShareLinkRepository::new((*pool).clone())
+        .insert(
+            post.id,
+            &hash_link_token(&token),
+            actor.user_id,
+            now,
+            now + chrono::Duration::seconds(lifetime as i64),
+            payload.max_uses,
+        )
+        .await
+    {
+        Ok(link) => {
+            let url = format!(
+                "{}{}?share={}",
+                config.federation.public_url,
+                config.versioned_path(version, &format!("/posts/{}", post.public_id)),
+                token
+            );
+            success_response(
+                "Share Link Created".to_string(),
+                CreatedShareLink { link, token, url },
+            )
+        }
+        Err(e) => {
+            error!("Handler: Failed to create share link: {}", e);
+            sql_error_generic(e, "Unable to share post")
+        }
+    }
+}
+
+/// List the share links of one of the caller's posts
+///
+/// Expired and used-up links are listed too, so authors can see how they were
+/// used. Tokens aren't stored, so they can't be shown again.
+#[utoipa::path(
+    get,
+    path = "/posts/{id}/share-links",
+    params(
+        ("id" = String, Path, description = "Public ID of the post"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    responses(
+        (status = 200, description = "The post's share links, newest first", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<ShareLink>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not the post author", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+pub async fn list_share_links(
+    State(pool): State<Arc<PgPool>>,
+    Authorize(actor): Authorize,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath(id): ValidatedPath<String>,
+) -> UnifiedResponse<Vec<ShareLink>> {
+    info!("Handler: Listing share links of post {}", id);
+
+    let post = match PostRepository::new((*pool).clone())
+        .find_by_public_id(&id)
+        .await
+    {
+        Ok(Some(post)) if in_org_scope(&post, org.as_deref()) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to retrieve share links");
+        }
+    };
+
+    if let Err(err) = policy::can_share_post(&actor, &post, org.as_deref()) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    match ShareLinkRepository::new((*pool).clone())
+        .list(post.id)
+        .await
+    {
+        Ok(links) => success_response("Share Links Retrieved".to_string(), links),
+        Err(e) => {
+            error!("Handler: Failed to list share links: {}", e);
+            sql_error_generic(e, "Unable to retrieve share links")
+        }
+    }
+}
+
+/// Revoke a share link; it stops working at once
+#[utoipa::path(
+    delete,
+    path = "/posts/{id}/share-links/{link_id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the post"),
+        ("link_id" = Uuid, Path, description = "ID of the share link"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    responses(
+        (status = 200, description = "Share link revoked", body = inline(crate::helpers::response::ApiSuccessResponse<String>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not the post author", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post or share link not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+pub async fn revoke_share_link(
+    State(pool): State<Arc<PgPool>>,
+    Authorize(actor): Authorize,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath((id, link_id)): ValidatedPath<(String, Uuid)>,
+) -> UnifiedResponse<Value> {
+    info!("Handler: Revoking share link {} of post {}", link_id, id);
+
+    let post = match PostRepository::new((*pool).clone())
+        .find_by_public_id(&id)
+        .await
+    {
+        Ok(Some(post)) if in_org_scope(&post, org.as_deref()) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to revoke share link");
+        }
+    };
+
+    if let Err(err) = policy::can_share_post(&actor, &post, org.as_deref()) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    match ShareLinkRepository::new((*pool).clone())
+        .revoke(post.id, link_id)
+        .await
+    {
+        Ok(true) => success_response("Share Link Revoked".to_string(), Value::Null),
+        Ok(false) => not_found_response_generic("Share link not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to revoke share link: {}", e);
+            sql_error_generic(e, "Unable to revoke share link")
+        }
+    }
+}
+
+/// Get all posts
+#[utoipa::path(
+    get,
+    path = "/posts",
+    params(
+        ("format" = Option<ContentFormat>, Query, description = "`raw` omits the rendered `content_html`"),
+        ("fields" = Option<String>, Query, description = "Comma-separated fields to return for each post, e.g. `id,title,excerpt,author.name`"),
+        ("X-Org-Id" = Option<String>, Header, description = "List the organization's posts instead of posts outside any organization")
+    ),
+    responses(
+        (status = 200, description = "All posts retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<PostResponse>>)),
+        (status = 400, description = "Malformed field name in `fields`", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Posts"
+)]
+pub async fn get_all_posts(
+    State(db): State<DbPools>,
+    State(cache): State<Arc<Cache>>,
+    org: Option<Extension<OrgContext>>,
+    Query(content): Query<ContentFormatQuery>,
+    Query(query): Query<FieldsQuery>,
+) -> UnifiedResponse<Sparse<Vec<PostResponse>>> {
+    info!("Handler: Retrieving all posts");
+
+    let fields = match FieldSelection::parse(query.fields.as_deref()) {
+        Ok(fields) => fields,
+        Err(message) => return error_response_generic("Invalid Fields".to_string(), message),
+    };
+
+    let repo = PostRepository::from_pools(&db).with_cache(cache);
+    let org_id = org.map(|Extension(org)| org.org_id);
+
+    match repo.get_all_posts(org_id).await {
+        Ok(posts) => success_response(
+            "Posts Retrieved".to_string(),
+            Sparse::new(with_format(posts, content.format), fields),
+        ),
+        Err(e) => {
+            error!("Handler: Failed to retrieve posts: {}", e);
+            sql_error_generic(e, "Unable to retrieve posts")
+        }
+    }
+}
+
+/// Get trending posts
+#[utoipa::path(
+    get,
+    path = "/posts/trending",
+    params(
+        ("limit" = Option<i64>, Query, description = "Number of posts to return (1-50, default 10)"),
+        ("format" = Option<ContentFormat>, Query, description = "`raw` omits the rendered `content_html`"),
+        ("X-Org-Id" = Option<String>, Header, description = "Rank the organization's posts instead of posts outside any organization")
+    ),
+    responses(
+        (status = 200, description = "Posts viewed in the last 7 days, ranked by a recency-weighted view score", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<PostResponse>>)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Posts"
+)]
+pub async fn get_trending_posts(
+    State(db): State<DbPools>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    org: Option<Extension<OrgContext>>,
+    Query(query): Query<TrendingQuery>,
+    Query(content): Query<ContentFormatQuery>,
+) -> UnifiedResponse<Vec<PostResponse>> {
+    info!("Handler: Retrieving trending posts");
+
+    let repo = PostRepository::from_pools(&db).with_cache(cache);
+    let org_id = org.map(|Extension(org)| org.org_id);
+
+    match repo
+        .find_trending(org_id, trending_limit(query.limit), clock.now())
+        .await
+    {
+        Ok(posts) => success_response(
+            "Trending Posts Retrieved".to_string(),
+            with_format(posts, content.format),
+        ),
+        Err(e) => {
+            error!("Handler: Failed to retrieve trending posts: {}", e);
+            sql_error_generic(e, "Unable to retrieve trending posts")
+        }
+    }
+}
+
+/// Get current user's posts
+#[utoipa::path(
+    get,
+    path = "/posts/my",
+    params(
+        ("status" = Option<PostStatus>, Query, description = "`published` for posts in listings, `archived` for archived ones; every post when omitted")
+    ),
+    responses(
+        (status = 200, description = "User posts retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<crate::model::model::Post>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+pub async fn get_user_posts(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+    Query(query): Query<MyPostsQuery>,
+) -> UnifiedResponse<Vec<model::Post>> {
+    info!("Handler: Retrieving posts for user_id: {}", user_id);
+
+    let repo = PostRepository::new((*pool).clone());
+
+    match repo.find_by_author(user_id).await {
+        Ok(mut posts) => {
+            if let Some(status) = query.status {
+                posts.retain(|post| (status == PostStatus::Archived) == post.archived_at.is_some());
+            }
+            success_response("Your Posts Retrieved".to_string(), posts)
+        }
+        Err(e) => {
+            error!("Handler: Failed to retrieve user posts: {}", e);
+            sql_error_generic(e, "Unable to retrieve your posts")
+        }
+    }
+}
+
+/// Download the current user's posts, to back them up or move them elsewhere
+#[utoipa::path(
+    get,
+    path = "/posts/my/export",
+    params(
+        ("format" = Option<ArchiveFormat>, Query, description = "`json` (default) for one JSON document, `markdown` for a ZIP of Markdown files with frontmatter")
+    ),
+    responses(
+        (status = 200, description = "The posts as an attachment", content(
+            (PostArchive = "application/json"),
+            (String = "application/zip")
+        )),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not allowed while impersonating a user", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+pub async fn export_my_posts(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    Query(query): Query<ArchiveQuery>,
+) -> Response {
+    info!(
+        "Handler: Exporting posts of user_id {} as {:?}",
+        user_id, query.format
+    );
+
+    let repo = PostRepository::new((*pool).clone());
+    let posts: Vec<PortablePost> = match repo.find_by_author(user_id).await {
+        Ok(posts) => posts.into_iter().map(PortablePost::from).collect(),
+        Err(e) => {
+            error!("Handler: Failed to load posts for export: {}", e);
+            return sql_error_generic::<PostArchive>(e, "Unable to export your posts")
+                .into_response();
+        }
+    };
+
+    let body = match query.format {
+        ArchiveFormat::Json => to_json(posts, clock.now()),
+        ArchiveFormat::Markdown => to_zip(&posts),
+    };
+    match body {
+        Ok(body) => (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    query.format.content_type().to_string(),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", query.format.file_name()),
+                ),
+            ],
+            body,
+        )
+            .into_response(),
+        Err(e) => {
+            error!("Handler: Failed to build post export: {}", e);
+            ErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Export Failed".to_string(),
+                "Unable to build the export".to_string(),
+            )
+            .into_response()
+        }
+    }
+}
+
+/// Import posts from an export of this or another service
+#[utoipa::path(
+    post,
+    path = "/posts/import",
+    request_body(content(
+        (PostArchive = "application/json"),
+        (String = "application/zip")
+    ), description = "The JSON document or Markdown ZIP produced by `GET /posts/my/export`; a bare JSON array of posts works too"),
+    responses(
+        (status = 200, description = "What was imported; items that failed validation are listed with the reason", body = inline(crate::helpers::response::ApiSuccessResponse<PostImportReport>)),
+        (status = 400, description = "Body is not a valid JSON document or ZIP", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 503, description = "Post creation is switched off", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn import_posts(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    State(flags): State<Arc<FeatureFlags>>,
+    Extension(user_id): Extension<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> UnifiedResponse<PostImportReport> {
+    info!(
+        "Handler: Importing posts ({} bytes) for user_id: {}",
+        body.len(),
+        user_id
+    );
+
+    if let Err(err) = flags.require(FeatureFlag::PostCreation) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    let is_zip = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/zip"))
+        || body.starts_with(b"PK\x03\x04");
+    let items = if is_zip {
+        parse_zip(&body)
+    } else {
+        parse_json(&body)
+    };
+    let items = match items {
+        Ok(items) => items,
+        Err(e) => {
+            error!("Handler: Invalid post import: {:#}", e);
+            return error_response_generic("Invalid Import".to_string(), format!("{:#}", e));
+        }
+    };
+
+    match post_archive::import_posts(&pool, cache, &config, user_id, items, clock.now()).await {
+        Ok(report) => success_response("Posts Imported".to_string(), report),
+        Err(e) => {
+            error!("Handler: Post import failed: {}", e);
+            sql_error_generic(e, "Unable to import posts")
+        }
+    }
+}
+
+/// List images without alt text in the current user's posts
+#[utoipa::path(
+    get,
+    path = "/posts/my/media/missing-alt",
+    responses(
+        (status = 200, description = "Images without alt text, newest post first", body = inline(crate::helpers::response::ApiSuccessResponse<Vec<MediaMissingAlt>>)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+pub async fn get_media_missing_alt(
+    State(pool): State<Arc<PgPool>>,
+    Extension(user_id): Extension<Uuid>,
+) -> UnifiedResponse<Vec<MediaMissingAlt>> {
+    info!(
+        "Handler: Listing images without alt text for user_id: {}",
+        user_id
+    );
+
+    let media = MediaRepository::new((*pool).clone());
+
+    match media.find_missing_alt(user_id).await {
+        Ok(missing) => success_response("Images Without Alt Text".to_string(), missing),
+        Err(e) => {
+            error!("Handler: Failed to list images without alt text: {}", e);
+            sql_error_generic(e, "Unable to retrieve images without alt text")
+        }
+    }
+}
+
+/// Report a post to the moderators
+#[utoipa::path(
+    post,
+    path = "/posts/{id}/report",
+    params(
+        ("id" = String, Path, description = "Public ID of the post to report"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    request_body = CreateReportRequest,
+    responses(
+        (status = 200, description = "Report filed", body = inline(crate::helpers::response::ApiSuccessResponse<Report>)),
+        (status = 400, description = "Details too long, own post, or an open report by this user already exists", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+pub async fn report_post(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user_id): Extension<Uuid>,
+    org: Option<Extension<OrgContext>>,
+    ValidatedPath(id): ValidatedPath<String>,
+    Json(mut payload): Json<CreateReportRequest>,
+) -> UnifiedResponse<Report> {
+    info!(
+        "Handler: Reporting post {} as {:?} for user_id: {}",
+        id, payload.reason, user_id
+    );
+
+    payload.details = payload
+        .details
+        .map(|details| details.trim().to_string())
+        .filter(|details| !details.is_empty());
+    if let Some(details) = &payload.details
+        && let Err(message) =
+            check_length("Details", details, config.limits.max_report_details_length)
+    {
+        return error_response_generic("Validation Error".to_string(), message);
+    }
+
+    let repo = PostRepository::new((*pool).clone());
+
+    let post = match repo.find_by_public_id(&id).await {
+        Ok(Some(post)) if in_org_scope(&post, org.as_deref()) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to report post");
+        }
+    };
+
+    if post.author_id == user_id {
+        return error_response_generic(
+            "Invalid Report".to_string(),
+            "You cannot report your own post".to_string(),
+        );
+    }
+
+    let reports = ReportRepository::new((*pool).clone());
+
+    match reports
+        .report_post(
+            post.id,
+            &post.public_id,
+            post.author_id,
+            user_id,
+            &payload,
+            clock.now(),
+        )
+        .await
+    {
+        Ok(Some(report)) => success_response("Report Filed".to_string(), report),
+        Ok(None) => error_response_generic(
+            "Already Reported".to_string(),
+            "You already have an open report on this post".to_string(),
+        ),
+        Err(e) => {
+            error!("Handler: Failed to file report: {}", e);
+            sql_error_generic(e, "Unable to report post")
+        }
+    }
+}
+
+/// Get a specific post by ID
+///
+/// Unlisted posts are served to anyone with the link; private posts only to
+/// their author and collaborators, who must send their token, or to anyone
+/// with a share link.
+#[utoipa::path(
+    get,
+    path = "/posts/{id}",
+    params(
+        ("id" = String, Path, description = "Public ID of the post to retrieve"),
+        ("format" = Option<ContentFormat>, Query, description = "`raw` omits the rendered `content_html`"),
+        ("include" = Option<String>, Query, description = "Comma-separated related data to return under `included`: `media` (the post's images) and `author_posts` (the author's latest other public posts)"),
+        ("share" = Option<String>, Query, description = "Token of a share link, for reading a private post"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    responses(
+        (status = 200, description = "Post retrieved successfully", body = inline(crate::helpers::response::ApiSuccessResponse<PostWithIncluded>)),
+        (status = 400, description = "`include` names something that can't be included", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post not found, or private and the caller is neither its author nor a collaborator and has no live share link", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Posts"
+)]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn get_post(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    org: Option<Extension<OrgContext>>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    OptionalUser(user_id): OptionalUser,
+    ValidatedPath(id): ValidatedPath<String>,
+    Query(content): Query<ContentFormatQuery>,
+    Query(query): Query<IncludeQuery>,
+    Query(share): Query<ShareQuery>,
+) -> UnifiedResponse<PostWithIncluded> {
+    info!("Handler: Retrieving post with id: {}", id);
+
+    let includes = match PostInclude::parse_list(query.include.as_deref()) {
+        Ok(includes) => includes,
+        Err(message) => return error_response_generic("Invalid Include".to_string(), message),
+    };
+
+    let repo = PostRepository::new((*pool).clone()).with_cache(cache);
+    let viewer = viewer_key(
+        user_id,
+        connect_info.map(|Extension(ConnectInfo(addr))| addr.ip()),
+    );
+
+    let found = match org.as_deref() {
+        // Public posts come from the cache, so their view count lags by up to the cache TTL
+        None => repo.find_public_with_author(&id).await,
+        Some(org) => find_org_post_with_author(&repo, &id, org).await,
+    };
+    let (post_id, post) = match found {
+        Ok(Some(found)) => found,
+        Ok(None) => {
+            error!("Post not found: {}", id);
+            return not_found_response_generic("Post not found".to_string());
+        }
+        Err(e) => {
+            error!("Handler: Failed to retrieve post: {}", e);
+            return sql_error_generic(e, "Unable to retrieve post");
+        }
+    };
+
+    let access = can_view(
+        &pool,
+        &repo,
+        post_id,
+        &post,
+        user_id,
+        share.share.as_deref(),
+        clock.now(),
+    );
+    match access.await {
+        Ok(true) => {}
+        Ok(false) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to check access to post: {}", e);
+            return sql_error_generic(e, "Unable to retrieve post");
+        }
+    }
+
+    record_view(&repo, post_id, viewer.as_deref(), clock.as_ref()).await;
+
+    let included = if includes.is_empty() {
+        None
+    } else {
+        match load_included(&pool, &repo, post_id, &post, &includes).await {
+            Ok(included) => Some(included),
+            Err(e) => {
+                error!("Handler: Failed to load included data: {}", e);
+                return sql_error_generic(e, "Unable to retrieve post");
+            }
+        }
+    };
+
+    success_response(
+        "Post Retrieved".to_string(),
+        PostWithIncluded {
+            post: post.with_format(content.format),
+            included,
+        },
+    )
+}
+
+/// Download a post as a Markdown, HTML or PDF file
+#[utoipa::path(
+    get,
+    path = "/posts/{id}/export",
+    params(
+        ("id" = String, Path, description = "Public ID of the post to export"),
+        ("format" = Option<ExportFormat>, Query, description = "`markdown` (default) for the source with frontmatter, `html` for a standalone page, `pdf` for a PDF document"),
+        ("share" = Option<String>, Query, description = "Token of a share link, for exporting a private post"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    responses(
+        (status = 200, description = "The post as an attachment", content(
+            (String = "text/markdown"),
+            (String = "text/html"),
+            (String = "application/pdf")
+        )),
+        (status = 400, description = "`format` is not one of the supported formats", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post not found, or private and the caller is neither its author nor a collaborator and has no live share link", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Posts"
+)]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn export_post(
+    State(pool): State<Arc<PgPool>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(cache): State<Arc<Cache>>,
+    org: Option<Extension<OrgContext>>,
+    OptionalUser(user_id): OptionalUser,
+    ValidatedPath(id): ValidatedPath<String>,
+    Query(query): Query<ExportQuery>,
+    Query(share): Query<ShareQuery>,
+) -> Response {
+    info!("Handler: Exporting post {} as {:?}", id, query.format);
+
+    let repo = PostRepository::new((*pool).clone()).with_cache(cache);
+    let found = match org.as_deref() {
+        None => repo.find_public_with_author(&id).await,
+        Some(org) => find_org_post_with_author(&repo, &id, org).await,
+    };
+    let post = match found {
+        Ok(Some((post_id, post))) => {
+            let access = can_view(
+                &pool,
+                &repo,
+                post_id,
+                &post,
+                user_id,
+                share.share.as_deref(),
+                clock.now(),
+            );
+            match access.await {
+                Ok(true) => post,
+                Ok(false) => {
+                    return not_found_response_generic::<PostResponse>(
+                        "Post not found".to_string(),
+                    )
+                    .into_response();
+                }
+                Err(e) => {
+                    error!("Handler: Failed to check access to post: {}", e);
+                    return sql_error_generic::<PostResponse>(e, "Unable to export post")
+                        .into_response();
+                }
+            }
+        }
+        Ok(None) => {
+            error!("Post not found: {}", id);
+            return not_found_response_generic::<PostResponse>("Post not found".to_string())
+                .into_response();
+        }
+        Err(e) => {
+            error!("Handler: Failed to load post for export: {}", e);
+            return sql_error_generic::<PostResponse>(e, "Unable to export post").into_response();
+        }
+    };
+
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                query.format.content_type().to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}\"",
+                    post_export::file_name(&post, query.format)
+                ),
+            ),
+        ],
+        post_export::export_post(&post, query.format),
+    )
+        .into_response()
+}
+
+/// Whether the signed-in `user_id`, if any, may read the post; only private
+/// posts need a look at who is asking. Failing that, a live `share` token
+/// lets anyone in, and uses up one of its reads.
+async fn can_view(
+    pool: &PgPool,
+    repo: &PostRepository,
+    post_id: Uuid,
+    post: &PostResponse,
+    user_id: Option<Uuid>,
+    share: Option<&str>,
+    now: DateTime<Utc>,
+) -> anyhow::Result<bool> {
+    if post.visibility != Visibility::PRIVATE {
+        return Ok(true);
+    }
+    if let Some(user_id) = user_id {
+        let found = repo.find_by_id(post_id).await?;
+        let collaborator = CollaboratorRepository::new(pool.clone())
+            .role(post_id, user_id)
+            .await?;
+        if found
+            .is_some_and(|post| policy::can_view_post(Some(user_id), &post, collaborator).is_ok())
+        {
+            return Ok(true);
+        }
+    }
+    match share {
+        Some(token) => {
+            ShareLinkRepository::new(pool.clone())
+                .redeem(post_id, &hash_link_token(token), now)
+                .await
+        }
+        None => Ok(false),
+    }
+}
+
+/// A post of `org` with its author, along with the post's internal id
+async fn find_org_post_with_author(
+    repo: &PostRepository,
+    id: &str,
+    org: &OrgContext,
+) -> anyhow::Result<Option<(Uuid, PostResponse)>> {
+    let post_id = match repo.find_by_public_id(id).await? {
+        Some(post) if in_org_scope(&post, Some(org)) => post.id,
+        _ => return Ok(None),
+    };
+    Ok(repo
+        .find_by_id_with_author(post_id)
+        .await?
+        .map(|post| (post_id, post)))
+}
+
+/// One query per requested include, run concurrently
+async fn load_included(
+    pool: &PgPool,
+    repo: &PostRepository,
+    post_id: Uuid,
+    post: &PostResponse,
+    includes: &[PostInclude],
+) -> anyhow::Result<PostIncluded> {
+    let media = async {
+        if !includes.contains(&PostInclude::Media) {
+            return Ok(None);
+        }
+        MediaRepository::new(pool.clone())
+            .find_for_post(post_id)
+            .await
+            .map(Some)
+    };
+    let author_posts = async {
+        if !includes.contains(&PostInclude::AuthorPosts) {
+            return Ok(None);
+        }
+        // One extra in case this post is among them
+        let posts = repo
+            .find_latest_by_author(&post.author.id, INCLUDED_AUTHOR_POSTS + 1)
+            .await?;
+        let related: Vec<RelatedPost> = posts
+            .into_iter()
+            .filter(|other| other.id != post.id)
+            .take(INCLUDED_AUTHOR_POSTS as usize)
+            .map(RelatedPost::from)
+            .collect();
+        anyhow::Ok(Some(related))
+    };
+
+    let (media, author_posts) = tokio::try_join!(media, author_posts)?;
+    Ok(PostIncluded {
+        media,
+        author_posts,
+    })
+}
+
+/// Issue a preview link for one of the caller's posts
+///
+/// Anyone holding the link can read the post as it will be published, without
+/// signing in. The link expires after `PREVIEW_LINK_TTL_SECS`, or sooner when
+/// the session that issued it ends.
+#[utoipa::path(
+    post,
+    path = "/posts/{id}/preview-token",
+    params(
+        ("id" = String, Path, description = "Public ID of the post to preview"),
+        ("X-Org-Id" = Option<String>, Header, description = "Organization the post belongs to")
+    ),
+    responses(
+        (status = 200, description = "Preview link issued", body = inline(crate::helpers::response::ApiSuccessResponse<PreviewLink>)),
+        (status = 400, description = "Preview links need a session; API keys can't issue them", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Unauthorized - Invalid or missing authentication", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 403, description = "Forbidden - Not the post author", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Post not found", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    security(
+        ("bearer_auth" = []),
+        ("cookie_auth" = [])
+    ),
+    tag = "Posts"
+)]
+#[allow(clippy::too_many_arguments, reason = "one argument per axum extractor")]
+pub async fn create_preview_token(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Authorize(actor): Authorize,
+    session_id: Option<Extension<SessionId>>,
+    org: Option<Extension<OrgContext>>,
+    version: ApiVersion,
+    ValidatedPath(id): ValidatedPath<String>,
+) -> UnifiedResponse<PreviewLink> {
+    info!(
+        "Handler: Issuing preview link for post {} to user_id: {}",
+        id, actor.user_id
+    );
+
+    let Some(Extension(SessionId(session_id))) = session_id else {
+        return error_response_generic(
+            "Preview Failed".to_string(),
+            "Preview links can only be issued from a signed-in session".to_string(),
+        );
+    };
+
+    let repo = PostRepository::new((*pool).clone());
+
+    let post = match repo.find_by_public_id(&id).await {
+        Ok(Some(post)) if in_org_scope(&post, org.as_deref()) => post,
+        Ok(_) => return not_found_response_generic("Post not found".to_string()),
+        Err(e) => {
+            error!("Handler: Failed to look up post: {}", e);
+            return sql_error_generic(e, "Unable to preview post");
+        }
+    };
+
+    if let Err(err) = policy::can_preview_post(&actor, &post) {
+        return UnifiedResponse::Error(*err);
+    }
+
+    match issue_preview_token(
+        &post.public_id,
+        &session_id,
+        config.preview_link_ttl,
+        clock.as_ref(),
+    ) {
+        Ok((token, expires_at)) => {
+            let url = format!(
+                "{}{}",
+                config.federation.public_url,
+                config.versioned_path(version, &format!("/previews/{}", token))
+            );
+            success_response(
+                "Preview Link Issued".to_string(),
+                PreviewLink {
+                    token,
+                    url,
+                    expires_at,
+                },
+            )
+        }
+        Err(e) => {
+            error!("Handler: Failed to sign preview token: {}", e);
+            error_response_generic(
+                "Preview Failed".to_string(),
+                "Unable to issue preview link".to_string(),
+            )
+        }
+    }
+}
+
+/// Read a post through a preview link
+///
+/// Renders the post exactly as `GET /posts/{id}` would once published, with
+/// the Open Graph metadata link unfurlers would see. Doesn't count as a view.
+#[utoipa::path(
+    get,
+    path = "/previews/{token}",
+    params(
+        ("token" = String, Path, description = "Token from `POST /posts/{id}/preview-token`")
+    ),
+    responses(
+        (status = 200, description = "Post preview", body = inline(crate::helpers::response::ApiSuccessResponse<PostPreview>)),
+        (status = 404, description = "Unknown, expired or revoked link, or the post is gone", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Posts"
+)]
+pub async fn get_preview(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    State(denylist): State<Arc<TokenDenylist>>,
+    ValidatedPath(token): ValidatedPath<String>,
+) -> UnifiedResponse<PostPreview> {
+    info!("Handler: Rendering post preview");
+
+    // Every way a link can fail looks the same, so links can't be probed
+    let not_found = || not_found_response_generic("Preview not found".to_string());
+
+    let claims = match verify_preview_token(&token, clock.as_ref()) {
+        Ok(claims) => claims,
+        Err(e) => {
+            info!("Handler: Rejected preview token: {}", e);
+            return not_found();
+        }
+    };
+
+    if denylist.is_denied(&claims.sid).await {
+        return not_found();
+    }
+    match SessionRepository::new((*pool).clone())
+        .is_active(&claims.sid, clock.now())
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return not_found(),
+        Err(e) => {
+            error!("Handler: Failed to check preview session: {}", e);
+            return sql_error_generic(e, "Unable to retrieve preview");
+        }
+    }
+
+    let repo = PostRepository::new((*pool).clone());
+
+    let post_id = match repo.find_by_public_id(&claims.sub).await {
+        Ok(Some(post)) => post.id,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            error!("Handler: Failed to look up previewed post: {}", e);
+            return sql_error_generic(e, "Unable to retrieve preview");
+        }
+    };
+
+    match repo.find_by_id_with_author(post_id).await {
+        Ok(Some(post)) => {
+            let url = post_url(&config.publish_hooks.post_url_template, &post.id);
+            let meta = post_meta(&post, url);
+            success_response("Preview Retrieved".to_string(), PostPreview { post, meta })
+        }
+        Ok(None) => not_found(),
+        Err(e) => {
+            error!("Handler: Failed to retrieve previewed post: {}", e);
+            sql_error_generic(e, "Unable to retrieve preview")
+        }
+    }
+}
+
+/// A failed view count shouldn't fail the read
+async fn record_view(
+    repo: &PostRepository,
+    post_id: Uuid,
+    viewer: Option<&str>,
+    clock: &dyn Clock,
+) {
+    let Some(viewer) = viewer else {
+        return;
+    };
+    if let Err(e) = repo.record_view(post_id, viewer, clock.now()).await {
+        error!("Handler: Failed to record view for post {}: {}", post_id, e);
+    }
+}
+
+/// Posts are only visible through the organization they belong to
+fn in_org_scope(post: &model::Post, org: Option<&OrgContext>) -> bool {
+    post.org_id == org.map(|org| org.org_id)
+}
+
+fn with_format(posts: Vec<PostResponse>, format: ContentFormat) -> Vec<PostResponse> {
+    posts
+        .into_iter()
+        .map(|post| post.with_format(format))
+        .collect()
 }
@@ -0,0 +1,181 @@
+use crate::config::AppConfig;
+use crate::db::pools::DbPools;
+use crate::db::repositories::{post_repo::PostRepository, user_repo::UserRepository};
+use crate::helpers::path_params::ValidatedPath;
+use crate::helpers::sitemap::{
+    MAX_SITEMAP_URLS, SitemapSection, SitemapUrl, index_pages, page_count, render_index,
+    render_urlset,
+};
+use crate::model::model::ErrorResponse;
+use axum::{
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing::{error, info};
+use utoipa;
+
+/// Public posts and author profiles for search engines
+#[utoipa::path(
+    get,
+    path = "/sitemap.xml",
+    responses(
+        (status = 200, description = "A sitemap, or a sitemap index over `/sitemaps/{file}` pages past 50,000 URLs", content_type = "application/xml", body = String),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Feeds"
+)]
+pub async fn sitemap(State(db): State<DbPools>, State(config): State<Arc<AppConfig>>) -> Response {
+    info!("Handler: Rendering sitemap");
+
+    let posts = PostRepository::from_pools(&db);
+    let users = UserRepository::from_pools(&db);
+
+    let counts = match (
+        posts.count_listed().await,
+        users.count_public_profiles().await,
+    ) {
+        (Ok(posts), Ok(profiles)) => (posts, profiles),
+        (Err(e), _) | (_, Err(e)) => return sitemap_error(e),
+    };
+
+    let pages = index_pages(counts.0, counts.1);
+    if !pages.is_empty() {
+        let sitemaps: Vec<String> = pages
+            .into_iter()
+            .map(|(section, page)| {
+                let path = format!("/sitemaps/{}", section.file_name(page));
+                format!("{}{}", config.sitemap.public_url, config.public_path(&path))
+            })
+            .collect();
+        return xml_response(&config, render_index(&sitemaps));
+    }
+
+    let mut urls = match section_urls(&db, &config, SitemapSection::Posts, 1).await {
+        Ok(urls) => urls,
+        Err(e) => return sitemap_error(e),
+    };
+    match section_urls(&db, &config, SitemapSection::Profiles, 1).await {
+        Ok(profiles) => urls.extend(profiles),
+        Err(e) => return sitemap_error(e),
+    }
+    xml_response(&config, render_urlset(&urls))
+}
+
+/// One page of a sitemap index
+#[utoipa::path(
+    get,
+    path = "/sitemaps/{file}",
+    params(
+        ("file" = String, Path, description = "`posts-N.xml` or `profiles-N.xml`, as listed in `/sitemap.xml`")
+    ),
+    responses(
+        (status = 200, description = "Up to 50,000 URLs", content_type = "application/xml", body = String),
+        (status = 404, description = "No such sitemap page", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Feeds"
+)]
+pub async fn sitemap_page(
+    State(db): State<DbPools>,
+    State(config): State<Arc<AppConfig>>,
+    ValidatedPath(file): ValidatedPath<String>,
+) -> Response {
+    let Some((section, page)) = SitemapSection::from_file_name(&file) else {
+        return sitemap_not_found();
+    };
+    info!(
+        "Handler: Rendering sitemap page {} of {}",
+        page,
+        section.name()
+    );
+
+    let count = match section {
+        SitemapSection::Posts => PostRepository::from_pools(&db).count_listed().await,
+        SitemapSection::Profiles => {
+            UserRepository::from_pools(&db)
+                .count_public_profiles()
+                .await
+        }
+    };
+    match count {
+        Ok(count) if page > page_count(count) => return sitemap_not_found(),
+        Ok(_) => {}
+        Err(e) => return sitemap_error(e),
+    }
+
+    match section_urls(&db, &config, section, page).await {
+        Ok(urls) => xml_response(&config, render_urlset(&urls)),
+        Err(e) => sitemap_error(e),
+    }
+}
+
+/// The URLs on one page of a section
+async fn section_urls(
+    db: &DbPools,
+    config: &AppConfig,
+    section: SitemapSection,
+    page: i64,
+) -> anyhow::Result<Vec<SitemapUrl>> {
+    let offset = (page - 1) * MAX_SITEMAP_URLS;
+    let (rows, template): (Vec<(String, DateTime<Utc>)>, &str) = match section {
+        SitemapSection::Posts => (
+            PostRepository::from_pools(db)
+                .find_listed_page(offset, MAX_SITEMAP_URLS)
+                .await?,
+            &config.publish_hooks.post_url_template,
+        ),
+        SitemapSection::Profiles => (
+            UserRepository::from_pools(db)
+                .find_public_profile_page(offset, MAX_SITEMAP_URLS)
+                .await?,
+            &config.sitemap.profile_url_template,
+        ),
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, lastmod)| SitemapUrl {
+            loc: template.replace("{id}", &id),
+            lastmod,
+        })
+        .collect())
+}
+
+fn xml_response(config: &AppConfig, body: String) -> Response {
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                "application/xml; charset=utf-8".to_string(),
+            ),
+            (
+                header::CACHE_CONTROL,
+                format!("public, max-age={}", config.sitemap.max_age.as_secs()),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+fn sitemap_not_found() -> Response {
+    ErrorResponse::new(
+        StatusCode::NOT_FOUND,
+        "Not Found".to_string(),
+        "No such sitemap".to_string(),
+    )
+    .into_response()
+}
+
+fn sitemap_error(e: anyhow::Error) -> Response {
+    error!("Handler: Failed to build sitemap: {}", e);
+    ErrorResponse::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Sitemap Unavailable".to_string(),
+        "Unable to build the sitemap".to_string(),
+    )
+    .into_response()
+}
@@ -0,0 +1,88 @@
+use crate::config::AppConfig;
+use crate::db::repositories::user_repo::UserRepository;
+use crate::helpers::clock::Clock;
+use crate::helpers::deliverability::{ResendEvent, verify_webhook};
+use crate::model::model::ErrorResponse;
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Delivery, bounce and complaint events from Resend
+///
+/// Answers 404 until `RESEND_WEBHOOK_SECRET` is set. Events that say nothing
+/// about deliverability are acknowledged and dropped.
+#[utoipa::path(
+    post,
+    path = "/webhooks/resend",
+    request_body(content = String, description = "Resend event, signed with the webhook secret", content_type = "application/json"),
+    responses(
+        (status = 204, description = "Event received"),
+        (status = 400, description = "Malformed event", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 401, description = "Missing or invalid signature", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 404, description = "Webhooks are not configured", body = inline(crate::helpers::response::ApiErrorResponse)),
+        (status = 500, description = "Internal server error", body = inline(crate::helpers::response::ApiErrorResponse))
+    ),
+    tag = "Administration"
+)]
+pub async fn resend_webhook(
+    State(pool): State<Arc<PgPool>>,
+    State(config): State<Arc<AppConfig>>,
+    State(clock): State<Arc<dyn Clock>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, ErrorResponse> {
+    let Some(secret) = config.email.webhook_secret.as_deref() else {
+        return Err(ErrorResponse::new(
+            StatusCode::NOT_FOUND,
+            "Not Found",
+            "Resend webhooks are not configured",
+        ));
+    };
+
+    if let Err(e) = verify_webhook(secret, &headers, &body, clock.now()) {
+        warn!("Rejected Resend webhook: {:?}", e);
+        return Err(ErrorResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized",
+            "Invalid webhook signature",
+        ));
+    }
+
+    let event: ResendEvent = serde_json::from_slice(&body).map_err(|e| {
+        warn!("Malformed Resend webhook: {}", e);
+        ErrorResponse::new(StatusCode::BAD_REQUEST, "Invalid Event", e.to_string())
+    })?;
+    let Some(delivery) = event.delivery() else {
+        return Ok(StatusCode::NO_CONTENT);
+    };
+
+    let repo = UserRepository::new((*pool).clone());
+    for email in &event.data.to {
+        match repo
+            .record_email_delivery(email, delivery, event.created_at)
+            .await
+        {
+            Ok(0) => {}
+            Ok(_) => info!(
+                "Handler: {} reported {:?} for {}",
+                event.kind, delivery, email
+            ),
+            Err(e) => {
+                error!("Handler: Failed to record {}: {:?}", event.kind, e);
+                // A 5xx makes Resend deliver the event again later
+                return Err(ErrorResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Database Error",
+                    "Unable to record the event",
+                ));
+            }
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
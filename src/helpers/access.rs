@@ -0,0 +1,246 @@
+//! Access tiers: optional API-key lockdown for every route, and a stricter,
+//! cache-friendly quota for anonymous reads of public posts.
+//!
+//! With Redis, rate limits are counted there so every replica enforces the
+//! same quota; without it each process keeps its own counters.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    num::NonZeroU32,
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use governor::{DefaultKeyedRateLimiter, Quota, RateLimiter, clock::Clock as _};
+use tracing::warn;
+
+use crate::config::{AccessConfig, AppConfig};
+use crate::helpers::cookies::AUTH_COOKIE;
+use crate::helpers::middleware::get_cookie;
+#[cfg(feature = "redis")]
+use crate::helpers::redis_client::RedisClient;
+use crate::model::model::ErrorResponse;
+#[cfg(feature = "redis")]
+use crate::state::AppState;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Rejects requests without a configured `X-API-Key` when lockdown is enabled
+pub async fn api_key_middleware(
+    State(config): State<Arc<AppConfig>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ErrorResponse> {
+    // Let CORS preflights through; browsers never attach custom headers to them.
+    // Webhook senders can't send a key either, and sign their requests instead
+    if !config.access.require_api_key
+        || request.method() == Method::OPTIONS
+        || request.uri().path().starts_with("/webhooks/")
+    {
+        return Ok(next.run(request).await);
+    }
+
+    let provided = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    match provided {
+        Some(key) if is_known_key(&config.access.api_keys, key) => Ok(next.run(request).await),
+        _ => {
+            warn!("Rejected request without a valid API key");
+            Err(ErrorResponse::new(
+                StatusCode::UNAUTHORIZED,
+                "Unauthorized",
+                "A valid X-API-Key header is required",
+            ))
+        }
+    }
+}
+
+pub(crate) fn is_known_key(keys: &[String], candidate: &str) -> bool {
+    // Constant-time per key so valid keys can't be discovered byte by byte
+    keys.iter().fold(false, |found, key| {
+        let matches = key.len() == candidate.len()
+            && key
+                .bytes()
+                .zip(candidate.bytes())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                == 0;
+        found | matches
+    })
+}
+
+/// Requests per client IP allowed on every route in each shared window
+pub const SHARED_RATE_LIMIT: u64 = 5;
+pub const SHARED_RATE_WINDOW: Duration = Duration::from_secs(5);
+
+pub struct PublicReadTier {
+    limiter: DefaultKeyedRateLimiter<IpAddr>,
+    cache_control: HeaderValue,
+    /// Requests allowed per shared window; the burst of the local quota
+    #[cfg(feature = "redis")]
+    burst: u32,
+    /// Long enough for the local quota to refill the whole burst
+    #[cfg(feature = "redis")]
+    window: Duration,
+    #[cfg(feature = "redis")]
+    redis: Option<RedisClient>,
+}
+
+impl PublicReadTier {
+    pub fn new(access: &AccessConfig) -> Self {
+        let burst = NonZeroU32::new(access.public_read_burst).unwrap_or(NonZeroU32::MIN);
+        let quota = Quota::with_period(access.public_read_period)
+            .unwrap_or_else(|| Quota::per_second(NonZeroU32::MIN))
+            .allow_burst(burst);
+
+        let cache_control = HeaderValue::from_str(&format!(
+            "public, max-age={}",
+            access.public_cache_max_age.as_secs()
+        ))
+        .unwrap_or_else(|_| HeaderValue::from_static("no-cache"));
+
+        Self {
+            limiter: RateLimiter::keyed(quota),
+            cache_control,
+            #[cfg(feature = "redis")]
+            burst: burst.get(),
+            #[cfg(feature = "redis")]
+            window: access.public_read_period * burst.get(),
+            #[cfg(feature = "redis")]
+            redis: None,
+        }
+    }
+
+    /// Counts anonymous reads in Redis so the quota holds across replicas
+    #[cfg(feature = "redis")]
+    pub fn with_redis(mut self, client: RedisClient) -> Self {
+        self.redis = Some(client);
+        self
+    }
+
+    pub fn retain_recent(&self) {
+        self.limiter.retain_recent();
+    }
+
+    /// `Err` holds how long the caller should wait
+    async fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        #[cfg(feature = "redis")]
+        if let Some(redis) = &self.redis {
+            let key = format!("ratelimit:public:{}", ip);
+            match redis.hit(&key, self.window).await {
+                Ok((count, _)) if count <= u64::from(self.burst) => return Ok(()),
+                Ok((_, reset)) => return Err(reset),
+                // An unreachable Redis shouldn't take reads down; fall back to this process
+                Err(e) => warn!("Shared public read limit unavailable: {}", e),
+            }
+        }
+
+        self.limiter
+            .check_key(&ip)
+            .map_err(|not_until| not_until.wait_time_from(self.limiter.clock().now()))
+    }
+}
+
+fn too_many_requests(message: &str, wait: Duration) -> Response {
+    let mut response =
+        ErrorResponse::new(StatusCode::TOO_MANY_REQUESTS, "Too Many Requests", message)
+            .into_response();
+    if let Ok(value) = HeaderValue::from_str(&wait.as_secs().max(1).to_string()) {
+        response.headers_mut().insert(header::RETRY_AFTER, value);
+    }
+    response
+}
+
+fn client_ip(request: &Request) -> Option<IpAddr> {
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// Per-IP limit on every route counted in Redis, replacing the per-process
+/// `GovernorLayer` when replicas share a Redis
+#[cfg(feature = "redis")]
+pub async fn shared_rate_limit_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (Some(redis), Some(ip)) = (&state.redis, client_ip(&request)) else {
+        return next.run(request).await;
+    };
+
+    let key = format!("ratelimit:global:{}", ip);
+    let (count, reset) = match redis.hit(&key, SHARED_RATE_WINDOW).await {
+        Ok(hit) => hit,
+        Err(e) => {
+            warn!(
+                "Shared rate limit unavailable, letting request through: {}",
+                e
+            );
+            return next.run(request).await;
+        }
+    };
+
+    if count > SHARED_RATE_LIMIT {
+        return too_many_requests("Too Many Requests! Wait for some time", reset);
+    }
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("x-ratelimit-limit", HeaderValue::from(SHARED_RATE_LIMIT));
+    headers.insert(
+        "x-ratelimit-remaining",
+        HeaderValue::from(SHARED_RATE_LIMIT - count),
+    );
+    response
+}
+
+fn is_anonymous(headers: &HeaderMap) -> bool {
+    !headers.contains_key(header::AUTHORIZATION) && get_cookie(headers, AUTH_COOKIE).is_none()
+}
+
+/// Rate limits and marks cacheable the public read routes for callers without credentials
+pub async fn public_read_middleware(
+    State(tier): State<Arc<PublicReadTier>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !is_anonymous(request.headers()) {
+        return next.run(request).await;
+    }
+
+    if let Some(ip) = client_ip(&request)
+        && let Err(wait) = tier.check(ip).await
+    {
+        return too_many_requests(
+            "Anonymous read limit reached; authenticate for a higher limit",
+            wait,
+        );
+    }
+
+    // A share link's expiry and read limit only hold if nothing caches what it opened
+    let shared = request
+        .uri()
+        .query()
+        .is_some_and(|query| query.split('&').any(|pair| pair.starts_with("share=")));
+
+    let mut response = next.run(request).await;
+    if response.status().is_success() && !shared {
+        let headers = response.headers_mut();
+        headers.insert(header::CACHE_CONTROL, tier.cache_control.clone());
+        headers.append(
+            header::VARY,
+            HeaderValue::from_static("Authorization, Cookie"),
+        );
+    }
+    response
+}
@@ -1,39 +1,112 @@
 use std::env;
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use bcrypt::{DEFAULT_COST, hash, verify};
-use chrono::{Duration, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
-use tracing::info;
+use chrono::Duration;
+use jsonwebtoken::Algorithm;
+use tracing::{info, warn};
 use uuid::Uuid;
 
+use crate::config::TokenConfig;
+use crate::helpers::clock::Clock;
+use crate::helpers::jwt_keys::JwtKeys;
+use crate::helpers::secrets::{pepper_password, read_secret};
 use crate::helpers::validation::generate_base64_string;
-use crate::model::model::{Claims, Role};
+use crate::model::model::{ActorClaim, Claims, Role};
 
 lazy_static::lazy_static! {
-    pub static ref JWT_SECRET: String = env::var("AUTH_SECRET")
-    .unwrap_or_else(|_| generate_base64_string());
+    /// `AUTH_SECRET` as configured; `None` means this process made one up
+    static ref AUTH_SECRET: Option<String> = read_secret("AUTH_SECRET")
+        .expect("invalid AUTH_SECRET configuration");
+
+    pub static ref JWT_SECRET: String = AUTH_SECRET
+        .clone()
+        .unwrap_or_else(generate_base64_string);
+
+    /// Server-side secret mixed into every password before it is hashed
+    static ref PASSWORD_PEPPER: Option<String> = read_secret("PASSWORD_PEPPER")
+        .expect("invalid PASSWORD_PEPPER configuration");
 
     pub static ref BASE_URL: String = env::var("DOMAIN")
         .unwrap_or_else(|_| "localhost".to_string());
+
+    /// Current signing key plus any previous key still inside its rotation window
+    pub static ref JWT_KEYS: JwtKeys = JwtKeys::from_env(&JWT_SECRET)
+        .expect("invalid JWT key configuration");
+}
+
+/// Allowed clock skew when checking `exp`, matching jsonwebtoken's default
+pub const TOKEN_LEEWAY_SECS: usize = 60;
+
+/// Loads the deployment secrets and refuses to run production with a signing
+/// secret generated at startup, since a restart would invalidate every token
+pub fn check_secrets(production: bool) -> Result<()> {
+    lazy_static::initialize(&PASSWORD_PEPPER);
+    if AUTH_SECRET.is_some() || JWT_KEYS.current_algorithm() != Algorithm::HS256 {
+        return Ok(());
+    }
+    if production {
+        bail!("AUTH_SECRET or AUTH_SECRET_FILE must be set in production");
+    }
+    warn!("AUTH_SECRET is not set; tokens signed now stop working when the server restarts");
+    Ok(())
 }
 
 pub struct AuthHelper;
 
 impl AuthHelper {
     pub fn hash_password(password: &str) -> Result<String> {
-        let hashed = hash(password, DEFAULT_COST)?;
+        let hashed = match PASSWORD_PEPPER.as_deref() {
+            Some(pepper) => hash(pepper_password(password, pepper), DEFAULT_COST)?,
+            None => hash(password, DEFAULT_COST)?,
+        };
         Ok(hashed)
     }
 
+    /// Hashes stored before a pepper was configured still verify, until the
+    /// password is next changed
     pub fn verify_password(password: &str, hashed: &str) -> Result<bool> {
+        let peppered = match PASSWORD_PEPPER.as_deref() {
+            Some(pepper) => verify(pepper_password(password, pepper), hashed)?,
+            None => false,
+        };
+        if peppered {
+            return Ok(true);
+        }
         let is_valid = verify(password, hashed)?;
         Ok(is_valid)
     }
 
-    pub fn generate_token(user_id: Uuid, role: Role) -> Result<(String, String)> {
-        let expiration = Utc::now()
-            .checked_add_signed(chrono::Duration::hours(24))
+    pub fn generate_token(
+        user_id: Uuid,
+        role: Role,
+        tokens: &TokenConfig,
+        clock: &dyn Clock,
+    ) -> Result<(String, String)> {
+        Self::issue_tokens(user_id, role, None, tokens, clock)
+    }
+
+    /// Auth and refresh tokens bound to `session_id`, so revoking the session revokes both
+    pub fn generate_session_tokens(
+        user_id: Uuid,
+        role: Role,
+        session_id: &str,
+        tokens: &TokenConfig,
+        clock: &dyn Clock,
+    ) -> Result<(String, String)> {
+        Self::issue_tokens(user_id, role, Some(session_id), tokens, clock)
+    }
+
+    fn issue_tokens(
+        user_id: Uuid,
+        role: Role,
+        session_id: Option<&str>,
+        tokens: &TokenConfig,
+        clock: &dyn Clock,
+    ) -> Result<(String, String)> {
+        let now = clock.now();
+        let expiration = now
+            .checked_add_signed(Duration::from_std(tokens.access_ttl)?)
             .expect("valid timestamp")
             .timestamp() as usize;
 
@@ -41,19 +114,18 @@ impl AuthHelper {
             iss: BASE_URL.clone(),
             sub: user_id.to_string(),
             role: role.clone(),
-            iat: Utc::now().timestamp() as usize,
+            iat: now.timestamp() as usize,
             exp: expiration,
+            sid: session_id.map(str::to_string),
+            jti: None,
+            act: None,
         };
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
-        )?;
+        let token = JWT_KEYS.sign(&claims)?;
         info!("Generated Auth token for user_id {}", user_id);
 
-        let expiration = Utc::now()
-            .checked_add_signed(Duration::days(7))
+        let expiration = now
+            .checked_add_signed(Duration::from_std(tokens.refresh_ttl)?)
             .expect("valid timestamp")
             .timestamp() as usize;
 
@@ -61,60 +133,85 @@ impl AuthHelper {
             iss: BASE_URL.clone(),
             sub: user_id.to_string(),
             role: role,
-            iat: Utc::now().timestamp() as usize,
+            iat: now.timestamp() as usize,
             exp: expiration,
+            sid: session_id.map(str::to_string),
+            jti: session_id.map(str::to_string),
+            act: None,
         };
 
-        let refresh_token = encode(
-            &Header::default(),
-            &refresh_claims,
-            &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
-        )?;
+        let refresh_token = JWT_KEYS.sign(&refresh_claims)?;
         info!("Generated Refresh token for user_id {}", user_id);
         Ok((token, refresh_token))
     }
 
-    pub fn validate_token(token: &str) -> Result<Claims> {
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
-            &Validation::default(),
-        )?;
-        Ok(token_data.claims)
+    /// Auth token for `admin_id` acting as `user_id` within `session_id`,
+    /// valid for `ttl`
+    pub fn generate_impersonation_token(
+        user_id: Uuid,
+        role: Role,
+        admin_id: Uuid,
+        session_id: &str,
+        ttl: Duration,
+        clock: &dyn Clock,
+    ) -> Result<String> {
+        let now = clock.now();
+        let claims = Claims {
+            iss: BASE_URL.clone(),
+            sub: user_id.to_string(),
+            role,
+            iat: now.timestamp() as usize,
+            exp: (now + ttl).timestamp() as usize,
+            sid: Some(session_id.to_string()),
+            jti: None,
+            act: Some(ActorClaim {
+                sub: admin_id.to_string(),
+            }),
+        };
+
+        let token = JWT_KEYS.sign(&claims)?;
+        info!(
+            "Generated impersonation token for user_id {} by admin {}",
+            user_id, admin_id
+        );
+        Ok(token)
+    }
+
+    pub fn validate_token(token: &str, clock: &dyn Clock) -> Result<Claims> {
+        // Expiry is checked against the injected clock rather than the system time
+        let claims: Claims = JWT_KEYS.verify(token, clock)?;
+
+        let now = clock.timestamp();
+        if claims.exp + TOKEN_LEEWAY_SECS < now {
+            anyhow::bail!("Token has expired");
+        }
+
+        Ok(claims)
     }
 
-    pub fn extract_user_id_from_token(token: &str) -> Result<Uuid> {
-        let claims = Self::validate_token(token)?;
+    pub fn extract_user_id_from_token(token: &str, clock: &dyn Clock) -> Result<Uuid> {
+        let claims = Self::validate_token(token, clock)?;
         let user_id = Uuid::parse_str(&claims.sub)?;
         Ok(user_id)
     }
 
-    pub fn extract_user_role_from_token(token: &str) -> Result<Role> {
-        let claims = Self::validate_token(token)?;
+    pub fn extract_user_role_from_token(token: &str, clock: &dyn Clock) -> Result<Role> {
+        let claims = Self::validate_token(token, clock)?;
         Ok(claims.role)
     }
 
-    pub fn generate_email_verification_token(user_id: Uuid) -> String {
-        let expiration = Utc::now()
-            .checked_add_signed(Duration::minutes(15))
-            .expect("valid timestamp")
-            .timestamp() as usize;
-
-        let claims = Claims {
-            iss: BASE_URL.clone(),
-            sub: user_id.to_string(),
-            role: Role::USER,
-            iat: Utc::now().timestamp() as usize,
-            exp: expiration,
-        };
+    pub fn extract_session_id_from_token(token: &str, clock: &dyn Clock) -> Result<Option<String>> {
+        let claims = Self::validate_token(token, clock)?;
+        Ok(claims.sid)
+    }
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
-        )
-        .expect("Failed to generate email verification token");
-        info!("Generated email verification token for user_id {}", user_id);
-        token
+    /// The admin behind an impersonation token; `None` for ordinary tokens
+    pub fn extract_impersonator_from_token(token: &str, clock: &dyn Clock) -> Result<Option<Uuid>> {
+        let claims = Self::validate_token(token, clock)?;
+        claims
+            .act
+            .map(|act| Uuid::parse_str(&act.sub))
+            .transpose()
+            .map_err(Into::into)
     }
 }
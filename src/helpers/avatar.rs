@@ -0,0 +1,29 @@
+use sha2::{Digest, Sha256};
+
+pub const MAX_AVATAR_URL_LEN: usize = 2048;
+
+/// Gravatar URL for `email`, falling back to a generated identicon
+pub fn gravatar_url(email: &str) -> String {
+    let hash = Sha256::digest(email.trim().to_lowercase().as_bytes());
+    format!("https://www.gravatar.com/avatar/{:x}?d=identicon", hash)
+}
+
+/// The user's own avatar when set, otherwise their Gravatar
+pub fn avatar_url(custom: Option<&str>, email: &str) -> String {
+    match custom {
+        Some(url) if !url.is_empty() => url.to_string(),
+        _ => gravatar_url(email),
+    }
+}
+
+pub fn validate_avatar_url(url: &str) -> Result<(), String> {
+    if url.len() > MAX_AVATAR_URL_LEN {
+        return Err("Avatar URL is too long".to_string());
+    }
+
+    if !url.starts_with("https://") {
+        return Err("Avatar URL must use https".to_string());
+    }
+
+    Ok(())
+}
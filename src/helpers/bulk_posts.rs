@@ -0,0 +1,141 @@
+//! Creating many posts in one request, for importers and scripted publishing.
+//!
+//! Every item is checked like a post created through `POST /posts` and gets
+//! its slug before anything is written; the valid ones then go in with a
+//! single multi-row insert. One bad item doesn't stop the rest, and the report
+//! lists each one with the new post's ID or the reason it was left out.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::db::repositories::post_repo::PostRepository;
+use crate::helpers::ids::new_public_id;
+use crate::helpers::slugs::with_suffix;
+use crate::helpers::validation::check_new_post;
+use crate::model::model::{CreatePostRequest, Post};
+
+/// Most posts a single bulk request takes
+pub const MAX_BULK_POSTS: usize = 100;
+
+const SLUG_IN_USE: &str = "Slug is already in use";
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkCreatePostsRequest {
+    /// Up to 100 posts, created in this order
+    pub posts: Vec<CreatePostRequest>,
+}
+
+/// Outcome of one item of a bulk request
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BulkPostResult {
+    /// Position of the item in `posts`
+    pub index: usize,
+    /// Public ID of the created post; `None` when the item failed
+    pub id: Option<String>,
+    pub slug: Option<String>,
+    /// Why the item was not created
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct BulkCreateReport {
+    pub created: usize,
+    pub failed: usize,
+    pub items: Vec<BulkPostResult>,
+}
+
+impl BulkPostResult {
+    fn failed(index: usize, error: String) -> Self {
+        Self {
+            index,
+            id: None,
+            slug: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Creates the valid items of `posts` for `author_id`; returns the report and
+/// the posts that were created
+pub async fn create_posts(
+    repo: &PostRepository,
+    config: &AppConfig,
+    author_id: Uuid,
+    org_id: Option<Uuid>,
+    posts: Vec<CreatePostRequest>,
+    now: DateTime<Utc>,
+) -> Result<(BulkCreateReport, Vec<Post>)> {
+    let mut items = Vec::with_capacity(posts.len());
+    let mut pending = Vec::new();
+    let mut indexes = Vec::new();
+    let mut claimed = HashSet::new();
+
+    for (index, post) in posts.into_iter().enumerate() {
+        let request = match check_new_post(post, config) {
+            Ok(request) => request,
+            Err(error) => {
+                items.push(BulkPostResult::failed(index, error));
+                continue;
+            }
+        };
+
+        // An explicit slug must be free, including of earlier items; a derived
+        // one gets a suffix instead
+        let slug = match request.slug.as_deref() {
+            Some(slug) if claimed.contains(slug) || repo.slug_taken(slug).await? => {
+                items.push(BulkPostResult::failed(index, SLUG_IN_USE.to_string()));
+                continue;
+            }
+            Some(slug) => slug.to_string(),
+            None => {
+                let slug = repo.free_slug(&request.title, &config.slugs).await?;
+                if claimed.contains(&slug) {
+                    with_suffix(&slug, &new_public_id(), &config.slugs)
+                } else {
+                    slug
+                }
+            }
+        };
+        claimed.insert(slug.clone());
+        indexes.push(index);
+        pending.push((request, slug));
+    }
+
+    let mut created = Vec::new();
+    for (index, post) in indexes
+        .into_iter()
+        .zip(repo.create_posts(pending, author_id, org_id, now).await?)
+    {
+        match post {
+            Some(post) => {
+                items.push(BulkPostResult {
+                    index,
+                    id: Some(post.public_id.clone()),
+                    slug: post.slug.clone(),
+                    error: None,
+                });
+                created.push(post);
+            }
+            None => items.push(BulkPostResult::failed(index, SLUG_IN_USE.to_string())),
+        }
+    }
+    items.sort_by_key(|item| item.index);
+
+    let report = BulkCreateReport {
+        created: created.len(),
+        failed: items.len() - created.len(),
+        items,
+    };
+    info!(
+        "Bulk post creation for {} finished: {} created, {} failed",
+        author_id, report.created, report.failed
+    );
+    Ok((report, created))
+}
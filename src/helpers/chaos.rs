@@ -0,0 +1,118 @@
+//! Fault injection for resilience testing, compiled in with the `chaos` feature.
+//!
+//! Faults are chosen per request, either explicitly through `X-Chaos-*` headers
+//! or randomly from the `CHAOS_*` environment probabilities. Without the feature
+//! every hook below is a no-op.
+
+#[cfg(not(feature = "chaos"))]
+use anyhow::Result;
+
+#[cfg(feature = "chaos")]
+pub use enabled::*;
+
+#[cfg(feature = "chaos")]
+mod enabled {
+    use std::{env, time::Duration};
+
+    use anyhow::Result;
+    use axum::{extract::Request, http::HeaderMap, middleware::Next, response::Response};
+    use tracing::warn;
+
+    pub const LATENCY_HEADER: &str = "x-chaos-latency-ms";
+    pub const DB_ERROR_HEADER: &str = "x-chaos-db-error";
+    pub const EMAIL_ERROR_HEADER: &str = "x-chaos-email-error";
+
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct ChaosFaults {
+        pub latency_ms: u64,
+        pub db_error: bool,
+        pub email_error: bool,
+    }
+
+    tokio::task_local! {
+        static FAULTS: ChaosFaults;
+    }
+
+    lazy_static::lazy_static! {
+        static ref LATENCY_MS: u64 = env_parse("CHAOS_LATENCY_MS").unwrap_or(0);
+        static ref LATENCY_RATE: f64 = env_parse("CHAOS_LATENCY_RATE").unwrap_or(0.0);
+        static ref DB_ERROR_RATE: f64 = env_parse("CHAOS_DB_ERROR_RATE").unwrap_or(0.0);
+        static ref EMAIL_ERROR_RATE: f64 = env_parse("CHAOS_EMAIL_ERROR_RATE").unwrap_or(0.0);
+    }
+
+    fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+        env::var(key).ok().and_then(|value| value.parse().ok())
+    }
+
+    fn roll(rate: f64) -> bool {
+        rate > 0.0 && rand::random::<f64>() < rate
+    }
+
+    fn header_flag(headers: &HeaderMap, name: &str) -> Option<bool> {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| matches!(value, "1" | "true"))
+    }
+
+    impl ChaosFaults {
+        pub fn from_headers(headers: &HeaderMap) -> Self {
+            let latency_ms = headers
+                .get(LATENCY_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(|| if roll(*LATENCY_RATE) { *LATENCY_MS } else { 0 });
+
+            Self {
+                latency_ms,
+                db_error: header_flag(headers, DB_ERROR_HEADER)
+                    .unwrap_or_else(|| roll(*DB_ERROR_RATE)),
+                email_error: header_flag(headers, EMAIL_ERROR_HEADER)
+                    .unwrap_or_else(|| roll(*EMAIL_ERROR_RATE)),
+            }
+        }
+    }
+
+    pub async fn chaos_middleware(request: Request, next: Next) -> Response {
+        let faults = ChaosFaults::from_headers(request.headers());
+
+        if faults.latency_ms > 0 {
+            warn!("Chaos: injecting {}ms latency", faults.latency_ms);
+            tokio::time::sleep(Duration::from_millis(faults.latency_ms)).await;
+        }
+
+        FAULTS.scope(faults, next.run(request)).await
+    }
+
+    fn current() -> ChaosFaults {
+        FAULTS.try_with(|faults| *faults).unwrap_or_default()
+    }
+
+    pub fn db_fault() -> Result<()> {
+        if current().db_error {
+            warn!("Chaos: injecting database error");
+            anyhow::bail!("Chaos: injected database error");
+        }
+        Ok(())
+    }
+
+    pub fn email_fault() -> Result<()> {
+        if current().email_error {
+            warn!("Chaos: injecting email delivery error");
+            anyhow::bail!("Chaos: injected email delivery error");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "chaos"))]
+#[inline(always)]
+pub fn db_fault() -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(feature = "chaos"))]
+#[inline(always)]
+pub fn email_fault() -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! The auth, refresh and CSRF cookies set at login and cleared at logout.
+//!
+//! Every cookie takes its `Secure`, `SameSite`, `Domain` and `Path` from
+//! [`CookieConfig`], so clearing a cookie matches the attributes it was set
+//! with; browsers keep a cookie when the clearing one differs in domain or path.
+
+use std::time::Duration;
+
+use axum_extra::extract::cookie::Cookie;
+
+use crate::config::{AppConfig, CookieConfig, TokenConfig};
+use crate::helpers::middleware::CSRF_COOKIE;
+
+pub const AUTH_COOKIE: &str = "auth_token";
+pub const REFRESH_COOKIE: &str = "refresh_token";
+
+pub struct CookieFactory<'a> {
+    cookies: &'a CookieConfig,
+    tokens: &'a TokenConfig,
+}
+
+impl<'a> CookieFactory<'a> {
+    pub fn new(config: &'a AppConfig) -> Self {
+        Self {
+            cookies: &config.cookies,
+            tokens: &config.tokens,
+        }
+    }
+
+    /// Lives as long as the auth token it carries
+    pub fn auth(&self, token: String) -> Cookie<'static> {
+        self.build(AUTH_COOKIE, token, Some(self.tokens.access_ttl), true)
+    }
+
+    /// Lives as long as the refresh token it carries
+    pub fn refresh(&self, token: String) -> Cookie<'static> {
+        self.build(REFRESH_COOKIE, token, Some(self.tokens.refresh_ttl), true)
+    }
+
+    /// Readable by scripts so clients can echo it in the X-CSRF-Token header
+    pub fn csrf(&self, token: String) -> Cookie<'static> {
+        self.build(CSRF_COOKIE, token, Some(self.tokens.access_ttl), false)
+    }
+
+    /// The auth, refresh and CSRF cookies of a new login
+    pub fn login(
+        &self,
+        auth_token: String,
+        refresh_token: String,
+        csrf_token: String,
+    ) -> Vec<Cookie<'static>> {
+        vec![
+            self.auth(auth_token),
+            self.refresh(refresh_token),
+            self.csrf(csrf_token),
+        ]
+    }
+
+    /// Expired copies of the login cookies, removing them from the browser
+    pub fn clear(&self) -> Vec<Cookie<'static>> {
+        vec![
+            self.build(AUTH_COOKIE, String::new(), None, true),
+            self.build(REFRESH_COOKIE, String::new(), None, true),
+            self.build(CSRF_COOKIE, String::new(), None, false),
+        ]
+    }
+
+    /// `max_age` of `None` makes the cookie already expired
+    fn build(
+        &self,
+        name: &'static str,
+        value: String,
+        max_age: Option<Duration>,
+        http_only: bool,
+    ) -> Cookie<'static> {
+        let max_age = match max_age {
+            Some(max_age) => time::Duration::seconds(max_age.as_secs() as i64),
+            None => time::Duration::seconds(-1),
+        };
+
+        let mut cookie = Cookie::build((name, value))
+            .path(self.cookies.path.clone())
+            .max_age(max_age)
+            .http_only(http_only)
+            .secure(self.cookies.secure)
+            .same_site(self.cookies.same_site)
+            .build();
+        if let Some(domain) = &self.cookies.domain {
+            cookie.set_domain(domain.clone());
+        }
+        cookie
+    }
+}
@@ -0,0 +1,149 @@
+//! Delivery events from Resend's webhook, and the addresses they rule out.
+//!
+//! Resend signs each event the Svix way: an HMAC-SHA256 over
+//! `{svix-id}.{svix-timestamp}.{body}`, keyed with the base64 part of the
+//! `whsec_` secret and sent as `v1,<base64>` in `svix-signature`. The latest
+//! event per address is stored on the user, and [`SuppressingMailer`] stops
+//! sending to addresses that hard-bounced or complained.
+
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use axum::http::HeaderMap;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::db::repositories::user_repo::UserRepository;
+use crate::helpers::email_templates::RenderedEmail;
+use crate::helpers::mailer::{MailFuture, Mailer};
+use crate::model::model::EmailDelivery;
+
+/// How far the signed timestamp may be from now, against replayed events
+pub const SIGNATURE_TOLERANCE_SECS: i64 = 5 * 60;
+
+/// Checks the Svix signature headers of a webhook request
+pub fn verify_webhook(
+    secret: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| anyhow!("missing {} header", name))
+    };
+    let id = header("svix-id")?;
+    let timestamp = header("svix-timestamp")?;
+    let signatures = header("svix-signature")?;
+
+    let sent_at: i64 = timestamp.parse().context("invalid svix-timestamp")?;
+    if (now.timestamp() - sent_at).abs() > SIGNATURE_TOLERANCE_SECS {
+        bail!("svix-timestamp is too far from the current time");
+    }
+
+    let key = STANDARD
+        .decode(secret.strip_prefix("whsec_").unwrap_or(secret))
+        .context("the webhook secret is not a whsec_ secret")?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("HMAC accepts any key length");
+    mac.update(id.as_bytes());
+    mac.update(b".");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    // Several signatures are sent while the secret is being rotated
+    let valid = signatures
+        .split_whitespace()
+        .filter_map(|signature| signature.strip_prefix("v1,"))
+        .filter_map(|signature| STANDARD.decode(signature).ok())
+        .any(|signature| mac.clone().verify_slice(&signature).is_ok());
+    if !valid {
+        bail!("no matching signature");
+    }
+    Ok(())
+}
+
+/// The parts of a Resend event this API reads
+#[derive(Debug, Deserialize)]
+pub struct ResendEvent {
+    /// `email.delivered`, `email.bounced`, ...
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub created_at: DateTime<Utc>,
+    pub data: ResendEventData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResendEventData {
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub bounce: Option<Bounce>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Bounce {
+    /// `Permanent`, `Transient` or `Undetermined`
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
+}
+
+impl ResendEvent {
+    /// What the event says about the recipients; `None` for events that
+    /// don't change it, such as `email.sent` or `email.opened`
+    pub fn delivery(&self) -> Option<EmailDelivery> {
+        match self.kind.as_str() {
+            "email.delivered" => Some(EmailDelivery::DELIVERED),
+            "email.delivery_delayed" => Some(EmailDelivery::DELAYED),
+            "email.complained" => Some(EmailDelivery::COMPLAINED),
+            "email.bounced" => match self.data.bounce.as_ref().and_then(|b| b.kind.as_deref()) {
+                Some("Transient") | Some("Undetermined") => Some(EmailDelivery::SOFT_BOUNCED),
+                _ => Some(EmailDelivery::BOUNCED),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Sends with `inner`, except to addresses whose last event rules them out
+pub struct SuppressingMailer {
+    inner: Arc<dyn Mailer>,
+    users: UserRepository,
+}
+
+impl SuppressingMailer {
+    pub fn new(inner: Arc<dyn Mailer>, pool: PgPool) -> Self {
+        Self {
+            inner,
+            users: UserRepository::new(pool),
+        }
+    }
+}
+
+impl Mailer for SuppressingMailer {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn send<'a>(&'a self, to: &'a str, email: &'a RenderedEmail) -> MailFuture<'a> {
+        Box::pin(async move {
+            match self.users.email_delivery(to).await {
+                Ok(Some(delivery)) if delivery.blocks_sending() => {
+                    info!("Not emailing {}: the address is {:?}", to, delivery);
+                    return Ok(());
+                }
+                Ok(_) => {}
+                // Better a send to a bad address than a lost verification email
+                Err(e) => warn!("Unable to check delivery status of {}: {:?}", to, e),
+            }
+            self.inner.send(to, email).await
+        })
+    }
+}
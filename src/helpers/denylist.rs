@@ -0,0 +1,78 @@
+//! Revoked sessions, shared through Redis.
+//!
+//! The `sessions` table stays authoritative; the denylist lets every replica
+//! reject the refresh and auth tokens of a revoked session without a database
+//! round trip. Without Redis it holds nothing and the database check applies.
+
+#[cfg(feature = "redis")]
+use std::time::Duration;
+
+#[cfg(feature = "redis")]
+use tracing::warn;
+
+#[cfg(feature = "redis")]
+use crate::helpers::redis_client::RedisClient;
+
+#[derive(Default)]
+pub struct TokenDenylist {
+    #[cfg(feature = "redis")]
+    redis: Option<RedisClient>,
+    /// Tokens of a session never outlive its refresh token
+    #[cfg(feature = "redis")]
+    ttl: Duration,
+}
+
+impl TokenDenylist {
+    /// A denylist that never denies; revocation is checked in the database only
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "redis")]
+impl TokenDenylist {
+    /// Denials last `ttl`, which must cover the refresh token lifetime
+    pub fn redis(client: RedisClient, ttl: Duration) -> Self {
+        Self {
+            redis: Some(client),
+            ttl,
+        }
+    }
+
+    /// Failures are logged; the revoked sessions are still rejected by the database
+    pub async fn deny(&self, session_ids: &[String]) {
+        let Some(redis) = &self.redis else {
+            return;
+        };
+
+        for id in session_ids {
+            if let Err(e) = redis.deny(&format!("session:{}", id), self.ttl).await {
+                warn!("Failed to deny session {}: {}", id, e);
+            }
+        }
+    }
+
+    /// Lookup failures count as not denied and fall through to the database
+    pub async fn is_denied(&self, session_id: &str) -> bool {
+        let Some(redis) = &self.redis else {
+            return false;
+        };
+
+        match redis.is_denied(&format!("session:{}", session_id)).await {
+            Ok(denied) => denied,
+            Err(e) => {
+                warn!("Failed to check denylist for session {}: {}", session_id, e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "redis"))]
+impl TokenDenylist {
+    pub async fn deny(&self, _session_ids: &[String]) {}
+
+    pub async fn is_denied(&self, _session_id: &str) -> bool {
+        false
+    }
+}
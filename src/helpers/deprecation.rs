@@ -0,0 +1,113 @@
+//! 410 Gone handling for endpoints removed between API versions.
+//!
+//! Each configured entry counts how often it is still hit so operators can
+//! tell when clients have finished migrating.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::config::GoneEndpointConfig;
+use crate::model::model::ErrorResponse;
+
+struct GoneEndpoint {
+    config: GoneEndpointConfig,
+    hits: AtomicU64,
+}
+
+impl GoneEndpoint {
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        let method_matches = self
+            .config
+            .method
+            .as_ref()
+            .is_none_or(|expected| expected == method);
+        let path_matches = match self.config.path.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == self.config.path,
+        };
+        method_matches && path_matches
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GoneEndpointStats {
+    pub method: Option<String>,
+    pub path: String,
+    pub hint: String,
+    pub hits: u64,
+}
+
+#[derive(Default)]
+pub struct GoneEndpoints {
+    endpoints: Vec<GoneEndpoint>,
+}
+
+impl GoneEndpoints {
+    pub fn new(configs: &[GoneEndpointConfig]) -> Self {
+        Self {
+            endpoints: configs
+                .iter()
+                .cloned()
+                .map(|config| GoneEndpoint {
+                    config,
+                    hits: AtomicU64::new(0),
+                })
+                .collect(),
+        }
+    }
+
+    /// Records a hit and returns the migration hint when the request targets a removed endpoint
+    pub fn check(&self, method: &Method, path: &str) -> Option<&str> {
+        let endpoint = self
+            .endpoints
+            .iter()
+            .find(|endpoint| endpoint.matches(method, path))?;
+        endpoint.hits.fetch_add(1, Ordering::Relaxed);
+        Some(&endpoint.config.hint)
+    }
+
+    pub fn stats(&self) -> Vec<GoneEndpointStats> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| GoneEndpointStats {
+                method: endpoint.config.method.as_ref().map(ToString::to_string),
+                path: endpoint.config.path.clone(),
+                hint: endpoint.config.hint.clone(),
+                hits: endpoint.hits.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+pub async fn gone_middleware(
+    State(gone): State<Arc<GoneEndpoints>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let hint = gone.check(request.method(), request.uri().path());
+
+    match hint {
+        Some(hint) => {
+            warn!(
+                target: "deprecation",
+                method = %request.method(),
+                path = %request.uri().path(),
+                "Request to removed endpoint"
+            );
+            ErrorResponse::new(StatusCode::GONE, "Gone", hint).into_response()
+        }
+        None => next.run(request).await,
+    }
+}
@@ -0,0 +1,464 @@
+//! What an operator needs to debug a deployment without a shell: the
+//! effective configuration with secrets masked, compiled-in features, and
+//! whether the services the API depends on answer.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+
+use crate::config::{AppConfig, CaptchaProvider, CdnProvider};
+use crate::db::health::probe;
+use crate::helpers::secrets::read_secret;
+use crate::model::model::{DbStatus, DependencyCheck, DependencyStatus};
+use crate::state::AppState;
+
+/// Shown instead of a secret
+pub const MASK: &str = "***";
+
+/// How long a dependency gets to answer
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Cargo features this binary was built with
+pub fn enabled_features() -> Vec<String> {
+    let mut features = Vec::new();
+    if cfg!(feature = "chaos") {
+        features.push("chaos".to_string());
+    }
+    if cfg!(feature = "swagger-ui") {
+        features.push("swagger-ui".to_string());
+    }
+    if cfg!(feature = "activitypub") {
+        features.push("activitypub".to_string());
+    }
+    if cfg!(feature = "redis") {
+        features.push("redis".to_string());
+    }
+    if cfg!(feature = "xml") {
+        features.push("xml".to_string());
+    }
+    if cfg!(feature = "msgpack") {
+        features.push("msgpack".to_string());
+    }
+    features
+}
+
+/// `url` with the password in its userinfo, if any, masked
+pub fn mask_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let authority_end = rest.find('/').unwrap_or(rest.len());
+    let (authority, path) = rest.split_at(authority_end);
+    match authority.rsplit_once('@') {
+        Some((userinfo, host)) => {
+            let user = userinfo.split_once(':').map_or(userinfo, |(user, _)| user);
+            format!("{}://{}:{}@{}{}", scheme, user, MASK, host, path)
+        }
+        None => url.to_string(),
+    }
+}
+
+/// Whether a secret is set, never its value
+fn secret(value: Option<&str>) -> String {
+    match value {
+        Some(value) if !value.is_empty() => MASK.to_string(),
+        _ => "unset".to_string(),
+    }
+}
+
+fn lowercase_debug(value: impl std::fmt::Debug) -> String {
+    format!("{:?}", value).to_lowercase()
+}
+
+/// Effective settings keyed by the environment variable that sets them
+pub fn config_summary(config: &AppConfig) -> BTreeMap<String, String> {
+    let mut summary = BTreeMap::new();
+    let mut set = |key: &str, value: String| {
+        summary.insert(key.to_string(), value);
+    };
+
+    set("APP_ENV", lowercase_debug(config.environment));
+    set("HOST", config.server.host.to_string());
+    set("PORT", config.server.port.to_string());
+    set("BASE_PATH", config.base_path.clone());
+    set(
+        "TLS",
+        if config.tls_enabled() {
+            "enabled"
+        } else {
+            "disabled"
+        }
+        .to_string(),
+    );
+    set(
+        "CORS_ALLOWED_ORIGINS",
+        config.cors.allowed_origins.join(","),
+    );
+    set("ERROR_FORMAT", lowercase_debug(config.error_format));
+    set(
+        "API_MESSAGES_DIR",
+        config.messages_dir.display().to_string(),
+    );
+    set(
+        "ACCESS_TOKEN_TTL_SECS",
+        config.tokens.access_ttl.as_secs().to_string(),
+    );
+    set(
+        "REFRESH_TOKEN_TTL_SECS",
+        config.tokens.refresh_ttl.as_secs().to_string(),
+    );
+    set(
+        "EMAIL_VERIFICATION_TTL_SECS",
+        config.tokens.email_verification_ttl.as_secs().to_string(),
+    );
+    set("COOKIE_SECURE", config.cookies.secure.to_string());
+    set(
+        "COOKIE_SAME_SITE",
+        config.cookies.same_site.to_string().to_lowercase(),
+    );
+    set(
+        "COOKIE_DOMAIN",
+        config
+            .cookies
+            .domain
+            .clone()
+            .unwrap_or_else(|| "none".to_string()),
+    );
+    set("COOKIE_PATH", config.cookies.path.clone());
+
+    set(
+        "DATABASE_URL",
+        env::var("DATABASE_URL")
+            .map(|url| mask_url(&url))
+            .unwrap_or_else(|_| "unset".to_string()),
+    );
+    set(
+        "DATABASE_READ_URL",
+        config
+            .database
+            .read_url
+            .as_deref()
+            .map(mask_url)
+            .unwrap_or_else(|| "unset".to_string()),
+    );
+    set(
+        "DB_MAX_CONNECTIONS",
+        config.database.max_connections.to_string(),
+    );
+    set(
+        "DB_ACQUIRE_TIMEOUT_SECS",
+        config.database.acquire_timeout.as_secs().to_string(),
+    );
+    set(
+        "DB_HEALTH_INTERVAL_SECS",
+        config.database.health_interval.as_secs().to_string(),
+    );
+    set(
+        "SLOW_QUERY_MS",
+        config.database.slow_query_threshold.as_millis().to_string(),
+    );
+
+    set(
+        "AUTH_SECRET",
+        secret(read_secret("AUTH_SECRET").ok().flatten().as_deref()),
+    );
+    set(
+        "PASSWORD_PEPPER",
+        secret(read_secret("PASSWORD_PEPPER").ok().flatten().as_deref()),
+    );
+    set(
+        "RESEND_API_KEY",
+        secret(env::var("RESEND_API_KEY").ok().as_deref()),
+    );
+    set(
+        "RESEND_WEBHOOK_SECRET",
+        secret(config.email.webhook_secret.as_deref()),
+    );
+    set("REQUIRE_API_KEY", config.access.require_api_key.to_string());
+    set(
+        "API_KEYS",
+        format!("{} key(s) {}", config.access.api_keys.len(), MASK),
+    );
+    set("DAILY_QUOTA", config.access.daily_quota.to_string());
+    set(
+        "ADMIN_DAILY_QUOTA",
+        config.access.admin_daily_quota.to_string(),
+    );
+
+    set("CACHE_BACKEND", lowercase_debug(config.cache.backend));
+    set("CACHE_TTL_SECS", config.cache.ttl.as_secs().to_string());
+    set(
+        "REDIS_URL",
+        config
+            .redis
+            .url
+            .as_deref()
+            .map(mask_url)
+            .unwrap_or_else(|| "unset".to_string()),
+    );
+
+    set(
+        "PUBLISH_HOOKS",
+        config
+            .publish_hooks
+            .hooks
+            .iter()
+            .map(|hook| lowercase_debug(hook.kind))
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    set(
+        "INDEXNOW_KEY",
+        secret(config.publish_hooks.indexnow_key.as_deref()),
+    );
+    set(
+        "PUBLIC_POST_URL",
+        config.publish_hooks.post_url_template.clone(),
+    );
+    set(
+        "CDN_PURGE_PROVIDER",
+        match &config.cdn.provider {
+            Some(CdnProvider::Cloudflare { .. }) => "cloudflare",
+            Some(CdnProvider::Fastly { .. }) => "fastly",
+            None => "none",
+        }
+        .to_string(),
+    );
+    set("CDN_PUBLIC_URL", config.cdn.public_url.clone());
+    set("SIGNUP_HONEYPOT", config.signup.honeypot.to_string());
+    set(
+        "SIGNUP_MIN_FILL_SECS",
+        config
+            .signup
+            .min_fill_time
+            .map_or(0, |time| time.as_secs())
+            .to_string(),
+    );
+    set(
+        "DISPOSABLE_DOMAINS_FILE",
+        config
+            .signup
+            .disposable_domains_file
+            .as_ref()
+            .map_or_else(|| "none".to_string(), |path| path.display().to_string()),
+    );
+    set(
+        "CAPTCHA_PROVIDER",
+        match &config.signup.captcha {
+            Some(CaptchaProvider::HCaptcha { .. }) => "hcaptcha",
+            Some(CaptchaProvider::Turnstile { .. }) => "turnstile",
+            None => "none",
+        }
+        .to_string(),
+    );
+    set(
+        "CAPTCHA_SECRET",
+        secret(config.signup.captcha.as_ref().map(|captcha| match captcha {
+            CaptchaProvider::HCaptcha { secret, .. }
+            | CaptchaProvider::Turnstile { secret, .. } => secret.as_str(),
+        })),
+    );
+
+    set(
+        "EMAIL_TEMPLATES_DIR",
+        config.email.templates_dir.display().to_string(),
+    );
+    set("EMAIL_DEFAULT_LOCALE", config.email.default_locale.clone());
+    set(
+        "VERIFY_EMAIL_REDIRECT_ORIGINS",
+        config.email.redirect_origins.join(","),
+    );
+    set(
+        "VERIFICATION_RESEND_MIN_AGE_SECS",
+        config.email.resend_min_age.as_secs().to_string(),
+    );
+    set(
+        "VERIFICATION_RESEND_BATCH_SIZE",
+        config.email.resend_batch_size.to_string(),
+    );
+    set(
+        "VERIFICATION_RESEND_BATCH_INTERVAL_SECS",
+        config.email.resend_batch_interval.as_secs().to_string(),
+    );
+    set(
+        "UNVERIFIED_CLEANUP",
+        match config.accounts.unverified_cleanup {
+            Some(mode) => lowercase_debug(mode),
+            None => "off".to_string(),
+        },
+    );
+    set(
+        "UNVERIFIED_MAX_AGE_DAYS",
+        (config.accounts.unverified_max_age.as_secs() / (24 * 60 * 60)).to_string(),
+    );
+    set(
+        "UNVERIFIED_CLEANUP_INTERVAL_SECS",
+        config
+            .accounts
+            .unverified_cleanup_interval
+            .as_secs()
+            .to_string(),
+    );
+    set(
+        "NEW_DEVICE_ALERTS",
+        config.accounts.new_device_alerts.to_string(),
+    );
+    set(
+        "GEO_COUNTRY_HEADER",
+        config
+            .accounts
+            .country_header
+            .clone()
+            .unwrap_or_else(|| "none".to_string()),
+    );
+    set(
+        "DEFAULT_LICENSE",
+        config
+            .default_license
+            .clone()
+            .unwrap_or_else(|| "none".to_string()),
+    );
+    set("REQUIRE_ALT_TEXT", config.require_alt_text.to_string());
+    set(
+        "PREVIEW_LINK_TTL_SECS",
+        config.preview_link_ttl.as_secs().to_string(),
+    );
+    set(
+        "SHARE_LINK_TTL_SECS",
+        config.share_link_ttl.as_secs().to_string(),
+    );
+    set(
+        "IMPERSONATION_TTL_SECS",
+        config.impersonation_ttl.as_secs().to_string(),
+    );
+    set(
+        "SLOW_REQUEST_MS",
+        config.slow_request_threshold.as_millis().to_string(),
+    );
+    set("MAINTENANCE_MODE", config.maintenance.forced.to_string());
+    set(
+        "MAINTENANCE_RETRY_AFTER_SECS",
+        config.maintenance.retry_after.as_secs().to_string(),
+    );
+    set(
+        "FEATURE_FLAG_REFRESH_SECS",
+        config.feature_flag_refresh.as_secs().to_string(),
+    );
+    set(
+        "MAX_TITLE_LENGTH",
+        config.limits.max_title_length.to_string(),
+    );
+    set(
+        "MAX_CONTENT_LENGTH",
+        config.limits.max_content_length.to_string(),
+    );
+    set(
+        "FEDERATION_PUBLIC_URL",
+        config.federation.public_url.clone(),
+    );
+    set(
+        "FEDERATION_PRIVATE_KEY",
+        secret(
+            read_secret("FEDERATION_PRIVATE_KEY")
+                .ok()
+                .flatten()
+                .as_deref(),
+        ),
+    );
+
+    summary
+}
+
+fn check(name: &str, started: Instant, result: Result<(), String>) -> DependencyCheck {
+    let latency_ms = Some(started.elapsed().as_millis() as u64);
+    match result {
+        Ok(()) => DependencyCheck {
+            name: name.to_string(),
+            status: DependencyStatus::UP,
+            latency_ms,
+            detail: None,
+        },
+        Err(detail) => DependencyCheck {
+            name: name.to_string(),
+            status: DependencyStatus::DOWN,
+            latency_ms,
+            detail: Some(detail),
+        },
+    }
+}
+
+fn disabled(name: &str, detail: &str) -> DependencyCheck {
+    DependencyCheck {
+        name: name.to_string(),
+        status: DependencyStatus::DISABLED,
+        latency_ms: None,
+        detail: Some(detail.to_string()),
+    }
+}
+
+/// Any HTTP answer counts as reachable; only connection failures and
+/// timeouts count as down
+async fn check_http(client: &Client, name: &str, url: &str) -> DependencyCheck {
+    let started = Instant::now();
+    let result = client
+        .head(url)
+        .timeout(CHECK_TIMEOUT)
+        .send()
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+    check(name, started, result)
+}
+
+/// Checks Postgres, Redis, the mail provider and the CDN API
+pub async fn check_dependencies(state: &AppState) -> Vec<DependencyCheck> {
+    let mut checks = Vec::new();
+
+    let started = Instant::now();
+    let database = match probe(&state.pool).await {
+        DbStatus::UP => Ok(()),
+        DbStatus::SATURATED => Err("every pooled connection is in use".to_string()),
+        DbStatus::DOWN => Err("unreachable".to_string()),
+    };
+    checks.push(check("postgres", started, database));
+
+    #[cfg(feature = "redis")]
+    match &state.redis {
+        Some(client) => {
+            let started = Instant::now();
+            let result = tokio::time::timeout(CHECK_TIMEOUT, client.ping())
+                .await
+                .map_err(|_| "timed out".to_string())
+                .and_then(|result| result.map_err(|e| e.to_string()));
+            checks.push(check("redis", started, result));
+        }
+        None => checks.push(disabled("redis", "REDIS_URL is not set")),
+    }
+    #[cfg(not(feature = "redis"))]
+    checks.push(disabled("redis", "built without the redis feature"));
+
+    let client = Client::new();
+    match state.mailer.name() {
+        "resend" => checks.push(check_http(&client, "resend", "https://api.resend.com").await),
+        other => checks.push(disabled(other, "emails are not delivered")),
+    }
+
+    match &state.config.cdn.provider {
+        Some(CdnProvider::Cloudflare { .. }) => checks.push(
+            check_http(
+                &client,
+                "cloudflare",
+                "https://api.cloudflare.com/client/v4",
+            )
+            .await,
+        ),
+        Some(CdnProvider::Fastly { .. }) => {
+            checks.push(check_http(&client, "fastly", "https://api.fastly.com").await)
+        }
+        None => checks.push(disabled("cdn", "CDN_PURGE_PROVIDER is not set")),
+    }
+
+    checks
+}
@@ -0,0 +1,240 @@
+//! Purging CDN copies of public content when posts change.
+//!
+//! When a post is published, updated or deleted, its URLs and every listing
+//! that may show it are purged through the configured [`CachePurger`], so the
+//! CDN stops serving the old version. Purges run in the background and
+//! failures are only logged; the cached copies then expire on their own.
+
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use anyhow::{Result, bail};
+use reqwest::{Client, header::CONTENT_TYPE};
+use serde_json::json;
+use tracing::{error, info};
+
+use crate::config::{AppConfig, CdnProvider};
+use crate::helpers::publish_hooks::post_url;
+
+pub type PurgeFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Timeout for a single purge request
+const PURGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CLOUDFLARE_API: &str = "https://api.cloudflare.com/client/v4";
+
+/// Cloudflare accepts at most this many URLs per purge request
+const CLOUDFLARE_BATCH: usize = 30;
+
+const FASTLY_API: &str = "https://api.fastly.com";
+
+pub trait CachePurger: Send + Sync {
+    /// `cloudflare` or `fastly`
+    fn name(&self) -> &'static str;
+    /// Drops the cached copies of every URL in `urls`
+    fn purge<'a>(&'a self, urls: &'a [String]) -> PurgeFuture<'a>;
+}
+
+/// Purges by URL through the zone's `purge_cache` endpoint
+pub struct CloudflarePurger {
+    client: Client,
+    zone_id: String,
+    api_token: String,
+}
+
+impl CloudflarePurger {
+    pub fn new(client: Client, zone_id: String, api_token: String) -> Self {
+        Self {
+            client,
+            zone_id,
+            api_token,
+        }
+    }
+}
+
+impl CachePurger for CloudflarePurger {
+    fn name(&self) -> &'static str {
+        "cloudflare"
+    }
+
+    fn purge<'a>(&'a self, urls: &'a [String]) -> PurgeFuture<'a> {
+        Box::pin(async move {
+            let endpoint = format!("{}/zones/{}/purge_cache", CLOUDFLARE_API, self.zone_id);
+            for batch in urls.chunks(CLOUDFLARE_BATCH) {
+                let response = self
+                    .client
+                    .post(&endpoint)
+                    .bearer_auth(&self.api_token)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(json!({ "files": batch }).to_string())
+                    .timeout(PURGE_TIMEOUT)
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    bail!("Cloudflare answered {}", response.status());
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Purges one URL at a time through the Fastly API
+pub struct FastlyPurger {
+    client: Client,
+    api_token: String,
+}
+
+impl FastlyPurger {
+    pub fn new(client: Client, api_token: String) -> Self {
+        Self { client, api_token }
+    }
+}
+
+impl CachePurger for FastlyPurger {
+    fn name(&self) -> &'static str {
+        "fastly"
+    }
+
+    fn purge<'a>(&'a self, urls: &'a [String]) -> PurgeFuture<'a> {
+        Box::pin(async move {
+            for url in urls {
+                // Fastly takes the cached URL without its scheme
+                let target = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+                let response = self
+                    .client
+                    .post(format!("{}/purge/{}", FASTLY_API, target))
+                    .header("Fastly-Key", &self.api_token)
+                    .timeout(PURGE_TIMEOUT)
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    bail!("Fastly answered {} for {}", response.status(), url);
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Works out which URLs a post change makes stale and purges them
+#[derive(Clone)]
+pub struct EdgeCache {
+    purger: Option<Arc<dyn CachePurger>>,
+    /// Public origin plus base path of the API
+    origin: String,
+    /// Where post routes live under the origin, one per served API version
+    api_roots: Vec<String>,
+    post_url_template: String,
+    listing_paths: Vec<String>,
+}
+
+impl EdgeCache {
+    /// Purges nothing
+    pub fn disabled() -> Self {
+        Self {
+            purger: None,
+            origin: String::new(),
+            api_roots: Vec::new(),
+            post_url_template: String::new(),
+            listing_paths: Vec::new(),
+        }
+    }
+
+    pub fn new(
+        purger: Arc<dyn CachePurger>,
+        origin: String,
+        post_url_template: String,
+        listing_paths: Vec<String>,
+    ) -> Self {
+        Self {
+            purger: Some(purger),
+            origin,
+            api_roots: vec![String::new()],
+            post_url_template,
+            listing_paths,
+        }
+    }
+
+    pub fn from_config(config: &AppConfig) -> Self {
+        let client = Client::new();
+        let purger: Arc<dyn CachePurger> = match config.cdn.provider.clone() {
+            Some(CdnProvider::Cloudflare { zone_id, api_token }) => {
+                Arc::new(CloudflarePurger::new(client, zone_id, api_token))
+            }
+            Some(CdnProvider::Fastly { api_token }) => {
+                Arc::new(FastlyPurger::new(client, api_token))
+            }
+            None => return Self::disabled(),
+        };
+        info!("Purging the {} edge cache on post changes", purger.name());
+
+        Self::new(
+            purger,
+            format!("{}{}", config.cdn.public_url, config.base_path),
+            config.publish_hooks.post_url_template.clone(),
+            config.cdn.listing_paths.clone(),
+        )
+        .with_api_roots(config.api.roots())
+    }
+
+    /// Post routes served under each of `roots`, e.g. `/api/v1`, rather than
+    /// directly under the origin
+    pub fn with_api_roots(mut self, roots: Vec<String>) -> Self {
+        self.api_roots = roots;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.purger.is_some()
+    }
+
+    /// The post's API URL and public page, then every listing
+    pub fn post_urls(&self, public_id: &str) -> Vec<String> {
+        let mut urls: Vec<String> = self
+            .api_roots
+            .iter()
+            .map(|root| format!("{}{}/posts/{}", self.origin, root, public_id))
+            .collect();
+        let page = post_url(&self.post_url_template, public_id);
+        if !page.is_empty() && !urls.contains(&page) {
+            urls.push(page);
+        }
+        for path in &self.listing_paths {
+            let url = if path.starts_with('/') {
+                format!("{}{}", self.origin, path)
+            } else {
+                format!("{}/{}", self.origin, path)
+            };
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        urls
+    }
+
+    /// Purges the post's URLs in the background
+    pub fn purge_post(&self, public_id: &str) {
+        let Some(purger) = self.purger.clone() else {
+            return;
+        };
+        let urls = self.post_urls(public_id);
+        let public_id = public_id.to_string();
+
+        tokio::spawn(async move {
+            match purger.purge(&urls).await {
+                Ok(()) => info!(
+                    "Purged {} URL(s) for post {} from {}",
+                    urls.len(),
+                    public_id,
+                    purger.name()
+                ),
+                Err(e) => error!(
+                    "Failed to purge post {} from {}: {:#}",
+                    public_id,
+                    purger.name(),
+                    e
+                ),
+            }
+        });
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use sqlx::PgPool;
+
+use crate::config::AccountConfig;
+use crate::db::repositories::email_domain_repo::EmailDomainRepository;
+use crate::model::model::{DomainRuleKind, EmailDomainRule};
+
+/// Allow/deny rules applied to emails at registration and on email change.
+/// Deny rules win; when any allow rule exists, unlisted domains are rejected.
+#[derive(Debug, Clone, Default)]
+pub struct EmailDomainPolicy {
+    rules: Vec<EmailDomainRule>,
+}
+
+impl EmailDomainPolicy {
+    pub fn new(rules: Vec<EmailDomainRule>) -> Self {
+        Self { rules }
+    }
+
+    /// Rules from the environment followed by those added at runtime
+    pub async fn load(pool: &PgPool, accounts: &AccountConfig) -> Result<Self> {
+        let mut rules = config_rules(accounts);
+        rules.extend(
+            EmailDomainRepository::new(pool.clone())
+                .list_rules()
+                .await?,
+        );
+        Ok(Self::new(rules))
+    }
+
+    pub fn rules(&self) -> &[EmailDomainRule] {
+        &self.rules
+    }
+
+    pub fn check(&self, email: &str) -> Result<(), String> {
+        let domain = email_domain(email).ok_or("Please provide a valid email address")?;
+
+        let matching = |kind: DomainRuleKind| {
+            self.rules
+                .iter()
+                .filter(move |rule| rule.kind == kind)
+                .any(|rule| domain_matches(&domain, &rule.domain))
+        };
+
+        if matching(DomainRuleKind::DENY) {
+            return Err(format!("Email addresses at {} are not accepted", domain));
+        }
+
+        let has_allow_list = self
+            .rules
+            .iter()
+            .any(|rule| rule.kind == DomainRuleKind::ALLOW);
+        if has_allow_list && !matching(DomainRuleKind::ALLOW) {
+            return Err(format!(
+                "Registration is restricted; {} is not an allowed email domain",
+                domain
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn config_rules(accounts: &AccountConfig) -> Vec<EmailDomainRule> {
+    let rules = |domains: &[String], kind: DomainRuleKind| {
+        domains
+            .iter()
+            .filter_map(|domain| normalize_domain(domain))
+            .map(|domain| EmailDomainRule {
+                domain,
+                kind,
+                from_config: true,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut all = rules(&accounts.allowed_email_domains, DomainRuleKind::ALLOW);
+    all.extend(rules(&accounts.denied_email_domains, DomainRuleKind::DENY));
+    all
+}
+
+/// Lowercases and strips a leading `@`; `None` if it doesn't look like a domain
+pub fn normalize_domain(raw: &str) -> Option<String> {
+    let domain = raw.trim().trim_start_matches('@').to_ascii_lowercase();
+    let valid = domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    valid.then_some(domain)
+}
+
+pub fn email_domain(email: &str) -> Option<String> {
+    let (_, domain) = email.trim().rsplit_once('@')?;
+    normalize_domain(domain)
+}
+
+/// `mail.company.com` matches a `company.com` rule; `notcompany.com` does not
+fn domain_matches(domain: &str, rule: &str) -> bool {
+    domain == rule
+        || domain
+            .strip_suffix(rule)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
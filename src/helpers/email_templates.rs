@@ -0,0 +1,310 @@
+//! Transactional emails rendered from templates on disk.
+//!
+//! Every email has an HTML and a plain-text template in the templates
+//! directory; both extend a shared layout. The wording lives in per-locale
+//! catalogs under `locales/`, e.g. `locales/es.json`, so translating an email
+//! never means touching its markup. Strings missing from a catalog fall back
+//! to the default locale's. The pages that email links land on live under
+//! `pages/` and use the same catalogs.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context as _, Result, bail};
+use tera::{Context, Tera};
+use tracing::info;
+
+type Strings = HashMap<String, String>;
+
+/// Section of a catalog shared by every email, e.g. the sign-off
+const COMMON_SECTION: &str = "common";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailKind {
+    VerifyEmail,
+    ResetPassword,
+    NewSignIn,
+}
+
+impl EmailKind {
+    pub const ALL: [EmailKind; 3] = [
+        EmailKind::VerifyEmail,
+        EmailKind::ResetPassword,
+        EmailKind::NewSignIn,
+    ];
+
+    /// Template file stem and catalog section of this email
+    pub fn name(self) -> &'static str {
+        match self {
+            EmailKind::VerifyEmail => "verify_email",
+            EmailKind::ResetPassword => "reset_password",
+            EmailKind::NewSignIn => "new_sign_in",
+        }
+    }
+}
+
+/// A page shown in the browser after following a link from an email
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LandingPage {
+    EmailVerified,
+    VerificationFailed,
+    ConfirmRevoke,
+    SessionRevoked,
+    RevokeFailed,
+}
+
+impl LandingPage {
+    pub const ALL: [LandingPage; 5] = [
+        LandingPage::EmailVerified,
+        LandingPage::VerificationFailed,
+        LandingPage::ConfirmRevoke,
+        LandingPage::SessionRevoked,
+        LandingPage::RevokeFailed,
+    ];
+
+    /// Template file stem under `pages/` and catalog section of this page
+    pub fn name(self) -> &'static str {
+        match self {
+            LandingPage::EmailVerified => "email_verified",
+            LandingPage::VerificationFailed => "verification_failed",
+            LandingPage::ConfirmRevoke => "confirm_revoke",
+            LandingPage::SessionRevoked => "session_revoked",
+            LandingPage::RevokeFailed => "revoke_failed",
+        }
+    }
+}
+
+/// A rendered email ready to hand to the mail provider
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub locale: String,
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+#[derive(Default)]
+pub struct EmailTemplates {
+    tera: Tera,
+    /// Locale -> catalog section -> string
+    catalogs: HashMap<String, HashMap<String, Strings>>,
+    default_locale: String,
+}
+
+impl EmailTemplates {
+    /// Loads the templates and locale catalogs under `dir`, checking that every
+    /// email can be rendered in `default_locale`
+    pub fn load(dir: &Path, default_locale: &str) -> Result<Self> {
+        let glob = format!("{}/**/*.{{html,txt}}", dir.display());
+        let tera = Tera::new(&glob)
+            .with_context(|| format!("unable to parse email templates in {}", dir.display()))?;
+
+        let mut catalogs = HashMap::new();
+        let locales_dir = dir.join("locales");
+        let entries = fs::read_dir(&locales_dir)
+            .with_context(|| format!("unable to read {}", locales_dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let catalog = fs::read_to_string(&path)
+                .with_context(|| format!("unable to read {}", path.display()))?;
+            let catalog = serde_json::from_str(&catalog)
+                .with_context(|| format!("invalid locale catalog {}", path.display()))?;
+            catalogs.insert(locale.to_lowercase(), catalog);
+        }
+
+        let templates = Self {
+            tera,
+            catalogs,
+            default_locale: default_locale.to_lowercase(),
+        };
+        templates.check()?;
+
+        info!(
+            "Loaded email templates from {} in {} locale(s)",
+            dir.display(),
+            templates.catalogs.len()
+        );
+        Ok(templates)
+    }
+
+    fn check(&self) -> Result<()> {
+        let Some(catalog) = self.catalogs.get(&self.default_locale) else {
+            bail!("no catalog for the default locale {}", self.default_locale);
+        };
+
+        for kind in EmailKind::ALL {
+            for suffix in ["html", "txt"] {
+                let name = format!("{}.{}", kind.name(), suffix);
+                self.tera
+                    .get_template(&name)
+                    .with_context(|| format!("missing email template {}", name))?;
+            }
+            if !catalog
+                .get(kind.name())
+                .is_some_and(|strings| strings.contains_key("subject"))
+            {
+                bail!(
+                    "the {} catalog has no subject for {}",
+                    self.default_locale,
+                    kind.name()
+                );
+            }
+        }
+
+        for page in LandingPage::ALL {
+            let name = format!("pages/{}.html", page.name());
+            self.tera
+                .get_template(&name)
+                .with_context(|| format!("missing page template {}", name))?;
+            if !catalog
+                .get(page.name())
+                .is_some_and(|strings| strings.contains_key("title"))
+            {
+                bail!(
+                    "the {} catalog has no title for {}",
+                    self.default_locale,
+                    page.name()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn default_locale(&self) -> &str {
+        &self.default_locale
+    }
+
+    /// Locales with a catalog, sorted
+    pub fn locales(&self) -> Vec<&str> {
+        let mut locales: Vec<&str> = self.catalogs.keys().map(String::as_str).collect();
+        locales.sort_unstable();
+        locales
+    }
+
+    /// The catalog to use for `requested`: an exact match, then its language
+    /// (`pt` for `pt-BR`), then the default locale
+    pub fn resolve_locale(&self, requested: Option<&str>) -> &str {
+        requested
+            .and_then(|tag| self.lookup(tag))
+            .unwrap_or(&self.default_locale)
+    }
+
+    /// Whether `locale` or its language has a catalog
+    pub fn supports(&self, locale: &str) -> bool {
+        self.lookup(locale).is_some()
+    }
+
+    /// Trims and lowercases a locale chosen by a user; returns the message to
+    /// show when there is no catalog for it
+    pub fn check_locale(&self, locale: &str) -> Result<String, String> {
+        if self.supports(locale) {
+            Ok(locale.trim().to_lowercase())
+        } else {
+            Err(format!(
+                "Unsupported locale; choose one of {}",
+                self.locales().join(", ")
+            ))
+        }
+    }
+
+    fn lookup(&self, requested: &str) -> Option<&str> {
+        let requested = requested.trim().to_lowercase();
+        let language = requested.split(['-', '_']).next().unwrap_or_default();
+
+        [requested.as_str(), language]
+            .into_iter()
+            .find_map(|tag| self.catalogs.get_key_value(tag))
+            .map(|(locale, _)| locale.as_str())
+    }
+
+    /// Renders `kind` in the best catalog for `locale`. `vars` are available to
+    /// the templates and substituted for `{name}` placeholders in the catalog.
+    pub fn render(
+        &self,
+        kind: EmailKind,
+        locale: Option<&str>,
+        vars: &[(&str, &str)],
+    ) -> Result<RenderedEmail> {
+        let locale = self.resolve_locale(locale);
+        let strings = self.strings(locale, kind.name(), vars);
+        let context = Self::context(locale, &strings, vars);
+
+        let html = self
+            .tera
+            .render(&format!("{}.html", kind.name()), &context)?;
+        let text = self
+            .tera
+            .render(&format!("{}.txt", kind.name()), &context)?;
+
+        Ok(RenderedEmail {
+            locale: locale.to_string(),
+            subject: strings.get("subject").cloned().unwrap_or_default(),
+            html,
+            text,
+        })
+    }
+
+    /// Renders `page` as HTML in the best catalog for `locale`
+    pub fn render_page(
+        &self,
+        page: LandingPage,
+        locale: Option<&str>,
+        vars: &[(&str, &str)],
+    ) -> Result<String> {
+        let locale = self.resolve_locale(locale);
+        let strings = self.strings(locale, page.name(), vars);
+        let context = Self::context(locale, &strings, vars);
+
+        Ok(self
+            .tera
+            .render(&format!("pages/{}.html", page.name()), &context)?)
+    }
+
+    fn context(locale: &str, strings: &Strings, vars: &[(&str, &str)]) -> Context {
+        let mut context = Context::new();
+        context.insert("lang", locale);
+        for (name, value) in vars {
+            context.insert(*name, value);
+        }
+        context.insert("t", strings);
+        context
+    }
+
+    /// Common and `section` strings of `locale` over the default locale's
+    fn strings(&self, locale: &str, section: &str, vars: &[(&str, &str)]) -> Strings {
+        let mut strings = Strings::new();
+        for catalog in [self.default_locale.as_str(), locale]
+            .into_iter()
+            .filter_map(|locale| self.catalogs.get(locale))
+        {
+            for section in [COMMON_SECTION, section] {
+                if let Some(section) = catalog.get(section) {
+                    strings.extend(section.clone());
+                }
+            }
+        }
+
+        for value in strings.values_mut() {
+            for (name, var) in vars {
+                *value = value.replace(&format!("{{{}}}", name), var);
+            }
+        }
+        strings
+    }
+}
+
+/// The first language of an `Accept-Language` header; clients list them by preference
+pub fn preferred_locale(accept_language: &str) -> Option<String> {
+    accept_language
+        .split(',')
+        .map(|entry| entry.split(';').next().unwrap_or_default().trim())
+        .find(|tag| !tag.is_empty() && *tag != "*")
+        .map(str::to_string)
+}
@@ -0,0 +1,52 @@
+//! Feature flags switched by admins at runtime.
+//!
+//! The flags live in the `feature_flags` table; each instance keeps a copy
+//! that the admin API updates right away and a background job reloads, so
+//! changes made on another replica show up within the refresh interval.
+
+use std::sync::RwLock;
+
+use axum::http::StatusCode;
+
+use crate::model::model::{ErrorResponse, FeatureFlag, FeatureFlagSet};
+
+#[derive(Debug, Default)]
+pub struct FeatureFlags {
+    flags: RwLock<FeatureFlagSet>,
+}
+
+impl FeatureFlags {
+    pub fn new(flags: FeatureFlagSet) -> Self {
+        Self {
+            flags: RwLock::new(flags),
+        }
+    }
+
+    pub fn current(&self) -> FeatureFlagSet {
+        *self.flags.read().unwrap()
+    }
+
+    pub fn is_enabled(&self, flag: FeatureFlag) -> bool {
+        self.current().is_enabled(flag)
+    }
+
+    /// Stores freshly loaded flags; returns whether anything changed
+    pub fn replace(&self, flags: FeatureFlagSet) -> bool {
+        let mut current = self.flags.write().unwrap();
+        let changed = *current != flags;
+        *current = flags;
+        changed
+    }
+
+    /// `503 Service Unavailable` naming the feature when `flag` is off
+    pub fn require(&self, flag: FeatureFlag) -> Result<(), Box<ErrorResponse>> {
+        if self.is_enabled(flag) {
+            return Ok(());
+        }
+        Err(Box::new(ErrorResponse::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Feature Disabled",
+            format!("{} is currently disabled", flag.describe()),
+        )))
+    }
+}
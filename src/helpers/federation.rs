@@ -0,0 +1,502 @@
+//! ActivityPub documents for authors and their posts.
+//!
+//! Authors are exposed as `Person` actors named by their public id, with an
+//! outbox of `Create` activities wrapping their posts. Remote servers find
+//! actors through WebFinger and follow them through their inbox, which only
+//! takes deliveries signed by the sending actor's key. Follows are accepted,
+//! and new and edited posts are pushed to followers' inboxes through the
+//! publish hook queue, signed with the instance key.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, ensure};
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, header::ACCEPT};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::FederationConfig;
+use crate::db::repositories::{
+    follower_repo::FollowerRepository, hook_repo::HookRepository, user_repo::UserRepository,
+};
+use crate::helpers::avatar::avatar_url;
+use crate::helpers::http_signatures::{self, SignatureHeader};
+use crate::helpers::ids::{IdGenerator, TimeOrderedIds};
+use crate::helpers::markdown::render_markdown;
+use crate::helpers::publish_hooks::PublishEvent;
+use crate::model::model::{DeliveryStatus, HookDelivery, Post, User};
+
+pub const ACTIVITY_JSON: &str = "application/activity+json";
+pub const JRD_JSON: &str = "application/jrd+json";
+const ACTIVITY_STREAMS: &str = "https://www.w3.org/ns/activitystreams";
+/// Defines `publicKey` on actors
+const SECURITY: &str = "https://w3id.org/security/v1";
+const PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// Timeout for fetching a remote actor
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts listed in an outbox
+pub const OUTBOX_LIMIT: i64 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebFingerLink {
+    pub rel: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub href: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebFinger {
+    pub subject: String,
+    pub links: Vec<WebFingerLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Image {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub url: String,
+}
+
+/// The key remote servers check an actor's deliveries against
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKey {
+    pub id: String,
+    pub owner: String,
+    pub public_key_pem: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Actor {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub preferred_username: String,
+    pub name: String,
+    pub inbox: String,
+    pub outbox: String,
+    pub followers: String,
+    pub icon: Image,
+    pub published: DateTime<Utc>,
+    pub public_key: PublicKey,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub attributed_to: String,
+    pub name: String,
+    /// Rendered, sanitized HTML
+    pub content: String,
+    pub to: Vec<String>,
+    pub published: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Activity {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub actor: String,
+    pub to: Vec<String>,
+    pub published: DateTime<Utc>,
+    pub object: Note,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderedCollection<T> {
+    #[serde(rename = "@context")]
+    pub context: String,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub total_items: i64,
+    pub ordered_items: Vec<T>,
+}
+
+/// The parts of an incoming activity the inbox acts on
+#[derive(Debug, Clone, PartialEq)]
+pub enum InboxActivity {
+    Follow { follower: String },
+    Unfollow { follower: String },
+    Ignored,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoints {
+    pub shared_inbox: Option<String>,
+}
+
+/// The parts of a remote actor's document deliveries need
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteActor {
+    pub id: String,
+    pub inbox: String,
+    #[serde(default)]
+    pub endpoints: Endpoints,
+    pub public_key: PublicKey,
+}
+
+impl RemoteActor {
+    /// Where activities for all our authors go; shared inboxes take one copy per server
+    pub fn delivery_inbox(&self) -> &str {
+        self.endpoints
+            .shared_inbox
+            .as_deref()
+            .unwrap_or(&self.inbox)
+    }
+}
+
+pub type ActorFuture<'a> = Pin<Box<dyn Future<Output = Result<RemoteActor>> + Send + 'a>>;
+
+pub trait ActorFetcher: Send + Sync {
+    /// Loads the actor document at `url`
+    fn fetch<'a>(&'a self, url: &'a str) -> ActorFuture<'a>;
+}
+
+/// Fetches actor documents over HTTP
+pub struct HttpActorFetcher {
+    client: Client,
+}
+
+impl HttpActorFetcher {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl ActorFetcher for HttpActorFetcher {
+    fn fetch<'a>(&'a self, url: &'a str) -> ActorFuture<'a> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get(url)
+                .header(ACCEPT, ACTIVITY_JSON)
+                .timeout(FETCH_TIMEOUT)
+                .send()
+                .await?
+                .error_for_status()?;
+            let actor: RemoteActor = response
+                .json()
+                .await
+                .with_context(|| format!("{} is not an actor", url))?;
+            Ok(actor)
+        })
+    }
+}
+
+/// Checks an inbox delivery was signed by the key of `actor`, the activity's
+/// sender, and returns that actor
+pub async fn verify_delivery(
+    actors: &dyn ActorFetcher,
+    actor: &str,
+    request_target: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> Result<RemoteActor> {
+    let signature = headers
+        .get("signature")
+        .and_then(|value| value.to_str().ok())
+        .and_then(SignatureHeader::parse)
+        .ok_or_else(|| anyhow!("missing or malformed signature header"))?;
+
+    let remote = actors.fetch(actor).await?;
+    ensure!(
+        remote.id == actor && remote.public_key.owner == actor,
+        "{} does not own its published key",
+        actor
+    );
+    ensure!(
+        remote.public_key.id == signature.key_id,
+        "signed with {}, not {}'s key",
+        signature.key_id,
+        actor
+    );
+
+    let public_key = http_signatures::parse_public_key(&remote.public_key.public_key_pem)?;
+    http_signatures::verify(
+        &signature,
+        &public_key,
+        "POST",
+        request_target,
+        headers,
+        body,
+        now,
+    )?;
+    Ok(remote)
+}
+
+/// `activity` as a standalone document, for delivery
+pub fn with_context<T: Serialize>(activity: &T) -> Value {
+    let mut document = serde_json::to_value(activity).unwrap_or_default();
+    if let Value::Object(fields) = &mut document {
+        fields.insert("@context".to_string(), json!(ACTIVITY_STREAMS));
+    }
+    document
+}
+
+/// A signed POST of `activity` to `inbox`, queued as `id`
+pub fn delivery(
+    id: Uuid,
+    event: &str,
+    post_id: Option<&str>,
+    inbox: &str,
+    key_id: &str,
+    activity: &Value,
+    now: DateTime<Utc>,
+) -> HookDelivery {
+    HookDelivery {
+        id,
+        event: event.to_string(),
+        post_id: post_id.map(str::to_string),
+        method: "POST".to_string(),
+        url: inbox.to_string(),
+        signed_by: Some(key_id.to_string()),
+        body: Some(activity.to_string()),
+        status: DeliveryStatus::PENDING,
+        attempts: 0,
+        last_status: None,
+        last_error: None,
+        next_attempt_at: now,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Queues the post's `Create` or `Update` for every follower inbox of its
+/// author; posts left out of the outbox are not sent
+pub async fn enqueue_post_activities(
+    pool: &PgPool,
+    config: &FederationConfig,
+    event: PublishEvent,
+    post: &Post,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    if post.org_id.is_some() || !post.visibility.is_listed() || post.archived_at.is_some() {
+        return Ok(0);
+    }
+
+    let inboxes = FollowerRepository::new(pool.clone())
+        .inboxes(post.author_id)
+        .await?;
+    if inboxes.is_empty() {
+        return Ok(0);
+    }
+    let author = UserRepository::new(pool.clone())
+        .find_by_id(post.author_id)
+        .await?
+        .ok_or_else(|| anyhow!("author of post {} not found", post.public_id))?;
+
+    let federation = Federation::new(config);
+    let (kind, activity) = match event {
+        PublishEvent::Published => ("activity.create", federation.create(&author, post)),
+        PublishEvent::Updated => ("activity.update", federation.update(&author, post)),
+    };
+    let activity = with_context(&activity);
+    let key_id = federation.key_id(&author.public_id);
+
+    let repo = HookRepository::new(pool.clone());
+    let ids = TimeOrderedIds;
+    for inbox in &inboxes {
+        repo.enqueue(&delivery(
+            ids.generate(),
+            kind,
+            Some(&post.public_id),
+            inbox,
+            &key_id,
+            &activity,
+            now,
+        ))
+        .await?;
+    }
+
+    Ok(inboxes.len())
+}
+
+pub struct Federation<'a> {
+    config: &'a FederationConfig,
+}
+
+impl<'a> Federation<'a> {
+    pub fn new(config: &'a FederationConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn actor_url(&self, name: &str) -> String {
+        format!("{}/users/{}", self.config.public_url, name)
+    }
+
+    /// Actor name from `acct:name@domain` or the actor's URL
+    pub fn resolve_resource<'r>(&self, resource: &'r str) -> Option<&'r str> {
+        if let Some(account) = resource.strip_prefix("acct:") {
+            let (name, domain) = account.rsplit_once('@')?;
+            return (domain.eq_ignore_ascii_case(self.config.domain()) && !name.is_empty())
+                .then_some(name);
+        }
+
+        let prefix = format!("{}/users/", self.config.public_url);
+        resource
+            .strip_prefix(prefix.as_str())
+            .filter(|name| !name.is_empty() && !name.contains('/'))
+    }
+
+    pub fn webfinger(&self, name: &str) -> WebFinger {
+        WebFinger {
+            subject: format!("acct:{}@{}", name, self.config.domain()),
+            links: vec![WebFingerLink {
+                rel: "self".to_string(),
+                kind: ACTIVITY_JSON.to_string(),
+                href: self.actor_url(name),
+            }],
+        }
+    }
+
+    /// Id of the key `name` signs with; every actor shares the instance key
+    pub fn key_id(&self, name: &str) -> String {
+        format!("{}#main-key", self.actor_url(name))
+    }
+
+    pub fn actor(&self, user: &User, public_key_pem: &str) -> Actor {
+        let id = self.actor_url(&user.public_id);
+        Actor {
+            context: vec![ACTIVITY_STREAMS.to_string(), SECURITY.to_string()],
+            kind: "Person".to_string(),
+            preferred_username: user.public_id.clone(),
+            name: user.name.clone(),
+            inbox: format!("{}/inbox", id),
+            outbox: format!("{}/outbox", id),
+            followers: format!("{}/followers", id),
+            icon: Image {
+                kind: "Image".to_string(),
+                url: avatar_url(user.avatar_url.as_deref(), &user.email),
+            },
+            published: user.created_at,
+            public_key: PublicKey {
+                id: self.key_id(&user.public_id),
+                owner: id.clone(),
+                public_key_pem: public_key_pem.to_string(),
+            },
+            id,
+        }
+    }
+
+    pub fn create(&self, author: &User, post: &Post) -> Activity {
+        let actor = self.actor_url(&author.public_id);
+        let note_id = format!("{}/posts/{}", self.config.public_url, post.public_id);
+        Activity {
+            id: format!("{}/activity", note_id),
+            kind: "Create".to_string(),
+            actor: actor.clone(),
+            to: vec![PUBLIC.to_string()],
+            published: post.created_at,
+            object: Note {
+                id: note_id,
+                kind: "Note".to_string(),
+                attributed_to: actor,
+                name: post.title.clone(),
+                content: render_markdown(&post.content),
+                to: vec![PUBLIC.to_string()],
+                published: post.created_at,
+                updated: post.updated_at,
+            },
+        }
+    }
+
+    /// The post's `Create` again as an `Update`, for followers who already have it
+    pub fn update(&self, author: &User, post: &Post) -> Activity {
+        let mut activity = self.create(author, post);
+        activity.id = format!(
+            "{}/updates/{}",
+            activity.object.id,
+            post.updated_at.timestamp()
+        );
+        activity.kind = "Update".to_string();
+        activity.published = post.updated_at;
+        activity
+    }
+
+    /// Answer to `follow`, an activity the inbox recognised as a follow of `name`
+    pub fn accept(&self, id: Uuid, name: &str, follow: &Value) -> Value {
+        let actor = self.actor_url(name);
+        json!({
+            "@context": ACTIVITY_STREAMS,
+            "id": format!("{}#accepts/{}", actor, id),
+            "type": "Accept",
+            "actor": actor,
+            "object": follow,
+        })
+    }
+
+    pub fn outbox(&self, author: &User, posts: &[Post], total: i64) -> OrderedCollection<Activity> {
+        OrderedCollection {
+            context: ACTIVITY_STREAMS.to_string(),
+            id: format!("{}/outbox", self.actor_url(&author.public_id)),
+            kind: "OrderedCollection".to_string(),
+            total_items: total,
+            ordered_items: posts.iter().map(|post| self.create(author, post)).collect(),
+        }
+    }
+
+    /// Follower URLs stay private; only the count is published
+    pub fn followers(&self, author: &User, total: i64) -> OrderedCollection<String> {
+        OrderedCollection {
+            context: ACTIVITY_STREAMS.to_string(),
+            id: format!("{}/followers", self.actor_url(&author.public_id)),
+            kind: "OrderedCollection".to_string(),
+            total_items: total,
+            ordered_items: Vec::new(),
+        }
+    }
+
+    /// Interprets an inbox delivery addressed to `name`
+    pub fn inbox_activity(&self, name: &str, activity: &serde_json::Value) -> InboxActivity {
+        let target = self.actor_url(name);
+        let kind = activity.get("type").and_then(|kind| kind.as_str());
+        let follower = activity.get("actor").and_then(|actor| actor.as_str());
+        let follows_target = |follow: &serde_json::Value| {
+            follow.get("type").and_then(|kind| kind.as_str()) == Some("Follow")
+                && object_id(follow.get("object")) == Some(target.as_str())
+        };
+
+        match (kind, follower) {
+            (Some("Follow"), Some(follower)) if follows_target(activity) => InboxActivity::Follow {
+                follower: follower.to_string(),
+            },
+            (Some("Undo"), Some(follower))
+                if activity.get("object").is_some_and(follows_target) =>
+            {
+                InboxActivity::Unfollow {
+                    follower: follower.to_string(),
+                }
+            }
+            _ => InboxActivity::Ignored,
+        }
+    }
+}
+
+/// Objects may be embedded or referenced by id
+fn object_id(object: Option<&serde_json::Value>) -> Option<&str> {
+    let object = object?;
+    object
+        .as_str()
+        .or_else(|| object.get("id").and_then(|id| id.as_str()))
+}
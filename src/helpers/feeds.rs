@@ -0,0 +1,240 @@
+//! RSS 2.0 and Atom feeds of the latest posts.
+//!
+//! Both formats are rendered from the same [`Feed`], with each post's HTML
+//! as its content and its license as the entry's rights.
+
+use chrono::{DateTime, Utc};
+use quick_xml::escape::escape;
+
+use crate::config::AppConfig;
+use crate::helpers::publish_hooks::post_url;
+use crate::model::model::PostResponse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+}
+
+impl FeedFormat {
+    /// The format for a feed file name, `posts.xml` for RSS or `posts.atom`
+    /// for Atom, and the name without its extension
+    pub fn from_file_name(name: &str) -> Option<(&str, FeedFormat)> {
+        if let Some(stem) = name.strip_suffix(".xml") {
+            Some((stem, FeedFormat::Rss))
+        } else {
+            name.strip_suffix(".atom")
+                .map(|stem| (stem, FeedFormat::Atom))
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "xml",
+            FeedFormat::Atom => "atom",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    pub title: String,
+    /// Absolute URL of the post; doubles as its permanent ID
+    pub url: String,
+    pub author: String,
+    pub content_html: String,
+    pub license: Option<String>,
+    pub published: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Feed {
+    pub title: String,
+    pub description: String,
+    /// The site the feed belongs to
+    pub site_url: String,
+    /// Where the feed itself is served
+    pub self_url: String,
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    /// A feed of `posts` at `path` (e.g. `/feeds/posts.xml`), titled
+    /// `subtitle` after the site name when given
+    pub fn from_posts(
+        config: &AppConfig,
+        path: &str,
+        subtitle: Option<&str>,
+        posts: Vec<PostResponse>,
+    ) -> Self {
+        let feeds = &config.feeds;
+        let title = match subtitle {
+            Some(subtitle) => format!("{} - {}", feeds.title, subtitle),
+            None => feeds.title.clone(),
+        };
+        let entries = posts
+            .into_iter()
+            .map(|post| FeedEntry {
+                url: post_url(&config.publish_hooks.post_url_template, &post.id),
+                title: post.title,
+                author: post.author.name,
+                content_html: post.content_html.unwrap_or_default(),
+                license: post.license,
+                published: post.created_at,
+                updated: post.updated_at,
+            })
+            .collect();
+
+        Feed {
+            title,
+            description: feeds.description.clone(),
+            site_url: format!("{}{}/", feeds.public_url, config.base_path),
+            self_url: format!("{}{}", feeds.public_url, config.public_path(path)),
+            entries,
+        }
+    }
+
+    /// When any entry last changed; the epoch for an empty feed
+    pub fn updated(&self) -> DateTime<Utc> {
+        self.entries
+            .iter()
+            .map(|entry| entry.updated)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn render(&self, format: FeedFormat) -> String {
+        match format {
+            FeedFormat::Rss => self.to_rss(),
+            FeedFormat::Atom => self.to_atom(),
+        }
+    }
+
+    pub fn to_rss(&self) -> String {
+        let items: String = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let rights = entry
+                    .license
+                    .as_deref()
+                    .map(|license| format!("<dc:rights>{}</dc:rights>", text(license)))
+                    .unwrap_or_default();
+                format!(
+                    concat!(
+                        "<item>",
+                        "<title>{title}</title>",
+                        "<link>{url}</link>",
+                        "<guid isPermaLink=\"true\">{url}</guid>",
+                        "<dc:creator>{author}</dc:creator>",
+                        "<pubDate>{published}</pubDate>",
+                        "<description>{content}</description>",
+                        "{rights}",
+                        "</item>"
+                    ),
+                    title = text(&entry.title),
+                    url = text(&entry.url),
+                    author = text(&entry.author),
+                    published = entry.published.to_rfc2822(),
+                    content = text(&entry.content_html),
+                    rights = rights,
+                )
+            })
+            .collect();
+
+        format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">",
+                "<channel>",
+                "<title>{title}</title>",
+                "<link>{site}</link>",
+                "<description>{description}</description>",
+                "<atom:link href=\"{self_url}\" rel=\"self\" type=\"application/rss+xml\"/>",
+                "<lastBuildDate>{updated}</lastBuildDate>",
+                "{items}",
+                "</channel>",
+                "</rss>\n"
+            ),
+            title = text(&self.title),
+            site = text(&self.site_url),
+            description = text(&self.description),
+            self_url = text(&self.self_url),
+            updated = self.updated().to_rfc2822(),
+            items = items,
+        )
+    }
+
+    pub fn to_atom(&self) -> String {
+        let entries: String = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let rights = entry
+                    .license
+                    .as_deref()
+                    .map(|license| format!("<rights>{}</rights>", text(license)))
+                    .unwrap_or_default();
+                format!(
+                    concat!(
+                        "<entry>",
+                        "<title>{title}</title>",
+                        "<link href=\"{url}\"/>",
+                        "<id>{url}</id>",
+                        "<author><name>{author}</name></author>",
+                        "<published>{published}</published>",
+                        "<updated>{updated}</updated>",
+                        "<content type=\"html\">{content}</content>",
+                        "{rights}",
+                        "</entry>"
+                    ),
+                    title = text(&entry.title),
+                    url = text(&entry.url),
+                    author = text(&entry.author),
+                    published = entry.published.to_rfc3339(),
+                    updated = entry.updated.to_rfc3339(),
+                    content = text(&entry.content_html),
+                    rights = rights,
+                )
+            })
+            .collect();
+
+        format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<feed xmlns=\"http://www.w3.org/2005/Atom\">",
+                "<title>{title}</title>",
+                "<subtitle>{description}</subtitle>",
+                "<link href=\"{site}\"/>",
+                "<link href=\"{self_url}\" rel=\"self\" type=\"application/atom+xml\"/>",
+                "<id>{self_url}</id>",
+                "<updated>{updated}</updated>",
+                "{entries}",
+                "</feed>\n"
+            ),
+            title = text(&self.title),
+            description = text(&self.description),
+            site = text(&self.site_url),
+            self_url = text(&self.self_url),
+            updated = self.updated().to_rfc3339(),
+            entries = entries,
+        )
+    }
+}
+
+/// `value` escaped for XML, without the control characters XML 1.0 forbids
+fn text(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    escape(cleaned.as_str()).into_owned()
+}
@@ -0,0 +1,105 @@
+//! Sparse fieldsets: `?fields=id,title,author.name` trims each item of a list
+//! response to the named fields.
+//!
+//! The selection is applied while the body is serialized, after redaction has
+//! decided what the requester may see, so it can only drop fields. Names that
+//! match nothing are ignored.
+
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Serializer, ser::Error};
+use serde_json::Value;
+
+/// Fields to keep, by name; `None` keeps the whole value, `Some` only the
+/// nested fields it names
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldSelection {
+    fields: BTreeMap<String, Option<FieldSelection>>,
+}
+
+impl FieldSelection {
+    /// Parses a `fields` query parameter; `None` when it is missing or names
+    /// no fields, meaning the full response
+    pub fn parse(raw: Option<&str>) -> Result<Option<Self>, String> {
+        let mut selection = Self::default();
+        for field in raw.unwrap_or_default().split(',').map(str::trim) {
+            if field.is_empty() {
+                continue;
+            }
+            let path: Vec<&str> = field.split('.').collect();
+            let valid = path.iter().all(|segment| {
+                !segment.is_empty()
+                    && segment
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+            if !valid {
+                return Err(format!("`{}` is not a valid field name", field));
+            }
+            selection.insert(&path);
+        }
+
+        Ok((!selection.fields.is_empty()).then_some(selection))
+    }
+
+    fn insert(&mut self, path: &[&str]) {
+        let Some((first, rest)) = path.split_first() else {
+            return;
+        };
+        let entry = self
+            .fields
+            .entry(first.to_string())
+            .or_insert_with(|| Some(Self::default()));
+        if rest.is_empty() {
+            // The whole field wins over any of its nested fields
+            *entry = None;
+        } else if let Some(nested) = entry {
+            nested.insert(rest);
+        }
+    }
+
+    /// `value` with only the selected fields; arrays are trimmed item by item
+    pub fn apply(&self, value: Value) -> Value {
+        match value {
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|item| self.apply(item)).collect())
+            }
+            Value::Object(mut object) => Value::Object(
+                self.fields
+                    .iter()
+                    .filter_map(|(name, nested)| {
+                        let value = object.remove(name)?;
+                        let value = match nested {
+                            Some(nested) => nested.apply(value),
+                            None => value,
+                        };
+                        Some((name.clone(), value))
+                    })
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+/// A response body trimmed to a [`FieldSelection`] as it is serialized
+pub struct Sparse<T> {
+    data: T,
+    fields: Option<FieldSelection>,
+}
+
+impl<T> Sparse<T> {
+    pub fn new(data: T, fields: Option<FieldSelection>) -> Self {
+        Self { data, fields }
+    }
+}
+
+impl<T: Serialize> Serialize for Sparse<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(fields) = &self.fields else {
+            return self.data.serialize(serializer);
+        };
+        let value = serde_json::to_value(&self.data).map_err(S::Error::custom)?;
+        fields.apply(value).serialize(serializer)
+    }
+}
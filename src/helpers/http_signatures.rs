@@ -0,0 +1,264 @@
+//! HTTP Signatures between ActivityPub servers.
+//!
+//! Follows draft-cavage-http-signatures as Mastodon and most of the fediverse
+//! use it: `rsa-sha256` over `(request-target)`, `host`, `date` and `digest`,
+//! the last one a SHA-256 of the body. Outgoing deliveries are signed with
+//! the instance key, and inbox deliveries must be signed over the same
+//! headers to be accepted.
+
+use std::fmt;
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use axum::http::HeaderMap;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Url;
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePublicKey, LineEnding};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::helpers::secrets::read_secret;
+
+/// Headers every signature must cover
+pub const SIGNED_HEADERS: [&str; 4] = ["(request-target)", "host", "date", "digest"];
+
+/// How far a `Date` header may be from our clock, either way
+pub const MAX_CLOCK_SKEW: Duration = Duration::hours(1);
+
+const KEY_BITS: usize = 2048;
+
+lazy_static::lazy_static! {
+    /// `FEDERATION_PRIVATE_KEY` as configured; `None` means this process made one up
+    static ref CONFIGURED_KEY: Option<InstanceKey> = read_secret("FEDERATION_PRIVATE_KEY")
+        .and_then(|pem| pem.map(|pem| InstanceKey::from_pem(&pem)).transpose())
+        .expect("invalid FEDERATION_PRIVATE_KEY configuration");
+
+    /// Key every ActivityPub delivery is signed with, published on each actor
+    pub static ref INSTANCE_KEY: InstanceKey = CONFIGURED_KEY
+        .clone()
+        .unwrap_or_else(|| InstanceKey::generate().expect("unable to generate a federation key"));
+}
+
+/// Loads the federation key and refuses to run production with one generated
+/// at startup, since remote servers cache actor keys and would reject
+/// deliveries signed after a restart
+pub fn check_federation_key(production: bool) -> Result<()> {
+    if CONFIGURED_KEY.is_some() {
+        return Ok(());
+    }
+    if production {
+        bail!("FEDERATION_PRIVATE_KEY or FEDERATION_PRIVATE_KEY_FILE must be set in production");
+    }
+    warn!(
+        "FEDERATION_PRIVATE_KEY is not set; remote servers stop trusting deliveries when the server restarts"
+    );
+    lazy_static::initialize(&INSTANCE_KEY);
+    Ok(())
+}
+
+/// The RSA key this server signs requests with
+#[derive(Clone)]
+pub struct InstanceKey {
+    signing: SigningKey<Sha256>,
+    public_pem: String,
+}
+
+impl fmt::Debug for InstanceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstanceKey")
+            .field("public_pem", &self.public_pem)
+            .finish_non_exhaustive()
+    }
+}
+
+impl InstanceKey {
+    /// A private key in PKCS#8 (`BEGIN PRIVATE KEY`) or PKCS#1 (`BEGIN RSA PRIVATE KEY`) PEM
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        let key = RsaPrivateKey::from_pkcs8_pem(pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+            .context("invalid RSA private key")?;
+        Self::from_key(key)
+    }
+
+    pub fn generate() -> Result<Self> {
+        Self::from_key(RsaPrivateKey::new(&mut rsa::rand_core::OsRng, KEY_BITS)?)
+    }
+
+    fn from_key(key: RsaPrivateKey) -> Result<Self> {
+        let public_pem = key.to_public_key().to_public_key_pem(LineEnding::LF)?;
+        Ok(Self {
+            signing: SigningKey::new(key),
+            public_pem,
+        })
+    }
+
+    /// SPKI PEM, as published in an actor's `publicKey`
+    pub fn public_key_pem(&self) -> &str {
+        &self.public_pem
+    }
+
+    /// The `Date`, `Digest` and `Signature` headers of a request to `url`;
+    /// `Host` is left to the HTTP client, which derives the same value from `url`
+    pub fn sign(
+        &self,
+        key_id: &str,
+        method: &str,
+        url: &Url,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(&'static str, String)>> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("{} has no host", url))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        let target = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let date = http_date(now);
+        let digest = digest(body);
+
+        let signing_string = format!(
+            "(request-target): {} {}\nhost: {}\ndate: {}\ndigest: {}",
+            method.to_lowercase(),
+            target,
+            host,
+            date,
+            digest
+        );
+        let signature = self.signing.sign(signing_string.as_bytes());
+        let header = format!(
+            r#"keyId="{}",algorithm="rsa-sha256",headers="{}",signature="{}""#,
+            key_id,
+            SIGNED_HEADERS.join(" "),
+            STANDARD.encode(signature.to_bytes())
+        );
+
+        Ok(vec![
+            ("date", date),
+            ("digest", digest),
+            ("signature", header),
+        ])
+    }
+}
+
+/// `SHA-256=` and the base64 of the body's hash, as sent in `Digest`
+pub fn digest(body: &[u8]) -> String {
+    format!("SHA-256={}", STANDARD.encode(Sha256::digest(body)))
+}
+
+/// IMF-fixdate, the format of the `Date` header
+pub fn http_date(at: DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// A parsed `Signature` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHeader {
+    pub key_id: String,
+    pub algorithm: Option<String>,
+    /// Lowercase names of the covered headers, in signing order
+    pub headers: Vec<String>,
+    pub signature: Vec<u8>,
+}
+
+impl SignatureHeader {
+    pub fn parse(value: &str) -> Option<Self> {
+        let (mut key_id, mut algorithm, mut headers, mut signature) = (None, None, None, None);
+        for param in value.split(',') {
+            let (name, value) = param.trim().split_once('=')?;
+            let value = value.trim_matches('"');
+            match name {
+                "keyId" => key_id = Some(value.to_string()),
+                "algorithm" => algorithm = Some(value.to_string()),
+                "headers" => {
+                    headers = Some(value.split_whitespace().map(str::to_lowercase).collect())
+                }
+                "signature" => signature = Some(STANDARD.decode(value).ok()?),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            key_id: key_id?,
+            algorithm,
+            // The draft's default when `headers` is left out
+            headers: headers.unwrap_or_else(|| vec!["date".to_string()]),
+            signature: signature?,
+        })
+    }
+}
+
+/// Public key in SPKI (`BEGIN PUBLIC KEY`) or PKCS#1 (`BEGIN RSA PUBLIC KEY`) PEM
+pub fn parse_public_key(pem: &str) -> Result<RsaPublicKey> {
+    RsaPublicKey::from_public_key_pem(pem)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+        .context("invalid RSA public key")
+}
+
+/// Checks `signature` covers the required headers, that `Digest` and `Date`
+/// match the request, and that `public_key` made it
+pub fn verify(
+    signature: &SignatureHeader,
+    public_key: &RsaPublicKey,
+    method: &str,
+    request_target: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if let Some(algorithm) = &signature.algorithm {
+        // `hs2019` leaves the algorithm to the key, which is RSA here
+        ensure!(
+            algorithm == "rsa-sha256" || algorithm == "hs2019",
+            "unsupported signature algorithm {}",
+            algorithm
+        );
+    }
+    for required in SIGNED_HEADERS {
+        ensure!(
+            signature.headers.iter().any(|name| name == required),
+            "signature does not cover {}",
+            required
+        );
+    }
+
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| anyhow!("missing {} header", name))
+    };
+    ensure!(
+        header("digest")? == digest(body),
+        "digest does not match the body"
+    );
+    let date = DateTime::parse_from_rfc2822(header("date")?)
+        .context("invalid date header")?
+        .with_timezone(&Utc);
+    ensure!(
+        (now - date).abs() <= MAX_CLOCK_SKEW,
+        "date header is too far from the current time"
+    );
+
+    let mut lines = Vec::with_capacity(signature.headers.len());
+    for name in &signature.headers {
+        let value = match name.as_str() {
+            "(request-target)" => format!("{} {}", method.to_lowercase(), request_target),
+            name => header(name)?.to_string(),
+        };
+        lines.push(format!("{}: {}", name, value));
+    }
+
+    let signed = Signature::try_from(signature.signature.as_slice())?;
+    VerifyingKey::<Sha256>::new(public_key.clone())
+        .verify(lines.join("\n").as_bytes(), &signed)
+        .context("signature does not match")
+}
@@ -0,0 +1,267 @@
+//! Translated response messages, picked by `Accept-Language`.
+//!
+//! Handlers write their messages in English. A catalog in `API_MESSAGES_DIR`,
+//! e.g. `locales/es.json`, maps those English messages to another language;
+//! keys may hold `{name}` placeholders standing for the parts that vary, such
+//! as `"{field} must be at most {max} characters"`. The middleware picks the
+//! first language in `Accept-Language` with a catalog, and the `message` of
+//! success bodies and the `detail` of error bodies are translated as they are
+//! written out. Error titles stay in English so clients can match on them, as
+//! do messages a catalog leaves out and responses to requests preferring
+//! English or a language without a catalog.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use tracing::info;
+
+/// Language messages are written in
+pub const SOURCE_LOCALE: &str = "en";
+
+/// One language's translations of English messages
+#[derive(Debug, Default)]
+pub struct Catalog {
+    exact: HashMap<String, String>,
+    /// Keys with placeholders, tried in order when no exact key matches
+    patterns: Vec<(Pattern, String)>,
+}
+
+impl Catalog {
+    pub fn new(strings: HashMap<String, String>) -> Self {
+        let mut catalog = Catalog::default();
+        for (key, translation) in strings {
+            match Pattern::parse(&key) {
+                Some(pattern) => catalog.patterns.push((pattern, translation)),
+                None => {
+                    catalog.exact.insert(key, translation);
+                }
+            }
+        }
+        // Longer patterns are more specific; try them first
+        catalog
+            .patterns
+            .sort_by_key(|(pattern, _)| std::cmp::Reverse(pattern.literal_len()));
+        catalog
+    }
+
+    /// `message` in this catalog's language, if it has a translation
+    pub fn translate(&self, message: &str) -> Option<String> {
+        if let Some(translation) = self.exact.get(message) {
+            return Some(translation.clone());
+        }
+        self.patterns.iter().find_map(|(pattern, translation)| {
+            let values = pattern.matches(message)?;
+            Some(
+                values
+                    .into_iter()
+                    .fold(translation.clone(), |text, (name, value)| {
+                        text.replace(&format!("{{{}}}", name), value)
+                    }),
+            )
+        })
+    }
+}
+
+/// A catalog key with `{name}` placeholders, as its literal text between them
+#[derive(Debug)]
+struct Pattern {
+    /// One more than there are placeholders; the first and last may be empty
+    literals: Vec<String>,
+    names: Vec<String>,
+}
+
+impl Pattern {
+    /// `None` for keys without placeholders
+    fn parse(key: &str) -> Option<Self> {
+        let mut literals = Vec::new();
+        let mut names = Vec::new();
+        let mut rest = key;
+        while let Some(start) = rest.find('{') {
+            let end = start + rest[start..].find('}')?;
+            let name = &rest[start + 1..end];
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return None;
+            }
+            literals.push(rest[..start].to_string());
+            names.push(name.to_string());
+            rest = &rest[end + 1..];
+        }
+        literals.push(rest.to_string());
+        (!names.is_empty()).then_some(Pattern { literals, names })
+    }
+
+    fn literal_len(&self) -> usize {
+        self.literals.iter().map(String::len).sum()
+    }
+
+    /// The placeholder values that turn this pattern into `message`
+    fn matches<'a>(&'a self, message: &'a str) -> Option<Vec<(&'a str, &'a str)>> {
+        let (first, rest) = self.literals.split_first()?;
+        let (last, middle) = rest.split_last()?;
+        let mut remaining = message.strip_prefix(first.as_str())?;
+        remaining = remaining.strip_suffix(last.as_str())?;
+
+        let mut values = Vec::with_capacity(self.names.len());
+        for literal in middle {
+            // Placeholders never match nothing, so skip at least one character
+            let skip = remaining.chars().next()?.len_utf8();
+            let at = skip + remaining[skip..].find(literal.as_str())?;
+            values.push(&remaining[..at]);
+            remaining = &remaining[at + literal.len()..];
+        }
+        if remaining.is_empty() {
+            return None;
+        }
+        values.push(remaining);
+
+        Some(self.names.iter().map(String::as_str).zip(values).collect())
+    }
+}
+
+/// Every loaded catalog, by lowercase locale
+#[derive(Debug, Default)]
+pub struct Messages {
+    catalogs: HashMap<String, Arc<Catalog>>,
+}
+
+impl Messages {
+    /// Loads the `<locale>.json` catalogs in `dir`; a missing directory leaves
+    /// every message in English
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut messages = Messages::default();
+        if !dir.is_dir() {
+            info!(
+                "No message catalogs in {}; responses stay in English",
+                dir.display()
+            );
+            return Ok(messages);
+        }
+
+        let entries =
+            fs::read_dir(dir).with_context(|| format!("unable to read {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let catalog = fs::read_to_string(&path)
+                .with_context(|| format!("unable to read {}", path.display()))?;
+            let strings = serde_json::from_str(&catalog)
+                .with_context(|| format!("invalid message catalog {}", path.display()))?;
+            messages.insert(locale, Catalog::new(strings));
+        }
+
+        info!(
+            "Loaded message catalogs from {} in {} locale(s)",
+            dir.display(),
+            messages.catalogs.len()
+        );
+        Ok(messages)
+    }
+
+    pub fn insert(&mut self, locale: &str, catalog: Catalog) {
+        self.catalogs
+            .insert(locale.to_lowercase(), Arc::new(catalog));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.catalogs.is_empty()
+    }
+
+    /// The locale and catalog to answer an `Accept-Language` header with;
+    /// `None` when English is preferred over every language with a catalog
+    pub fn negotiate(&self, accept_language: &str) -> Option<(String, Arc<Catalog>)> {
+        let mut ranges: Vec<(&str, f32)> = accept_language
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(1.0);
+                (!tag.is_empty() && tag != "*" && quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        // Stable, so equally weighted languages keep the client's order
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for (tag, _) in ranges {
+            let tag = tag.to_lowercase();
+            let language = tag.split(['-', '_']).next().unwrap_or_default();
+            if language == SOURCE_LOCALE {
+                return None;
+            }
+            let found = [tag.as_str(), language]
+                .into_iter()
+                .find_map(|locale| self.catalogs.get_key_value(locale));
+            if let Some((locale, catalog)) = found {
+                return Some((locale.clone(), catalog.clone()));
+            }
+        }
+        None
+    }
+}
+
+tokio::task_local! {
+    static CATALOG: Option<Arc<Catalog>>;
+}
+
+/// Picks the catalog for the request and marks the response with its
+/// language
+pub async fn locale_middleware(
+    State(messages): State<Arc<Messages>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if messages.is_empty() {
+        return next.run(request).await;
+    }
+
+    let chosen = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| messages.negotiate(value));
+    let (locale, catalog) = match chosen {
+        Some((locale, catalog)) => (locale, Some(catalog)),
+        None => (SOURCE_LOCALE.to_string(), None),
+    };
+
+    let mut response = CATALOG.scope(catalog, next.run(request)).await;
+
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&locale) {
+        headers.insert(header::CONTENT_LANGUAGE, value);
+    }
+    headers.append(header::VARY, HeaderValue::from_static("accept-language"));
+    response
+}
+
+/// Replaces `message` with its translation for the current request, if there
+/// is one
+pub fn localize(message: &mut String) {
+    let translated = CATALOG
+        .try_with(|catalog| {
+            catalog
+                .as_ref()
+                .and_then(|catalog| catalog.translate(message))
+        })
+        .ok()
+        .flatten();
+    if let Some(translated) = translated {
+        *message = translated;
+    }
+}
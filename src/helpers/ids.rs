@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use uuid::{Uuid, Version};
+
+/// Length of the short identifiers exposed in URLs and responses
+pub const PUBLIC_ID_LEN: usize = 12;
+
+/// Generates primary keys for new rows.
+pub trait IdGenerator: Send + Sync {
+    fn generate(&self) -> Uuid;
+}
+
+/// UUIDv7: millisecond timestamp prefix, so new rows sort by creation time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeOrderedIds;
+
+impl IdGenerator for TimeOrderedIds {
+    fn generate(&self) -> Uuid {
+        Uuid::now_v7()
+    }
+}
+
+/// UUIDv4: fully random, as used for rows created before v7 was adopted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIds;
+
+impl IdGenerator for RandomIds {
+    fn generate(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+pub fn is_time_ordered(id: &Uuid) -> bool {
+    id.get_version() == Some(Version::SortRand)
+}
+
+/// Creation time embedded in a v7 id; `None` for legacy v4 ids.
+pub fn id_timestamp(id: &Uuid) -> Option<DateTime<Utc>> {
+    if !is_time_ordered(id) {
+        return None;
+    }
+    let (secs, nanos) = id.get_timestamp()?.to_unix();
+    DateTime::from_timestamp(secs as i64, nanos)
+}
+
+/// Short, non-enumerable id shown to clients in place of the internal UUID.
+pub fn new_public_id() -> String {
+    nanoid::nanoid!(PUBLIC_ID_LEN)
+}
@@ -0,0 +1,103 @@
+//! JSON request bodies with rejections in the API's error format.
+//!
+//! Axum's [`axum::Json`] answers a bad body with a plain-text message. The
+//! [`Json`] here extracts the same way but answers with the standard error
+//! body: `415` when the body isn't sent as JSON, `400` when it isn't valid
+//! JSON, and `422` when it is but doesn't fit the request type, with a
+//! `pointer` (RFC 6901) to the member that didn't.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::error::Category;
+use serde_path_to_error::Segment;
+
+use crate::model::model::ErrorResponse;
+
+/// A JSON body; also serializes as one, like [`axum::Json`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ErrorResponse;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json(req.headers()) {
+            return Err(ErrorResponse::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Unsupported Media Type",
+                "Send the body as application/json",
+            ));
+        }
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| ErrorResponse::new(e.status(), "Invalid Body", e.body_text()))?;
+        parse_json_body(&body).map(Json).map_err(|e| *e)
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+/// `application/json`, or a JSON-based type such as `application/activity+json`
+fn is_json(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase())
+    else {
+        return false;
+    };
+    content_type == "application/json"
+        || (content_type.starts_with("application/") && content_type.ends_with("+json"))
+}
+
+/// Parses a JSON body into `T`
+pub fn parse_json_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, Box<ErrorResponse>> {
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let pointer = json_pointer(e.path().iter());
+        let inner = e.into_inner();
+        match inner.classify() {
+            Category::Data => ErrorResponse::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Invalid Body",
+                inner.to_string(),
+            )
+            .with_pointer(pointer),
+            _ => malformed(inner),
+        }
+    })?;
+    deserializer.end().map_err(malformed)?;
+    Ok(value)
+}
+
+fn malformed(e: serde_json::Error) -> ErrorResponse {
+    ErrorResponse::new(StatusCode::BAD_REQUEST, "Malformed JSON", e.to_string())
+}
+
+/// The RFC 6901 pointer to a member, e.g. `/tags/2`; empty for the whole body
+fn json_pointer<'a>(segments: impl Iterator<Item = &'a Segment>) -> String {
+    segments
+        .filter_map(|segment| match segment {
+            Segment::Seq { index } => Some(index.to_string()),
+            Segment::Map { key } => Some(key.clone()),
+            Segment::Enum { variant } => Some(variant.clone()),
+            Segment::Unknown => None,
+        })
+        .map(|token| format!("/{}", token.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
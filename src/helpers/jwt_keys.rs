@@ -0,0 +1,328 @@
+//! Signing keys for auth tokens.
+//!
+//! Tokens are signed with the current key and carry its `kid`. During a key
+//! rotation the previous key keeps verifying tokens until its expiry, and
+//! asymmetric public keys are published as a JWKS so other services can
+//! verify tokens without sharing a secret.
+
+use std::{env, fs};
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+};
+use rsa::{RsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts};
+use serde::{Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+
+use crate::helpers::clock::Clock;
+use crate::helpers::secrets::read_secret;
+
+/// DER prefix of an Ed25519 SubjectPublicKeyInfo; the raw 32-byte key follows it
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// A public key in JSON Web Key form
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Jwk {
+    pub kty: String,
+    pub kid: String,
+    pub alg: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    /// RSA modulus
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    /// RSA exponent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    /// OKP curve
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    /// OKP public key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+pub struct JwtKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    encoding: Option<EncodingKey>,
+    decoding: DecodingKey,
+    /// `None` for shared secrets, which must never be published
+    jwk: Option<Jwk>,
+    /// After this instant the key no longer verifies tokens
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl JwtKey {
+    pub fn hmac(kid: impl Into<String>, secret: &[u8]) -> Self {
+        Self {
+            kid: kid.into(),
+            algorithm: Algorithm::HS256,
+            encoding: Some(EncodingKey::from_secret(secret)),
+            decoding: DecodingKey::from_secret(secret),
+            jwk: None,
+            expires_at: None,
+        }
+    }
+
+    /// An RS256 or EdDSA key from PEM; without a private key it only verifies
+    pub fn from_pem(
+        kid: impl Into<String>,
+        algorithm: Algorithm,
+        private_pem: Option<&[u8]>,
+        public_pem: &[u8],
+    ) -> Result<Self> {
+        let kid = kid.into();
+        let (encoding, decoding, jwk) = match algorithm {
+            Algorithm::RS256 => {
+                let public = RsaPublicKey::from_public_key_pem(std::str::from_utf8(public_pem)?)
+                    .context("invalid RSA public key")?;
+                let jwk = Jwk {
+                    kty: "RSA".to_string(),
+                    kid: kid.clone(),
+                    alg: "RS256".to_string(),
+                    key_use: "sig".to_string(),
+                    n: Some(URL_SAFE_NO_PAD.encode(public.n().to_bytes_be())),
+                    e: Some(URL_SAFE_NO_PAD.encode(public.e().to_bytes_be())),
+                    crv: None,
+                    x: None,
+                };
+                (
+                    private_pem.map(EncodingKey::from_rsa_pem).transpose()?,
+                    DecodingKey::from_rsa_pem(public_pem)?,
+                    jwk,
+                )
+            }
+            Algorithm::EdDSA => {
+                let raw = ed25519_public_key(public_pem)?;
+                let jwk = Jwk {
+                    kty: "OKP".to_string(),
+                    kid: kid.clone(),
+                    alg: "EdDSA".to_string(),
+                    key_use: "sig".to_string(),
+                    n: None,
+                    e: None,
+                    crv: Some("Ed25519".to_string()),
+                    x: Some(URL_SAFE_NO_PAD.encode(raw)),
+                };
+                (
+                    private_pem.map(EncodingKey::from_ed_pem).transpose()?,
+                    DecodingKey::from_ed_pem(public_pem)?,
+                    jwk,
+                )
+            }
+            other => bail!("unsupported asymmetric JWT algorithm {:?}", other),
+        };
+
+        Ok(Self {
+            kid,
+            algorithm,
+            encoding,
+            decoding,
+            jwk: Some(jwk),
+            expires_at: None,
+        })
+    }
+
+    pub fn expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T> {
+        let mut validation = Validation::new(self.algorithm);
+        validation.validate_exp = false;
+
+        Ok(decode::<T>(token, &self.decoding, &validation)?.claims)
+    }
+
+    fn is_active(&self, clock: &dyn Clock) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| clock.now() < expires_at)
+    }
+}
+
+pub struct JwtKeys {
+    current: JwtKey,
+    previous: Vec<JwtKey>,
+}
+
+impl JwtKeys {
+    pub fn new(current: JwtKey, previous: Vec<JwtKey>) -> Result<Self> {
+        if current.encoding.is_none() {
+            bail!("the current JWT key needs a private key to sign with");
+        }
+        Ok(Self { current, previous })
+    }
+
+    /// Reads `JWT_ALGORITHM` and its key material; `hmac_secret` is used for HS256
+    pub fn from_env(hmac_secret: &str) -> Result<Self> {
+        let algorithm = parse_algorithm(&env::var("JWT_ALGORITHM").unwrap_or_default())?;
+        let current = match algorithm {
+            Algorithm::HS256 => JwtKey::hmac(
+                env::var("JWT_KEY_ID").unwrap_or_else(|_| "hs256".to_string()),
+                hmac_secret.as_bytes(),
+            ),
+            _ => {
+                let private_pem = read_key("JWT_PRIVATE_KEY_PATH")?;
+                let public_pem = read_key("JWT_PUBLIC_KEY_PATH")?;
+                let kid = env::var("JWT_KEY_ID").unwrap_or_else(|_| key_id(&public_pem));
+                JwtKey::from_pem(kid, algorithm, Some(&private_pem), &public_pem)?
+            }
+        };
+
+        let mut previous = Vec::new();
+        let previous_algorithm = match env::var("JWT_PREVIOUS_ALGORITHM") {
+            Ok(value) => parse_algorithm(&value)?,
+            Err(_) => algorithm,
+        };
+        let previous_key = match previous_algorithm {
+            Algorithm::HS256 => read_secret("AUTH_SECRET_PREVIOUS")?.map(|secret| {
+                JwtKey::hmac(
+                    env::var("JWT_PREVIOUS_KEY_ID")
+                        .unwrap_or_else(|_| "hs256-previous".to_string()),
+                    secret.as_bytes(),
+                )
+            }),
+            _ => match env::var("JWT_PREVIOUS_PUBLIC_KEY_PATH") {
+                Ok(_) => {
+                    let public_pem = read_key("JWT_PREVIOUS_PUBLIC_KEY_PATH")?;
+                    let kid =
+                        env::var("JWT_PREVIOUS_KEY_ID").unwrap_or_else(|_| key_id(&public_pem));
+                    Some(JwtKey::from_pem(
+                        kid,
+                        previous_algorithm,
+                        None,
+                        &public_pem,
+                    )?)
+                }
+                Err(_) => None,
+            },
+        };
+
+        if let Some(mut key) = previous_key {
+            if let Ok(value) = env::var("JWT_PREVIOUS_KEY_EXPIRES_AT") {
+                let expires_at = DateTime::parse_from_rfc3339(&value)
+                    .context("JWT_PREVIOUS_KEY_EXPIRES_AT must be an RFC 3339 timestamp")?;
+                key = key.expires_at(expires_at.with_timezone(&Utc));
+            }
+            if key.kid == current.kid {
+                bail!("the previous JWT key id must differ from the current one");
+            }
+            previous.push(key);
+        }
+
+        Self::new(current, previous)
+    }
+
+    pub fn current_kid(&self) -> &str {
+        &self.current.kid
+    }
+
+    pub fn current_algorithm(&self) -> Algorithm {
+        self.current.algorithm
+    }
+
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String> {
+        let mut header = Header::new(self.current.algorithm);
+        header.kid = Some(self.current.kid.clone());
+
+        let encoding = self
+            .current
+            .encoding
+            .as_ref()
+            .ok_or_else(|| anyhow!("current JWT key cannot sign"))?;
+        Ok(encode(&header, claims, encoding)?)
+    }
+
+    /// Verifies the signature with the key named by `kid`. Tokens without a
+    /// `kid` predate rotation and are tried against every active key.
+    /// Expiry claims are left to the caller.
+    pub fn verify<T: DeserializeOwned>(&self, token: &str, clock: &dyn Clock) -> Result<T> {
+        let header = decode_header(token)?;
+
+        if let Some(kid) = &header.kid {
+            let key = self
+                .keys()
+                .find(|key| &key.kid == kid)
+                .ok_or_else(|| anyhow!("unknown signing key {}", kid))?;
+            if !key.is_active(clock) {
+                bail!("signing key {} has been retired", key.kid);
+            }
+            return key.verify(token);
+        }
+
+        let mut last_error = anyhow!("no active key matches the token");
+        for key in self.keys().filter(|key| key.is_active(clock)) {
+            match key.verify(token) {
+                Ok(claims) => return Ok(claims),
+                Err(err) => last_error = err,
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Public keys that currently verify tokens
+    pub fn jwks(&self, clock: &dyn Clock) -> JwkSet {
+        JwkSet {
+            keys: self
+                .keys()
+                .filter(|key| key.is_active(clock))
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &JwtKey> {
+        std::iter::once(&self.current).chain(&self.previous)
+    }
+}
+
+fn parse_algorithm(value: &str) -> Result<Algorithm> {
+    match value.to_ascii_uppercase().as_str() {
+        "" | "HS256" => Ok(Algorithm::HS256),
+        "RS256" => Ok(Algorithm::RS256),
+        "EDDSA" | "ED25519" => Ok(Algorithm::EdDSA),
+        other => bail!("unsupported JWT_ALGORITHM {}", other),
+    }
+}
+
+fn read_key(var: &str) -> Result<Vec<u8>> {
+    let path =
+        env::var(var).with_context(|| format!("{} is required for this JWT_ALGORITHM", var))?;
+    fs::read(&path).with_context(|| format!("unable to read {} ({})", var, path))
+}
+
+/// A stable id derived from the public key, so rotating keys changes the `kid`
+fn key_id(public_pem: &[u8]) -> String {
+    let digest = Sha256::digest(public_pem);
+    URL_SAFE_NO_PAD.encode(&digest[..12])
+}
+
+fn ed25519_public_key(public_pem: &[u8]) -> Result<Vec<u8>> {
+    let pem = std::str::from_utf8(public_pem)?;
+    let body: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    let der = base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .context("invalid Ed25519 public key PEM")?;
+
+    match der.strip_prefix(&ED25519_SPKI_PREFIX[..]) {
+        Some(raw) if raw.len() == 32 => Ok(raw.to_vec()),
+        _ => bail!("expected an Ed25519 SubjectPublicKeyInfo"),
+    }
+}
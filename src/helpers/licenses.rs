@@ -0,0 +1,52 @@
+//! Licenses attached to posts.
+//!
+//! A license is either an SPDX identifier or free text such as "All rights
+//! reserved". Identifiers of common content licenses are recognized in any
+//! case and stored in their canonical spelling.
+
+/// Longest license text accepted
+pub const MAX_LICENSE_LENGTH: usize = 200;
+
+/// SPDX identifiers of licenses commonly used for written content
+const KNOWN_LICENSES: [&str; 10] = [
+    "CC0-1.0",
+    "CC-BY-4.0",
+    "CC-BY-SA-4.0",
+    "CC-BY-ND-4.0",
+    "CC-BY-NC-4.0",
+    "CC-BY-NC-SA-4.0",
+    "CC-BY-NC-ND-4.0",
+    "MIT",
+    "Apache-2.0",
+    "GFDL-1.3-or-later",
+];
+
+/// The canonical SPDX identifier when `license` names a known one
+pub fn spdx_identifier(license: &str) -> Option<&'static str> {
+    KNOWN_LICENSES
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(license))
+        .copied()
+}
+
+/// Trims `license` and canonicalizes known identifiers; returns the message to
+/// show when it is refused
+pub fn normalize_license(license: &str) -> Result<String, String> {
+    let license = license.trim();
+    if license.is_empty() {
+        return Err("License cannot be empty".to_string());
+    }
+    if license.chars().count() > MAX_LICENSE_LENGTH {
+        return Err(format!(
+            "License can be at most {} characters",
+            MAX_LICENSE_LENGTH
+        ));
+    }
+    if license.chars().any(char::is_control) {
+        return Err("License cannot contain control characters".to_string());
+    }
+
+    Ok(spdx_identifier(license)
+        .map(str::to_string)
+        .unwrap_or_else(|| license.to_string()))
+}
@@ -0,0 +1,178 @@
+//! Opaque single-use tokens for links sent by email.
+//!
+//! A link carries 32 random bytes, base64url-encoded, instead of a JWT, so
+//! the copy left in server logs and browser history names nobody and stops
+//! working once the link is followed. Only the SHA-256 of a token is stored,
+//! with its user, what it is for, what it acts on when that isn't just the
+//! user, and when it expires; redeeming it deletes the row.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::db::repositories::link_token_repo::LinkTokenRepository;
+
+/// Random bytes in a token
+pub const TOKEN_BYTES: usize = 32;
+
+/// What a link token lets its holder do; a token only works for its purpose
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkPurpose {
+    VerifyEmail,
+    /// Signs out the session named by the token's subject
+    RevokeSession,
+}
+
+impl LinkPurpose {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LinkPurpose::VerifyEmail => "verify_email",
+            LinkPurpose::RevokeSession => "revoke_session",
+        }
+    }
+}
+
+/// A fresh token, as it goes in a link
+pub fn new_link_token() -> String {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    rand::rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// The form a token is stored in
+pub fn hash_link_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// What a redeemed token was issued for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkGrant {
+    pub user_id: Uuid,
+    /// What the link acts on besides the user, like the session a revoke
+    /// link signs out
+    pub subject: Option<String>,
+}
+
+pub type LinkTokenFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Where link tokens are kept: the `link_tokens` table, or memory in tests
+pub trait LinkTokenStore: Send + Sync {
+    /// Issues a token for `user_id`, and `subject` if the purpose needs one,
+    /// that works until `expires_at`
+    fn issue<'a>(
+        &'a self,
+        user_id: Uuid,
+        purpose: LinkPurpose,
+        subject: Option<&'a str>,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> LinkTokenFuture<'a, String>;
+
+    /// What `token` was issued for, if it is live and for `purpose`; once
+    /// redeemed, it is gone
+    fn redeem<'a>(
+        &'a self,
+        token: &'a str,
+        purpose: LinkPurpose,
+        now: DateTime<Utc>,
+    ) -> LinkTokenFuture<'a, Option<LinkGrant>>;
+}
+
+impl LinkTokenStore for LinkTokenRepository {
+    fn issue<'a>(
+        &'a self,
+        user_id: Uuid,
+        purpose: LinkPurpose,
+        subject: Option<&'a str>,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> LinkTokenFuture<'a, String> {
+        Box::pin(async move {
+            let token = new_link_token();
+            self.insert(
+                &hash_link_token(&token),
+                user_id,
+                purpose.as_str(),
+                subject,
+                now,
+                expires_at,
+            )
+            .await?;
+            Ok(token)
+        })
+    }
+
+    fn redeem<'a>(
+        &'a self,
+        token: &'a str,
+        purpose: LinkPurpose,
+        now: DateTime<Utc>,
+    ) -> LinkTokenFuture<'a, Option<LinkGrant>> {
+        Box::pin(async move {
+            self.take(&hash_link_token(token), purpose.as_str(), now)
+                .await
+        })
+    }
+}
+
+/// Grant, purpose and expiry of an issued token
+type IssuedToken = (LinkGrant, LinkPurpose, DateTime<Utc>);
+
+/// Keeps link tokens in memory, for tests
+#[derive(Debug, Default)]
+pub struct MemoryLinkTokens {
+    tokens: Mutex<HashMap<String, IssuedToken>>,
+}
+
+impl MemoryLinkTokens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LinkTokenStore for MemoryLinkTokens {
+    fn issue<'a>(
+        &'a self,
+        user_id: Uuid,
+        purpose: LinkPurpose,
+        subject: Option<&'a str>,
+        _now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> LinkTokenFuture<'a, String> {
+        let token = new_link_token();
+        let grant = LinkGrant {
+            user_id,
+            subject: subject.map(str::to_string),
+        };
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(hash_link_token(&token), (grant, purpose, expires_at));
+        Box::pin(async move { Ok(token) })
+    }
+
+    fn redeem<'a>(
+        &'a self,
+        token: &'a str,
+        purpose: LinkPurpose,
+        now: DateTime<Utc>,
+    ) -> LinkTokenFuture<'a, Option<LinkGrant>> {
+        let hash = hash_link_token(token);
+        let mut tokens = self.tokens.lock().unwrap();
+        let live = tokens
+            .get(&hash)
+            .is_some_and(|&(_, issued_for, expires_at)| issued_for == purpose && expires_at > now);
+        let grant = live
+            .then(|| tokens.remove(&hash))
+            .flatten()
+            .map(|(grant, _, _)| grant);
+        Box::pin(async move { Ok(grant) })
+    }
+}
@@ -0,0 +1,95 @@
+//! Socket and connection settings for the HTTP and HTTPS servers.
+//!
+//! Long-lived streaming connections and bursty REST traffic share the same
+//! listener, so the backlog, `TCP_NODELAY`, keep-alive and HTTP/2 stream
+//! limits are taken from [`ServerConfig`] instead of the library defaults.
+
+use std::{
+    future::{Ready, ready},
+    io,
+    net::SocketAddr,
+    sync::Arc,
+};
+
+use axum_server::{accept::Accept, tls_rustls::RustlsConfig};
+use hyper_util::{
+    rt::{TokioExecutor, TokioTimer},
+    server::conn::auto::Builder,
+};
+use tokio::net::{TcpSocket, TcpStream};
+use tracing::info;
+
+use crate::config::ServerConfig;
+
+/// Binds `addr` with the configured backlog
+pub fn bind(addr: SocketAddr, config: &ServerConfig) -> io::Result<std::net::TcpListener> {
+    let socket = match addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+    socket.set_reuseaddr(true)?;
+    socket.bind(addr)?;
+    socket.listen(config.tcp_backlog)?.into_std()
+}
+
+/// Applies the keep-alive, header timeout and HTTP/2 settings to a server's connections
+pub fn tune(builder: &mut Builder<TokioExecutor>, config: &ServerConfig) {
+    if !config.http2 {
+        *builder = builder.clone().http1_only();
+    }
+
+    builder
+        .http1()
+        .timer(TokioTimer::new())
+        .keep_alive(config.http1_keep_alive)
+        .header_read_timeout(config.http1_header_read_timeout);
+    builder
+        .http2()
+        .timer(TokioTimer::new())
+        .max_concurrent_streams(config.http2_max_concurrent_streams)
+        .keep_alive_interval(config.http2_keep_alive_interval)
+        .keep_alive_timeout(config.http2_keep_alive_timeout);
+
+    info!(
+        "HTTP/2 {}, keep-alive {}, nodelay {}, backlog {}",
+        if config.http2 { "enabled" } else { "disabled" },
+        config.http1_keep_alive,
+        config.tcp_nodelay,
+        config.tcp_backlog
+    );
+}
+
+/// Stops offering `h2` during the TLS handshake when HTTP/2 is disabled
+pub fn restrict_alpn(rustls_config: &RustlsConfig, config: &ServerConfig) {
+    if config.http2 {
+        return;
+    }
+
+    let mut inner = (*rustls_config.get_inner()).clone();
+    inner.alpn_protocols = vec![b"http/1.1".to_vec()];
+    rustls_config.reload_from_config(Arc::new(inner));
+}
+
+/// Sets `TCP_NODELAY` on accepted connections as configured
+#[derive(Clone, Copy, Debug)]
+pub struct TcpAcceptor {
+    nodelay: bool,
+}
+
+impl TcpAcceptor {
+    pub fn new(config: &ServerConfig) -> Self {
+        Self {
+            nodelay: config.tcp_nodelay,
+        }
+    }
+}
+
+impl<S> Accept<TcpStream, S> for TcpAcceptor {
+    type Stream = TcpStream;
+    type Service = S;
+    type Future = Ready<io::Result<(TcpStream, S)>>;
+
+    fn accept(&self, stream: TcpStream, service: S) -> Self::Future {
+        ready(stream.set_nodelay(self.nodelay).map(|()| (stream, service)))
+    }
+}
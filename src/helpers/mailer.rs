@@ -0,0 +1,71 @@
+//! Outgoing email delivery.
+//!
+//! Handlers send through the [`Mailer`] in the app state: Resend by default,
+//! or a [`MemoryMailer`] that keeps every email so examples and tests can read
+//! the links in them.
+
+use std::{pin::Pin, sync::Mutex};
+
+use anyhow::Result;
+use tracing::info;
+
+use crate::helpers::email_templates::RenderedEmail;
+
+pub type MailFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+pub trait Mailer: Send + Sync {
+    /// `resend` or `memory`
+    fn name(&self) -> &'static str;
+    fn send<'a>(&'a self, to: &'a str, email: &'a RenderedEmail) -> MailFuture<'a>;
+}
+
+#[derive(Debug, Clone)]
+pub struct SentEmail {
+    pub to: String,
+    pub email: RenderedEmail,
+}
+
+/// Keeps sent emails in memory instead of delivering them
+#[derive(Debug, Default)]
+pub struct MemoryMailer {
+    sent: Mutex<Vec<SentEmail>>,
+}
+
+impl MemoryMailer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every email sent so far, oldest first
+    pub fn sent(&self) -> Vec<SentEmail> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// The most recent email sent to `to`
+    pub fn last_to(&self, to: &str) -> Option<RenderedEmail> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|sent| sent.to == to)
+            .map(|sent| sent.email.clone())
+    }
+}
+
+impl Mailer for MemoryMailer {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn send<'a>(&'a self, to: &'a str, email: &'a RenderedEmail) -> MailFuture<'a> {
+        Box::pin(async move {
+            info!("Captured email to {}: {}", to, email.subject);
+            self.sent.lock().unwrap().push(SentEmail {
+                to: to.to_string(),
+                email: email.clone(),
+            });
+            Ok(())
+        })
+    }
+}
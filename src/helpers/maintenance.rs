@@ -0,0 +1,96 @@
+//! Maintenance mode: writes answer `503` with `Retry-After` while reads keep
+//! working.
+//!
+//! It is on while the `maintenance` feature flag is set through the admin API,
+//! which keeps it across restarts, or while `MAINTENANCE_MODE` forces it.
+//! Admins can still write, and anyone can sign in or out, so an admin can
+//! always get in to end it. An admin token only counts while its session is
+//! live and the account in good standing.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::config::MaintenanceConfig;
+use crate::helpers::auth::AuthHelper;
+use crate::helpers::feature_flags::FeatureFlags;
+use crate::helpers::middleware::{SessionGuard, is_state_changing, request_token};
+use crate::model::model::{ErrorResponse, FeatureFlag, MaintenanceStatus, Role};
+
+/// Writes that stay open during maintenance
+const EXEMPT_PATHS: [&str; 3] = ["/auth/login", "/auth/logout", "/auth/revoke-session"];
+
+#[derive(Clone)]
+pub struct MaintenanceGate {
+    pub flags: Arc<FeatureFlags>,
+    pub guard: SessionGuard,
+    pub config: MaintenanceConfig,
+}
+
+impl MaintenanceGate {
+    pub fn is_active(&self) -> bool {
+        self.config.forced || self.flags.is_enabled(FeatureFlag::Maintenance)
+    }
+
+    pub fn status(&self) -> MaintenanceStatus {
+        MaintenanceStatus {
+            enabled: self.is_active(),
+            forced: self.config.forced,
+            retry_after_secs: self.config.retry_after.as_secs(),
+        }
+    }
+
+    /// Whether `token` belongs to an admin who may still use it
+    async fn is_admin(&self, token: &str) -> bool {
+        let clock = self.guard.clock.as_ref();
+        if AuthHelper::extract_user_role_from_token(token, clock).ok() != Some(Role::ADMIN) {
+            return false;
+        }
+        let Ok(user_id) = AuthHelper::extract_user_id_from_token(token, clock) else {
+            return false;
+        };
+        self.guard.check(token, user_id).await.is_ok()
+    }
+
+    fn rejection(&self) -> Response {
+        let mut response = ErrorResponse::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Maintenance",
+            "The API is down for maintenance; reads still work, but changes are paused",
+        )
+        .into_response();
+        response.headers_mut().insert(
+            header::RETRY_AFTER,
+            HeaderValue::from(self.config.retry_after.as_secs()),
+        );
+        response
+    }
+}
+
+/// Rejects writes by anyone but admins while maintenance mode is on; expects
+/// paths without the API prefix
+pub async fn maintenance_middleware(
+    State(gate): State<MaintenanceGate>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !is_state_changing(request.method())
+        || EXEMPT_PATHS.contains(&request.uri().path())
+        || !gate.is_active()
+    {
+        return next.run(request).await;
+    }
+
+    if let Some(token) = request_token(request.headers())
+        && gate.is_admin(&token).await
+    {
+        return next.run(request).await;
+    }
+
+    gate.rejection()
+}
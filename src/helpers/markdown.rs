@@ -0,0 +1,239 @@
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd, TextMergeStream, html};
+
+use crate::helpers::slugs::{MAX_USERNAME_LENGTH, MIN_USERNAME_LENGTH};
+
+/// An image referenced by post Markdown, with its alt text as written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownImage {
+    pub url: String,
+    /// `None` when the image has no alt text, or only whitespace
+    pub alt: Option<String>,
+}
+
+pub(crate) fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+}
+
+/// Renders post Markdown to HTML with anything unsafe (scripts, event
+/// handlers, `javascript:` links) stripped
+pub fn render_markdown(source: &str) -> String {
+    let mut rendered = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut rendered, Parser::new_ext(source, options()));
+
+    ammonia::clean(&rendered)
+}
+
+/// The stored rendering, or a fresh one for posts written before it was cached
+pub fn cached_or_render(cached: Option<String>, source: &str) -> String {
+    cached.unwrap_or_else(|| render_markdown(source))
+}
+
+/// `value` with the characters HTML gives meaning escaped, for text and
+/// attribute values
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Images in post Markdown, in document order. Alt text is the image's
+/// description with formatting dropped, as it appears in the rendered `alt`.
+pub fn extract_images(source: &str) -> Vec<MarkdownImage> {
+    let mut images = Vec::new();
+    // Alt text of the images being read; images can nest inside descriptions
+    let mut open: Vec<(String, String)> = Vec::new();
+
+    for event in Parser::new_ext(source, options()) {
+        match event {
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                open.push((dest_url.to_string(), String::new()));
+            }
+            Event::End(TagEnd::Image) => {
+                if let Some((url, alt)) = open.pop() {
+                    let alt = alt.trim();
+                    images.push(MarkdownImage {
+                        url,
+                        alt: (!alt.is_empty()).then(|| alt.to_string()),
+                    });
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, alt)) = open.last_mut() {
+                    alt.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some((_, alt)) = open.last_mut() {
+                    alt.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    images
+}
+
+/// Usernames mentioned as `@username` in post Markdown, lowercased, in order
+/// of first mention. Code is skipped, and so is an `@` inside a word, as in an
+/// email address.
+pub fn extract_mentions(source: &str) -> Vec<String> {
+    let mut usernames: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    for event in TextMergeStream::new(Parser::new_ext(source, options())) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(text) if !in_code_block => {
+                for username in mentions_in(&text) {
+                    if !usernames.contains(&username) {
+                        usernames.push(username);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    usernames
+}
+
+fn mentions_in(text: &str) -> Vec<String> {
+    let is_username_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut found = Vec::new();
+    let mut previous = None;
+
+    for (start, c) in text.char_indices() {
+        if c == '@' && !previous.is_some_and(is_username_char) {
+            let rest = &text[start + 1..];
+            let end = rest
+                .find(|c: char| !is_username_char(c))
+                .unwrap_or(rest.len());
+            let username = rest[..end].to_ascii_lowercase();
+            if (MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&username.len())
+                && username.starts_with(|c: char| c.is_ascii_lowercase())
+            {
+                found.push(username);
+            }
+        }
+        previous = Some(c);
+    }
+
+    found
+}
+
+/// Words per minute assumed when estimating reading time
+pub const READING_WORDS_PER_MINUTE: usize = 200;
+/// Sentences kept in a post's excerpt
+pub const EXCERPT_SENTENCES: usize = 2;
+/// Upper bound on an excerpt, for posts whose sentences run long
+pub const EXCERPT_MAX_CHARS: usize = 300;
+
+/// What index pages show of a post instead of its full content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostSummary {
+    pub word_count: i32,
+    /// Rounded up; `0` only for posts without text
+    pub reading_time_minutes: i32,
+    /// The first [`EXCERPT_SENTENCES`] sentences as plain text
+    pub excerpt: String,
+}
+
+/// The text of post Markdown without formatting or HTML, whitespace collapsed
+fn flatten(source: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new_ext(source, options()) {
+        match event {
+            Event::Text(fragment) | Event::Code(fragment) => text.push_str(&fragment),
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::Item
+                | TagEnd::CodeBlock
+                | TagEnd::TableCell,
+            ) => text.push(' '),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `text` cut at a word boundary to at most `max_chars` characters
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    // Leave room for the ellipsis and don't end mid-word
+    let keep = max_chars.saturating_sub(1);
+    let cut: String = text.chars().take(keep).collect();
+    let cut = match text.chars().nth(keep) {
+        Some(' ') => cut.as_str(),
+        _ => cut.rfind(' ').map_or(cut.as_str(), |space| &cut[..space]),
+    };
+    format!("{}…", cut)
+}
+
+/// The start of `text` up to the end of its `count`th sentence, or all of it
+/// when it has fewer
+fn first_sentences(text: &str, count: usize) -> &str {
+    let mut ends = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let ends_sentence = matches!(c, '.' | '!' | '?')
+            && chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+        if ends_sentence {
+            ends += 1;
+            if ends == count {
+                return &text[..index + c.len_utf8()];
+            }
+        }
+    }
+    text
+}
+
+/// The text of post Markdown without formatting, whitespace collapsed and cut
+/// at a word boundary to at most `max_chars` characters, for summaries
+pub fn plain_text(source: &str, max_chars: usize) -> String {
+    truncate(&flatten(source), max_chars)
+}
+
+/// Word count, reading time and excerpt of post Markdown, stored with the
+/// post whenever its content changes
+pub fn summarize(source: &str) -> PostSummary {
+    let text = flatten(source);
+    let word_count = text.split_whitespace().count();
+
+    PostSummary {
+        word_count: word_count as i32,
+        reading_time_minutes: word_count.div_ceil(READING_WORDS_PER_MINUTE) as i32,
+        excerpt: truncate(first_sentences(&text, EXCERPT_SENTENCES), EXCERPT_MAX_CHARS),
+    }
+}
+
+/// The stored summary, or a fresh one for posts written before it was stored
+pub fn stored_or_summarize(
+    word_count: Option<i32>,
+    reading_time_minutes: Option<i32>,
+    excerpt: Option<String>,
+    source: &str,
+) -> PostSummary {
+    match (word_count, reading_time_minutes, excerpt) {
+        (Some(word_count), Some(reading_time_minutes), Some(excerpt)) => PostSummary {
+            word_count,
+            reading_time_minutes,
+            excerpt,
+        },
+        _ => summarize(source),
+    }
+}
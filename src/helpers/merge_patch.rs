@@ -0,0 +1,145 @@
+//! RFC 7396 JSON Merge Patch bodies for `PATCH` routes.
+//!
+//! A merge patch names only the fields to change: an absent member keeps the
+//! current value and `null` removes it. [`Patch`] keeps those two apart where
+//! `Option` can't, and [`MergePatch`] extracts a patch body into a struct of
+//! them.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{StatusCode, header},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::model::model::ErrorResponse;
+
+pub const MERGE_PATCH_JSON: &str = "application/merge-patch+json";
+
+/// One member of a merge patch; fields of this type need `#[serde(default)]`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Absent,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Patch::Null)
+    }
+
+    pub fn as_value(&self) -> Option<&T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_value_mut(&mut self) -> Option<&mut T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The new value; `None` both when absent and when null
+    pub fn into_value(self) -> Option<T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The patched version of a nullable `current` value
+    pub fn apply(self, current: Option<T>) -> Option<T> {
+        match self {
+            Patch::Absent => current,
+            Patch::Null => None,
+            Patch::Value(value) => Some(value),
+        }
+    }
+}
+
+/// Bodies where a missing field means "unchanged" and null isn't possible
+impl<T> From<Option<T>> for Patch<T> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Patch::Absent, Patch::Value)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Only present members reach here; absent ones take the default
+        Ok(Option::<T>::deserialize(deserializer)?.map_or(Patch::Null, Patch::Value))
+    }
+}
+
+/// Absent and null members both write `null`, which is how the schema shows
+/// a patch's default
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Value(value) => value.serialize(serializer),
+            _ => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Extracts a merge patch sent as `application/merge-patch+json`; plain
+/// `application/json` is accepted too
+///
+/// The patch must be a JSON object, since replacing a whole resource with a
+/// scalar or array isn't meaningful here.
+pub struct MergePatch<T>(pub T);
+
+impl<T, S> FromRequest<S> for MergePatch<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ErrorResponse;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase());
+        if !matches!(
+            content_type.as_deref(),
+            Some(MERGE_PATCH_JSON | "application/json")
+        ) {
+            return Err(ErrorResponse::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Unsupported Media Type",
+                format!("Send the patch as {}", MERGE_PATCH_JSON),
+            ));
+        }
+
+        let body = Bytes::from_request(req, state).await.map_err(|e| {
+            ErrorResponse::new(StatusCode::BAD_REQUEST, "Invalid Patch", e.body_text())
+        })?;
+        parse_merge_patch(&body).map(MergePatch).map_err(|e| *e)
+    }
+}
+
+/// Parses a merge patch body into `T`
+pub fn parse_merge_patch<T: DeserializeOwned>(body: &[u8]) -> Result<T, Box<ErrorResponse>> {
+    let invalid = |detail: String| {
+        Box::new(ErrorResponse::new(
+            StatusCode::BAD_REQUEST,
+            "Invalid Patch",
+            detail,
+        ))
+    };
+
+    let patch: Value = serde_json::from_slice(body).map_err(|e| invalid(e.to_string()))?;
+    if !patch.is_object() {
+        return Err(invalid("A merge patch must be a JSON object".to_string()));
+    }
+    serde_json::from_value(patch).map_err(|e| invalid(e.to_string()))
+}
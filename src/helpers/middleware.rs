@@ -1,36 +1,41 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
 use axum::{
-    Json,
-    extract::Request,
-    http::{StatusCode, header},
+    extract::{FromRef, FromRequestParts, Request, State},
+    http::{HeaderMap, Method, StatusCode, header, request::Parts},
     middleware::Next,
     response::Response,
 };
 
+use crate::db::repositories::audit_repo::AuditRepository;
+use crate::db::repositories::org_repo::OrgRepository;
+use crate::db::repositories::session_repo::SessionRepository;
+use crate::db::repositories::user_repo::UserRepository;
 use crate::helpers::auth::AuthHelper;
-use crate::model::model::{ErrorResponse, Role};
+use crate::helpers::clock::Clock;
+use crate::helpers::cookies::AUTH_COOKIE;
+use crate::helpers::denylist::TokenDenylist;
+use crate::helpers::request_log::AuthenticatedUser;
+use crate::model::model::{
+    ErrorResponse, Impersonator, OrgContext, Role, SessionId, UserStanding, UserStatus,
+};
+use crate::state::AppState;
 
+use sqlx::PgPool;
 use tracing::{error, info};
+use uuid::Uuid;
 
 pub async fn auth_middleware(
+    State(state): State<AppState>,
     mut request: Request,
     next: Next,
-) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Response, ErrorResponse> {
     // First try to get token from cookies
-    let mut token_opt = None;
-
-    // Extract cookies from request headers
-    if let Some(cookie_header) = request.headers().get(header::COOKIE) {
-        if let Ok(cookie_str) = cookie_header.to_str() {
-            // Parse cookies manually to find auth_token
-            for cookie_part in cookie_str.split(';') {
-                let cookie_part = cookie_part.trim();
-                if cookie_part.starts_with("auth_token=") {
-                    token_opt = Some(cookie_part[11..].to_string());
-                    info!("Found auth token in cookies");
-                    break;
-                }
-            }
-        }
+    let mut token_opt = get_cookie(request.headers(), AUTH_COOKIE);
+    let cookie_auth = token_opt.is_some();
+    if cookie_auth {
+        info!("Found auth token in cookies");
     }
 
     // If no cookie token found, try Authorization header
@@ -49,66 +54,358 @@ pub async fn auth_middleware(
             });
     }
 
+    // Cookie-authenticated state-changing requests must echo the CSRF cookie
+    // in the X-CSRF-Token header (double-submit). Bearer requests are exempt.
+    if cookie_auth
+        && is_state_changing(request.method())
+        && let Err(message) = verify_csrf(request.headers())
+    {
+        error!("CSRF validation failed: {}", message);
+        return Err(ErrorResponse::new(
+            StatusCode::FORBIDDEN,
+            "Forbidden",
+            message,
+        ));
+    }
+
     let token = match token_opt {
         Some(token) => token,
         None => {
             error!("No authentication found - neither cookie nor Authorization header");
-            return Err((
+            return Err(ErrorResponse::new(
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "Unauthorized".to_string(),
-                    message: "Authentication required - provide either auth_token cookie or Authorization header".to_string(),
-                }),
+                "Unauthorized",
+                "Authentication required - provide either auth_token cookie or Authorization header",
             ));
         }
     };
 
-    let user_id = match AuthHelper::extract_user_id_from_token(&token) {
+    let user_id = match AuthHelper::extract_user_id_from_token(&token, state.clock.as_ref()) {
         Ok(user_id) => user_id,
         Err(err) => {
             error!("Token validation failed: {}", err);
-            return Err((
+            return Err(ErrorResponse::new(
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "Unauthorized".to_string(),
-                    message: "Invalid or expired token".to_string(),
-                }),
+                "Unauthorized",
+                "Invalid or expired token",
             ));
         }
     };
 
-    let user_role = match AuthHelper::extract_user_role_from_token(&token) {
+    let user_role = match AuthHelper::extract_user_role_from_token(&token, state.clock.as_ref()) {
         Ok(role) => role,
         Err(err) => {
             error!("Role extraction failed: {}", err);
-            return Err((
+            return Err(ErrorResponse::new(
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "Unauthorized".to_string(),
-                    message: "Invalid token format".to_string(),
-                }),
+                "Unauthorized",
+                "Invalid token format",
             ));
         }
     };
 
+    let (session_id, standing) = SessionGuard::from_ref(&state)
+        .check(&token, user_id)
+        .await?;
+    if let Some(session_id) = session_id {
+        request.extensions_mut().insert(SessionId(session_id));
+    }
+
+    // Every request made while impersonating lands in the audit log
+    let impersonator = AuthHelper::extract_impersonator_from_token(&token, state.clock.as_ref())
+        .ok()
+        .flatten();
+    if let Some(admin_id) = impersonator {
+        let target = match &standing {
+            Some(standing) => format!("user:{}", standing.user_id),
+            None => format!("user:{}", user_id),
+        };
+        let detail = format!("{} {}", request.method(), request.uri().path());
+        let audit = AuditRepository::new((*state.pool).clone());
+        if let Err(err) = audit
+            .record(
+                admin_id,
+                "impersonation.request",
+                &target,
+                Some(&detail),
+                state.clock.now(),
+            )
+            .await
+        {
+            error!("Failed to audit impersonated request: {}", err);
+            return Err(ErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database Error",
+                "Unable to record impersonated request",
+            ));
+        }
+        request.extensions_mut().insert(Impersonator(admin_id));
+    }
+
     info!(
         "Authenticated user_id: {} with role: {:?}",
         user_id, user_role
     );
     request.extensions_mut().insert(user_id);
     request.extensions_mut().insert(user_role);
-    Ok(next.run(request).await)
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(AuthenticatedUser(user_id));
+    Ok(response)
+}
+
+/// Checks that a validly signed token may still be used: its login session
+/// hasn't been revoked and the account isn't banned or suspended
+#[derive(Clone)]
+pub struct SessionGuard {
+    pub pool: Arc<PgPool>,
+    pub denylist: Arc<TokenDenylist>,
+    pub clock: Arc<dyn Clock>,
+}
+
+impl SessionGuard {
+    /// The token's session, if it has one, and the account's standing
+    pub async fn check(
+        &self,
+        token: &str,
+        user_id: Uuid,
+    ) -> Result<(Option<String>, Option<UserStanding>), ErrorResponse> {
+        // Tokens tied to a login session stop working once it is revoked
+        let session_id = AuthHelper::extract_session_id_from_token(token, self.clock.as_ref())
+            .ok()
+            .flatten();
+        if let Some(session_id) = &session_id {
+            // Revocations reach every replica through the denylist before the database
+            if self.denylist.is_denied(session_id).await {
+                error!("Rejected token for denylisted session");
+                return Err(ErrorResponse::new(
+                    StatusCode::UNAUTHORIZED,
+                    "Unauthorized",
+                    "Session has been revoked or has expired",
+                ));
+            }
+
+            let sessions = SessionRepository::new((*self.pool).clone());
+            match sessions.touch(session_id, user_id, self.clock.now()).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    error!("Rejected token for revoked or expired session");
+                    return Err(ErrorResponse::new(
+                        StatusCode::UNAUTHORIZED,
+                        "Unauthorized",
+                        "Session has been revoked or has expired",
+                    ));
+                }
+                Err(err) => {
+                    error!("Session lookup failed: {}", err);
+                    return Err(ErrorResponse::new(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Database Error",
+                        "Unable to verify session",
+                    ));
+                }
+            }
+        }
+
+        // Restricting an account revokes its sessions; this also covers tokens
+        // issued without one
+        let users = UserRepository::new((*self.pool).clone());
+        match users.standing(user_id, self.clock.now()).await {
+            Ok(Some(standing)) if standing.is_restricted() => {
+                error!("Rejected token for {:?} user {}", standing.status, user_id);
+                let title = match standing.status {
+                    UserStatus::BANNED => "Account Banned",
+                    _ => "Account Suspended",
+                };
+                Err(ErrorResponse::new(
+                    StatusCode::FORBIDDEN,
+                    title,
+                    standing.describe(),
+                ))
+            }
+            Ok(standing) => Ok((session_id, standing)),
+            Err(err) => {
+                error!("Account status lookup failed: {}", err);
+                Err(ErrorResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Database Error",
+                    "Unable to verify account status",
+                ))
+            }
+        }
+    }
+}
+
+/// The auth token from the `auth_token` cookie or a Bearer header, unvalidated
+pub fn request_token(headers: &HeaderMap) -> Option<String> {
+    get_cookie(headers, AUTH_COOKIE).or_else(|| {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::to_string)
+    })
+}
+
+/// The signed-in user on routes that don't require auth. A token that would
+/// be rejected there, including one for a revoked session or a restricted
+/// account, counts as anonymous
+pub struct OptionalUser(pub Option<Uuid>);
+
+impl<S> FromRequestParts<S> for OptionalUser
+where
+    SessionGuard: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let guard = SessionGuard::from_ref(state);
+        let Some(token) = request_token(&parts.headers) else {
+            return Ok(OptionalUser(None));
+        };
+        let Ok(user_id) = AuthHelper::extract_user_id_from_token(&token, guard.clock.as_ref())
+        else {
+            return Ok(OptionalUser(None));
+        };
+        match guard.check(&token, user_id).await {
+            Ok(_) => Ok(OptionalUser(Some(user_id))),
+            Err(err) => {
+                info!("Treating request as anonymous: {}", err.detail);
+                Ok(OptionalUser(None))
+            }
+        }
+    }
 }
 
-pub fn check_admin_role(role: &Role) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+pub fn check_admin_role(role: &Role) -> Result<(), Box<ErrorResponse>> {
     match role {
         Role::ADMIN => Ok(()),
-        Role::USER => Err((
+        Role::USER => Err(Box::new(ErrorResponse::new(
+            StatusCode::FORBIDDEN,
+            "Forbidden",
+            "Admin access required",
+        ))),
+    }
+}
+
+/// Refuses a route to impersonation tokens. Admins acting as a user may look
+/// around and reproduce problems, but not take over or carry off the account
+/// by changing its password, deleting or exporting it, or revoking sessions.
+pub async fn forbid_impersonation(request: Request, next: Next) -> Result<Response, ErrorResponse> {
+    if let Some(Impersonator(admin_id)) = request.extensions().get::<Impersonator>() {
+        info!(
+            "Refusing {} {} to admin {} impersonating a user",
+            request.method(),
+            request.uri().path(),
+            admin_id
+        );
+        return Err(ErrorResponse::new(
             StatusCode::FORBIDDEN,
-            Json(ErrorResponse {
-                error: "Forbidden".to_string(),
-                message: "Admin access required".to_string(),
-            }),
-        )),
+            "Forbidden",
+            "Not allowed while impersonating a user",
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
+pub const ORG_HEADER: &str = "x-org-id";
+
+/// Resolves `X-Org-Id` into an [`OrgContext`] extension, including the caller's
+/// membership role when the request was authenticated.
+pub async fn org_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ErrorResponse> {
+    let org_public_id = match request
+        .headers()
+        .get(ORG_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(org_public_id) => org_public_id.to_string(),
+        None => return Ok(next.run(request).await),
+    };
+
+    let repo = OrgRepository::new((*state.pool).clone());
+
+    let org = match repo.find_by_public_id(&org_public_id).await {
+        Ok(Some(org)) => org,
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                StatusCode::NOT_FOUND,
+                "Not Found",
+                "Organization not found",
+            ));
+        }
+        Err(err) => {
+            error!("Organization lookup failed: {}", err);
+            return Err(ErrorResponse::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database Error",
+                "Unable to resolve organization",
+            ));
+        }
+    };
+
+    let role = match request.extensions().get::<Uuid>().copied() {
+        Some(user_id) => match repo.membership_role(org.id, user_id).await {
+            Ok(role) => role,
+            Err(err) => {
+                error!("Membership lookup failed: {}", err);
+                return Err(ErrorResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Database Error",
+                    "Unable to resolve organization membership",
+                ));
+            }
+        },
+        None => None,
+    };
+
+    request.extensions_mut().insert(OrgContext {
+        org_id: org.id,
+        public_id: org.public_id,
+        role,
+    });
+    Ok(next.run(request).await)
+}
+
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+pub fn get_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    let cookie_str = headers.get(header::COOKIE)?.to_str().ok()?;
+    cookie_str.split(';').find_map(|cookie_part| {
+        let (key, value) = cookie_part.trim().split_once('=')?;
+        (key == name && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+pub fn is_state_changing(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    )
+}
+
+fn verify_csrf(headers: &HeaderMap) -> Result<(), &'static str> {
+    let cookie_token = get_cookie(headers, CSRF_COOKIE).ok_or("Missing CSRF cookie")?;
+    let header_token = headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or("Missing X-CSRF-Token header")?;
+
+    // Constant-time comparison so the token can't be guessed byte by byte
+    let matches = cookie_token.len() == header_token.len()
+        && cookie_token
+            .bytes()
+            .zip(header_token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0;
+
+    if matches {
+        Ok(())
+    } else {
+        Err("CSRF token mismatch")
     }
 }
@@ -1,5 +1,63 @@
+pub mod access;
 pub mod auth;
+pub mod avatar;
+pub mod bulk_posts;
+pub mod chaos;
+pub mod clock;
+pub mod cookies;
+pub mod deliverability;
+pub mod denylist;
+pub mod deprecation;
+pub mod diagnostics;
+pub mod edge_cache;
+pub mod email_policy;
+pub mod email_templates;
+pub mod feature_flags;
+#[cfg(feature = "activitypub")]
+pub mod federation;
+pub mod feeds;
+pub mod fields;
+pub mod http_signatures;
+pub mod i18n;
+pub mod ids;
+pub mod json;
+pub mod jwt_keys;
+pub mod licenses;
+pub mod link_tokens;
+pub mod listener;
+pub mod mailer;
+pub mod maintenance;
+pub mod markdown;
+pub mod merge_patch;
 pub mod middleware;
+pub mod negotiation;
+pub mod notifications;
+pub mod password_strength;
+pub mod path_params;
+pub mod pdf;
+pub mod policy;
+pub mod post_archive;
+pub mod post_export;
+pub mod previews;
+pub mod publish_hooks;
+pub mod redaction;
+pub mod redirects;
+#[cfg(feature = "redis")]
+pub mod redis_client;
+pub mod request_id;
+pub mod request_log;
 pub mod resend;
 pub mod response;
+pub mod secrets;
+pub mod sessions;
+pub mod sign_in_alerts;
+pub mod signup_guard;
+pub mod sitemap;
+pub mod slugs;
+pub mod timestamps;
+pub mod usage;
 pub mod validation;
+pub mod verification;
+pub mod versioning;
+pub mod views;
+pub mod wordpress;
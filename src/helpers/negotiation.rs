@@ -0,0 +1,219 @@
+//! Response body formats picked from the request's `Accept` header.
+//!
+//! JSON is always available; XML and MessagePack come with the `xml` and
+//! `msgpack` features. The format is chosen once per request and kept in a
+//! task-local, so [`Negotiated`] bodies can be encoded without the handler
+//! having to pass the headers along. Error bodies are always JSON.
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use tracing::error;
+
+use crate::helpers::timestamps;
+use crate::model::model::ErrorResponse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    #[cfg(feature = "xml")]
+    Xml,
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+}
+
+impl ResponseFormat {
+    /// Every format this build can produce, JSON first
+    pub const ALL: &'static [ResponseFormat] = &[
+        ResponseFormat::Json,
+        #[cfg(feature = "xml")]
+        ResponseFormat::Xml,
+        #[cfg(feature = "msgpack")]
+        ResponseFormat::MessagePack,
+    ];
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "application/json",
+            #[cfg(feature = "xml")]
+            ResponseFormat::Xml => "application/xml",
+            #[cfg(feature = "msgpack")]
+            ResponseFormat::MessagePack => "application/msgpack",
+        }
+    }
+
+    /// Encodes `value`; XML documents are rooted at `<response>`
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            ResponseFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "xml")]
+            ResponseFormat::Xml => quick_xml::se::to_string_with_root("response", value)
+                .map(String::into_bytes)
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "msgpack")]
+            ResponseFormat::MessagePack => {
+                rmp_serde::to_vec_named(value).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+/// The format to answer an `Accept` header with, or `None` when it accepts
+/// none of them
+///
+/// A missing header accepts anything. Among acceptable formats the highest
+/// `q` wins, then the most specific range (`application/xml` over `*/*`),
+/// then JSON.
+pub fn negotiate(accept: Option<&str>) -> Option<ResponseFormat> {
+    let Some(accept) = accept.filter(|accept| !accept.trim().is_empty()) else {
+        return Some(ResponseFormat::Json);
+    };
+
+    let ranges: Vec<(String, f32)> = accept
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let media = parts.next()?.trim().to_ascii_lowercase();
+            let q = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!media.is_empty()).then_some((media, q))
+        })
+        .collect();
+
+    let mut best: Option<(ResponseFormat, f32, u8)> = None;
+    for &format in ResponseFormat::ALL {
+        let content_type = format.content_type();
+        let main_type = content_type.split('/').next().unwrap_or_default();
+
+        // The most specific matching range decides the format's q
+        let Some((q, specificity)) = ranges
+            .iter()
+            .filter_map(|(media, q)| {
+                let specificity = if media == content_type {
+                    2
+                } else if media.strip_suffix("/*") == Some(main_type) {
+                    1
+                } else if media == "*/*" {
+                    0
+                } else {
+                    return None;
+                };
+                Some((*q, specificity))
+            })
+            .max_by_key(|(_, specificity)| *specificity)
+        else {
+            continue;
+        };
+
+        if q > 0.0
+            && best.is_none_or(|(_, best_q, best_spec)| (q, specificity) > (best_q, best_spec))
+        {
+            best = Some((format, q, specificity));
+        }
+    }
+    best.map(|(format, _, _)| format)
+}
+
+/// Path prefixes of the JSON API, relative to the base path
+pub const API_PREFIXES: &[&str] = &[
+    "/api",
+    "/auth",
+    "/admin",
+    "/posts",
+    "/profiles",
+    "/previews",
+    "/notifications",
+    "/orgs",
+    "/feeds",
+    "/sitemaps",
+    "/limits",
+    "/health",
+    "/.well-known",
+    "/users",
+];
+
+/// Whether `path` belongs to the API rather than a page a browser might open
+pub fn is_api_path(path: &str) -> bool {
+    API_PREFIXES.iter().any(|prefix| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Whether the client is a browser asking for a page
+pub fn prefers_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"))
+}
+
+tokio::task_local! {
+    static FORMAT: Option<ResponseFormat>;
+}
+
+pub async fn negotiation_middleware(request: Request, next: Next) -> Response {
+    let format = negotiate(
+        request
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok()),
+    );
+    FORMAT.scope(format, next.run(request)).await
+}
+
+/// The format negotiated for the current request; JSON outside of one
+pub fn current_format() -> Option<ResponseFormat> {
+    FORMAT
+        .try_with(|format| *format)
+        .unwrap_or(Some(ResponseFormat::Json))
+}
+
+pub fn not_acceptable() -> ErrorResponse {
+    let supported: Vec<&str> = ResponseFormat::ALL
+        .iter()
+        .map(|format| format.content_type())
+        .collect();
+    ErrorResponse::new(
+        StatusCode::NOT_ACCEPTABLE,
+        "Not Acceptable",
+        format!("Supported response types: {}", supported.join(", ")),
+    )
+}
+
+/// A body encoded in the format the client asked for, or 406 Not Acceptable
+pub struct Negotiated<T>(pub T);
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        let Some(format) = current_format() else {
+            return not_acceptable().into_response();
+        };
+
+        match timestamps::while_encoding(|| format.encode(&self.0)) {
+            Ok(body) => (
+                [
+                    (header::CONTENT_TYPE, format.content_type()),
+                    (header::VARY, "accept"),
+                ],
+                body,
+            )
+                .into_response(),
+            Err(e) => {
+                error!("Failed to encode response as {:?}: {}", format, e);
+                ErrorResponse::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Encoding Error",
+                    format!("Unable to encode the response as {}", format.content_type()),
+                )
+                .into_response()
+            }
+        }
+    }
+}
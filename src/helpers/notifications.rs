@@ -0,0 +1,151 @@
+//! In-app notifications.
+//!
+//! Events are recorded in the affected user's inbox as they happen. Failing
+//! to record one is logged and never fails the action that caused it.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::db::repositories::notification_repo::NotificationRepository;
+use crate::model::model::{CollaboratorRole, NotificationKind, ReportAction};
+
+/// Inbox page size when the client doesn't ask for one
+pub const DEFAULT_NOTIFICATION_LIMIT: i64 = 50;
+pub const MAX_NOTIFICATION_LIMIT: i64 = 100;
+
+/// Something that happened to a user's account or content
+#[derive(Debug, Clone, Copy)]
+pub enum NotificationEvent<'a> {
+    AccountSuspended {
+        until: Option<DateTime<Utc>>,
+        reason: Option<&'a str>,
+    },
+    AccountBanned {
+        reason: Option<&'a str>,
+    },
+    AccountReinstated,
+    PostRemoved {
+        post_id: &'a str,
+        reason: Option<&'a str>,
+    },
+    ReportResolved {
+        post_id: &'a str,
+        action: ReportAction,
+    },
+    Mentioned {
+        post_id: &'a str,
+        title: &'a str,
+    },
+    CollaboratorInvited {
+        post_id: &'a str,
+        title: &'a str,
+        role: CollaboratorRole,
+    },
+}
+
+impl NotificationEvent<'_> {
+    pub fn kind(&self) -> NotificationKind {
+        match self {
+            NotificationEvent::AccountSuspended { .. } => NotificationKind::ACCOUNT_SUSPENDED,
+            NotificationEvent::AccountBanned { .. } => NotificationKind::ACCOUNT_BANNED,
+            NotificationEvent::AccountReinstated => NotificationKind::ACCOUNT_REINSTATED,
+            NotificationEvent::PostRemoved { .. } => NotificationKind::POST_REMOVED,
+            NotificationEvent::ReportResolved { .. } => NotificationKind::REPORT_RESOLVED,
+            NotificationEvent::Mentioned { .. } => NotificationKind::MENTIONED,
+            NotificationEvent::CollaboratorInvited { .. } => NotificationKind::COLLABORATOR_INVITED,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        let (message, reason) = match self {
+            NotificationEvent::AccountSuspended {
+                until: Some(until),
+                reason,
+            } => (
+                format!(
+                    "Your account was suspended until {}",
+                    until.format("%Y-%m-%d %H:%M UTC")
+                ),
+                *reason,
+            ),
+            NotificationEvent::AccountSuspended {
+                until: None,
+                reason,
+            } => ("Your account was suspended".to_string(), *reason),
+            NotificationEvent::AccountBanned { reason } => {
+                ("Your account was banned".to_string(), *reason)
+            }
+            NotificationEvent::AccountReinstated => {
+                ("Your account was reinstated".to_string(), None)
+            }
+            NotificationEvent::PostRemoved { reason, .. } => {
+                ("A moderator removed your post".to_string(), *reason)
+            }
+            NotificationEvent::ReportResolved { action, .. } => {
+                let outcome = match action {
+                    ReportAction::DISMISS => "no action was taken",
+                    ReportAction::DELETE => "the post was removed",
+                    ReportAction::SUSPEND => "its author was suspended",
+                };
+                (
+                    format!("A moderator reviewed your report: {}", outcome),
+                    None,
+                )
+            }
+            NotificationEvent::Mentioned { title, .. } => {
+                (format!("You were mentioned in \"{}\"", title), None)
+            }
+            NotificationEvent::CollaboratorInvited { title, role, .. } => {
+                let verb = if role.can_edit() { "edit" } else { "view" };
+                (format!("You were invited to {} \"{}\"", verb, title), None)
+            }
+        };
+
+        match reason {
+            Some(reason) => format!("{}: {}", message, reason),
+            None => message,
+        }
+    }
+
+    /// What the notification links to, as `kind:id`
+    pub fn target(&self) -> Option<String> {
+        match self {
+            NotificationEvent::PostRemoved { post_id, .. }
+            | NotificationEvent::ReportResolved { post_id, .. }
+            | NotificationEvent::Mentioned { post_id, .. }
+            | NotificationEvent::CollaboratorInvited { post_id, .. } => {
+                Some(format!("post:{}", post_id))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Records `event` in the user's inbox; failures are logged
+pub async fn notify(
+    pool: &PgPool,
+    user_id: Uuid,
+    event: NotificationEvent<'_>,
+    now: DateTime<Utc>,
+) {
+    let repo = NotificationRepository::new(pool.clone());
+    if let Err(e) = repo
+        .create(
+            user_id,
+            event.kind(),
+            &event.message(),
+            event.target().as_deref(),
+            now,
+        )
+        .await
+    {
+        error!(
+            "Failed to notify user {} of {:?}: {}",
+            user_id,
+            event.kind(),
+            e
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Password strength estimates with feedback for sign-up forms.
+//!
+//! Strength comes from [`zxcvbn`], which scores a password by how many guesses
+//! an attacker would need, so a long passphrase passes while `Password1!`
+//! doesn't. Passwords from the bundled common-password list are refused
+//! outright, whatever their score.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use zxcvbn::zxcvbn;
+
+use crate::model::model::PasswordStrength;
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Lowest zxcvbn score (0 to 4) a password needs to be accepted
+pub const MIN_PASSWORD_SCORE: u8 = 3;
+
+static COMMON_PASSWORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+
+/// Whether `password` is on the bundled list of common passwords; case-insensitive
+pub fn is_common_password(password: &str) -> bool {
+    COMMON_PASSWORDS
+        .get_or_init(|| include_str!("common_passwords.txt").lines().collect())
+        .contains(password.trim().to_lowercase().as_str())
+}
+
+/// Scores `password`; `user_inputs` such as the name and email make passwords
+/// built from them weaker
+pub fn estimate_password(password: &str, user_inputs: &[&str]) -> PasswordStrength {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return rejected(format!("Use at least {} characters", MIN_PASSWORD_LENGTH));
+    }
+    if is_common_password(password) {
+        return rejected("This is one of the most common passwords".to_string());
+    }
+
+    let estimate = zxcvbn(password, user_inputs);
+    let score = estimate.score() as u8;
+    let (warning, mut suggestions) = match estimate.feedback() {
+        Some(feedback) => (
+            feedback.warning().map(|warning| warning.to_string()),
+            feedback
+                .suggestions()
+                .iter()
+                .map(|suggestion| suggestion.to_string())
+                .collect(),
+        ),
+        None => (None, Vec::new()),
+    };
+    if score < MIN_PASSWORD_SCORE && suggestions.is_empty() {
+        suggestions.push("Add another word or two; uncommon words are better".to_string());
+    }
+
+    PasswordStrength {
+        score,
+        acceptable: score >= MIN_PASSWORD_SCORE,
+        warning,
+        suggestions,
+    }
+}
+
+fn rejected(warning: String) -> PasswordStrength {
+    PasswordStrength {
+        score: 0,
+        acceptable: false,
+        warning: Some(warning),
+        suggestions: vec![
+            "Use a few words that don't go together, or a password manager".to_string(),
+        ],
+    }
+}
@@ -0,0 +1,128 @@
+//! Path parameters that fail to parse get the standard error body.
+//!
+//! Axum's own [`Path`] rejects a malformed parameter, such as `/notifications/abc`
+//! where a UUID is expected, with a plain-text message. [`ValidatedPath`]
+//! extracts the same way but answers 400 in the API's error format, naming
+//! the parameter and what it should have been.
+
+use std::any::type_name;
+
+use axum::{
+    extract::{
+        FromRequestParts, Path, RawPathParams,
+        path::{ErrorKind, FailedToDeserializePathParams},
+        rejection::PathRejection,
+    },
+    http::{StatusCode, request::Parts},
+};
+use serde::de::DeserializeOwned;
+
+use crate::model::model::ErrorResponse;
+
+/// [`Path`] with rejections in the API's error format
+#[derive(Debug, Clone, Copy)]
+pub struct ValidatedPath<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ValidatedPath<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = ErrorResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Path::<T>::from_request_parts(parts, state).await {
+            Ok(Path(value)) => Ok(ValidatedPath(value)),
+            Err(rejection) => {
+                // A lone value's parse error doesn't say which key it was at
+                let only_key = RawPathParams::from_request_parts(parts, state)
+                    .await
+                    .ok()
+                    .and_then(|params| {
+                        let mut keys = params.iter().map(|(key, _)| key.to_string());
+                        keys.next().filter(|_| keys.next().is_none())
+                    });
+                Err(path_rejection::<T>(rejection, only_key.as_deref()))
+            }
+        }
+    }
+}
+
+/// The error body for a path `T` couldn't be extracted from; `only_key` is
+/// the parameter's name when the route has just one
+pub fn path_rejection<T>(rejection: PathRejection, only_key: Option<&str>) -> ErrorResponse {
+    let failed = match rejection {
+        PathRejection::FailedToDeserializePathParams(failed) => failed,
+        other => return ErrorResponse::new(other.status(), "Invalid Path", other.body_text()),
+    };
+    match invalid_parameter::<T>(&failed, only_key) {
+        Some(detail) => {
+            ErrorResponse::new(StatusCode::BAD_REQUEST, "Invalid Path Parameter", detail)
+        }
+        // Mismatches between the route and `T` are bugs, not bad requests
+        None => ErrorResponse::new(failed.status(), "Invalid Path", failed.body_text()),
+    }
+}
+
+fn invalid_parameter<T>(
+    failed: &FailedToDeserializePathParams,
+    only_key: Option<&str>,
+) -> Option<String> {
+    let detail = match failed.kind() {
+        ErrorKind::ParseErrorAtKey {
+            key,
+            value,
+            expected_type,
+        } => format!(
+            "`{}` must be {}, got `{}`",
+            key,
+            describe(expected_type),
+            value
+        ),
+        ErrorKind::ParseErrorAtIndex {
+            index,
+            value,
+            expected_type,
+        } => format!(
+            "Path parameter {} must be {}, got `{}`",
+            index + 1,
+            describe(expected_type),
+            value
+        ),
+        ErrorKind::ParseError {
+            value,
+            expected_type,
+        } => format!(
+            "{} must be {}, got `{}`",
+            only_key.map_or("Path parameter".to_string(), |key| format!("`{}`", key)),
+            describe(expected_type),
+            value
+        ),
+        ErrorKind::DeserializeError { key, value, .. } => {
+            format!(
+                "`{}` must be {}, got `{}`",
+                key,
+                describe(type_name::<T>()),
+                value
+            )
+        }
+        ErrorKind::InvalidUtf8InPathParam { key } => format!("`{}` is not valid UTF-8", key),
+        _ => return None,
+    };
+    Some(detail)
+}
+
+/// What a value of the named type looks like, for error messages
+fn describe(type_name: &str) -> String {
+    if type_name.starts_with('(') {
+        return "a valid value".to_string();
+    }
+    match type_name.rsplit("::").next().unwrap_or(type_name) {
+        "Uuid" => "a UUID".to_string(),
+        "u8" | "u16" | "u32" | "u64" | "usize" => "a non-negative integer".to_string(),
+        "i8" | "i16" | "i32" | "i64" | "isize" => "an integer".to_string(),
+        "f32" | "f64" => "a number".to_string(),
+        "bool" => "`true` or `false`".to_string(),
+        other => format!("a valid {}", other),
+    }
+}
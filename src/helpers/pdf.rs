@@ -0,0 +1,549 @@
+//! A small PDF writer for post exports.
+//!
+//! Posts are laid out as text in Helvetica and Courier, two of the standard
+//! fonts every PDF reader ships with, so nothing is embedded and no renderer
+//! is needed. Headings, paragraphs, lists, quotes and code blocks keep their
+//! shape; images appear as their alt text. Text is written in Windows-1252,
+//! the encoding those fonts come with, and anything outside it is
+//! transliterated.
+
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Utc};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+
+use crate::helpers::markdown;
+
+/// A4, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+/// Indent per level of list or quote nesting
+const INDENT: f32 = 18.0;
+
+/// Document metadata, shown by readers in their properties dialog
+pub struct PdfInfo<'a> {
+    pub title: &'a str,
+    pub author: &'a str,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Mono => "F3",
+        }
+    }
+
+    fn base_font(self) -> &'static str {
+        match self {
+            Font::Regular => "Helvetica",
+            Font::Bold => "Helvetica-Bold",
+            Font::Mono => "Courier",
+        }
+    }
+
+    /// Advance of a Windows-1252 character, in thousandths of the font size
+    fn width(self, byte: u8) -> f32 {
+        let table = match self {
+            Font::Mono => return 600.0,
+            Font::Regular => &HELVETICA,
+            Font::Bold => &HELVETICA_BOLD,
+        };
+        match byte {
+            b' '..=b'~' => f32::from(table[usize::from(byte - b' ')]),
+            // Close enough for the accented letters and punctuation above ASCII
+            _ => 556.0,
+        }
+    }
+
+    fn measure(self, text: &[u8]) -> f32 {
+        text.iter().map(|&byte| self.width(byte)).sum()
+    }
+}
+
+/// Helvetica advances for ` ` through `~`, from the font's AFM metrics
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Helvetica-Bold advances for ` ` through `~`
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// A piece of the post laid out on its own lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Heading { level: HeadingLevel, text: String },
+    Paragraph { text: String, depth: usize },
+    Code { text: String, depth: usize },
+    Rule,
+}
+
+/// Splits post Markdown into blocks of plain text
+fn blocks(source: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut text = String::new();
+    // Next number of each open list; `None` for bullet lists
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut quotes = 0;
+    let mut heading = None;
+
+    let flush = |blocks: &mut Vec<Block>, text: &mut String, depth: usize| {
+        if !text.trim().is_empty() {
+            blocks.push(Block::Paragraph {
+                text: text.trim().to_string(),
+                depth,
+            });
+        }
+        text.clear();
+    };
+
+    for event in Parser::new_ext(source, markdown::options()) {
+        let depth = lists.len() + quotes;
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                flush(&mut blocks, &mut text, depth);
+                heading = Some(level);
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(level) = heading.take() {
+                    blocks.push(Block::Heading {
+                        level,
+                        text: text.trim().to_string(),
+                    });
+                }
+                text.clear();
+            }
+            Event::Start(Tag::List(start)) => {
+                flush(&mut blocks, &mut text, depth);
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                flush(&mut blocks, &mut text, depth);
+                lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                flush(&mut blocks, &mut text, depth);
+                match lists.last_mut() {
+                    Some(Some(number)) => {
+                        text.push_str(&format!("{}. ", number));
+                        *number += 1;
+                    }
+                    _ => text.push_str("• "),
+                }
+            }
+            Event::Start(Tag::BlockQuote(_)) => {
+                flush(&mut blocks, &mut text, depth);
+                quotes += 1;
+            }
+            Event::End(TagEnd::BlockQuote(_)) => {
+                flush(&mut blocks, &mut text, depth);
+                quotes -= 1;
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                flush(&mut blocks, &mut text, depth);
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                blocks.push(Block::Code {
+                    text: text.trim_end_matches('\n').to_string(),
+                    depth,
+                });
+                text.clear();
+            }
+            Event::Start(Tag::Image { .. }) => text.push_str("[Image: "),
+            Event::End(TagEnd::Image) => text.push(']'),
+            Event::Text(fragment) | Event::Code(fragment) => text.push_str(&fragment),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak => text.push('\n'),
+            Event::TaskListMarker(done) => text.push_str(if done { "[x] " } else { "[ ] " }),
+            Event::FootnoteReference(label) => text.push_str(&format!("[{}]", label)),
+            Event::End(TagEnd::TableCell) => text.push_str("    "),
+            Event::End(TagEnd::Paragraph | TagEnd::Item | TagEnd::TableHead | TagEnd::TableRow) => {
+                flush(&mut blocks, &mut text, depth)
+            }
+            Event::Rule => {
+                flush(&mut blocks, &mut text, depth);
+                blocks.push(Block::Rule);
+            }
+            // Raw HTML has no place in a text layout
+            _ => {}
+        }
+    }
+    flush(&mut blocks, &mut text, 0);
+
+    blocks
+}
+
+/// `text` in Windows-1252, transliterating what it can't hold
+fn encode(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => bytes.push(b'\n'),
+            '\t' => bytes.extend_from_slice(b"    "),
+            ' '..='~' => bytes.push(c as u8),
+            '\u{a0}'..='\u{ff}' => bytes.push(c as u32 as u8),
+            _ => match windows_1252(c) {
+                Some(byte) => bytes.push(byte),
+                None => bytes.extend(
+                    deunicode::deunicode_char(c)
+                        .unwrap_or("?")
+                        .bytes()
+                        .filter(|byte| (b' '..=b'~').contains(byte)),
+                ),
+            },
+        }
+    }
+    bytes
+}
+
+/// The characters Windows-1252 puts where Latin-1 has control codes
+fn windows_1252(c: char) -> Option<u8> {
+    Some(match c {
+        '€' => 0x80,
+        '‚' => 0x82,
+        'ƒ' => 0x83,
+        '„' => 0x84,
+        '…' => 0x85,
+        '†' => 0x86,
+        '‡' => 0x87,
+        'ˆ' => 0x88,
+        '‰' => 0x89,
+        'Š' => 0x8a,
+        '‹' => 0x8b,
+        'Œ' => 0x8c,
+        'Ž' => 0x8e,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '˜' => 0x98,
+        '™' => 0x99,
+        'š' => 0x9a,
+        '›' => 0x9b,
+        'œ' => 0x9c,
+        'ž' => 0x9e,
+        'Ÿ' => 0x9f,
+        _ => return None,
+    })
+}
+
+/// Breaks encoded text into lines at most `width` points wide, at spaces
+/// where possible; `\n` forces a break
+fn wrap(text: &[u8], font: Font, size: f32, width: f32) -> Vec<Vec<u8>> {
+    let max = width * 1000.0 / size;
+    let space = font.width(b' ');
+    let mut lines = Vec::new();
+
+    for paragraph in text.split(|&byte| byte == b'\n') {
+        let mut line: Vec<u8> = Vec::new();
+        let mut line_width = 0.0;
+        for word in paragraph.split(|&byte| byte == b' ') {
+            if word.is_empty() {
+                continue;
+            }
+            if !line.is_empty() && line_width + space + font.measure(word) > max {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0.0;
+            }
+            if !line.is_empty() {
+                line.push(b' ');
+                line_width += space;
+            }
+            // Only a word longer than a whole line still overflows; cut it
+            for &byte in word {
+                let advance = font.width(byte);
+                if !line.is_empty() && line_width + advance > max {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0.0;
+                }
+                line.push(byte);
+                line_width += advance;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Breaks encoded code at the line's width, keeping its spacing
+fn wrap_code(text: &[u8], size: f32, width: f32) -> Vec<Vec<u8>> {
+    let per_line = ((width * 1000.0 / size) / Font::Mono.width(b' ')).max(1.0) as usize;
+    text.split(|&byte| byte == b'\n')
+        .flat_map(|line| {
+            if line.is_empty() {
+                vec![Vec::new()]
+            } else {
+                line.chunks(per_line).map(<[u8]>::to_vec).collect()
+            }
+        })
+        .collect()
+}
+
+/// A PDF literal string
+fn literal(text: &[u8]) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('(');
+    for &byte in text {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            }
+            b' '..=b'~' => escaped.push(byte as char),
+            // Octal keeps content streams ASCII
+            _ => escaped.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    escaped.push(')');
+    escaped
+}
+
+/// A PDF text string in UTF-16, for metadata outside content streams
+fn text_string(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        let _ = write!(hex, "{:04X}", unit);
+    }
+    hex.push('>');
+    hex
+}
+
+/// Content streams of the pages laid out so far
+struct Layout {
+    pages: Vec<String>,
+    page: String,
+    /// Baseline of the last line written
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Layout {
+            pages: Vec::new(),
+            page: String::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(std::mem::take(&mut self.page));
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn gap(&mut self, points: f32) {
+        // Space at the top of a page would only push the text down
+        if self.y < PAGE_HEIGHT - MARGIN {
+            self.y -= points;
+        }
+    }
+
+    fn line(&mut self, font: Font, size: f32, leading: f32, indent: f32, text: &[u8]) {
+        if self.y - leading < MARGIN {
+            self.new_page();
+        }
+        self.y -= leading;
+        let _ = writeln!(
+            self.page,
+            "BT /{} {:.1} Tf {:.2} {:.2} Td {} Tj ET",
+            font.resource(),
+            size,
+            MARGIN + indent,
+            self.y,
+            literal(text)
+        );
+    }
+
+    fn text(&mut self, font: Font, size: f32, indent: f32, text: &str) {
+        let width = PAGE_WIDTH - 2.0 * MARGIN - indent;
+        for line in wrap(&encode(text), font, size, width) {
+            self.line(font, size, size * 1.4, indent, &line);
+        }
+    }
+
+    fn code(&mut self, size: f32, indent: f32, text: &str) {
+        let width = PAGE_WIDTH - 2.0 * MARGIN - indent;
+        for line in wrap_code(&encode(text), size, width) {
+            self.line(Font::Mono, size, size * 1.35, indent, &line);
+        }
+    }
+
+    fn rule(&mut self) {
+        if self.y - 12.0 < MARGIN {
+            self.new_page();
+        }
+        self.y -= 8.0;
+        let _ = writeln!(
+            self.page,
+            "0.6 G 0.5 w {:.2} {:.2} m {:.2} {:.2} l S 0 G",
+            MARGIN,
+            self.y,
+            PAGE_WIDTH - MARGIN,
+            self.y
+        );
+        self.y -= 4.0;
+    }
+
+    /// Every page, numbered at the foot
+    fn finish(mut self) -> Vec<String> {
+        if !self.page.is_empty() || self.pages.is_empty() {
+            self.pages.push(self.page);
+        }
+        let count = self.pages.len();
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            let number = format!("{} / {}", index + 1, count);
+            let x = PAGE_WIDTH - MARGIN - Font::Regular.measure(number.as_bytes()) * 9.0 / 1000.0;
+            let _ = writeln!(
+                page,
+                "0.4 g BT /F1 9.0 Tf {:.2} {:.2} Td {} Tj ET 0 g",
+                x,
+                MARGIN / 2.0,
+                literal(number.as_bytes())
+            );
+        }
+        self.pages
+    }
+}
+
+/// Lays out `title`, the `byline` under it and the Markdown `content` as a
+/// PDF document
+pub fn render_pdf(info: &PdfInfo, byline: &[String], content: &str) -> Vec<u8> {
+    let mut layout = Layout::new();
+    layout.text(Font::Bold, 20.0, 0.0, info.title);
+    layout.gap(4.0);
+    for line in byline {
+        layout.text(Font::Regular, 10.0, 0.0, line);
+    }
+    layout.gap(6.0);
+    layout.rule();
+    layout.gap(6.0);
+
+    for block in blocks(content) {
+        match block {
+            Block::Heading { level, text } => {
+                let size = match level {
+                    HeadingLevel::H1 => 16.0,
+                    HeadingLevel::H2 => 14.0,
+                    _ => 12.0,
+                };
+                layout.gap(size * 0.6);
+                layout.text(Font::Bold, size, 0.0, &text);
+                layout.gap(2.0);
+            }
+            Block::Paragraph { text, depth } => {
+                layout.text(Font::Regular, 11.0, depth as f32 * INDENT, &text);
+                layout.gap(6.0);
+            }
+            Block::Code { text, depth } => {
+                layout.code(9.0, depth as f32 * INDENT + 8.0, &text);
+                layout.gap(6.0);
+            }
+            Block::Rule => {
+                layout.rule();
+                layout.gap(6.0);
+            }
+        }
+    }
+
+    write_document(info, &layout.finish())
+}
+
+/// Serializes pages and metadata with a cross-reference table
+fn write_document(info: &PdfInfo, pages: &[String]) -> Vec<u8> {
+    // 1 catalog, 2 page tree, 3-5 fonts, 6 info, then a page and its
+    // content stream for each page
+    const FIRST_PAGE: usize = 7;
+    let fonts = [Font::Regular, Font::Bold, Font::Mono];
+
+    let kids: Vec<String> = (0..pages.len())
+        .map(|index| format!("{} 0 R", FIRST_PAGE + 2 * index))
+        .collect();
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+    ];
+    for font in fonts {
+        objects.push(
+            format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                font.base_font()
+            )
+            .into_bytes(),
+        );
+    }
+    objects.push(
+        format!(
+            "<< /Title {} /Author {} /Producer (axum-rest) /CreationDate (D:{}Z) >>",
+            text_string(info.title),
+            text_string(info.author),
+            info.created_at.format("%Y%m%d%H%M%S")
+        )
+        .into_bytes(),
+    );
+    for (index, content) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                FIRST_PAGE + 2 * index + 1
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(content.as_bytes());
+        stream.extend_from_slice(b"\nendstream");
+        objects.push(stream);
+    }
+
+    // The binary comment tells transfer tools the file isn't text
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref = pdf.len();
+    let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(table, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        table,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 6 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    pdf.extend_from_slice(table.as_bytes());
+    pdf
+}
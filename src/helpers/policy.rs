@@ -0,0 +1,165 @@
+//! Who may do what, decided in one place.
+//!
+//! Handlers resolve the resources involved, then ask a policy before acting.
+//! A refusal is a ready 403 [`ErrorResponse`], so repositories only run
+//! queries and never need to know who is asking.
+
+use axum::{extract::FromRequestParts, http::StatusCode, http::request::Parts};
+use uuid::Uuid;
+
+use crate::helpers::middleware::check_admin_role;
+use crate::model::model::{CollaboratorRole, ErrorResponse, OrgContext, Post, Role, Visibility};
+
+/// Refusals are boxed to keep the `Ok` path small
+pub type PolicyResult = Result<(), Box<ErrorResponse>>;
+
+/// The authenticated caller, as resolved by the auth middleware
+#[derive(Debug, Clone, PartialEq)]
+pub struct Actor {
+    pub user_id: Uuid,
+    pub role: Role,
+}
+
+impl Actor {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::ADMIN
+    }
+}
+
+/// Extracts the [`Actor`] of an authenticated route; answers 401 when the
+/// auth middleware didn't run for the request
+pub struct Authorize(pub Actor);
+
+impl<S: Send + Sync> FromRequestParts<S> for Authorize {
+    type Rejection = ErrorResponse;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_id = parts.extensions.get::<Uuid>().copied();
+        let role = parts.extensions.get::<Role>().cloned();
+        match (user_id, role) {
+            (Some(user_id), Some(role)) => Ok(Authorize(Actor { user_id, role })),
+            _ => Err(ErrorResponse::new(
+                StatusCode::UNAUTHORIZED,
+                "Unauthorized",
+                "Authentication required",
+            )),
+        }
+    }
+}
+
+fn forbidden(detail: &str) -> PolicyResult {
+    Err(Box::new(ErrorResponse::new(
+        StatusCode::FORBIDDEN,
+        "Forbidden",
+        detail,
+    )))
+}
+
+pub fn require_admin(actor: &Actor) -> PolicyResult {
+    check_admin_role(&actor.role)
+}
+
+/// Writing in an organization takes an editor or owner role there
+pub fn can_write_in_org(org: Option<&OrgContext>) -> PolicyResult {
+    if org.is_none_or(|org| org.role.is_some_and(|role| role.can_write())) {
+        Ok(())
+    } else {
+        forbidden("Only organization editors can create or change posts")
+    }
+}
+
+/// Authors and the editors they invited change posts; inside an
+/// organization they must still be allowed to write there. `collaborator` is
+/// the actor's role on the post, if they were invited.
+pub fn can_edit_post(
+    actor: &Actor,
+    post: &Post,
+    org: Option<&OrgContext>,
+    collaborator: Option<CollaboratorRole>,
+) -> PolicyResult {
+    can_write_in_org(org)?;
+    if post.author_id != actor.user_id && !collaborator.is_some_and(|role| role.can_edit()) {
+        return forbidden("You can only change your own posts or posts you were invited to edit");
+    }
+    Ok(())
+}
+
+/// Only the author deletes a post, whoever else may edit it
+pub fn can_delete_post(actor: &Actor, post: &Post, org: Option<&OrgContext>) -> PolicyResult {
+    can_write_in_org(org)?;
+    if post.author_id != actor.user_id {
+        return forbidden("Only the author can delete a post");
+    }
+    Ok(())
+}
+
+pub fn can_archive_post(actor: &Actor, post: &Post, org: Option<&OrgContext>) -> PolicyResult {
+    can_write_in_org(org)?;
+    if post.author_id != actor.user_id {
+        return forbidden("Only the author can archive a post");
+    }
+    Ok(())
+}
+
+/// Only the author invites and removes collaborators
+pub fn can_manage_collaborators(
+    actor: &Actor,
+    post: &Post,
+    org: Option<&OrgContext>,
+) -> PolicyResult {
+    can_write_in_org(org)?;
+    if post.author_id != actor.user_id {
+        return forbidden("Only the author can manage collaborators");
+    }
+    Ok(())
+}
+
+/// Only the author hands out and revokes share links
+pub fn can_share_post(actor: &Actor, post: &Post, org: Option<&OrgContext>) -> PolicyResult {
+    can_write_in_org(org)?;
+    if post.author_id != actor.user_id {
+        return forbidden("Only the author can share a post");
+    }
+    Ok(())
+}
+
+/// Private posts are readable by their author and collaborators only;
+/// `viewer` is `None` for anonymous requests
+pub fn can_view_post(
+    viewer: Option<Uuid>,
+    post: &Post,
+    collaborator: Option<CollaboratorRole>,
+) -> PolicyResult {
+    if post.visibility != Visibility::PRIVATE
+        || viewer == Some(post.author_id)
+        || collaborator.is_some()
+    {
+        Ok(())
+    } else {
+        forbidden("This post is private")
+    }
+}
+
+/// Only the author decides who can find a post
+pub fn can_change_visibility(actor: &Actor, post: &Post) -> PolicyResult {
+    if post.author_id != actor.user_id {
+        return forbidden("Only the author can change a post's visibility");
+    }
+    Ok(())
+}
+
+pub fn can_preview_post(actor: &Actor, post: &Post) -> PolicyResult {
+    if post.author_id != actor.user_id {
+        return forbidden("Only the author can preview a post");
+    }
+    Ok(())
+}
+
+/// Private account details are visible to the account itself and to admins
+pub fn can_view_user(actor: &Actor, target: Uuid) -> PolicyResult {
+    if actor.user_id == target || actor.is_admin() {
+        Ok(())
+    } else {
+        forbidden("You can only view your own account")
+    }
+}
@@ -0,0 +1,428 @@
+//! Export and import of an author's posts, for moving a blog in and out.
+//!
+//! Posts travel as one JSON document, or as a ZIP of Markdown files with a
+//! frontmatter block each. Imports take the same formats and report every
+//! item separately, so one bad file doesn't hold back the rest.
+
+use std::io::{Cursor, Read, Write};
+use std::sync::Arc;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::info;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use zip::{CompressionMethod, ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+use crate::cache::Cache;
+use crate::config::AppConfig;
+use crate::db::repositories::{
+    media_repo::MediaRepository, mention_repo::MentionRepository, post_repo::PostRepository,
+};
+use crate::helpers::markdown::{extract_images, extract_mentions};
+use crate::helpers::validation::check_new_post;
+use crate::model::model::{CreatePostRequest, Post, Visibility};
+
+/// Largest import body, JSON or ZIP
+pub const MAX_IMPORT_BYTES: usize = 16 * 1024 * 1024;
+/// Most posts a single import takes
+pub const MAX_IMPORT_POSTS: usize = 1000;
+
+const FRONTMATTER_DELIMITER: &str = "---";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    /// One JSON document with every post
+    #[default]
+    Json,
+    /// A ZIP with one Markdown file per post, metadata in frontmatter
+    Markdown,
+}
+
+impl ArchiveFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ArchiveFormat::Json => "application/json",
+            ArchiveFormat::Markdown => "application/zip",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ArchiveFormat::Json => "posts.json",
+            ArchiveFormat::Markdown => "posts.zip",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArchiveQuery {
+    #[serde(default)]
+    pub format: ArchiveFormat,
+}
+
+/// A post as exported, and as accepted by imports
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct PortablePost {
+    pub title: String,
+    /// Kept on import when it is valid and still free
+    #[serde(default)]
+    pub slug: Option<String>,
+    /// Markdown
+    pub content: String,
+    /// Falls back to the deployment's default license on import
+    #[serde(default)]
+    pub license: Option<String>,
+    /// Kept on import; defaults to the time of the import
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Informational; imported posts start out unedited
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Kept on import; `PUBLIC` when omitted
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+}
+
+impl From<Post> for PortablePost {
+    fn from(post: Post) -> Self {
+        PortablePost {
+            title: post.title,
+            slug: post.slug,
+            content: post.content,
+            license: post.license,
+            created_at: Some(post.created_at),
+            updated_at: Some(post.updated_at),
+            visibility: Some(post.visibility),
+        }
+    }
+}
+
+/// The JSON export format
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PostArchive {
+    #[serde(default)]
+    pub exported_at: Option<DateTime<Utc>>,
+    pub posts: Vec<PortablePost>,
+}
+
+/// Outcome of one imported item
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportedItem {
+    /// File name inside the ZIP, or `posts[N]` for JSON
+    pub item: String,
+    pub title: Option<String>,
+    /// Public ID of the created post; `None` when the item failed
+    pub id: Option<String>,
+    pub slug: Option<String>,
+    /// Why the item was not imported
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct PostImportReport {
+    pub imported: usize,
+    pub failed: usize,
+    pub items: Vec<ImportedItem>,
+}
+
+/// Item name, and the post or why it could not be read
+pub type ArchiveItem = (String, Result<PortablePost, String>);
+
+pub fn to_json(posts: Vec<PortablePost>, now: DateTime<Utc>) -> Result<Vec<u8>> {
+    let archive = PostArchive {
+        exported_at: Some(now),
+        posts,
+    };
+    Ok(serde_json::to_vec_pretty(&archive)?)
+}
+
+pub fn to_zip(posts: &[PortablePost]) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (index, post) in posts.iter().enumerate() {
+        let name = match &post.slug {
+            Some(slug) => format!("{}.md", slug),
+            None => format!("post-{}.md", index + 1),
+        };
+        zip.start_file(name, options)?;
+        zip.write_all(to_markdown(post).as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// The post as Markdown behind a frontmatter block; values are JSON strings,
+/// which YAML readers accept too
+pub fn to_markdown(post: &PortablePost) -> String {
+    with_frontmatter(
+        &[
+            ("title", Some(post.title.clone())),
+            ("slug", post.slug.clone()),
+            ("license", post.license.clone()),
+            ("created_at", post.created_at.map(|date| date.to_rfc3339())),
+            ("updated_at", post.updated_at.map(|date| date.to_rfc3339())),
+            ("visibility", post.visibility.map(String::from)),
+        ],
+        &post.content,
+    )
+}
+
+/// `content` behind a frontmatter block of `fields`, leaving out those
+/// without a value
+pub fn with_frontmatter(fields: &[(&str, Option<String>)], content: &str) -> String {
+    let mut text = format!("{}\n", FRONTMATTER_DELIMITER);
+    for (key, value) in fields {
+        if let Some(value) = value {
+            text.push_str(&format!("{}: {}\n", key, quote(value)));
+        }
+    }
+    text.push_str(&format!("{}\n\n{}", FRONTMATTER_DELIMITER, content));
+    text
+}
+
+fn quote(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+/// Reads a Markdown file written by [`to_markdown`], or by hand: values may
+/// be quoted or bare, and unknown keys are ignored
+pub fn parse_markdown(text: &str) -> Result<PortablePost, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut lines = text.split_inclusive('\n');
+    if lines.next().map(str::trim_end) != Some(FRONTMATTER_DELIMITER) {
+        return Err("missing frontmatter; the file must start with ---".to_string());
+    }
+
+    let mut post = PortablePost {
+        title: String::new(),
+        slug: None,
+        content: String::new(),
+        license: None,
+        created_at: None,
+        updated_at: None,
+        visibility: None,
+    };
+    let mut closed = false;
+    let mut consumed = 0;
+
+    for line in lines.by_ref() {
+        consumed += line.len();
+        let line = line.trim_end();
+        if line == FRONTMATTER_DELIMITER {
+            closed = true;
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(format!("invalid frontmatter line `{}`", line));
+        };
+        let value = unquote(value.trim())?;
+        match key.trim() {
+            "title" => post.title = value,
+            "slug" => post.slug = Some(value).filter(|slug| !slug.is_empty()),
+            "license" => post.license = Some(value).filter(|license| !license.is_empty()),
+            "created_at" => post.created_at = Some(parse_date("created_at", &value)?),
+            "updated_at" => post.updated_at = Some(parse_date("updated_at", &value)?),
+            "visibility" => post.visibility = Some(parse_visibility(&value)?),
+            _ => {}
+        }
+    }
+    if !closed {
+        return Err("frontmatter is not closed with ---".to_string());
+    }
+
+    let header = text.split_inclusive('\n').next().map_or(0, str::len);
+    let body = &text[header + consumed..];
+    let body = body
+        .strip_prefix("\r\n")
+        .or_else(|| body.strip_prefix('\n'))
+        .unwrap_or(body);
+    post.content = body.to_string();
+
+    if post.title.trim().is_empty() {
+        return Err("frontmatter has no title".to_string());
+    }
+    Ok(post)
+}
+
+fn unquote(value: &str) -> Result<String, String> {
+    if value.starts_with('"') {
+        serde_json::from_str(value).map_err(|_| format!("invalid quoted value {}", value))
+    } else if let Some(inner) = value
+        .strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+    {
+        Ok(inner.replace("''", "'"))
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+fn parse_date(key: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|_| format!("{} is not an RFC 3339 date", key))
+}
+
+/// A misspelled visibility is refused rather than read as public
+fn parse_visibility(value: &str) -> Result<Visibility, String> {
+    match value.to_uppercase().as_str() {
+        "PUBLIC" => Ok(Visibility::PUBLIC),
+        "UNLISTED" => Ok(Visibility::UNLISTED),
+        "PRIVATE" => Ok(Visibility::PRIVATE),
+        _ => Err(format!(
+            "visibility must be PUBLIC, UNLISTED or PRIVATE, not {}",
+            value
+        )),
+    }
+}
+
+/// Posts of a JSON import; either a [`PostArchive`] or a bare array of posts
+pub fn parse_json(body: &[u8]) -> Result<Vec<ArchiveItem>> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Document {
+        Archive { posts: Vec<serde_json::Value> },
+        Posts(Vec<serde_json::Value>),
+    }
+
+    let posts = match serde_json::from_slice(body)? {
+        Document::Archive { posts } | Document::Posts(posts) => posts,
+    };
+    if posts.len() > MAX_IMPORT_POSTS {
+        bail!("at most {} posts can be imported at once", MAX_IMPORT_POSTS);
+    }
+
+    Ok(posts
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let post = serde_json::from_value(value).map_err(|e| e.to_string());
+            (format!("posts[{}]", index), post)
+        })
+        .collect())
+}
+
+/// Markdown files of a ZIP import, in archive order; directories are skipped
+pub fn parse_zip(body: &[u8]) -> Result<Vec<ArchiveItem>> {
+    let mut archive = ZipArchive::new(Cursor::new(body))?;
+    let mut items = Vec::new();
+
+    for index in 0..archive.len() {
+        let file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        if items.len() == MAX_IMPORT_POSTS {
+            bail!("at most {} posts can be imported at once", MAX_IMPORT_POSTS);
+        }
+
+        let name = file.name().to_string();
+        if !name.ends_with(".md") {
+            items.push((name, Err("only .md files are imported".to_string())));
+            continue;
+        }
+
+        // The declared size can't be trusted, so stop reading past the limit
+        let mut bytes = Vec::new();
+        file.take(MAX_IMPORT_BYTES as u64 + 1)
+            .read_to_end(&mut bytes)?;
+        let post = if bytes.len() > MAX_IMPORT_BYTES {
+            Err("file is too large".to_string())
+        } else {
+            String::from_utf8(bytes)
+                .map_err(|_| "file is not UTF-8".to_string())
+                .and_then(|text| parse_markdown(&text))
+        };
+        items.push((name, post));
+    }
+
+    Ok(items)
+}
+
+/// The create request for `post`, checked like a post created through the API
+pub fn check_post(post: &PortablePost, config: &AppConfig) -> Result<CreatePostRequest, String> {
+    check_new_post(
+        CreatePostRequest {
+            title: post.title.clone(),
+            content: post.content.clone(),
+            slug: post.slug.clone(),
+            license: post.license.clone(),
+            visibility: post.visibility,
+        },
+        config,
+    )
+}
+
+/// Creates the valid items as posts of `author_id`, keeping their dates
+pub async fn import_posts(
+    pool: &PgPool,
+    cache: Arc<Cache>,
+    config: &AppConfig,
+    author_id: Uuid,
+    items: Vec<ArchiveItem>,
+    now: DateTime<Utc>,
+) -> Result<PostImportReport> {
+    let posts = PostRepository::new(pool.clone()).with_cache(cache);
+    let media = MediaRepository::new(pool.clone());
+    let mentions = MentionRepository::new(pool.clone());
+    let mut report = PostImportReport::default();
+
+    for (item, parsed) in items {
+        let checked = parsed.and_then(|post| match check_post(&post, config) {
+            Ok(request) => Ok((request, post.created_at)),
+            Err(error) => Err(format!("{}: {}", post.title, error)),
+        });
+        let (request, created_at) = match checked {
+            Ok(checked) => checked,
+            Err(error) => {
+                report.failed += 1;
+                report.items.push(ImportedItem {
+                    item,
+                    title: None,
+                    id: None,
+                    slug: None,
+                    error: Some(error),
+                });
+                continue;
+            }
+        };
+
+        // A slug taken since the export gets a fresh one instead of failing the item
+        let slug = match request.slug.as_deref() {
+            Some(slug) if !posts.slug_taken(slug).await? => slug.to_string(),
+            _ => posts.free_slug(&request.title, &config.slugs).await?,
+        };
+        let post = posts
+            .import_post(request, author_id, created_at.unwrap_or(now), slug)
+            .await?;
+        media
+            .replace_for_post(post.id, &extract_images(&post.content))
+            .await?;
+        mentions
+            .replace_for_post(post.id, &extract_mentions(&post.content))
+            .await?;
+
+        report.imported += 1;
+        report.items.push(ImportedItem {
+            item,
+            title: Some(post.title),
+            id: Some(post.public_id),
+            slug: post.slug,
+            error: None,
+        });
+    }
+
+    info!(
+        "Post import for {} finished: {} imported, {} failed",
+        author_id, report.imported, report.failed
+    );
+    Ok(report)
+}
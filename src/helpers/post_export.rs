@@ -0,0 +1,151 @@
+//! A single post as a file to download, for backing it up or posting it
+//! elsewhere.
+//!
+//! Markdown keeps the source behind the frontmatter of the archive export,
+//! plus the `date`, `author` and `description` keys static site generators
+//! read, so the file can also be imported again. HTML is a standalone page
+//! around the sanitized rendering, and PDF is laid out by [`pdf`].
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::helpers::markdown::{cached_or_render, escape_html};
+use crate::helpers::pdf::{self, PdfInfo};
+use crate::helpers::post_archive::with_frontmatter;
+use crate::model::model::PostResponse;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// The Markdown source behind a frontmatter block
+    #[default]
+    Markdown,
+    /// A standalone HTML page
+    Html,
+    /// A PDF document
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Html => "text/html; charset=utf-8",
+            ExportFormat::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// Name of the downloaded file: the slug, or the ID of posts without one
+pub fn file_name(post: &PostResponse, format: ExportFormat) -> String {
+    format!(
+        "{}.{}",
+        post.slug.as_deref().unwrap_or(&post.id),
+        format.extension()
+    )
+}
+
+/// The post in `format`
+pub fn export_post(post: &PostResponse, format: ExportFormat) -> Vec<u8> {
+    match format {
+        ExportFormat::Markdown => to_markdown(post).into_bytes(),
+        ExportFormat::Html => to_html(post).into_bytes(),
+        ExportFormat::Pdf => to_pdf(post),
+    }
+}
+
+pub fn to_markdown(post: &PostResponse) -> String {
+    with_frontmatter(
+        &[
+            ("title", Some(post.title.clone())),
+            ("slug", post.slug.clone()),
+            ("author", Some(post.author.name.clone())),
+            ("description", Some(post.excerpt.clone())),
+            ("date", Some(post.created_at.to_rfc3339())),
+            ("license", post.license.clone()),
+            ("created_at", Some(post.created_at.to_rfc3339())),
+            ("updated_at", Some(post.updated_at.to_rfc3339())),
+            ("visibility", Some(String::from(post.visibility))),
+        ],
+        &post.content,
+    )
+}
+
+pub fn to_html(post: &PostResponse) -> String {
+    let content = cached_or_render(post.content_html.clone(), &post.content);
+    let license = match &post.license {
+        Some(license) => format!(
+            "\n<footer><p>License: {}</p></footer>",
+            escape_html(license)
+        ),
+        None => String::new(),
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<meta name="author" content="{author}">
+<meta name="description" content="{description}">
+<meta property="article:published_time" content="{created_at}">
+<meta property="article:modified_time" content="{updated_at}">
+</head>
+<body>
+<article>
+<header>
+<h1>{title}</h1>
+<p>By {author} &middot; <time datetime="{created_at}">{date}</time></p>
+</header>
+{content}{license}
+</article>
+</body>
+</html>
+"#,
+        title = escape_html(&post.title),
+        author = escape_html(&post.author.name),
+        description = escape_html(&post.excerpt),
+        created_at = post.created_at.to_rfc3339(),
+        updated_at = post.updated_at.to_rfc3339(),
+        date = post.created_at.format("%B %-d, %Y"),
+        content = content,
+        license = license,
+    )
+}
+
+pub fn to_pdf(post: &PostResponse) -> Vec<u8> {
+    let mut byline = vec![format!(
+        "By {} · {}",
+        post.author.name,
+        post.created_at.format("%B %-d, %Y")
+    )];
+    if let Some(license) = &post.license {
+        byline.push(format!("License: {}", license));
+    }
+
+    pdf::render_pdf(
+        &PdfInfo {
+            title: &post.title,
+            author: &post.author.name,
+            created_at: post.created_at,
+        },
+        &byline,
+        &post.content,
+    )
+}
@@ -0,0 +1,121 @@
+//! Links that let anyone holding them read a post as it is published,
+//! without signing in.
+//!
+//! A preview token is a JWT naming the post and the login session that issued
+//! it. It stops working when it expires or when that session ends, whichever
+//! comes first, so signing out revokes every link shared from that device.
+
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::helpers::auth::{BASE_URL, JWT_KEYS, TOKEN_LEEWAY_SECS};
+use crate::helpers::clock::Clock;
+use crate::helpers::markdown::{extract_images, plain_text};
+use crate::model::model::PostResponse;
+
+/// Marks a token as a preview link; auth tokens never carry a `scope`
+const PREVIEW_SCOPE: &str = "post-preview";
+
+/// Longest `description` in a preview's metadata
+pub const MAX_DESCRIPTION_LENGTH: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreviewClaims {
+    pub iss: String,
+    pub scope: String,
+    /// Public id of the post
+    pub sub: String,
+    /// Session that issued the link
+    pub sid: String,
+    pub iat: usize,
+    pub exp: usize,
+}
+
+/// A shareable link to a post preview
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PreviewLink {
+    pub token: String,
+    pub url: String,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Open Graph metadata, as link unfurlers would read it from the published post
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct PostMeta {
+    pub title: String,
+    /// Opening text of the post, without formatting
+    pub description: String,
+    /// Where the post is published
+    pub url: String,
+    /// First image in the post
+    pub image: Option<String>,
+    /// Always `article`
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub author: String,
+    pub published_time: DateTime<Utc>,
+    pub modified_time: DateTime<Utc>,
+}
+
+/// A post rendered as it will be published, with its link metadata
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PostPreview {
+    pub post: PostResponse,
+    pub meta: PostMeta,
+}
+
+/// Signs a preview token for `post_id` that lasts `ttl` at most
+pub fn issue_preview_token(
+    post_id: &str,
+    session_id: &str,
+    ttl: Duration,
+    clock: &dyn Clock,
+) -> Result<(String, DateTime<Utc>)> {
+    let now = clock.now();
+    let expires_at = now + ttl;
+    let claims = PreviewClaims {
+        iss: BASE_URL.clone(),
+        scope: PREVIEW_SCOPE.to_string(),
+        sub: post_id.to_string(),
+        sid: session_id.to_string(),
+        iat: now.timestamp() as usize,
+        exp: expires_at.timestamp() as usize,
+    };
+
+    Ok((JWT_KEYS.sign(&claims)?, expires_at))
+}
+
+/// Checks the signature, scope and expiry; the session is left to the caller
+pub fn verify_preview_token(token: &str, clock: &dyn Clock) -> Result<PreviewClaims> {
+    let claims: PreviewClaims = JWT_KEYS.verify(token, clock)?;
+
+    if claims.scope != PREVIEW_SCOPE {
+        bail!("not a preview token");
+    }
+    if claims.exp + TOKEN_LEEWAY_SECS < clock.timestamp() {
+        bail!("preview link has expired");
+    }
+    Ok(claims)
+}
+
+/// Metadata for `post` published at `url`
+pub fn post_meta(post: &PostResponse, url: String) -> PostMeta {
+    PostMeta {
+        title: post.title.clone(),
+        description: plain_text(&post.content, MAX_DESCRIPTION_LENGTH),
+        url,
+        image: extract_images(&post.content)
+            .into_iter()
+            .next()
+            .map(|image| image.url),
+        kind: "article".to_string(),
+        author: post.author.name.clone(),
+        published_time: post.created_at,
+        modified_time: post.updated_at,
+    }
+}
@@ -0,0 +1,248 @@
+//! Notifications sent when a public post is published or updated.
+//!
+//! Each configured hook becomes a row in `hook_deliveries` when the post is
+//! saved; the delivery job sends due rows, retrying failures with
+//! exponential backoff, and the rows double as the delivery log.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Url};
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::{PublishHookConfig, PublishHookKind, PublishHooksConfig};
+use crate::db::repositories::hook_repo::HookRepository;
+use crate::helpers::http_signatures::INSTANCE_KEY;
+use crate::helpers::ids::{IdGenerator, TimeOrderedIds};
+use crate::model::model::{DeliveryStatus, HookDelivery, Post};
+
+/// Retries never wait longer than this
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Deliveries sent per run of the delivery job
+const DELIVERY_BATCH: i64 = 20;
+
+/// Timeout for a single delivery attempt
+pub const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishEvent {
+    Published,
+    Updated,
+}
+
+impl PublishEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PublishEvent::Published => "post.published",
+            PublishEvent::Updated => "post.updated",
+        }
+    }
+}
+
+pub fn post_url(template: &str, public_id: &str) -> String {
+    template.replace("{id}", public_id)
+}
+
+/// The request a hook sends for a post, queued as `id`, or `None` if it cannot be sent
+pub fn build_delivery(
+    id: Uuid,
+    hook: &PublishHookConfig,
+    indexnow_key: Option<&str>,
+    event: PublishEvent,
+    post_id: &str,
+    post_url: &str,
+    now: DateTime<Utc>,
+) -> Option<HookDelivery> {
+    let (method, url, body) = match hook.kind {
+        PublishHookKind::IndexNow => {
+            let key = indexnow_key?;
+            let host = Url::parse(post_url).ok()?.host_str()?.to_string();
+            let body = json!({ "host": host, "key": key, "urlList": [post_url] });
+            ("POST", hook.url.clone(), Some(body.to_string()))
+        }
+        PublishHookKind::Ping => ("GET", hook.url.replace("{url}", &encode(post_url)), None),
+        PublishHookKind::Purge => {
+            let body = json!({ "event": event.as_str(), "post_id": post_id, "url": post_url });
+            ("POST", hook.url.clone(), Some(body.to_string()))
+        }
+    };
+
+    Some(HookDelivery {
+        id,
+        event: event.as_str().to_string(),
+        post_id: Some(post_id.to_string()),
+        method: method.to_string(),
+        url,
+        signed_by: None,
+        body,
+        status: DeliveryStatus::PENDING,
+        attempts: 0,
+        last_status: None,
+        last_error: None,
+        next_attempt_at: now,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// Wait before the attempt following `attempts` failed ones
+pub fn retry_delay(attempts: i32, base: Duration) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    base.saturating_mul(2u32.pow(exponent)).min(MAX_RETRY_DELAY)
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Queues a delivery per configured hook; organization posts are private and skipped
+pub async fn enqueue_publish_hooks(
+    pool: &PgPool,
+    config: &PublishHooksConfig,
+    event: PublishEvent,
+    post: &Post,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    if config.hooks.is_empty() || post.org_id.is_some() || !post.visibility.is_listed() {
+        return Ok(0);
+    }
+
+    let url = post_url(&config.post_url_template, &post.public_id);
+    let repo = HookRepository::new(pool.clone());
+    let ids = TimeOrderedIds;
+    let mut queued = 0;
+
+    for hook in &config.hooks {
+        let Some(delivery) = build_delivery(
+            ids.generate(),
+            hook,
+            config.indexnow_key.as_deref(),
+            event,
+            &post.public_id,
+            &url,
+            now,
+        ) else {
+            continue;
+        };
+        repo.enqueue(&delivery).await?;
+        queued += 1;
+    }
+
+    Ok(queued)
+}
+
+/// Sends one delivery; returns the HTTP status if a response arrived
+async fn send(
+    client: &Client,
+    delivery: &HookDelivery,
+    now: DateTime<Utc>,
+) -> (Option<i32>, Result<(), String>) {
+    let body = delivery.body.clone().unwrap_or_default();
+    let mut request = match delivery.method.as_str() {
+        "GET" => client.get(&delivery.url),
+        _ => client.post(&delivery.url),
+    };
+
+    // Signed deliveries are ActivityPub activities
+    if let Some(key_id) = &delivery.signed_by {
+        let signed = Url::parse(&delivery.url)
+            .map_err(anyhow::Error::from)
+            .and_then(|url| {
+                INSTANCE_KEY.sign(key_id, &delivery.method, &url, body.as_bytes(), now)
+            });
+        match signed {
+            Ok(headers) => {
+                for (name, value) in headers {
+                    request = request.header(name, value);
+                }
+                request =
+                    request.header(reqwest::header::CONTENT_TYPE, "application/activity+json");
+            }
+            Err(err) => return (None, Err(format!("unable to sign the request: {}", err))),
+        }
+    } else if delivery.method != "GET" {
+        request = request.header(reqwest::header::CONTENT_TYPE, "application/json");
+    }
+    if delivery.method != "GET" {
+        request = request.body(body);
+    }
+
+    match request.timeout(DELIVERY_TIMEOUT).send().await {
+        Ok(response) => {
+            let status = response.status();
+            let result = if status.is_success() {
+                Ok(())
+            } else {
+                Err(format!("endpoint answered {}", status))
+            };
+            (Some(status.as_u16() as i32), result)
+        }
+        Err(err) => (None, Err(err.to_string())),
+    }
+}
+
+/// Sends every due delivery once and schedules retries for the failures
+pub async fn deliver_due(
+    pool: &PgPool,
+    client: &Client,
+    config: &PublishHooksConfig,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let repo = HookRepository::new(pool.clone());
+    let due = repo.claim_due(now, DELIVERY_BATCH).await?;
+
+    for delivery in &due {
+        let (http_status, result) = send(client, delivery, now).await;
+        let attempts = delivery.attempts + 1;
+
+        let (status, error, next_attempt_at) = match result {
+            Ok(()) => {
+                info!("Delivered {} hook to {}", delivery.event, delivery.url);
+                (DeliveryStatus::DELIVERED, None, now)
+            }
+            Err(err) if attempts >= config.max_attempts => {
+                error!(
+                    "Giving up on {} hook to {} after {} attempts: {}",
+                    delivery.event, delivery.url, attempts, err
+                );
+                (DeliveryStatus::FAILED, Some(err), now)
+            }
+            Err(err) => {
+                let delay = retry_delay(attempts, config.retry_base);
+                warn!(
+                    "{} hook to {} failed ({}), retrying in {:?}",
+                    delivery.event, delivery.url, err, delay
+                );
+                let next =
+                    now + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::hours(1));
+                (DeliveryStatus::PENDING, Some(err), next)
+            }
+        };
+
+        repo.record_attempt(
+            delivery.id,
+            status,
+            http_status,
+            error.as_deref(),
+            next_attempt_at,
+            now,
+        )
+        .await?;
+    }
+
+    Ok(due.len())
+}
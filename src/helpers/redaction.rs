@@ -0,0 +1,110 @@
+//! Field-level redaction of response bodies based on who is asking.
+//!
+//! The requester is resolved once per request and kept in a task-local, so
+//! response types can decide during serialization which fields to include
+//! instead of needing a separate struct per audience. A token only counts
+//! while [`SessionGuard`] accepts it; otherwise the requester is anonymous.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::{Serialize, Serializer};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::helpers::{
+    auth::AuthHelper,
+    middleware::{SessionGuard, request_token},
+};
+use crate::model::model::Role;
+
+/// Who a response is being rendered for
+#[derive(Debug, Clone, Default)]
+pub struct Audience {
+    pub user_id: Option<Uuid>,
+    pub role: Option<Role>,
+}
+
+impl Audience {
+    pub fn anonymous() -> Self {
+        Self::default()
+    }
+
+    pub fn user(user_id: Uuid, role: Role) -> Self {
+        Self {
+            user_id: Some(user_id),
+            role: Some(role),
+        }
+    }
+
+    /// Private fields are visible to their owner and to admins
+    pub fn sees_private(&self, owner: Uuid) -> bool {
+        self.role == Some(Role::ADMIN) || self.user_id == Some(owner)
+    }
+}
+
+tokio::task_local! {
+    static AUDIENCE: Audience;
+}
+
+pub async fn redaction_middleware(
+    State(guard): State<SessionGuard>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let audience = match request_token(request.headers()) {
+        Some(token) => resolve(&guard, &token).await,
+        None => Audience::anonymous(),
+    };
+
+    with_audience(audience, next.run(request)).await
+}
+
+/// The requester `token` names, if its session and account still let it be used
+async fn resolve(guard: &SessionGuard, token: &str) -> Audience {
+    let Some((user_id, role)) = AuthHelper::validate_token(token, guard.clock.as_ref())
+        .ok()
+        .and_then(|claims| {
+            Uuid::parse_str(&claims.sub)
+                .ok()
+                .map(|user_id| (user_id, claims.role))
+        })
+    else {
+        return Audience::anonymous();
+    };
+
+    match guard.check(token, user_id).await {
+        Ok(_) => Audience::user(user_id, role),
+        Err(err) => {
+            info!("Redacting as for an anonymous requester: {}", err.detail);
+            Audience::anonymous()
+        }
+    }
+}
+
+/// Runs `future` with `audience` as the requester for serialization
+pub async fn with_audience<F: Future>(audience: Audience, future: F) -> F::Output {
+    AUDIENCE.scope(audience, future).await
+}
+
+/// Serializes a value as `audience` would see it, whoever made the request.
+/// Login and registration answer anonymous requests with the account they
+/// were about, which its new owner should see in full; exports and admin
+/// views name their reader the same way rather than rely on the middleware.
+pub struct SeenBy<T>(pub Audience, pub T);
+
+impl<T: Serialize> Serialize for SeenBy<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AUDIENCE.sync_scope(self.0.clone(), || self.1.serialize(serializer))
+    }
+}
+
+/// Whether the current requester may see `owner`'s private fields. Outside a
+/// request private fields are hidden unless a [`SeenBy`] names who may see them.
+pub fn sees_private(owner: Uuid) -> bool {
+    AUDIENCE
+        .try_with(|audience| audience.sees_private(owner))
+        .unwrap_or(false)
+}
@@ -0,0 +1,33 @@
+//! Redirects back to a front-end after a link in an email is followed.
+//!
+//! Only URIs on an allowed origin are accepted, so verification links can't
+//! be turned into an open redirect.
+
+use reqwest::Url;
+
+/// Whether `uri` is an absolute http(s) URL whose origin, e.g.
+/// `https://app.example.com`, is one of `allowed_origins`
+pub fn is_allowed_redirect(uri: &str, allowed_origins: &[String]) -> bool {
+    let Ok(url) = Url::parse(uri) else {
+        return false;
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+
+    let origin = url.origin().ascii_serialization();
+    allowed_origins.iter().any(|allowed| {
+        Url::parse(allowed).is_ok_and(|allowed| allowed.origin().ascii_serialization() == origin)
+    })
+}
+
+/// `uri` with `params` appended to its query string
+pub fn with_query(uri: &str, params: &[(&str, &str)]) -> String {
+    match Url::parse(uri) {
+        Ok(mut url) => {
+            url.query_pairs_mut().extend_pairs(params);
+            url.to_string()
+        }
+        Err(_) => uri.to_string(),
+    }
+}
@@ -0,0 +1,83 @@
+//! Shared Redis connection for state that must agree across replicas: rate
+//! limit counters, the revoked-session denylist and the cache.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use redis::{AsyncCommands, Client, aio::ConnectionManager};
+use tracing::info;
+
+/// Cheap to clone; every clone multiplexes over the same connection
+#[derive(Clone)]
+pub struct RedisClient {
+    connection: ConnectionManager,
+    prefix: String,
+}
+
+impl RedisClient {
+    pub async fn connect(url: &str, key_prefix: &str) -> Result<Self> {
+        let client = Client::open(url).context("invalid REDIS_URL")?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .context("unable to connect to Redis")?;
+        info!("Connected to Redis with key prefix {:?}", key_prefix);
+        Ok(Self {
+            connection,
+            prefix: key_prefix.to_string(),
+        })
+    }
+
+    /// `key` namespaced with the configured prefix
+    pub fn key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    pub fn connection(&self) -> ConnectionManager {
+        self.connection.clone()
+    }
+
+    /// Counts a hit in the fixed window `key` belongs to; returns the hits so
+    /// far and the time until the window resets
+    pub async fn hit(&self, key: &str, window: Duration) -> Result<(u64, Duration)> {
+        let key = self.key(key);
+        let mut connection = self.connection();
+        // SET NX starts the window with its expiry; INCR never resets it
+        let (count, ttl): (u64, i64) = redis::pipe()
+            .atomic()
+            .cmd("SET")
+            .arg(&key)
+            .arg(0)
+            .arg("EX")
+            .arg(window.as_secs().max(1))
+            .arg("NX")
+            .ignore()
+            .incr(&key, 1)
+            .ttl(&key)
+            .query_async(&mut connection)
+            .await?;
+        Ok((count, Duration::from_secs(ttl.max(1) as u64)))
+    }
+
+    /// Marks `id` as denied for `ttl`
+    pub async fn deny(&self, id: &str, ttl: Duration) -> Result<()> {
+        let mut connection = self.connection();
+        let _: () = connection
+            .set_ex(self.key(&format!("denied:{}", id)), 1, ttl.as_secs().max(1))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn is_denied(&self, id: &str) -> Result<bool> {
+        let mut connection = self.connection();
+        Ok(connection
+            .exists(self.key(&format!("denied:{}", id)))
+            .await?)
+    }
+
+    /// Round trip to the server, for diagnostics
+    pub async fn ping(&self) -> Result<()> {
+        let mut connection = self.connection();
+        let _: String = redis::cmd("PING").query_async(&mut connection).await?;
+        Ok(())
+    }
+}
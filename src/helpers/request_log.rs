@@ -0,0 +1,64 @@
+//! One structured log event per request.
+//!
+//! Each event carries the method, the route template rather than the raw URI
+//! (paths can hold preview and verification tokens, queries share-link ones),
+//! the status, latency, the authenticated user and the request id. Server
+//! errors log at `error`, and requests slower than the configured threshold
+//! at `warn`.
+
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::helpers::request_id::RequestId;
+
+/// Put on the response by the auth middleware so the request log can name
+/// the caller; request extensions don't travel back out of inner layers
+#[derive(Debug, Clone, Copy)]
+pub struct AuthenticatedUser(pub Uuid);
+
+pub async fn request_log_middleware(
+    State(slow_threshold): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone())
+        .unwrap_or_default();
+
+    let response = next.run(request).await;
+
+    let latency = started.elapsed();
+    let latency_ms = latency.as_millis() as u64;
+    let status = response.status().as_u16();
+    let user_id = response
+        .extensions()
+        .get::<AuthenticatedUser>()
+        .map(|user| user.0.to_string())
+        .unwrap_or_else(|| "-".to_string());
+
+    if response.status().is_server_error() {
+        error!(%method, %route, status, latency_ms, %user_id, %request_id, "request failed");
+    } else if latency >= slow_threshold {
+        warn!(%method, %route, status, latency_ms, %user_id, %request_id, "slow request");
+    } else {
+        info!(%method, %route, status, latency_ms, %user_id, %request_id, "request completed");
+    }
+
+    response
+}
@@ -1,19 +1,46 @@
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use std::sync::OnceLock;
+
+use axum::{
+    Json,
+    http::{HeaderValue, Method, StatusCode, header},
+    response::{IntoResponse, Response},
+};
 use axum_extra::extract::cookie::Cookie;
 use serde_json::Value;
 use utoipa::ToSchema;
 
-use crate::model::model::{ApiResponse, ErrorResponse};
+use crate::config::ErrorFormat;
+use crate::helpers::i18n::localize;
+use crate::helpers::negotiation::Negotiated;
+use crate::helpers::request_id::current_request_id;
+use crate::model::model::{ApiResponse, ErrorResponse, LegacyErrorResponse};
 
 // Type aliases for OpenAPI documentation
 pub type ApiSuccessResponse<T> = ApiResponse<T>;
 pub type ApiErrorResponse = ErrorResponse;
 
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+static ERROR_FORMAT: OnceLock<ErrorFormat> = OnceLock::new();
+
+/// Picks the error body shape for the whole process; later calls are ignored
+pub fn set_error_format(format: ErrorFormat) {
+    let _ = ERROR_FORMAT.set(format);
+}
+
+pub fn error_format() -> ErrorFormat {
+    ERROR_FORMAT.get().copied().unwrap_or_default()
+}
+
 #[derive(serde::Serialize, ToSchema)]
 #[serde(untagged)]
 pub enum UnifiedResponse<T> {
     Success(ApiResponse<T>),
     Error(ErrorResponse),
+    /// 201 with a `Location` header; documented per route, so left out of
+    /// the schema
+    #[serde(skip)]
+    Created(String, ApiResponse<T>),
 }
 
 impl<T> IntoResponse for UnifiedResponse<T>
@@ -22,20 +49,73 @@ where
 {
     fn into_response(self) -> axum::response::Response {
         match self {
-            UnifiedResponse::Success(response) => {
-                let (status, json) = (StatusCode::OK, Json(response));
-                (status, json).into_response()
+            UnifiedResponse::Success(mut response) => {
+                localize(&mut response.message);
+                (StatusCode::OK, Negotiated(response)).into_response()
             }
-            UnifiedResponse::Error(err) => {
-                let (status, json) = (StatusCode::BAD_REQUEST, Json(err));
-                (status, json).into_response()
+            UnifiedResponse::Error(err) => err.into_response(),
+            UnifiedResponse::Created(location, mut response) => {
+                localize(&mut response.message);
+                (
+                    StatusCode::CREATED,
+                    [(header::LOCATION, location)],
+                    Negotiated(response),
+                )
+                    .into_response()
             }
         }
     }
 }
 
+impl IntoResponse for ErrorResponse {
+    fn into_response(mut self) -> axum::response::Response {
+        let status = self.status_code();
+        // Titles stay in English so clients can match on them
+        localize(&mut self.detail);
+
+        match error_format() {
+            ErrorFormat::Problem => {
+                if self.instance.is_none() {
+                    self.instance = current_request_id();
+                }
+                let mut response = (status, Json(self)).into_response();
+                response
+                    .headers_mut()
+                    .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+                response
+            }
+            ErrorFormat::Legacy => (status, Json(LegacyErrorResponse::from(self))).into_response(),
+        }
+    }
+}
+
+/// Fallback for a known path called with a method it doesn't support; the
+/// router adds the `Allow` header listing the ones it does
+///
+/// A plain `OPTIONS` request gets 204 with that header, everything else a
+/// 405 error body.
+pub async fn method_not_allowed(method: Method) -> Response {
+    if method == Method::OPTIONS {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+    ErrorResponse::new(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "Method Not Allowed",
+        format!("{} is not supported on this endpoint", method),
+    )
+    .into_response()
+}
+
 pub fn error_response_generic<T>(error: String, message: String) -> UnifiedResponse<T> {
-    UnifiedResponse::Error(ErrorResponse { error, message })
+    UnifiedResponse::Error(ErrorResponse::new(StatusCode::BAD_REQUEST, error, message))
+}
+
+pub fn forbidden_response_generic<T>(message: String) -> UnifiedResponse<T> {
+    UnifiedResponse::Error(ErrorResponse::new(
+        StatusCode::FORBIDDEN,
+        "Forbidden",
+        message,
+    ))
 }
 
 pub fn not_found_response_generic<T>(message: String) -> UnifiedResponse<T> {
@@ -46,10 +126,11 @@ pub fn not_found_response_generic<T>(message: String) -> UnifiedResponse<T> {
 }
 
 pub fn sql_error_generic<T>(_error: anyhow::Error, context: &str) -> UnifiedResponse<T> {
-    UnifiedResponse::Error(ErrorResponse {
-        error: "Database Error".to_string(),
-        message: context.to_string(),
-    })
+    UnifiedResponse::Error(ErrorResponse::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Database Error",
+        context,
+    ))
 }
 
 pub fn create_response<T>(
@@ -65,15 +146,11 @@ pub fn create_error_response(
     error: String,
     message: String,
     status_code: StatusCode,
-) -> (StatusCode, Json<ErrorResponse>) {
-    let response = ErrorResponse { error, message };
-    (status_code, Json(response))
+) -> ErrorResponse {
+    ErrorResponse::new(status_code, error, message)
 }
 
-pub fn handle_sql_error(
-    error: anyhow::Error,
-    error_ctx: &str,
-) -> (StatusCode, Json<ErrorResponse>) {
+pub fn handle_sql_error(error: anyhow::Error, error_ctx: &str) -> ErrorResponse {
     create_error_response(
         "DatabaseError".to_string(),
         format!("{}: {}", error_ctx, error),
@@ -88,8 +165,19 @@ pub fn success_response<T>(message: String, data: T) -> UnifiedResponse<T> {
     })
 }
 
+/// 201 Created for a new resource at `location`, a path under the base path
+pub fn created_response<T>(location: String, message: String, data: T) -> UnifiedResponse<T> {
+    UnifiedResponse::Created(
+        location,
+        ApiResponse {
+            message,
+            data: Some(data),
+        },
+    )
+}
+
 pub fn error_response(error: String, message: String) -> UnifiedResponse<Value> {
-    UnifiedResponse::Error(ErrorResponse { error, message })
+    UnifiedResponse::Error(ErrorResponse::new(StatusCode::BAD_REQUEST, error, message))
 }
 
 pub fn not_found_response(message: String) -> UnifiedResponse<Value> {
@@ -100,10 +188,11 @@ pub fn not_found_response(message: String) -> UnifiedResponse<Value> {
 }
 
 pub fn sql_error_response(error: anyhow::Error, context: &str) -> UnifiedResponse<Value> {
-    UnifiedResponse::Error(ErrorResponse {
-        error: "Database Error".to_string(),
-        message: format!("{}: {}", context, error),
-    })
+    UnifiedResponse::Error(ErrorResponse::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Database Error",
+        format!("{}: {}", context, error),
+    ))
 }
 
 // Cookie-enabled response for login functionality
@@ -164,15 +253,20 @@ pub fn success_response_with_cookies<T>(
 }
 
 pub fn error_response_with_cookies<T>(error: String, message: String) -> CookieResponse<T> {
-    CookieResponse::new(UnifiedResponse::Error(ErrorResponse { error, message }))
+    CookieResponse::new(UnifiedResponse::Error(ErrorResponse::new(
+        StatusCode::BAD_REQUEST,
+        error,
+        message,
+    )))
 }
 
 pub fn sql_error_response_with_cookies<T>(
     error: anyhow::Error,
     context: &str,
 ) -> CookieResponse<T> {
-    CookieResponse::new(UnifiedResponse::Error(ErrorResponse {
-        error: "DatabaseError".to_string(),
-        message: format!("{}: {}", context, error),
-    }))
+    CookieResponse::new(UnifiedResponse::Error(ErrorResponse::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "DatabaseError",
+        format!("{}: {}", context, error),
+    )))
 }
@@ -0,0 +1,43 @@
+//! Deployment secrets read from files or the environment.
+//!
+//! Every secret `NAME` can also be given as `NAME_FILE`, a path whose contents
+//! hold the value, so orchestrators can mount secrets instead of exposing them
+//! in the process environment. The file wins when both are set.
+
+use std::{env, fs};
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Reads `name` from the file named by `{name}_FILE`, else from `name` itself;
+/// surrounding whitespace is dropped and an empty value counts as unset
+pub fn read_secret(name: &str) -> Result<Option<String>> {
+    let file_var = format!("{}_FILE", name);
+    let value = match env::var(&file_var) {
+        Ok(path) => {
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("unable to read {} ({})", file_var, path))?;
+            if contents.trim().is_empty() {
+                bail!("{} ({}) is empty", file_var, path);
+            }
+            contents
+        }
+        Err(_) => env::var(name).unwrap_or_default(),
+    };
+
+    let value = value.trim();
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/// Mixes the server-side `pepper` into `password` before it is hashed
+///
+/// The HMAC digest is base64 encoded, which keeps it inside bcrypt's 72 byte
+/// limit however long the password is.
+pub fn pepper_password(password: &str, pepper: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(pepper.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(password.as_bytes());
+    STANDARD.encode(mac.finalize().into_bytes())
+}
@@ -0,0 +1,143 @@
+//! Emails warning users of sign-ins from devices they haven't used before.
+//!
+//! Each session records the user agent, the network it signed in from (the
+//! /24 of an IPv4 address, the /48 of an IPv6 one) and, when a proxy in front
+//! of the API reports it, the country. A login is from a known device when an
+//! earlier session of the user had the same user agent and either the same
+//! network or the same country. Otherwise the user is emailed the device,
+//! address and time, with a link that signs that session out without having
+//! to log in. The link carries a single-use token from
+//! [`crate::helpers::link_tokens`] naming the session, valid as long as the
+//! session could be. Following it only shows a confirmation page; the session
+//! is signed out by the form on that page, so mail scanners and link previews
+//! fetching the link change nothing.
+
+use std::env;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use axum::http::HeaderMap;
+use tracing::{error, info};
+
+use crate::config::AppConfig;
+use crate::helpers::clock::Clock;
+use crate::helpers::email_templates::{EmailKind, EmailTemplates};
+use crate::helpers::feature_flags::FeatureFlags;
+use crate::helpers::link_tokens::{LinkPurpose, LinkTokenStore};
+use crate::helpers::mailer::Mailer;
+use crate::helpers::redirects::with_query;
+use crate::model::model::{FeatureFlag, Session, User};
+
+/// Country codes proxies send when they don't know: Cloudflare uses `XX` for
+/// unknown addresses and `T1` for Tor
+const UNKNOWN_COUNTRIES: [&str; 2] = ["XX", "T1"];
+
+/// The network `ip` belongs to, coarse enough to stay the same while a
+/// device moves around one ISP's addresses
+pub fn coarse_network(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{}.{}.{}.0/24", a, b, c)
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            format!("{:x}:{:x}:{:x}::/48", segments[0], segments[1], segments[2])
+        }
+    }
+}
+
+/// The uppercased two-letter country code in `header`, if it holds one
+pub fn country_from(headers: &HeaderMap, header: Option<&str>) -> Option<String> {
+    let value = headers.get(header?)?.to_str().ok()?.trim().to_uppercase();
+    (value.len() == 2
+        && value.chars().all(|c| c.is_ascii_alphabetic())
+        && !UNKNOWN_COUNTRIES.contains(&value.as_str()))
+    .then_some(value)
+}
+
+/// Everything needed to send new sign-in alerts from a request
+#[derive(Clone)]
+pub struct SignInAlerts {
+    pub flags: Arc<FeatureFlags>,
+    pub mailer: Arc<dyn Mailer>,
+    pub tokens: Arc<dyn LinkTokenStore>,
+    pub emails: Arc<EmailTemplates>,
+    pub config: Arc<AppConfig>,
+    pub clock: Arc<dyn Clock>,
+}
+
+impl SignInAlerts {
+    /// Tells `user` that `session` signed in from a new device; failures are
+    /// logged and never fail the login, and nothing is sent while alerts or
+    /// email sending are switched off
+    pub async fn send(&self, user: &User, locale: Option<&str>, session: &Session) {
+        if !self.config.accounts.new_device_alerts {
+            return;
+        }
+        if !self.flags.is_enabled(FeatureFlag::EmailSending) {
+            info!(
+                "Email sending is disabled; not sending a new sign-in alert to user {}",
+                user.id
+            );
+            return;
+        }
+
+        let token = match self
+            .tokens
+            .issue(
+                session.user_id,
+                LinkPurpose::RevokeSession,
+                Some(&session.id),
+                self.clock.now(),
+                session.expires_at,
+            )
+            .await
+        {
+            Ok(token) => token,
+            Err(e) => {
+                error!("Failed to issue session revoke token: {:?}", e);
+                return;
+            }
+        };
+        let base_url = env::var("BASE_URL").unwrap_or_else(|_| "localhost:3000".to_string());
+        let revoke_link = with_query(
+            &format!(
+                "http://{}{}",
+                base_url,
+                self.config.api_path("/auth/revoke-session")
+            ),
+            &[("token", token.as_str())],
+        );
+
+        let unknown = "unknown".to_string();
+        let location = session
+            .country
+            .as_ref()
+            .or(session.network.as_ref())
+            .unwrap_or(&unknown);
+        let time = session.created_at.format("%Y-%m-%d %H:%M UTC").to_string();
+        let email = match self.emails.render(
+            EmailKind::NewSignIn,
+            locale,
+            &[
+                ("name", &user.name),
+                ("device", &session.device),
+                ("ip", session.ip.as_ref().unwrap_or(&unknown)),
+                ("location", location),
+                ("time", &time),
+                ("link", &revoke_link),
+            ],
+        ) {
+            Ok(email) => email,
+            Err(e) => {
+                error!("Failed to render new sign-in alert: {:?}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = self.mailer.send(&user.email, &email).await {
+            error!("Failed to send new sign-in alert: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! Bot checks on `POST /auth/register`, each switched on separately.
+//!
+//! - A honeypot: registration forms carry a `website` field hidden from
+//!   people, so only bots fill it in.
+//! - A minimum fill time: `GET /auth/register/form` hands out a signed form
+//!   token, and registrations sent sooner than `SIGNUP_MIN_FILL_SECS` after
+//!   it was issued are turned away.
+//! - Disposable email domains from `DISPOSABLE_DOMAINS_FILE`, on top of the
+//!   ones mailchecker already knows.
+//! - A CAPTCHA, checked with the configured [`CaptchaVerifier`].
+//!
+//! Bots tripping the honeypot or the timer get a vague answer, so they learn
+//! nothing about which check caught them.
+
+use std::collections::HashSet;
+use std::fs;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::config::{CaptchaProvider, SignupConfig};
+use crate::helpers::auth::{BASE_URL, JWT_KEYS, TOKEN_LEEWAY_SECS};
+use crate::helpers::clock::Clock;
+use crate::helpers::email_policy::{email_domain, normalize_domain};
+use crate::model::model::SignupProof;
+
+pub type VerifyFuture<'a> = Pin<Box<dyn Future<Output = Result<bool>> + Send + 'a>>;
+
+/// Marks a token as a registration form token; auth tokens never carry a `scope`
+const FORM_SCOPE: &str = "signup-form";
+
+/// How long a form token works; a form left open longer must be reloaded
+pub const FORM_TOKEN_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Timeout for a single CAPTCHA check
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const HCAPTCHA_VERIFY_URL: &str = "https://api.hcaptcha.com/siteverify";
+const TURNSTILE_VERIFY_URL: &str = "https://challenges.cloudflare.com/turnstile/v0/siteverify";
+
+pub trait CaptchaVerifier: Send + Sync {
+    /// `hcaptcha` or `turnstile`
+    fn name(&self) -> &'static str;
+    /// Whether the service accepts the response token a client solved
+    fn verify<'a>(&'a self, token: &'a str, remote_ip: Option<IpAddr>) -> VerifyFuture<'a>;
+}
+
+/// Checks tokens against a `siteverify` endpoint; hCaptcha and Turnstile
+/// share the same protocol
+pub struct SiteVerifyCaptcha {
+    name: &'static str,
+    client: Client,
+    url: String,
+    secret: String,
+}
+
+impl SiteVerifyCaptcha {
+    pub fn hcaptcha(client: Client, secret: String) -> Self {
+        Self::new("hcaptcha", client, HCAPTCHA_VERIFY_URL.to_string(), secret)
+    }
+
+    pub fn turnstile(client: Client, secret: String) -> Self {
+        Self::new(
+            "turnstile",
+            client,
+            TURNSTILE_VERIFY_URL.to_string(),
+            secret,
+        )
+    }
+
+    pub fn new(name: &'static str, client: Client, url: String, secret: String) -> Self {
+        Self {
+            name,
+            client,
+            url,
+            secret,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+}
+
+impl CaptchaVerifier for SiteVerifyCaptcha {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn verify<'a>(&'a self, token: &'a str, remote_ip: Option<IpAddr>) -> VerifyFuture<'a> {
+        Box::pin(async move {
+            let remote_ip = remote_ip.map(|ip| ip.to_string());
+            let mut form = vec![("secret", self.secret.as_str()), ("response", token)];
+            if let Some(ip) = &remote_ip {
+                form.push(("remoteip", ip.as_str()));
+            }
+
+            let response = self
+                .client
+                .post(&self.url)
+                .form(&form)
+                .timeout(VERIFY_TIMEOUT)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                bail!("{} answered {}", self.name, response.status());
+            }
+            let body: SiteVerifyResponse = serde_json::from_str(&response.text().await?)?;
+            Ok(body.success)
+        })
+    }
+}
+
+/// What a registration form needs to pass the enabled checks
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RegistrationForm {
+    /// Send back as `form_token`; `None` when registrations need none
+    pub form_token: Option<String>,
+    /// Seconds to wait after loading the form before it is accepted
+    pub min_fill_secs: u64,
+    /// Whether to include an empty, hidden `website` field
+    pub honeypot: bool,
+    /// CAPTCHA widget to show; `None` when registrations need none
+    pub captcha: Option<CaptchaWidget>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CaptchaWidget {
+    /// `hcaptcha` or `turnstile`
+    pub provider: String,
+    pub site_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FormClaims {
+    iss: String,
+    scope: String,
+    iat: usize,
+    exp: usize,
+}
+
+/// Why a registration was turned away
+#[derive(Debug)]
+pub enum SignupRejection {
+    Honeypot,
+    TooFast,
+    MissingFormToken,
+    InvalidFormToken,
+    DisposableEmail(String),
+    MissingCaptcha,
+    FailedCaptcha,
+    /// The CAPTCHA service couldn't be asked; registrations fail closed
+    CaptchaUnavailable(anyhow::Error),
+}
+
+impl SignupRejection {
+    /// What the client is told; bot checks stay vague
+    pub fn message(&self) -> String {
+        match self {
+            SignupRejection::Honeypot | SignupRejection::TooFast => {
+                "Registration could not be completed; please try again".to_string()
+            }
+            SignupRejection::MissingFormToken | SignupRejection::InvalidFormToken => {
+                "The registration form has expired; reload it and try again".to_string()
+            }
+            SignupRejection::DisposableEmail(domain) => {
+                format!("Disposable email addresses at {} are not accepted", domain)
+            }
+            SignupRejection::MissingCaptcha | SignupRejection::FailedCaptcha => {
+                "Complete the CAPTCHA and try again".to_string()
+            }
+            SignupRejection::CaptchaUnavailable(_) => {
+                "The CAPTCHA could not be checked; please try again later".to_string()
+            }
+        }
+    }
+}
+
+/// The enabled bot checks
+#[derive(Clone, Default)]
+pub struct SignupGuard {
+    honeypot: bool,
+    min_fill_time: Option<Duration>,
+    disposable_domains: Arc<HashSet<String>>,
+    captcha: Option<(Arc<dyn CaptchaVerifier>, String)>,
+}
+
+impl SignupGuard {
+    /// Checks nothing
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn from_config(config: &SignupConfig) -> Result<Self> {
+        let mut guard = Self::disabled()
+            .with_honeypot(config.honeypot)
+            .with_min_fill_time(config.min_fill_time);
+
+        if let Some(path) = &config.disposable_domains_file {
+            let list = fs::read_to_string(path)
+                .with_context(|| format!("unable to read {}", path.display()))?;
+            guard = guard.with_disposable_domains(parse_domain_list(&list));
+            info!(
+                "Refusing {} disposable email domain(s) from {}",
+                guard.disposable_domains.len(),
+                path.display()
+            );
+        }
+
+        if let Some(provider) = &config.captcha {
+            let client = Client::new();
+            let (verifier, site_key) = match provider.clone() {
+                CaptchaProvider::HCaptcha { site_key, secret } => {
+                    (SiteVerifyCaptcha::hcaptcha(client, secret), site_key)
+                }
+                CaptchaProvider::Turnstile { site_key, secret } => {
+                    (SiteVerifyCaptcha::turnstile(client, secret), site_key)
+                }
+            };
+            info!("Registrations need a {} CAPTCHA", verifier.name());
+            guard = guard.with_captcha(Arc::new(verifier), site_key);
+        }
+
+        Ok(guard)
+    }
+
+    pub fn with_honeypot(mut self, honeypot: bool) -> Self {
+        self.honeypot = honeypot;
+        self
+    }
+
+    pub fn with_min_fill_time(mut self, min_fill_time: Option<Duration>) -> Self {
+        self.min_fill_time = min_fill_time;
+        self
+    }
+
+    pub fn with_disposable_domains(mut self, domains: HashSet<String>) -> Self {
+        self.disposable_domains = Arc::new(domains);
+        self
+    }
+
+    pub fn with_captcha(mut self, verifier: Arc<dyn CaptchaVerifier>, site_key: String) -> Self {
+        self.captcha = Some((verifier, site_key));
+        self
+    }
+
+    /// What a client needs to build a registration form that passes
+    pub fn form(&self, clock: &dyn Clock) -> Result<RegistrationForm> {
+        let form_token = match self.min_fill_time {
+            Some(_) => Some(issue_form_token(clock)?),
+            None => None,
+        };
+        Ok(RegistrationForm {
+            form_token,
+            min_fill_secs: self.min_fill_time.map_or(0, |time| time.as_secs()),
+            honeypot: self.honeypot,
+            captcha: self
+                .captcha
+                .as_ref()
+                .map(|(verifier, site_key)| CaptchaWidget {
+                    provider: verifier.name().to_string(),
+                    site_key: site_key.clone(),
+                }),
+        })
+    }
+
+    /// Runs the enabled checks, cheapest first
+    pub async fn check(
+        &self,
+        email: &str,
+        proof: &SignupProof,
+        remote_ip: Option<IpAddr>,
+        clock: &dyn Clock,
+    ) -> Result<(), SignupRejection> {
+        if self.honeypot
+            && proof
+                .website
+                .as_deref()
+                .is_some_and(|value| !value.is_empty())
+        {
+            return Err(SignupRejection::Honeypot);
+        }
+
+        if let Some(min_fill_time) = self.min_fill_time {
+            let token = proof
+                .form_token
+                .as_deref()
+                .ok_or(SignupRejection::MissingFormToken)?;
+            let issued_at =
+                verify_form_token(token, clock).map_err(|_| SignupRejection::InvalidFormToken)?;
+            if clock.timestamp().saturating_sub(issued_at) < min_fill_time.as_secs() as usize {
+                return Err(SignupRejection::TooFast);
+            }
+        }
+
+        if let Some(domain) = email_domain(email).filter(|domain| self.is_disposable(domain)) {
+            return Err(SignupRejection::DisposableEmail(domain));
+        }
+
+        if let Some((verifier, _)) = &self.captcha {
+            let token = proof
+                .captcha_token
+                .as_deref()
+                .filter(|token| !token.is_empty())
+                .ok_or(SignupRejection::MissingCaptcha)?;
+            match verifier.verify(token, remote_ip).await {
+                Ok(true) => {}
+                Ok(false) => return Err(SignupRejection::FailedCaptcha),
+                Err(e) => return Err(SignupRejection::CaptchaUnavailable(e)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `domain` or any domain it is under is on the list
+    fn is_disposable(&self, domain: &str) -> bool {
+        let mut candidate = domain;
+        loop {
+            if self.disposable_domains.contains(candidate) {
+                return true;
+            }
+            match candidate.split_once('.') {
+                Some((_, parent)) if parent.contains('.') => candidate = parent,
+                _ => return false,
+            }
+        }
+    }
+}
+
+/// Domains in a blocklist file, one per line; blank lines and `#` comments
+/// are skipped
+pub fn parse_domain_list(list: &str) -> HashSet<String> {
+    list.lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter_map(normalize_domain)
+        .collect()
+}
+
+fn issue_form_token(clock: &dyn Clock) -> Result<String> {
+    let now = clock.timestamp();
+    JWT_KEYS.sign(&FormClaims {
+        iss: BASE_URL.clone(),
+        scope: FORM_SCOPE.to_string(),
+        iat: now,
+        exp: now + FORM_TOKEN_TTL.as_secs() as usize,
+    })
+}
+
+/// When the form token was issued, in seconds since the epoch
+fn verify_form_token(token: &str, clock: &dyn Clock) -> Result<usize> {
+    let claims: FormClaims = JWT_KEYS.verify(token, clock)?;
+
+    if claims.scope != FORM_SCOPE {
+        bail!("not a registration form token");
+    }
+    if claims.exp + TOKEN_LEEWAY_SECS < clock.timestamp() {
+        bail!("registration form token has expired");
+    }
+    Ok(claims.iat)
+}
@@ -0,0 +1,105 @@
+//! Sitemaps of the public posts and author profiles, for search engines.
+//!
+//! Up to [`MAX_SITEMAP_URLS`] URLs are listed in `/sitemap.xml` itself.
+//! Past that it becomes a sitemap index over numbered pages of posts and
+//! profiles, each within the limit.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use quick_xml::escape::escape;
+
+/// Most URLs a single sitemap may list, per the sitemaps protocol
+pub const MAX_SITEMAP_URLS: i64 = 50_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SitemapSection {
+    Posts,
+    Profiles,
+}
+
+impl SitemapSection {
+    pub fn name(self) -> &'static str {
+        match self {
+            SitemapSection::Posts => "posts",
+            SitemapSection::Profiles => "profiles",
+        }
+    }
+
+    /// The section and 1-based page of a page file name, e.g. `posts-2.xml`
+    pub fn from_file_name(name: &str) -> Option<(SitemapSection, i64)> {
+        let (section, page) = name.strip_suffix(".xml")?.rsplit_once('-')?;
+        let section = match section {
+            "posts" => SitemapSection::Posts,
+            "profiles" => SitemapSection::Profiles,
+            _ => return None,
+        };
+        let page = page.parse().ok().filter(|page| *page >= 1)?;
+        Some((section, page))
+    }
+
+    pub fn file_name(self, page: i64) -> String {
+        format!("{}-{}.xml", self.name(), page)
+    }
+}
+
+/// One listed page and when it last changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapUrl {
+    pub loc: String,
+    pub lastmod: DateTime<Utc>,
+}
+
+/// Pages needed to list `count` URLs
+pub fn page_count(count: i64) -> i64 {
+    (count.max(0) + MAX_SITEMAP_URLS - 1) / MAX_SITEMAP_URLS
+}
+
+/// Page files of the index, posts first; empty when everything fits in one
+/// sitemap
+pub fn index_pages(posts: i64, profiles: i64) -> Vec<(SitemapSection, i64)> {
+    if posts + profiles <= MAX_SITEMAP_URLS {
+        return Vec::new();
+    }
+    let posts = (1..=page_count(posts)).map(|page| (SitemapSection::Posts, page));
+    let profiles = (1..=page_count(profiles)).map(|page| (SitemapSection::Profiles, page));
+    posts.chain(profiles).collect()
+}
+
+pub fn render_urlset(urls: &[SitemapUrl]) -> String {
+    let entries: String = urls
+        .iter()
+        .map(|url| {
+            format!(
+                "<url><loc>{}</loc><lastmod>{}</lastmod></url>",
+                escape(url.loc.as_str()),
+                url.lastmod.to_rfc3339_opts(SecondsFormat::Secs, true)
+            )
+        })
+        .collect();
+
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">",
+            "{}",
+            "</urlset>\n"
+        ),
+        entries
+    )
+}
+
+pub fn render_index(sitemaps: &[String]) -> String {
+    let entries: String = sitemaps
+        .iter()
+        .map(|loc| format!("<sitemap><loc>{}</loc></sitemap>", escape(loc.as_str())))
+        .collect();
+
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">",
+            "{}",
+            "</sitemapindex>\n"
+        ),
+        entries
+    )
+}
@@ -0,0 +1,172 @@
+//! Slugs for post URLs, usernames, and the names nobody may claim.
+//!
+//! Generated slugs follow the deployment's [`SlugConfig`]. Names chosen by
+//! users go through [`validate_slug`] or [`validate_username`], so every
+//! subsystem that hands out public names rejects the same reserved list.
+
+use deunicode::deunicode;
+
+use crate::config::SlugConfig;
+
+/// Used when a title has no usable characters
+const FALLBACK_SLUG: &str = "post";
+
+/// Characters taken from a random id to tell apart posts with the same title
+const SUFFIX_LEN: usize = 6;
+
+pub fn is_reserved(name: &str, config: &SlugConfig) -> bool {
+    let name = name.to_lowercase();
+    config.reserved.contains(&name)
+}
+
+/// Lowercase letters and digits; non-ASCII letters only without transliteration
+fn is_slug_char(c: char, config: &SlugConfig) -> bool {
+    if config.transliterate {
+        c.is_ascii_lowercase() || c.is_ascii_digit()
+    } else {
+        c.is_alphanumeric() && !c.is_uppercase()
+    }
+}
+
+/// Cuts at the last hyphen that fits, or mid-word for a single long word
+fn truncate(slug: &str, max_length: usize) -> String {
+    if slug.chars().count() <= max_length {
+        return slug.to_string();
+    }
+
+    let cut: String = slug.chars().take(max_length).collect();
+    let at_boundary = slug.chars().nth(max_length) == Some('-');
+    let cut = match cut.rfind('-') {
+        Some(pos) if !at_boundary && pos > 0 => cut[..pos].to_string(),
+        _ => cut,
+    };
+    cut.trim_end_matches('-').to_string()
+}
+
+/// Lowercase words of `title` joined by hyphens, without stop words
+pub fn slugify(title: &str, config: &SlugConfig) -> String {
+    let text = if config.transliterate {
+        deunicode(title)
+    } else {
+        title.to_string()
+    }
+    .to_lowercase();
+
+    let words: Vec<&str> = text
+        .split(|c: char| !is_slug_char(c, config))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let kept: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|word| !config.stop_words.iter().any(|stop| stop == word))
+        .collect();
+    // A title made only of stop words keeps them
+    let words = if kept.is_empty() { words } else { kept };
+
+    let slug = truncate(&words.join("-"), config.max_length);
+    if slug.is_empty() {
+        FALLBACK_SLUG.to_string()
+    } else {
+        slug
+    }
+}
+
+/// `slug` with a suffix taken from `random_id`, still within `max_length`
+pub fn with_suffix(slug: &str, random_id: &str, config: &SlugConfig) -> String {
+    let suffix: String = random_id
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .take(SUFFIX_LEN)
+        .collect();
+    let stem = truncate(slug, config.max_length.saturating_sub(suffix.len() + 1));
+    format!("{}-{}", stem, suffix)
+}
+
+/// Checks a name chosen by a user; returns the message to show when it is refused
+pub fn validate_slug(slug: &str, config: &SlugConfig) -> Result<(), String> {
+    if slug.is_empty() {
+        return Err("Slug cannot be empty".to_string());
+    }
+    if slug.chars().count() > config.max_length {
+        return Err(format!(
+            "Slug can be at most {} characters",
+            config.max_length
+        ));
+    }
+    if !slug.chars().all(|c| c == '-' || is_slug_char(c, config)) {
+        return Err("Slug may only contain lowercase letters, digits and hyphens".to_string());
+    }
+    if slug.starts_with('-') || slug.ends_with('-') || slug.contains("--") {
+        return Err("Slug cannot start or end with a hyphen or repeat hyphens".to_string());
+    }
+    if is_reserved(slug, config) {
+        return Err(format!("'{}' is reserved", slug));
+    }
+    Ok(())
+}
+
+pub const MIN_USERNAME_LENGTH: usize = 3;
+pub const MAX_USERNAME_LENGTH: usize = 30;
+
+/// Checks a username; usernames are case-insensitive, so pass it lowercased
+pub fn validate_username(username: &str, config: &SlugConfig) -> Result<(), String> {
+    let length = username.chars().count();
+    if !(MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&length) {
+        return Err(format!(
+            "Username must be between {} and {} characters",
+            MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH
+        ));
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(
+            "Username may only contain lowercase letters, digits and underscores".to_string(),
+        );
+    }
+    if !username.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return Err("Username must start with a letter".to_string());
+    }
+    if is_reserved(username, config) {
+        return Err(format!("'{}' is reserved", username));
+    }
+    Ok(())
+}
+
+/// A valid username derived from a display name, e.g. `ada_lovelace`; short
+/// enough that [`username_with_suffix`] still fits
+pub fn suggest_username(name: &str) -> String {
+    let text = deunicode(name).to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut username = words.join("_");
+    if !username.starts_with(|c: char| c.is_ascii_lowercase()) {
+        username = format!("user_{}", username);
+    }
+    username.truncate(MAX_USERNAME_LENGTH - SUFFIX_LEN - 1);
+    let username = username.trim_end_matches('_');
+    if username.len() < MIN_USERNAME_LENGTH {
+        format!("{}_user", username)
+    } else {
+        username.to_string()
+    }
+}
+
+/// `username` with a suffix taken from `random_id`, still within the maximum length
+pub fn username_with_suffix(username: &str, random_id: &str) -> String {
+    let suffix: String = random_id
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .take(SUFFIX_LEN)
+        .collect();
+    let mut stem = username.to_string();
+    stem.truncate(MAX_USERNAME_LENGTH - suffix.len() - 1);
+    format!("{}_{}", stem.trim_end_matches('_'), suffix)
+}
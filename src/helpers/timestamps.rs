@@ -0,0 +1,186 @@
+//! How timestamps in response bodies are written, chosen per request.
+//!
+//! By default they are RFC 3339 strings in UTC, such as
+//! `2025-03-01T09:00:00Z`. A client can ask for milliseconds since the Unix
+//! epoch instead with `?timestamps=epoch_ms` or `X-Timestamp-Format:
+//! epoch_ms`, or for RFC 3339 at a fixed UTC offset with `?tz=-05:00` or
+//! `X-Timezone: -05:00`. Query parameters win over headers.
+//!
+//! The choice is kept in a task-local and only applies while a
+//! [`Negotiated`](crate::helpers::negotiation::Negotiated) body is encoded,
+//! so values serialized for caches along the way keep the default form.
+
+use std::str::FromStr;
+
+use axum::{
+    extract::{Query, Request},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::model::model::ErrorResponse;
+
+pub const TIMESTAMP_FORMAT_HEADER: &str = "x-timestamp-format";
+pub const TIMEZONE_HEADER: &str = "x-timezone";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// RFC 3339 at `offset`; UTC is written with a `Z`
+    Rfc3339 { offset: FixedOffset },
+    /// Milliseconds since the Unix epoch, as a number
+    EpochMillis,
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        TimestampFormat::Rfc3339 {
+            offset: FixedOffset::east_opt(0).expect("zero is a valid offset"),
+        }
+    }
+}
+
+impl TimestampFormat {
+    /// The format named by `format` (`rfc3339` or `epoch_ms`) at `tz` (`Z`,
+    /// `UTC` or an offset like `+05:30`); either may be left out
+    pub fn parse(format: Option<&str>, tz: Option<&str>) -> Result<Self, String> {
+        let offset = match tz.map(str::trim) {
+            None | Some("") => None,
+            Some(tz) if tz.eq_ignore_ascii_case("z") || tz.eq_ignore_ascii_case("utc") => {
+                FixedOffset::east_opt(0)
+            }
+            Some(tz) => Some(FixedOffset::from_str(tz).map_err(|_| {
+                format!(
+                    "`{}` is not a UTC offset; use one such as +05:30, -08:00 or Z",
+                    tz
+                )
+            })?),
+        };
+
+        match format
+            .map(|format| format.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            None | Some("") | Some("rfc3339") => Ok(offset
+                .map(|offset| TimestampFormat::Rfc3339 { offset })
+                .unwrap_or_default()),
+            Some("epoch_ms") if offset.is_none() => Ok(TimestampFormat::EpochMillis),
+            Some("epoch_ms") => Err("Epoch timestamps have no time zone".to_string()),
+            Some(other) => Err(format!(
+                "Unknown timestamp format `{}`; use rfc3339 or epoch_ms",
+                other
+            )),
+        }
+    }
+
+    fn write<S: Serializer>(
+        self,
+        timestamp: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match self {
+            TimestampFormat::EpochMillis => serializer.serialize_i64(timestamp.timestamp_millis()),
+            TimestampFormat::Rfc3339 { offset } if offset.local_minus_utc() == 0 => {
+                timestamp.serialize(serializer)
+            }
+            TimestampFormat::Rfc3339 { offset } => serializer.serialize_str(
+                &timestamp
+                    .with_timezone(&offset)
+                    .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            ),
+        }
+    }
+}
+
+tokio::task_local! {
+    static REQUESTED: TimestampFormat;
+    static ENCODING: TimestampFormat;
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TimestampParams {
+    timestamps: Option<String>,
+    tz: Option<String>,
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Reads the timestamp format asked for, answering 400 when it makes no sense
+pub async fn timestamps_middleware(request: Request, next: Next) -> Response {
+    let params = Query::<TimestampParams>::try_from_uri(request.uri())
+        .map(|Query(params)| params)
+        .unwrap_or_default();
+    // `+` in a query string stands for a space, so `?tz=+05:30` arrives as ` 05:30`
+    let query_tz = params.tz.map(|tz| {
+        tz.strip_prefix(' ')
+            .map_or(tz.clone(), |rest| format!("+{}", rest))
+    });
+
+    let headers = request.headers();
+    let format = params
+        .timestamps
+        .as_deref()
+        .or_else(|| header_value(headers, TIMESTAMP_FORMAT_HEADER));
+    let tz = query_tz
+        .as_deref()
+        .or_else(|| header_value(headers, TIMEZONE_HEADER));
+
+    let format = match TimestampFormat::parse(format, tz) {
+        Ok(format) => format,
+        Err(detail) => {
+            return ErrorResponse::new(StatusCode::BAD_REQUEST, "Invalid Timestamp Format", detail)
+                .into_response();
+        }
+    };
+
+    let mut response = REQUESTED.scope(format, next.run(request)).await;
+    response.headers_mut().append(
+        header::VARY,
+        HeaderValue::from_static("x-timestamp-format, x-timezone"),
+    );
+    response
+}
+
+/// Runs `encode` with the request's timestamp format in effect
+pub fn while_encoding<R>(encode: impl FnOnce() -> R) -> R {
+    match REQUESTED.try_with(|format| *format) {
+        Ok(format) => ENCODING.sync_scope(format, encode),
+        Err(_) => encode(),
+    }
+}
+
+fn current() -> TimestampFormat {
+    ENCODING.try_with(|format| *format).unwrap_or_default()
+}
+
+/// `serialize_with` for `DateTime<Utc>` fields of response bodies
+pub fn serialize<S: Serializer>(
+    timestamp: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    current().write(timestamp, serializer)
+}
+
+/// `serialize_with` for `Option<DateTime<Utc>>` fields of response bodies
+pub fn serialize_option<S: Serializer>(
+    timestamp: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match timestamp {
+        Some(timestamp) => serializer.serialize_some(&Timestamp(timestamp)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// A timestamp written in the request's format, for hand-written `Serialize` impls
+pub struct Timestamp<'a>(pub &'a DateTime<Utc>);
+
+impl Serialize for Timestamp<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(self.0, serializer)
+    }
+}
@@ -0,0 +1,277 @@
+//! Daily usage quotas for signed-in users and API keys.
+//!
+//! Each request counts against the signed-in user, or against the
+//! `X-API-Key` it carries when nobody is signed in, over a rolling 24 hours
+//! kept in hourly buckets. Admins get a larger quota. Responses carry
+//! `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset`, and
+//! once the quota is spent requests get `429` until the oldest hour leaves the
+//! window. Anonymous callers without a key fall under the per-IP limits
+//! instead. Counts are kept per process.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::AccessConfig;
+use crate::helpers::access::{API_KEY_HEADER, is_known_key};
+use crate::helpers::auth::AuthHelper;
+use crate::helpers::middleware::request_token;
+use crate::model::model::{ErrorResponse, Role};
+use crate::state::AppState;
+
+/// Hours a request keeps counting against the quota
+pub const WINDOW_HOURS: i64 = 24;
+
+const SECONDS_PER_HOUR: i64 = 60 * 60;
+
+/// Who a request is counted against
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UsageSubject {
+    User(Uuid),
+    ApiKey(String),
+}
+
+/// Requests of one subject per hour, oldest first
+#[derive(Debug, Default)]
+struct HourlyCounts {
+    hours: VecDeque<(i64, u64)>,
+}
+
+impl HourlyCounts {
+    /// Drops the hours that left the window ending in `hour`
+    fn trim(&mut self, hour: i64) {
+        while self
+            .hours
+            .front()
+            .is_some_and(|&(start, _)| start <= hour - WINDOW_HOURS)
+        {
+            self.hours.pop_front();
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.hours.iter().map(|&(_, count)| count).sum()
+    }
+
+    fn add(&mut self, hour: i64) {
+        match self.hours.back_mut() {
+            Some((last, count)) if *last == hour => *count += 1,
+            _ => self.hours.push_back((hour, 1)),
+        }
+    }
+}
+
+/// Request counts of every subject over the rolling window
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    counts: Mutex<HashMap<UsageSubject, HourlyCounts>>,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a request of `subject` at `now` unless the quota of `limit` is
+    /// already spent; returns whether it was allowed, and the usage after it
+    pub fn hit(&self, subject: &UsageSubject, limit: u64, now: DateTime<Utc>) -> (bool, Usage) {
+        let hour = hour_of(now);
+        let mut counts = self.counts.lock().unwrap();
+        let entry = counts.entry(subject.clone()).or_default();
+        entry.trim(hour);
+
+        let allowed = entry.total() < limit;
+        if allowed {
+            entry.add(hour);
+        }
+        (allowed, Usage::new(entry, limit, now))
+    }
+
+    /// Usage of `subject` at `now` without counting a request
+    pub fn usage(&self, subject: &UsageSubject, limit: u64, now: DateTime<Utc>) -> Usage {
+        let mut counts = self.counts.lock().unwrap();
+        match counts.get_mut(subject) {
+            Some(entry) => {
+                entry.trim(hour_of(now));
+                Usage::new(entry, limit, now)
+            }
+            None => Usage::new(&HourlyCounts::default(), limit, now),
+        }
+    }
+
+    /// Forgets subjects without requests in the window
+    pub fn prune(&self, now: DateTime<Utc>) {
+        let hour = hour_of(now);
+        self.counts.lock().unwrap().retain(|_, entry| {
+            entry.trim(hour);
+            !entry.hours.is_empty()
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn hour_of(time: DateTime<Utc>) -> i64 {
+    time.timestamp().div_euclid(SECONDS_PER_HOUR)
+}
+
+fn start_of(hour: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(hour * SECONDS_PER_HOUR, 0)
+        .single()
+        .unwrap_or_default()
+}
+
+/// A caller's consumption of their daily quota
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Usage {
+    /// Requests allowed in any 24 hours
+    pub limit: u64,
+    /// Requests made in the last 24 hours
+    pub used: u64,
+    pub remaining: u64,
+    /// When the oldest counted hour leaves the window and frees its requests;
+    /// now when nothing is counted
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub resets_at: DateTime<Utc>,
+    /// Requests per hour in the window, oldest first; hours without requests
+    /// are left out
+    pub hours: Vec<UsageHour>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UsageHour {
+    /// Start of the hour
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub hour: DateTime<Utc>,
+    pub requests: u64,
+}
+
+impl Usage {
+    fn new(counts: &HourlyCounts, limit: u64, now: DateTime<Utc>) -> Self {
+        let used = counts.total();
+        let resets_at = counts
+            .hours
+            .front()
+            .map(|&(oldest, _)| start_of(oldest + WINDOW_HOURS))
+            .unwrap_or(now);
+
+        Usage {
+            limit,
+            used,
+            remaining: limit.saturating_sub(used),
+            resets_at,
+            hours: counts
+                .hours
+                .iter()
+                .map(|&(hour, requests)| UsageHour {
+                    hour: start_of(hour),
+                    requests,
+                })
+                .collect(),
+        }
+    }
+
+    fn set_headers(&self, headers: &mut HeaderMap) {
+        headers.insert("x-ratelimit-limit", HeaderValue::from(self.limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(self.remaining));
+        headers.insert(
+            "x-ratelimit-reset",
+            HeaderValue::from(self.resets_at.timestamp().max(0)),
+        );
+    }
+}
+
+/// The daily quota for a caller with `role`; `None` when quotas are off
+pub fn daily_quota(access: &AccessConfig, role: Option<Role>) -> Option<u64> {
+    if access.daily_quota == 0 {
+        return None;
+    }
+    Some(match role {
+        Some(Role::ADMIN) => access.admin_daily_quota.max(access.daily_quota),
+        _ => access.daily_quota,
+    })
+}
+
+/// Who `headers` are counted against and their role; `None` for anonymous
+/// callers without a known API key
+fn subject_of(headers: &HeaderMap, state: &AppState) -> Option<(UsageSubject, Option<Role>)> {
+    let user = request_token(headers)
+        .and_then(|token| AuthHelper::validate_token(&token, state.clock.as_ref()).ok())
+        .and_then(|claims| Some((Uuid::parse_str(&claims.sub).ok()?, claims.role)));
+    if let Some((user_id, role)) = user {
+        return Some((UsageSubject::User(user_id), Some(role)));
+    }
+
+    let key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())?;
+    is_known_key(&state.config.access.api_keys, key)
+        .then(|| (UsageSubject::ApiKey(key.to_string()), None))
+}
+
+/// Counts requests against the caller's daily quota and reports it in the
+/// `X-RateLimit-*` headers, which replace the per-IP ones for these callers
+pub async fn usage_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some((subject, role)) = subject_of(request.headers(), &state) else {
+        return next.run(request).await;
+    };
+    let Some(limit) = daily_quota(&state.config.access, role) else {
+        return next.run(request).await;
+    };
+
+    let now = state.clock.now();
+    let (allowed, usage) = state.usage.hit(&subject, limit, now);
+    if !allowed {
+        let mut response = ErrorResponse::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too Many Requests",
+            format!(
+                "Daily quota of {} requests used up; see GET /auth/usage",
+                limit
+            ),
+        )
+        .into_response();
+        let wait = (usage.resets_at - now).num_seconds().max(1);
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(wait));
+        usage.set_headers(response.headers_mut());
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    usage.set_headers(response.headers_mut());
+    response
+}
+
+/// The usage of `user_id`, as `GET /auth/usage` reports it
+pub fn user_usage(
+    tracker: &UsageTracker,
+    access: &AccessConfig,
+    user_id: Uuid,
+    role: Role,
+    now: DateTime<Utc>,
+) -> Option<Usage> {
+    let limit = daily_quota(access, Some(role))?;
+    Some(tracker.usage(&UsageSubject::User(user_id), limit, now))
+}
@@ -3,7 +3,12 @@ use mailchecker::is_valid;
 use base64::{Engine, engine::general_purpose::STANDARD_NO_PAD};
 use rand::RngCore;
 
-use crate::model::model::{CreateUserRequest, User};
+use crate::config::AppConfig;
+use crate::helpers::licenses::normalize_license;
+use crate::helpers::markdown::extract_images;
+use crate::helpers::password_strength::estimate_password;
+use crate::helpers::slugs::validate_slug;
+use crate::model::model::{CreatePostRequest, CreateUserRequest, Limits, User};
 
 pub fn validate_user(user: &User) -> Result<(), String> {
     if !is_valid(&user.email) {
@@ -21,34 +26,105 @@ pub fn validate_user(user: &User) -> Result<(), String> {
     Ok(())
 }
 
-pub fn validate_user_registration(user: &CreateUserRequest) -> Result<(), String> {
+pub fn validate_user_registration(user: &CreateUserRequest, limits: &Limits) -> Result<(), String> {
     if !is_valid(&user.email) {
         return Err("Invalid email address".to_string());
     }
 
-    if !strong_password(&user.password) {
-        return Err("Password is not strong enough".to_string());
-    }
+    let mut user_inputs = vec![user.name.as_str(), user.email.as_str()];
+    user_inputs.extend(user.username.as_deref());
+    check_password(&user.password, &user_inputs)?;
 
     if user.name.trim().is_empty() {
         return Err("Name cannot be empty".to_string());
     }
 
-    if user.name.trim().len() > 100 {
-        return Err("Name is too long".to_string());
+    check_length("Name", user.name.trim(), limits.max_name_length)
+}
+
+/// Fails when `value` is longer than `max` characters; `field` names it in the message
+pub fn check_length(field: &str, value: &str, max: usize) -> Result<(), String> {
+    if value.chars().count() > max {
+        return Err(format!("{} must be at most {} characters", field, max));
     }
+    Ok(())
+}
 
+/// Checks whichever of a post's title and content are given against `limits`
+pub fn validate_post_fields(
+    title: Option<&str>,
+    content: Option<&str>,
+    limits: &Limits,
+) -> Result<(), String> {
+    if let Some(title) = title {
+        check_length("Title", title, limits.max_title_length)?;
+    }
+    if let Some(content) = content {
+        check_length("Content", content, limits.max_content_length)?;
+    }
     Ok(())
 }
 
+/// Refuses content with images lacking alt text when the deployment requires it
+pub fn check_alt_text(config: &AppConfig, content: &str) -> Result<(), String> {
+    if !config.require_alt_text {
+        return Ok(());
+    }
+
+    let missing: Vec<String> = extract_images(content)
+        .into_iter()
+        .filter(|image| image.alt.is_none())
+        .map(|image| image.url)
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Every image needs alt text; missing for: {}",
+            missing.join(", ")
+        ))
+    }
+}
+
+/// `post` checked like a post created through the API, with its license
+/// normalized or set to the deployment's default
+pub fn check_new_post(
+    mut post: CreatePostRequest,
+    config: &AppConfig,
+) -> Result<CreatePostRequest, String> {
+    if post.title.trim().is_empty() || post.content.trim().is_empty() {
+        return Err("Title and content are required".to_string());
+    }
+    validate_post_fields(Some(&post.title), Some(&post.content), &config.limits)?;
+    check_alt_text(config, &post.content)?;
+
+    post.license = match post.license.as_deref().map(normalize_license) {
+        Some(license) => Some(license?),
+        None => config.default_license.clone(),
+    };
+    if let Some(slug) = &post.slug {
+        validate_slug(slug, &config.slugs)?;
+    }
+
+    Ok(post)
+}
+
+/// Whether `password` is hard enough to guess; see [`estimate_password`]
 pub fn strong_password(password: &str) -> bool {
-    let has_min_length = password.len() >= 8;
-    let has_uppercase = password.chars().any(|c| c.is_uppercase());
-    let has_lowercase = password.chars().any(|c| c.is_lowercase());
-    let has_digit = password.chars().any(|c| c.is_digit(10));
-    let has_special_char = password.chars().any(|c| !c.is_alphanumeric());
+    estimate_password(password, &[]).acceptable
+}
 
-    has_min_length && has_uppercase && has_lowercase && has_digit && has_special_char
+/// Like [`strong_password`], also weighing the account's own details, with
+/// the reason in the error
+pub fn check_password(password: &str, user_inputs: &[&str]) -> Result<(), String> {
+    let strength = estimate_password(password, user_inputs);
+    if strength.acceptable {
+        return Ok(());
+    }
+    Err(match strength.warning {
+        Some(warning) => format!("Password is not strong enough: {}", warning),
+        None => "Password is not strong enough".to_string(),
+    })
 }
 
 pub fn generate_base64_string() -> String {
@@ -0,0 +1,88 @@
+//! Verification emails: sent at registration and on email changes, and again
+//! in batches when an admin resends them to accounts still unverified. Each
+//! link carries a single-use token from [`crate::helpers::link_tokens`].
+
+use std::env;
+use std::sync::Arc;
+
+use tracing::{error, info};
+
+use crate::config::AppConfig;
+use crate::helpers::clock::Clock;
+use crate::helpers::email_templates::{EmailKind, EmailTemplates};
+use crate::helpers::feature_flags::FeatureFlags;
+use crate::helpers::link_tokens::{LinkPurpose, LinkTokenStore};
+use crate::helpers::mailer::Mailer;
+use crate::helpers::redirects::with_query;
+use crate::model::model::{FeatureFlag, User};
+
+/// Everything needed to send verification emails, in a request or from the
+/// resend job
+#[derive(Clone)]
+pub struct VerificationMailer {
+    pub flags: Arc<FeatureFlags>,
+    pub mailer: Arc<dyn Mailer>,
+    pub tokens: Arc<dyn LinkTokenStore>,
+    pub emails: Arc<EmailTemplates>,
+    pub config: Arc<AppConfig>,
+    pub clock: Arc<dyn Clock>,
+}
+
+impl VerificationMailer {
+    /// Emails `user` a link verifying their address; failures are logged and
+    /// never fail the request, and nothing is sent while email sending is
+    /// switched off
+    pub async fn send(&self, user: &User, locale: Option<&str>, redirect_uri: Option<&str>) {
+        if !self.flags.is_enabled(FeatureFlag::EmailSending) {
+            info!(
+                "Email sending is disabled; not sending a verification email to user {}",
+                user.id
+            );
+            return;
+        }
+
+        let now = self.clock.now();
+        let ttl = chrono::Duration::from_std(self.config.tokens.email_verification_ttl)
+            .unwrap_or_else(|_| chrono::Duration::minutes(15));
+        let verification_token = match self
+            .tokens
+            .issue(user.id, LinkPurpose::VerifyEmail, None, now, now + ttl)
+            .await
+        {
+            Ok(token) => token,
+            Err(e) => {
+                error!("Failed to issue verification token: {:?}", e);
+                return;
+            }
+        };
+        let base_url = env::var("BASE_URL").unwrap_or_else(|_| "localhost:3000".to_string());
+        let mut params = vec![("token", verification_token.as_str())];
+        if let Some(redirect_uri) = redirect_uri {
+            params.push(("redirect_uri", redirect_uri));
+        }
+        let verification_link = with_query(
+            &format!(
+                "http://{}{}",
+                base_url,
+                self.config.api_path("/auth/verify-email")
+            ),
+            &params,
+        );
+
+        let email = match self.emails.render(
+            EmailKind::VerifyEmail,
+            locale,
+            &[("name", &user.name), ("link", &verification_link)],
+        ) {
+            Ok(email) => email,
+            Err(e) => {
+                error!("Failed to render verification email: {:?}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = self.mailer.send(&user.email, &email).await {
+            error!("Failed to send verification email: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! API versions and how requests are tied to one.
+//!
+//! Each version is mounted at `{API_PREFIX}/v{n}`, and every version is built
+//! from the same handlers. When a later version changes behaviour, the
+//! handler takes the [`ApiVersion`] extractor and branches on it; a route that
+//! only exists from some version on is added when that version's router is
+//! built. The unversioned paths from before versioning keep answering as the
+//! oldest version, marked deprecated with a link to their versioned successor.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{HeaderName, HeaderValue, header, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::config::AppConfig;
+
+pub const DEFAULT_API_PREFIX: &str = "/api";
+
+/// Response header naming the version that answered
+pub const API_VERSION_HEADER: HeaderName = HeaderName::from_static("api-version");
+
+const DEPRECATION_HEADER: HeaderName = HeaderName::from_static("deprecation");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    /// Every served version, oldest first
+    pub const ALL: &'static [ApiVersion] = &[ApiVersion::V1];
+    pub const LATEST: ApiVersion = ApiVersion::V1;
+    /// What the unversioned paths answer as
+    pub const UNVERSIONED: ApiVersion = ApiVersion::V1;
+
+    pub fn number(self) -> u32 {
+        match self {
+            ApiVersion::V1 => 1,
+        }
+    }
+
+    /// Path segment of the version, e.g. `/v1`
+    pub fn segment(self) -> String {
+        format!("/v{}", self.number())
+    }
+}
+
+/// The version the request was routed to; unversioned paths count as
+/// [`ApiVersion::UNVERSIONED`]
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<ApiVersion>()
+            .copied()
+            .unwrap_or(ApiVersion::UNVERSIONED))
+    }
+}
+
+/// Tags requests under a version's prefix with that version
+pub async fn versioned_middleware(
+    State(version): State<ApiVersion>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    request.extensions_mut().insert(version);
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert(API_VERSION_HEADER, HeaderValue::from(version.number()));
+    response
+}
+
+/// Serves an unversioned path as [`ApiVersion::UNVERSIONED`], with a
+/// `Deprecation` header and a `Link` to the same route under its version
+pub async fn unversioned_middleware(
+    State(config): State<Arc<AppConfig>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let version = ApiVersion::UNVERSIONED;
+    let successor = config.versioned_path(version, request.uri().path());
+    request.extensions_mut().insert(version);
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(API_VERSION_HEADER, HeaderValue::from(version.number()));
+    headers.insert(DEPRECATION_HEADER, HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor))
+    {
+        headers.append(header::LINK, link);
+    }
+    response
+}
@@ -0,0 +1,26 @@
+use std::net::IpAddr;
+
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub const DEFAULT_TRENDING_LIMIT: i64 = 10;
+pub const MAX_TRENDING_LIMIT: i64 = 50;
+
+/// Identifies a viewer for view dedup: the user when signed in, otherwise a
+/// hash of the client IP so raw addresses are never stored
+pub fn viewer_key(user_id: Option<Uuid>, ip: Option<IpAddr>) -> Option<String> {
+    match (user_id, ip) {
+        (Some(user_id), _) => Some(format!("user:{}", user_id)),
+        (None, Some(ip)) => Some(format!(
+            "ip:{:x}",
+            Sha256::digest(ip.to_string().as_bytes())
+        )),
+        (None, None) => None,
+    }
+}
+
+pub fn trending_limit(requested: Option<i64>) -> i64 {
+    requested
+        .unwrap_or(DEFAULT_TRENDING_LIMIT)
+        .clamp(1, MAX_TRENDING_LIMIT)
+}
@@ -0,0 +1,294 @@
+//! Import of WordPress WXR exports.
+//!
+//! Authors are matched to existing users by email or created unverified;
+//! published posts are imported with their original dates, and
+//! password-protected ones as private posts. Comments have no counterpart in
+//! this schema yet, so they are counted in the report instead of being
+//! imported. Media files are not downloaded either: there is no file storage
+//! to put them in, so their URLs are listed in the report and posts keep
+//! linking to the old host.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use quick_xml::{Reader, events::Event};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::{collections::HashMap, sync::Arc};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::cache::Cache;
+use crate::config::AppConfig;
+use crate::db::repositories::{
+    media_repo::MediaRepository, post_repo::PostRepository, user_repo::UserRepository,
+};
+use crate::helpers::auth::AuthHelper;
+use crate::helpers::markdown::extract_images;
+use crate::helpers::slugs::validate_slug;
+use crate::helpers::validation::generate_base64_string;
+use crate::model::model::{CreatePostRequest, CreateUserRequest, Visibility};
+
+/// Largest export accepted by the import endpoint
+pub const MAX_WXR_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WxrAuthor {
+    pub login: String,
+    pub email: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WxrItem {
+    pub title: String,
+    /// Author login from `dc:creator`
+    pub creator: String,
+    pub content: String,
+    /// The WordPress slug, kept when it is valid here and still free
+    pub post_name: String,
+    pub post_type: String,
+    pub status: String,
+    /// Set on password-protected posts; empty otherwise
+    pub post_password: String,
+    pub post_date_gmt: Option<DateTime<Utc>>,
+    pub attachment_url: Option<String>,
+    pub comments: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WxrExport {
+    pub authors: Vec<WxrAuthor>,
+    pub items: Vec<WxrItem>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportIssue {
+    /// Title of the item, or author login
+    pub item: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct ImportReport {
+    pub authors_created: usize,
+    pub authors_matched: usize,
+    pub posts_imported: usize,
+    /// Password-protected posts, imported as private since posts here have
+    /// no passwords; share links can open them to their readers again
+    pub posts_made_private: Vec<String>,
+    /// Comments are not supported and were left out
+    pub comments_skipped: usize,
+    /// Media files referenced by the export; they are not downloaded, so
+    /// copy them to their new host and update links as needed
+    pub media: Vec<String>,
+    pub skipped: Vec<ImportIssue>,
+}
+
+/// Parses the parts of a WXR document this schema can hold
+pub fn parse_wxr(xml: &str) -> Result<WxrExport> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut export = WxrExport::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut author = WxrAuthor::default();
+    let mut item = WxrItem::default();
+    let mut saw_channel = false;
+
+    loop {
+        match reader
+            .read_event()
+            .with_context(|| format!("invalid WXR at byte {}", reader.buffer_position()))?
+        {
+            Event::Start(start) => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                match name.as_str() {
+                    "channel" => saw_channel = true,
+                    "wp:author" => author = WxrAuthor::default(),
+                    "item" => item = WxrItem::default(),
+                    _ => {}
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Text(value) => text.push_str(&value.unescape()?),
+            Event::CData(value) => text.push_str(&String::from_utf8_lossy(&value.into_inner())),
+            Event::End(_) => {
+                let Some(name) = path.pop() else {
+                    bail!("unbalanced closing tag in WXR");
+                };
+                let parent = path.last().map(String::as_str).unwrap_or_default();
+                let value = std::mem::take(&mut text);
+
+                match (parent, name.as_str()) {
+                    ("wp:author", "wp:author_login") => author.login = value,
+                    ("wp:author", "wp:author_email") => author.email = value,
+                    ("wp:author", "wp:author_display_name") => author.display_name = value,
+                    ("channel", "wp:author") => export.authors.push(std::mem::take(&mut author)),
+                    ("item", "title") => item.title = value,
+                    ("item", "dc:creator") => item.creator = value,
+                    ("item", "content:encoded") => item.content = value,
+                    ("item", "wp:post_name") => item.post_name = value,
+                    ("item", "wp:post_type") => item.post_type = value,
+                    ("item", "wp:status") => item.status = value,
+                    ("item", "wp:post_password") => item.post_password = value,
+                    ("item", "wp:post_date_gmt") => item.post_date_gmt = parse_wxr_date(&value),
+                    ("item", "wp:attachment_url") => item.attachment_url = Some(value),
+                    ("item", "wp:comment") => item.comments += 1,
+                    ("channel", "item") => export.items.push(std::mem::take(&mut item)),
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !saw_channel {
+        bail!("not a WordPress export: missing <channel>");
+    }
+
+    Ok(export)
+}
+
+/// WXR dates are `YYYY-MM-DD HH:MM:SS`; drafts carry an all-zero date
+fn parse_wxr_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|date| date.and_utc())
+}
+
+/// Writes a parsed export into the database and reports what was left out
+pub async fn import_export(
+    pool: &PgPool,
+    cache: Arc<Cache>,
+    config: &AppConfig,
+    export: WxrExport,
+    now: DateTime<Utc>,
+) -> Result<ImportReport> {
+    let users = UserRepository::new(pool.clone());
+    let posts = PostRepository::new(pool.clone()).with_cache(cache);
+    let media = MediaRepository::new(pool.clone());
+    let mut report = ImportReport::default();
+    let mut authors: HashMap<String, Uuid> = HashMap::new();
+
+    for author in export.authors {
+        if let Some(user) = users.find_by_email(&author.email).await? {
+            authors.insert(author.login, user.id);
+            report.authors_matched += 1;
+            continue;
+        }
+
+        let name = if author.display_name.trim().is_empty() {
+            author.login.clone()
+        } else {
+            author.display_name.clone()
+        };
+        // Imported authors cannot sign in until an admin sets their password
+        let password = format!("Wp1!{}", generate_base64_string());
+        let hashed = AuthHelper::hash_password(&password)?;
+        let username = users.free_username(&author.login, &config.slugs).await?;
+        let request = CreateUserRequest {
+            name,
+            username: Some(username),
+            email: author.email.clone(),
+            password,
+            locale: None,
+            redirect_uri: None,
+            website: None,
+            form_token: None,
+            captcha_token: None,
+        };
+
+        match users.create_user(request, hashed, now).await {
+            Ok(user) => {
+                authors.insert(author.login, user.id);
+                report.authors_created += 1;
+            }
+            Err(err) => {
+                warn!("Skipping WordPress author {}: {}", author.login, err);
+                report.skipped.push(ImportIssue {
+                    item: author.login,
+                    reason: format!("author could not be created: {}", err),
+                });
+            }
+        }
+    }
+
+    for item in export.items {
+        report.comments_skipped += item.comments;
+
+        if item.post_type == "attachment" {
+            if let Some(url) = item.attachment_url {
+                report.media.push(url);
+            }
+            continue;
+        }
+
+        let reason = if item.post_type != "post" {
+            Some(format!("{} items are not supported", item.post_type))
+        } else if item.status != "publish" {
+            Some(format!(
+                "only published posts are imported ({})",
+                item.status
+            ))
+        } else if !authors.contains_key(&item.creator) {
+            Some(format!("unknown author {}", item.creator))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            report.skipped.push(ImportIssue {
+                item: item.title,
+                reason,
+            });
+            continue;
+        }
+
+        let title = if item.title.trim().is_empty() {
+            "Untitled".to_string()
+        } else {
+            item.title
+        };
+        let slug = if validate_slug(&item.post_name, &config.slugs).is_ok()
+            && !posts.slug_taken(&item.post_name).await?
+        {
+            item.post_name
+        } else {
+            posts.free_slug(&title, &config.slugs).await?
+        };
+        let protected = !item.post_password.is_empty();
+        let post = posts
+            .import_post(
+                CreatePostRequest {
+                    title,
+                    content: item.content,
+                    slug: None,
+                    license: config.default_license.clone(),
+                    visibility: protected.then_some(Visibility::PRIVATE),
+                },
+                authors[&item.creator],
+                item.post_date_gmt.unwrap_or(now),
+                slug,
+            )
+            .await?;
+        if protected {
+            report.posts_made_private.push(post.title.clone());
+        }
+        media
+            .replace_for_post(post.id, &extract_images(&post.content))
+            .await?;
+        report.posts_imported += 1;
+    }
+
+    info!(
+        "WordPress import finished: {} posts, {} authors created, {} skipped",
+        report.posts_imported,
+        report.authors_created,
+        report.skipped.len()
+    );
+
+    Ok(report)
+}
@@ -0,0 +1,337 @@
+use std::{sync::Arc, time::Duration};
+
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::config::{AccountConfig, AppConfig, PublishHooksConfig, UnverifiedCleanup};
+use crate::db::health::{DbHealth, probe};
+use crate::db::pools::DbPools;
+use crate::db::repositories::audit_repo::AuditRepository;
+use crate::db::repositories::feature_flag_repo::FeatureFlagRepository;
+use crate::db::repositories::link_token_repo::LinkTokenRepository;
+use crate::db::repositories::user_repo::UserRepository;
+use crate::helpers::clock::Clock;
+use crate::helpers::feature_flags::FeatureFlags;
+use crate::helpers::publish_hooks::deliver_due;
+use crate::helpers::verification::VerificationMailer;
+use crate::jobs::runs::JobRuns;
+use crate::model::model::{DbStatus, JobInfo, PendingVerification};
+
+pub const ACCOUNT_PURGE: &str = "account_purge";
+pub const UNVERIFIED_CLEANUP: &str = "unverified_cleanup";
+pub const HOOK_DELIVERY: &str = "hook_delivery";
+pub const DB_HEALTH_MONITOR: &str = "db_health_monitor";
+pub const REPLICA_HEALTH_MONITOR: &str = "replica_health_monitor";
+pub const FEATURE_FLAG_REFRESH: &str = "feature_flag_refresh";
+
+/// The jobs below as configured, for the admin diagnostics and job status
+pub fn scheduled_jobs(config: &AppConfig) -> Vec<JobInfo> {
+    vec![
+        JobInfo {
+            name: ACCOUNT_PURGE.to_string(),
+            description: "Deletes accounts past their grace period and lifts expired suspensions"
+                .to_string(),
+            enabled: true,
+            interval_secs: config.accounts.purge_interval.as_secs(),
+        },
+        JobInfo {
+            name: UNVERIFIED_CLEANUP.to_string(),
+            description: "Deletes or anonymizes accounts left unverified for too long".to_string(),
+            enabled: config.accounts.unverified_cleanup.is_some(),
+            interval_secs: config.accounts.unverified_cleanup_interval.as_secs(),
+        },
+        JobInfo {
+            name: HOOK_DELIVERY.to_string(),
+            description: "Sends queued publish hook notifications".to_string(),
+            enabled: !config.publish_hooks.hooks.is_empty(),
+            interval_secs: config.publish_hooks.poll_interval.as_secs(),
+        },
+        JobInfo {
+            name: DB_HEALTH_MONITOR.to_string(),
+            description: "Probes the database and updates readiness".to_string(),
+            enabled: true,
+            interval_secs: config.database.health_interval.as_secs(),
+        },
+        JobInfo {
+            name: REPLICA_HEALTH_MONITOR.to_string(),
+            description: "Probes the read replica and sends reads to the primary while it is down"
+                .to_string(),
+            enabled: config.database.read_url.is_some(),
+            interval_secs: config.database.health_interval.as_secs(),
+        },
+        JobInfo {
+            name: FEATURE_FLAG_REFRESH.to_string(),
+            description: "Reloads feature flags changed on other replicas".to_string(),
+            enabled: true,
+            interval_secs: config.feature_flag_refresh.as_secs(),
+        },
+    ]
+}
+
+/// Periodically deletes accounts whose deletion grace period has passed,
+/// reactivates accounts whose suspension has run out and drops expired link
+/// tokens
+pub fn spawn_account_purge(
+    pool: Arc<PgPool>,
+    clock: Arc<dyn Clock>,
+    runs: Arc<JobRuns>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            info!("Running scheduled account purge...");
+            let started_at = clock.now();
+
+            let repo = UserRepository::new((*pool).clone());
+            let mut outcome = Ok(None);
+            match repo.purge_scheduled_deletions(clock.now()).await {
+                Ok(0) => {}
+                Ok(purged) => outcome = Ok(Some(format!("Purged {} accounts", purged))),
+                Err(e) => {
+                    error!("Scheduled account purge failed: {}", e);
+                    outcome = Err(e.to_string());
+                }
+            }
+            if let Err(e) = repo.lift_expired_suspensions(clock.now()).await {
+                error!("Lifting expired suspensions failed: {}", e);
+                outcome = Err(e.to_string());
+            }
+            let link_tokens = LinkTokenRepository::new((*pool).clone());
+            if let Err(e) = link_tokens.delete_expired(clock.now()).await {
+                error!("Deleting expired link tokens failed: {}", e);
+                outcome = Err(e.to_string());
+            }
+
+            runs.record(ACCOUNT_PURGE, started_at, clock.now(), outcome);
+        }
+    })
+}
+
+/// Periodically deletes or anonymizes accounts that were never verified
+/// within `unverified_max_age`, recording each purge in the audit log
+pub fn spawn_unverified_cleanup(
+    pool: Arc<PgPool>,
+    clock: Arc<dyn Clock>,
+    runs: Arc<JobRuns>,
+    config: AccountConfig,
+    cleanup: UnverifiedCleanup,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let users = UserRepository::new((*pool).clone());
+        let audit = AuditRepository::new((*pool).clone());
+        let max_age = chrono::Duration::seconds(config.unverified_max_age.as_secs() as i64);
+        let mut ticker = tokio::time::interval(config.unverified_cleanup_interval);
+        loop {
+            ticker.tick().await;
+            let started_at = clock.now();
+            let cutoff = started_at - max_age;
+
+            let (action, result) = match cleanup {
+                UnverifiedCleanup::Delete => (
+                    "users.unverified_deleted",
+                    users.delete_unverified_before(cutoff).await,
+                ),
+                UnverifiedCleanup::Anonymize => (
+                    "users.unverified_anonymized",
+                    users.anonymize_unverified_before(cutoff, started_at).await,
+                ),
+            };
+
+            let outcome = match result {
+                Ok(0) => Ok(None),
+                Ok(count) => {
+                    let detail =
+                        format!("{} accounts created before {}", count, cutoff.to_rfc3339());
+                    if let Err(e) = audit
+                        .record_system(action, "users", Some(detail.as_str()), clock.now())
+                        .await
+                    {
+                        error!("Failed to audit the unverified account cleanup: {}", e);
+                    }
+                    Ok(Some(detail))
+                }
+                Err(e) => {
+                    error!("Unverified account cleanup failed: {}", e);
+                    Err(e.to_string())
+                }
+            };
+            runs.record(UNVERIFIED_CLEANUP, started_at, clock.now(), outcome);
+        }
+    })
+}
+
+/// Sends queued publish hook notifications and retries failed ones
+pub fn spawn_hook_delivery(
+    pool: Arc<PgPool>,
+    clock: Arc<dyn Clock>,
+    runs: Arc<JobRuns>,
+    config: PublishHooksConfig,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let mut ticker = tokio::time::interval(config.poll_interval);
+        loop {
+            ticker.tick().await;
+            let started_at = clock.now();
+
+            let outcome = match deliver_due(&pool, &client, &config, clock.now()).await {
+                Ok(0) => Ok(None),
+                Ok(sent) => {
+                    info!("Processed {} publish hook deliveries", sent);
+                    Ok(Some(format!("Processed {} deliveries", sent)))
+                }
+                Err(e) => {
+                    error!("Publish hook delivery failed: {}", e);
+                    Err(e.to_string())
+                }
+            };
+            runs.record(HOOK_DELIVERY, started_at, clock.now(), outcome);
+        }
+    })
+}
+
+/// Probes the database and keeps readiness in step with it, logging when it
+/// goes down, saturates or recovers
+pub fn spawn_db_health_monitor(
+    pool: Arc<PgPool>,
+    health: Arc<DbHealth>,
+    clock: Arc<dyn Clock>,
+    runs: Arc<JobRuns>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let started_at = clock.now();
+
+            let status = probe(&pool).await;
+            let now = clock.now();
+            // The probe answering at all is a successful run
+            runs.record(
+                DB_HEALTH_MONITOR,
+                started_at,
+                now,
+                Ok(Some(format!("Database {:?}", status))),
+            );
+            let Some((previous, since)) = health.record(status, now) else {
+                continue;
+            };
+            match status {
+                DbStatus::DOWN => error!("Database unreachable, marking instance not ready"),
+                DbStatus::SATURATED => warn!(
+                    "Connection pool exhausted ({} connections in use), marking instance not ready",
+                    pool.size()
+                ),
+                DbStatus::UP => info!(
+                    "Database recovered after {}s {:?}, marking instance ready",
+                    (now - since).num_seconds(),
+                    previous
+                ),
+            }
+        }
+    })
+}
+
+/// Probes the read replica, sending reads back to the primary while it is
+/// unreachable or saturated and to the replica again once it recovers
+pub fn spawn_replica_monitor(
+    pools: DbPools,
+    clock: Arc<dyn Clock>,
+    runs: Arc<JobRuns>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let Some(replica) = pools.replica.clone() else {
+            return;
+        };
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let started_at = clock.now();
+
+            let status = probe(&replica).await;
+            let up = status == DbStatus::UP;
+            if pools.set_replica_up(up) {
+                if up {
+                    info!("Read replica is up, routing reads to it");
+                } else {
+                    warn!("Read replica is {:?}, routing reads to the primary", status);
+                }
+            }
+            runs.record(
+                REPLICA_HEALTH_MONITOR,
+                started_at,
+                clock.now(),
+                Ok(Some(format!("Replica {:?}", status))),
+            );
+        }
+    })
+}
+
+/// Reloads the feature flags so a change made through another replica takes
+/// effect here too; on failure the last loaded flags stay in force
+pub fn spawn_feature_flag_refresh(
+    pool: Arc<PgPool>,
+    flags: Arc<FeatureFlags>,
+    clock: Arc<dyn Clock>,
+    runs: Arc<JobRuns>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let repo = FeatureFlagRepository::new((*pool).clone());
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let started_at = clock.now();
+
+            let outcome = match repo.load().await {
+                Ok(loaded) => {
+                    if flags.replace(loaded) {
+                        info!("Feature flags changed: {:?}", loaded);
+                        Ok(Some("Flags changed".to_string()))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to reload feature flags: {}", e);
+                    Err(e.to_string())
+                }
+            };
+            runs.record(FEATURE_FLAG_REFRESH, started_at, clock.now(), outcome);
+        }
+    })
+}
+
+/// Sends verification emails to `pending` in batches of `batch_size`,
+/// pausing `batch_interval` between them so a large backlog stays within the
+/// email provider's rate limits. Runs once, in the background.
+pub fn spawn_verification_resend(
+    sender: VerificationMailer,
+    pending: Vec<PendingVerification>,
+    batch_size: usize,
+    batch_interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let batches: Vec<_> = pending.chunks(batch_size.max(1)).collect();
+        for (index, batch) in batches.iter().enumerate() {
+            if index > 0 {
+                tokio::time::sleep(batch_interval).await;
+            }
+            for account in batch.iter() {
+                sender
+                    .send(&account.user, account.locale.as_deref(), None)
+                    .await;
+            }
+            info!(
+                "Resent verification emails: batch {} of {} done",
+                index + 1,
+                batches.len()
+            );
+        }
+    })
+}
@@ -0,0 +1,4 @@
+pub mod jobs;
+pub mod runs;
+
+pub use jobs::*;
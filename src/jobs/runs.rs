@@ -0,0 +1,44 @@
+//! The latest run of each recurring job, kept in memory for `GET /admin/jobs`.
+//!
+//! Every replica runs its own jobs, so this only covers the instance that
+//! answers the request, and starts empty after a restart.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+
+use crate::model::model::JobRun;
+
+#[derive(Debug, Default)]
+pub struct JobRuns {
+    runs: RwLock<HashMap<String, JobRun>>,
+}
+
+impl JobRuns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores how a run of `job` went, replacing the previous one; `Ok` holds
+    /// a summary of what it did, if anything worth mentioning
+    pub fn record(
+        &self,
+        job: &str,
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+        outcome: Result<Option<String>, String>,
+    ) {
+        let run = JobRun {
+            started_at,
+            finished_at,
+            succeeded: outcome.is_ok(),
+            message: outcome.unwrap_or_else(Some),
+        };
+        self.runs.write().unwrap().insert(job.to_string(), run);
+    }
+
+    pub fn last(&self, job: &str) -> Option<JobRun> {
+        self.runs.read().unwrap().get(job).cloned()
+    }
+}
@@ -1,6 +1,18 @@
+pub mod app;
+pub mod cache;
+pub mod cli;
+pub mod config;
 pub mod db;
+pub mod docs;
 pub mod handlers;
 pub mod helpers;
+pub mod jobs;
 pub mod model;
+pub mod server;
+pub mod state;
 
+pub use app::build_router;
+pub use config::AppConfig;
 pub use model::model::{CreatePostRequest, CreateUserRequest, LoginRequest};
+pub use server::{build_state, run, spawn_jobs};
+pub use state::AppState;
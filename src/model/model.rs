@@ -1,8 +1,17 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::cache::CacheStats;
+use crate::db::instrument::QueryStats;
+use crate::helpers::merge_patch::Patch;
+use crate::helpers::timestamps::Timestamp;
+use crate::helpers::{avatar, redaction};
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub enum Role {
     USER,
@@ -33,41 +42,592 @@ impl From<&str> for Role {
     }
 }
 
+/// A user's role inside one organization
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum OrgRole {
+    OWNER,
+    EDITOR,
+    VIEWER,
+}
+
+impl OrgRole {
+    pub fn can_write(&self) -> bool {
+        matches!(self, OrgRole::OWNER | OrgRole::EDITOR)
+    }
+
+    pub fn is_owner(&self) -> bool {
+        *self == OrgRole::OWNER
+    }
+}
+
+impl From<OrgRole> for String {
+    fn from(role: OrgRole) -> Self {
+        match role {
+            OrgRole::OWNER => "OWNER".to_string(),
+            OrgRole::EDITOR => "EDITOR".to_string(),
+            OrgRole::VIEWER => "VIEWER".to_string(),
+        }
+    }
+}
+
+impl From<&str> for OrgRole {
+    fn from(s: &str) -> Self {
+        match s {
+            "OWNER" => OrgRole::OWNER,
+            "EDITOR" => OrgRole::EDITOR,
+            _ => OrgRole::VIEWER,
+        }
+    }
+}
+
+/// Who can find and read a post
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema, sqlx::Type,
+)]
+#[sqlx(type_name = "varchar")]
+pub enum Visibility {
+    /// Listed everywhere
+    #[default]
+    PUBLIC,
+    /// Readable by anyone with the link, but left out of listings, feeds and the sitemap
+    UNLISTED,
+    /// Readable only by the author and the post's collaborators
+    PRIVATE,
+}
+
+impl Visibility {
+    /// Whether the post shows up in listings, feeds, the sitemap and publish hooks
+    pub fn is_listed(&self) -> bool {
+        *self == Visibility::PUBLIC
+    }
+}
+
+impl From<Visibility> for String {
+    fn from(visibility: Visibility) -> Self {
+        match visibility {
+            Visibility::PUBLIC => "PUBLIC".to_string(),
+            Visibility::UNLISTED => "UNLISTED".to_string(),
+            Visibility::PRIVATE => "PRIVATE".to_string(),
+        }
+    }
+}
+
+impl From<&str> for Visibility {
+    fn from(s: &str) -> Self {
+        match s {
+            "UNLISTED" => Visibility::UNLISTED,
+            "PRIVATE" => Visibility::PRIVATE,
+            _ => Visibility::PUBLIC,
+        }
+    }
+}
+
+/// What a co-author invited to a post may do with it
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum CollaboratorRole {
+    /// Changes the post, but can't delete or archive it
+    EDITOR,
+    VIEWER,
+}
+
+impl CollaboratorRole {
+    pub fn can_edit(&self) -> bool {
+        *self == CollaboratorRole::EDITOR
+    }
+}
+
+impl From<CollaboratorRole> for String {
+    fn from(role: CollaboratorRole) -> Self {
+        match role {
+            CollaboratorRole::EDITOR => "EDITOR".to_string(),
+            CollaboratorRole::VIEWER => "VIEWER".to_string(),
+        }
+    }
+}
+
+impl From<&str> for CollaboratorRole {
+    fn from(s: &str) -> Self {
+        match s {
+            "EDITOR" => CollaboratorRole::EDITOR,
+            _ => CollaboratorRole::VIEWER,
+        }
+    }
+}
+
+/// Whether an email domain rule admits or rejects addresses
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum DomainRuleKind {
+    ALLOW,
+    DENY,
+}
+
+impl From<DomainRuleKind> for String {
+    fn from(kind: DomainRuleKind) -> Self {
+        match kind {
+            DomainRuleKind::ALLOW => "ALLOW".to_string(),
+            DomainRuleKind::DENY => "DENY".to_string(),
+        }
+    }
+}
+
+impl From<&str> for DomainRuleKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "ALLOW" => DomainRuleKind::ALLOW,
+            _ => DomainRuleKind::DENY,
+        }
+    }
+}
+
+/// Progress of a publish hook delivery
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum DeliveryStatus {
+    PENDING,
+    DELIVERED,
+    FAILED,
+}
+
+impl From<DeliveryStatus> for String {
+    fn from(status: DeliveryStatus) -> Self {
+        match status {
+            DeliveryStatus::PENDING => "PENDING".to_string(),
+            DeliveryStatus::DELIVERED => "DELIVERED".to_string(),
+            DeliveryStatus::FAILED => "FAILED".to_string(),
+        }
+    }
+}
+
+impl From<&str> for DeliveryStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "DELIVERED" => DeliveryStatus::DELIVERED,
+            "FAILED" => DeliveryStatus::FAILED,
+            _ => DeliveryStatus::PENDING,
+        }
+    }
+}
+
+/// Database state as last seen by the health monitor
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum DbStatus {
+    UP,
+    /// Reachable, but no pooled connection freed up within the acquire timeout
+    SATURATED,
+    DOWN,
+}
+
+/// Whether this instance should receive traffic
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Readiness {
+    pub ready: bool,
+    pub database: DbStatus,
+    /// When the database entered its current state
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub since: DateTime<Utc>,
+}
+
+/// Whether a dependency answered a diagnostics check
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum DependencyStatus {
+    UP,
+    DOWN,
+    /// Not configured, so not checked
+    DISABLED,
+}
+
+/// Whether an account may sign in
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum UserStatus {
+    ACTIVE,
+    /// Barred until `suspended_until`, or until lifted when it is unset
+    SUSPENDED,
+    /// Barred until an admin lifts the ban
+    BANNED,
+}
+
+impl From<UserStatus> for String {
+    fn from(status: UserStatus) -> Self {
+        match status {
+            UserStatus::ACTIVE => "ACTIVE".to_string(),
+            UserStatus::SUSPENDED => "SUSPENDED".to_string(),
+            UserStatus::BANNED => "BANNED".to_string(),
+        }
+    }
+}
+
+impl From<&str> for UserStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "SUSPENDED" => UserStatus::SUSPENDED,
+            "BANNED" => UserStatus::BANNED,
+            _ => UserStatus::ACTIVE,
+        }
+    }
+}
+
+/// What Resend last reported about email sent to an account's address
+#[allow(non_camel_case_types)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum EmailDelivery {
+    DELIVERED,
+    /// Still being retried by the provider
+    DELAYED,
+    /// Temporarily refused, e.g. a full mailbox; sending goes on
+    SOFT_BOUNCED,
+    /// Permanently refused; nothing more is sent to the address
+    BOUNCED,
+    /// Marked as spam by the recipient; nothing more is sent to the address
+    COMPLAINED,
+}
+
+impl EmailDelivery {
+    pub fn blocks_sending(self) -> bool {
+        matches!(self, EmailDelivery::BOUNCED | EmailDelivery::COMPLAINED)
+    }
+}
+
+impl From<EmailDelivery> for String {
+    fn from(delivery: EmailDelivery) -> Self {
+        match delivery {
+            EmailDelivery::DELIVERED => "DELIVERED".to_string(),
+            EmailDelivery::DELAYED => "DELAYED".to_string(),
+            EmailDelivery::SOFT_BOUNCED => "SOFT_BOUNCED".to_string(),
+            EmailDelivery::BOUNCED => "BOUNCED".to_string(),
+            EmailDelivery::COMPLAINED => "COMPLAINED".to_string(),
+        }
+    }
+}
+
+impl From<&str> for EmailDelivery {
+    fn from(s: &str) -> Self {
+        match s {
+            "DELAYED" => EmailDelivery::DELAYED,
+            "SOFT_BOUNCED" => EmailDelivery::SOFT_BOUNCED,
+            "BOUNCED" => EmailDelivery::BOUNCED,
+            "COMPLAINED" => EmailDelivery::COMPLAINED,
+            _ => EmailDelivery::DELIVERED,
+        }
+    }
+}
+
+/// An account's status with when and why it was restricted
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct UserStanding {
+    /// Public id of the user
+    pub user_id: String,
+    pub status: UserStatus,
+    /// End of a suspension; `None` while active, banned or suspended indefinitely
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize_option")]
+    pub suspended_until: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+}
+
+impl UserStanding {
+    /// Treats a suspension that has run out as lifted
+    pub fn as_of(mut self, now: DateTime<Utc>) -> Self {
+        if self.status == UserStatus::SUSPENDED
+            && self.suspended_until.is_some_and(|until| until <= now)
+        {
+            self.status = UserStatus::ACTIVE;
+            self.suspended_until = None;
+            self.reason = None;
+        }
+        self
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.status != UserStatus::ACTIVE
+    }
+
+    /// What the account holder is told when turned away
+    pub fn describe(&self) -> String {
+        let mut message = match (self.status, self.suspended_until) {
+            (UserStatus::ACTIVE, _) => return "This account is active".to_string(),
+            (UserStatus::SUSPENDED, Some(until)) => {
+                format!("This account is suspended until {}", until.to_rfc3339())
+            }
+            (UserStatus::SUSPENDED, None) => "This account is suspended".to_string(),
+            (UserStatus::BANNED, _) => "This account is banned".to_string(),
+        };
+        if let Some(reason) = &self.reason {
+            message.push_str(": ");
+            message.push_str(reason);
+        }
+        message
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SuspendUserRequest {
+    /// When the suspension ends; omit to suspend until lifted
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    /// Shown to the user when they are turned away, at most 500 characters
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct BanUserRequest {
+    /// Shown to the user when they are turned away, at most 500 characters
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct User {
+    #[serde(skip)]
     pub id: Uuid,
+    #[serde(rename = "id")]
+    pub public_id: String,
     pub name: String,
+    /// Unique, lowercase; `None` for accounts created before usernames existed
+    pub username: Option<String>,
     pub email: String,
     pub password: String,
     pub role: Role,
     pub email_verified: bool,
+    /// Custom avatar; `None` falls back to Gravatar
+    pub avatar_url: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// An author's totals across every post they wrote, archived and private ones included
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct ProfileStats {
+    pub post_count: i64,
+    /// Counted views of all their posts
+    pub total_views: i64,
+    /// Always `null`: posts can't be liked yet
+    pub total_likes: Option<i64>,
+    /// Always `null`: posts can't be commented on yet
+    pub total_comments: Option<i64>,
+    /// Posts written per month, oldest first; months without posts are left out
+    pub posts_per_month: Vec<MonthlyPosts>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct MonthlyPosts {
+    /// `YYYY-MM`, in UTC
+    pub month: String,
+    pub posts: i64,
+}
+
+/// What anyone can see about a user, without signing in
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PublicProfile {
+    /// Public identifier; internal UUIDs are never exposed
+    pub id: String,
+    pub name: String,
+    pub username: Option<String>,
+    /// Uploaded avatar, or a Gravatar URL derived from the email
+    pub avatar_url: String,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// The author embedded in post responses; unlike [`UserResponse`] it has no
+/// email, role or verification state, whoever is asking
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PublicAuthor {
+    /// Public identifier; internal UUIDs are never exposed
+    pub id: String,
+    pub name: String,
+    /// `None` for accounts created before usernames existed
+    pub username: Option<String>,
+    /// Uploaded avatar, or a Gravatar URL derived from the email
+    pub avatar_url: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct CreateUserRequest {
     pub name: String,
+    /// 3 to 30 letters, digits or underscores, starting with a letter; one is
+    /// derived from `name` when omitted
+    #[serde(default)]
+    pub username: Option<String>,
     pub email: String,
     pub password: String,
+    /// Language of the emails sent to this account, e.g. `es`; defaults to the
+    /// request's `Accept-Language`
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Front-end page the verification link sends the user back to; must be
+    /// on an origin in `VERIFY_EMAIL_REDIRECT_ORIGINS`
+    #[serde(default)]
+    pub redirect_uri: Option<String>,
+    /// Honeypot; leave empty and hide it from people
+    #[serde(default)]
+    pub website: Option<String>,
+    /// Token from `GET /auth/register/form`
+    #[serde(default)]
+    pub form_token: Option<String>,
+    /// Response token of the solved hCaptcha or Turnstile widget
+    #[serde(default)]
+    pub captcha_token: Option<String>,
+}
+
+impl CreateUserRequest {
+    /// The fields the bot checks look at
+    pub fn proof(&self) -> SignupProof {
+        SignupProof {
+            website: self.website.clone(),
+            form_token: self.form_token.clone(),
+            captcha_token: self.captcha_token.clone(),
+        }
+    }
+}
+
+/// What a registration sends to pass the bot checks that are switched on;
+/// see `GET /auth/register/form`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SignupProof {
+    /// Honeypot; leave empty and hide it from people
+    #[serde(default)]
+    pub website: Option<String>,
+    /// Token from `GET /auth/register/form`
+    #[serde(default)]
+    pub form_token: Option<String>,
+    /// Response token of the solved hCaptcha or Turnstile widget
+    #[serde(default)]
+    pub captcha_token: Option<String>,
+}
+
+/// The token of a new sign-in alert's revoke link, in its query string and
+/// in the form of its confirmation page
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RevokeSessionToken {
+    pub token: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct UpdatePasswordRequest {
     pub old_password: String,
     pub new_password: String,
+    /// Also revoke every other session of this account
+    #[serde(default)]
+    pub log_out_everywhere: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct UpdateUserRequest {
     pub name: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
     pub email: Option<String>,
+    /// HTTPS image URL; an empty string reverts to Gravatar
+    pub avatar_url: Option<String>,
+    /// Language of the emails sent to this account; an empty string reverts to
+    /// the default
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Merge patch of the caller's profile; omitted fields are unchanged
+#[derive(Debug, Deserialize, Clone, Default, ToSchema)]
+#[serde(default)]
+pub struct PatchUserRequest {
+    #[schema(value_type = Option<String>)]
+    pub name: Patch<String>,
+    #[schema(value_type = Option<String>)]
+    pub username: Patch<String>,
+    #[schema(value_type = Option<String>)]
+    pub email: Patch<String>,
+    /// `null` reverts to Gravatar
+    #[schema(value_type = Option<String>)]
+    pub avatar_url: Patch<String>,
+    /// `null` reverts to the default language
+    #[schema(value_type = Option<String>)]
+    pub locale: Patch<String>,
+}
+
+impl From<UpdateUserRequest> for PatchUserRequest {
+    fn from(update: UpdateUserRequest) -> Self {
+        Self {
+            name: update.name.into(),
+            username: update.username.into(),
+            email: update.email.into(),
+            avatar_url: update.avatar_url.into(),
+            locale: update.locale.into(),
+        }
+    }
+}
+
+/// Null clears the optional fields, as an empty string does in a PUT body;
+/// the required ones can't be null
+impl TryFrom<PatchUserRequest> for UpdateUserRequest {
+    type Error = String;
+
+    fn try_from(patch: PatchUserRequest) -> Result<Self, Self::Error> {
+        for (field, null) in [
+            ("name", patch.name.is_null()),
+            ("username", patch.username.is_null()),
+            ("email", patch.email.is_null()),
+        ] {
+            if null {
+                return Err(format!("{} cannot be null", field));
+            }
+        }
+
+        let cleared = |patch: Patch<String>| match patch {
+            Patch::Null => Some(String::new()),
+            patch => patch.into_value(),
+        };
+        Ok(Self {
+            name: patch.name.into_value(),
+            username: patch.username.into_value(),
+            email: patch.email.into_value(),
+            avatar_url: cleared(patch.avatar_url),
+            locale: cleared(patch.locale),
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct LoginRequest {
-    pub email: String,
+    /// Email address or username; also accepted as `email` or `username`
+    #[serde(alias = "email", alias = "username")]
+    pub identifier: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsernameQuery {
+    pub u: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PasswordStrengthRequest {
     pub password: String,
+    /// Account details a password shouldn't be built from
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct PasswordStrength {
+    /// 0 (guessed instantly) to 4 (very hard to guess)
+    pub score: u8,
+    /// Whether registration and password changes accept the password
+    pub acceptable: bool,
+    /// What makes the password weak, if anything
+    pub warning: Option<String>,
+    /// How to make it stronger
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct UsernameAvailability {
+    /// The username as it would be stored, lowercased
+    pub username: String,
+    pub available: bool,
+    /// Why the username can't be used; `None` when it is available
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
@@ -77,77 +637,1356 @@ pub struct LoginResponse {
     pub user: UserResponse,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+/// Serialized through [`redaction`]: `email` and `email_verified` are only
+/// included for the user themselves and for admins.
+#[derive(Debug, Deserialize, Clone, ToSchema)]
 pub struct UserResponse {
-    pub id: Uuid,
+    /// Internal UUID, used for ownership checks and never serialized
+    #[serde(skip)]
+    pub internal_id: Uuid,
+    /// Public identifier; internal UUIDs are never exposed
+    pub id: String,
     pub name: String,
+    pub username: Option<String>,
+    /// Omitted unless the requester is this user or an admin
     pub email: String,
     pub role: Role,
+    /// Omitted unless the requester is this user or an admin
     pub email_verified: bool,
+    /// Uploaded avatar, or a Gravatar URL derived from the email
+    pub avatar_url: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Last delivery event for the email; only listed by `GET /admin/users`
+    /// and omitted while nothing has been reported
+    #[serde(default)]
+    pub email_delivery: Option<EmailDelivery>,
+}
+
+impl Serialize for UserResponse {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let private = redaction::sees_private(self.internal_id);
+        let delivery = self.email_delivery.filter(|_| private);
+        let len = (if private { 9 } else { 7 }) + usize::from(delivery.is_some());
+        let mut state = serializer.serialize_struct("UserResponse", len)?;
+        state.serialize_field("id", &self.id)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("username", &self.username)?;
+        if private {
+            state.serialize_field("email", &self.email)?;
+        }
+        state.serialize_field("role", &self.role)?;
+        if private {
+            state.serialize_field("email_verified", &self.email_verified)?;
+        }
+        state.serialize_field("avatar_url", &self.avatar_url)?;
+        state.serialize_field("created_at", &Timestamp(&self.created_at))?;
+        state.serialize_field("updated_at", &Timestamp(&self.updated_at))?;
+        if let Some(delivery) = delivery {
+            state.serialize_field("email_delivery", &delivery)?;
+        }
+        state.end()
+    }
+}
+
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        UserResponse {
+            internal_id: user.id,
+            id: user.public_id,
+            avatar_url: avatar::avatar_url(user.avatar_url.as_deref(), &user.email),
+            name: user.name,
+            username: user.username,
+            email: user.email,
+            role: user.role,
+            email_verified: user.email_verified,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            email_delivery: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Post {
+    #[serde(skip)]
     pub id: Uuid,
+    #[serde(rename = "id")]
+    pub public_id: String,
+    /// URL-friendly name; `None` for posts created before slugs existed
+    pub slug: Option<String>,
     pub title: String,
     pub content: String,
+    /// SPDX identifier or free text; `None` when the post has no license
+    pub license: Option<String>,
+    #[serde(skip)]
     pub author_id: Uuid,
+    /// Owning organization; `None` for posts outside any organization
+    #[serde(skip)]
+    pub org_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// When the author archived the post; `None` while it is listed
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub visibility: Visibility,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct CreatePostRequest {
     pub title: String,
     pub content: String,
+    /// Derived from the title when omitted
+    #[serde(default)]
+    pub slug: Option<String>,
+    /// SPDX identifier such as `CC-BY-4.0`, or free text; the deployment's
+    /// default license when omitted
+    #[serde(default)]
+    pub license: Option<String>,
+    /// `PUBLIC` when omitted
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct UpdatePostRequest {
     pub title: Option<String>,
     pub content: Option<String>,
+    #[serde(default)]
+    pub license: Option<String>,
+    /// Only the author can change it
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+}
+
+/// Merge patch of a post; omitted fields are unchanged
+#[derive(Debug, Deserialize, Clone, Default, ToSchema)]
+#[serde(default)]
+pub struct PatchPostRequest {
+    #[schema(value_type = Option<String>)]
+    pub title: Patch<String>,
+    #[schema(value_type = Option<String>)]
+    pub content: Patch<String>,
+    /// `null` removes the license
+    #[schema(value_type = Option<String>)]
+    pub license: Patch<String>,
+    /// Only the author can change it
+    #[schema(value_type = Option<Visibility>)]
+    pub visibility: Patch<Visibility>,
+}
+
+impl From<UpdatePostRequest> for PatchPostRequest {
+    fn from(update: UpdatePostRequest) -> Self {
+        Self {
+            title: update.title.into(),
+            content: update.content.into(),
+            license: update.license.into(),
+            visibility: update.visibility.into(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct PostResponse {
-    pub id: Uuid,
+    /// Public identifier; internal UUIDs are never exposed
+    pub id: String,
+    /// URL-friendly name; `None` for posts created before slugs existed
+    pub slug: Option<String>,
     pub title: String,
+    /// Raw Markdown as written by the author
     pub content: String,
-    pub author: UserResponse,
+    /// Sanitized HTML rendering of `content`; omitted with `?format=raw`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+    /// SPDX identifier or free text; `None` when the post has no license
+    pub license: Option<String>,
+    pub word_count: i32,
+    /// Estimated at 200 words a minute, rounded up
+    pub reading_time_minutes: i32,
+    /// The opening sentences as plain text, for index pages
+    pub excerpt: String,
+    pub author: PublicAuthor,
+    /// Distinct viewers, each counted at most once per 24 hours
+    pub view_count: i64,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
     pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
     pub updated_at: DateTime<Utc>,
+    /// When the author archived the post, for clients to show a banner;
+    /// omitted while it is listed
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::helpers::timestamps::serialize_option"
+    )]
+    pub archived_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Users mentioned as `@username` in `content`, for clients to link;
+    /// omitted when there are none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mentions: Vec<PostMention>,
+    /// Co-authors the author invited; omitted when there are none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collaborators: Vec<PostCollaborator>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct Claims {
-    pub iss: String,
-    pub sub: String,
-    pub role: Role,
-    pub exp: usize,
-    pub iat: usize,
+/// A user mentioned in a post
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct PostMention {
+    /// Public ID of the user
+    pub id: String,
+    pub username: String,
+    /// Display name
+    pub name: String,
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
-pub struct ApiResponse<T> {
-    pub message: String,
-    pub data: Option<T>,
+/// A co-author of a post
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct PostCollaborator {
+    /// Public ID of the user
+    pub id: String,
+    /// `None` for accounts that haven't picked a username
+    pub username: Option<String>,
+    /// Display name
+    pub name: String,
+    pub role: CollaboratorRole,
 }
 
-#[derive(Serialize, Deserialize, Clone, ToSchema)]
-pub struct ErrorResponse {
-    pub error: String,
-    pub message: String,
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AddCollaboratorRequest {
+    /// Public ID of the user to invite
+    pub user_id: String,
+    pub role: CollaboratorRole,
 }
 
-#[derive(Serialize, Deserialize)]
-pub enum UnifiedApiResponse<T> {
-    Success(ApiResponse<T>),
-    Error(ErrorResponse),
+/// A link that lets anyone holding it read a post, private or not
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ShareLink {
+    pub id: Uuid,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub expires_at: DateTime<Utc>,
+    /// Reads allowed before the link stops working; `None` for no limit
+    pub max_uses: Option<i32>,
+    /// Reads made through the link so far
+    pub uses: i32,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct VerifyEmailQuery {
+/// A new share link; the token is only ever shown here
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CreatedShareLink {
+    #[serde(flatten)]
+    pub link: ShareLink,
     pub token: String,
+    /// The post's URL with the token under `share`
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct CreateShareLinkRequest {
+    /// Seconds until the link stops working; `SHARE_LINK_TTL_SECS` when omitted
+    pub expires_in_secs: Option<u64>,
+    /// Reads allowed before the link stops working; unlimited when omitted
+    pub max_uses: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareQuery {
+    /// Token of a share link, for reading a private post
+    pub share: Option<String>,
+}
+
+impl PostResponse {
+    pub fn with_format(mut self, format: ContentFormat) -> Self {
+        if format == ContentFormat::Raw {
+            self.content_html = None;
+        }
+        self
+    }
+}
+
+/// An image without alt text, as listed by `GET /posts/my/media/missing-alt`
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct MediaMissingAlt {
+    /// Public id of the post the image appears in
+    pub post_id: String,
+    pub post_title: String,
+    /// Zero-based index of the image within the post
+    pub position: i32,
+    pub url: String,
+}
+
+/// An image in a post, as included by `GET /posts/{id}?include=media`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct PostMedia {
+    pub url: String,
+    /// `None` when the image has no alt text
+    pub alt_text: Option<String>,
+}
+
+/// Another post by the same author, as included by
+/// `GET /posts/{id}?include=author_posts`
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct RelatedPost {
+    pub id: String,
+    pub slug: Option<String>,
+    pub title: String,
+    pub excerpt: String,
+    pub reading_time_minutes: i32,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<PostResponse> for RelatedPost {
+    fn from(post: PostResponse) -> Self {
+        RelatedPost {
+            id: post.id,
+            slug: post.slug,
+            title: post.title,
+            excerpt: post.excerpt,
+            reading_time_minutes: post.reading_time_minutes,
+            created_at: post.created_at,
+        }
+    }
+}
+
+/// Related data `GET /posts/{id}` can embed with `?include=`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PostInclude {
+    /// The post's images with their alt text
+    Media,
+    /// The author's latest other public posts
+    AuthorPosts,
+}
+
+impl PostInclude {
+    pub const ALL: [PostInclude; 2] = [PostInclude::Media, PostInclude::AuthorPosts];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PostInclude::Media => "media",
+            PostInclude::AuthorPosts => "author_posts",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|include| include.name() == name)
+    }
+
+    /// Parses an `include` query parameter such as `media,author_posts`,
+    /// naming the permitted includes when one isn't
+    pub fn parse_list(raw: Option<&str>) -> Result<Vec<Self>, String> {
+        let mut includes = Vec::new();
+        for name in raw.unwrap_or_default().split(',').map(str::trim) {
+            if name.is_empty() {
+                continue;
+            }
+            let Some(include) = Self::from_name(name) else {
+                let permitted: Vec<&str> = Self::ALL.iter().map(|include| include.name()).collect();
+                return Err(format!(
+                    "`{}` can't be included; permitted: {}",
+                    name,
+                    permitted.join(", ")
+                ));
+            };
+            if !includes.contains(&include) {
+                includes.push(include);
+            }
+        }
+        Ok(includes)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IncludeQuery {
+    pub include: Option<String>,
+}
+
+/// The `included` section of `GET /posts/{id}`; only the requested members are present
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct PostIncluded {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media: Option<Vec<PostMedia>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_posts: Option<Vec<RelatedPost>>,
+}
+
+/// A post with the related data asked for with `?include=`
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PostWithIncluded {
+    #[serde(flatten)]
+    pub post: PostResponse,
+    /// Omitted unless `include` names something
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub included: Option<PostIncluded>,
+}
+
+/// How post content is returned on reads
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    /// Markdown only, for clients that render it themselves
+    Raw,
+    /// Markdown plus the server-rendered `content_html`
+    #[default]
+    Html,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContentFormatQuery {
+    #[serde(default)]
+    pub format: ContentFormat,
+}
+
+/// `?fields=` of list endpoints; see [`crate::helpers::fields`]
+#[derive(Debug, Deserialize)]
+pub struct FieldsQuery {
+    pub fields: Option<String>,
+}
+
+/// Session of the current request, inserted by the auth middleware
+#[derive(Debug, Clone)]
+pub struct SessionId(pub String);
+
+/// The admin behind an impersonation token, inserted by the auth middleware
+#[derive(Debug, Clone, Copy)]
+pub struct Impersonator(pub Uuid);
+
+/// Bearer token for acting as another user; it has no refresh token
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ImpersonationResponse {
+    pub auth_token: String,
+    /// Public id of the impersonated user
+    pub user_id: String,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Session {
+    pub id: String,
+    #[serde(skip)]
+    pub user_id: Uuid,
+    /// Short description such as "Firefox on Linux"
+    pub device: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// The /24 (IPv4) or /48 (IPv6) the session signed in from
+    pub network: Option<String>,
+    /// ISO 3166 country code sent by the proxy in front of the API, if any
+    pub country: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SessionResponse {
+    pub id: String,
+    pub device: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// Country the session signed in from, when known
+    pub country: Option<String>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub last_seen_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub expires_at: DateTime<Utc>,
+    /// The session making this request
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn new(session: Session, current: Option<&str>) -> Self {
+        Self {
+            current: current == Some(session.id.as_str()),
+            id: session.id,
+            device: session.device,
+            ip: session.ip,
+            user_agent: session.user_agent,
+            country: session.country,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            expires_at: session.expires_at,
+        }
+    }
+}
+
+/// Everything stored about a user, returned by `GET /auth/profile/export`
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AccountExport {
+    pub user: UserResponse,
+    pub posts: Vec<Post>,
+    /// Sign-ins, including revoked and expired ones
+    pub sessions: Vec<ExportedSession>,
+    pub notifications: Vec<Notification>,
+    pub memberships: Vec<ExportedMembership>,
+    /// Posts the user was invited to as a co-author
+    pub collaborations: Vec<ExportedCollaboration>,
+    /// Public ids of posts that mention the user
+    pub mentioned_in: Vec<String>,
+    pub share_links: Vec<ExportedShareLink>,
+    /// Emailed links issued to the user; the tokens themselves are not stored
+    pub link_tokens: Vec<ExportedLinkToken>,
+    /// Remote ActivityPub actors following the user
+    pub followers: Vec<ExportedFollower>,
+    /// Reports the user filed
+    pub reports: Vec<ExportedReport>,
+    /// Administrative actions the user took
+    pub audit_log: Vec<AuditEntry>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize_option")]
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub exported_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ExportedSession {
+    pub id: String,
+    pub device: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub network: Option<String>,
+    pub country: Option<String>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub last_seen_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize_option")]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ExportedMembership {
+    /// Public ID of the organization
+    pub org_id: String,
+    pub org_name: String,
+    pub role: OrgRole,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ExportedCollaboration {
+    /// Public ID of the post
+    pub post_id: String,
+    pub role: CollaboratorRole,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// A share link the user created
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ExportedShareLink {
+    /// Public ID of the shared post
+    pub post_id: String,
+    #[serde(flatten)]
+    pub link: ShareLink,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ExportedLinkToken {
+    /// What the link does, such as `verify_email`
+    pub purpose: String,
+    /// What the link acts on besides the user, e.g. a session id
+    pub subject: Option<String>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ExportedFollower {
+    /// URL of the remote actor
+    pub actor: String,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// A report as its reporter sees it, without moderators' notes
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ExportedReport {
+    pub id: Uuid,
+    pub target_kind: String,
+    /// Public id of the reported content
+    pub target_id: String,
+    pub reason: ReportReason,
+    pub details: Option<String>,
+    pub status: ReportStatus,
+    pub action: Option<ReportAction>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize_option")]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Largest values the API accepts, in characters
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct Limits {
+    pub max_title_length: usize,
+    /// Raw Markdown of a post
+    pub max_content_length: usize,
+    /// Display names of users
+    pub max_name_length: usize,
+    /// `details` of a post report
+    pub max_report_details_length: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_title_length: 200,
+            max_content_length: 100_000,
+            max_name_length: 100,
+            max_report_details_length: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct HomeResponse {
+    pub name: String,
+    pub tagline: String,
+    pub version: String,
+    pub docs_url: String,
+    /// `ok`, or `degraded` when the database could not be reached
+    pub status: String,
+    pub featured_posts: Vec<PostResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Organization {
+    #[serde(skip)]
+    pub id: Uuid,
+    #[serde(rename = "id")]
+    pub public_id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct OrganizationResponse {
+    pub id: String,
+    pub name: String,
+    /// The requesting user's role in this organization
+    pub role: OrgRole,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OrganizationResponse {
+    pub fn new(org: Organization, role: OrgRole) -> Self {
+        OrganizationResponse {
+            id: org.public_id,
+            name: org.name,
+            role,
+            created_at: org.created_at,
+            updated_at: org.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CreateOrganizationRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct UpdateOrganizationRequest {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct MemberResponse {
+    /// Public ID of the member
+    pub user_id: String,
+    pub name: String,
+    pub role: OrgRole,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AddMemberRequest {
+    /// Public ID of the user to add
+    pub user_id: String,
+    pub role: OrgRole,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct UpdateMemberRequest {
+    pub role: OrgRole,
+}
+
+/// Organization selected through `X-Org-Id` for the current request
+#[derive(Debug, Clone)]
+pub struct OrgContext {
+    pub org_id: Uuid,
+    pub public_id: String,
+    /// `None` when the requester is anonymous or not a member
+    pub role: Option<OrgRole>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Claims {
+    pub iss: String,
+    pub sub: String,
+    pub role: Role,
+    pub exp: usize,
+    pub iat: usize,
+    /// Login session the token belongs to; absent on tokens not tied to a session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Set on refresh tokens, where it doubles as the `sessions` row id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// The admin acting as `sub` on an impersonation token (RFC 8693)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ActorClaim {
+    pub sub: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ApiResponse<T> {
+    pub message: String,
+    pub data: Option<T>,
+}
+
+/// RFC 7807 problem details; `instance` carries the request id when known
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ErrorResponse {
+    #[serde(rename = "type")]
+    pub error_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// RFC 6901 pointer to the request body member that was rejected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+}
+
+impl ErrorResponse {
+    pub fn new(status: StatusCode, title: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            error_type: "about:blank".to_string(),
+            title: title.into(),
+            status: status.as_u16(),
+            detail: detail.into(),
+            instance: None,
+            pointer: None,
+        }
+    }
+
+    pub fn with_pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.pointer = Some(pointer.into());
+        self
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::BAD_REQUEST)
+    }
+}
+
+/// Pre-RFC 7807 error body, served when `ERROR_FORMAT=legacy`
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct LegacyErrorResponse {
+    pub error: String,
+    pub message: String,
+}
+
+impl From<ErrorResponse> for LegacyErrorResponse {
+    fn from(err: ErrorResponse) -> Self {
+        Self {
+            error: err.title,
+            message: err.detail,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum UnifiedApiResponse<T> {
+    Success(ApiResponse<T>),
+    Error(ErrorResponse),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailQuery {
+    pub token: String,
+    #[serde(default)]
+    pub redirect_uri: Option<String>,
+}
+
+/// Which of your posts `GET /posts/my` lists
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PostStatus {
+    /// Shown in listings and feeds
+    Published,
+    /// Kept out of listings and feeds, still readable by direct link
+    Archived,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MyPostsQuery {
+    /// Every post when omitted
+    pub status: Option<PostStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrendingQuery {
+    pub limit: Option<i64>,
+}
+
+/// One notification of a publish hook, with its retry history
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct HookDelivery {
+    pub id: Uuid,
+    /// `post.published` or `post.updated`; ActivityPub deliveries use
+    /// `activity.create`, `activity.update` and `follow.accepted`
+    pub event: String,
+    /// Public id of the post; `None` for follow acceptances
+    pub post_id: Option<String>,
+    pub method: String,
+    pub url: String,
+    /// Key id the request is signed with; set on ActivityPub deliveries
+    pub signed_by: Option<String>,
+    #[serde(skip)]
+    pub body: Option<String>,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    /// HTTP status of the last attempt, if a response arrived
+    pub last_status: Option<i32>,
+    pub last_error: Option<String>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub next_attempt_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebFingerQuery {
+    pub resource: String,
+}
+
+/// An allow/deny rule for registration and email-change addresses
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct EmailDomainRule {
+    /// Matches this domain and its subdomains
+    pub domain: String,
+    pub kind: DomainRuleKind,
+    /// Rules from `ALLOWED_EMAIL_DOMAINS`/`DENIED_EMAIL_DOMAINS` can't be removed at runtime
+    pub from_config: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CreateEmailDomainRuleRequest {
+    pub domain: String,
+    pub kind: DomainRuleKind,
+}
+
+/// A feature operators can switch off at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureFlag {
+    Registration,
+    PostCreation,
+    EmailSending,
+    /// Unlike the others, off by default: on pauses writes for non-admins
+    Maintenance,
+}
+
+impl FeatureFlag {
+    pub const ALL: [FeatureFlag; 4] = [
+        FeatureFlag::Registration,
+        FeatureFlag::PostCreation,
+        FeatureFlag::EmailSending,
+        FeatureFlag::Maintenance,
+    ];
+
+    /// Key in the `feature_flags` table and the admin API
+    pub fn name(self) -> &'static str {
+        match self {
+            FeatureFlag::Registration => "registration",
+            FeatureFlag::PostCreation => "post_creation",
+            FeatureFlag::EmailSending => "email_sending",
+            FeatureFlag::Maintenance => "maintenance",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|flag| flag.name() == name)
+    }
+
+    /// What the flag turns off, for error messages
+    pub fn describe(self) -> &'static str {
+        match self {
+            FeatureFlag::Registration => "Registration",
+            FeatureFlag::PostCreation => "Creating posts",
+            FeatureFlag::EmailSending => "Sending email",
+            FeatureFlag::Maintenance => "Maintenance mode",
+        }
+    }
+}
+
+/// Every feature flag; features are on and maintenance is off unless an admin
+/// switched them
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct FeatureFlagSet {
+    /// `POST /auth/register`
+    pub registration: bool,
+    /// `POST /posts` and post imports
+    pub post_creation: bool,
+    /// Verification emails; while off they are skipped, not queued
+    pub email_sending: bool,
+    /// Writes by anyone but admins answer 503
+    pub maintenance: bool,
+}
+
+impl Default for FeatureFlagSet {
+    fn default() -> Self {
+        Self {
+            registration: true,
+            post_creation: true,
+            email_sending: true,
+            maintenance: false,
+        }
+    }
+}
+
+impl FeatureFlagSet {
+    pub fn is_enabled(&self, flag: FeatureFlag) -> bool {
+        match flag {
+            FeatureFlag::Registration => self.registration,
+            FeatureFlag::PostCreation => self.post_creation,
+            FeatureFlag::EmailSending => self.email_sending,
+            FeatureFlag::Maintenance => self.maintenance,
+        }
+    }
+
+    pub fn set(&mut self, flag: FeatureFlag, enabled: bool) {
+        match flag {
+            FeatureFlag::Registration => self.registration = enabled,
+            FeatureFlag::PostCreation => self.post_creation = enabled,
+            FeatureFlag::EmailSending => self.email_sending = enabled,
+            FeatureFlag::Maintenance => self.maintenance = enabled,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SetMaintenanceRequest {
+    pub enabled: bool,
+}
+
+/// Whether writes are paused, and why
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    /// `MAINTENANCE_MODE` is set, so the admin API can't turn it off
+    pub forced: bool,
+    /// `Retry-After` sent with rejected writes
+    pub retry_after_secs: u64,
+}
+
+/// An unverified account found by `POST /admin/users/resend-verifications`
+#[derive(Debug, Clone)]
+pub struct PendingVerification {
+    pub user: User,
+    pub locale: Option<String>,
+    /// `false` for suspended or banned accounts and ones scheduled for deletion
+    pub eligible: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ResendVerificationsRequest {
+    /// Only accounts created at least this many hours ago; defaults to
+    /// `VERIFICATION_RESEND_MIN_AGE_SECS`
+    pub older_than_hours: Option<u32>,
+}
+
+/// What `POST /admin/users/resend-verifications` queued; the emails go out
+/// in the background
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ResendVerificationsSummary {
+    /// Unverified accounts that will get an email
+    pub queued: usize,
+    /// Unverified accounts left out because they are suspended, banned or
+    /// scheduled for deletion
+    pub skipped: usize,
+    pub batch_size: usize,
+    pub batch_interval_secs: u64,
+}
+
+/// Flags to switch; those left out keep their current value
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct UpdateFeatureFlagsRequest {
+    pub registration: Option<bool>,
+    pub post_creation: Option<bool>,
+    pub email_sending: Option<bool>,
+    pub maintenance: Option<bool>,
+}
+
+impl UpdateFeatureFlagsRequest {
+    pub fn changes(&self) -> Vec<(FeatureFlag, bool)> {
+        [
+            (FeatureFlag::Registration, self.registration),
+            (FeatureFlag::PostCreation, self.post_creation),
+            (FeatureFlag::EmailSending, self.email_sending),
+            (FeatureFlag::Maintenance, self.maintenance),
+        ]
+        .into_iter()
+        .filter_map(|(flag, enabled)| enabled.map(|enabled| (flag, enabled)))
+        .collect()
+    }
+}
+
+/// Result of checking one external dependency
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct DependencyCheck {
+    /// e.g. `postgres`, `redis`, `resend`
+    pub name: String,
+    pub status: DependencyStatus,
+    pub latency_ms: Option<u64>,
+    /// Error or configuration note
+    pub detail: Option<String>,
+}
+
+/// Whether the tables the server creates at startup all exist
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct SchemaStatus {
+    pub up_to_date: bool,
+    pub missing_tables: Vec<String>,
+}
+
+/// Connection pool usage
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct PoolStats {
+    /// Open connections, idle or in use
+    pub size: u32,
+    pub idle: usize,
+    pub max_connections: u32,
+    pub acquire_timeout_secs: u64,
+    pub health: Readiness,
+}
+
+/// A recurring background task
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct JobInfo {
+    pub name: String,
+    pub description: String,
+    /// `false` when configuration leaves the job with nothing to do
+    pub enabled: bool,
+    pub interval_secs: u64,
+}
+
+/// How the latest run of a recurring job went
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct JobRun {
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub started_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub finished_at: DateTime<Utc>,
+    pub succeeded: bool,
+    /// What the run did, or why it failed
+    pub message: Option<String>,
+}
+
+/// A recurring job and its latest run on this instance
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct JobStatus {
+    #[serde(flatten)]
+    pub job: JobInfo,
+    /// `None` until the job has run since the server started
+    pub last_run: Option<JobRun>,
+}
+
+/// Snapshot of a deployment for debugging without shell access
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct Diagnostics {
+    pub version: String,
+    pub environment: String,
+    /// Cargo features compiled into this binary
+    pub features: Vec<String>,
+    /// Effective settings by environment variable; secrets are masked
+    pub config: BTreeMap<String, String>,
+    pub schema: SchemaStatus,
+    pub pool: PoolStats,
+    pub jobs: Vec<JobInfo>,
+    pub dependencies: Vec<DependencyCheck>,
+}
+
+/// Runtime counters for operators
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct Metrics {
+    pub cache: CacheStats,
+    /// Latency of each repository method called so far, slowest p99 first
+    pub queries: Vec<QueryStats>,
+}
+
+/// Why content was reported
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum ReportReason {
+    SPAM,
+    HARASSMENT,
+    HATE,
+    VIOLENCE,
+    SEXUAL,
+    MISINFORMATION,
+    COPYRIGHT,
+    OTHER,
+}
+
+impl From<ReportReason> for String {
+    fn from(reason: ReportReason) -> Self {
+        match reason {
+            ReportReason::SPAM => "SPAM".to_string(),
+            ReportReason::HARASSMENT => "HARASSMENT".to_string(),
+            ReportReason::HATE => "HATE".to_string(),
+            ReportReason::VIOLENCE => "VIOLENCE".to_string(),
+            ReportReason::SEXUAL => "SEXUAL".to_string(),
+            ReportReason::MISINFORMATION => "MISINFORMATION".to_string(),
+            ReportReason::COPYRIGHT => "COPYRIGHT".to_string(),
+            ReportReason::OTHER => "OTHER".to_string(),
+        }
+    }
+}
+
+impl From<&str> for ReportReason {
+    fn from(s: &str) -> Self {
+        match s {
+            "SPAM" => ReportReason::SPAM,
+            "HARASSMENT" => ReportReason::HARASSMENT,
+            "HATE" => ReportReason::HATE,
+            "VIOLENCE" => ReportReason::VIOLENCE,
+            "SEXUAL" => ReportReason::SEXUAL,
+            "MISINFORMATION" => ReportReason::MISINFORMATION,
+            "COPYRIGHT" => ReportReason::COPYRIGHT,
+            _ => ReportReason::OTHER,
+        }
+    }
+}
+
+/// Where a report stands in the moderation queue
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+pub enum ReportStatus {
+    #[default]
+    OPEN,
+    /// Closed without action
+    DISMISSED,
+    /// Closed after the content was deleted or its author suspended
+    RESOLVED,
+}
+
+impl From<ReportStatus> for String {
+    fn from(status: ReportStatus) -> Self {
+        match status {
+            ReportStatus::OPEN => "OPEN".to_string(),
+            ReportStatus::DISMISSED => "DISMISSED".to_string(),
+            ReportStatus::RESOLVED => "RESOLVED".to_string(),
+        }
+    }
+}
+
+impl From<&str> for ReportStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "DISMISSED" => ReportStatus::DISMISSED,
+            "RESOLVED" => ReportStatus::RESOLVED,
+            _ => ReportStatus::OPEN,
+        }
+    }
+}
+
+/// What a moderator did about a report
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum ReportAction {
+    /// Close the report and leave the content up
+    DISMISS,
+    /// Delete the reported content
+    DELETE,
+    /// Suspend the content's author, signing them out everywhere
+    SUSPEND,
+}
+
+impl From<ReportAction> for String {
+    fn from(action: ReportAction) -> Self {
+        match action {
+            ReportAction::DISMISS => "DISMISS".to_string(),
+            ReportAction::DELETE => "DELETE".to_string(),
+            ReportAction::SUSPEND => "SUSPEND".to_string(),
+        }
+    }
+}
+
+impl From<&str> for ReportAction {
+    fn from(s: &str) -> Self {
+        match s {
+            "DELETE" => ReportAction::DELETE,
+            "SUSPEND" => ReportAction::SUSPEND,
+            _ => ReportAction::DISMISS,
+        }
+    }
+}
+
+/// A user's report of a post, as seen by moderators
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Report {
+    pub id: Uuid,
+    /// `post`; the only kind of content that can be reported so far
+    pub target_kind: String,
+    #[serde(skip)]
+    pub target_internal_id: Uuid,
+    /// Public id of the reported content
+    pub target_id: String,
+    #[serde(skip)]
+    pub author_internal_id: Option<Uuid>,
+    /// Public id of the reported content's author; `None` once their account is deleted
+    pub author_id: Option<String>,
+    /// Public id of the reporter; `None` once their account is deleted
+    pub reporter_id: Option<String>,
+    pub reason: ReportReason,
+    pub details: Option<String>,
+    pub status: ReportStatus,
+    pub action: Option<ReportAction>,
+    pub note: Option<String>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize_option")]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CreateReportRequest {
+    pub reason: ReportReason,
+    /// Free-text context for moderators; `GET /limits` gives the longest allowed
+    #[serde(default)]
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ResolveReportRequest {
+    pub action: ReportAction,
+    /// Kept with the report and in the audit log
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportsQuery {
+    #[serde(default)]
+    pub status: ReportStatus,
+}
+
+/// An administrative action, newest first in `GET /admin/audit-log`
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    /// Public id of the admin who acted; `None` for background jobs and once
+    /// their account is deleted
+    pub actor_id: Option<String>,
+    /// Dotted name such as `report.dismissed`
+    pub action: String,
+    /// What was acted on, as `kind:id`
+    pub target: String,
+    pub detail: Option<String>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// What a notification is about
+#[allow(non_camel_case_types)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum NotificationKind {
+    ACCOUNT_SUSPENDED,
+    ACCOUNT_BANNED,
+    ACCOUNT_REINSTATED,
+    /// A moderator removed one of the user's posts
+    POST_REMOVED,
+    /// A moderator acted on one of the user's reports
+    REPORT_RESOLVED,
+    /// Someone mentioned the user in a post
+    MENTIONED,
+    /// An author invited the user to co-author a post
+    COLLABORATOR_INVITED,
+}
+
+impl From<NotificationKind> for String {
+    fn from(kind: NotificationKind) -> Self {
+        match kind {
+            NotificationKind::ACCOUNT_SUSPENDED => "ACCOUNT_SUSPENDED".to_string(),
+            NotificationKind::ACCOUNT_BANNED => "ACCOUNT_BANNED".to_string(),
+            NotificationKind::ACCOUNT_REINSTATED => "ACCOUNT_REINSTATED".to_string(),
+            NotificationKind::POST_REMOVED => "POST_REMOVED".to_string(),
+            NotificationKind::REPORT_RESOLVED => "REPORT_RESOLVED".to_string(),
+            NotificationKind::MENTIONED => "MENTIONED".to_string(),
+            NotificationKind::COLLABORATOR_INVITED => "COLLABORATOR_INVITED".to_string(),
+        }
+    }
+}
+
+impl From<&str> for NotificationKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "ACCOUNT_SUSPENDED" => NotificationKind::ACCOUNT_SUSPENDED,
+            "ACCOUNT_BANNED" => NotificationKind::ACCOUNT_BANNED,
+            "ACCOUNT_REINSTATED" => NotificationKind::ACCOUNT_REINSTATED,
+            "POST_REMOVED" => NotificationKind::POST_REMOVED,
+            "MENTIONED" => NotificationKind::MENTIONED,
+            "COLLABORATOR_INVITED" => NotificationKind::COLLABORATOR_INVITED,
+            _ => NotificationKind::REPORT_RESOLVED,
+        }
+    }
+}
+
+/// An entry in a user's inbox
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Notification {
+    pub id: Uuid,
+    pub kind: NotificationKind,
+    pub message: String,
+    /// What the notification is about, as `kind:id`
+    pub target: Option<String>,
+    /// `None` while unread
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize_option")]
+    pub read_at: Option<DateTime<Utc>>,
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationsQuery {
+    /// Only unread notifications
+    #[serde(default)]
+    pub unread: bool,
+    /// Only notifications created before this time, for paging
+    pub before: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+/// Cheap to poll; clients fetch the inbox when `unread` changes
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NotificationSummary {
+    pub unread: i64,
+    /// Creation time of the newest notification, read or not
+    #[serde(serialize_with = "crate::helpers::timestamps::serialize_option")]
+    pub latest_at: Option<DateTime<Utc>>,
 }
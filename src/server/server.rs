@@ -0,0 +1,328 @@
+//! Building the shared state and serving the API.
+//!
+//! [`run`] does everything the binary does. Embedders that bring their own
+//! server call [`build_state`] and [`spawn_jobs`], then mount
+//! [`build_router`](crate::app::build_router) into their own app.
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use axum::{
+    Router,
+    http::{HeaderMap, Uri, header},
+    response::Redirect,
+};
+use axum_server::{Handle, tls_rustls::RustlsConfig};
+use tokio::signal;
+use tracing::info;
+
+use crate::app::build_router;
+use crate::cache::Cache;
+use crate::config::AppConfig;
+#[cfg(feature = "redis")]
+use crate::config::CacheBackend;
+use crate::db::db::get_pg_client;
+use crate::db::instrument;
+use crate::db::pools::connect_replica;
+use crate::db::repositories::feature_flag_repo::FeatureFlagRepository;
+use crate::helpers;
+use crate::helpers::diagnostics::mask_url;
+use crate::helpers::edge_cache::EdgeCache;
+use crate::helpers::email_templates::EmailTemplates;
+use crate::helpers::feature_flags::FeatureFlags;
+use crate::helpers::i18n::Messages;
+use crate::helpers::listener::{self, TcpAcceptor};
+#[cfg(feature = "redis")]
+use crate::helpers::redis_client::RedisClient;
+use crate::helpers::signup_guard::SignupGuard;
+use crate::jobs;
+use crate::state::AppState;
+
+/// Serves the API as configured until Ctrl+C or SIGTERM
+///
+/// Tracing is left to the caller, so install a subscriber first to see logs.
+pub async fn run(config: AppConfig) -> Result<()> {
+    // One structured event operators can grep for, then the masked settings
+    tracing::info!(
+        version = env!("CARGO_PKG_VERSION"),
+        environment = ?config.environment,
+        addr = %config.socket_addr(),
+        base_path = %config.base_path,
+        tls = config.tls_enabled(),
+        features = ?helpers::diagnostics::enabled_features(),
+        "Starting Axum REST API server"
+    );
+    for (key, value) in helpers::diagnostics::config_summary(&config) {
+        tracing::debug!("config {}={}", key, value);
+    }
+
+    let state = build_state(&config).await?;
+    spawn_jobs(&state, &config);
+    let app = build_router(state);
+
+    let sock_addr: SocketAddr = config.socket_addr();
+    let tcp_listener = listener::bind(sock_addr, &config.server)
+        .with_context(|| format!("failed to bind {}", sock_addr))?;
+
+    let handle = Handle::new();
+    let shutdown_handle = handle.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        shutdown_handle.graceful_shutdown(Some(Duration::from_secs(10)));
+    });
+
+    let acceptor = TcpAcceptor::new(&config.server);
+    let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    match &config.tls {
+        Some(tls) => {
+            let rustls_config = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+                .await
+                .context("failed to load TLS certificate or key")?;
+            listener::restrict_alpn(&rustls_config, &config.server);
+
+            if let Some(http_port) = tls.redirect_http_port {
+                tokio::spawn(redirect_http_to_https(
+                    SocketAddr::new(sock_addr.ip(), http_port),
+                    sock_addr.port(),
+                ));
+            }
+
+            let mut server = axum_server::from_tcp_rustls(tcp_listener, rustls_config)
+                .map(|tls_acceptor| tls_acceptor.acceptor(acceptor))
+                .handle(handle);
+            listener::tune(server.http_builder(), &config.server);
+
+            tracing::info!("Server starting on https://{}", sock_addr);
+            server.serve(make_service).await?;
+        }
+        None => {
+            let mut server = axum_server::from_tcp(tcp_listener)
+                .acceptor(acceptor)
+                .handle(handle);
+            listener::tune(server.http_builder(), &config.server);
+
+            tracing::info!("Server starting on http://{}", sock_addr);
+            server.serve(make_service).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the signing setup, connects to the database (and Redis, when
+/// configured) and loads the email templates and feature flags
+pub async fn build_state(config: &AppConfig) -> Result<AppState> {
+    helpers::response::set_error_format(config.error_format);
+    instrument::set_slow_query_threshold(config.database.slow_query_threshold);
+    // Fail at startup, not on the first login, if the signing keys are misconfigured
+    lazy_static::initialize(&helpers::auth::JWT_KEYS);
+    helpers::auth::check_secrets(config.is_production())?;
+    #[cfg(feature = "activitypub")]
+    helpers::http_signatures::check_federation_key(config.is_production())?;
+    tracing::info!(
+        "Signing auth tokens with key {}",
+        helpers::auth::JWT_KEYS.current_kid()
+    );
+
+    let sql_db = get_pg_client(&config.database)
+        .await
+        .context("failed to connect to the database")?;
+    tracing::info!("Successfully connected to the database.");
+
+    let pool = Arc::new(sql_db.get_pool().clone());
+
+    #[cfg(feature = "redis")]
+    let redis = match config.redis.url.as_deref() {
+        Some(url) => Some(
+            RedisClient::connect(url, &config.redis.key_prefix)
+                .await
+                .context("failed to connect to Redis")?,
+        ),
+        None => None,
+    };
+    #[cfg(not(feature = "redis"))]
+    if config.redis.url.is_some() {
+        tracing::warn!(
+            "REDIS_URL is set but the redis feature is disabled; state stays per process"
+        );
+    }
+
+    #[cfg(feature = "redis")]
+    let cache = match &redis {
+        Some(client) if config.cache.backend == CacheBackend::Redis => {
+            Cache::redis(client.clone(), &config.cache)
+        }
+        _ => Cache::from_config(&config.cache),
+    };
+    #[cfg(not(feature = "redis"))]
+    let cache = Cache::from_config(&config.cache);
+
+    let emails = EmailTemplates::load(&config.email.templates_dir, &config.email.default_locale)
+        .context("failed to load email templates")?;
+    let messages =
+        Messages::load(&config.messages_dir).context("failed to load message catalogs")?;
+    let signup =
+        SignupGuard::from_config(&config.signup).context("failed to load the signup checks")?;
+
+    let flags = FeatureFlagRepository::new((*pool).clone())
+        .load()
+        .await
+        .context("failed to load feature flags")?;
+
+    let state = AppState::new(pool, config.clone())
+        .with_cache(Arc::new(cache))
+        .with_edge_cache(Arc::new(EdgeCache::from_config(config)))
+        .with_emails(Arc::new(emails))
+        .with_messages(Arc::new(messages))
+        .with_signup_guard(Arc::new(signup))
+        .with_feature_flags(Arc::new(FeatureFlags::new(flags)));
+    #[cfg(feature = "redis")]
+    let state = match redis {
+        Some(client) => state.with_redis(client),
+        None => state,
+    };
+    let state = match config.database.read_url.as_deref() {
+        Some(url) => {
+            let replica =
+                connect_replica(url, &config.database).context("invalid DATABASE_READ_URL")?;
+            tracing::info!("Routing public reads to the replica at {}", mask_url(url));
+            state.with_replica(Arc::new(replica))
+        }
+        None => state,
+    };
+
+    Ok(state)
+}
+
+/// Starts the background jobs: account purges, database health probes,
+/// feature flag reloads and, when configured, unverified account cleanup,
+/// replica health probes and publish hook delivery
+pub fn spawn_jobs(state: &AppState, config: &AppConfig) {
+    jobs::spawn_account_purge(
+        state.pool.clone(),
+        state.clock.clone(),
+        state.jobs.clone(),
+        config.accounts.purge_interval,
+    );
+
+    if let Some(cleanup) = config.accounts.unverified_cleanup {
+        info!(
+            "Cleaning up accounts unverified after {} days ({:?})",
+            config.accounts.unverified_max_age.as_secs() / (24 * 60 * 60),
+            cleanup
+        );
+        jobs::spawn_unverified_cleanup(
+            state.pool.clone(),
+            state.clock.clone(),
+            state.jobs.clone(),
+            config.accounts.clone(),
+            cleanup,
+        );
+    }
+
+    jobs::spawn_db_health_monitor(
+        state.pool.clone(),
+        state.health.clone(),
+        state.clock.clone(),
+        state.jobs.clone(),
+        config.database.health_interval,
+    );
+
+    if state.pools.replica.is_some() {
+        jobs::spawn_replica_monitor(
+            state.pools.clone(),
+            state.clock.clone(),
+            state.jobs.clone(),
+            config.database.health_interval,
+        );
+    }
+
+    jobs::spawn_feature_flag_refresh(
+        state.pool.clone(),
+        state.flags.clone(),
+        state.clock.clone(),
+        state.jobs.clone(),
+        config.feature_flag_refresh,
+    );
+
+    // ActivityPub deliveries to followers share the publish hook queue
+    if !config.publish_hooks.hooks.is_empty() || cfg!(feature = "activitypub") {
+        info!(
+            "Notifying {} publish hook(s) on post changes",
+            config.publish_hooks.hooks.len()
+        );
+        jobs::spawn_hook_delivery(
+            state.pool.clone(),
+            state.clock.clone(),
+            state.jobs.clone(),
+            config.publish_hooks.clone(),
+        );
+    }
+}
+
+async fn redirect_http_to_https(http_addr: SocketAddr, https_port: u16) {
+    let redirect = move |headers: HeaderMap, uri: Uri| async move {
+        let host = headers
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .map(|host| host.split(':').next().unwrap_or(host).to_string())
+            .unwrap_or_else(|| "localhost".to_string());
+        let path = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+
+        let location = if https_port == 443 {
+            format!("https://{}{}", host, path)
+        } else {
+            format!("https://{}:{}{}", host, https_port, path)
+        };
+        Redirect::permanent(&location)
+    };
+
+    let listener = match tokio::net::TcpListener::bind(http_addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(
+                "Failed to bind HTTP redirect listener on {}: {}",
+                http_addr,
+                e
+            );
+            return;
+        }
+    };
+
+    tracing::info!("Redirecting http://{} to HTTPS", http_addr);
+    if let Err(e) = axum::serve(listener, Router::new().fallback(redirect))
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+    {
+        tracing::error!("HTTP redirect listener failed: {}", e);
+    }
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {
+            tracing::info!("Received Ctrl+C signal, initiating graceful shutdown...");
+        },
+        _ = terminate => {
+            tracing::info!("Received SIGTERM signal, initiating graceful shutdown...");
+        },
+    }
+}
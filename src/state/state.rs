@@ -0,0 +1,323 @@
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+use sqlx::PgPool;
+
+use crate::cache::Cache;
+use crate::config::AppConfig;
+use crate::db::health::DbHealth;
+use crate::db::pools::DbPools;
+use crate::db::repositories::link_token_repo::LinkTokenRepository;
+use crate::helpers::clock::{Clock, SystemClock};
+use crate::helpers::deliverability::SuppressingMailer;
+use crate::helpers::denylist::TokenDenylist;
+use crate::helpers::deprecation::GoneEndpoints;
+use crate::helpers::edge_cache::EdgeCache;
+use crate::helpers::email_templates::EmailTemplates;
+use crate::helpers::feature_flags::FeatureFlags;
+#[cfg(feature = "activitypub")]
+use crate::helpers::federation::{ActorFetcher, HttpActorFetcher};
+use crate::helpers::i18n::Messages;
+use crate::helpers::link_tokens::LinkTokenStore;
+use crate::helpers::mailer::Mailer;
+use crate::helpers::maintenance::MaintenanceGate;
+use crate::helpers::middleware::SessionGuard;
+#[cfg(feature = "redis")]
+use crate::helpers::redis_client::RedisClient;
+use crate::helpers::resend::ResendClient;
+use crate::helpers::sign_in_alerts::SignInAlerts;
+use crate::helpers::signup_guard::SignupGuard;
+use crate::helpers::usage::UsageTracker;
+use crate::helpers::verification::VerificationMailer;
+use crate::jobs::runs::JobRuns;
+
+#[derive(Clone)]
+pub struct AppState {
+    /// The primary, for writes and reads that must see them
+    pub pool: Arc<PgPool>,
+    /// The primary and, when configured, the replica public reads go to
+    pub pools: DbPools,
+    pub config: Arc<AppConfig>,
+    pub clock: Arc<dyn Clock>,
+    pub gone: Arc<GoneEndpoints>,
+    pub cache: Arc<Cache>,
+    pub edge: Arc<EdgeCache>,
+    pub denylist: Arc<TokenDenylist>,
+    pub emails: Arc<EmailTemplates>,
+    /// Translations of response messages
+    pub messages: Arc<Messages>,
+    pub mailer: Arc<dyn Mailer>,
+    /// Single-use tokens of emailed links
+    pub link_tokens: Arc<dyn LinkTokenStore>,
+    pub health: Arc<DbHealth>,
+    /// Bot checks on registrations
+    pub signup: Arc<SignupGuard>,
+    pub flags: Arc<FeatureFlags>,
+    /// Latest run of each recurring job started by `spawn_jobs`
+    pub jobs: Arc<JobRuns>,
+    /// Requests of each signed-in user and API key against their daily quota
+    pub usage: Arc<UsageTracker>,
+    /// Loads remote actors to check the signatures of inbox deliveries
+    #[cfg(feature = "activitypub")]
+    pub actors: Arc<dyn ActorFetcher>,
+    /// Shared by every replica; `None` keeps rate limits and revocations per process
+    #[cfg(feature = "redis")]
+    pub redis: Option<RedisClient>,
+}
+
+impl AppState {
+    pub fn new(pool: Arc<PgPool>, config: AppConfig) -> Self {
+        // Resend, skipping addresses that bounced or complained
+        let mailer = Arc::new(SuppressingMailer::new(
+            Arc::new(ResendClient::new()),
+            (*pool).clone(),
+        ));
+        Self {
+            pools: DbPools::new(pool.clone()),
+            link_tokens: Arc::new(LinkTokenRepository::new((*pool).clone())),
+            pool,
+            gone: Arc::new(GoneEndpoints::new(&config.gone_endpoints)),
+            config: Arc::new(config),
+            clock: Arc::new(SystemClock),
+            cache: Arc::new(Cache::disabled()),
+            edge: Arc::new(EdgeCache::disabled()),
+            denylist: Arc::new(TokenDenylist::disabled()),
+            emails: Arc::new(EmailTemplates::default()),
+            messages: Arc::new(Messages::default()),
+            mailer,
+            health: Arc::new(DbHealth::default()),
+            signup: Arc::new(SignupGuard::disabled()),
+            flags: Arc::new(FeatureFlags::default()),
+            jobs: Arc::new(JobRuns::new()),
+            usage: Arc::new(UsageTracker::new()),
+            #[cfg(feature = "activitypub")]
+            actors: Arc::new(HttpActorFetcher::new(reqwest::Client::new())),
+            #[cfg(feature = "redis")]
+            redis: None,
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn with_edge_cache(mut self, edge: Arc<EdgeCache>) -> Self {
+        self.edge = edge;
+        self
+    }
+
+    pub fn with_emails(mut self, emails: Arc<EmailTemplates>) -> Self {
+        self.emails = emails;
+        self
+    }
+
+    pub fn with_messages(mut self, messages: Arc<Messages>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    pub fn with_mailer(mut self, mailer: Arc<dyn Mailer>) -> Self {
+        self.mailer = mailer;
+        self
+    }
+
+    pub fn with_replica(mut self, replica: Arc<PgPool>) -> Self {
+        self.pools = self.pools.with_replica(replica);
+        self
+    }
+
+    pub fn with_health(mut self, health: Arc<DbHealth>) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub fn with_signup_guard(mut self, signup: Arc<SignupGuard>) -> Self {
+        self.signup = signup;
+        self
+    }
+
+    pub fn with_feature_flags(mut self, flags: Arc<FeatureFlags>) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    #[cfg(feature = "activitypub")]
+    pub fn with_actor_fetcher(mut self, actors: Arc<dyn ActorFetcher>) -> Self {
+        self.actors = actors;
+        self
+    }
+
+    /// Shares rate limits and revoked sessions with the other replicas
+    #[cfg(feature = "redis")]
+    pub fn with_redis(mut self, client: RedisClient) -> Self {
+        self.denylist = Arc::new(TokenDenylist::redis(
+            client.clone(),
+            self.config.tokens.refresh_ttl,
+        ));
+        self.redis = Some(client);
+        self
+    }
+
+    pub fn shares_rate_limits(&self) -> bool {
+        #[cfg(feature = "redis")]
+        if self.redis.is_some() {
+            return true;
+        }
+        false
+    }
+}
+
+impl FromRef<AppState> for Arc<PgPool> {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for DbPools {
+    fn from_ref(state: &AppState) -> Self {
+        state.pools.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<AppConfig> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn Clock> {
+    fn from_ref(state: &AppState) -> Self {
+        state.clock.clone()
+    }
+}
+
+#[cfg(feature = "activitypub")]
+impl FromRef<AppState> for Arc<dyn ActorFetcher> {
+    fn from_ref(state: &AppState) -> Self {
+        state.actors.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<GoneEndpoints> {
+    fn from_ref(state: &AppState) -> Self {
+        state.gone.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Cache> {
+    fn from_ref(state: &AppState) -> Self {
+        state.cache.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<EdgeCache> {
+    fn from_ref(state: &AppState) -> Self {
+        state.edge.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<TokenDenylist> {
+    fn from_ref(state: &AppState) -> Self {
+        state.denylist.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<EmailTemplates> {
+    fn from_ref(state: &AppState) -> Self {
+        state.emails.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn Mailer> {
+    fn from_ref(state: &AppState) -> Self {
+        state.mailer.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn LinkTokenStore> {
+    fn from_ref(state: &AppState) -> Self {
+        state.link_tokens.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<DbHealth> {
+    fn from_ref(state: &AppState) -> Self {
+        state.health.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<SignupGuard> {
+    fn from_ref(state: &AppState) -> Self {
+        state.signup.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<FeatureFlags> {
+    fn from_ref(state: &AppState) -> Self {
+        state.flags.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<JobRuns> {
+    fn from_ref(state: &AppState) -> Self {
+        state.jobs.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<UsageTracker> {
+    fn from_ref(state: &AppState) -> Self {
+        state.usage.clone()
+    }
+}
+
+impl FromRef<AppState> for MaintenanceGate {
+    fn from_ref(state: &AppState) -> Self {
+        MaintenanceGate {
+            flags: state.flags.clone(),
+            guard: SessionGuard::from_ref(state),
+            config: state.config.maintenance.clone(),
+        }
+    }
+}
+
+impl FromRef<AppState> for SessionGuard {
+    fn from_ref(state: &AppState) -> Self {
+        SessionGuard {
+            pool: state.pool.clone(),
+            denylist: state.denylist.clone(),
+            clock: state.clock.clone(),
+        }
+    }
+}
+
+impl FromRef<AppState> for VerificationMailer {
+    fn from_ref(state: &AppState) -> Self {
+        VerificationMailer {
+            flags: state.flags.clone(),
+            mailer: state.mailer.clone(),
+            tokens: state.link_tokens.clone(),
+            emails: state.emails.clone(),
+            config: state.config.clone(),
+            clock: state.clock.clone(),
+        }
+    }
+}
+
+impl FromRef<AppState> for SignInAlerts {
+    fn from_ref(state: &AppState) -> Self {
+        SignInAlerts {
+            flags: state.flags.clone(),
+            mailer: state.mailer.clone(),
+            tokens: state.link_tokens.clone(),
+            emails: state.emails.clone(),
+            config: state.config.clone(),
+            clock: state.clock.clone(),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Runs against the database in `TEST_DATABASE_URL` and is skipped without one.
+
+mod common;
+
+use std::collections::BTreeSet;
+
+use axum_rest::db::repositories::export_repo::{
+    EXPORTED_USER_COLUMNS, ExportRepository, WITHHELD_USER_COLUMNS,
+};
+use chrono::{Duration, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use common::with_test_db;
+
+/// Foreign keys to `users`, plus user ids kept without one, such as `feature_flags.updated_by`
+const USER_COLUMNS: &str = r#"
+    SELECT kcu.table_name, kcu.column_name
+    FROM information_schema.referential_constraints rc
+    JOIN information_schema.key_column_usage kcu
+        ON kcu.constraint_schema = rc.constraint_schema AND kcu.constraint_name = rc.constraint_name
+    JOIN information_schema.table_constraints target
+        ON target.constraint_schema = rc.unique_constraint_schema AND target.constraint_name = rc.unique_constraint_name
+    WHERE rc.constraint_schema = current_schema() AND target.table_name = 'users'
+    UNION
+    SELECT table_name, column_name
+    FROM information_schema.columns
+    WHERE table_schema = current_schema()
+        AND data_type = 'uuid'
+        AND (column_name = 'user_id' OR column_name LIKE '%\_by')
+"#;
+
+#[tokio::test]
+async fn every_column_pointing_at_a_user_is_exported_or_withheld_on_purpose() {
+    with_test_db(|pool| async move {
+        let in_schema: BTreeSet<String> = sqlx::query(USER_COLUMNS)
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|row| {
+                format!(
+                    "{}.{}",
+                    row.get::<String, _>("table_name"),
+                    row.get::<String, _>("column_name")
+                )
+            })
+            .collect();
+
+        let accounted_for: BTreeSet<String> = EXPORTED_USER_COLUMNS
+            .into_iter()
+            .chain(WITHHELD_USER_COLUMNS.into_iter().map(|(column, _)| column))
+            .map(String::from)
+            .collect();
+
+        assert_eq!(
+            in_schema, accounted_for,
+            "add new user columns to the account export, or to WITHHELD_USER_COLUMNS with a reason"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn revoked_sessions_are_exported_too() {
+    with_test_db(|pool| async move {
+        let user_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO users (id, public_id, name, email, password) VALUES ($1, 'ada', 'Ada', 'ada@example.com', 'x')")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let now = Utc::now();
+        for (id, revoked_at) in [("live", None), ("revoked", Some(now))] {
+            sqlx::query(
+                "INSERT INTO sessions (id, user_id, device, created_at, last_seen_at, expires_at, revoked_at) VALUES ($1, $2, 'Firefox on Linux', $3, $3, $4, $5)",
+            )
+            .bind(id)
+            .bind(user_id)
+            .bind(now)
+            .bind(now + Duration::days(1))
+            .bind(revoked_at)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let sessions = ExportRepository::new(pool.clone())
+            .sessions(user_id)
+            .await
+            .unwrap();
+
+        let mut ids: Vec<_> = sessions.iter().map(|s| s.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["live", "revoked"]);
+    })
+    .await;
+}
@@ -0,0 +1,100 @@
+//! Runs against the database in `TEST_DATABASE_URL` and is skipped without one.
+
+mod common;
+
+use axum_rest::db::repositories::stats_repo::StatsRepository;
+use axum_rest::model::model::MonthlyPosts;
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use common::with_test_db;
+
+async fn author(pool: &PgPool) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, public_id, name, email, password) VALUES ($1, $2, 'Author', $3, 'x')",
+    )
+    .bind(id)
+    .bind(id.simple().to_string())
+    .bind(format!("{}@example.com", id.simple()))
+    .execute(pool)
+    .await
+    .unwrap();
+    id
+}
+
+async fn post(pool: &PgPool, author_id: Uuid, created_at: DateTime<Utc>, views: i64) {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO posts (id, public_id, title, content, author_id, created_at, view_count) VALUES ($1, $2, 'Title', 'Body', $3, $4, $5)",
+    )
+    .bind(id)
+    .bind(id.simple().to_string())
+    .bind(author_id)
+    .bind(created_at)
+    .bind(views)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+fn at(month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, month, day, hour, 0, 0).unwrap()
+}
+
+fn month(month: &str, posts: i64) -> MonthlyPosts {
+    MonthlyPosts {
+        month: month.to_string(),
+        posts,
+    }
+}
+
+#[tokio::test]
+async fn totals_and_months_cover_every_post() {
+    with_test_db(|pool| async move {
+        let (author_id, other_id) = (author(&pool).await, author(&pool).await);
+        post(&pool, author_id, at(3, 31, 23), 5).await;
+        post(&pool, author_id, at(1, 2, 0), 2).await;
+        post(&pool, author_id, at(1, 20, 0), 0).await;
+        post(&pool, other_id, at(2, 1, 0), 100).await;
+
+        let stats = StatsRepository::new(pool)
+            .author_stats(author_id)
+            .await
+            .unwrap();
+
+        assert_eq!(stats.post_count, 3);
+        assert_eq!(stats.total_views, 7);
+        assert_eq!(
+            stats.posts_per_month,
+            vec![month("2025-01", 2), month("2025-03", 1)]
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn authors_without_posts_get_zeros_and_null_engagement() {
+    with_test_db(|pool| async move {
+        let author_id = author(&pool).await;
+
+        let stats = StatsRepository::new(pool)
+            .author_stats(author_id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap(),
+            json!({
+                "post_count": 0,
+                "total_views": 0,
+                "total_likes": null,
+                "total_comments": null,
+                "posts_per_month": []
+            })
+        );
+    })
+    .await;
+}
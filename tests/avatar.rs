@@ -0,0 +1,38 @@
+use axum_rest::helpers::avatar::{avatar_url, gravatar_url, validate_avatar_url};
+
+#[test]
+fn gravatar_hashes_normalized_email() {
+    assert_eq!(
+        gravatar_url("ada@example.com"),
+        "https://www.gravatar.com/avatar/b5fc85e55755f9e0d030a10ab4429b6b2944855f9a0d60077fe832becbc41d72?d=identicon"
+    );
+    assert_eq!(
+        gravatar_url("  Ada@Example.COM "),
+        gravatar_url("ada@example.com")
+    );
+}
+
+#[test]
+fn custom_avatar_takes_precedence() {
+    let custom = "https://cdn.example.com/ada.png";
+
+    assert_eq!(avatar_url(Some(custom), "ada@example.com"), custom);
+    assert_eq!(
+        avatar_url(Some(""), "ada@example.com"),
+        gravatar_url("ada@example.com")
+    );
+    assert_eq!(
+        avatar_url(None, "ada@example.com"),
+        gravatar_url("ada@example.com")
+    );
+}
+
+#[test]
+fn rejects_insecure_or_oversized_urls() {
+    assert!(validate_avatar_url("https://cdn.example.com/ada.png").is_ok());
+    assert!(validate_avatar_url("http://cdn.example.com/ada.png").is_err());
+    assert!(validate_avatar_url("javascript:alert(1)").is_err());
+
+    let long = format!("https://cdn.example.com/{}", "a".repeat(2048));
+    assert!(validate_avatar_url(&long).is_err());
+}
@@ -0,0 +1,42 @@
+use axum_rest::config::AppConfig;
+use axum_rest::helpers::validation::check_new_post;
+use axum_rest::model::model::CreatePostRequest;
+
+fn post(title: &str, license: Option<&str>) -> CreatePostRequest {
+    CreatePostRequest {
+        title: title.to_string(),
+        content: "Some content".to_string(),
+        slug: None,
+        license: license.map(str::to_string),
+        visibility: None,
+    }
+}
+
+fn config() -> AppConfig {
+    let mut config = AppConfig::from_env();
+    config.default_license = Some("CC-BY-4.0".to_string());
+    config
+}
+
+#[test]
+fn new_posts_get_the_default_license() {
+    let checked = check_new_post(post("Hello", None), &config()).unwrap();
+
+    assert_eq!(checked.license.as_deref(), Some("CC-BY-4.0"));
+}
+
+#[test]
+fn blank_titles_are_refused() {
+    assert_eq!(
+        check_new_post(post("  ", None), &config()).unwrap_err(),
+        "Title and content are required"
+    );
+}
+
+#[test]
+fn invalid_slugs_are_refused() {
+    let mut request = post("Hello", None);
+    request.slug = Some("Not A Slug".to_string());
+
+    assert!(check_new_post(request, &config()).is_err());
+}
@@ -0,0 +1,180 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::time::Duration;
+
+use axum_rest::cache::{Cache, LATEST_POSTS_KEY, TRENDING_POSTS_KEY, post_key, profile_key};
+use axum_rest::config::{CacheBackend, CacheConfig};
+
+fn config() -> CacheConfig {
+    CacheConfig {
+        backend: CacheBackend::Memory,
+        ttl: Duration::from_secs(60),
+        stale_for: Duration::from_secs(30),
+        max_entries: 100,
+    }
+}
+
+#[test]
+fn backends_parse_from_env_values() {
+    assert_eq!(CacheBackend::from("redis"), CacheBackend::Redis);
+    assert_eq!(CacheBackend::from("OFF"), CacheBackend::Disabled);
+    assert_eq!(CacheBackend::from("memory"), CacheBackend::Memory);
+    assert_eq!(CacheBackend::from("unknown"), CacheBackend::Memory);
+}
+
+#[test]
+fn keys_are_namespaced_per_kind() {
+    assert_eq!(post_key("abc"), "post:abc");
+    assert_eq!(profile_key("abc"), "profile:abc");
+    assert_ne!(post_key("abc"), LATEST_POSTS_KEY);
+    assert_ne!(TRENDING_POSTS_KEY, LATEST_POSTS_KEY);
+}
+
+#[tokio::test]
+async fn reads_hit_after_a_write_and_miss_after_invalidation() {
+    let cache = Cache::memory(&config());
+    let key = post_key("abc");
+
+    assert_eq!(cache.get::<Vec<String>>(&key).await, None);
+
+    cache.set(&key, &vec!["hello".to_string()]).await;
+    assert_eq!(
+        cache.get::<Vec<String>>(&key).await,
+        Some(vec!["hello".to_string()])
+    );
+
+    cache.invalidate(&key).await;
+    assert_eq!(cache.get::<Vec<String>>(&key).await, None);
+
+    let stats = cache.stats();
+    assert_eq!(stats.backend, "memory");
+    assert_eq!((stats.hits, stats.misses, stats.invalidations), (1, 2, 1));
+    assert!((stats.hit_ratio - 1.0 / 3.0).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn values_of_another_shape_count_as_misses() {
+    let cache = Cache::memory(&config());
+
+    cache.set(LATEST_POSTS_KEY, &"not a list").await;
+
+    assert_eq!(cache.get::<Vec<String>>(LATEST_POSTS_KEY).await, None);
+    assert_eq!(cache.stats().misses, 1);
+}
+
+#[tokio::test]
+async fn disabled_cache_never_holds_values_or_counts_lookups() {
+    let cache = Cache::disabled();
+
+    cache.set("post:abc", &1).await;
+
+    assert_eq!(cache.get::<i32>("post:abc").await, None);
+    let stats = cache.stats();
+    assert_eq!(stats.backend, "disabled");
+    assert_eq!((stats.hits, stats.misses, stats.entries), (0, 0, None));
+}
+
+#[test]
+fn redis_backend_falls_back_to_memory_without_a_redis_client() {
+    let config = CacheConfig {
+        backend: CacheBackend::Redis,
+        ..config()
+    };
+
+    let cache = Cache::from_config(&config);
+
+    assert_eq!(cache.backend(), "memory");
+}
+
+/// Every entry is stale as soon as it is written
+fn always_stale() -> CacheConfig {
+    CacheConfig {
+        ttl: Duration::ZERO,
+        ..config()
+    }
+}
+
+#[tokio::test]
+async fn concurrent_misses_load_once() {
+    let cache = Arc::new(Cache::memory(&config()));
+    let loads = Arc::new(AtomicUsize::new(0));
+
+    let load = || {
+        let loads = loads.clone();
+        move || async move {
+            loads.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            anyhow::Ok(vec!["fresh".to_string()])
+        }
+    };
+
+    let (a, b, c) = tokio::join!(
+        cache.get_or_refresh(LATEST_POSTS_KEY, load()),
+        cache.get_or_refresh(LATEST_POSTS_KEY, load()),
+        cache.get_or_refresh(LATEST_POSTS_KEY, load()),
+    );
+
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    for value in [a, b, c] {
+        assert_eq!(value.unwrap(), vec!["fresh".to_string()]);
+    }
+}
+
+#[tokio::test]
+async fn stale_entries_are_served_while_one_refresh_runs() {
+    let cache = Arc::new(Cache::memory(&always_stale()));
+    let loads = Arc::new(AtomicUsize::new(0));
+    cache.set(TRENDING_POSTS_KEY, &1).await;
+    tokio::time::sleep(Duration::from_millis(5)).await;
+
+    for _ in 0..3 {
+        let loads = loads.clone();
+        let value = cache
+            .get_or_refresh(TRENDING_POSTS_KEY, move || async move {
+                loads.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                anyhow::Ok(2)
+            })
+            .await
+            .unwrap();
+        assert_eq!(value, 1);
+    }
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    let value = cache
+        .get_or_refresh(TRENDING_POSTS_KEY, || async { anyhow::Ok(3) })
+        .await
+        .unwrap();
+    assert_eq!(value, 2);
+    assert_eq!(cache.stats().stale_hits, 4);
+}
+
+#[tokio::test]
+async fn plain_reads_treat_stale_entries_as_misses() {
+    let cache = Cache::memory(&always_stale());
+
+    cache.set(&post_key("abc"), &1).await;
+    tokio::time::sleep(Duration::from_millis(5)).await;
+
+    assert_eq!(cache.get::<i32>(&post_key("abc")).await, None);
+}
+
+#[tokio::test]
+async fn disabled_cache_always_loads() {
+    let cache = Arc::new(Cache::disabled());
+
+    for expected in [1, 2] {
+        let value = cache
+            .get_or_refresh(
+                LATEST_POSTS_KEY,
+                move || async move { anyhow::Ok(expected) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(value, expected);
+    }
+}
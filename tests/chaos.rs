@@ -0,0 +1,69 @@
+//! Runs only with `--features chaos`.
+#![cfg(feature = "chaos")]
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    Extension, Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+    middleware,
+    routing::get,
+};
+use axum_rest::db::pools::DbPools;
+use axum_rest::handlers::auth_handlers::get_profile_stats;
+use axum_rest::helpers::chaos::{DB_ERROR_HEADER, LATENCY_HEADER, chaos_middleware};
+use serde_json::Value;
+use sqlx::postgres::PgPoolOptions;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+// Nothing listens on port 1; a real lookup would wait out the acquire timeout
+fn app() -> Router {
+    let pool = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_secs(10))
+        .connect_lazy("postgres://app@127.0.0.1:1/none")
+        .unwrap();
+    Router::new()
+        .route("/auth/profile/stats", get(get_profile_stats))
+        .route("/health/live", get(|| async { "ok" }))
+        .layer(Extension(Uuid::new_v4()))
+        .layer(middleware::from_fn(chaos_middleware))
+        .with_state(DbPools::new(Arc::new(pool)))
+}
+
+#[tokio::test]
+async fn injected_database_errors_become_500s() {
+    let request = Request::builder()
+        .uri("/auth/profile/stats")
+        .header(DB_ERROR_HEADER, "true")
+        .body(Body::empty())
+        .unwrap();
+
+    let started = Instant::now();
+    let response = app().oneshot(request).await.unwrap();
+
+    // Failed before the repository reached for a connection
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: Value =
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(body["title"], "Database Error");
+    assert_eq!(body["detail"], "Unable to compute statistics");
+}
+
+#[tokio::test]
+async fn injected_latency_delays_the_response() {
+    let request = Request::builder()
+        .uri("/health/live")
+        .header(LATENCY_HEADER, "200")
+        .body(Body::empty())
+        .unwrap();
+
+    let started = Instant::now();
+    let response = app().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(started.elapsed() >= Duration::from_millis(200));
+}
@@ -0,0 +1,49 @@
+use axum_rest::cli::{Cli, Command, parse_age};
+use chrono::Duration;
+use clap::Parser;
+
+#[test]
+fn ages_take_a_unit() {
+    assert_eq!(parse_age("30d"), Ok(Duration::days(30)));
+    assert_eq!(parse_age("12h"), Ok(Duration::hours(12)));
+    assert_eq!(parse_age("2w"), Ok(Duration::weeks(2)));
+    assert!(parse_age("30").is_err());
+    assert!(parse_age("30y").is_err());
+    assert!(parse_age("d").is_err());
+}
+
+#[test]
+fn serving_is_the_default() {
+    let cli = Cli::try_parse_from(["axum-rest"]).unwrap();
+    assert!(cli.command.is_none());
+    assert!(cli.export_openapi.is_none());
+}
+
+#[test]
+fn export_flag_defaults_to_stdout() {
+    let cli = Cli::try_parse_from(["axum-rest", "--export-openapi"]).unwrap();
+    assert_eq!(cli.export_openapi.as_deref(), Some("-"));
+
+    let cli = Cli::try_parse_from(["axum-rest", "--export-openapi", "openapi.yaml"]).unwrap();
+    assert_eq!(cli.export_openapi.as_deref(), Some("openapi.yaml"));
+}
+
+#[test]
+fn prune_defaults_to_thirty_days() {
+    let cli = Cli::try_parse_from(["axum-rest", "prune-unverified"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Some(Command::PruneUnverified { older_than }) if older_than == Duration::days(30)
+    ));
+
+    let cli = Cli::try_parse_from(["axum-rest", "prune-unverified", "--older-than", "7d"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Some(Command::PruneUnverified { older_than }) if older_than == Duration::days(7)
+    ));
+}
+
+#[test]
+fn create_admin_needs_an_email() {
+    assert!(Cli::try_parse_from(["axum-rest", "create-admin", "--password", "x"]).is_err());
+}
@@ -0,0 +1,80 @@
+//! Postgres fixture shared by the tests that need a database.
+//!
+//! They run against `TEST_DATABASE_URL` and are skipped without one. It is
+//! kept apart from `DATABASE_URL` on purpose, since that names the app's own
+//! database. Every test gets a schema of its own holding freshly created
+//! tables, which is dropped once the test ends, whether it passed or not.
+
+use std::future::Future;
+use std::panic;
+use std::time::Duration;
+
+use axum_rest::config::DatabaseConfig;
+use axum_rest::db::db::Db;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub fn config() -> DatabaseConfig {
+    DatabaseConfig {
+        max_connections: 2,
+        acquire_timeout: Duration::from_secs(5),
+        connect_attempts: 1,
+        connect_backoff: Duration::from_millis(500),
+        max_connect_backoff: Duration::from_secs(3),
+        health_interval: Duration::from_secs(10),
+        read_url: None,
+        slow_query_threshold: Duration::from_millis(250),
+    }
+}
+
+/// `url` with every connection's search path set to `schema`
+fn scoped_url(url: &str, schema: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{url}{separator}options=-c%20search_path%3D{schema}")
+}
+
+/// Runs `test` with the URL of a throwaway, empty schema of `TEST_DATABASE_URL`
+pub async fn with_test_schema<F, Fut>(test: F)
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set; skipping");
+        return;
+    };
+
+    let schema = format!("test_{}", Uuid::new_v4().simple());
+    let admin = PgPool::connect(&url).await.unwrap();
+    sqlx::query(&format!("CREATE SCHEMA {schema}"))
+        .execute(&admin)
+        .await
+        .unwrap();
+
+    let outcome = tokio::spawn(test(scoped_url(&url, &schema))).await;
+
+    sqlx::query(&format!("DROP SCHEMA {schema} CASCADE"))
+        .execute(&admin)
+        .await
+        .unwrap();
+
+    if let Err(err) = outcome {
+        panic::resume_unwind(err.into_panic());
+    }
+}
+
+/// Runs `test` with a pool on a throwaway schema holding every table
+#[allow(dead_code)]
+pub async fn with_test_db<F, Fut>(test: F)
+where
+    F: FnOnce(PgPool) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    with_test_schema(|url| async move {
+        let db = Db::new(&url, &config()).await.unwrap();
+        let pool = db.get_pool().clone();
+        test(pool.clone()).await;
+        pool.close().await;
+    })
+    .await;
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use axum_extra::extract::cookie::SameSite;
+use axum_rest::config::AppConfig;
+use axum_rest::helpers::cookies::{AUTH_COOKIE, CookieFactory, REFRESH_COOKIE};
+use axum_rest::helpers::middleware::CSRF_COOKIE;
+
+fn config() -> AppConfig {
+    let mut config = AppConfig::from_env();
+    config.cookies.secure = true;
+    config.cookies.same_site = SameSite::Strict;
+    config.cookies.domain = Some("example.com".to_string());
+    config.cookies.path = "/blog".to_string();
+    config.tokens.access_ttl = Duration::from_secs(600);
+    config.tokens.refresh_ttl = Duration::from_secs(3600);
+    config
+}
+
+#[test]
+fn login_cookies_follow_the_config() {
+    let config = config();
+    let cookies = CookieFactory::new(&config).login(
+        "auth".to_string(),
+        "refresh".to_string(),
+        "csrf".to_string(),
+    );
+
+    let names: Vec<_> = cookies.iter().map(|cookie| cookie.name()).collect();
+    assert_eq!(names, [AUTH_COOKIE, REFRESH_COOKIE, CSRF_COOKIE]);
+    for cookie in &cookies {
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Strict));
+        assert_eq!(cookie.domain(), Some("example.com"));
+        assert_eq!(cookie.path(), Some("/blog"));
+    }
+
+    assert_eq!(cookies[0].max_age(), Some(time::Duration::seconds(600)));
+    assert_eq!(cookies[1].max_age(), Some(time::Duration::seconds(3600)));
+    assert_eq!(cookies[0].http_only(), Some(true));
+    assert_eq!(cookies[2].http_only(), Some(false));
+}
+
+#[test]
+fn cleared_cookies_match_the_ones_set() {
+    let config = config();
+    let factory = CookieFactory::new(&config);
+    let set = factory.login(String::new(), String::new(), String::new());
+    let cleared = factory.clear();
+
+    for (set, cleared) in set.iter().zip(&cleared) {
+        assert_eq!(set.name(), cleared.name());
+        assert_eq!(set.domain(), cleared.domain());
+        assert_eq!(set.path(), cleared.path());
+        assert_eq!(cleared.value(), "");
+        assert!(cleared.max_age().unwrap().is_negative());
+    }
+}
@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, header},
+    routing::get,
+};
+use axum_rest::config::{CorsConfig, Environment};
+use tower::ServiceExt;
+
+async fn allowed_origin(origin: &str) -> Option<String> {
+    let cors = CorsConfig {
+        allowed_origins: Vec::new(),
+        allow_credentials: true,
+        exposed_headers: Vec::new(),
+        max_age: Duration::from_secs(600),
+    };
+    let app = Router::new()
+        .route("/auth/profile", get(|| async { "profile" }))
+        .layer(cors.layer(Environment::Development));
+
+    let request = Request::get("/auth/profile")
+        .header(header::ORIGIN, origin)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    response
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn development_mirrors_local_origins() {
+    for origin in [
+        "http://localhost:3000",
+        "http://localhost",
+        "https://127.0.0.1:5173",
+        "http://[::1]:8080",
+    ] {
+        assert_eq!(allowed_origin(origin).await.as_deref(), Some(origin));
+    }
+}
+
+#[tokio::test]
+async fn development_rejects_other_sites() {
+    for origin in [
+        "https://evil.example.com",
+        "http://localhost.evil.example.com",
+        "http://127.0.0.1.nip.io",
+        "null",
+    ] {
+        assert_eq!(allowed_origin(origin).await, None, "{}", origin);
+    }
+}
+
+#[tokio::test]
+async fn production_preflights_allow_the_api_headers() {
+    let cors = CorsConfig {
+        allowed_origins: vec!["https://app.example.com".to_string()],
+        allow_credentials: true,
+        exposed_headers: Vec::new(),
+        max_age: Duration::from_secs(600),
+    };
+    let app = Router::new()
+        .route("/posts", get(|| async { "posts" }))
+        .layer(cors.layer(Environment::Production));
+
+    let request = Request::options("/posts")
+        .header(header::ORIGIN, "https://app.example.com")
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+        .header(
+            header::ACCESS_CONTROL_REQUEST_HEADERS,
+            "api-version, x-api-key",
+        )
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    let allowed = response
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_HEADERS)
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(allowed.contains("api-version"), "{}", allowed);
+    assert!(allowed.contains("x-api-key"), "{}", allowed);
+}
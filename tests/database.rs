@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum_rest::config::DatabaseConfig;
+use axum_rest::db::health::DbHealth;
+use axum_rest::db::pools::{DbPools, connect_replica};
+use axum_rest::model::model::DbStatus;
+use chrono::{TimeZone, Utc};
+use sqlx::PgPool;
+
+fn config() -> DatabaseConfig {
+    DatabaseConfig {
+        max_connections: 10,
+        acquire_timeout: Duration::from_secs(5),
+        connect_attempts: 5,
+        connect_backoff: Duration::from_millis(500),
+        max_connect_backoff: Duration::from_secs(3),
+        health_interval: Duration::from_secs(10),
+        read_url: None,
+        slow_query_threshold: Duration::from_millis(250),
+    }
+}
+
+#[test]
+fn startup_retries_back_off_up_to_the_cap() {
+    let config = config();
+
+    assert_eq!(config.connect_delay(1), Duration::from_millis(500));
+    assert_eq!(config.connect_delay(2), Duration::from_secs(1));
+    assert_eq!(config.connect_delay(3), Duration::from_secs(2));
+    assert_eq!(config.connect_delay(4), Duration::from_secs(3));
+    assert_eq!(config.connect_delay(u32::MAX), Duration::from_secs(3));
+}
+
+#[test]
+fn readiness_follows_recorded_status_changes() {
+    let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let health = DbHealth::new(start);
+    assert!(health.is_ready());
+
+    let down_at = start + chrono::Duration::minutes(1);
+    assert_eq!(health.record(DbStatus::UP, down_at), None);
+    assert_eq!(
+        health.record(DbStatus::DOWN, down_at),
+        Some((DbStatus::UP, start))
+    );
+    assert!(!health.is_ready());
+
+    // Repeated probes keep the time the outage began
+    assert_eq!(
+        health.record(DbStatus::DOWN, down_at + chrono::Duration::seconds(10)),
+        None
+    );
+    let readiness = health.readiness();
+    assert_eq!(readiness.database, DbStatus::DOWN);
+    assert_eq!(readiness.since, down_at);
+
+    let up_at = down_at + chrono::Duration::minutes(2);
+    assert_eq!(
+        health.record(DbStatus::UP, up_at),
+        Some((DbStatus::DOWN, down_at))
+    );
+    assert!(health.readiness().ready);
+}
+
+/// A pool that never connects, told apart by its database name
+fn lazy_pool(database: &str) -> Arc<PgPool> {
+    Arc::new(PgPool::connect_lazy(&format!("postgres://app@127.0.0.1:1/{}", database)).unwrap())
+}
+
+fn database(pool: &PgPool) -> String {
+    pool.connect_options().get_database().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn reads_use_the_replica_only_while_it_is_up() {
+    let pools = DbPools::new(lazy_pool("primary"));
+    assert_eq!(database(pools.reader()), "primary");
+
+    let pools = pools.with_replica(lazy_pool("replica"));
+    // Until the monitor has seen the replica answer
+    assert_eq!(database(pools.reader()), "primary");
+
+    assert!(pools.set_replica_up(true));
+    assert!(!pools.set_replica_up(true));
+    assert_eq!(database(pools.reader()), "replica");
+
+    // Clones share the replica status, as handlers and the monitor do
+    let handler_pools = pools.clone();
+    assert!(pools.set_replica_up(false));
+    assert_eq!(database(handler_pools.reader()), "primary");
+    assert_eq!(database(&handler_pools.primary), "primary");
+}
+
+#[tokio::test]
+async fn an_unreachable_replica_does_not_fail_startup() {
+    assert!(connect_replica("postgres://app@127.0.0.1:1/replica", &config()).is_ok());
+    assert!(connect_replica("not a url", &config()).is_err());
+}
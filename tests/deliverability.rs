@@ -0,0 +1,98 @@
+use axum::http::{HeaderMap, HeaderValue};
+use axum_rest::helpers::deliverability::{ResendEvent, verify_webhook};
+use axum_rest::model::model::EmailDelivery;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{Duration, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const KEY: &[u8] = b"resend-webhook-test-key";
+const BODY: &[u8] = br#"{"type":"email.bounced","created_at":"2025-03-01T09:00:00.000Z","data":{"to":["ada@example.com"]}}"#;
+
+fn secret() -> String {
+    format!("whsec_{}", STANDARD.encode(KEY))
+}
+
+fn sign(key: &[u8], id: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(format!("{}.{}.", id, timestamp).as_bytes());
+    mac.update(body);
+    format!("v1,{}", STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+fn headers(timestamp: i64, signature: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("svix-id", HeaderValue::from_static("msg_2a"));
+    headers.insert("svix-timestamp", timestamp.to_string().parse().unwrap());
+    headers.insert("svix-signature", signature.parse().unwrap());
+    headers
+}
+
+#[test]
+fn signed_events_are_accepted() {
+    let now = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+    let signature = sign(KEY, "msg_2a", now.timestamp(), BODY);
+
+    assert!(verify_webhook(&secret(), &headers(now.timestamp(), &signature), BODY, now).is_ok());
+
+    // While the secret rotates, any one of the signatures may match
+    let rotated = format!(
+        "{} {}",
+        sign(b"old", "msg_2a", now.timestamp(), BODY),
+        signature
+    );
+    assert!(verify_webhook(&secret(), &headers(now.timestamp(), &rotated), BODY, now).is_ok());
+}
+
+#[test]
+fn forged_or_replayed_events_are_refused() {
+    let now = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+    let signature = sign(KEY, "msg_2a", now.timestamp(), BODY);
+    let headers = headers(now.timestamp(), &signature);
+
+    let tampered = String::from_utf8_lossy(BODY).replace("ada@", "eve@");
+    assert!(verify_webhook(&secret(), &headers, tampered.as_bytes(), now).is_err());
+
+    let other_secret = format!("whsec_{}", STANDARD.encode(b"another key"));
+    assert!(verify_webhook(&other_secret, &headers, BODY, now).is_err());
+
+    let later = now + Duration::minutes(10);
+    assert!(verify_webhook(&secret(), &headers, BODY, later).is_err());
+
+    assert!(verify_webhook(&secret(), &HeaderMap::new(), BODY, now).is_err());
+}
+
+fn event(json: &str) -> ResendEvent {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn events_map_to_delivery_statuses() {
+    let bounced: ResendEvent = serde_json::from_slice(BODY).unwrap();
+    assert_eq!(bounced.delivery(), Some(EmailDelivery::BOUNCED));
+    assert_eq!(bounced.data.to, ["ada@example.com"]);
+
+    let transient = event(
+        r#"{"type":"email.bounced","created_at":"2025-03-01T09:00:00Z","data":{"to":["ada@example.com"],"bounce":{"type":"Transient"}}}"#,
+    );
+    assert_eq!(transient.delivery(), Some(EmailDelivery::SOFT_BOUNCED));
+
+    let complained = event(
+        r#"{"type":"email.complained","created_at":"2025-03-01T09:00:00Z","data":{"to":["ada@example.com"]}}"#,
+    );
+    assert_eq!(complained.delivery(), Some(EmailDelivery::COMPLAINED));
+
+    let opened = event(
+        r#"{"type":"email.opened","created_at":"2025-03-01T09:00:00Z","data":{"to":["ada@example.com"]}}"#,
+    );
+    assert_eq!(opened.delivery(), None);
+}
+
+#[test]
+fn only_dead_addresses_stop_sending() {
+    assert!(EmailDelivery::BOUNCED.blocks_sending());
+    assert!(EmailDelivery::COMPLAINED.blocks_sending());
+    assert!(!EmailDelivery::SOFT_BOUNCED.blocks_sending());
+    assert!(!EmailDelivery::DELAYED.blocks_sending());
+    assert!(!EmailDelivery::DELIVERED.blocks_sending());
+}
@@ -0,0 +1,10 @@
+use axum_rest::helpers::denylist::TokenDenylist;
+
+#[tokio::test]
+async fn disabled_denylist_leaves_revocation_to_the_database() {
+    let denylist = TokenDenylist::disabled();
+
+    denylist.deny(&["session-1".to_string()]).await;
+
+    assert!(!denylist.is_denied("session-1").await);
+}
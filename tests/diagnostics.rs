@@ -0,0 +1,32 @@
+use axum_rest::helpers::diagnostics::{MASK, enabled_features, mask_url};
+
+#[test]
+fn url_passwords_are_masked() {
+    assert_eq!(
+        mask_url("postgresql://postgres:hunter2@db:5432/axumdb"),
+        format!("postgresql://postgres:{}@db:5432/axumdb", MASK)
+    );
+    assert_eq!(
+        mask_url("redis://:s3cret@cache:6379/0"),
+        format!("redis://:{}@cache:6379/0", MASK)
+    );
+    // Only the authority is inspected, so an @ in the path is left alone
+    assert_eq!(
+        mask_url("http://localhost:8080/users/@ada"),
+        "http://localhost:8080/users/@ada"
+    );
+    assert_eq!(mask_url("not a url"), "not a url");
+}
+
+#[test]
+fn features_match_the_build() {
+    let features = enabled_features();
+    assert_eq!(
+        features.contains(&"redis".to_string()),
+        cfg!(feature = "redis")
+    );
+    assert_eq!(
+        features.contains(&"chaos".to_string()),
+        cfg!(feature = "chaos")
+    );
+}
@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum_rest::helpers::edge_cache::{CachePurger, EdgeCache, PurgeFuture};
+
+#[derive(Default)]
+struct RecordingPurger {
+    purged: Mutex<Vec<Vec<String>>>,
+}
+
+impl CachePurger for RecordingPurger {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+
+    fn purge<'a>(&'a self, urls: &'a [String]) -> PurgeFuture<'a> {
+        Box::pin(async move {
+            self.purged.lock().unwrap().push(urls.to_vec());
+            Ok(())
+        })
+    }
+}
+
+fn edge(purger: Arc<RecordingPurger>) -> EdgeCache {
+    EdgeCache::new(
+        purger,
+        "https://api.example.com/v1".to_string(),
+        "https://blog.example.com/p/{id}".to_string(),
+        vec!["/".to_string(), "posts".to_string(), "/posts".to_string()],
+    )
+}
+
+#[test]
+fn post_changes_purge_the_post_and_each_listing_once() {
+    let edge = edge(Arc::new(RecordingPurger::default()));
+
+    assert_eq!(
+        edge.post_urls("abc123"),
+        vec![
+            "https://api.example.com/v1/posts/abc123",
+            "https://blog.example.com/p/abc123",
+            "https://api.example.com/v1/",
+            "https://api.example.com/v1/posts",
+        ]
+    );
+}
+
+#[test]
+fn a_disabled_edge_cache_purges_nothing() {
+    let edge = EdgeCache::disabled();
+    assert!(!edge.is_enabled());
+    // Must not need a runtime, since nothing is spawned
+    edge.purge_post("abc123");
+}
+
+#[tokio::test]
+async fn purges_run_in_the_background() {
+    let purger = Arc::new(RecordingPurger::default());
+    let edge = edge(purger.clone());
+
+    edge.purge_post("abc123");
+
+    for _ in 0..100 {
+        if !purger.purged.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let purged = purger.purged.lock().unwrap();
+    assert_eq!(purged.len(), 1);
+    assert_eq!(purged[0][0], "https://api.example.com/v1/posts/abc123");
+}
@@ -0,0 +1,61 @@
+use axum_rest::helpers::email_policy::{EmailDomainPolicy, email_domain, normalize_domain};
+use axum_rest::model::model::{DomainRuleKind, EmailDomainRule};
+
+fn rule(domain: &str, kind: DomainRuleKind) -> EmailDomainRule {
+    EmailDomainRule {
+        domain: domain.to_string(),
+        kind,
+        from_config: false,
+    }
+}
+
+#[test]
+fn empty_policy_accepts_everything() {
+    assert!(
+        EmailDomainPolicy::default()
+            .check("ada@example.com")
+            .is_ok()
+    );
+}
+
+#[test]
+fn allow_list_restricts_to_listed_domains_and_subdomains() {
+    let policy = EmailDomainPolicy::new(vec![rule("company.com", DomainRuleKind::ALLOW)]);
+
+    assert!(policy.check("ada@company.com").is_ok());
+    assert!(policy.check("ada@eu.company.com").is_ok());
+    assert!(policy.check("ada@notcompany.com").is_err());
+    assert!(policy.check("ada@gmail.com").is_err());
+}
+
+#[test]
+fn deny_rules_win_over_allow_rules() {
+    let policy = EmailDomainPolicy::new(vec![
+        rule("company.com", DomainRuleKind::ALLOW),
+        rule("contractors.company.com", DomainRuleKind::DENY),
+    ]);
+
+    assert!(policy.check("ada@company.com").is_ok());
+    assert!(policy.check("bob@contractors.company.com").is_err());
+}
+
+#[test]
+fn domains_are_compared_case_insensitively() {
+    let policy = EmailDomainPolicy::new(vec![rule("mailinator.com", DomainRuleKind::DENY)]);
+
+    assert!(policy.check("spam@MailInator.COM").is_err());
+    assert_eq!(
+        email_domain("Ada@Company.COM"),
+        Some("company.com".to_string())
+    );
+}
+
+#[test]
+fn normalizes_admin_input() {
+    assert_eq!(
+        normalize_domain(" @Company.com "),
+        Some("company.com".to_string())
+    );
+    assert_eq!(normalize_domain("localhost"), None);
+    assert_eq!(normalize_domain("bad domain.com"), None);
+}
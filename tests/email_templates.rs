@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use axum_rest::helpers::email_templates::{
+    EmailKind, EmailTemplates, LandingPage, preferred_locale,
+};
+
+fn templates() -> EmailTemplates {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates/email");
+    EmailTemplates::load(&dir, "en").unwrap()
+}
+
+#[test]
+fn every_email_renders_html_and_text() {
+    let templates = templates();
+    for kind in EmailKind::ALL {
+        let email = templates
+            .render(kind, None, &[("name", "Ada"), ("link", "http://x/verify")])
+            .unwrap();
+        assert!(!email.subject.is_empty());
+        assert!(email.html.contains("Hi Ada,"));
+        assert!(email.text.contains("Hi Ada,"));
+        assert!(email.text.contains("http://x/verify"));
+    }
+}
+
+#[test]
+fn regional_locales_fall_back_to_their_language() {
+    let templates = templates();
+    assert_eq!(templates.resolve_locale(Some("es-MX")), "es");
+    assert_eq!(templates.resolve_locale(Some("fr")), "en");
+
+    let email = templates
+        .render(
+            EmailKind::VerifyEmail,
+            Some("es-MX"),
+            &[("name", "Ana"), ("link", "http://x/verify")],
+        )
+        .unwrap();
+    assert_eq!(email.subject, "Verifica tu dirección de correo");
+    assert!(email.html.contains(r#"lang="es""#));
+}
+
+#[test]
+fn user_input_is_escaped_in_html_only() {
+    let email = templates()
+        .render(
+            EmailKind::VerifyEmail,
+            None,
+            &[("name", "<b>Ada</b>"), ("link", "http://x/verify")],
+        )
+        .unwrap();
+    assert!(email.html.contains("&lt;b&gt;Ada&lt;&#x2F;b&gt;"));
+    assert!(email.text.contains("Hi <b>Ada</b>,"));
+}
+
+#[test]
+fn only_locales_with_a_catalog_can_be_chosen() {
+    let templates = templates();
+    assert_eq!(templates.check_locale(" ES ").unwrap(), "es");
+    assert!(templates.check_locale("fr").is_err());
+    assert_eq!(
+        preferred_locale("es-ES;q=0.9, en;q=0.8").as_deref(),
+        Some("es-ES")
+    );
+    assert_eq!(preferred_locale("*"), None);
+}
+
+#[test]
+fn landing_pages_render_in_the_requested_locale() {
+    let templates = templates();
+    let page = templates
+        .render_page(
+            LandingPage::EmailVerified,
+            Some("en"),
+            &[("name", "Ada"), ("link", "/")],
+        )
+        .unwrap();
+    assert!(page.contains("Thanks, Ada!"));
+    // Autoescaping covers the link too; browsers decode `&#x2F;` back to `/`
+    assert!(page.contains(r#"href="&#x2F;""#));
+
+    let page = templates
+        .render_page(
+            LandingPage::VerificationFailed,
+            Some("es"),
+            &[("reason", "invalid_token"), ("link", "/")],
+        )
+        .unwrap();
+    assert!(page.contains(r#"lang="es""#));
+    assert!(!page.contains("invalid_token"));
+}
+
+#[test]
+fn the_revoke_confirmation_posts_its_token() {
+    let page = templates()
+        .render_page(
+            LandingPage::ConfirmRevoke,
+            Some("en"),
+            &[("token", "abc-123_x"), ("link", "/auth/revoke-session")],
+        )
+        .unwrap();
+
+    assert!(page.contains(r#"<form method="post" action="&#x2F;auth&#x2F;revoke-session""#));
+    assert!(page.contains(r#"name="token" value="abc-123_x""#));
+    assert!(page.contains("Sign This Device Out"));
+    assert!(!page.contains("<a href"));
+}
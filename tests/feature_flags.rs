@@ -0,0 +1,37 @@
+use axum::http::StatusCode;
+use axum_rest::helpers::feature_flags::FeatureFlags;
+use axum_rest::model::model::{FeatureFlag, FeatureFlagSet, UpdateFeatureFlagsRequest};
+
+#[test]
+fn features_start_on_and_maintenance_off() {
+    let flags = FeatureFlags::default();
+    for flag in FeatureFlag::ALL {
+        assert_eq!(flags.is_enabled(flag), flag != FeatureFlag::Maintenance);
+        assert_eq!(FeatureFlag::from_name(flag.name()), Some(flag));
+    }
+}
+
+#[test]
+fn disabled_features_answer_503_naming_the_feature() {
+    let flags = FeatureFlags::default();
+    let mut set = FeatureFlagSet::default();
+    set.set(FeatureFlag::Registration, false);
+
+    assert!(flags.replace(set));
+    assert!(!flags.replace(set));
+
+    let err = flags.require(FeatureFlag::Registration).unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(err.detail, "Registration is currently disabled");
+    assert!(flags.require(FeatureFlag::PostCreation).is_ok());
+}
+
+#[test]
+fn updates_only_touch_the_flags_given() {
+    let request: UpdateFeatureFlagsRequest =
+        serde_json::from_str(r#"{"email_sending": false}"#).unwrap();
+    assert_eq!(request.changes(), vec![(FeatureFlag::EmailSending, false)]);
+
+    let empty: UpdateFeatureFlagsRequest = serde_json::from_str("{}").unwrap();
+    assert!(empty.changes().is_empty());
+}
@@ -0,0 +1,403 @@
+#![cfg(feature = "activitypub")]
+
+mod common;
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use axum_rest::config::FederationConfig;
+use axum_rest::db::repositories::{follower_repo::FollowerRepository, hook_repo::HookRepository};
+use axum_rest::helpers::federation::{
+    ActorFetcher, ActorFuture, Endpoints, Federation, InboxActivity, PublicKey, RemoteActor,
+    delivery, enqueue_post_activities, verify_delivery, with_context,
+};
+use axum_rest::helpers::http_signatures::InstanceKey;
+use axum_rest::helpers::publish_hooks::PublishEvent;
+use axum_rest::model::model::{DeliveryStatus, Post, Role, User, Visibility};
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::Url;
+use serde_json::json;
+use uuid::Uuid;
+
+use common::with_test_db;
+
+const REMOTE_KEY: &str = include_str!("fixtures/other_key.pem");
+const BOB: &str = "https://mastodon.example/users/bob";
+
+fn config() -> FederationConfig {
+    FederationConfig {
+        public_url: "https://blog.example.com".to_string(),
+    }
+}
+
+fn author() -> User {
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    User {
+        id: Uuid::new_v4(),
+        public_id: "alice123".to_string(),
+        name: "Alice".to_string(),
+        username: Some("alice".to_string()),
+        email: "alice@example.com".to_string(),
+        password: String::new(),
+        role: Role::USER,
+        email_verified: true,
+        avatar_url: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[test]
+fn domain_is_taken_from_the_public_url() {
+    assert_eq!(config().domain(), "blog.example.com");
+}
+
+#[test]
+fn webfinger_resources_resolve_to_actor_names() {
+    let config = config();
+    let federation = Federation::new(&config);
+
+    assert_eq!(
+        federation.resolve_resource("acct:alice123@blog.example.com"),
+        Some("alice123")
+    );
+    assert_eq!(
+        federation.resolve_resource("https://blog.example.com/users/alice123"),
+        Some("alice123")
+    );
+    assert_eq!(
+        federation.resolve_resource("acct:alice123@elsewhere.org"),
+        None
+    );
+    assert_eq!(federation.resolve_resource("acct:@blog.example.com"), None);
+
+    let webfinger = federation.webfinger("alice123");
+    assert_eq!(webfinger.subject, "acct:alice123@blog.example.com");
+    assert_eq!(
+        webfinger.links[0].href,
+        "https://blog.example.com/users/alice123"
+    );
+}
+
+#[test]
+fn outbox_wraps_posts_in_create_activities() {
+    let config = config();
+    let federation = Federation::new(&config);
+    let author = author();
+    let post = Post {
+        id: Uuid::new_v4(),
+        public_id: "post1".to_string(),
+        slug: Some("hello".to_string()),
+        title: "Hello".to_string(),
+        content: "Some *emphasis*".to_string(),
+        license: None,
+        author_id: author.id,
+        org_id: None,
+        created_at: author.created_at,
+        updated_at: author.created_at,
+        archived_at: None,
+        visibility: Visibility::PUBLIC,
+    };
+
+    let outbox = federation.outbox(&author, &[post], 7);
+    let value = serde_json::to_value(&outbox).unwrap();
+
+    assert_eq!(value["totalItems"], 7);
+    assert_eq!(value["orderedItems"][0]["type"], "Create");
+    assert_eq!(
+        value["orderedItems"][0]["object"]["attributedTo"],
+        "https://blog.example.com/users/alice123"
+    );
+    assert!(
+        value["orderedItems"][0]["object"]["content"]
+            .as_str()
+            .unwrap()
+            .contains("<em>emphasis</em>")
+    );
+}
+
+#[test]
+fn follows_addressed_to_the_actor_are_recognised() {
+    let config = config();
+    let federation = Federation::new(&config);
+    let follow = json!({
+        "type": "Follow",
+        "actor": "https://mastodon.example/users/bob",
+        "object": "https://blog.example.com/users/alice123"
+    });
+
+    assert_eq!(
+        federation.inbox_activity("alice123", &follow),
+        InboxActivity::Follow {
+            follower: "https://mastodon.example/users/bob".to_string()
+        }
+    );
+    assert_eq!(
+        federation.inbox_activity(
+            "alice123",
+            &json!({
+                "type": "Undo",
+                "actor": "https://mastodon.example/users/bob",
+                "object": follow
+            })
+        ),
+        InboxActivity::Unfollow {
+            follower: "https://mastodon.example/users/bob".to_string()
+        }
+    );
+    assert_eq!(
+        federation.inbox_activity("someone-else", &follow),
+        InboxActivity::Ignored
+    );
+    assert_eq!(
+        federation.inbox_activity("alice123", &json!({ "type": "Like" })),
+        InboxActivity::Ignored
+    );
+}
+
+#[test]
+fn actors_publish_the_instance_key() {
+    let config = config();
+    let key = InstanceKey::from_pem(REMOTE_KEY).unwrap();
+    let actor = Federation::new(&config).actor(&author(), key.public_key_pem());
+    let value = serde_json::to_value(&actor).unwrap();
+
+    assert_eq!(
+        value["publicKey"]["id"],
+        "https://blog.example.com/users/alice123#main-key"
+    );
+    assert_eq!(
+        value["publicKey"]["owner"],
+        "https://blog.example.com/users/alice123"
+    );
+    assert_eq!(value["publicKey"]["publicKeyPem"], key.public_key_pem());
+    assert_eq!(value["@context"][1], "https://w3id.org/security/v1");
+}
+
+#[test]
+fn follows_are_accepted_with_a_signed_delivery() {
+    let config = config();
+    let federation = Federation::new(&config);
+    let follow =
+        json!({ "id": "https://mastodon.example/follows/1", "type": "Follow", "actor": BOB });
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+
+    let accept = federation.accept(Uuid::nil(), "alice123", &follow);
+    let queued = delivery(
+        Uuid::nil(),
+        "follow.accepted",
+        None,
+        "https://mastodon.example/users/bob/inbox",
+        &federation.key_id("alice123"),
+        &accept,
+        now,
+    );
+
+    assert_eq!(accept["type"], "Accept");
+    assert_eq!(accept["actor"], "https://blog.example.com/users/alice123");
+    assert_eq!(accept["object"], follow);
+    assert_eq!(queued.method, "POST");
+    assert_eq!(queued.status, DeliveryStatus::PENDING);
+    assert_eq!(
+        queued.signed_by.as_deref(),
+        Some("https://blog.example.com/users/alice123#main-key")
+    );
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(queued.body.as_deref().unwrap()).unwrap(),
+        accept
+    );
+}
+
+#[test]
+fn delivered_activities_carry_their_context() {
+    let config = config();
+    let federation = Federation::new(&config);
+    let author = author();
+    let post = Post {
+        id: Uuid::new_v4(),
+        public_id: "post1".to_string(),
+        slug: None,
+        title: "Hello".to_string(),
+        content: "Edited".to_string(),
+        license: None,
+        author_id: author.id,
+        org_id: None,
+        created_at: author.created_at,
+        updated_at: author.created_at + chrono::Duration::days(1),
+        archived_at: None,
+        visibility: Visibility::PUBLIC,
+    };
+
+    let update = with_context(&federation.update(&author, &post));
+
+    assert_eq!(update["@context"], "https://www.w3.org/ns/activitystreams");
+    assert_eq!(update["type"], "Update");
+    assert_eq!(
+        update["object"]["id"],
+        "https://blog.example.com/posts/post1"
+    );
+    assert_ne!(
+        update["id"],
+        with_context(&federation.create(&author, &post))["id"]
+    );
+}
+
+/// Serves Bob's actor document, as his server would
+struct Bob(RemoteActor);
+
+impl ActorFetcher for Bob {
+    fn fetch<'a>(&'a self, url: &'a str) -> ActorFuture<'a> {
+        Box::pin(async move {
+            anyhow::ensure!(url == self.0.id, "no actor at {}", url);
+            Ok(self.0.clone())
+        })
+    }
+}
+
+fn bob(key: &InstanceKey) -> Bob {
+    Bob(RemoteActor {
+        id: BOB.to_string(),
+        inbox: format!("{}/inbox", BOB),
+        endpoints: Endpoints {
+            shared_inbox: Some("https://mastodon.example/inbox".to_string()),
+        },
+        public_key: PublicKey {
+            id: format!("{}#main-key", BOB),
+            owner: BOB.to_string(),
+            public_key_pem: key.public_key_pem().to_string(),
+        },
+    })
+}
+
+fn inbox_headers(key: &InstanceKey, key_id: &str, body: &[u8], now: DateTime<Utc>) -> HeaderMap {
+    let url = Url::parse("https://blog.example.com/users/alice123/inbox").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("host", HeaderValue::from_static("blog.example.com"));
+    for (name, value) in key.sign(key_id, "POST", &url, body, now).unwrap() {
+        headers.insert(
+            HeaderName::from_static(name),
+            HeaderValue::from_str(&value).unwrap(),
+        );
+    }
+    headers
+}
+
+#[tokio::test]
+async fn inbox_deliveries_must_be_signed_by_the_sender() {
+    let key = InstanceKey::from_pem(REMOTE_KEY).unwrap();
+    let bob = bob(&key);
+    let body = json!({ "type": "Follow", "actor": BOB }).to_string();
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let target = "/users/alice123/inbox";
+
+    let signed = inbox_headers(&key, &format!("{}#main-key", BOB), body.as_bytes(), now);
+    let remote = verify_delivery(&bob, BOB, target, &signed, body.as_bytes(), now)
+        .await
+        .unwrap();
+    assert_eq!(remote.delivery_inbox(), "https://mastodon.example/inbox");
+
+    let unsigned = HeaderMap::new();
+    assert!(
+        verify_delivery(&bob, BOB, target, &unsigned, body.as_bytes(), now)
+            .await
+            .is_err()
+    );
+
+    // Signed by Bob's key but claiming to come from someone else
+    assert!(
+        verify_delivery(
+            &bob,
+            "https://mastodon.example/users/mallory",
+            target,
+            &signed,
+            body.as_bytes(),
+            now
+        )
+        .await
+        .is_err()
+    );
+
+    let wrong_key_id = inbox_headers(&key, "https://elsewhere.example/key", body.as_bytes(), now);
+    assert!(
+        verify_delivery(&bob, BOB, target, &wrong_key_id, body.as_bytes(), now)
+            .await
+            .is_err()
+    );
+}
+
+/// Runs against the database in `TEST_DATABASE_URL` and is skipped without one
+#[tokio::test]
+async fn new_posts_are_queued_once_per_follower_inbox() {
+    with_test_db(|pool| async move {
+        let author = author();
+        sqlx::query("INSERT INTO users (id, public_id, name, email, password) VALUES ($1, $2, 'Alice', 'alice@example.com', 'x')")
+            .bind(author.id)
+            .bind(&author.public_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let followers = FollowerRepository::new(pool.clone());
+        for (actor, inbox) in [
+            (BOB, "https://mastodon.example/inbox"),
+            ("https://mastodon.example/users/carol", "https://mastodon.example/inbox"),
+            ("https://pleroma.example/users/dan", "https://pleroma.example/users/dan/inbox"),
+        ] {
+            followers
+                .add_follower(author.id, actor, inbox, author.created_at)
+                .await
+                .unwrap();
+        }
+        let post = Post {
+            id: Uuid::new_v4(),
+            public_id: "post1".to_string(),
+            slug: None,
+            title: "Hello".to_string(),
+            content: "World".to_string(),
+            license: None,
+            author_id: author.id,
+            org_id: None,
+            created_at: author.created_at,
+            updated_at: author.created_at,
+            archived_at: None,
+            visibility: Visibility::PUBLIC,
+        };
+
+        let queued = enqueue_post_activities(
+            &pool,
+            &config(),
+            PublishEvent::Published,
+            &post,
+            author.created_at,
+        )
+        .await
+        .unwrap();
+        let unlisted = Post {
+            visibility: Visibility::UNLISTED,
+            ..post.clone()
+        };
+        let skipped = enqueue_post_activities(
+            &pool,
+            &config(),
+            PublishEvent::Updated,
+            &unlisted,
+            author.created_at,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!((queued, skipped), (2, 0));
+        let deliveries = HookRepository::new(pool.clone()).list_recent(10).await.unwrap();
+        let mut inboxes: Vec<_> = deliveries.iter().map(|d| d.url.as_str()).collect();
+        inboxes.sort();
+        assert_eq!(
+            inboxes,
+            [
+                "https://mastodon.example/inbox",
+                "https://pleroma.example/users/dan/inbox"
+            ]
+        );
+        assert!(deliveries.iter().all(|d| d.event == "activity.create"
+            && d.post_id.as_deref() == Some("post1")
+            && d.signed_by.as_deref()
+                == Some("https://blog.example.com/users/alice123#main-key")));
+    })
+    .await;
+}
@@ -0,0 +1,83 @@
+use axum_rest::helpers::feeds::{Feed, FeedEntry, FeedFormat};
+use chrono::{TimeZone, Utc};
+
+fn feed(entries: Vec<FeedEntry>) -> Feed {
+    Feed {
+        title: "Axum Blog".to_string(),
+        description: "Posts & notes".to_string(),
+        site_url: "https://blog.example.com/".to_string(),
+        self_url: "https://blog.example.com/feeds/posts.xml".to_string(),
+        entries,
+    }
+}
+
+fn entry(license: Option<&str>) -> FeedEntry {
+    FeedEntry {
+        title: "Tom & Jerry <3".to_string(),
+        url: "https://blog.example.com/p/abc".to_string(),
+        author: "Ada".to_string(),
+        content_html: "<p>Hello\u{0}</p>".to_string(),
+        license: license.map(String::from),
+        published: Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap(),
+        updated: Utc.with_ymd_and_hms(2024, 2, 1, 12, 0, 0).unwrap(),
+    }
+}
+
+#[test]
+fn feed_files_pick_their_format() {
+    assert_eq!(
+        FeedFormat::from_file_name("posts.xml"),
+        Some(("posts", FeedFormat::Rss))
+    );
+    assert_eq!(
+        FeedFormat::from_file_name("V1St-GXR8_Z5j.atom"),
+        Some(("V1St-GXR8_Z5j", FeedFormat::Atom))
+    );
+    assert_eq!(FeedFormat::from_file_name("posts.json"), None);
+    assert!(
+        FeedFormat::Rss
+            .content_type()
+            .starts_with("application/rss+xml")
+    );
+    assert!(
+        FeedFormat::Atom
+            .content_type()
+            .starts_with("application/atom+xml")
+    );
+}
+
+#[test]
+fn rss_items_are_escaped_and_licensed() {
+    let rss = feed(vec![entry(Some("CC-BY-4.0"))]).to_rss();
+
+    assert!(rss.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\""));
+    assert!(rss.contains("<title>Tom &amp; Jerry &lt;3</title>"));
+    assert!(rss.contains("<description>&lt;p&gt;Hello&lt;/p&gt;</description>"));
+    assert!(rss.contains("<guid isPermaLink=\"true\">https://blog.example.com/p/abc</guid>"));
+    assert!(rss.contains("<pubDate>Mon, 1 Jan 2024 12:00:00 +0000</pubDate>"));
+    assert!(rss.contains("<dc:rights>CC-BY-4.0</dc:rights>"));
+    assert!(
+        rss.contains("<atom:link href=\"https://blog.example.com/feeds/posts.xml\" rel=\"self\"")
+    );
+}
+
+#[test]
+fn atom_entries_carry_dates_and_rights() {
+    let atom = feed(vec![entry(Some("CC0-1.0")), entry(None)]).to_atom();
+
+    assert!(atom.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+    assert!(atom.contains("<updated>2024-02-01T12:00:00+00:00</updated>"));
+    assert!(atom.contains("<published>2024-01-01T12:00:00+00:00</published>"));
+    assert!(atom.contains("<content type=\"html\">&lt;p&gt;Hello&lt;/p&gt;</content>"));
+    assert_eq!(atom.matches("<entry>").count(), 2);
+    assert_eq!(atom.matches("<rights>").count(), 1);
+}
+
+#[test]
+fn empty_feeds_still_render() {
+    let feed = feed(Vec::new());
+
+    assert_eq!(feed.updated(), Utc.timestamp_opt(0, 0).unwrap());
+    assert!(feed.render(FeedFormat::Rss).contains("</channel>"));
+    assert!(feed.render(FeedFormat::Atom).ends_with("</feed>\n"));
+}
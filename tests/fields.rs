@@ -0,0 +1,77 @@
+use axum_rest::helpers::fields::{FieldSelection, Sparse};
+use axum_rest::helpers::redaction::{Audience, with_audience};
+use axum_rest::model::model::{Role, UserResponse};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+fn selection(raw: &str) -> FieldSelection {
+    FieldSelection::parse(Some(raw)).unwrap().unwrap()
+}
+
+#[test]
+fn keeps_only_the_selected_fields_of_each_item() {
+    let posts = json!([
+        {"id": "a", "title": "First", "content": "long", "author": {"id": "u", "name": "Ada"}},
+        {"id": "b", "title": "Second", "content": "long", "author": null},
+    ]);
+
+    assert_eq!(
+        selection("id, title,author.name").apply(posts),
+        json!([
+            {"id": "a", "title": "First", "author": {"name": "Ada"}},
+            {"id": "b", "title": "Second", "author": null},
+        ])
+    );
+}
+
+#[test]
+fn a_whole_field_wins_over_its_nested_fields() {
+    let post = json!({"id": "a", "author": {"id": "u", "name": "Ada"}});
+    let expected = json!({"author": {"id": "u", "name": "Ada"}});
+
+    assert_eq!(
+        selection("author.name,author").apply(post.clone()),
+        expected
+    );
+    assert_eq!(selection("author,author.name").apply(post), expected);
+}
+
+#[test]
+fn missing_or_empty_selections_mean_everything() {
+    assert_eq!(FieldSelection::parse(None), Ok(None));
+    assert_eq!(FieldSelection::parse(Some(" , ")), Ok(None));
+    assert!(FieldSelection::parse(Some("author..name")).is_err());
+    assert!(FieldSelection::parse(Some("title;drop")).is_err());
+}
+
+#[tokio::test]
+async fn selections_cannot_reveal_redacted_fields() {
+    let user = UserResponse {
+        internal_id: Uuid::new_v4(),
+        id: "V1StGXR8_Z5j".to_string(),
+        name: "Ada Lovelace".to_string(),
+        username: None,
+        email: "ada@example.com".to_string(),
+        role: Role::USER,
+        email_verified: true,
+        avatar_url: "https://cdn.example.com/ada.png".to_string(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        email_delivery: None,
+    };
+    let body = Sparse::new(vec![user], Some(selection("name,email")));
+
+    let json = with_audience(Audience::anonymous(), async {
+        serde_json::to_value(&body).unwrap()
+    })
+    .await;
+    assert_eq!(json, json!([{"name": "Ada Lovelace"}]));
+
+    let admin = Audience::user(Uuid::new_v4(), Role::ADMIN);
+    let json = with_audience(admin, async { serde_json::to_value(&body).unwrap() }).await;
+    assert_eq!(
+        json,
+        json!([{"name": "Ada Lovelace", "email": "ada@example.com"}])
+    );
+}
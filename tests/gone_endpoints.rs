@@ -0,0 +1,41 @@
+use axum::http::Method;
+use axum_rest::config::GoneEndpointConfig;
+use axum_rest::helpers::deprecation::GoneEndpoints;
+
+#[test]
+fn parses_method_path_and_hint() {
+    let entry = GoneEndpointConfig::parse("DELETE /auth/delete-account=Use DELETE /auth/profile")
+        .expect("valid entry");
+
+    assert_eq!(entry.method, Some(Method::DELETE));
+    assert_eq!(entry.path, "/auth/delete-account");
+    assert_eq!(entry.hint, "Use DELETE /auth/profile");
+}
+
+#[test]
+fn rejects_entries_without_a_path() {
+    assert!(GoneEndpointConfig::parse("no hint here").is_none());
+    assert!(GoneEndpointConfig::parse("GET posts=relative path").is_none());
+}
+
+#[test]
+fn matches_prefixes_and_counts_hits() {
+    let configs = vec![
+        GoneEndpointConfig::parse("/v0/*=Move to the unversioned routes").unwrap(),
+        GoneEndpointConfig::parse("DELETE /auth/delete-account=Use DELETE /auth/profile").unwrap(),
+    ];
+    let gone = GoneEndpoints::new(&configs);
+
+    assert_eq!(
+        gone.check(&Method::GET, "/v0/posts"),
+        Some("Move to the unversioned routes")
+    );
+    assert_eq!(gone.check(&Method::GET, "/auth/delete-account"), None);
+    assert!(
+        gone.check(&Method::DELETE, "/auth/delete-account")
+            .is_some()
+    );
+
+    let hits: Vec<u64> = gone.stats().iter().map(|stats| stats.hits).collect();
+    assert_eq!(hits, vec![1, 1]);
+}
@@ -0,0 +1,111 @@
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use axum_rest::helpers::http_signatures::{
+    InstanceKey, SignatureHeader, http_date, parse_public_key, verify,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use reqwest::Url;
+
+const KEY: &str = include_str!("fixtures/federation_key.pem");
+const OTHER_KEY: &str = include_str!("fixtures/other_key.pem");
+const KEY_ID: &str = "https://blog.example.com/users/alice123#main-key";
+const BODY: &[u8] = br#"{"type":"Follow"}"#;
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap()
+}
+
+/// Headers of a signed POST to the inbox, as the receiving server sees them
+fn signed_headers(key: &InstanceKey, body: &[u8]) -> HeaderMap {
+    let url = Url::parse("https://social.example/users/bob/inbox").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("host", HeaderValue::from_static("social.example"));
+    for (name, value) in key.sign(KEY_ID, "POST", &url, body, now()).unwrap() {
+        headers.insert(
+            HeaderName::from_static(name),
+            HeaderValue::from_str(&value).unwrap(),
+        );
+    }
+    headers
+}
+
+fn check(headers: &HeaderMap, body: &[u8], public_pem: &str) -> anyhow::Result<()> {
+    let signature =
+        SignatureHeader::parse(headers["signature"].to_str().unwrap()).expect("parseable");
+    verify(
+        &signature,
+        &parse_public_key(public_pem)?,
+        "POST",
+        "/users/bob/inbox",
+        headers,
+        body,
+        now(),
+    )
+}
+
+#[test]
+fn signed_requests_verify_against_the_public_key() {
+    let key = InstanceKey::from_pem(KEY).unwrap();
+    let headers = signed_headers(&key, BODY);
+
+    let signature = SignatureHeader::parse(headers["signature"].to_str().unwrap()).unwrap();
+    assert_eq!(signature.key_id, KEY_ID);
+    assert_eq!(
+        signature.headers,
+        ["(request-target)", "host", "date", "digest"]
+    );
+    assert_eq!(headers["date"], "Wed, 01 Jan 2025 12:00:00 GMT");
+    check(&headers, BODY, key.public_key_pem()).unwrap();
+}
+
+#[test]
+fn another_body_or_key_is_rejected() {
+    let key = InstanceKey::from_pem(KEY).unwrap();
+    let other = InstanceKey::from_pem(OTHER_KEY).unwrap();
+    let headers = signed_headers(&key, BODY);
+
+    assert!(check(&headers, br#"{"type":"Undo"}"#, key.public_key_pem()).is_err());
+    assert!(check(&headers, BODY, other.public_key_pem()).is_err());
+}
+
+#[test]
+fn stale_dates_are_rejected() {
+    let key = InstanceKey::from_pem(KEY).unwrap();
+    let mut headers = signed_headers(&key, BODY);
+    headers.insert(
+        "date",
+        HeaderValue::from_str(&http_date(now() - Duration::hours(2))).unwrap(),
+    );
+
+    let err = check(&headers, BODY, key.public_key_pem()).unwrap_err();
+    assert!(err.to_string().contains("date"), "{err}");
+}
+
+#[test]
+fn signatures_must_cover_the_digest() {
+    let key = InstanceKey::from_pem(KEY).unwrap();
+    let mut headers = signed_headers(&key, BODY);
+    let signature = headers["signature"].to_str().unwrap().replace(
+        "(request-target) host date digest",
+        "(request-target) host date",
+    );
+    headers.insert("signature", HeaderValue::from_str(&signature).unwrap());
+
+    let err = check(&headers, BODY, key.public_key_pem()).unwrap_err();
+    assert!(err.to_string().contains("digest"), "{err}");
+}
+
+#[test]
+fn signature_headers_default_to_covering_the_date() {
+    let signature = SignatureHeader::parse(
+        r#"keyId="https://social.example/users/bob#main-key",signature="AAAA""#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        signature.key_id,
+        "https://social.example/users/bob#main-key"
+    );
+    assert_eq!(signature.algorithm, None);
+    assert_eq!(signature.headers, ["date"]);
+    assert_eq!(SignatureHeader::parse(r#"keyId="no-signature""#), None);
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+    middleware,
+    routing::get,
+};
+use axum_rest::helpers::i18n::{Catalog, Messages, locale_middleware};
+use axum_rest::helpers::response::{error_response_generic, success_response};
+use serde_json::Value;
+use tower::ServiceExt;
+
+fn catalog(strings: &[(&str, &str)]) -> Catalog {
+    Catalog::new(
+        strings
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>(),
+    )
+}
+
+fn messages() -> Messages {
+    let mut messages = Messages::default();
+    messages.insert(
+        "es",
+        catalog(&[
+            ("Post Deleted", "Publicación eliminada"),
+            ("Title is too long", "El título es demasiado largo"),
+        ]),
+    );
+    messages.insert("pt-BR", catalog(&[("Post Deleted", "Post excluído")]));
+    messages
+}
+
+#[test]
+fn exact_messages_are_translated() {
+    let catalog = catalog(&[("Post not found", "Publicación no encontrada")]);
+
+    assert_eq!(
+        catalog.translate("Post not found").as_deref(),
+        Some("Publicación no encontrada")
+    );
+    assert_eq!(catalog.translate("User not found"), None);
+}
+
+#[test]
+fn placeholders_carry_the_parts_that_vary() {
+    let catalog = catalog(&[
+        (
+            "{field} must be at most {max} characters",
+            "{field} debe tener como máximo {max} caracteres",
+        ),
+        ("{method} is not supported", "{method} no está permitido"),
+    ]);
+
+    assert_eq!(
+        catalog
+            .translate("Title must be at most 200 characters")
+            .as_deref(),
+        Some("Title debe tener como máximo 200 caracteres")
+    );
+    assert_eq!(
+        catalog.translate("DELETE is not supported").as_deref(),
+        Some("DELETE no está permitido")
+    );
+    assert_eq!(catalog.translate(" must be at most 200 characters"), None);
+}
+
+#[test]
+fn the_most_wanted_language_with_a_catalog_wins() {
+    let messages = messages();
+    let locale = |header: &str| messages.negotiate(header).map(|(locale, _)| locale);
+
+    assert_eq!(locale("es").as_deref(), Some("es"));
+    assert_eq!(locale("es-MX,es;q=0.9").as_deref(), Some("es"));
+    assert_eq!(locale("pt-BR").as_deref(), Some("pt-br"));
+    assert_eq!(locale("fr, es;q=0.5").as_deref(), Some("es"));
+    assert_eq!(locale("en;q=0.4, es;q=0.8").as_deref(), Some("es"));
+    assert_eq!(locale("en-GB, es;q=0.8"), None);
+    assert_eq!(locale("de, fr"), None);
+    assert_eq!(locale("es;q=0"), None);
+}
+
+#[test]
+fn the_bundled_catalogs_load() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("locales");
+    let messages = Messages::load(&dir).unwrap();
+
+    let (_, catalog) = messages.negotiate("es").unwrap();
+    assert_eq!(
+        catalog.translate("Post not found").as_deref(),
+        Some("Publicación no encontrada")
+    );
+}
+
+#[test]
+fn a_missing_directory_leaves_messages_in_english() {
+    let messages = Messages::load(Path::new("/nonexistent/locales")).unwrap();
+
+    assert!(messages.is_empty());
+}
+
+fn router() -> Router {
+    Router::new()
+        .route(
+            "/deleted",
+            get(|| async { success_response("Post Deleted".to_string(), Value::Null) }),
+        )
+        .route(
+            "/invalid",
+            get(|| async {
+                error_response_generic::<Value>(
+                    "Validation Error".to_string(),
+                    "Title is too long".to_string(),
+                )
+            }),
+        )
+        .layer(middleware::from_fn_with_state(
+            Arc::new(messages()),
+            locale_middleware,
+        ))
+}
+
+async fn call(uri: &str, accept_language: &str) -> (StatusCode, Option<String>, Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .header(header::ACCEPT_LANGUAGE, accept_language)
+        .body(Body::empty())
+        .unwrap();
+    let response = router().oneshot(request).await.unwrap();
+    let status = response.status();
+    let language = response
+        .headers()
+        .get(header::CONTENT_LANGUAGE)
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, language, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn success_messages_are_translated() {
+    let (status, language, body) = call("/deleted", "es-ES").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(language.as_deref(), Some("es"));
+    assert_eq!(body["message"], "Publicación eliminada");
+}
+
+#[tokio::test]
+async fn error_details_are_translated_but_titles_are_not() {
+    let (status, _, body) = call("/invalid", "es").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["title"], "Validation Error");
+    assert_eq!(body["detail"], "El título es demasiado largo");
+}
+
+#[tokio::test]
+async fn english_is_the_fallback() {
+    let (_, language, body) = call("/deleted", "de").await;
+
+    assert_eq!(language.as_deref(), Some("en"));
+    assert_eq!(body["message"], "Post Deleted");
+}
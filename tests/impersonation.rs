@@ -0,0 +1,62 @@
+use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    http::StatusCode,
+    middleware::{self, Next},
+    response::Response,
+    routing::put,
+};
+use axum_rest::helpers::middleware::forbid_impersonation;
+use axum_rest::model::model::Impersonator;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Stands in for `auth_middleware`, which marks requests made with an
+/// impersonation token
+async fn impersonating(mut request: Request, next: Next) -> Response {
+    if request.headers().contains_key("x-test-impersonator") {
+        request
+            .extensions_mut()
+            .insert(Impersonator(Uuid::new_v4()));
+    }
+    next.run(request).await
+}
+
+fn app() -> Router {
+    Router::new()
+        .route(
+            "/auth/change-password",
+            put(|| async { "changed" }).route_layer(middleware::from_fn(forbid_impersonation)),
+        )
+        .layer(middleware::from_fn(impersonating))
+}
+
+fn change_password(impersonated: bool) -> Request {
+    let request = Request::put("/auth/change-password");
+    let request = if impersonated {
+        request.header("x-test-impersonator", "1")
+    } else {
+        request
+    };
+    request.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn impersonation_tokens_are_refused() {
+    let response = app().oneshot(change_password(true)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["detail"], "Not allowed while impersonating a user");
+}
+
+#[tokio::test]
+async fn the_account_owner_is_let_through() {
+    let response = app().oneshot(change_password(false)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,83 @@
+use axum_rest::model::model::{
+    PostInclude, PostIncluded, PostMedia, PostResponse, PostWithIncluded, PublicAuthor,
+    RelatedPost, Visibility,
+};
+use chrono::Utc;
+
+fn post(id: &str) -> PostResponse {
+    PostResponse {
+        id: id.to_string(),
+        slug: Some("hello".to_string()),
+        title: "Hello".to_string(),
+        content: "First post. With more.".to_string(),
+        content_html: None,
+        license: None,
+        word_count: 4,
+        reading_time_minutes: 1,
+        excerpt: "First post. With more.".to_string(),
+        author: PublicAuthor {
+            id: "V1StGXR8_Z5j".to_string(),
+            name: "Ada Lovelace".to_string(),
+            username: None,
+            avatar_url: "https://cdn.example.com/ada.png".to_string(),
+        },
+        view_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        archived_at: None,
+        visibility: Visibility::PUBLIC,
+        mentions: Vec::new(),
+        collaborators: Vec::new(),
+    }
+}
+
+#[test]
+fn parses_permitted_includes_once_each() {
+    assert_eq!(PostInclude::parse_list(None), Ok(vec![]));
+    assert_eq!(
+        PostInclude::parse_list(Some("media, author_posts,media")),
+        Ok(vec![PostInclude::Media, PostInclude::AuthorPosts])
+    );
+}
+
+#[test]
+fn unknown_includes_name_the_permitted_ones() {
+    let message = PostInclude::parse_list(Some("media,comments")).unwrap_err();
+
+    assert!(message.contains("`comments`"), "{}", message);
+    assert!(message.contains("media, author_posts"), "{}", message);
+}
+
+#[test]
+fn included_data_sits_beside_the_post_fields() {
+    let plain = PostWithIncluded {
+        post: post("IRFa-VaY2b3x"),
+        included: None,
+    };
+    let json = serde_json::to_value(&plain).unwrap();
+    assert_eq!(json["id"], "IRFa-VaY2b3x");
+    assert!(json.get("included").is_none());
+
+    let with_media = PostWithIncluded {
+        post: post("IRFa-VaY2b3x"),
+        included: Some(PostIncluded {
+            media: Some(vec![PostMedia {
+                url: "fox.png".to_string(),
+                alt_text: None,
+            }]),
+            author_posts: None,
+        }),
+    };
+    let json = serde_json::to_value(&with_media).unwrap();
+    assert_eq!(json["title"], "Hello");
+    assert_eq!(json["included"]["media"][0]["url"], "fox.png");
+    assert!(json["included"].get("author_posts").is_none());
+}
+
+#[test]
+fn related_posts_carry_no_content() {
+    let json = serde_json::to_value(RelatedPost::from(post("other"))).unwrap();
+
+    assert_eq!(json["excerpt"], "First post. With more.");
+    assert!(json.get("content").is_none());
+}
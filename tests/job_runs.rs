@@ -0,0 +1,61 @@
+use chrono::{Duration, TimeZone, Utc};
+
+use axum_rest::config::{AppConfig, UnverifiedCleanup};
+use axum_rest::jobs::runs::JobRuns;
+use axum_rest::jobs::{ACCOUNT_PURGE, UNVERIFIED_CLEANUP, scheduled_jobs};
+
+#[test]
+fn the_latest_run_of_each_job_is_kept() {
+    let runs = JobRuns::new();
+    let started_at = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    assert!(runs.last(ACCOUNT_PURGE).is_none());
+
+    runs.record(
+        ACCOUNT_PURGE,
+        started_at,
+        started_at + Duration::seconds(1),
+        Err("database unreachable".to_string()),
+    );
+    let failed = runs.last(ACCOUNT_PURGE).unwrap();
+    assert!(!failed.succeeded);
+    assert_eq!(failed.message.as_deref(), Some("database unreachable"));
+
+    let later = started_at + Duration::hours(1);
+    runs.record(ACCOUNT_PURGE, later, later, Ok(None));
+    let succeeded = runs.last(ACCOUNT_PURGE).unwrap();
+    assert!(succeeded.succeeded);
+    assert_eq!(succeeded.started_at, later);
+    assert!(succeeded.message.is_none());
+
+    assert!(runs.last(UNVERIFIED_CLEANUP).is_none());
+}
+
+#[test]
+fn unverified_cleanup_modes() {
+    assert_eq!(
+        UnverifiedCleanup::parse("delete"),
+        Some(UnverifiedCleanup::Delete)
+    );
+    assert_eq!(
+        UnverifiedCleanup::parse("Anonymize"),
+        Some(UnverifiedCleanup::Anonymize)
+    );
+    assert_eq!(UnverifiedCleanup::parse("off"), None);
+    assert_eq!(UnverifiedCleanup::parse(""), None);
+}
+
+#[test]
+fn unverified_cleanup_is_enabled_only_when_configured() {
+    let mut config = AppConfig::from_env();
+    config.accounts.unverified_cleanup = None;
+    let job = |config: &AppConfig| {
+        scheduled_jobs(config)
+            .into_iter()
+            .find(|job| job.name == UNVERIFIED_CLEANUP)
+            .unwrap()
+    };
+    assert!(!job(&config).enabled);
+
+    config.accounts.unverified_cleanup = Some(UnverifiedCleanup::Anonymize);
+    assert!(job(&config).enabled);
+}
@@ -0,0 +1,98 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+    routing::post,
+};
+use axum_rest::helpers::json::{Json, parse_json_body};
+use serde::Deserialize;
+use serde_json::Value;
+use tower::ServiceExt;
+
+#[derive(Debug, Deserialize)]
+struct CreatePost {
+    title: String,
+    #[serde(default)]
+    tags: Vec<Tag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tag {
+    name: String,
+}
+
+#[test]
+fn well_formed_bodies_parse() {
+    let post: CreatePost =
+        parse_json_body(br#"{"title": "Hello", "tags": [{"name": "rust"}]}"#).unwrap();
+
+    assert_eq!(post.title, "Hello");
+    assert_eq!(post.tags[0].name, "rust");
+}
+
+#[test]
+fn mistyped_members_get_a_422_pointing_at_them() {
+    let err = parse_json_body::<CreatePost>(br#"{"title": 5}"#).unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(err.pointer.as_deref(), Some("/title"));
+
+    let err = parse_json_body::<CreatePost>(br#"{"title": "Hello", "tags": [{"name": "a"}, {}]}"#)
+        .unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(err.pointer.as_deref(), Some("/tags/1"));
+    assert!(
+        err.detail.contains("missing field `name`"),
+        "{}",
+        err.detail
+    );
+}
+
+#[test]
+fn malformed_json_is_a_400() {
+    for body in [&b"{"[..], b"", b"{\"title\": \"Hello\"} trailing"] {
+        let err = parse_json_body::<CreatePost>(body).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(err.title, "Malformed JSON");
+        assert_eq!(err.pointer, None);
+    }
+}
+
+async fn call(content_type: &str, body: &'static str) -> (StatusCode, Value) {
+    let router = Router::new().route(
+        "/posts",
+        post(|Json(post): Json<CreatePost>| async move { post.title }),
+    );
+    let request = Request::builder()
+        .method("POST")
+        .uri("/posts")
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from(body))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn rejections_use_the_error_body() {
+    let (status, body) = call("application/json", r#"{"title": 5}"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["status"], 422);
+    assert_eq!(body["pointer"], "/title");
+
+    let (status, body) = call("text/plain", r#"{"title": "Hello"}"#).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(body["status"], 415);
+}
+
+#[tokio::test]
+async fn json_based_media_types_are_accepted() {
+    let (status, _) = call(
+        "application/activity+json; charset=utf-8",
+        r#"{"title": "Hello"}"#,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,24 @@
+use axum_rest::helpers::licenses::{MAX_LICENSE_LENGTH, normalize_license, spdx_identifier};
+
+#[test]
+fn known_identifiers_are_canonicalized() {
+    assert_eq!(normalize_license("cc-by-sa-4.0").unwrap(), "CC-BY-SA-4.0");
+    assert_eq!(normalize_license("  mit ").unwrap(), "MIT");
+    assert_eq!(spdx_identifier("apache-2.0"), Some("Apache-2.0"));
+}
+
+#[test]
+fn custom_text_is_kept_as_written() {
+    assert_eq!(
+        normalize_license(" All rights reserved ").unwrap(),
+        "All rights reserved"
+    );
+    assert_eq!(spdx_identifier("All rights reserved"), None);
+}
+
+#[test]
+fn empty_long_and_multiline_licenses_are_rejected() {
+    assert!(normalize_license("   ").is_err());
+    assert!(normalize_license(&"x".repeat(MAX_LICENSE_LENGTH + 1)).is_err());
+    assert!(normalize_license("CC-BY-4.0\nor not").is_err());
+}
@@ -0,0 +1,60 @@
+use axum_rest::docs::ApiDoc;
+use axum_rest::helpers::validation::{check_length, validate_post_fields};
+use axum_rest::model::model::Limits;
+
+fn limits() -> Limits {
+    Limits {
+        max_title_length: 5,
+        max_content_length: 10,
+        max_name_length: 3,
+        max_report_details_length: 4,
+    }
+}
+
+#[test]
+fn lengths_count_characters_not_bytes() {
+    assert!(check_length("Name", "日本語", 3).is_ok());
+    assert_eq!(
+        check_length("Name", "Ada L", 3).unwrap_err(),
+        "Name must be at most 3 characters"
+    );
+}
+
+#[test]
+fn post_fields_are_checked_when_present() {
+    let limits = limits();
+
+    assert!(validate_post_fields(Some("Hello"), Some("Short post"), &limits).is_ok());
+    assert!(validate_post_fields(None, None, &limits).is_ok());
+    assert_eq!(
+        validate_post_fields(Some("Hello!"), None, &limits).unwrap_err(),
+        "Title must be at most 5 characters"
+    );
+    assert_eq!(
+        validate_post_fields(None, Some("A longer post"), &limits).unwrap_err(),
+        "Content must be at most 10 characters"
+    );
+}
+
+#[test]
+fn openapi_schemas_carry_the_limits() {
+    let spec = serde_json::to_value(ApiDoc::with_limits(&limits())).unwrap();
+    let schemas = &spec["components"]["schemas"];
+
+    assert_eq!(
+        schemas["CreatePostRequest"]["properties"]["title"]["maxLength"],
+        5
+    );
+    assert_eq!(
+        schemas["UpdatePostRequest"]["properties"]["content"]["maxLength"],
+        10
+    );
+    assert_eq!(
+        schemas["CreateUserRequest"]["properties"]["name"]["maxLength"],
+        3
+    );
+    assert_eq!(
+        schemas["CreateReportRequest"]["properties"]["details"]["maxLength"],
+        4
+    );
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+mod common;
+
+use axum_rest::db::repositories::link_token_repo::LinkTokenRepository;
+use axum_rest::helpers::link_tokens::{
+    LinkGrant, LinkPurpose, LinkTokenStore, MemoryLinkTokens, hash_link_token, new_link_token,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+use common::with_test_db;
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap()
+}
+
+#[test]
+fn tokens_are_random_and_url_safe() {
+    let tokens: HashSet<String> = (0..100).map(|_| new_link_token()).collect();
+
+    assert_eq!(tokens.len(), 100);
+    for token in &tokens {
+        assert_eq!(token.len(), 43);
+        assert!(
+            token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "{}",
+            token
+        );
+    }
+}
+
+#[test]
+fn only_the_hash_is_kept() {
+    let token = new_link_token();
+    let hash = hash_link_token(&token);
+
+    assert_eq!(hash.len(), 64);
+    assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_ne!(hash, token);
+    assert_eq!(hash, hash_link_token(&token));
+}
+
+#[tokio::test]
+async fn a_token_can_be_redeemed_once() {
+    let store = MemoryLinkTokens::new();
+    let user_id = Uuid::new_v4();
+    let token = store
+        .issue(
+            user_id,
+            LinkPurpose::VerifyEmail,
+            None,
+            now(),
+            now() + Duration::minutes(15),
+        )
+        .await
+        .unwrap();
+
+    let redeemed = store
+        .redeem(&token, LinkPurpose::VerifyEmail, now())
+        .await
+        .unwrap();
+    assert_eq!(redeemed.map(|grant| grant.user_id), Some(user_id));
+
+    let again = store
+        .redeem(&token, LinkPurpose::VerifyEmail, now())
+        .await
+        .unwrap();
+    assert_eq!(again, None);
+}
+
+#[tokio::test]
+async fn expired_and_unknown_tokens_are_refused() {
+    let store = MemoryLinkTokens::new();
+    let token = store
+        .issue(
+            Uuid::new_v4(),
+            LinkPurpose::VerifyEmail,
+            None,
+            now(),
+            now() + Duration::minutes(15),
+        )
+        .await
+        .unwrap();
+
+    let later = now() + Duration::minutes(16);
+    assert_eq!(
+        store
+            .redeem(&token, LinkPurpose::VerifyEmail, later)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        store
+            .redeem(&new_link_token(), LinkPurpose::VerifyEmail, now())
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+/// Runs against the database in `TEST_DATABASE_URL` and is skipped without one
+#[tokio::test]
+async fn stored_tokens_keep_their_subject_and_purpose() {
+    with_test_db(|pool| async move {
+        let user_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, public_id, name, email, password) VALUES ($1, $2, 'Ada', 'ada@example.com', 'x')",
+        )
+        .bind(user_id)
+        .bind(user_id.simple().to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+        let store = LinkTokenRepository::new(pool.clone());
+        let token = store
+            .issue(
+                user_id,
+                LinkPurpose::RevokeSession,
+                Some("abc"),
+                now(),
+                now() + Duration::days(7),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .redeem(&token, LinkPurpose::VerifyEmail, now())
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            store
+                .redeem(&token, LinkPurpose::RevokeSession, now())
+                .await
+                .unwrap(),
+            Some(LinkGrant {
+                user_id,
+                subject: Some("abc".to_string()),
+            })
+        );
+        assert_eq!(
+            store
+                .redeem(&token, LinkPurpose::RevokeSession, now())
+                .await
+                .unwrap(),
+            None
+        );
+    })
+    .await;
+}
@@ -0,0 +1,12 @@
+use axum_rest::db::loader::Loader;
+use sqlx::PgPool;
+
+#[tokio::test]
+async fn an_empty_page_loads_without_a_query() {
+    // Nothing listens here, so any query would fail
+    let pool = PgPool::connect_lazy("postgres://loader@127.0.0.1:1/none").unwrap();
+
+    let authors = Loader::new(pool).authors([]).await.unwrap();
+
+    assert!(authors.is_empty());
+}
@@ -0,0 +1,32 @@
+use axum_rest::helpers::email_templates::RenderedEmail;
+use axum_rest::helpers::mailer::{Mailer, MemoryMailer};
+
+fn email(subject: &str) -> RenderedEmail {
+    RenderedEmail {
+        locale: "en".to_string(),
+        subject: subject.to_string(),
+        html: "<p>Hi</p>".to_string(),
+        text: "Hi".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn memory_mailer_keeps_every_email() {
+    let mailer = MemoryMailer::new();
+    mailer
+        .send("ada@example.com", &email("First"))
+        .await
+        .unwrap();
+    mailer
+        .send("bob@example.com", &email("Other"))
+        .await
+        .unwrap();
+    mailer
+        .send("ada@example.com", &email("Second"))
+        .await
+        .unwrap();
+
+    assert_eq!(mailer.sent().len(), 3);
+    assert_eq!(mailer.last_to("ada@example.com").unwrap().subject, "Second");
+    assert!(mailer.last_to("eve@example.com").is_none());
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header},
+    middleware,
+    routing::{get, post},
+};
+use axum_rest::config::{MaintenanceConfig, TokenConfig};
+use axum_rest::db::repositories::session_repo::SessionRepository;
+use axum_rest::helpers::auth::AuthHelper;
+use axum_rest::helpers::clock::MockClock;
+use axum_rest::helpers::denylist::TokenDenylist;
+use axum_rest::helpers::feature_flags::FeatureFlags;
+use axum_rest::helpers::maintenance::{MaintenanceGate, maintenance_middleware};
+use axum_rest::helpers::middleware::SessionGuard;
+use axum_rest::model::model::{FeatureFlagSet, Role, Session};
+use chrono::Duration as ChronoDuration;
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use common::with_test_db;
+
+/// Nothing listens on port 1; only admin tokens are looked up
+fn unreachable_pool() -> PgPool {
+    PgPoolOptions::new()
+        .connect_lazy("postgres://app@127.0.0.1:1/none")
+        .unwrap()
+}
+
+fn gate(pool: PgPool, maintenance: bool, forced: bool) -> MaintenanceGate {
+    MaintenanceGate {
+        flags: Arc::new(FeatureFlags::new(FeatureFlagSet {
+            maintenance,
+            ..FeatureFlagSet::default()
+        })),
+        guard: SessionGuard {
+            pool: Arc::new(pool),
+            denylist: Arc::new(TokenDenylist::disabled()),
+            clock: Arc::new(MockClock::default()),
+        },
+        config: MaintenanceConfig {
+            forced,
+            retry_after: Duration::from_secs(120),
+        },
+    }
+}
+
+fn token(gate: &MaintenanceGate, user_id: Uuid, role: Role, session_id: Option<&str>) -> String {
+    let clock = gate.guard.clock.as_ref();
+    let tokens = TokenConfig::default();
+    let (token, _) = match session_id {
+        Some(session_id) => {
+            AuthHelper::generate_session_tokens(user_id, role, session_id, &tokens, clock)
+        }
+        None => AuthHelper::generate_token(user_id, role, &tokens, clock),
+    }
+    .unwrap();
+    token
+}
+
+async fn status(
+    gate: &MaintenanceGate,
+    method: &str,
+    uri: &str,
+    token: Option<&str>,
+) -> StatusCode {
+    let app = Router::new()
+        .route(
+            "/posts",
+            get(|| async { "list" }).post(|| async { "created" }),
+        )
+        .route("/auth/login", post(|| async { "logged in" }))
+        .layer(middleware::from_fn_with_state(
+            gate.clone(),
+            maintenance_middleware,
+        ));
+
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+
+    let response = app
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    if response.status() == StatusCode::SERVICE_UNAVAILABLE {
+        assert_eq!(response.headers()[header::RETRY_AFTER], "120");
+    }
+    response.status()
+}
+
+#[tokio::test]
+async fn writes_pause_while_reads_keep_working() {
+    let gate = gate(unreachable_pool(), true, false);
+    let user = token(&gate, Uuid::new_v4(), Role::USER, None);
+
+    assert_eq!(status(&gate, "GET", "/posts", None).await, StatusCode::OK);
+    assert_eq!(
+        status(&gate, "POST", "/posts", Some(&user)).await,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(
+        status(&gate, "POST", "/auth/login", None).await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn the_environment_can_force_maintenance() {
+    let open = gate(unreachable_pool(), false, false);
+    assert_eq!(status(&open, "POST", "/posts", None).await, StatusCode::OK);
+
+    let forced = gate(unreachable_pool(), false, true);
+    assert!(forced.status().forced);
+    assert_eq!(
+        status(&forced, "POST", "/posts", None).await,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
+
+/// Runs against the database in `TEST_DATABASE_URL` and is skipped without one
+#[tokio::test]
+async fn admins_write_only_while_their_session_and_standing_hold() {
+    with_test_db(|pool| async move {
+        let gate = gate(pool.clone(), true, false);
+        let now = gate.guard.clock.now();
+        let admin_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, public_id, name, email, password, role) VALUES ($1, $2, 'Admin', 'admin@example.com', 'x', 'ADMIN')",
+        )
+        .bind(admin_id)
+        .bind(admin_id.simple().to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+        let sessions = SessionRepository::new(pool.clone());
+        sessions
+            .create_session(&Session {
+                id: "admin-session".to_string(),
+                user_id: admin_id,
+                device: "Firefox on Linux".to_string(),
+                ip: None,
+                user_agent: None,
+                network: None,
+                country: None,
+                created_at: now,
+                last_seen_at: now,
+                expires_at: now + ChronoDuration::days(7),
+            })
+            .await
+            .unwrap();
+
+        let live = token(&gate, admin_id, Role::ADMIN, Some("admin-session"));
+        let sessionless = token(&gate, admin_id, Role::ADMIN, None);
+        assert_eq!(
+            status(&gate, "POST", "/posts", Some(&live)).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(&gate, "POST", "/posts", Some(&sessionless)).await,
+            StatusCode::OK
+        );
+
+        sessions
+            .revoke("admin-session", admin_id, now)
+            .await
+            .unwrap();
+        assert_eq!(
+            status(&gate, "POST", "/posts", Some(&live)).await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        sqlx::query("UPDATE users SET status = 'BANNED' WHERE id = $1")
+            .bind(admin_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            status(&gate, "POST", "/posts", Some(&sessionless)).await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    })
+    .await;
+}
@@ -0,0 +1,106 @@
+use axum_rest::helpers::markdown::{
+    EXCERPT_MAX_CHARS, MarkdownImage, cached_or_render, extract_images, extract_mentions,
+    render_markdown, stored_or_summarize, summarize,
+};
+
+#[test]
+fn renders_common_markdown() {
+    let html = render_markdown("# Title\n\nSome **bold** and ~~struck~~ text");
+
+    assert!(html.contains("<h1>Title</h1>"));
+    assert!(html.contains("<strong>bold</strong>"));
+    assert!(html.contains("<del>struck</del>"));
+}
+
+#[test]
+fn strips_scripts_and_javascript_links() {
+    let html = render_markdown(
+        "<script>alert(1)</script>\n\n[click](javascript:alert(1))\n\n<img src=x onerror=alert(1)>",
+    );
+
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("javascript:"));
+    assert!(!html.contains("onerror"));
+}
+
+#[test]
+fn prefers_the_cached_rendering() {
+    assert_eq!(
+        cached_or_render(Some("<p>cached</p>".to_string()), "fresh"),
+        "<p>cached</p>"
+    );
+    assert_eq!(cached_or_render(None, "fresh"), "<p>fresh</p>\n");
+}
+
+#[test]
+fn extracts_images_with_their_alt_text() {
+    let images =
+        extract_images("![A *red* fox](fox.png)\n\nText ![](blank.png) and ![   ](spaces.png)");
+
+    assert_eq!(
+        images,
+        vec![
+            MarkdownImage {
+                url: "fox.png".to_string(),
+                alt: Some("A red fox".to_string()),
+            },
+            MarkdownImage {
+                url: "blank.png".to_string(),
+                alt: None,
+            },
+            MarkdownImage {
+                url: "spaces.png".to_string(),
+                alt: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn rendered_images_keep_their_alt_text() {
+    let html = render_markdown("![A red fox](https://example.com/fox.png)");
+
+    assert!(html.contains(r#"alt="A red fox""#));
+}
+
+#[test]
+fn summaries_count_words_and_keep_the_opening_sentences() {
+    let summary = summarize(
+        "# Intro\n\nFirst <b>bold</b> point. Second *one*!\n\nThird sentence is dropped.",
+    );
+
+    assert_eq!(summary.excerpt, "Intro First bold point. Second one!");
+    assert_eq!(summary.word_count, 10);
+    assert_eq!(summary.reading_time_minutes, 1);
+}
+
+#[test]
+fn reading_time_rounds_up_and_excerpts_stay_short() {
+    let summary = summarize(&"word ".repeat(401));
+    assert_eq!(summary.word_count, 401);
+    assert_eq!(summary.reading_time_minutes, 3);
+    assert!(summary.excerpt.chars().count() <= EXCERPT_MAX_CHARS);
+    assert!(summary.excerpt.ends_with("word…"));
+
+    assert_eq!(summarize("").reading_time_minutes, 0);
+}
+
+#[test]
+fn prefers_the_stored_summary() {
+    let stored = stored_or_summarize(Some(7), Some(1), Some("stored".to_string()), "fresh");
+    assert_eq!(stored.excerpt, "stored");
+
+    let fresh = stored_or_summarize(None, None, None, "fresh text");
+    assert_eq!(fresh, summarize("fresh text"));
+}
+
+#[test]
+fn finds_each_mention_once_outside_code() {
+    let source = "Thanks @Ada_Lovelace and (@bob)! Mail ada@example.com, cc @ada_lovelace.\n\n\
+                  `@inline` and\n\n```\n@fenced\n```\n\n@x is too short";
+
+    assert_eq!(
+        extract_mentions(source),
+        vec!["ada_lovelace".to_string(), "bob".to_string()]
+    );
+}
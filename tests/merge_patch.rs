@@ -0,0 +1,120 @@
+use axum::{
+    body::Body,
+    extract::FromRequest,
+    http::{Request, StatusCode, header},
+};
+use axum_rest::helpers::merge_patch::{MERGE_PATCH_JSON, MergePatch, Patch, parse_merge_patch};
+use axum_rest::model::model::{
+    PatchPostRequest, PatchUserRequest, UpdatePostRequest, UpdateUserRequest, Visibility,
+};
+
+#[test]
+fn absent_and_null_members_are_told_apart() {
+    let patch: PatchPostRequest =
+        parse_merge_patch(br#"{"title": "New title", "license": null}"#).unwrap();
+
+    assert_eq!(patch.title, Patch::Value("New title".to_string()));
+    assert_eq!(patch.content, Patch::Absent);
+    assert_eq!(patch.license, Patch::Null);
+}
+
+#[test]
+fn patches_apply_to_nullable_values() {
+    let current = Some("MIT".to_string());
+
+    assert_eq!(Patch::Absent.apply(current.clone()), current);
+    assert_eq!(Patch::Null.apply(current.clone()), None);
+    assert_eq!(
+        Patch::Value("CC0-1.0".to_string()).apply(current),
+        Some("CC0-1.0".to_string())
+    );
+}
+
+#[test]
+fn patches_serialize_as_their_value_or_null() {
+    assert_eq!(
+        serde_json::to_value(Patch::Value("MIT".to_string())).unwrap(),
+        serde_json::json!("MIT")
+    );
+    assert!(
+        serde_json::to_value(Patch::<String>::Null)
+            .unwrap()
+            .is_null()
+    );
+    assert!(
+        serde_json::to_value(Patch::<String>::Absent)
+            .unwrap()
+            .is_null()
+    );
+}
+
+#[test]
+fn only_objects_are_patches() {
+    for body in [&b"[]"[..], b"null", b"\"title\"", b"{"] {
+        let err = parse_merge_patch::<PatchPostRequest>(body).unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    let err = parse_merge_patch::<PatchPostRequest>(br#"{"title": 5}"#).unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn put_bodies_become_patches_of_present_fields() {
+    let patch = PatchPostRequest::from(UpdatePostRequest {
+        title: Some("Title".to_string()),
+        content: None,
+        license: None,
+        visibility: Some(Visibility::UNLISTED),
+    });
+
+    assert_eq!(patch.title, Patch::Value("Title".to_string()));
+    assert_eq!(patch.content, Patch::Absent);
+    assert_eq!(patch.license, Patch::Absent);
+    assert_eq!(patch.visibility, Patch::Value(Visibility::UNLISTED));
+}
+
+#[test]
+fn profile_patches_clear_optional_fields_only() {
+    let patch: PatchUserRequest =
+        parse_merge_patch(br#"{"avatar_url": null, "locale": null}"#).unwrap();
+    let update = UpdateUserRequest::try_from(patch).unwrap();
+    assert_eq!(update.name, None);
+    assert_eq!(update.avatar_url.as_deref(), Some(""));
+    assert_eq!(update.locale.as_deref(), Some(""));
+
+    let patch: PatchUserRequest = parse_merge_patch(br#"{"email": null}"#).unwrap();
+    assert_eq!(
+        UpdateUserRequest::try_from(patch).unwrap_err(),
+        "email cannot be null"
+    );
+}
+
+async fn extract(content_type: &str, body: &'static str) -> Result<PatchPostRequest, StatusCode> {
+    let request = Request::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from(body))
+        .unwrap();
+    MergePatch::<PatchPostRequest>::from_request(request, &())
+        .await
+        .map(|MergePatch(patch)| patch)
+        .map_err(|err| err.status_code())
+}
+
+#[tokio::test]
+async fn patch_bodies_must_be_json() {
+    let patch = extract(MERGE_PATCH_JSON, r#"{"content": "Body"}"#)
+        .await
+        .unwrap();
+    assert_eq!(patch.content, Patch::Value("Body".to_string()));
+
+    assert!(
+        extract("application/json; charset=utf-8", "{}")
+            .await
+            .is_ok()
+    );
+    assert_eq!(
+        extract("text/plain", "{}").await.unwrap_err(),
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+}
@@ -0,0 +1,61 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Method, Request, StatusCode, header},
+    routing::get,
+};
+use axum_rest::helpers::response::method_not_allowed;
+use tower::ServiceExt;
+
+fn router() -> Router {
+    Router::new()
+        .route(
+            "/posts",
+            get(|| async { "listing" }).post(|| async { "created" }),
+        )
+        .method_not_allowed_fallback(method_not_allowed)
+}
+
+async fn call(method: Method) -> (StatusCode, Option<String>, String) {
+    let request = Request::builder()
+        .method(method)
+        .uri("/posts")
+        .body(Body::empty())
+        .unwrap();
+    let response = router().oneshot(request).await.unwrap();
+    let allow = response
+        .headers()
+        .get(header::ALLOW)
+        .map(|allow| allow.to_str().unwrap().to_string());
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, allow, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn head_is_answered_like_get_without_a_body() {
+    let (status, _, body) = call(Method::HEAD).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn unsupported_methods_get_405_with_allow() {
+    let (status, allow, body) = call(Method::DELETE).await;
+
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(allow.as_deref(), Some("GET,HEAD,POST"));
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["status"], 405);
+    assert_eq!(body["detail"], "DELETE is not supported on this endpoint");
+}
+
+#[tokio::test]
+async fn options_lists_the_allowed_methods() {
+    let (status, allow, body) = call(Method::OPTIONS).await;
+
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(allow.as_deref(), Some("GET,HEAD,POST"));
+    assert!(body.is_empty());
+}
@@ -0,0 +1,119 @@
+use axum::{
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::IntoResponse,
+};
+use axum_rest::helpers::negotiation::{
+    Negotiated, ResponseFormat, is_api_path, negotiate, not_acceptable, prefers_html,
+};
+use axum_rest::helpers::response::created_response;
+use serde_json::json;
+
+#[test]
+fn json_is_served_without_a_preference() {
+    assert_eq!(negotiate(None), Some(ResponseFormat::Json));
+    assert_eq!(negotiate(Some(" ")), Some(ResponseFormat::Json));
+    assert_eq!(negotiate(Some("*/*")), Some(ResponseFormat::Json));
+    assert_eq!(negotiate(Some("application/*")), Some(ResponseFormat::Json));
+    assert_eq!(
+        negotiate(Some("text/html, application/json;q=0.5")),
+        Some(ResponseFormat::Json)
+    );
+}
+
+#[test]
+fn unsupported_types_are_not_acceptable() {
+    assert_eq!(negotiate(Some("text/csv")), None);
+    assert_eq!(negotiate(Some("*/*;q=0")), None);
+    assert_eq!(negotiate(Some("application/json;q=0, text/*")), None);
+
+    let error = not_acceptable();
+    assert_eq!(error.status_code(), StatusCode::NOT_ACCEPTABLE);
+    assert!(error.detail.contains("application/json"));
+}
+
+#[test]
+fn negotiated_bodies_default_to_json_outside_a_request() {
+    let response = Negotiated(json!({ "message": "ok" })).into_response();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    assert_eq!(response.headers()[header::VARY], "accept");
+}
+
+#[test]
+fn created_responses_point_at_the_new_resource() {
+    let response = created_response(
+        "/api/posts/IRFa-VaY2b3x".to_string(),
+        "Post Created".to_string(),
+        json!({ "id": "IRFa-VaY2b3x" }),
+    )
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        "/api/posts/IRFa-VaY2b3x"
+    );
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+}
+
+#[test]
+fn api_paths_are_told_apart_from_pages() {
+    assert!(is_api_path("/posts"));
+    assert!(is_api_path("/posts/abc/nope"));
+    assert!(is_api_path("/auth"));
+    assert!(!is_api_path("/"));
+    assert!(!is_api_path("/postscript"));
+    assert!(!is_api_path("/about"));
+}
+
+#[test]
+fn browsers_are_recognized_by_accept() {
+    let mut headers = HeaderMap::new();
+    assert!(!prefers_html(&headers));
+
+    headers.insert(
+        header::ACCEPT,
+        HeaderValue::from_static("text/html,application/xhtml+xml,*/*;q=0.8"),
+    );
+    assert!(prefers_html(&headers));
+
+    headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+    assert!(!prefers_html(&headers));
+}
+
+#[cfg(feature = "xml")]
+#[test]
+fn xml_is_served_when_preferred() {
+    assert_eq!(
+        negotiate(Some("application/xml, application/json;q=0.9")),
+        Some(ResponseFormat::Xml)
+    );
+    assert_eq!(
+        negotiate(Some("application/xml, */*")),
+        Some(ResponseFormat::Xml)
+    );
+
+    let body = ResponseFormat::Xml
+        .encode(&json!({ "message": "ok" }))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(body).unwrap(),
+        "<response><message>ok</message></response>"
+    );
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack_is_served_when_preferred() {
+    assert_eq!(
+        negotiate(Some("application/msgpack")),
+        Some(ResponseFormat::MessagePack)
+    );
+
+    let body = ResponseFormat::MessagePack
+        .encode(&json!({ "message": "ok" }))
+        .unwrap();
+    let decoded: serde_json::Value = rmp_serde::from_slice(&body).unwrap();
+    assert_eq!(decoded, json!({ "message": "ok" }));
+}
@@ -0,0 +1,70 @@
+use axum_rest::helpers::notifications::NotificationEvent;
+use axum_rest::model::model::{CollaboratorRole, NotificationKind, ReportAction};
+use chrono::{TimeZone, Utc};
+
+#[test]
+fn kinds_round_trip() {
+    for kind in [
+        NotificationKind::ACCOUNT_SUSPENDED,
+        NotificationKind::ACCOUNT_BANNED,
+        NotificationKind::ACCOUNT_REINSTATED,
+        NotificationKind::POST_REMOVED,
+        NotificationKind::REPORT_RESOLVED,
+        NotificationKind::MENTIONED,
+        NotificationKind::COLLABORATOR_INVITED,
+    ] {
+        assert_eq!(NotificationKind::from(String::from(kind).as_str()), kind);
+    }
+}
+
+#[test]
+fn messages_include_the_reason_and_end_of_a_suspension() {
+    let until = Utc.with_ymd_and_hms(2025, 3, 1, 12, 30, 0).unwrap();
+    let event = NotificationEvent::AccountSuspended {
+        until: Some(until),
+        reason: Some("spam"),
+    };
+    assert_eq!(event.kind(), NotificationKind::ACCOUNT_SUSPENDED);
+    assert_eq!(
+        event.message(),
+        "Your account was suspended until 2025-03-01 12:30 UTC: spam"
+    );
+    assert_eq!(event.target(), None);
+
+    let banned = NotificationEvent::AccountBanned { reason: None };
+    assert_eq!(banned.message(), "Your account was banned");
+}
+
+#[test]
+fn post_events_point_at_the_post() {
+    let removed = NotificationEvent::PostRemoved {
+        post_id: "p_123",
+        reason: None,
+    };
+    assert_eq!(removed.target().as_deref(), Some("post:p_123"));
+
+    let resolved = NotificationEvent::ReportResolved {
+        post_id: "p_123",
+        action: ReportAction::DISMISS,
+    };
+    assert_eq!(resolved.kind(), NotificationKind::REPORT_RESOLVED);
+    assert_eq!(resolved.target().as_deref(), Some("post:p_123"));
+    assert!(resolved.message().ends_with("no action was taken"));
+
+    let mentioned = NotificationEvent::Mentioned {
+        post_id: "p_123",
+        title: "Hello",
+    };
+    assert_eq!(mentioned.kind(), NotificationKind::MENTIONED);
+    assert_eq!(mentioned.target().as_deref(), Some("post:p_123"));
+    assert_eq!(mentioned.message(), "You were mentioned in \"Hello\"");
+
+    let invited = NotificationEvent::CollaboratorInvited {
+        post_id: "p_123",
+        title: "Hello",
+        role: CollaboratorRole::EDITOR,
+    };
+    assert_eq!(invited.kind(), NotificationKind::COLLABORATOR_INVITED);
+    assert_eq!(invited.target().as_deref(), Some("post:p_123"));
+    assert_eq!(invited.message(), "You were invited to edit \"Hello\"");
+}
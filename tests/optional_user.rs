@@ -0,0 +1,84 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{Request, header},
+};
+use axum_rest::config::TokenConfig;
+use axum_rest::helpers::auth::AuthHelper;
+use axum_rest::helpers::clock::MockClock;
+use axum_rest::helpers::denylist::TokenDenylist;
+use axum_rest::helpers::middleware::{OptionalUser, SessionGuard};
+use axum_rest::model::model::Role;
+use sqlx::postgres::PgPoolOptions;
+use uuid::Uuid;
+
+// Nothing listens on port 1, so every session and standing lookup fails
+fn guard() -> SessionGuard {
+    SessionGuard {
+        pool: Arc::new(
+            PgPoolOptions::new()
+                .acquire_timeout(Duration::from_millis(200))
+                .connect_lazy("postgres://app@127.0.0.1:1/none")
+                .unwrap(),
+        ),
+        denylist: Arc::new(TokenDenylist::disabled()),
+        clock: Arc::new(MockClock::default()),
+    }
+}
+
+async fn optional_user(guard: &SessionGuard, authorization: Option<String>) -> Option<Uuid> {
+    let mut request = Request::builder().uri("/posts/abc");
+    if let Some(authorization) = authorization {
+        request = request.header(header::AUTHORIZATION, authorization);
+    }
+    let (mut parts, _) = request.body(()).unwrap().into_parts();
+    let OptionalUser(user_id) = OptionalUser::from_request_parts(&mut parts, guard)
+        .await
+        .unwrap();
+    user_id
+}
+
+#[tokio::test]
+async fn missing_or_malformed_tokens_are_anonymous() {
+    let guard = guard();
+
+    assert_eq!(optional_user(&guard, None).await, None);
+    assert_eq!(
+        optional_user(&guard, Some("Bearer not-a-token".to_string())).await,
+        None
+    );
+}
+
+#[tokio::test]
+async fn signed_tokens_that_cannot_be_checked_are_anonymous() {
+    let guard = guard();
+    let user_id = Uuid::new_v4();
+
+    // A valid signature alone doesn't say the session is live or the account in good standing
+    let (token, _) = AuthHelper::generate_token(
+        user_id,
+        Role::USER,
+        &TokenConfig::default(),
+        guard.clock.as_ref(),
+    )
+    .unwrap();
+    assert_eq!(
+        optional_user(&guard, Some(format!("Bearer {}", token))).await,
+        None
+    );
+
+    let (token, _) = AuthHelper::generate_session_tokens(
+        user_id,
+        Role::USER,
+        "session-1",
+        &TokenConfig::default(),
+        guard.clock.as_ref(),
+    )
+    .unwrap();
+    assert_eq!(
+        optional_user(&guard, Some(format!("Bearer {}", token))).await,
+        None
+    );
+}
@@ -0,0 +1,62 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+    routing::get,
+};
+use axum_rest::helpers::path_params::ValidatedPath;
+use serde_json::Value;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn router() -> Router {
+    Router::new()
+        .route(
+            "/notifications/{id}",
+            get(|ValidatedPath(id): ValidatedPath<Uuid>| async move { id.to_string() }),
+        )
+        .route(
+            "/pages/{page}",
+            get(|ValidatedPath(page): ValidatedPath<u32>| async move { page.to_string() }),
+        )
+}
+
+async fn call(uri: &str) -> (StatusCode, String) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = router().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn well_formed_ids_are_extracted() {
+    let id = Uuid::new_v4();
+    let (status, body) = call(&format!("/notifications/{}", id)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, id.to_string());
+}
+
+#[tokio::test]
+async fn malformed_uuids_get_a_400_naming_the_parameter() {
+    let (status, body) = call("/notifications/abc").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["status"], 400);
+    assert_eq!(body["title"], "Invalid Path Parameter");
+    assert_eq!(body["detail"], "`id` must be a UUID, got `abc`");
+}
+
+#[tokio::test]
+async fn numbers_are_described_too() {
+    let (status, body) = call("/pages/-1").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        body["detail"],
+        "`page` must be a non-negative integer, got `-1`"
+    );
+}
@@ -0,0 +1,177 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{Request, StatusCode},
+};
+use axum_rest::helpers::policy::{
+    Actor, Authorize, can_archive_post, can_change_visibility, can_delete_post, can_edit_post,
+    can_manage_collaborators, can_preview_post, can_share_post, can_view_post, can_view_user,
+    can_write_in_org, require_admin,
+};
+use axum_rest::model::model::{CollaboratorRole, OrgContext, OrgRole, Post, Role, Visibility};
+use chrono::Utc;
+use uuid::Uuid;
+
+fn actor(role: Role) -> Actor {
+    Actor {
+        user_id: Uuid::new_v4(),
+        role,
+    }
+}
+
+fn post_by(author_id: Uuid) -> Post {
+    Post {
+        id: Uuid::new_v4(),
+        public_id: "post1".to_string(),
+        slug: None,
+        title: "Title".to_string(),
+        content: "Content".to_string(),
+        license: None,
+        author_id,
+        org_id: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        archived_at: None,
+        visibility: Visibility::PUBLIC,
+    }
+}
+
+fn org(role: Option<OrgRole>) -> OrgContext {
+    OrgContext {
+        org_id: Uuid::new_v4(),
+        public_id: "org1".to_string(),
+        role,
+    }
+}
+
+#[test]
+fn authors_can_edit_their_own_posts() {
+    let author = actor(Role::USER);
+    let post = post_by(author.user_id);
+
+    assert!(can_edit_post(&author, &post, None, None).is_ok());
+    assert!(can_delete_post(&author, &post, None).is_ok());
+    assert!(can_archive_post(&author, &post, None).is_ok());
+    assert!(can_preview_post(&author, &post).is_ok());
+
+    let other = actor(Role::USER);
+    let err = can_edit_post(&other, &post, None, None).unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+    assert!(can_preview_post(&other, &post).is_err());
+    assert!(can_archive_post(&other, &post, None).is_err());
+}
+
+#[test]
+fn organization_posts_need_a_writing_role() {
+    let author = actor(Role::USER);
+    let post = post_by(author.user_id);
+
+    assert!(can_write_in_org(None).is_ok());
+    assert!(can_edit_post(&author, &post, Some(&org(Some(OrgRole::EDITOR))), None).is_ok());
+
+    let err = can_edit_post(&author, &post, Some(&org(Some(OrgRole::VIEWER))), None).unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+    assert!(can_write_in_org(Some(&org(None))).is_err());
+}
+
+#[test]
+fn invited_editors_can_change_but_not_delete_posts() {
+    let author = actor(Role::USER);
+    let editor = actor(Role::USER);
+    let post = post_by(author.user_id);
+    let as_editor = Some(CollaboratorRole::EDITOR);
+
+    assert!(can_edit_post(&editor, &post, None, as_editor).is_ok());
+    assert!(can_delete_post(&editor, &post, None).is_err());
+    assert!(can_archive_post(&editor, &post, None).is_err());
+    assert!(can_manage_collaborators(&editor, &post, None).is_err());
+    assert!(can_manage_collaborators(&author, &post, None).is_ok());
+
+    let err = can_edit_post(&editor, &post, None, Some(CollaboratorRole::VIEWER)).unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+
+    // Inside an organization the editor still needs a writing role there
+    let viewer_org = org(Some(OrgRole::VIEWER));
+    assert!(can_edit_post(&editor, &post, Some(&viewer_org), as_editor).is_err());
+}
+
+#[test]
+fn private_posts_are_for_the_author_and_collaborators() {
+    let author = actor(Role::USER);
+    let stranger = actor(Role::USER);
+    let post = Post {
+        visibility: Visibility::PRIVATE,
+        ..post_by(author.user_id)
+    };
+
+    assert!(can_view_post(Some(author.user_id), &post, None).is_ok());
+    assert!(
+        can_view_post(
+            Some(stranger.user_id),
+            &post,
+            Some(CollaboratorRole::VIEWER)
+        )
+        .is_ok()
+    );
+    assert!(can_view_post(Some(stranger.user_id), &post, None).is_err());
+    assert!(can_view_post(None, &post, None).is_err());
+
+    let unlisted = Post {
+        visibility: Visibility::UNLISTED,
+        ..post_by(author.user_id)
+    };
+    assert!(can_view_post(None, &unlisted, None).is_ok());
+
+    // Editors can change the post but not who can find it
+    assert!(can_change_visibility(&author, &post).is_ok());
+    assert!(can_change_visibility(&stranger, &post).is_err());
+}
+
+#[test]
+fn only_the_author_shares_a_post() {
+    let author = actor(Role::USER);
+    let post = post_by(author.user_id);
+
+    assert!(can_share_post(&author, &post, None).is_ok());
+    assert!(can_share_post(&actor(Role::USER), &post, None).is_err());
+    assert!(can_share_post(&author, &post, Some(&org(Some(OrgRole::VIEWER)))).is_err());
+}
+
+#[test]
+fn accounts_are_visible_to_themselves_and_admins() {
+    let user = actor(Role::USER);
+    let admin = actor(Role::ADMIN);
+
+    assert!(can_view_user(&user, user.user_id).is_ok());
+    assert!(can_view_user(&admin, user.user_id).is_ok());
+    assert!(can_view_user(&user, admin.user_id).is_err());
+
+    assert!(require_admin(&admin).is_ok());
+    assert_eq!(
+        require_admin(&user).unwrap_err().status_code(),
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+async fn authorize_reads_the_auth_extensions() {
+    let user_id = Uuid::new_v4();
+    let (mut parts, _) = Request::builder()
+        .extension(user_id)
+        .extension(Role::ADMIN)
+        .body(())
+        .unwrap()
+        .into_parts();
+
+    let Authorize(actor) = Authorize::from_request_parts(&mut parts, &())
+        .await
+        .unwrap();
+    assert_eq!(actor.user_id, user_id);
+    assert!(actor.is_admin());
+
+    let (mut parts, _) = Request::new(()).into_parts();
+    let rejection = Authorize::from_request_parts(&mut parts, &())
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(rejection.status_code(), StatusCode::UNAUTHORIZED);
+}
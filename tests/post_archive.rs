@@ -0,0 +1,95 @@
+use axum_rest::helpers::post_archive::{
+    MAX_IMPORT_POSTS, PortablePost, parse_json, parse_markdown, parse_zip, to_json, to_markdown,
+    to_zip,
+};
+use axum_rest::model::model::Visibility;
+use chrono::{TimeZone, Utc};
+
+fn post(slug: Option<&str>) -> PortablePost {
+    PortablePost {
+        title: r#"Hello: a "quoted" title"#.to_string(),
+        slug: slug.map(String::from),
+        content: "# Heading\n\n---\n\nText after a rule.\n".to_string(),
+        license: Some("CC-BY-4.0".to_string()),
+        created_at: Some(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()),
+        updated_at: Some(Utc.with_ymd_and_hms(2024, 2, 1, 12, 0, 0).unwrap()),
+        visibility: None,
+    }
+}
+
+#[test]
+fn markdown_round_trips() {
+    let post = post(Some("hello"));
+    let markdown = to_markdown(&post);
+
+    assert!(markdown.starts_with("---\ntitle: \"Hello: a \\\"quoted\\\" title\"\n"));
+    assert_eq!(parse_markdown(&markdown).unwrap(), post);
+}
+
+#[test]
+fn handwritten_frontmatter_is_accepted() {
+    let post =
+        parse_markdown("---\r\ntitle: Bare title\r\nslug: 'it''s'\r\ntags: ignored\r\n---\r\nBody")
+            .unwrap();
+
+    assert_eq!(post.title, "Bare title");
+    assert_eq!(post.slug.as_deref(), Some("it's"));
+    assert_eq!(post.content, "Body");
+    assert_eq!(post.created_at, None);
+}
+
+#[test]
+fn malformed_markdown_is_rejected() {
+    assert!(parse_markdown("# Just a heading").is_err());
+    assert!(parse_markdown("---\ntitle: Never closed\n").is_err());
+    assert!(parse_markdown("---\nslug: no-title\n---\nBody").is_err());
+    assert!(parse_markdown("---\ntitle: x\ncreated_at: yesterday\n---\nBody").is_err());
+}
+
+#[test]
+fn private_posts_stay_private_through_markdown() {
+    let post = PortablePost {
+        visibility: Some(Visibility::PRIVATE),
+        ..post(Some("hello"))
+    };
+    let markdown = to_markdown(&post);
+
+    assert!(markdown.contains("visibility: \"PRIVATE\"\n"));
+    assert_eq!(parse_markdown(&markdown).unwrap(), post);
+
+    let unlisted = parse_markdown("---\ntitle: x\nvisibility: unlisted\n---\nBody").unwrap();
+    assert_eq!(unlisted.visibility, Some(Visibility::UNLISTED));
+    assert!(parse_markdown("---\ntitle: x\nvisibility: secret\n---\nBody").is_err());
+}
+
+#[test]
+fn json_exports_import_item_by_item() {
+    let export = to_json(vec![post(None)], Utc::now()).unwrap();
+    let items = parse_json(&export).unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].0, "posts[0]");
+    assert_eq!(items[0].1.as_ref().unwrap(), &post(None));
+
+    let items = parse_json(br#"[{"title": "Ok", "content": "Body"}, {"title": 1}]"#).unwrap();
+    assert!(items[0].1.is_ok());
+    assert!(items[1].1.is_err());
+}
+
+#[test]
+fn oversized_json_imports_are_refused() {
+    let posts = vec![serde_json::json!({}); MAX_IMPORT_POSTS + 1];
+    assert!(parse_json(serde_json::to_string(&posts).unwrap().as_bytes()).is_err());
+    assert!(parse_json(b"not json").is_err());
+}
+
+#[test]
+fn zip_exports_import_item_by_item() {
+    let zip = to_zip(&[post(Some("hello")), post(None)]).unwrap();
+    let items = parse_zip(&zip).unwrap();
+
+    let names: Vec<&str> = items.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["hello.md", "post-2.md"]);
+    assert_eq!(items[0].1.as_ref().unwrap(), &post(Some("hello")));
+    assert!(parse_zip(b"PK\x03\x04 not really a zip").is_err());
+}
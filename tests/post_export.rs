@@ -0,0 +1,114 @@
+use axum_rest::helpers::post_archive::parse_markdown;
+use axum_rest::helpers::post_export::{ExportFormat, file_name, to_html, to_markdown, to_pdf};
+use axum_rest::model::model::{PostResponse, PublicAuthor, Visibility};
+use chrono::{Duration, TimeZone, Utc};
+
+fn post(content: &str) -> PostResponse {
+    let time = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+    PostResponse {
+        id: "IRFa-VaY2b3x".to_string(),
+        slug: Some("hello".to_string()),
+        title: "Hello <world> (again)".to_string(),
+        content: content.to_string(),
+        content_html: None,
+        license: Some("CC-BY-4.0".to_string()),
+        word_count: 0,
+        reading_time_minutes: 0,
+        excerpt: "A first post.".to_string(),
+        author: PublicAuthor {
+            id: "V1StGXR8_Z5j".to_string(),
+            name: "Ada Lovelace".to_string(),
+            username: Some("ada_lovelace".to_string()),
+            avatar_url: "https://cdn.example.com/ada.png".to_string(),
+        },
+        view_count: 0,
+        created_at: time,
+        updated_at: time + Duration::hours(1),
+        archived_at: None,
+        visibility: Visibility::PUBLIC,
+        mentions: Vec::new(),
+        collaborators: Vec::new(),
+    }
+}
+
+#[test]
+fn files_are_named_after_the_slug() {
+    let mut post = post("");
+    assert_eq!(file_name(&post, ExportFormat::Pdf), "hello.pdf");
+
+    post.slug = None;
+    assert_eq!(file_name(&post, ExportFormat::Markdown), "IRFa-VaY2b3x.md");
+}
+
+#[test]
+fn markdown_exports_can_be_imported_again() {
+    let post = post("# Heading\n\nSome *text*.\n");
+    let markdown = to_markdown(&post);
+
+    assert!(markdown.contains("author: \"Ada Lovelace\"\n"));
+    assert!(markdown.contains("date: \"2024-01-01T12:00:00+00:00\"\n"));
+    assert!(markdown.contains("description: \"A first post.\"\n"));
+
+    let imported = parse_markdown(&markdown).unwrap();
+    assert_eq!(imported.title, post.title);
+    assert_eq!(imported.slug, post.slug);
+    assert_eq!(imported.license, post.license);
+    assert_eq!(imported.created_at, Some(post.created_at));
+    assert_eq!(imported.content, post.content);
+}
+
+#[test]
+fn html_exports_are_standalone_pages() {
+    let html = to_html(&post("Some *text* <script>alert(1)</script>"));
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Hello &lt;world&gt; (again)</title>"));
+    assert!(html.contains(r#"<meta name="author" content="Ada Lovelace">"#));
+    assert!(html.contains("<em>text</em>"));
+    assert!(!html.contains("<script>"));
+    assert!(html.contains("License: CC-BY-4.0"));
+}
+
+fn text(pdf: &[u8]) -> String {
+    String::from_utf8_lossy(pdf).into_owned()
+}
+
+#[test]
+fn pdf_exports_are_well_formed() {
+    let pdf = to_pdf(&post(
+        "A paragraph with an é and a “quote”.\n\n- one\n- two\n",
+    ));
+    let body = text(&pdf);
+
+    assert!(pdf.starts_with(b"%PDF-1.4\n"));
+    assert!(body.ends_with("%%EOF\n"));
+    let startxref: usize = body
+        .rsplit("startxref\n")
+        .next()
+        .and_then(|tail| tail.lines().next())
+        .and_then(|offset| offset.parse().ok())
+        .unwrap();
+    assert!(pdf[startxref..].starts_with(b"xref\n"));
+
+    // Titles are escaped, and text beyond ASCII is written in Windows-1252
+    assert!(body.contains(r"(Hello <world> \(again\))"));
+    assert!(body.contains(r"(A paragraph with an \351 and a \223quote\224.)"));
+    assert!(body.contains(r"(\225 one)"));
+    assert!(body.contains("/Count 1 "));
+}
+
+#[test]
+fn long_posts_run_over_several_pages() {
+    let content = "A sentence that goes on for a while. ".repeat(40) + "\n\n";
+    let pdf = to_pdf(&post(&content.repeat(20)));
+    let body = text(&pdf);
+
+    let count: usize = body
+        .split("/Count ")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|count| count.parse().ok())
+        .unwrap();
+    assert!(count > 1, "{} page(s)", count);
+    assert!(body.contains(&format!("(1 / {})", count)));
+}
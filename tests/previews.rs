@@ -0,0 +1,113 @@
+use std::time::Duration as StdDuration;
+
+use axum_rest::config::TokenConfig;
+use axum_rest::helpers::auth::AuthHelper;
+use axum_rest::helpers::clock::{Clock, MockClock};
+use axum_rest::helpers::markdown::plain_text;
+use axum_rest::helpers::previews::{
+    MAX_DESCRIPTION_LENGTH, issue_preview_token, post_meta, verify_preview_token,
+};
+use axum_rest::model::model::{PostResponse, PublicAuthor, Role, Visibility};
+use chrono::{Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+fn post(content: &str) -> PostResponse {
+    let time = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+    PostResponse {
+        id: "IRFa-VaY2b3x".to_string(),
+        slug: Some("hello".to_string()),
+        title: "Hello".to_string(),
+        content: content.to_string(),
+        content_html: None,
+        license: None,
+        word_count: 0,
+        reading_time_minutes: 0,
+        excerpt: String::new(),
+        author: PublicAuthor {
+            id: "V1StGXR8_Z5j".to_string(),
+            name: "Ada Lovelace".to_string(),
+            username: Some("ada_lovelace".to_string()),
+            avatar_url: "https://cdn.example.com/ada.png".to_string(),
+        },
+        view_count: 0,
+        created_at: time,
+        updated_at: time + Duration::hours(1),
+        archived_at: None,
+        visibility: Visibility::PUBLIC,
+        mentions: Vec::new(),
+        collaborators: Vec::new(),
+    }
+}
+
+#[test]
+fn preview_tokens_name_the_post_and_session() {
+    let clock = MockClock::default();
+    let (token, expires_at) = issue_preview_token(
+        "IRFa-VaY2b3x",
+        "session-1",
+        StdDuration::from_secs(600),
+        &clock,
+    )
+    .unwrap();
+
+    assert_eq!(expires_at.timestamp(), clock.now().timestamp() + 600);
+    let claims = verify_preview_token(&token, &clock).unwrap();
+    assert_eq!(claims.sub, "IRFa-VaY2b3x");
+    assert_eq!(claims.sid, "session-1");
+}
+
+#[test]
+fn preview_tokens_expire() {
+    let clock = MockClock::default();
+    let (token, _) = issue_preview_token(
+        "IRFa-VaY2b3x",
+        "session-1",
+        StdDuration::from_secs(600),
+        &clock,
+    )
+    .unwrap();
+
+    clock.advance(Duration::hours(1));
+    assert!(verify_preview_token(&token, &clock).is_err());
+}
+
+#[test]
+fn auth_tokens_are_not_preview_tokens() {
+    let clock = MockClock::default();
+    let (access_token, _) =
+        AuthHelper::generate_token(Uuid::new_v4(), Role::USER, &TokenConfig::default(), &clock)
+            .unwrap();
+
+    assert!(verify_preview_token(&access_token, &clock).is_err());
+}
+
+#[test]
+fn meta_describes_the_published_post() {
+    let post = post("# Hello\n\n![A cat](https://cdn.example.com/cat.png)\n\nSome **bold** text.");
+    let meta = post_meta(
+        &post,
+        "https://blog.example.com/posts/IRFa-VaY2b3x".to_string(),
+    );
+
+    assert_eq!(meta.title, "Hello");
+    assert_eq!(meta.description, "Hello A cat Some bold text.");
+    assert_eq!(
+        meta.image.as_deref(),
+        Some("https://cdn.example.com/cat.png")
+    );
+    assert_eq!(meta.author, "Ada Lovelace");
+    assert_eq!(meta.modified_time, post.updated_at);
+
+    let json = serde_json::to_value(&meta).unwrap();
+    assert_eq!(json["type"], "article");
+}
+
+#[test]
+fn plain_text_is_cut_at_a_word_boundary() {
+    let text = plain_text(&"lorem ipsum ".repeat(50), MAX_DESCRIPTION_LENGTH);
+
+    assert!(text.chars().count() <= MAX_DESCRIPTION_LENGTH);
+    assert!(text.ends_with("…"));
+    assert!(!text.trim_end_matches('…').ends_with(' '));
+    assert_eq!(plain_text("Short *and* sweet", 200), "Short and sweet");
+}
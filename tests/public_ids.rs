@@ -0,0 +1,89 @@
+//! Runs against the database in `TEST_DATABASE_URL` and is skipped without one.
+
+mod common;
+
+use axum_rest::db::db::Db;
+use axum_rest::helpers::ids::PUBLIC_ID_LEN;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use common::{config, with_test_schema};
+
+/// The tables as they were before public ids existed
+const LEGACY_SCHEMA: [&str; 2] = [
+    r#"
+    CREATE TABLE users (
+        id UUID PRIMARY KEY,
+        name TEXT NOT NULL,
+        email TEXT UNIQUE NOT NULL,
+        password TEXT NOT NULL,
+        role VARCHAR(10) NOT NULL DEFAULT 'USER',
+        email_verified BOOLEAN NOT NULL DEFAULT FALSE,
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+    )
+    "#,
+    r#"
+    CREATE TABLE posts (
+        id UUID PRIMARY KEY,
+        title TEXT NOT NULL,
+        content TEXT NOT NULL,
+        author_id UUID NOT NULL REFERENCES users(id),
+        created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+    )
+    "#,
+];
+
+#[tokio::test]
+async fn upgraded_databases_get_the_same_public_ids_as_new_ones() {
+    with_test_schema(|url| async move {
+        let legacy = PgPool::connect(&url).await.unwrap();
+        for statement in LEGACY_SCHEMA {
+            sqlx::query(statement).execute(&legacy).await.unwrap();
+        }
+        let (user_id, post_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO users (id, name, email, password) VALUES ($1, 'Ada', 'ada@example.com', 'x')")
+            .bind(user_id)
+            .execute(&legacy)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO posts (id, title, content, author_id) VALUES ($1, 'Old', 'Body', $2)")
+            .bind(post_id)
+            .bind(user_id)
+            .execute(&legacy)
+            .await
+            .unwrap();
+        legacy.close().await;
+
+        let db = Db::new(&url, &config()).await.unwrap();
+        let pool = db.get_pool();
+
+        for (table, id) in [("users", user_id), ("posts", post_id)] {
+            let public_id: String =
+                sqlx::query_scalar(&format!("SELECT public_id FROM {table} WHERE id = $1"))
+                    .bind(id)
+                    .fetch_one(pool)
+                    .await
+                    .unwrap();
+            assert_eq!(public_id.len(), PUBLIC_ID_LEN, "{table}");
+            assert!(
+                public_id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+                "{public_id}"
+            );
+
+            let nullable: String = sqlx::query_scalar(
+                "SELECT is_nullable FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = $1 AND column_name = 'public_id'",
+            )
+            .bind(table)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+            assert_eq!(nullable, "NO", "{table}");
+        }
+        pool.close().await;
+    })
+    .await;
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, header},
+    middleware,
+    routing::get,
+};
+use axum_rest::config::config::AccessConfig;
+use axum_rest::helpers::access::{PublicReadTier, public_read_middleware};
+use axum_rest::helpers::negotiation::Negotiated;
+use serde_json::json;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    let tier = Arc::new(PublicReadTier::new(&AccessConfig {
+        require_api_key: false,
+        api_keys: Vec::new(),
+        public_read_burst: 10,
+        public_read_period: Duration::from_secs(1),
+        public_cache_max_age: Duration::from_secs(60),
+        daily_quota: 0,
+        admin_daily_quota: 0,
+    }));
+
+    Router::new().route(
+        "/posts",
+        get(|| async { Negotiated(json!({ "posts": [] })) })
+            .route_layer(middleware::from_fn_with_state(tier, public_read_middleware)),
+    )
+}
+
+#[tokio::test]
+async fn anonymous_reads_keep_varying_on_the_format() {
+    let response = app()
+        .oneshot(Request::get("/posts").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.headers()[header::CACHE_CONTROL],
+        "public, max-age=60"
+    );
+    let vary: Vec<_> = response
+        .headers()
+        .get_all(header::VARY)
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect();
+    assert!(vary.contains(&"accept"));
+    assert!(vary.contains(&"Authorization, Cookie"));
+}
+
+#[tokio::test]
+async fn signed_in_reads_are_not_marked_public() {
+    let response = app()
+        .oneshot(
+            Request::get("/posts")
+                .header(header::AUTHORIZATION, "Bearer token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+    assert_eq!(response.headers()[header::VARY], "accept");
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use axum_rest::config::{PublishHookConfig, PublishHookKind};
+use axum_rest::helpers::publish_hooks::{PublishEvent, build_delivery, post_url, retry_delay};
+use axum_rest::model::model::DeliveryStatus;
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+const POST_URL: &str = "https://blog.example.com/posts/abc123";
+
+fn hook(entry: &str) -> PublishHookConfig {
+    PublishHookConfig::parse(entry).expect("valid hook")
+}
+
+#[test]
+fn hooks_parse_from_kind_and_url() {
+    assert_eq!(
+        hook("indexnow=https://api.indexnow.org/indexnow").kind,
+        PublishHookKind::IndexNow
+    );
+    assert_eq!(
+        hook(" PURGE = https://cdn.example.com/purge ").url,
+        "https://cdn.example.com/purge"
+    );
+    assert!(PublishHookConfig::parse("smoke=https://example.com").is_none());
+    assert!(PublishHookConfig::parse("ping=ftp://example.com").is_none());
+    assert!(PublishHookConfig::parse("https://example.com").is_none());
+}
+
+#[test]
+fn post_urls_fill_in_the_public_id() {
+    assert_eq!(
+        post_url("https://blog.example.com/posts/{id}", "abc123"),
+        POST_URL
+    );
+}
+
+#[test]
+fn ping_hooks_embed_the_encoded_post_url() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let delivery = build_delivery(
+        Uuid::now_v7(),
+        &hook("ping=https://search.example/ping?url={url}"),
+        None,
+        PublishEvent::Published,
+        "abc123",
+        POST_URL,
+        now,
+    )
+    .expect("ping delivery");
+
+    assert_eq!(delivery.method, "GET");
+    assert_eq!(
+        delivery.url,
+        "https://search.example/ping?url=https%3A%2F%2Fblog.example.com%2Fposts%2Fabc123"
+    );
+    assert_eq!(delivery.status, DeliveryStatus::PENDING);
+    assert_eq!(delivery.next_attempt_at, now);
+}
+
+#[test]
+fn indexnow_needs_a_key() {
+    let now = Utc::now();
+    let indexnow = hook("indexnow=https://api.indexnow.org/indexnow");
+
+    assert!(
+        build_delivery(
+            Uuid::now_v7(),
+            &indexnow,
+            None,
+            PublishEvent::Updated,
+            "abc123",
+            POST_URL,
+            now
+        )
+        .is_none()
+    );
+
+    let delivery = build_delivery(
+        Uuid::now_v7(),
+        &indexnow,
+        Some("secret"),
+        PublishEvent::Updated,
+        "abc123",
+        POST_URL,
+        now,
+    )
+    .expect("indexnow delivery");
+    let body: serde_json::Value = serde_json::from_str(delivery.body.as_deref().unwrap()).unwrap();
+
+    assert_eq!(delivery.method, "POST");
+    assert_eq!(body["host"], "blog.example.com");
+    assert_eq!(body["key"], "secret");
+    assert_eq!(body["urlList"][0], POST_URL);
+}
+
+#[test]
+fn purge_hooks_describe_the_event() {
+    let delivery = build_delivery(
+        Uuid::now_v7(),
+        &hook("purge=https://cdn.example.com/purge"),
+        None,
+        PublishEvent::Updated,
+        "abc123",
+        POST_URL,
+        Utc::now(),
+    )
+    .expect("purge delivery");
+    let body: serde_json::Value = serde_json::from_str(delivery.body.as_deref().unwrap()).unwrap();
+
+    assert_eq!(delivery.event, "post.updated");
+    assert_eq!(body["event"], "post.updated");
+    assert_eq!(body["post_id"], "abc123");
+}
+
+#[test]
+fn retries_back_off_exponentially_up_to_an_hour() {
+    let base = Duration::from_secs(30);
+
+    assert_eq!(retry_delay(1, base), Duration::from_secs(30));
+    assert_eq!(retry_delay(2, base), Duration::from_secs(60));
+    assert_eq!(retry_delay(4, base), Duration::from_secs(240));
+    assert_eq!(retry_delay(20, base), Duration::from_secs(3600));
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use axum_rest::db::instrument::{BUCKETS_MS, QueryTimer, query_stats, record};
+
+#[test]
+fn calls_are_bucketed_per_query() {
+    for millis in [2, 3, 40, 40, 3000] {
+        record("test_repo.bucketed", Duration::from_millis(millis));
+    }
+
+    let stats = query_stats()
+        .into_iter()
+        .find(|stats| stats.name == "test_repo.bucketed")
+        .unwrap();
+    assert_eq!(stats.calls, 5);
+    assert_eq!(stats.max_ms, 3000.0);
+    assert!((stats.mean_ms - 617.0).abs() < 1e-6, "{}", stats.mean_ms);
+    // Percentiles report the bucket bound, or the slowest call past the last bound
+    assert_eq!(stats.p50_ms, 50.0);
+    assert_eq!(stats.p99_ms, 3000.0);
+    // The default threshold is 250ms
+    assert_eq!(stats.slow_calls, 1);
+
+    assert_eq!(stats.buckets.len(), BUCKETS_MS.len() + 1);
+    let count = |le_ms: Option<u64>| {
+        stats
+            .buckets
+            .iter()
+            .find(|bucket| bucket.le_ms == le_ms)
+            .unwrap()
+            .count
+    };
+    assert_eq!(count(Some(5)), 2);
+    assert_eq!(count(Some(50)), 2);
+    assert_eq!(count(None), 1);
+}
+
+#[test]
+fn timers_record_when_dropped() {
+    {
+        let _timer = QueryTimer::start("test_repo.timed");
+    }
+
+    let stats = query_stats()
+        .into_iter()
+        .find(|stats| stats.name == "test_repo.timed")
+        .unwrap();
+    assert_eq!(stats.calls, 1);
+    assert_eq!(stats.slow_calls, 0);
+}
@@ -0,0 +1,213 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    body::{Body, to_bytes},
+    http::{Request, header},
+    middleware,
+    routing::get,
+};
+use axum_rest::config::TokenConfig;
+use axum_rest::helpers::auth::AuthHelper;
+use axum_rest::helpers::clock::MockClock;
+use axum_rest::helpers::denylist::TokenDenylist;
+use axum_rest::helpers::middleware::SessionGuard;
+use axum_rest::helpers::redaction::{Audience, SeenBy, redaction_middleware, with_audience};
+use axum_rest::model::model::{
+    EmailDelivery, PostResponse, PublicAuthor, Role, UserResponse, Visibility,
+};
+use chrono::Utc;
+use serde_json::Value;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use common::with_test_db;
+
+fn user(internal_id: Uuid) -> UserResponse {
+    UserResponse {
+        internal_id,
+        id: "V1StGXR8_Z5j".to_string(),
+        name: "Ada Lovelace".to_string(),
+        username: Some("ada_lovelace".to_string()),
+        email: "ada@example.com".to_string(),
+        role: Role::USER,
+        email_verified: true,
+        avatar_url: "https://cdn.example.com/ada.png".to_string(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        email_delivery: None,
+    }
+}
+
+async fn render(audience: Audience, user: &UserResponse) -> serde_json::Value {
+    with_audience(audience, async { serde_json::to_value(user).unwrap() }).await
+}
+
+#[tokio::test]
+async fn anonymous_requesters_do_not_see_private_fields() {
+    let json = render(Audience::anonymous(), &user(Uuid::new_v4())).await;
+
+    assert!(json.get("email").is_none());
+    assert!(json.get("email_verified").is_none());
+    assert_eq!(json["name"], "Ada Lovelace");
+    assert_eq!(json["username"], "ada_lovelace");
+}
+
+#[tokio::test]
+async fn other_users_do_not_see_private_fields() {
+    let audience = Audience::user(Uuid::new_v4(), Role::USER);
+    let json = render(audience, &user(Uuid::new_v4())).await;
+
+    assert!(json.get("email").is_none());
+}
+
+#[tokio::test]
+async fn owners_and_admins_see_private_fields() {
+    let owner = Uuid::new_v4();
+
+    let json = render(Audience::user(owner, Role::USER), &user(owner)).await;
+    assert_eq!(json["email"], "ada@example.com");
+    assert_eq!(json["email_verified"], true);
+
+    let json = render(Audience::user(Uuid::new_v4(), Role::ADMIN), &user(owner)).await;
+    assert_eq!(json["email"], "ada@example.com");
+}
+
+#[tokio::test]
+async fn delivery_status_is_private_and_only_shown_once_reported() {
+    let owner = Uuid::new_v4();
+    let admin = || Audience::user(Uuid::new_v4(), Role::ADMIN);
+
+    let json = render(admin(), &user(owner)).await;
+    assert!(json.get("email_delivery").is_none());
+
+    let bounced = UserResponse {
+        email_delivery: Some(EmailDelivery::BOUNCED),
+        ..user(owner)
+    };
+    let json = render(admin(), &bounced).await;
+    assert_eq!(json["email_delivery"], "BOUNCED");
+
+    let json = render(Audience::anonymous(), &bounced).await;
+    assert!(json.get("email_delivery").is_none());
+}
+
+#[test]
+fn private_fields_are_hidden_outside_a_request() {
+    let owner = Uuid::new_v4();
+
+    let json = serde_json::to_value(user(owner)).unwrap();
+    assert!(json.get("email").is_none());
+    assert!(json.get("email_verified").is_none());
+
+    let json =
+        serde_json::to_value(SeenBy(Audience::user(owner, Role::USER), user(owner))).unwrap();
+    assert_eq!(json["email"], "ada@example.com");
+}
+
+#[tokio::test]
+async fn responses_can_be_rendered_for_the_user_they_describe() {
+    let owner = Uuid::new_v4();
+    let seen = SeenBy(Audience::user(owner, Role::USER), user(owner));
+
+    let json = with_audience(Audience::anonymous(), async {
+        serde_json::to_value(&seen).unwrap()
+    })
+    .await;
+    assert_eq!(json["email"], "ada@example.com");
+    assert_eq!(json["email_verified"], true);
+
+    let json = render(Audience::anonymous(), &seen.1).await;
+    assert!(json.get("email").is_none());
+}
+
+#[tokio::test]
+async fn post_authors_never_carry_private_fields() {
+    let user = user(Uuid::new_v4());
+    let post = PostResponse {
+        id: "IRFa-VaY2b3x".to_string(),
+        slug: None,
+        title: "Hello".to_string(),
+        content: "First post".to_string(),
+        content_html: None,
+        license: None,
+        word_count: 2,
+        reading_time_minutes: 1,
+        excerpt: "First post".to_string(),
+        author: PublicAuthor {
+            id: user.id,
+            name: user.name,
+            username: user.username,
+            avatar_url: user.avatar_url,
+        },
+        view_count: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        archived_at: None,
+        visibility: Visibility::PUBLIC,
+        mentions: Vec::new(),
+        collaborators: Vec::new(),
+    };
+
+    let admin = Audience::user(Uuid::new_v4(), Role::ADMIN);
+    let json = with_audience(admin, async { serde_json::to_value(&post).unwrap() }).await;
+    let author = json["author"].as_object().unwrap();
+
+    assert_eq!(author["username"], "ada_lovelace");
+    for field in ["email", "email_verified", "role"] {
+        assert!(!author.contains_key(field), "author exposes {}", field);
+    }
+}
+
+/// Runs against the database in `TEST_DATABASE_URL` and is skipped without one
+#[tokio::test]
+async fn tokens_of_banned_users_get_the_anonymous_view() {
+    with_test_db(|pool| async move {
+        let owner = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, public_id, name, email, password) VALUES ($1, $2, 'Ada', 'ada@example.com', 'x')",
+        )
+        .bind(owner)
+        .bind(owner.simple().to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+        let guard = SessionGuard {
+            pool: Arc::new(pool.clone()),
+            denylist: Arc::new(TokenDenylist::disabled()),
+            clock: Arc::new(MockClock::default()),
+        };
+        let (token, _) = AuthHelper::generate_token(
+            owner,
+            Role::USER,
+            &TokenConfig::default(),
+            guard.clock.as_ref(),
+        )
+        .unwrap();
+        let app = Router::new()
+            .route("/me", get(move || async move { Json(user(owner)) }))
+            .layer(middleware::from_fn_with_state(guard, redaction_middleware));
+        let me = || async {
+            let request = Request::builder()
+                .uri("/me")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<Value>(&body).unwrap()
+        };
+
+        assert_eq!(me().await["email"], "ada@example.com");
+
+        sqlx::query("UPDATE users SET status = 'BANNED' WHERE id = $1")
+            .bind(owner)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(me().await.get("email").is_none());
+    })
+    .await;
+}
@@ -0,0 +1,33 @@
+use axum_rest::helpers::redirects::{is_allowed_redirect, with_query};
+
+#[test]
+fn only_allowed_origins_are_redirected_to() {
+    let allowed = vec!["https://app.example.com".to_string()];
+
+    assert!(is_allowed_redirect(
+        "https://app.example.com/welcome?tab=1",
+        &allowed
+    ));
+    assert!(!is_allowed_redirect(
+        "http://app.example.com/welcome",
+        &allowed
+    ));
+    assert!(!is_allowed_redirect(
+        "https://app.example.com.evil.test/welcome",
+        &allowed
+    ));
+    assert!(!is_allowed_redirect("//evil.test/welcome", &allowed));
+    assert!(!is_allowed_redirect("javascript:alert(1)", &allowed));
+    assert!(!is_allowed_redirect("https://app.example.com/", &[]));
+}
+
+#[test]
+fn results_are_appended_to_the_query() {
+    assert_eq!(
+        with_query(
+            "https://app.example.com/welcome?tab=1",
+            &[("verified", "false"), ("error", "invalid_token")]
+        ),
+        "https://app.example.com/welcome?tab=1&verified=false&error=invalid_token"
+    );
+}
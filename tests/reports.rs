@@ -0,0 +1,49 @@
+use axum_rest::model::model::{Report, ReportAction, ReportReason, ReportStatus};
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+#[test]
+fn stored_values_round_trip() {
+    for reason in [
+        ReportReason::SPAM,
+        ReportReason::COPYRIGHT,
+        ReportReason::OTHER,
+    ] {
+        assert_eq!(ReportReason::from(String::from(reason).as_str()), reason);
+    }
+    for action in [
+        ReportAction::DISMISS,
+        ReportAction::DELETE,
+        ReportAction::SUSPEND,
+    ] {
+        assert_eq!(ReportAction::from(String::from(action).as_str()), action);
+    }
+    assert_eq!(ReportStatus::from("RESOLVED"), ReportStatus::RESOLVED);
+    assert_eq!(ReportStatus::from("unknown"), ReportStatus::OPEN);
+}
+
+#[test]
+fn internal_ids_are_not_exposed() {
+    let report = Report {
+        id: Uuid::nil(),
+        target_kind: "post".to_string(),
+        target_internal_id: Uuid::from_u128(1),
+        target_id: "p_123".to_string(),
+        author_internal_id: Some(Uuid::from_u128(2)),
+        author_id: Some("u_456".to_string()),
+        reporter_id: None,
+        reason: ReportReason::SPAM,
+        details: None,
+        status: ReportStatus::OPEN,
+        action: None,
+        note: None,
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+        resolved_at: None,
+    };
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["target_id"], "p_123");
+    assert_eq!(json["author_id"], "u_456");
+    assert!(json.get("target_internal_id").is_none());
+    assert!(json.get("author_internal_id").is_none());
+}
@@ -0,0 +1,110 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+    middleware,
+    response::IntoResponse,
+    routing::get,
+};
+use axum_rest::helpers::request_log::{AuthenticatedUser, request_log_middleware};
+use tower::ServiceExt;
+use tracing_subscriber::fmt::MakeWriter;
+use uuid::Uuid;
+
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Captured {
+    type Writer = Captured;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+async fn log_of(uri: &str, slow_threshold: Duration) -> String {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(captured.clone())
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let user_id = Uuid::nil();
+    let app = Router::new()
+        .route(
+            "/posts/{id}",
+            get(move || async move {
+                let mut response = "post".into_response();
+                response.extensions_mut().insert(AuthenticatedUser(user_id));
+                response
+            }),
+        )
+        .route(
+            "/broken",
+            get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+        )
+        .layer(middleware::from_fn_with_state(
+            slow_threshold,
+            request_log_middleware,
+        ));
+
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    app.oneshot(request).await.unwrap();
+
+    String::from_utf8(captured.0.lock().unwrap().clone()).unwrap()
+}
+
+#[tokio::test]
+async fn requests_are_logged_by_route_template() {
+    let log = log_of("/posts/secret-token", Duration::from_secs(60)).await;
+
+    assert!(log.contains(" INFO "), "{}", log);
+    assert!(log.contains("route=/posts/{id}"), "{}", log);
+    assert!(log.contains("status=200"), "{}", log);
+    assert!(log.contains(&format!("user_id={}", Uuid::nil())), "{}", log);
+    assert!(!log.contains("secret-token"), "{}", log);
+}
+
+#[tokio::test]
+async fn query_strings_are_left_out() {
+    let log = log_of("/posts/abc?share=share-token", Duration::from_secs(60)).await;
+
+    assert!(log.contains("route=/posts/{id}"), "{}", log);
+    assert!(!log.contains("share-token"), "{}", log);
+}
+
+#[tokio::test]
+async fn slow_requests_and_server_errors_stand_out() {
+    let log = log_of("/posts/abc", Duration::ZERO).await;
+    assert!(
+        log.contains(" WARN ") && log.contains("slow request"),
+        "{}",
+        log
+    );
+
+    let log = log_of("/broken", Duration::from_secs(60)).await;
+    assert!(
+        log.contains("ERROR") && log.contains("status=500"),
+        "{}",
+        log
+    );
+    assert!(log.contains("user_id=-"), "{}", log);
+}
@@ -0,0 +1,48 @@
+use std::{env, fs};
+
+use axum_rest::helpers::secrets::{pepper_password, read_secret};
+
+// Each test uses its own variable names, so they can run in parallel
+#[test]
+fn secret_files_win_over_the_environment() {
+    let path = env::temp_dir().join(format!("axum-rest-secret-{}", std::process::id()));
+    fs::write(&path, "from-file\n").unwrap();
+    unsafe {
+        env::set_var("TEST_FILE_SECRET", "from-env");
+        env::set_var("TEST_FILE_SECRET_FILE", &path);
+    }
+
+    assert_eq!(
+        read_secret("TEST_FILE_SECRET").unwrap().as_deref(),
+        Some("from-file")
+    );
+
+    fs::write(&path, "  \n").unwrap();
+    assert!(read_secret("TEST_FILE_SECRET").is_err());
+    fs::remove_file(&path).unwrap();
+    assert!(read_secret("TEST_FILE_SECRET").is_err());
+}
+
+#[test]
+fn unset_and_blank_secrets_are_none() {
+    assert_eq!(read_secret("TEST_UNSET_SECRET").unwrap(), None);
+
+    unsafe { env::set_var("TEST_BLANK_SECRET", " ") };
+    assert_eq!(read_secret("TEST_BLANK_SECRET").unwrap(), None);
+}
+
+#[test]
+fn peppering_depends_on_the_pepper() {
+    let peppered = pepper_password("correct horse battery staple", "pepper-one");
+
+    assert_eq!(
+        peppered,
+        pepper_password("correct horse battery staple", "pepper-one")
+    );
+    assert_ne!(
+        peppered,
+        pepper_password("correct horse battery staple", "pepper-two")
+    );
+    // Long passwords still fit in bcrypt's 72 bytes
+    assert!(pepper_password(&"a".repeat(500), "pepper-one").len() <= 72);
+}
@@ -0,0 +1,42 @@
+use std::collections::HashSet;
+
+use axum_rest::cli::seed::{DEFAULT_SEED_PASSWORD, generate};
+use axum_rest::helpers::validation::{strong_password, validate_post_fields};
+use axum_rest::model::model::Limits;
+use chrono::{Duration, TimeZone, Utc};
+
+#[test]
+fn the_same_seed_generates_the_same_data() {
+    let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+
+    assert_eq!(generate(7, 5, 3, now), generate(7, 5, 3, now));
+    assert_ne!(generate(7, 5, 3, now), generate(8, 5, 3, now));
+}
+
+#[test]
+fn seeded_users_are_predictable_and_valid() {
+    let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+    let users = generate(42, 20, 4, now);
+
+    assert_eq!(users.len(), 20);
+    assert_eq!(users[0].email, "seed-user-1@example.com");
+    let emails: HashSet<_> = users.iter().map(|user| &user.email).collect();
+    assert_eq!(emails.len(), 20);
+    assert!(strong_password(DEFAULT_SEED_PASSWORD));
+
+    for user in &users {
+        assert_eq!(user.posts.len(), 4);
+        for post in &user.posts {
+            assert!(
+                validate_post_fields(Some(&post.title), Some(&post.content), &Limits::default())
+                    .is_ok()
+            );
+            assert!(post.created_at <= now && post.created_at > now - Duration::days(366));
+        }
+        assert!(
+            user.posts
+                .windows(2)
+                .all(|pair| pair[0].created_at <= pair[1].created_at)
+        );
+    }
+}
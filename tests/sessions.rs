@@ -0,0 +1,53 @@
+use axum_rest::helpers::sessions::{describe_device, truncate_user_agent};
+use axum_rest::model::model::{Session, SessionResponse};
+use chrono::{Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+const FIREFOX_LINUX: &str =
+    "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0";
+const CHROME_ANDROID: &str = "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Mobile Safari/537.36";
+const EDGE_WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36 Edg/126.0.0.0";
+const SAFARI_IPHONE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1";
+
+#[test]
+fn common_browsers_are_described() {
+    assert_eq!(describe_device(Some(FIREFOX_LINUX)), "Firefox on Linux");
+    assert_eq!(describe_device(Some(CHROME_ANDROID)), "Chrome on Android");
+    assert_eq!(describe_device(Some(EDGE_WINDOWS)), "Edge on Windows");
+    assert_eq!(describe_device(Some(SAFARI_IPHONE)), "Safari on iOS");
+    assert_eq!(describe_device(Some("curl/8.7.1")), "curl");
+}
+
+#[test]
+fn missing_user_agent_is_unknown() {
+    assert_eq!(describe_device(None), "Unknown device");
+    assert_eq!(describe_device(Some("  ")), "Unknown device");
+    assert_eq!(describe_device(Some("custom-client")), "Unknown device");
+}
+
+#[test]
+fn long_user_agents_are_truncated() {
+    assert_eq!(truncate_user_agent(&"a".repeat(2000)).len(), 512);
+    assert_eq!(truncate_user_agent(FIREFOX_LINUX), FIREFOX_LINUX);
+}
+
+#[test]
+fn response_marks_the_current_session() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    let session = Session {
+        id: "abc".to_string(),
+        user_id: Uuid::new_v4(),
+        device: "Firefox on Linux".to_string(),
+        ip: Some("203.0.113.7".to_string()),
+        user_agent: Some(FIREFOX_LINUX.to_string()),
+        network: Some("203.0.113.0/24".to_string()),
+        country: Some("DE".to_string()),
+        created_at: now,
+        last_seen_at: now,
+        expires_at: now + Duration::days(7),
+    };
+
+    assert!(SessionResponse::new(session.clone(), Some("abc")).current);
+    assert!(!SessionResponse::new(session.clone(), Some("other")).current);
+    assert!(!SessionResponse::new(session, None).current);
+}
@@ -0,0 +1,142 @@
+//! Runs against the database in `TEST_DATABASE_URL` and is skipped without one.
+
+mod common;
+
+use axum_rest::db::repositories::share_link_repo::ShareLinkRepository;
+use axum_rest::helpers::link_tokens::{hash_link_token, new_link_token};
+use chrono::{DateTime, TimeZone, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use common::with_test_db;
+
+/// A private post by a new author; returns the author and post IDs
+async fn private_post(pool: &PgPool) -> (Uuid, Uuid) {
+    let (author_id, post_id) = (Uuid::new_v4(), Uuid::new_v4());
+    sqlx::query(
+        "INSERT INTO users (id, public_id, name, email, password) VALUES ($1, $2, 'Author', $3, 'x')",
+    )
+    .bind(author_id)
+    .bind(author_id.simple().to_string())
+    .bind(format!("{}@example.com", author_id.simple()))
+    .execute(pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO posts (id, public_id, title, content, author_id, visibility) VALUES ($1, $2, 'Shared', 'Body', $3, 'PRIVATE')",
+    )
+    .bind(post_id)
+    .bind(post_id.simple().to_string())
+    .bind(author_id)
+    .execute(pool)
+    .await
+    .unwrap();
+    (author_id, post_id)
+}
+
+fn at(hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap()
+}
+
+#[tokio::test]
+async fn links_are_listed_and_revoked_per_post() {
+    with_test_db(|pool| async move {
+        let (author_id, post_id) = private_post(&pool).await;
+        let (_, other_post_id) = private_post(&pool).await;
+        let repo = ShareLinkRepository::new(pool);
+
+        let token = new_link_token();
+        let link = repo
+            .insert(
+                post_id,
+                &hash_link_token(&token),
+                author_id,
+                at(0),
+                at(2),
+                Some(3),
+            )
+            .await
+            .unwrap();
+        assert_eq!(link.uses, 0);
+        assert_eq!(link.max_uses, Some(3));
+        assert_eq!(link.expires_at, at(2));
+
+        let listed = repo.list(post_id).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, link.id);
+        assert!(repo.list(other_post_id).await.unwrap().is_empty());
+
+        // Only through the post it belongs to
+        assert!(!repo.revoke(other_post_id, link.id).await.unwrap());
+        assert!(repo.revoke(post_id, link.id).await.unwrap());
+        assert!(!repo.revoke(post_id, link.id).await.unwrap());
+        assert!(
+            !repo
+                .redeem(post_id, &hash_link_token(&token), at(1))
+                .await
+                .unwrap()
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn links_stop_working_when_they_expire() {
+    with_test_db(|pool| async move {
+        let (author_id, post_id) = private_post(&pool).await;
+        let repo = ShareLinkRepository::new(pool);
+
+        let hash = hash_link_token(&new_link_token());
+        repo.insert(post_id, &hash, author_id, at(0), at(2), None)
+            .await
+            .unwrap();
+
+        assert!(repo.redeem(post_id, &hash, at(1)).await.unwrap());
+        assert!(!repo.redeem(post_id, &hash, at(2)).await.unwrap());
+        assert!(!repo.redeem(post_id, &hash, at(3)).await.unwrap());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn links_stop_working_when_their_uses_run_out() {
+    with_test_db(|pool| async move {
+        let (author_id, post_id) = private_post(&pool).await;
+        let repo = ShareLinkRepository::new(pool);
+
+        let hash = hash_link_token(&new_link_token());
+        repo.insert(post_id, &hash, author_id, at(0), at(2), Some(2))
+            .await
+            .unwrap();
+
+        assert!(repo.redeem(post_id, &hash, at(1)).await.unwrap());
+        assert!(repo.redeem(post_id, &hash, at(1)).await.unwrap());
+        assert!(!repo.redeem(post_id, &hash, at(1)).await.unwrap());
+        assert_eq!(repo.list(post_id).await.unwrap()[0].uses, 2);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn links_only_open_their_own_post() {
+    with_test_db(|pool| async move {
+        let (author_id, post_id) = private_post(&pool).await;
+        let (_, other_post_id) = private_post(&pool).await;
+        let repo = ShareLinkRepository::new(pool);
+
+        let hash = hash_link_token(&new_link_token());
+        repo.insert(post_id, &hash, author_id, at(0), at(2), None)
+            .await
+            .unwrap();
+
+        assert!(!repo.redeem(other_post_id, &hash, at(1)).await.unwrap());
+        assert!(
+            !repo
+                .redeem(post_id, &hash_link_token(&new_link_token()), at(1))
+                .await
+                .unwrap()
+        );
+        assert_eq!(repo.list(post_id).await.unwrap()[0].uses, 0);
+    })
+    .await;
+}
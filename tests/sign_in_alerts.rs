@@ -0,0 +1,166 @@
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::http::{HeaderMap, HeaderValue};
+use axum_rest::config::AppConfig;
+use axum_rest::helpers::clock::{Clock, MockClock};
+use axum_rest::helpers::email_templates::EmailTemplates;
+use axum_rest::helpers::feature_flags::FeatureFlags;
+use axum_rest::helpers::link_tokens::{LinkGrant, LinkPurpose, LinkTokenStore, MemoryLinkTokens};
+use axum_rest::helpers::mailer::MemoryMailer;
+use axum_rest::helpers::sign_in_alerts::{SignInAlerts, coarse_network, country_from};
+use axum_rest::model::model::{FeatureFlagSet, Role, Session, User};
+use chrono::Duration;
+use uuid::Uuid;
+
+fn session(clock: &MockClock) -> Session {
+    let now = clock.now();
+    Session {
+        id: "abc".to_string(),
+        user_id: Uuid::new_v4(),
+        device: "Firefox on Linux".to_string(),
+        ip: Some("203.0.113.7".to_string()),
+        user_agent: Some("Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Firefox/128.0".to_string()),
+        network: Some("203.0.113.0/24".to_string()),
+        country: Some("DE".to_string()),
+        created_at: now,
+        last_seen_at: now,
+        expires_at: now + Duration::days(7),
+    }
+}
+
+fn user(id: Uuid) -> User {
+    User {
+        id,
+        public_id: "ada".to_string(),
+        name: "Ada".to_string(),
+        username: None,
+        email: "ada@example.com".to_string(),
+        password: String::new(),
+        role: Role::USER,
+        email_verified: true,
+        avatar_url: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    }
+}
+
+fn alerts(
+    mailer: Arc<MemoryMailer>,
+    tokens: Arc<MemoryLinkTokens>,
+    email_sending: bool,
+) -> SignInAlerts {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates/email");
+    SignInAlerts {
+        flags: Arc::new(FeatureFlags::new(FeatureFlagSet {
+            email_sending,
+            ..FeatureFlagSet::default()
+        })),
+        mailer,
+        tokens,
+        emails: Arc::new(EmailTemplates::load(&dir, "en").unwrap()),
+        config: Arc::new(AppConfig::from_env()),
+        clock: Arc::new(MockClock::default()),
+    }
+}
+
+/// The token in the revoke link of an alert's text part
+fn revoke_token(text: &str) -> &str {
+    text.split("revoke-session?token=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap_or_else(|| panic!("no revoke link in {}", text))
+}
+
+#[test]
+fn networks_are_coarse() {
+    let ipv4: IpAddr = "203.0.113.7".parse().unwrap();
+    let ipv6: IpAddr = "2001:db8:85a3:8d3:1319:8a2e:370:7348".parse().unwrap();
+
+    assert_eq!(coarse_network(ipv4), "203.0.113.0/24");
+    assert_eq!(coarse_network(ipv6), "2001:db8:85a3::/48");
+}
+
+#[test]
+fn countries_come_only_from_the_configured_header() {
+    let mut headers = HeaderMap::new();
+    headers.insert("cf-ipcountry", HeaderValue::from_static("de"));
+
+    assert_eq!(
+        country_from(&headers, Some("CF-IPCountry")).as_deref(),
+        Some("DE")
+    );
+    assert_eq!(country_from(&headers, None), None);
+
+    headers.insert("cf-ipcountry", HeaderValue::from_static("XX"));
+    assert_eq!(country_from(&headers, Some("CF-IPCountry")), None);
+}
+
+#[tokio::test]
+async fn alerts_describe_the_device_and_link_to_revoke_it() {
+    let mailer = Arc::new(MemoryMailer::new());
+    let tokens = Arc::new(MemoryLinkTokens::new());
+    let clock = MockClock::default();
+    let session = session(&clock);
+
+    alerts(mailer.clone(), tokens.clone(), true)
+        .send(&user(session.user_id), None, &session)
+        .await;
+
+    let email = mailer.last_to("ada@example.com").unwrap();
+    assert_eq!(email.subject, "New sign-in to your account");
+    assert!(
+        email.text.contains("Device: Firefox on Linux"),
+        "{}",
+        email.text
+    );
+    assert!(email.text.contains("203.0.113.7 (DE)"), "{}", email.text);
+    let token = revoke_token(&email.text);
+
+    // The link only signs out this session, and only once
+    let revoke = || tokens.redeem(token, LinkPurpose::RevokeSession, clock.now());
+    assert_eq!(
+        revoke().await.unwrap(),
+        Some(LinkGrant {
+            user_id: session.user_id,
+            subject: Some("abc".to_string()),
+        })
+    );
+    assert_eq!(revoke().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn revoke_links_last_as_long_as_the_session() {
+    let mailer = Arc::new(MemoryMailer::new());
+    let tokens = Arc::new(MemoryLinkTokens::new());
+    let clock = MockClock::default();
+    let session = session(&clock);
+
+    alerts(mailer.clone(), tokens.clone(), true)
+        .send(&user(session.user_id), None, &session)
+        .await;
+    let email = mailer.last_to("ada@example.com").unwrap();
+    let token = revoke_token(&email.text);
+
+    let later = session.expires_at + Duration::seconds(1);
+    assert_eq!(
+        tokens
+            .redeem(token, LinkPurpose::RevokeSession, later)
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn no_alert_is_sent_while_email_sending_is_off() {
+    let mailer = Arc::new(MemoryMailer::new());
+    let session = session(&MockClock::default());
+
+    alerts(mailer.clone(), Arc::new(MemoryLinkTokens::new()), false)
+        .send(&user(session.user_id), None, &session)
+        .await;
+
+    assert!(mailer.sent().is_empty());
+}
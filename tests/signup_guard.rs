@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use anyhow::anyhow;
+use axum_rest::helpers::clock::MockClock;
+use axum_rest::helpers::signup_guard::{
+    CaptchaVerifier, SignupGuard, SignupRejection, VerifyFuture, parse_domain_list,
+};
+use axum_rest::model::model::SignupProof;
+use chrono::Duration;
+
+/// Accepts `pass`, refuses anything else and fails on `down`
+struct FakeCaptcha;
+
+impl CaptchaVerifier for FakeCaptcha {
+    fn name(&self) -> &'static str {
+        "turnstile"
+    }
+
+    fn verify<'a>(&'a self, token: &'a str, _remote_ip: Option<IpAddr>) -> VerifyFuture<'a> {
+        Box::pin(async move {
+            match token {
+                "pass" => Ok(true),
+                "down" => Err(anyhow!("connection refused")),
+                _ => Ok(false),
+            }
+        })
+    }
+}
+
+fn proof() -> SignupProof {
+    SignupProof::default()
+}
+
+#[tokio::test]
+async fn disabled_checks_let_everyone_in() {
+    let clock = MockClock::default();
+    let proof = SignupProof {
+        website: Some("https://spam.example".to_string()),
+        ..proof()
+    };
+
+    let result = SignupGuard::disabled()
+        .check("ada@example.com", &proof, None, &clock)
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn the_honeypot_must_stay_empty() {
+    let clock = MockClock::default();
+    let guard = SignupGuard::disabled().with_honeypot(true);
+
+    let filled = SignupProof {
+        website: Some("https://spam.example".to_string()),
+        ..proof()
+    };
+    let result = guard.check("ada@example.com", &filled, None, &clock).await;
+    assert!(matches!(result, Err(SignupRejection::Honeypot)));
+
+    let empty = SignupProof {
+        website: Some(String::new()),
+        ..proof()
+    };
+    assert!(
+        guard
+            .check("ada@example.com", &empty, None, &clock)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn forms_sent_too_quickly_are_refused() {
+    let clock = MockClock::default();
+    let guard = SignupGuard::disabled().with_min_fill_time(Some(StdDuration::from_secs(5)));
+
+    let form = guard.form(&clock).unwrap();
+    assert_eq!(form.min_fill_secs, 5);
+    let proof = SignupProof {
+        form_token: form.form_token,
+        ..proof()
+    };
+
+    clock.advance(Duration::seconds(2));
+    let result = guard.check("ada@example.com", &proof, None, &clock).await;
+    assert!(matches!(result, Err(SignupRejection::TooFast)));
+
+    clock.advance(Duration::seconds(3));
+    assert!(
+        guard
+            .check("ada@example.com", &proof, None, &clock)
+            .await
+            .is_ok()
+    );
+
+    // A form left open longer than a token lasts must be reloaded
+    clock.advance(Duration::hours(7));
+    let result = guard.check("ada@example.com", &proof, None, &clock).await;
+    assert!(matches!(result, Err(SignupRejection::InvalidFormToken)));
+}
+
+#[tokio::test]
+async fn the_timer_needs_a_genuine_form_token() {
+    let clock = MockClock::default();
+    let guard = SignupGuard::disabled().with_min_fill_time(Some(StdDuration::from_secs(5)));
+
+    let result = guard.check("ada@example.com", &proof(), None, &clock).await;
+    assert!(matches!(result, Err(SignupRejection::MissingFormToken)));
+
+    let forged = SignupProof {
+        form_token: Some("not-a-token".to_string()),
+        ..proof()
+    };
+    let result = guard.check("ada@example.com", &forged, None, &clock).await;
+    assert!(matches!(result, Err(SignupRejection::InvalidFormToken)));
+}
+
+#[test]
+fn forms_only_carry_tokens_when_they_are_timed() {
+    let clock = MockClock::default();
+    let form = SignupGuard::disabled()
+        .with_honeypot(true)
+        .form(&clock)
+        .unwrap();
+
+    assert!(form.form_token.is_none());
+    assert_eq!(form.min_fill_secs, 0);
+    assert!(form.honeypot);
+    assert!(form.captcha.is_none());
+}
+
+#[test]
+fn domain_lists_skip_comments_and_blank_lines() {
+    let list = parse_domain_list(
+        "# Throwaway inboxes\nTrashMail.example\n\nburner.example # and its subdomains\n",
+    );
+
+    assert_eq!(
+        list,
+        HashSet::from([
+            "trashmail.example".to_string(),
+            "burner.example".to_string()
+        ])
+    );
+}
+
+#[tokio::test]
+async fn listed_domains_and_their_subdomains_are_refused() {
+    let clock = MockClock::default();
+    let guard =
+        SignupGuard::disabled().with_disposable_domains(parse_domain_list("burner.example\n"));
+
+    for email in ["bot@burner.example", "bot@mx.Burner.example"] {
+        let result = guard.check(email, &proof(), None, &clock).await;
+        assert!(
+            matches!(result, Err(SignupRejection::DisposableEmail(ref domain)) if domain.ends_with("burner.example")),
+            "{} got through",
+            email
+        );
+    }
+    assert!(
+        guard
+            .check("ada@notburner.example", &proof(), None, &clock)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn captchas_are_checked_and_fail_closed() {
+    let clock = MockClock::default();
+    let guard = SignupGuard::disabled().with_captcha(Arc::new(FakeCaptcha), "site-key".to_string());
+    let with_token = |token: &str| SignupProof {
+        captcha_token: Some(token.to_string()),
+        ..proof()
+    };
+
+    let widget = guard.form(&clock).unwrap().captcha.unwrap();
+    assert_eq!(widget.provider, "turnstile");
+    assert_eq!(widget.site_key, "site-key");
+
+    let result = guard.check("ada@example.com", &proof(), None, &clock).await;
+    assert!(matches!(result, Err(SignupRejection::MissingCaptcha)));
+
+    let result = guard
+        .check("ada@example.com", &with_token("wrong"), None, &clock)
+        .await;
+    assert!(matches!(result, Err(SignupRejection::FailedCaptcha)));
+
+    let result = guard
+        .check("ada@example.com", &with_token("down"), None, &clock)
+        .await;
+    assert!(matches!(
+        result,
+        Err(SignupRejection::CaptchaUnavailable(_))
+    ));
+
+    assert!(
+        guard
+            .check("ada@example.com", &with_token("pass"), None, &clock)
+            .await
+            .is_ok()
+    );
+}
+
+#[test]
+fn bot_checks_give_nothing_away() {
+    assert_eq!(
+        SignupRejection::Honeypot.message(),
+        SignupRejection::TooFast.message()
+    );
+}
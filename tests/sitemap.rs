@@ -0,0 +1,61 @@
+use axum_rest::helpers::sitemap::{
+    MAX_SITEMAP_URLS, SitemapSection, SitemapUrl, index_pages, page_count, render_index,
+    render_urlset,
+};
+use chrono::{TimeZone, Utc};
+
+#[test]
+fn small_sites_fit_in_one_sitemap() {
+    assert!(index_pages(0, 0).is_empty());
+    assert!(index_pages(MAX_SITEMAP_URLS - 10, 10).is_empty());
+}
+
+#[test]
+fn large_sites_get_an_index_of_pages() {
+    assert_eq!(page_count(0), 0);
+    assert_eq!(page_count(MAX_SITEMAP_URLS), 1);
+    assert_eq!(page_count(MAX_SITEMAP_URLS + 1), 2);
+
+    assert_eq!(
+        index_pages(MAX_SITEMAP_URLS + 1, 5),
+        [
+            (SitemapSection::Posts, 1),
+            (SitemapSection::Posts, 2),
+            (SitemapSection::Profiles, 1),
+        ]
+    );
+}
+
+#[test]
+fn page_files_round_trip() {
+    let file = SitemapSection::Profiles.file_name(3);
+    assert_eq!(file, "profiles-3.xml");
+    assert_eq!(
+        SitemapSection::from_file_name(&file),
+        Some((SitemapSection::Profiles, 3))
+    );
+
+    assert_eq!(SitemapSection::from_file_name("posts-0.xml"), None);
+    assert_eq!(SitemapSection::from_file_name("posts-1.atom"), None);
+    assert_eq!(SitemapSection::from_file_name("users-1.xml"), None);
+}
+
+#[test]
+fn urls_are_escaped_with_their_lastmod() {
+    let xml = render_urlset(&[SitemapUrl {
+        loc: "https://blog.example.com/posts?id=a&b".to_string(),
+        lastmod: Utc.with_ymd_and_hms(2024, 2, 1, 12, 0, 0).unwrap(),
+    }]);
+
+    assert!(xml.contains("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">"));
+    assert!(xml.contains(
+        "<url><loc>https://blog.example.com/posts?id=a&amp;b</loc><lastmod>2024-02-01T12:00:00Z</lastmod></url>"
+    ));
+
+    let index = render_index(&["https://blog.example.com/sitemaps/posts-1.xml".to_string()]);
+    assert!(
+        index.contains(
+            "<sitemap><loc>https://blog.example.com/sitemaps/posts-1.xml</loc></sitemap>"
+        )
+    );
+}
@@ -0,0 +1,100 @@
+use axum_rest::config::{DEFAULT_RESERVED_SLUGS, SlugConfig};
+use axum_rest::helpers::slugs::{is_reserved, slugify, validate_slug, with_suffix};
+
+fn config() -> SlugConfig {
+    SlugConfig {
+        transliterate: true,
+        max_length: 40,
+        stop_words: vec!["a".to_string(), "the".to_string(), "of".to_string()],
+        reserved: DEFAULT_RESERVED_SLUGS
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    }
+}
+
+#[test]
+fn titles_become_hyphenated_lowercase_words() {
+    assert_eq!(
+        slugify("Hello, World! 2024 Edition", &config()),
+        "hello-world-2024-edition"
+    );
+    assert_eq!(slugify("  --Rust   &  Axum--  ", &config()), "rust-axum");
+}
+
+#[test]
+fn stop_words_are_dropped_unless_nothing_else_is_left() {
+    assert_eq!(slugify("The Lord of the Rings", &config()), "lord-rings");
+    assert_eq!(slugify("The A", &config()), "the-a");
+}
+
+#[test]
+fn non_latin_titles_are_transliterated() {
+    assert_eq!(
+        slugify("Crème brûlée à Paris", &config()),
+        "creme-brulee-paris"
+    );
+    assert_eq!(slugify("Straße", &config()), "strasse");
+}
+
+#[test]
+fn without_transliteration_unicode_letters_are_kept() {
+    let config = SlugConfig {
+        transliterate: false,
+        ..config()
+    };
+
+    assert_eq!(slugify("Crème Brûlée", &config), "crème-brûlée");
+    assert!(validate_slug("crème-brûlée", &config).is_ok());
+}
+
+#[test]
+fn titles_without_usable_characters_fall_back() {
+    assert_eq!(slugify("!!! ???", &config()), "post");
+}
+
+#[test]
+fn long_titles_are_cut_at_a_word_boundary() {
+    let slug = slugify(
+        "An unreasonably long title that keeps going well past the limit",
+        &config(),
+    );
+
+    assert!(slug.len() <= 40);
+    assert_eq!(slug, "an-unreasonably-long-title-that-keeps");
+}
+
+#[test]
+fn suffixes_fit_within_the_limit() {
+    let config = config();
+    let base = "an-unreasonably-long-title-that-keeps";
+
+    let slug = with_suffix(base, "Ab_3-xYz9Q", &config);
+
+    assert!(slug.len() <= config.max_length);
+    assert!(slug.ends_with("-ab3xyz"));
+}
+
+#[test]
+fn reserved_names_are_refused_in_any_case() {
+    let config = config();
+
+    for name in ["admin", "API", "auth", "Docs"] {
+        assert!(is_reserved(name, &config), "{} should be reserved", name);
+    }
+    assert!(!is_reserved("admins", &config));
+    assert!(validate_slug("admin", &config).is_err());
+}
+
+#[test]
+fn custom_slugs_are_validated() {
+    let config = config();
+
+    assert!(validate_slug("my-first-post", &config).is_ok());
+    assert!(validate_slug("", &config).is_err());
+    assert!(validate_slug("Upper-Case", &config).is_err());
+    assert!(validate_slug("spaces here", &config).is_err());
+    assert!(validate_slug("-leading", &config).is_err());
+    assert!(validate_slug("double--hyphen", &config).is_err());
+    assert!(validate_slug(&"x".repeat(41), &config).is_err());
+}
@@ -0,0 +1,115 @@
+use std::path::Path;
+
+#[cfg(not(any(feature = "xml", feature = "msgpack")))]
+use axum_rest::docs::ApiDoc;
+use axum_rest::helpers::avatar::gravatar_url;
+use axum_rest::helpers::email_templates::{EmailKind, EmailTemplates};
+use axum_rest::helpers::redaction::{Audience, SeenBy};
+use axum_rest::helpers::response::{UnifiedResponse, error_response_generic, success_response};
+use axum_rest::model::model::{PostResponse, PublicAuthor, Role, UserResponse, Visibility};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+fn fixed_time() -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn sample_user() -> UserResponse {
+    UserResponse {
+        internal_id: Uuid::nil(),
+        id: "V1StGXR8_Z5j".to_string(),
+        name: "Ada Lovelace".to_string(),
+        username: Some("ada_lovelace".to_string()),
+        email: "ada@example.com".to_string(),
+        role: Role::USER,
+        email_verified: true,
+        avatar_url: gravatar_url("ada@example.com"),
+        created_at: fixed_time(),
+        updated_at: fixed_time(),
+        email_delivery: None,
+    }
+}
+
+#[test]
+fn user_success_envelope() {
+    let owner = Audience::user(Uuid::nil(), Role::USER);
+    let response: UnifiedResponse<SeenBy<UserResponse>> = success_response(
+        "Profile Retrieved".to_string(),
+        SeenBy(owner, sample_user()),
+    );
+    insta::assert_json_snapshot!(response);
+}
+
+#[test]
+fn post_success_envelope() {
+    let post = PostResponse {
+        id: "IRFa-VaY2b3x".to_string(),
+        slug: Some("hello".to_string()),
+        title: "Hello".to_string(),
+        content: "First post".to_string(),
+        content_html: Some("<p>First post</p>\n".to_string()),
+        license: Some("CC-BY-4.0".to_string()),
+        word_count: 2,
+        reading_time_minutes: 1,
+        excerpt: "First post".to_string(),
+        author: PublicAuthor {
+            id: "V1StGXR8_Z5j".to_string(),
+            name: "Ada Lovelace".to_string(),
+            username: Some("ada_lovelace".to_string()),
+            avatar_url: gravatar_url("ada@example.com"),
+        },
+        view_count: 42,
+        created_at: fixed_time(),
+        updated_at: fixed_time(),
+        archived_at: None,
+        visibility: Visibility::PUBLIC,
+        mentions: Vec::new(),
+        collaborators: Vec::new(),
+    };
+    let response = success_response("Post Retrieved".to_string(), post);
+    insta::assert_json_snapshot!(response);
+}
+
+#[test]
+fn error_envelope() {
+    let response: UnifiedResponse<UserResponse> = error_response_generic(
+        "Login Failed".to_string(),
+        "Invalid email or password".to_string(),
+    );
+    insta::assert_json_snapshot!(response);
+}
+
+// The response formats behind `xml` and `msgpack` add content types to every
+// success response, so the spec is only pinned for the default formats
+#[cfg(not(any(feature = "xml", feature = "msgpack")))]
+#[test]
+fn openapi_spec() {
+    insta::assert_json_snapshot!(ApiDoc::with_security());
+}
+
+fn render_email(kind: EmailKind, link: &str) -> String {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates/email");
+    EmailTemplates::load(&dir, "en")
+        .unwrap()
+        .render(kind, None, &[("name", "Ada"), ("link", link)])
+        .unwrap()
+        .html
+}
+
+#[test]
+fn verify_email_html() {
+    insta::assert_snapshot!(render_email(
+        EmailKind::VerifyEmail,
+        "http://localhost:8080/auth/verify-email?token=TOKEN"
+    ));
+}
+
+#[test]
+fn reset_password_html() {
+    insta::assert_snapshot!(render_email(
+        EmailKind::ResetPassword,
+        "http://localhost:8080/auth/reset-password?token=TOKEN"
+    ));
+}
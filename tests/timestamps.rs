@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Method, Request, StatusCode, header},
+    middleware,
+    routing::get,
+};
+use axum_rest::config::{CorsConfig, Environment};
+use axum_rest::helpers::response::success_response;
+use axum_rest::helpers::timestamps::{TimestampFormat, timestamps_middleware};
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tower::ServiceExt;
+
+#[derive(Serialize)]
+struct Event {
+    #[serde(serialize_with = "axum_rest::helpers::timestamps::serialize")]
+    at: DateTime<Utc>,
+    #[serde(serialize_with = "axum_rest::helpers::timestamps::serialize_option")]
+    ended_at: Option<DateTime<Utc>>,
+}
+
+fn event() -> Event {
+    Event {
+        at: Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap(),
+        ended_at: None,
+    }
+}
+
+#[test]
+fn formats_and_offsets_parse() {
+    let offset = |secs| TimestampFormat::Rfc3339 {
+        offset: FixedOffset::east_opt(secs).unwrap(),
+    };
+
+    assert_eq!(TimestampFormat::parse(None, None), Ok(offset(0)));
+    assert_eq!(
+        TimestampFormat::parse(Some("RFC3339"), Some("utc")),
+        Ok(offset(0))
+    );
+    assert_eq!(
+        TimestampFormat::parse(None, Some("+05:30")),
+        Ok(offset(5 * 3600 + 30 * 60))
+    );
+    assert_eq!(
+        TimestampFormat::parse(Some("rfc3339"), Some("-08:00")),
+        Ok(offset(-8 * 3600))
+    );
+    assert_eq!(
+        TimestampFormat::parse(Some("epoch_ms"), None),
+        Ok(TimestampFormat::EpochMillis)
+    );
+}
+
+#[test]
+fn nonsense_is_rejected() {
+    assert!(TimestampFormat::parse(Some("unix"), None).is_err());
+    assert!(TimestampFormat::parse(None, Some("Europe/Paris")).is_err());
+    assert!(TimestampFormat::parse(Some("epoch_ms"), Some("+01:00")).is_err());
+}
+
+#[test]
+fn outside_a_response_timestamps_keep_the_default_form() {
+    let json = serde_json::to_value(event()).unwrap();
+
+    assert_eq!(json["at"], serde_json::to_value(event().at).unwrap());
+    assert_eq!(json["ended_at"], Value::Null);
+}
+
+async fn call(uri: &str, headers: &[(&str, &str)]) -> (StatusCode, Value) {
+    let router = Router::new()
+        .route(
+            "/event",
+            get(|| async { success_response("Event".to_string(), event()) }),
+        )
+        .layer(middleware::from_fn(timestamps_middleware));
+    let mut request = Request::builder().uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = router
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn the_default_is_utc_rfc3339() {
+    let (status, body) = call("/event", &[]).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["at"], "2025-03-01T09:00:00Z");
+}
+
+#[tokio::test]
+async fn epoch_millis_are_numbers() {
+    let (_, body) = call("/event?timestamps=epoch_ms", &[]).await;
+    assert_eq!(body["data"]["at"], 1_740_819_600_000_i64);
+    assert_eq!(body["data"]["ended_at"], Value::Null);
+
+    let (_, body) = call("/event", &[("x-timestamp-format", "epoch_ms")]).await;
+    assert_eq!(body["data"]["at"], 1_740_819_600_000_i64);
+}
+
+#[tokio::test]
+async fn offsets_shift_the_wall_clock() {
+    let (_, body) = call("/event?tz=-05:00", &[]).await;
+    assert_eq!(body["data"]["at"], "2025-03-01T04:00:00-05:00");
+
+    // An unescaped `+` arrives as a space
+    let (_, body) = call("/event?tz=+05:30", &[]).await;
+    assert_eq!(body["data"]["at"], "2025-03-01T14:30:00+05:30");
+
+    let (_, body) = call("/event", &[("x-timezone", "+01:00")]).await;
+    assert_eq!(body["data"]["at"], "2025-03-01T10:00:00+01:00");
+}
+
+#[tokio::test]
+async fn invalid_choices_are_a_400() {
+    let (status, body) = call("/event?tz=Mars/Olympus", &[]).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["title"], "Invalid Timestamp Format");
+}
+
+#[tokio::test]
+async fn production_cors_allows_the_timestamp_headers() {
+    let cors = CorsConfig {
+        allowed_origins: vec!["https://app.example.com".to_string()],
+        allow_credentials: true,
+        exposed_headers: Vec::new(),
+        max_age: Duration::from_secs(600),
+    };
+    let app = Router::new()
+        .route("/events", get(|| async { "events" }))
+        .layer(cors.layer(Environment::Production));
+
+    let request = Request::builder()
+        .method(Method::OPTIONS)
+        .uri("/events")
+        .header(header::ORIGIN, "https://app.example.com")
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+        .header(
+            header::ACCESS_CONTROL_REQUEST_HEADERS,
+            "x-timestamp-format,x-timezone",
+        )
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let allowed = response.headers()[header::ACCESS_CONTROL_ALLOW_HEADERS]
+        .to_str()
+        .unwrap();
+    assert!(allowed.contains("x-timestamp-format"), "{}", allowed);
+    assert!(allowed.contains("x-timezone"), "{}", allowed);
+}
@@ -0,0 +1,148 @@
+use axum_rest::config::TokenConfig;
+use axum_rest::helpers::auth::{AuthHelper, JWT_SECRET, TOKEN_LEEWAY_SECS};
+use axum_rest::helpers::clock::{Clock, MockClock, SystemClock};
+use axum_rest::model::model::{Claims, Role};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use proptest::prelude::*;
+use uuid::Uuid;
+
+fn sign(claims: &Claims) -> String {
+    encode(
+        &Header::default(),
+        claims,
+        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+fn role_strategy() -> impl Strategy<Value = Role> {
+    prop_oneof![Just(Role::USER), Just(Role::ADMIN)]
+}
+
+proptest! {
+    #[test]
+    fn generated_tokens_round_trip(bytes in any::<[u8; 16]>(), role in role_strategy()) {
+        let user_id = Uuid::from_bytes(bytes);
+        let (auth_token, refresh_token) =
+            AuthHelper::generate_token(user_id, role.clone(), &TokenConfig::default(), &SystemClock).unwrap();
+
+        for token in [auth_token, refresh_token] {
+            let claims = AuthHelper::validate_token(&token, &SystemClock).unwrap();
+            prop_assert_eq!(&claims.sub, &user_id.to_string());
+            prop_assert_eq!(&claims.role, &role);
+            prop_assert!(claims.exp > claims.iat);
+            prop_assert_eq!(
+                AuthHelper::extract_user_id_from_token(&token, &SystemClock).unwrap(),
+                user_id
+            );
+        }
+    }
+
+    #[test]
+    fn tampered_tokens_are_rejected(
+        index in any::<prop::sample::Index>(),
+        replacement in "[A-Za-z0-9_-]",
+    ) {
+        let (token, _) =
+            AuthHelper::generate_token(Uuid::new_v4(), Role::USER, &TokenConfig::default(), &SystemClock).unwrap();
+        let position = index.index(token.len());
+        prop_assume!(&token[position..position + 1] != ".");
+        prop_assume!(token[position..position + 1] != replacement);
+
+        let mut tampered = token.clone();
+        tampered.replace_range(position..position + 1, &replacement);
+        prop_assert!(AuthHelper::validate_token(&tampered, &SystemClock).is_err());
+    }
+
+    #[test]
+    fn expired_tokens_respect_leeway(seconds_ago in 0i64..3600) {
+        let clock = MockClock::new(Utc::now());
+        let now = clock.now().timestamp();
+        let claims = Claims {
+            iss: "localhost".to_string(),
+            sub: Uuid::new_v4().to_string(),
+            role: Role::USER,
+            iat: (now - 7200) as usize,
+            exp: (now - seconds_ago) as usize,
+            sid: None,
+            jti: None,
+            act: None,
+        };
+
+        let result = AuthHelper::validate_token(&sign(&claims), &clock);
+        prop_assert_eq!(result.is_ok(), seconds_ago as usize <= TOKEN_LEEWAY_SECS);
+    }
+
+    #[test]
+    fn tokens_issued_by_a_fast_clock_are_accepted(skew in 0i64..300) {
+        let clock = MockClock::new(Utc::now());
+        let issuer = MockClock::new(clock.now() + Duration::seconds(skew));
+
+        let (token, _) = AuthHelper::generate_token(Uuid::new_v4(), Role::ADMIN, &TokenConfig::default(), &issuer).unwrap();
+        let decoded = AuthHelper::validate_token(&token, &clock).unwrap();
+        prop_assert_eq!(decoded.iat, issuer.timestamp());
+        prop_assert_eq!(decoded.role, Role::ADMIN);
+    }
+
+    #[test]
+    fn auth_tokens_expire_after_a_day(extra_minutes in 2i64..600) {
+        let clock = MockClock::new(Utc::now());
+        let (auth_token, refresh_token) =
+            AuthHelper::generate_token(Uuid::new_v4(), Role::USER, &TokenConfig::default(), &clock).unwrap();
+
+        clock.advance(Duration::hours(24) + Duration::minutes(extra_minutes));
+        prop_assert!(AuthHelper::validate_token(&auth_token, &clock).is_err());
+        prop_assert!(AuthHelper::validate_token(&refresh_token, &clock).is_ok());
+    }
+}
+
+#[test]
+fn impersonation_tokens_name_the_admin_and_expire_quickly() {
+    let clock = MockClock::new(Utc::now());
+    let (user_id, admin_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let token = AuthHelper::generate_impersonation_token(
+        user_id,
+        Role::USER,
+        admin_id,
+        "session",
+        Duration::minutes(15),
+        &clock,
+    )
+    .unwrap();
+
+    let claims = AuthHelper::validate_token(&token, &clock).unwrap();
+    assert_eq!(claims.sub, user_id.to_string());
+    assert_eq!(claims.sid.as_deref(), Some("session"));
+    assert_eq!(
+        AuthHelper::extract_impersonator_from_token(&token, &clock).unwrap(),
+        Some(admin_id)
+    );
+
+    let (ordinary, _) =
+        AuthHelper::generate_token(user_id, Role::USER, &TokenConfig::default(), &clock).unwrap();
+    assert_eq!(
+        AuthHelper::extract_impersonator_from_token(&ordinary, &clock).unwrap(),
+        None
+    );
+
+    clock.advance(Duration::minutes(17));
+    assert!(AuthHelper::validate_token(&token, &clock).is_err());
+}
+
+#[test]
+fn token_lifetimes_come_from_the_config() {
+    let clock = MockClock::new(Utc::now());
+    let tokens = TokenConfig {
+        access_ttl: std::time::Duration::from_secs(10 * 60),
+        refresh_ttl: std::time::Duration::from_secs(60 * 60),
+        email_verification_ttl: std::time::Duration::from_secs(5 * 60),
+    };
+    let (auth_token, refresh_token) =
+        AuthHelper::generate_token(Uuid::new_v4(), Role::USER, &tokens, &clock).unwrap();
+
+    let exp = |token: &str| AuthHelper::validate_token(token, &clock).unwrap().exp;
+    let now = clock.timestamp();
+    assert_eq!(exp(&auth_token), now + 10 * 60);
+    assert_eq!(exp(&refresh_token), now + 60 * 60);
+}
@@ -0,0 +1,103 @@
+//! Runs against the database in `TEST_DATABASE_URL` and is skipped without one.
+
+mod common;
+
+use axum_rest::db::repositories::user_repo::UserRepository;
+use chrono::{DateTime, TimeZone, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use common::with_test_db;
+
+fn at(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap()
+}
+
+async fn account(pool: &PgPool, verified: bool, created_at: DateTime<Utc>) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, public_id, name, email, password, email_verified, created_at) VALUES ($1, $2, 'Someone', $3, 'x', $4, $5)",
+    )
+    .bind(id)
+    .bind(id.simple().to_string())
+    .bind(format!("{}@example.com", id.simple()))
+    .bind(verified)
+    .bind(created_at)
+    .execute(pool)
+    .await
+    .unwrap();
+    id
+}
+
+/// An unverified account with a post, like an imported WordPress author
+async fn author(pool: &PgPool, created_at: DateTime<Utc>) -> Uuid {
+    let author_id = account(pool, false, created_at).await;
+    let post_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO posts (id, public_id, title, content, author_id) VALUES ($1, $2, 'Imported', 'Body', $3)",
+    )
+    .bind(post_id)
+    .bind(post_id.simple().to_string())
+    .bind(author_id)
+    .execute(pool)
+    .await
+    .unwrap();
+    author_id
+}
+
+async fn name(pool: &PgPool, id: Uuid) -> Option<String> {
+    sqlx::query_scalar("SELECT name FROM users WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn anonymizing_spares_verified_recent_and_posting_accounts() {
+    with_test_db(|pool| async move {
+        let stale = account(&pool, false, at(1)).await;
+        let verified = account(&pool, true, at(1)).await;
+        let recent = account(&pool, false, at(20)).await;
+        let author = author(&pool, at(1)).await;
+        let repo = UserRepository::new(pool.clone());
+
+        assert_eq!(
+            repo.anonymize_unverified_before(at(10), at(30))
+                .await
+                .unwrap(),
+            1
+        );
+
+        assert_eq!(name(&pool, stale).await.as_deref(), Some("Deleted user"));
+        for id in [verified, recent, author] {
+            assert_eq!(name(&pool, id).await.as_deref(), Some("Someone"));
+        }
+        assert_eq!(
+            repo.anonymize_unverified_before(at(10), at(30))
+                .await
+                .unwrap(),
+            0
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn deleting_spares_verified_recent_and_posting_accounts() {
+    with_test_db(|pool| async move {
+        let stale = account(&pool, false, at(1)).await;
+        let verified = account(&pool, true, at(1)).await;
+        let recent = account(&pool, false, at(20)).await;
+        let author = author(&pool, at(1)).await;
+        let repo = UserRepository::new(pool.clone());
+
+        assert_eq!(repo.delete_unverified_before(at(10)).await.unwrap(), 1);
+
+        assert_eq!(name(&pool, stale).await, None);
+        for id in [verified, recent, author] {
+            assert_eq!(name(&pool, id).await.as_deref(), Some("Someone"));
+        }
+    })
+    .await;
+}
@@ -0,0 +1,91 @@
+use axum_rest::config::{AccessConfig, AppConfig};
+use axum_rest::helpers::usage::{UsageSubject, UsageTracker, daily_quota};
+use axum_rest::model::model::Role;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 1, hour, minute, 0).unwrap()
+}
+
+fn access(daily_quota: u64, admin_daily_quota: u64) -> AccessConfig {
+    let mut access = AppConfig::from_env().access;
+    access.daily_quota = daily_quota;
+    access.admin_daily_quota = admin_daily_quota;
+    access
+}
+
+#[test]
+fn requests_beyond_the_quota_are_refused() {
+    let tracker = UsageTracker::new();
+    let user = UsageSubject::User(Uuid::new_v4());
+
+    assert!(tracker.hit(&user, 2, at(9, 0)).0);
+    let (allowed, usage) = tracker.hit(&user, 2, at(9, 5));
+    assert!(allowed);
+    assert_eq!(usage.remaining, 0);
+
+    let (allowed, usage) = tracker.hit(&user, 2, at(9, 10));
+    assert!(!allowed);
+    assert_eq!(usage.used, 2);
+    assert_eq!(usage.resets_at, at(9, 0) + Duration::hours(24));
+}
+
+#[test]
+fn old_hours_leave_the_window() {
+    let tracker = UsageTracker::new();
+    let key = UsageSubject::ApiKey("key-1".to_string());
+
+    tracker.hit(&key, 10, at(9, 30));
+    tracker.hit(&key, 10, at(9, 45));
+    tracker.hit(&key, 10, at(15, 0));
+
+    let usage = tracker.usage(&key, 10, at(9, 0) + Duration::hours(24));
+    assert_eq!(usage.used, 1);
+    assert_eq!(usage.hours.len(), 1);
+    assert_eq!(usage.hours[0].hour, at(15, 0));
+}
+
+#[test]
+fn usage_is_counted_per_hour() {
+    let tracker = UsageTracker::new();
+    let user = UsageSubject::User(Uuid::new_v4());
+
+    tracker.hit(&user, 10, at(9, 0));
+    tracker.hit(&user, 10, at(9, 59));
+    tracker.hit(&user, 10, at(10, 0));
+
+    let usage = tracker.usage(&user, 10, at(10, 30));
+    let hours: Vec<_> = usage
+        .hours
+        .iter()
+        .map(|hour| (hour.hour, hour.requests))
+        .collect();
+    assert_eq!(hours, vec![(at(9, 0), 2), (at(10, 0), 1)]);
+    assert_eq!(usage.remaining, 7);
+}
+
+#[test]
+fn idle_subjects_are_pruned() {
+    let tracker = UsageTracker::new();
+    tracker.hit(&UsageSubject::User(Uuid::new_v4()), 10, at(9, 0));
+    tracker.hit(&UsageSubject::User(Uuid::new_v4()), 10, at(20, 0));
+
+    tracker.prune(at(9, 0) + Duration::hours(25));
+
+    assert_eq!(tracker.len(), 1);
+}
+
+#[test]
+fn admins_get_the_larger_quota() {
+    let access = access(100, 1_000);
+
+    assert_eq!(daily_quota(&access, Some(Role::USER)), Some(100));
+    assert_eq!(daily_quota(&access, None), Some(100));
+    assert_eq!(daily_quota(&access, Some(Role::ADMIN)), Some(1_000));
+}
+
+#[test]
+fn a_zero_quota_turns_quotas_off() {
+    assert_eq!(daily_quota(&access(0, 1_000), Some(Role::ADMIN)), None);
+}
@@ -0,0 +1,53 @@
+use axum_rest::model::model::{UserStanding, UserStatus};
+use chrono::{Duration, TimeZone, Utc};
+
+fn standing(status: UserStatus, days: Option<i64>) -> UserStanding {
+    let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    UserStanding {
+        user_id: "u_123".to_string(),
+        status,
+        suspended_until: days.map(|days| start + Duration::days(days)),
+        reason: Some("spam".to_string()),
+    }
+}
+
+#[test]
+fn suspensions_lapse_at_their_end() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).unwrap();
+
+    let running = standing(UserStatus::SUSPENDED, Some(7)).as_of(now);
+    assert!(running.is_restricted());
+
+    let lapsed = standing(UserStatus::SUSPENDED, Some(2)).as_of(now);
+    assert_eq!(lapsed.status, UserStatus::ACTIVE);
+    assert_eq!((lapsed.suspended_until, lapsed.reason), (None, None));
+}
+
+#[test]
+fn indefinite_suspensions_and_bans_never_lapse() {
+    let far_future = Utc.with_ymd_and_hms(2100, 1, 1, 0, 0, 0).unwrap();
+
+    assert!(
+        standing(UserStatus::SUSPENDED, None)
+            .as_of(far_future)
+            .is_restricted()
+    );
+    assert!(
+        standing(UserStatus::BANNED, None)
+            .as_of(far_future)
+            .is_restricted()
+    );
+}
+
+#[test]
+fn descriptions_name_the_end_and_reason() {
+    assert_eq!(
+        standing(UserStatus::SUSPENDED, Some(7)).describe(),
+        "This account is suspended until 2025-01-08T00:00:00+00:00: spam"
+    );
+    assert_eq!(
+        standing(UserStatus::BANNED, None).describe(),
+        "This account is banned: spam"
+    );
+    assert_eq!(UserStatus::from("BANNED"), UserStatus::BANNED);
+}
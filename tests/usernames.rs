@@ -0,0 +1,96 @@
+use axum_rest::LoginRequest;
+use axum_rest::config::{DEFAULT_RESERVED_SLUGS, SlugConfig};
+use axum_rest::helpers::slugs::{
+    MAX_USERNAME_LENGTH, suggest_username, username_with_suffix, validate_username,
+};
+
+fn config() -> SlugConfig {
+    SlugConfig {
+        transliterate: true,
+        max_length: 40,
+        stop_words: Vec::new(),
+        reserved: DEFAULT_RESERVED_SLUGS
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    }
+}
+
+#[test]
+fn usernames_are_lowercase_letters_digits_and_underscores() {
+    assert!(validate_username("ada_lovelace", &config()).is_ok());
+    assert!(validate_username("ada1815", &config()).is_ok());
+
+    assert!(validate_username("Ada", &config()).is_err());
+    assert!(validate_username("ada-lovelace", &config()).is_err());
+    assert!(validate_username("ada@example.com", &config()).is_err());
+    assert_eq!(
+        validate_username("1ada", &config()).unwrap_err(),
+        "Username must start with a letter"
+    );
+}
+
+#[test]
+fn usernames_have_a_length_range() {
+    assert!(validate_username("ab", &config()).is_err());
+    assert!(validate_username("abc", &config()).is_ok());
+    assert!(validate_username(&"a".repeat(MAX_USERNAME_LENGTH), &config()).is_ok());
+    assert!(validate_username(&"a".repeat(MAX_USERNAME_LENGTH + 1), &config()).is_err());
+}
+
+#[test]
+fn reserved_names_cannot_be_usernames() {
+    assert_eq!(
+        validate_username("admin", &config()).unwrap_err(),
+        "'admin' is reserved"
+    );
+}
+
+#[test]
+fn suggestions_are_derived_from_names() {
+    assert_eq!(suggest_username("Ada Lovelace"), "ada_lovelace");
+    assert_eq!(suggest_username("Zoë Ångström"), "zoe_angstrom");
+    assert_eq!(suggest_username("42"), "user_42");
+    assert_eq!(suggest_username("Al"), "al_user");
+    assert_eq!(suggest_username(""), "user");
+}
+
+#[test]
+fn suggestions_and_suffixed_usernames_are_valid() {
+    for name in [
+        "Ada Lovelace",
+        "A very long display name indeed, really",
+        "日本語",
+        "Al",
+    ] {
+        let username = suggest_username(name);
+        assert!(
+            validate_username(&username, &config()).is_ok(),
+            "{}",
+            username
+        );
+
+        let suffixed = username_with_suffix(&username, "V1St-GXR8_Z5j");
+        assert!(
+            validate_username(&suffixed, &config()).is_ok(),
+            "{}",
+            suffixed
+        );
+    }
+    assert_eq!(
+        username_with_suffix("ada_lovelace", "V1St-GXR8_Z5j"),
+        "ada_lovelace_v1stgx"
+    );
+}
+
+#[test]
+fn login_accepts_email_or_username_fields() {
+    for body in [
+        r#"{"identifier": "ada", "password": "x"}"#,
+        r#"{"email": "ada", "password": "x"}"#,
+        r#"{"username": "ada", "password": "x"}"#,
+    ] {
+        let request: LoginRequest = serde_json::from_str(body).unwrap();
+        assert_eq!(request.identifier, "ada");
+    }
+}
@@ -0,0 +1,119 @@
+use axum_rest::config::{DEFAULT_RESERVED_SLUGS, SlugConfig};
+use axum_rest::helpers::password_strength::{estimate_password, is_common_password};
+use axum_rest::helpers::slugs::{is_reserved, slugify, validate_slug, with_suffix};
+use axum_rest::helpers::validation::{check_password, strong_password};
+use proptest::prelude::*;
+
+fn slug_config() -> SlugConfig {
+    SlugConfig {
+        transliterate: true,
+        max_length: 40,
+        stop_words: vec!["a".to_string(), "the".to_string(), "of".to_string()],
+        reserved: DEFAULT_RESERVED_SLUGS
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    }
+}
+
+proptest! {
+    #[test]
+    fn short_passwords_are_rejected(password in "\\PC{0,7}") {
+        prop_assert!(!strong_password(&password));
+    }
+
+    #[test]
+    fn multibyte_characters_count_once_towards_length(
+        base in "[a-z][A-Z][0-9]!",
+        padding in "[éß日😀]{0,3}",
+    ) {
+        // Seven characters or fewer, but often eight or more bytes
+        let password = format!("{}{}", base, padding);
+        prop_assert!(!strong_password(&password));
+    }
+
+    #[test]
+    fn long_random_passwords_are_accepted(password in "[a-zA-Z0-9]{20,40}") {
+        prop_assert!(strong_password(&password));
+    }
+
+    #[test]
+    fn repeated_characters_are_rejected(c in "[a-zA-Z0-9!@#]", len in 8usize..40) {
+        prop_assert!(!strong_password(&c.repeat(len)));
+    }
+
+    #[test]
+    fn generated_slugs_pass_validation(title in "\\PC{0,120}") {
+        let config = slug_config();
+        let slug = slugify(&title, &config);
+        prop_assert!(
+            validate_slug(&slug, &config).is_ok() || is_reserved(&slug, &config),
+            "{:?} gave {:?}",
+            title,
+            slug
+        );
+    }
+
+    #[test]
+    fn transliterated_slugs_are_lowercase_ascii(title in "\\PC{0,120}") {
+        let slug = slugify(&title, &slug_config());
+        prop_assert!(slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'));
+    }
+
+    #[test]
+    fn slugifying_a_slug_changes_nothing(title in "\\PC{0,120}") {
+        let config = slug_config();
+        let slug = slugify(&title, &config);
+        prop_assert_eq!(slugify(&slug, &config), slug);
+    }
+
+    #[test]
+    fn slugs_and_suffixes_fit_the_limit(
+        title in "\\PC{0,200}",
+        random_id in "[A-Za-z0-9_-]{0,12}",
+        max_length in 8usize..80,
+    ) {
+        let config = SlugConfig { max_length, ..slug_config() };
+        let slug = slugify(&title, &config);
+        prop_assert!(slug.chars().count() <= max_length);
+        prop_assert!(with_suffix(&slug, &random_id, &config).chars().count() <= max_length);
+    }
+}
+
+#[test]
+fn character_classes_alone_do_not_make_a_password_strong() {
+    for password in ["Password1!", "P@ssw0rd", "Qwerty123!", "Summer2024!"] {
+        let strength = estimate_password(password, &[]);
+        assert!(!strength.acceptable, "{} was accepted", password);
+        assert!(strength.warning.is_some() || !strength.suggestions.is_empty());
+    }
+}
+
+#[test]
+fn passphrases_are_strong_without_symbols() {
+    assert!(strong_password("violet kettle marathon tundra"));
+    assert!(strong_password("seed-lantern-orbit-tundra"));
+}
+
+#[test]
+fn common_passwords_are_refused_whatever_their_case() {
+    assert!(is_common_password("password123"));
+    assert!(is_common_password("P@SSW0RD"));
+    assert!(!is_common_password("violet kettle marathon tundra"));
+
+    let strength = estimate_password("Password123", &[]);
+    assert_eq!(strength.score, 0);
+    assert!(!strength.acceptable);
+}
+
+#[test]
+fn account_details_weaken_passwords_built_from_them() {
+    let password = "adalovelace1815";
+    let alone = estimate_password(password, &[]);
+    let with_name = estimate_password(password, &["Ada Lovelace", "adalovelace@example.com"]);
+    assert!(with_name.score <= alone.score);
+
+    assert!(check_password(password, &["adalovelace", "adalovelace1815@example.com"]).is_err());
+}
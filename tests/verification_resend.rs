@@ -0,0 +1,80 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum_rest::config::AppConfig;
+use axum_rest::helpers::clock::MockClock;
+use axum_rest::helpers::email_templates::EmailTemplates;
+use axum_rest::helpers::feature_flags::FeatureFlags;
+use axum_rest::helpers::link_tokens::MemoryLinkTokens;
+use axum_rest::helpers::mailer::MemoryMailer;
+use axum_rest::helpers::verification::VerificationMailer;
+use axum_rest::jobs::jobs::spawn_verification_resend;
+use axum_rest::model::model::{FeatureFlagSet, PendingVerification, Role, User};
+use chrono::Utc;
+use uuid::Uuid;
+
+fn sender(mailer: Arc<MemoryMailer>, email_sending: bool) -> VerificationMailer {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates/email");
+    VerificationMailer {
+        flags: Arc::new(FeatureFlags::new(FeatureFlagSet {
+            email_sending,
+            ..FeatureFlagSet::default()
+        })),
+        mailer,
+        emails: Arc::new(EmailTemplates::load(&dir, "en").unwrap()),
+        config: Arc::new(AppConfig::from_env()),
+        clock: Arc::new(MockClock::default()),
+        tokens: Arc::new(MemoryLinkTokens::new()),
+    }
+}
+
+fn pending(name: &str) -> PendingVerification {
+    PendingVerification {
+        user: User {
+            id: Uuid::new_v4(),
+            public_id: name.to_string(),
+            name: name.to_string(),
+            username: None,
+            email: format!("{}@example.com", name),
+            password: String::new(),
+            role: Role::USER,
+            email_verified: false,
+            avatar_url: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        },
+        locale: None,
+        eligible: true,
+    }
+}
+
+#[tokio::test]
+async fn every_pending_account_gets_an_email_across_batches() {
+    let mailer = Arc::new(MemoryMailer::new());
+    let accounts = vec![pending("ada"), pending("bob"), pending("eve")];
+
+    spawn_verification_resend(sender(mailer.clone(), true), accounts, 2, Duration::ZERO)
+        .await
+        .unwrap();
+
+    assert_eq!(mailer.sent().len(), 3);
+    let email = mailer.last_to("eve@example.com").unwrap();
+    assert!(email.text.contains("verify-email?token="), "{}", email.text);
+}
+
+#[tokio::test]
+async fn nothing_is_sent_while_email_sending_is_off() {
+    let mailer = Arc::new(MemoryMailer::new());
+
+    spawn_verification_resend(
+        sender(mailer.clone(), false),
+        vec![pending("ada")],
+        10,
+        Duration::ZERO,
+    )
+    .await
+    .unwrap();
+
+    assert!(mailer.sent().is_empty());
+}
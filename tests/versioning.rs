@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, header},
+    middleware,
+    routing::get,
+};
+use axum_rest::config::{ApiConfig, AppConfig};
+use axum_rest::docs::ApiDoc;
+use axum_rest::helpers::versioning::{
+    API_VERSION_HEADER, ApiVersion, unversioned_middleware, versioned_middleware,
+};
+use axum_rest::model::model::Limits;
+use tower::ServiceExt;
+
+fn router() -> Router {
+    let config = Arc::new(AppConfig::from_env());
+    let routes = || {
+        Router::new().route(
+            "/posts",
+            get(|version: ApiVersion| async move { version.number().to_string() }),
+        )
+    };
+
+    Router::new()
+        .merge(routes().layer(middleware::from_fn_with_state(
+            config,
+            unversioned_middleware,
+        )))
+        .nest(
+            "/api/v1",
+            routes().layer(middleware::from_fn_with_state(
+                ApiVersion::V1,
+                versioned_middleware,
+            )),
+        )
+}
+
+async fn get_path(path: &str) -> (axum::http::HeaderMap, String) {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let response = router().oneshot(request).await.unwrap();
+    let headers = response.headers().clone();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (headers, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn versioned_paths_carry_their_version() {
+    let (headers, body) = get_path("/api/v1/posts").await;
+
+    assert_eq!(body, "1");
+    assert_eq!(headers[&API_VERSION_HEADER], "1");
+    assert!(headers.get("deprecation").is_none());
+}
+
+#[tokio::test]
+async fn unversioned_paths_point_at_their_successor() {
+    let (headers, body) = get_path("/posts").await;
+
+    assert_eq!(body, "1");
+    assert_eq!(headers["deprecation"], "true");
+    assert_eq!(
+        headers[header::LINK],
+        "</api/v1/posts>; rel=\"successor-version\""
+    );
+}
+
+#[test]
+fn api_roots_list_every_version() {
+    let api = ApiConfig {
+        prefix: "/api".to_string(),
+        unversioned_routes: true,
+    };
+    assert_eq!(api.roots(), vec!["", "/api/v1"]);
+
+    let api = ApiConfig {
+        unversioned_routes: false,
+        ..api
+    };
+    assert_eq!(api.roots(), vec!["/api/v1"]);
+}
+
+#[test]
+fn the_spec_is_served_from_the_version_prefix() {
+    let spec = serde_json::to_value(ApiDoc::for_version(
+        "/blog",
+        "/api",
+        ApiVersion::V1,
+        &Limits::default(),
+    ))
+    .unwrap();
+
+    assert_eq!(spec["servers"][0]["url"], "/blog/api/v1");
+    // Site routes stay at the base path
+    assert_eq!(spec["paths"]["/health/live"]["servers"][0]["url"], "/blog");
+    assert!(spec["paths"]["/posts"].get("servers").is_none());
+}
@@ -0,0 +1,33 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use axum_rest::helpers::views::{MAX_TRENDING_LIMIT, trending_limit, viewer_key};
+use uuid::Uuid;
+
+const IP: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+
+#[test]
+fn signed_in_viewers_are_keyed_by_user() {
+    let user_id = Uuid::new_v4();
+
+    assert_eq!(
+        viewer_key(Some(user_id), Some(IP)),
+        Some(format!("user:{}", user_id))
+    );
+}
+
+#[test]
+fn anonymous_viewers_are_keyed_by_hashed_ip() {
+    let key = viewer_key(None, Some(IP)).expect("ip key");
+
+    assert!(key.starts_with("ip:"));
+    assert!(!key.contains("203.0.113.7"));
+    assert_eq!(viewer_key(None, Some(IP)), Some(key));
+    assert_eq!(viewer_key(None, None), None);
+}
+
+#[test]
+fn trending_limit_is_clamped() {
+    assert_eq!(trending_limit(None), 10);
+    assert_eq!(trending_limit(Some(0)), 1);
+    assert_eq!(trending_limit(Some(1000)), MAX_TRENDING_LIMIT);
+}
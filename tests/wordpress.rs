@@ -0,0 +1,110 @@
+use axum_rest::helpers::wordpress::parse_wxr;
+use chrono::{TimeZone, Utc};
+
+const EXPORT: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<rss version="2.0"
+    xmlns:content="http://purl.org/rss/1.0/modules/content/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:wp="http://wordpress.org/export/1.2/">
+<channel>
+    <title>Example Blog</title>
+    <wp:author>
+        <wp:author_id>1</wp:author_id>
+        <wp:author_login><![CDATA[alice]]></wp:author_login>
+        <wp:author_email><![CDATA[alice@example.com]]></wp:author_email>
+        <wp:author_display_name><![CDATA[Alice Example]]></wp:author_display_name>
+    </wp:author>
+    <item>
+        <title>Hello &amp; welcome</title>
+        <dc:creator><![CDATA[alice]]></dc:creator>
+        <content:encoded><![CDATA[<p>First <strong>post</strong></p>]]></content:encoded>
+        <wp:post_date_gmt><![CDATA[2021-03-04 05:06:07]]></wp:post_date_gmt>
+        <wp:post_name><![CDATA[hello-welcome]]></wp:post_name>
+        <wp:status><![CDATA[publish]]></wp:status>
+        <wp:post_password><![CDATA[]]></wp:post_password>
+        <wp:post_type><![CDATA[post]]></wp:post_type>
+        <wp:comment>
+            <wp:comment_author><![CDATA[Bob]]></wp:comment_author>
+            <wp:comment_content><![CDATA[Nice]]></wp:comment_content>
+        </wp:comment>
+        <wp:comment>
+            <wp:comment_content><![CDATA[Agreed]]></wp:comment_content>
+        </wp:comment>
+    </item>
+    <item>
+        <title>Members only</title>
+        <dc:creator><![CDATA[alice]]></dc:creator>
+        <wp:post_date_gmt><![CDATA[2021-05-06 07:08:09]]></wp:post_date_gmt>
+        <wp:status><![CDATA[publish]]></wp:status>
+        <wp:post_password><![CDATA[hunter2]]></wp:post_password>
+        <wp:post_type><![CDATA[post]]></wp:post_type>
+    </item>
+    <item>
+        <title>Draft</title>
+        <dc:creator><![CDATA[alice]]></dc:creator>
+        <wp:post_date_gmt><![CDATA[0000-00-00 00:00:00]]></wp:post_date_gmt>
+        <wp:status><![CDATA[draft]]></wp:status>
+        <wp:post_type><![CDATA[post]]></wp:post_type>
+    </item>
+    <item>
+        <title>photo</title>
+        <wp:post_type><![CDATA[attachment]]></wp:post_type>
+        <wp:attachment_url><![CDATA[https://example.com/wp-content/uploads/photo.jpg]]></wp:attachment_url>
+    </item>
+</channel>
+</rss>"#;
+
+#[test]
+fn authors_are_parsed() {
+    let export = parse_wxr(EXPORT).expect("valid export");
+
+    assert_eq!(export.authors.len(), 1);
+    assert_eq!(export.authors[0].login, "alice");
+    assert_eq!(export.authors[0].email, "alice@example.com");
+    assert_eq!(export.authors[0].display_name, "Alice Example");
+}
+
+#[test]
+fn posts_keep_content_date_and_comment_count() {
+    let export = parse_wxr(EXPORT).expect("valid export");
+    let post = &export.items[0];
+
+    assert_eq!(post.title, "Hello & welcome");
+    assert_eq!(post.creator, "alice");
+    assert_eq!(post.content, "<p>First <strong>post</strong></p>");
+    assert_eq!(post.status, "publish");
+    assert_eq!(post.post_name, "hello-welcome");
+    assert_eq!(
+        post.post_date_gmt,
+        Some(Utc.with_ymd_and_hms(2021, 3, 4, 5, 6, 7).unwrap())
+    );
+    assert_eq!(post.comments, 2);
+}
+
+#[test]
+fn password_protected_posts_keep_their_password() {
+    let export = parse_wxr(EXPORT).expect("valid export");
+
+    assert_eq!(export.items[0].post_password, "");
+    assert_eq!(export.items[1].status, "publish");
+    assert_eq!(export.items[1].post_password, "hunter2");
+}
+
+#[test]
+fn drafts_have_no_date_and_attachments_keep_their_url() {
+    let export = parse_wxr(EXPORT).expect("valid export");
+
+    assert_eq!(export.items.len(), 4);
+    assert_eq!(export.items[2].post_date_gmt, None);
+    assert_eq!(export.items[3].post_type, "attachment");
+    assert_eq!(
+        export.items[3].attachment_url.as_deref(),
+        Some("https://example.com/wp-content/uploads/photo.jpg")
+    );
+}
+
+#[test]
+fn non_wxr_documents_are_rejected() {
+    assert!(parse_wxr("<html><body>nope</body></html>").is_err());
+    assert!(parse_wxr("<rss><channel><item></channel></rss>").is_err());
+}